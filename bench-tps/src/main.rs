@@ -162,6 +162,18 @@ fn create_client(
                         exit(1);
                     }),
                 ),
+                ConnectionCache::InMemory(cache) => Arc::new(
+                    TpuClient::new_with_connection_cache(
+                        rpc_client,
+                        websocket_url,
+                        TpuClientConfig::default(),
+                        cache,
+                    )
+                    .unwrap_or_else(|err| {
+                        eprintln!("Could not create TpuClient {err:?}");
+                        exit(1);
+                    }),
+                ),
             }
         }
     }
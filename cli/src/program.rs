@@ -2651,6 +2651,16 @@ fn send_deploy_messages(
                     write_messages,
                     &[fee_payer_signer, write_signer],
                 ),
+                ConnectionCache::InMemory(cache) => TpuClient::new_with_connection_cache(
+                    rpc_client.clone(),
+                    &config.websocket_url,
+                    TpuClientConfig::default(),
+                    cache,
+                )?
+                .send_and_confirm_messages_with_spinner(
+                    write_messages,
+                    &[fee_payer_signer, write_signer],
+                ),
                 ConnectionCache::Quic(cache) => {
                     let tpu_client_fut = solana_client::nonblocking::tpu_client::TpuClient::new_with_connection_cache(
                         rpc_client.get_inner_client().clone(),
@@ -891,6 +891,16 @@ fn send_messages(
                 write_messages,
                 &[config.payer, config.authority],
             ),
+            ConnectionCache::InMemory(cache) => TpuClient::new_with_connection_cache(
+                rpc_client.clone(),
+                config.websocket_url,
+                TpuClientConfig::default(),
+                cache,
+            )?
+            .send_and_confirm_messages_with_spinner(
+                write_messages,
+                &[config.payer, config.authority],
+            ),
             ConnectionCache::Quic(cache) => {
                 let tpu_client_fut =
                     solana_client::nonblocking::tpu_client::TpuClient::new_with_connection_cache(
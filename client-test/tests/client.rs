@@ -3,11 +3,15 @@ use {
     rand::Rng,
     serde_json::{json, Value},
     solana_ledger::{blockstore::Blockstore, get_tmp_ledger_path_auto_delete},
-    solana_pubsub_client::{nonblocking, pubsub_client::PubsubClient},
+    solana_pubsub_client::{
+        nonblocking::{self, pubsub_client::PubsubEvent},
+        pubsub_client::PubsubClient,
+    },
     solana_rpc::{
         optimistically_confirmed_bank_tracker::OptimisticallyConfirmedBank,
         rpc::{create_test_transaction_entries, populate_blockstore_for_tests},
         rpc_pubsub_service::{PubSubConfig, PubSubService},
+        rpc_subscription_tracker::SubscriptionParams,
         rpc_subscriptions::RpcSubscriptions,
     },
     solana_rpc_client::rpc_client::RpcClient,
@@ -620,6 +624,259 @@ async fn test_slot_subscription_async() {
     unsubscribe().await;
 }
 
+#[tokio::test]
+async fn test_slot_subscription_reconnect_async() {
+    let sync_service = Arc::new(AtomicU64::new(0));
+    let sync_client = Arc::clone(&sync_service);
+
+    fn wait_until(atomic: &Arc<AtomicU64>, value: u64) {
+        let now = Instant::now();
+        while atomic.load(Ordering::Relaxed) != value {
+            if now.elapsed() > Duration::from_secs(5) {
+                panic!("wait for too long")
+            }
+            sleep(Duration::from_millis(1))
+        }
+    }
+
+    let pubsub_addr = pubsub_addr();
+
+    tokio::task::spawn_blocking(move || {
+        let exit = Arc::new(AtomicBool::new(false));
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(10_000);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let bank_forks = BankForks::new_rw_arc(bank);
+        let optimistically_confirmed_bank =
+            OptimisticallyConfirmedBank::locked_from_bank_forks_root(&bank_forks);
+        let max_complete_transaction_status_slot = Arc::new(AtomicU64::default());
+        let max_complete_rewards_slot = Arc::new(AtomicU64::default());
+        let subscriptions = Arc::new(RpcSubscriptions::new_for_tests(
+            exit.clone(),
+            max_complete_transaction_status_slot,
+            max_complete_rewards_slot,
+            bank_forks,
+            Arc::new(RwLock::new(BlockCommitmentCache::default())),
+            optimistically_confirmed_bank,
+        ));
+        let (trigger, pubsub_service) =
+            PubSubService::new(PubSubConfig::default(), &subscriptions, pubsub_addr);
+
+        check_server_is_ready_or_panic(&pubsub_addr, 10, Duration::from_millis(100));
+
+        sync_service.store(1, Ordering::Relaxed);
+
+        wait_until(&sync_service, 2);
+        subscriptions.notify_slot(1, 0, 0);
+        sync_service.store(3, Ordering::Relaxed);
+
+        // Restart the service, dropping the client connection
+        wait_until(&sync_service, 4);
+        trigger.cancel();
+        pubsub_service.close().unwrap();
+        let (trigger, pubsub_service) =
+            PubSubService::new(PubSubConfig::default(), &subscriptions, pubsub_addr);
+
+        // The client restores its subscription once reconnected
+        wait_until(&sync_service, 5);
+        subscriptions.notify_slot(2, 1, 1);
+        sync_service.store(6, Ordering::Relaxed);
+
+        wait_until(&sync_service, 7);
+        exit.store(true, Ordering::Relaxed);
+        trigger.cancel();
+        pubsub_service.close().unwrap();
+    });
+
+    wait_until(&sync_client, 1);
+    let url = format!("ws://0.0.0.0:{}/", pubsub_addr.port());
+    let reconnect_config = nonblocking::pubsub_client::PubsubReconnectConfig {
+        replay_from_slot: false,
+        ..nonblocking::pubsub_client::PubsubReconnectConfig::default()
+    };
+    let pubsub_client =
+        nonblocking::pubsub_client::PubsubClient::new_with_reconnect(&url, reconnect_config)
+            .await
+            .unwrap();
+    let (mut events, unsubscribe) = pubsub_client
+        .subscribe_events::<SlotInfo>("slot", json!([]))
+        .await
+        .unwrap();
+    sync_client.store(2, Ordering::Relaxed);
+
+    wait_until(&sync_client, 3);
+    assert_eq!(
+        tokio::time::timeout(Duration::from_millis(25), events.next()).await,
+        Ok(Some(PubsubEvent::Notification(SlotInfo {
+            slot: 1,
+            parent: 0,
+            root: 0,
+        })))
+    );
+    sync_client.store(4, Ordering::Relaxed);
+
+    assert_eq!(
+        tokio::time::timeout(Duration::from_secs(5), events.next()).await,
+        Ok(Some(PubsubEvent::Gap {
+            last_seen_slot: Some(1)
+        }))
+    );
+    sync_client.store(5, Ordering::Relaxed);
+
+    wait_until(&sync_client, 6);
+    assert_eq!(
+        tokio::time::timeout(Duration::from_millis(25), events.next()).await,
+        Ok(Some(PubsubEvent::Notification(SlotInfo {
+            slot: 2,
+            parent: 1,
+            root: 1,
+        })))
+    );
+    sync_client.store(7, Ordering::Relaxed);
+
+    unsubscribe().await;
+}
+
+#[tokio::test]
+async fn test_slot_subscription_replay_async() {
+    let sync_service = Arc::new(AtomicU64::new(0));
+    let sync_client = Arc::clone(&sync_service);
+
+    fn wait_until(atomic: &Arc<AtomicU64>, value: u64) {
+        let now = Instant::now();
+        while atomic.load(Ordering::Relaxed) != value {
+            if now.elapsed() > Duration::from_secs(5) {
+                panic!("wait for too long")
+            }
+            sleep(Duration::from_millis(1))
+        }
+    }
+
+    let pubsub_addr = pubsub_addr();
+
+    tokio::task::spawn_blocking(move || {
+        let exit = Arc::new(AtomicBool::new(false));
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(10_000);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let bank_forks = BankForks::new_rw_arc(bank);
+        let optimistically_confirmed_bank =
+            OptimisticallyConfirmedBank::locked_from_bank_forks_root(&bank_forks);
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Arc::new(Blockstore::open(ledger_path.path()).unwrap());
+        let subscriptions = Arc::new(RpcSubscriptions::new_with_config(
+            exit.clone(),
+            Arc::new(AtomicU64::default()),
+            Arc::new(AtomicU64::default()),
+            blockstore,
+            bank_forks,
+            Arc::new(RwLock::new(BlockCommitmentCache::default())),
+            optimistically_confirmed_bank,
+            &PubSubConfig {
+                replay_capacity_items: 100,
+                ..PubSubConfig::default()
+            },
+            None,
+        ));
+        // Keeps the subscription, and its history, alive while the client is disconnected
+        let token = subscriptions
+            .control()
+            .subscribe(SubscriptionParams::Slot)
+            .unwrap();
+        let history = subscriptions.control().history().unwrap();
+        let wait_for_history = |from_slot, len| {
+            let now = Instant::now();
+            while history
+                .replay(token.id(), from_slot)
+                .map_or(true, |replayed| replayed.len() != len)
+            {
+                if now.elapsed() > Duration::from_secs(5) {
+                    panic!("wait for too long")
+                }
+                sleep(Duration::from_millis(1))
+            }
+        };
+        wait_for_history(1, 0);
+        let (trigger, pubsub_service) =
+            PubSubService::new(PubSubConfig::default(), &subscriptions, pubsub_addr);
+
+        check_server_is_ready_or_panic(&pubsub_addr, 10, Duration::from_millis(100));
+
+        sync_service.store(1, Ordering::Relaxed);
+
+        wait_until(&sync_service, 2);
+        subscriptions.notify_slot(1, 0, 0);
+        sync_service.store(3, Ordering::Relaxed);
+
+        // Notify slot 2 while the client is disconnected
+        wait_until(&sync_service, 4);
+        trigger.cancel();
+        pubsub_service.close().unwrap();
+        subscriptions.notify_slot(2, 1, 1);
+        wait_for_history(2, 1);
+        let (trigger, pubsub_service) =
+            PubSubService::new(PubSubConfig::default(), &subscriptions, pubsub_addr);
+
+        wait_until(&sync_service, 5);
+        subscriptions.notify_slot(3, 2, 2);
+        sync_service.store(6, Ordering::Relaxed);
+
+        wait_until(&sync_service, 7);
+        exit.store(true, Ordering::Relaxed);
+        trigger.cancel();
+        pubsub_service.close().unwrap();
+    });
+
+    wait_until(&sync_client, 1);
+    let url = format!("ws://0.0.0.0:{}/", pubsub_addr.port());
+    let reconnect_config = nonblocking::pubsub_client::PubsubReconnectConfig {
+        replay_from_slot: true,
+        ..nonblocking::pubsub_client::PubsubReconnectConfig::default()
+    };
+    let pubsub_client =
+        nonblocking::pubsub_client::PubsubClient::new_with_reconnect(&url, reconnect_config)
+            .await
+            .unwrap();
+    let (mut events, unsubscribe) = pubsub_client
+        .subscribe_events::<SlotInfo>("slot", json!([]))
+        .await
+        .unwrap();
+    sync_client.store(2, Ordering::Relaxed);
+
+    wait_until(&sync_client, 3);
+    assert_eq!(
+        tokio::time::timeout(Duration::from_millis(25), events.next()).await,
+        Ok(Some(PubsubEvent::Notification(SlotInfo {
+            slot: 1,
+            parent: 0,
+            root: 0,
+        })))
+    );
+    sync_client.store(4, Ordering::Relaxed);
+
+    // The missed notification is replayed once, without a gap and without repeating slot 1
+    assert_eq!(
+        tokio::time::timeout(Duration::from_secs(5), events.next()).await,
+        Ok(Some(PubsubEvent::Notification(SlotInfo {
+            slot: 2,
+            parent: 1,
+            root: 1,
+        })))
+    );
+    sync_client.store(5, Ordering::Relaxed);
+
+    wait_until(&sync_client, 6);
+    assert_eq!(
+        tokio::time::timeout(Duration::from_millis(25), events.next()).await,
+        Ok(Some(PubsubEvent::Notification(SlotInfo {
+            slot: 3,
+            parent: 2,
+            root: 2,
+        })))
+    );
+    sync_client.store(7, Ordering::Relaxed);
+
+    unsubscribe().await;
+}
+
 fn check_server_is_ready_or_panic(
     socket_addr: &SocketAddr,
     mut retry: u8,
@@ -1,4 +1,7 @@
-pub use solana_connection_cache::connection_cache::Protocol;
+pub use solana_connection_cache::{
+    connection_cache::Protocol,
+    in_memory::{InMemoryFaults, InMemoryNetwork, InMemoryNetworkStats},
+};
 use {
     quinn::Endpoint,
     solana_connection_cache::{
@@ -7,6 +10,7 @@ use {
             BaseClientConnection, ConnectionCache as BackendConnectionCache, ConnectionPool,
             NewConnectionConfig,
         },
+        in_memory::{InMemoryConfig, InMemoryConnectionManager, InMemoryPool},
     },
     solana_quic_client::{QuicConfig, QuicConnectionManager, QuicPool},
     solana_sdk::{
@@ -28,28 +32,35 @@ const DEFAULT_CONNECTION_CACHE_USE_QUIC: bool = true;
 /// A thin wrapper over connection-cache/ConnectionCache to ease
 /// construction of the ConnectionCache for code dealing both with udp and quic.
 /// For the scenario only using udp or quic, use connection-cache/ConnectionCache directly.
+/// The in-memory variant sends over an `InMemoryNetwork` instead of sockets, for tests.
 pub enum ConnectionCache {
     Quic(Arc<BackendConnectionCache<QuicPool, QuicConnectionManager, QuicConfig>>),
     Udp(Arc<BackendConnectionCache<UdpPool, UdpConnectionManager, UdpConfig>>),
+    InMemory(Arc<BackendConnectionCache<InMemoryPool, InMemoryConnectionManager, InMemoryConfig>>),
 }
 
 type QuicBaseClientConnection = <QuicPool as ConnectionPool>::BaseClientConnection;
 type UdpBaseClientConnection = <UdpPool as ConnectionPool>::BaseClientConnection;
+type InMemoryBaseClientConnection = <InMemoryPool as ConnectionPool>::BaseClientConnection;
 
 pub enum BlockingClientConnection {
     Quic(Arc<<QuicBaseClientConnection as BaseClientConnection>::BlockingClientConnection>),
     Udp(Arc<<UdpBaseClientConnection as BaseClientConnection>::BlockingClientConnection>),
+    InMemory(Arc<<InMemoryBaseClientConnection as BaseClientConnection>::BlockingClientConnection>),
 }
 
 pub enum NonblockingClientConnection {
     Quic(Arc<<QuicBaseClientConnection as BaseClientConnection>::NonblockingClientConnection>),
     Udp(Arc<<UdpBaseClientConnection as BaseClientConnection>::NonblockingClientConnection>),
+    InMemory(
+        Arc<<InMemoryBaseClientConnection as BaseClientConnection>::NonblockingClientConnection>,
+    ),
 }
 
 impl NotifyKeyUpdate for ConnectionCache {
     fn update_key(&self, key: &Keypair) -> Result<(), Box<dyn std::error::Error>> {
         match self {
-            Self::Udp(_) => Ok(()),
+            Self::Udp(_) | Self::InMemory(_) => Ok(()),
             Self::Quic(backend) => backend.update_key(key),
         }
     }
@@ -106,7 +117,7 @@ impl ConnectionCache {
     pub fn protocol(&self) -> Protocol {
         match self {
             Self::Quic(_) => Protocol::QUIC,
-            Self::Udp(_) => Protocol::UDP,
+            Self::Udp(_) | Self::InMemory(_) => Protocol::UDP,
         }
     }
 
@@ -142,6 +153,20 @@ impl ConnectionCache {
         Self::Udp(Arc::new(cache))
     }
 
+    /// Create a connection_cache sending over `network` instead of sockets.
+    pub fn with_in_memory(
+        name: &'static str,
+        connection_pool_size: usize,
+        network: InMemoryNetwork,
+    ) -> Self {
+        // The minimum pool size is 1.
+        let connection_pool_size = 1.max(connection_pool_size);
+        let connection_manager = InMemoryConnectionManager::new(network);
+        let cache =
+            BackendConnectionCache::new(name, connection_manager, connection_pool_size).unwrap();
+        Self::InMemory(Arc::new(cache))
+    }
+
     pub fn use_quic(&self) -> bool {
         matches!(self, Self::Quic(_))
     }
@@ -150,6 +175,7 @@ impl ConnectionCache {
         match self {
             Self::Quic(cache) => BlockingClientConnection::Quic(cache.get_connection(addr)),
             Self::Udp(cache) => BlockingClientConnection::Udp(cache.get_connection(addr)),
+            Self::InMemory(cache) => BlockingClientConnection::InMemory(cache.get_connection(addr)),
         }
     }
 
@@ -161,6 +187,9 @@ impl ConnectionCache {
             Self::Udp(cache) => {
                 NonblockingClientConnection::Udp(cache.get_nonblocking_connection(addr))
             }
+            Self::InMemory(cache) => {
+                NonblockingClientConnection::InMemory(cache.get_nonblocking_connection(addr))
+            }
         }
     }
 }
@@ -173,6 +202,7 @@ macro_rules! dispatch {
             match self {
                 Self::Quic(this) => this.$name($($arg, )*),
                 Self::Udp(this) => this.$name($($arg, )*),
+                Self::InMemory(this) => this.$name($($arg, )*),
             }
         }
     };
@@ -183,6 +213,7 @@ macro_rules! dispatch {
             match self {
                 Self::Quic(this) => this.$name($($arg, )*),
                 Self::Udp(this) => this.$name($($arg, )*),
+                Self::InMemory(this) => this.$name($($arg, )*),
             }
         }
     };
@@ -208,6 +239,7 @@ impl solana_connection_cache::nonblocking::client_connection::ClientConnection
         match self {
             Self::Quic(cache) => Ok(cache.send_data(buffer).await?),
             Self::Udp(cache) => Ok(cache.send_data(buffer).await?),
+            Self::InMemory(cache) => Ok(cache.send_data(buffer).await?),
         }
     }
 
@@ -215,6 +247,7 @@ impl solana_connection_cache::nonblocking::client_connection::ClientConnection
         match self {
             Self::Quic(cache) => Ok(cache.send_data_batch(buffers).await?),
             Self::Udp(cache) => Ok(cache.send_data_batch(buffers).await?),
+            Self::InMemory(cache) => Ok(cache.send_data_batch(buffers).await?),
         }
     }
 }
@@ -227,7 +260,8 @@ mod tests {
         crossbeam_channel::unbounded,
         solana_sdk::{net::DEFAULT_TPU_COALESCE, signature::Keypair},
         solana_streamer::{
-            nonblocking::quic::DEFAULT_WAIT_FOR_CHUNK_TIMEOUT, quic::SpawnServerResult,
+            nonblocking::quic::{DEFAULT_MAX_STREAMS_PER_MS, DEFAULT_WAIT_FOR_CHUNK_TIMEOUT},
+            quic::SpawnServerResult,
             streamer::StakedNodes,
         },
         std::{
@@ -259,6 +293,7 @@ mod tests {
             endpoint: response_recv_endpoint,
             thread: response_recv_thread,
             key_updater: _,
+            stats: _,
         } = solana_streamer::quic::spawn_server(
             "solQuicTest",
             "quic_streamer_test",
@@ -270,6 +305,7 @@ mod tests {
             staked_nodes,
             10,
             10,
+            DEFAULT_MAX_STREAMS_PER_MS,
             DEFAULT_WAIT_FOR_CHUNK_TIMEOUT,
             DEFAULT_TPU_COALESCE,
         )
@@ -87,7 +87,7 @@ impl TpuClient<QuicPool, QuicConnectionManager, QuicConfig> {
     ) -> Result<Self> {
         let connection_cache = match ConnectionCache::new(name) {
             ConnectionCache::Quic(cache) => cache,
-            ConnectionCache::Udp(_) => {
+            ConnectionCache::Udp(_) | ConnectionCache::InMemory(_) => {
                 return Err(TpuSenderError::Custom(String::from(
                     "Invalid default connection cache",
                 )))
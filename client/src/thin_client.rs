@@ -5,6 +5,7 @@
 #[allow(deprecated)]
 use {
     crate::connection_cache::{dispatch, ConnectionCache},
+    solana_connection_cache::in_memory::{InMemoryConfig, InMemoryConnectionManager, InMemoryPool},
     solana_quic_client::{QuicConfig, QuicConnectionManager, QuicPool},
     solana_rpc_client::rpc_client::RpcClient,
     solana_rpc_client_api::config::RpcProgramAccountsConfig,
@@ -36,6 +37,7 @@ use {
 pub enum ThinClient {
     Quic(BackendThinClient<QuicPool, QuicConnectionManager, QuicConfig>),
     Udp(BackendThinClient<UdpPool, UdpConnectionManager, UdpConfig>),
+    InMemory(BackendThinClient<InMemoryPool, InMemoryConnectionManager, InMemoryConfig>),
 }
 
 #[allow(deprecated)]
@@ -59,6 +61,11 @@ impl ThinClient {
                     BackendThinClient::new(rpc_addr, tpu_addr, connection_cache.clone());
                 ThinClient::Udp(thin_client)
             }
+            ConnectionCache::InMemory(connection_cache) => {
+                let thin_client =
+                    BackendThinClient::new(rpc_addr, tpu_addr, connection_cache.clone());
+                ThinClient::InMemory(thin_client)
+            }
         }
     }
 
@@ -87,6 +94,15 @@ impl ThinClient {
                 );
                 ThinClient::Udp(thin_client)
             }
+            ConnectionCache::InMemory(connection_cache) => {
+                let thin_client = BackendThinClient::new_socket_with_timeout(
+                    rpc_addr,
+                    tpu_addr,
+                    timeout,
+                    connection_cache.clone(),
+                );
+                ThinClient::InMemory(thin_client)
+            }
         }
     }
 
@@ -112,6 +128,14 @@ impl ThinClient {
                 );
                 ThinClient::Udp(thin_client)
             }
+            ConnectionCache::InMemory(connection_cache) => {
+                let thin_client = BackendThinClient::new_from_addrs(
+                    rpc_addrs,
+                    tpu_addrs,
+                    connection_cache.clone(),
+                );
+                ThinClient::InMemory(thin_client)
+            }
         }
     }
 
@@ -1,8 +1,11 @@
 use {
     crate::connection_cache::ConnectionCache,
-    solana_connection_cache::connection_cache::{
-        ConnectionCache as BackendConnectionCache, ConnectionManager, ConnectionPool,
-        NewConnectionConfig,
+    solana_connection_cache::{
+        connection_cache::{
+            ConnectionCache as BackendConnectionCache, ConnectionManager, ConnectionPool,
+            NewConnectionConfig,
+        },
+        in_memory::{InMemoryConfig, InMemoryConnectionManager, InMemoryPool},
     },
     solana_quic_client::{QuicConfig, QuicConnectionManager, QuicPool},
     solana_rpc_client::rpc_client::RpcClient,
@@ -26,6 +29,7 @@ pub type QuicTpuClient = TpuClient<QuicPool, QuicConnectionManager, QuicConfig>;
 pub enum TpuClientWrapper {
     Quic(TpuClient<QuicPool, QuicConnectionManager, QuicConfig>),
     Udp(TpuClient<UdpPool, UdpConnectionManager, UdpConfig>),
+    InMemory(TpuClient<InMemoryPool, InMemoryConnectionManager, InMemoryConfig>),
 }
 
 /// Client which sends transactions directly to the current leader's TPU port over UDP.
@@ -86,7 +90,7 @@ impl TpuClient<QuicPool, QuicConnectionManager, QuicConfig> {
     ) -> Result<Self> {
         let connection_cache = match ConnectionCache::new("connection_cache_tpu_client") {
             ConnectionCache::Quic(cache) => cache,
-            ConnectionCache::Udp(_) => {
+            ConnectionCache::Udp(_) | ConnectionCache::InMemory(_) => {
                 return Err(TpuSenderError::Custom(String::from(
                     "Invalid default connection cache",
                 )))
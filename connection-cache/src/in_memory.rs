@@ -0,0 +1,433 @@
+//! An in-process transport for the connection cache, delivering the data sent
+//! to an address through a channel instead of a socket.
+//!
+//! Endpoints are registered on an [`InMemoryNetwork`] with
+//! [`InMemoryNetwork::bind`], and clients created by the
+//! [`InMemoryConnectionManager`] of the same network send to them. Packet loss
+//! and reordering can be injected with [`InMemoryNetwork::set_faults`]; the
+//! faults are driven by a seeded rng, so that a test sending the same data
+//! observes the same deliveries on each run.
+
+use {
+    crate::{
+        client_connection::ClientConnection,
+        connection_cache::{
+            BaseClientConnection, ClientError, ConnectionManager, ConnectionPool,
+            ConnectionPoolError, NewConnectionConfig, Protocol,
+        },
+        connection_cache_stats::ConnectionCacheStats,
+        nonblocking::client_connection::ClientConnection as NonblockingClientConnection,
+    },
+    async_trait::async_trait,
+    crossbeam_channel::{unbounded, Receiver, Sender},
+    rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng},
+    solana_sdk::{
+        signature::Keypair,
+        transport::{Result as TransportResult, TransportError},
+    },
+    std::{
+        collections::HashMap,
+        net::SocketAddr,
+        sync::{Arc, Mutex},
+    },
+};
+
+/// Faults injected on the packets sent over an [`InMemoryNetwork`].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct InMemoryFaults {
+    /// Probability of each packet to be dropped, in [0, 1]
+    pub drop_rate: f64,
+    /// Number of packets to an address which are held and then delivered in
+    /// a random order. Zero or one delivers packets in order.
+    pub reorder_window: usize,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct InMemoryNetworkStats {
+    pub packets_sent: u64,
+    pub packets_delivered: u64,
+    pub packets_dropped: u64,
+}
+
+struct NetworkState {
+    endpoints: HashMap<SocketAddr, Sender<Vec<u8>>>,
+    // Packets held for reordering, by destination.
+    held: HashMap<SocketAddr, Vec<Vec<u8>>>,
+    faults: InMemoryFaults,
+    rng: StdRng,
+    stats: InMemoryNetworkStats,
+}
+
+impl NetworkState {
+    fn send(&mut self, addr: &SocketAddr, data: Vec<u8>) -> TransportResult<()> {
+        if !self.endpoints.contains_key(addr) {
+            return Err(TransportError::Custom(format!(
+                "no in-memory endpoint bound to {addr}"
+            )));
+        }
+        self.stats.packets_sent += 1;
+        let drop_rate = self.faults.drop_rate.clamp(0.0, 1.0);
+        if drop_rate > 0.0 && self.rng.gen_bool(drop_rate) {
+            self.stats.packets_dropped += 1;
+            return Ok(());
+        }
+        if self.faults.reorder_window <= 1 {
+            self.deliver(addr, data);
+            return Ok(());
+        }
+        let held = self.held.entry(*addr).or_default();
+        held.push(data);
+        if held.len() >= self.faults.reorder_window {
+            self.release(addr);
+        }
+        Ok(())
+    }
+
+    fn release(&mut self, addr: &SocketAddr) {
+        let Some(mut packets) = self.held.remove(addr) else {
+            return;
+        };
+        packets.shuffle(&mut self.rng);
+        for data in packets {
+            self.deliver(addr, data);
+        }
+    }
+
+    fn deliver(&mut self, addr: &SocketAddr, data: Vec<u8>) {
+        let delivered = self
+            .endpoints
+            .get(addr)
+            .map(|sender| sender.send(data).is_ok())
+            .unwrap_or_default();
+        if delivered {
+            self.stats.packets_delivered += 1;
+        } else {
+            // The receiving end of the endpoint was dropped.
+            self.endpoints.remove(addr);
+            self.stats.packets_dropped += 1;
+        }
+    }
+}
+
+/// A set of in-memory endpoints, shared by the clients and servers of a test.
+#[derive(Clone)]
+pub struct InMemoryNetwork {
+    state: Arc<Mutex<NetworkState>>,
+}
+
+impl Default for InMemoryNetwork {
+    fn default() -> Self {
+        Self::new_with_seed(0)
+    }
+}
+
+impl InMemoryNetwork {
+    /// Creates a network whose injected faults are driven by `seed`.
+    pub fn new_with_seed(seed: u64) -> Self {
+        let state = NetworkState {
+            endpoints: HashMap::default(),
+            held: HashMap::default(),
+            faults: InMemoryFaults::default(),
+            rng: StdRng::seed_from_u64(seed),
+            stats: InMemoryNetworkStats::default(),
+        };
+        Self {
+            state: Arc::new(Mutex::new(state)),
+        }
+    }
+
+    /// Registers an endpoint at `addr`, returning the receiver of the data
+    /// sent to it. Replaces any endpoint previously bound to `addr`.
+    pub fn bind(&self, addr: SocketAddr) -> Receiver<Vec<u8>> {
+        let (sender, receiver) = unbounded();
+        self.state.lock().unwrap().endpoints.insert(addr, sender);
+        receiver
+    }
+
+    /// Removes the endpoint at `addr`, dropping the packets held for it.
+    pub fn unbind(&self, addr: &SocketAddr) {
+        let mut state = self.state.lock().unwrap();
+        state.endpoints.remove(addr);
+        state.held.remove(addr);
+    }
+
+    pub fn set_faults(&self, faults: InMemoryFaults) {
+        self.state.lock().unwrap().faults = faults;
+    }
+
+    /// Delivers the packets held for reordering, in a random order.
+    pub fn flush(&self) {
+        let mut state = self.state.lock().unwrap();
+        let addrs: Vec<_> = state.held.keys().copied().collect();
+        for addr in addrs {
+            state.release(&addr);
+        }
+    }
+
+    pub fn stats(&self) -> InMemoryNetworkStats {
+        self.state.lock().unwrap().stats
+    }
+
+    pub fn send(&self, addr: &SocketAddr, data: Vec<u8>) -> TransportResult<()> {
+        self.state.lock().unwrap().send(addr, data)
+    }
+
+    pub fn send_batch<I>(&self, addr: &SocketAddr, buffers: I) -> TransportResult<()>
+    where
+        I: IntoIterator<Item = Vec<u8>>,
+    {
+        let mut state = self.state.lock().unwrap();
+        buffers
+            .into_iter()
+            .try_for_each(|data| state.send(addr, data))
+    }
+}
+
+pub struct InMemoryPool {
+    connections: Vec<Arc<InMemory>>,
+}
+
+impl ConnectionPool for InMemoryPool {
+    type BaseClientConnection = InMemory;
+    type NewConnectionConfig = InMemoryConfig;
+
+    fn add_connection(&mut self, config: &Self::NewConnectionConfig, addr: &SocketAddr) -> usize {
+        let connection = self.create_pool_entry(config, addr);
+        let idx = self.connections.len();
+        self.connections.push(connection);
+        idx
+    }
+
+    fn num_connections(&self) -> usize {
+        self.connections.len()
+    }
+
+    fn get(&self, index: usize) -> Result<Arc<Self::BaseClientConnection>, ConnectionPoolError> {
+        self.connections
+            .get(index)
+            .cloned()
+            .ok_or(ConnectionPoolError::IndexOutOfRange)
+    }
+
+    fn create_pool_entry(
+        &self,
+        config: &Self::NewConnectionConfig,
+        _addr: &SocketAddr,
+    ) -> Arc<Self::BaseClientConnection> {
+        Arc::new(InMemory(config.network.clone()))
+    }
+}
+
+pub struct InMemoryConfig {
+    network: InMemoryNetwork,
+}
+
+impl NewConnectionConfig for InMemoryConfig {
+    fn new() -> Result<Self, ClientError> {
+        Ok(Self {
+            network: InMemoryNetwork::default(),
+        })
+    }
+}
+
+pub struct InMemory(InMemoryNetwork);
+
+impl BaseClientConnection for InMemory {
+    type BlockingClientConnection = InMemoryClientConnection;
+    type NonblockingClientConnection = NonblockingInMemoryClientConnection;
+
+    fn new_blocking_connection(
+        &self,
+        addr: SocketAddr,
+        _stats: Arc<ConnectionCacheStats>,
+    ) -> Arc<Self::BlockingClientConnection> {
+        Arc::new(InMemoryClientConnection {
+            network: self.0.clone(),
+            addr,
+        })
+    }
+
+    fn new_nonblocking_connection(
+        &self,
+        addr: SocketAddr,
+        _stats: Arc<ConnectionCacheStats>,
+    ) -> Arc<Self::NonblockingClientConnection> {
+        Arc::new(NonblockingInMemoryClientConnection {
+            network: self.0.clone(),
+            addr,
+        })
+    }
+}
+
+/// Connection manager of clients sending over an [`InMemoryNetwork`].
+///
+/// Endpoints are addressed like UDP sockets, so clients such as the
+/// `TpuClient` send to the UDP TPU address of the leaders.
+#[derive(Default)]
+pub struct InMemoryConnectionManager {
+    network: InMemoryNetwork,
+}
+
+impl InMemoryConnectionManager {
+    pub fn new(network: InMemoryNetwork) -> Self {
+        Self { network }
+    }
+}
+
+impl ConnectionManager for InMemoryConnectionManager {
+    type ConnectionPool = InMemoryPool;
+    type NewConnectionConfig = InMemoryConfig;
+
+    const PROTOCOL: Protocol = Protocol::UDP;
+
+    fn new_connection_pool(&self) -> Self::ConnectionPool {
+        InMemoryPool {
+            connections: Vec::default(),
+        }
+    }
+
+    fn new_connection_config(&self) -> Self::NewConnectionConfig {
+        InMemoryConfig {
+            network: self.network.clone(),
+        }
+    }
+
+    fn update_key(&self, _key: &Keypair) -> Result<(), Box<dyn std::error::Error>> {
+        Ok(())
+    }
+}
+
+pub struct InMemoryClientConnection {
+    network: InMemoryNetwork,
+    addr: SocketAddr,
+}
+
+impl ClientConnection for InMemoryClientConnection {
+    fn server_addr(&self) -> &SocketAddr {
+        &self.addr
+    }
+
+    fn send_data(&self, buffer: &[u8]) -> TransportResult<()> {
+        self.network.send(&self.addr, buffer.to_vec())
+    }
+
+    fn send_data_async(&self, buffer: Vec<u8>) -> TransportResult<()> {
+        self.network.send(&self.addr, buffer)
+    }
+
+    fn send_data_batch(&self, buffers: &[Vec<u8>]) -> TransportResult<()> {
+        self.network.send_batch(&self.addr, buffers.iter().cloned())
+    }
+
+    fn send_data_batch_async(&self, buffers: Vec<Vec<u8>>) -> TransportResult<()> {
+        self.network.send_batch(&self.addr, buffers)
+    }
+}
+
+pub struct NonblockingInMemoryClientConnection {
+    network: InMemoryNetwork,
+    addr: SocketAddr,
+}
+
+#[async_trait]
+impl NonblockingClientConnection for NonblockingInMemoryClientConnection {
+    fn server_addr(&self) -> &SocketAddr {
+        &self.addr
+    }
+
+    async fn send_data(&self, buffer: &[u8]) -> TransportResult<()> {
+        self.network.send(&self.addr, buffer.to_vec())
+    }
+
+    async fn send_data_batch(&self, buffers: &[Vec<u8>]) -> TransportResult<()> {
+        self.network.send_batch(&self.addr, buffers.iter().cloned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::connection_cache::ConnectionCache,
+        std::net::{IpAddr, Ipv4Addr},
+    };
+
+    fn new_cache(
+        network: &InMemoryNetwork,
+    ) -> ConnectionCache<InMemoryPool, InMemoryConnectionManager, InMemoryConfig> {
+        let manager = InMemoryConnectionManager::new(network.clone());
+        ConnectionCache::new("connection_cache_test", manager, 1).unwrap()
+    }
+
+    #[test]
+    fn test_in_memory_connection() {
+        let network = InMemoryNetwork::default();
+        let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 8003);
+        let cache = new_cache(&network);
+        let connection = cache.get_connection(&addr);
+        assert_eq!(connection.server_addr(), &addr);
+        assert!(connection.send_data(&[1]).is_err());
+
+        let receiver = network.bind(addr);
+        connection.send_data(&[1]).unwrap();
+        connection.send_data_batch(&[vec![2], vec![3, 4]]).unwrap();
+        assert_eq!(
+            receiver.try_iter().collect::<Vec<_>>(),
+            vec![vec![1], vec![2], vec![3, 4]]
+        );
+
+        let connection = cache.get_nonblocking_connection(&addr);
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(connection.send_data(&[5])).unwrap();
+        assert_eq!(receiver.try_recv().unwrap(), vec![5]);
+        assert_eq!(
+            network.stats(),
+            InMemoryNetworkStats {
+                packets_sent: 4,
+                packets_delivered: 4,
+                packets_dropped: 0,
+            }
+        );
+
+        network.unbind(&addr);
+        assert!(runtime.block_on(connection.send_data(&[6])).is_err());
+    }
+
+    #[test]
+    fn test_in_memory_faults() {
+        let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 8003);
+        let packets: Vec<_> = (0..100u8).map(|k| vec![k]).collect();
+        let run = |faults: InMemoryFaults| {
+            let network = InMemoryNetwork::new_with_seed(42);
+            network.set_faults(faults);
+            let receiver = network.bind(addr);
+            network.send_batch(&addr, packets.clone()).unwrap();
+            network.flush();
+            let stats = network.stats();
+            assert_eq!(stats.packets_sent, 100);
+            assert_eq!(stats.packets_delivered + stats.packets_dropped, 100);
+            receiver.try_iter().collect::<Vec<_>>()
+        };
+
+        let faults = InMemoryFaults {
+            drop_rate: 0.5,
+            reorder_window: 0,
+        };
+        let received = run(faults);
+        assert!(!received.is_empty() && received.len() < packets.len());
+        assert!(received.windows(2).all(|w| w[0] < w[1]));
+        // Faults are deterministic for a given seed.
+        assert_eq!(run(faults), received);
+
+        let faults = InMemoryFaults {
+            drop_rate: 0.0,
+            reorder_window: 16,
+        };
+        let received = run(faults);
+        assert_ne!(received, packets);
+        let mut sorted = received.clone();
+        sorted.sort();
+        assert_eq!(sorted, packets);
+        assert_eq!(run(faults), received);
+    }
+}
@@ -3,6 +3,7 @@
 pub mod client_connection;
 pub mod connection_cache;
 pub mod connection_cache_stats;
+pub mod in_memory;
 pub mod nonblocking;
 
 #[macro_use]
@@ -1,15 +1,20 @@
 use {
     crate::{
         cluster_slots_service::cluster_slots::ClusterSlots,
-        repair::{outstanding_requests::OutstandingRequests, serve_repair::ShredRepairType},
+        repair::{
+            outstanding_requests::OutstandingRequests, repair_timeline::RepairTimeline,
+            serve_repair::ShredRepairType,
+        },
     },
     solana_gossip::cluster_info::ClusterInfo,
+    solana_ledger::blockstore::Blockstore,
     solana_runtime::bank_forks::BankForks,
     solana_sdk::{pubkey::Pubkey, quic::NotifyKeyUpdate},
+    solana_streamer::quic::StreamStats,
     std::{
         collections::HashSet,
         net::UdpSocket,
-        sync::{Arc, RwLock},
+        sync::{Arc, Mutex, RwLock},
     },
 };
 
@@ -17,10 +22,16 @@ use {
 pub struct AdminRpcRequestMetadataPostInit {
     pub cluster_info: Arc<ClusterInfo>,
     pub bank_forks: Arc<RwLock<BankForks>>,
+    pub blockstore: Arc<Blockstore>,
     pub vote_account: Pubkey,
     pub repair_whitelist: Arc<RwLock<HashSet<Pubkey>>>,
     pub notifies: Vec<Arc<dyn NotifyKeyUpdate + Sync + Send>>,
     pub repair_socket: Arc<UdpSocket>,
     pub outstanding_repair_requests: Arc<RwLock<OutstandingRequests<ShredRepairType>>>,
     pub cluster_slots: Arc<ClusterSlots>,
+    pub tpu_quic_stats: Arc<StreamStats>,
+    pub tpu_forwards_quic_stats: Arc<StreamStats>,
+    pub repair_timeline: Option<Arc<RepairTimeline>>,
+    /// Blockstore columns being compacted by the admin rpc service
+    pub blockstore_compactions: Arc<Mutex<HashSet<String>>>,
 }
@@ -587,12 +587,20 @@ impl BankingStage {
             let packet_deserializer =
                 PacketDeserializer::new(non_vote_receiver, bank_forks.clone());
             let scheduler = PrioGraphScheduler::new(work_senders, finished_work_receiver);
+            let forwarder = Forwarder::new(
+                poh_recorder.clone(),
+                bank_forks.clone(),
+                cluster_info.clone(),
+                connection_cache.clone(),
+                data_budget.clone(),
+            );
             let scheduler_controller = SchedulerController::new(
                 decision_maker.clone(),
                 packet_deserializer,
                 bank_forks,
                 scheduler,
                 worker_metrics,
+                Some(forwarder),
             );
             Builder::new()
                 .name("solBnkTxSched".to_string())
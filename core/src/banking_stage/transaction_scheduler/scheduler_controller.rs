@@ -14,13 +14,15 @@ use {
         consume_worker::ConsumeWorkerMetrics,
         consumer::Consumer,
         decision_maker::{BufferedPacketsDecision, DecisionMaker},
+        forwarder::Forwarder,
         immutable_deserialized_packet::ImmutableDeserializedPacket,
         packet_deserializer::PacketDeserializer,
-        TOTAL_BUFFERED_PACKETS,
+        ForwardOption, TOTAL_BUFFERED_PACKETS,
     },
     crossbeam_channel::RecvTimeoutError,
     solana_cost_model::cost_model::CostModel,
     solana_measure::measure_us,
+    solana_perf::packet::Packet,
     solana_program_runtime::compute_budget_processor::process_compute_budget_instructions,
     solana_runtime::{bank::Bank, bank_forks::BankForks},
     solana_sdk::{
@@ -46,6 +48,11 @@ pub(crate) struct SchedulerController {
     decision_maker: DecisionMaker,
     /// Packet/Transaction ingress.
     packet_receiver: PacketDeserializer,
+    /// Forwards buffered transactions to the upcoming leaders, if any.
+    forwarder: Option<Forwarder>,
+    /// Whether the container may hold transactions which were not forwarded
+    /// yet. Avoids walking the priority queue when everything was forwarded.
+    has_unforwarded_transactions: bool,
     bank_forks: Arc<RwLock<BankForks>>,
     /// Generates unique IDs for incoming transactions.
     transaction_id_generator: TransactionIdGenerator,
@@ -71,10 +78,13 @@ impl SchedulerController {
         bank_forks: Arc<RwLock<BankForks>>,
         scheduler: PrioGraphScheduler,
         worker_metrics: Vec<Arc<ConsumeWorkerMetrics>>,
+        forwarder: Option<Forwarder>,
     ) -> Self {
         Self {
             decision_maker,
             packet_receiver: packet_deserializer,
+            forwarder,
+            has_unforwarded_transactions: false,
             bank_forks,
             transaction_id_generator: TransactionIdGenerator::default(),
             container: TransactionStateContainer::with_capacity(TOTAL_BUFFERED_PACKETS),
@@ -87,16 +97,14 @@ impl SchedulerController {
 
     pub fn run(mut self) -> Result<(), SchedulerError> {
         loop {
-            // BufferedPacketsDecision is shared with legacy BankingStage, which will forward
-            // packets. Initially, not renaming these decision variants but the actions taken
-            // are different, since new BankingStage will not forward packets.
-            // For `Forward` and `ForwardAndHold`, we want to receive packets but will not
-            // forward them to the next leader. In this case, `ForwardAndHold` is
-            // indistiguishable from `Hold`.
+            // BufferedPacketsDecision is shared with legacy BankingStage.
+            // For `Forward` and `ForwardAndHold`, buffered transactions which were not
+            // forwarded yet are sent to the next leader in priority order, if a forwarder
+            // is configured. Otherwise, `ForwardAndHold` is indistiguishable from `Hold`.
             //
-            // `Forward` will drop packets from the buffer instead of forwarding.
-            // During receiving, since packets would be dropped from buffer anyway, we can
-            // bypass sanitization and buffering and immediately drop the packets.
+            // `Forward` will drop packets from the buffer after forwarding them.
+            // Without a forwarder, since packets would be dropped from buffer anyway, we
+            // can bypass sanitization and buffering and immediately drop the packets.
             let (decision, decision_time_us) =
                 measure_us!(self.decision_maker.make_consume_or_forward_decision());
             self.timing_metrics.update(|timing_metrics| {
@@ -172,15 +180,19 @@ impl SchedulerController {
                 });
             }
             BufferedPacketsDecision::Forward => {
+                let (_, forward_time_us) = measure_us!(self.forward_transactions());
                 let (_, clear_time_us) = measure_us!(self.clear_container());
                 self.timing_metrics.update(|timing_metrics| {
+                    saturating_add_assign!(timing_metrics.forward_time_us, forward_time_us);
                     saturating_add_assign!(timing_metrics.clear_time_us, clear_time_us);
                 });
             }
             BufferedPacketsDecision::ForwardAndHold => {
                 let (_, clean_time_us) = measure_us!(self.clean_queue());
+                let (_, forward_time_us) = measure_us!(self.forward_transactions());
                 self.timing_metrics.update(|timing_metrics| {
                     saturating_add_assign!(timing_metrics.clean_time_us, clean_time_us);
+                    saturating_add_assign!(timing_metrics.forward_time_us, forward_time_us);
                 });
             }
             BufferedPacketsDecision::Hold => {}
@@ -213,6 +225,74 @@ impl SchedulerController {
         }
     }
 
+    /// Forwards the buffered transactions which were not forwarded yet to the
+    /// next leader, highest priority first. Transactions are kept in the
+    /// container, and are forwarded at most once.
+    fn forward_transactions(&mut self) {
+        // Bound the work done per iteration; the forwarder data budget
+        // limits the number of packets which are actually sent.
+        const MAX_FORWARDED_TRANSACTIONS: usize = 1_024;
+        if self.forwarder.is_none() {
+            return;
+        }
+
+        let packets = self.take_packets_to_forward(MAX_FORWARDED_TRANSACTIONS);
+        let Some(forwarder) = self.forwarder.as_ref() else {
+            return;
+        };
+        if packets.is_empty() {
+            return;
+        }
+        let (res, num_forwarded, _forward_us, _leader_pubkey) =
+            forwarder.forward_packets(&ForwardOption::ForwardTransaction, packets.iter());
+        if let Err(err) = res {
+            debug!("failed to forward transactions: {err:?}");
+        }
+        self.count_metrics.update(|count_metrics| {
+            saturating_add_assign!(count_metrics.num_forwarded, num_forwarded);
+        });
+    }
+
+    /// Marks up to `max_packets` of the buffered transactions which were not
+    /// forwarded yet as forwarded, highest priority first, and returns their
+    /// packets. Only the ids popped from the priority queue until the budget
+    /// is full are pushed back into it.
+    fn take_packets_to_forward(&mut self, max_packets: usize) -> Vec<Packet> {
+        let mut packets = Vec::with_capacity(max_packets);
+        if !self.has_unforwarded_transactions {
+            return packets;
+        }
+
+        let mut transaction_ids = Vec::new();
+        while packets.len() < max_packets {
+            let Some(id) = self.container.pop() else {
+                // Every transaction in the queue was forwarded.
+                self.has_unforwarded_transactions = false;
+                break;
+            };
+            transaction_ids.push(id);
+            let state = self
+                .container
+                .get_mut_transaction_state(&id.id)
+                .expect("transaction must exist");
+            if !state.should_forward() {
+                continue;
+            }
+            let transaction = state
+                .transaction_ttl()
+                .transaction
+                .to_versioned_transaction();
+            if let Ok(packet) = Packet::from_data(None, transaction) {
+                packets.push(packet);
+            }
+            state.mark_forwarded();
+        }
+        for id in transaction_ids {
+            self.container.push_id_into_queue(id);
+        }
+        packets
+    }
+
     /// Clears the transaction state container.
     /// This only clears pending transactions, and does **not** clear in-flight transactions.
     fn clear_container(&mut self) {
@@ -269,6 +349,8 @@ impl SchedulerController {
                 if result.is_err() {
                     saturating_add_assign!(num_dropped_on_age_and_status, 1);
                     self.container.remove_by_id(&id.id);
+                } else {
+                    self.container.push_id_into_queue(*id);
                 }
             }
         }
@@ -286,6 +368,11 @@ impl SchedulerController {
         let ((num_transactions, num_retryable), receive_completed_time_us) =
             measure_us!(self.scheduler.receive_completed(&mut self.container)?);
 
+        // Retried transactions are back in the queue, and may not have been
+        // forwarded yet.
+        if num_retryable > 0 {
+            self.has_unforwarded_transactions = true;
+        }
         self.count_metrics.update(|count_metrics| {
             saturating_add_assign!(count_metrics.num_finished, num_transactions);
             saturating_add_assign!(count_metrics.num_retryable, num_retryable);
@@ -314,7 +401,7 @@ impl SchedulerController {
                 },
                 true,
             ),
-            BufferedPacketsDecision::Forward => (MAX_PACKET_RECEIVE_TIME, false),
+            BufferedPacketsDecision::Forward => (MAX_PACKET_RECEIVE_TIME, self.forwarder.is_some()),
             BufferedPacketsDecision::ForwardAndHold | BufferedPacketsDecision::Hold => {
                 (MAX_PACKET_RECEIVE_TIME, true)
             }
@@ -375,19 +462,22 @@ impl SchedulerController {
             let (transactions, fee_budget_limits_vec): (Vec<_>, Vec<_>) = chunk
                 .iter()
                 .filter_map(|packet| {
-                    packet.build_sanitized_transaction(feature_set, vote_only, bank.as_ref())
+                    let forwarded = packet.original_packet().meta().forwarded();
+                    packet
+                        .build_sanitized_transaction(feature_set, vote_only, bank.as_ref())
+                        .map(|tx| (tx, forwarded))
                 })
                 .inspect(|_| saturating_add_assign!(post_sanitization_count, 1))
-                .filter(|tx| {
+                .filter(|(tx, _)| {
                     SanitizedTransaction::validate_account_locks(
                         tx.message(),
                         transaction_account_lock_limit,
                     )
                     .is_ok()
                 })
-                .filter_map(|tx| {
+                .filter_map(|(tx, forwarded)| {
                     process_compute_budget_instructions(tx.message().program_instructions_iter())
-                        .map(|compute_budget| (tx, compute_budget.into()))
+                        .map(|compute_budget| (tx, (compute_budget.into(), forwarded)))
                         .ok()
                 })
                 .unzip();
@@ -403,7 +493,7 @@ impl SchedulerController {
             let mut post_transaction_check_count: usize = 0;
             let mut num_dropped_on_capacity: usize = 0;
             let mut num_buffered: usize = 0;
-            for ((transaction, fee_budget_limits), _) in transactions
+            for ((transaction, (fee_budget_limits, forwarded)), _) in transactions
                 .into_iter()
                 .zip(fee_budget_limits_vec)
                 .zip(check_results)
@@ -427,6 +517,15 @@ impl SchedulerController {
                 ) {
                     saturating_add_assign!(num_dropped_on_capacity, 1);
                 }
+                // Transactions received from other nodes were already
+                // forwarded once, and are not forwarded again.
+                if forwarded {
+                    if let Some(state) = self.container.get_mut_transaction_state(&transaction_id) {
+                        state.mark_forwarded();
+                    }
+                } else {
+                    self.has_unforwarded_transactions = true;
+                }
                 saturating_add_assign!(num_buffered, 1);
             }
 
@@ -526,13 +625,19 @@ mod tests {
             blockstore::Blockstore, genesis_utils::GenesisConfigInfo,
             get_tmp_ledger_path_auto_delete, leader_schedule_cache::LeaderScheduleCache,
         },
-        solana_perf::packet::{to_packet_batches, PacketBatch, NUM_PACKETS},
+        solana_perf::packet::{to_packet_batches, PacketBatch, PacketFlags, NUM_PACKETS},
         solana_poh::poh_recorder::{PohRecorder, Record, WorkingBankEntry},
         solana_runtime::bank::Bank,
         solana_sdk::{
-            compute_budget::ComputeBudgetInstruction, hash::Hash, message::Message,
-            poh_config::PohConfig, pubkey::Pubkey, signature::Keypair, signer::Signer,
-            system_instruction, system_transaction, transaction::Transaction,
+            compute_budget::ComputeBudgetInstruction,
+            hash::Hash,
+            message::Message,
+            poh_config::PohConfig,
+            pubkey::Pubkey,
+            signature::Keypair,
+            signer::Signer,
+            system_instruction, system_transaction,
+            transaction::{Transaction, VersionedTransaction},
         },
         std::sync::{atomic::AtomicBool, Arc, RwLock},
         tempfile::TempDir,
@@ -607,6 +712,7 @@ mod tests {
             bank_forks,
             PrioGraphScheduler::new(consume_work_senders, finished_consume_work_receiver),
             vec![], // no actual workers with metrics to report, this can be empty
+            None,
         );
 
         (test_frame, scheduler_controller)
@@ -1006,4 +1112,69 @@ mod tests {
             .collect_vec();
         assert_eq!(message_hashes, vec![&tx1_hash]);
     }
+
+    #[test]
+    fn test_forward_transactions_in_priority_order() {
+        let (test_frame, mut scheduler_controller) = create_test_frame(1);
+        let TestFrame {
+            bank,
+            mint_keypair,
+            banking_packet_sender,
+            ..
+        } = &test_frame;
+
+        // Transactions with priorities 1 through 4, the third of which was
+        // forwarded to this node by another one.
+        let txs = (1..=4)
+            .map(|compute_unit_price| {
+                create_and_fund_prioritized_transfer(
+                    bank,
+                    mint_keypair,
+                    &Keypair::new(),
+                    &Pubkey::new_unique(),
+                    1,
+                    compute_unit_price,
+                    bank.last_blockhash(),
+                )
+            })
+            .collect_vec();
+        let mut packet_batches = to_packet_batches(&txs, NUM_PACKETS);
+        packet_batches[0][2].meta_mut().flags |= PacketFlags::FORWARDED;
+        banking_packet_sender
+            .send(Arc::new((packet_batches, None)))
+            .unwrap();
+        assert!(scheduler_controller
+            .receive_and_buffer_packets(&BufferedPacketsDecision::ForwardAndHold));
+        let remaining_queue_capacity = scheduler_controller.container.remaining_queue_capacity();
+
+        let take_signatures = |scheduler_controller: &mut SchedulerController| {
+            scheduler_controller
+                .take_packets_to_forward(2)
+                .iter()
+                .map(|packet| {
+                    packet
+                        .deserialize_slice::<VersionedTransaction, _>(..)
+                        .unwrap()
+                        .signatures[0]
+                })
+                .collect_vec()
+        };
+        assert_eq!(
+            take_signatures(&mut scheduler_controller),
+            vec![txs[3].signatures[0], txs[1].signatures[0]]
+        );
+        assert!(scheduler_controller.has_unforwarded_transactions);
+        assert_eq!(
+            take_signatures(&mut scheduler_controller),
+            vec![txs[0].signatures[0]]
+        );
+        assert!(!scheduler_controller.has_unforwarded_transactions);
+        assert_eq!(take_signatures(&mut scheduler_controller), vec![]);
+
+        // Forwarded transactions are kept in the container.
+        assert_eq!(
+            scheduler_controller.container.remaining_queue_capacity(),
+            remaining_queue_capacity
+        );
+    }
 }
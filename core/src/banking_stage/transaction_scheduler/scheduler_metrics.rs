@@ -67,6 +67,8 @@ pub struct SchedulerCountMetricsInner {
     /// Number of transactions that were dropped due to failed transaction
     /// checks during receive.
     pub num_dropped_on_receive_transaction_checks: usize,
+    /// Number of transactions that were forwarded to the next leader.
+    pub num_forwarded: usize,
     /// Number of transactions that were dropped due to clearing.
     pub num_dropped_on_clear: usize,
     /// Number of transactions that were dropped due to age and status checks.
@@ -136,6 +138,7 @@ impl SchedulerCountMetricsInner {
                 self.num_dropped_on_receive_transaction_checks,
                 i64
             ),
+            ("num_forwarded", self.num_forwarded, i64),
             ("num_dropped_on_clear", self.num_dropped_on_clear, i64),
             (
                 "num_dropped_on_age_and_status",
@@ -164,6 +167,7 @@ impl SchedulerCountMetricsInner {
             || self.num_dropped_on_sanitization != 0
             || self.num_dropped_on_validate_locks != 0
             || self.num_dropped_on_receive_transaction_checks != 0
+            || self.num_forwarded != 0
             || self.num_dropped_on_clear != 0
             || self.num_dropped_on_age_and_status != 0
             || self.num_dropped_on_capacity != 0
@@ -181,6 +185,7 @@ impl SchedulerCountMetricsInner {
         self.num_dropped_on_sanitization = 0;
         self.num_dropped_on_validate_locks = 0;
         self.num_dropped_on_receive_transaction_checks = 0;
+        self.num_forwarded = 0;
         self.num_dropped_on_clear = 0;
         self.num_dropped_on_age_and_status = 0;
         self.num_dropped_on_capacity = 0;
@@ -264,6 +269,8 @@ pub struct SchedulerTimingMetricsInner {
     pub schedule_filter_time_us: u64,
     /// Time spent scheduling transactions.
     pub schedule_time_us: u64,
+    /// Time spent forwarding transactions to the next leader.
+    pub forward_time_us: u64,
     /// Time spent clearing transactions from the container.
     pub clear_time_us: u64,
     /// Time spent cleaning expired or processed transactions from the container.
@@ -307,6 +314,7 @@ impl SchedulerTimingMetricsInner {
             ("buffer_time_us", self.buffer_time_us, i64),
             ("schedule_filter_time_us", self.schedule_filter_time_us, i64),
             ("schedule_time_us", self.schedule_time_us, i64),
+            ("forward_time_us", self.forward_time_us, i64),
             ("clear_time_us", self.clear_time_us, i64),
             ("clean_time_us", self.clean_time_us, i64),
             (
@@ -327,6 +335,7 @@ impl SchedulerTimingMetricsInner {
         self.buffer_time_us = 0;
         self.schedule_filter_time_us = 0;
         self.schedule_time_us = 0;
+        self.forward_time_us = 0;
         self.clear_time_us = 0;
         self.clean_time_us = 0;
         self.receive_completed_time_us = 0;
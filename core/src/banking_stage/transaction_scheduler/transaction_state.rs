@@ -32,9 +32,16 @@ pub(crate) enum TransactionState {
         transaction_ttl: SanitizedTransactionTTL,
         priority: u64,
         cost: u64,
+        /// Whether the transaction was already forwarded, either by this
+        /// node or by the node it was received from.
+        forwarded: bool,
     },
     /// The transaction is currently scheduled or being processed.
-    Pending { priority: u64, cost: u64 },
+    Pending {
+        priority: u64,
+        cost: u64,
+        forwarded: bool,
+    },
 }
 
 impl TransactionState {
@@ -44,6 +51,7 @@ impl TransactionState {
             transaction_ttl,
             priority,
             cost,
+            forwarded: false,
         }
     }
 
@@ -65,6 +73,25 @@ impl TransactionState {
         }
     }
 
+    /// Returns true if the transaction is unprocessed and was not forwarded
+    /// yet, so that it is forwarded at most once. The forwarded flag is kept
+    /// through state transitions so retried transactions are not forwarded
+    /// again.
+    pub(crate) fn should_forward(&self) -> bool {
+        match self {
+            Self::Unprocessed { forwarded, .. } => !*forwarded,
+            Self::Pending { .. } => false,
+        }
+    }
+
+    /// Marks the transaction as forwarded. Has no effect on a `Pending`
+    /// transaction.
+    pub(crate) fn mark_forwarded(&mut self) {
+        if let Self::Unprocessed { forwarded, .. } = self {
+            *forwarded = true;
+        }
+    }
+
     /// Intended to be called when a transaction is scheduled. This method will
     /// transition the transaction from `Unprocessed` to `Pending` and return the
     /// `SanitizedTransactionTTL` for processing.
@@ -78,8 +105,13 @@ impl TransactionState {
                 transaction_ttl,
                 priority,
                 cost,
+                forwarded,
             } => {
-                *self = TransactionState::Pending { priority, cost };
+                *self = TransactionState::Pending {
+                    priority,
+                    cost,
+                    forwarded,
+                };
                 transaction_ttl
             }
             TransactionState::Pending { .. } => {
@@ -97,11 +129,16 @@ impl TransactionState {
     pub(crate) fn transition_to_unprocessed(&mut self, transaction_ttl: SanitizedTransactionTTL) {
         match self.take() {
             TransactionState::Unprocessed { .. } => panic!("already unprocessed"),
-            TransactionState::Pending { priority, cost } => {
+            TransactionState::Pending {
+                priority,
+                cost,
+                forwarded,
+            } => {
                 *self = Self::Unprocessed {
                     transaction_ttl,
                     priority,
                     cost,
+                    forwarded,
                 }
             }
         }
@@ -128,6 +165,7 @@ impl TransactionState {
             Self::Pending {
                 priority: 0,
                 cost: 0,
+                forwarded: false,
             },
         )
     }
@@ -235,6 +273,27 @@ mod tests {
         assert_eq!(transaction_state.priority(), priority);
     }
 
+    #[test]
+    fn test_forwarded() {
+        let mut transaction_state = create_transaction_state(0);
+        assert!(transaction_state.should_forward());
+        transaction_state.mark_forwarded();
+        assert!(!transaction_state.should_forward());
+
+        // pending transactions are never forwarded
+        let mut transaction_state = create_transaction_state(0);
+        let transaction_ttl = transaction_state.transition_to_pending();
+        assert!(!transaction_state.should_forward());
+        transaction_state.transition_to_unprocessed(transaction_ttl);
+        assert!(transaction_state.should_forward());
+
+        // forwarded flag is not lost through state transitions
+        transaction_state.mark_forwarded();
+        let transaction_ttl = transaction_state.transition_to_pending();
+        transaction_state.transition_to_unprocessed(transaction_ttl);
+        assert!(!transaction_state.should_forward());
+    }
+
     #[test]
     #[should_panic(expected = "transaction is pending")]
     fn test_transaction_ttl_panic() {
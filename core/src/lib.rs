@@ -31,6 +31,7 @@ pub mod replay_stage;
 mod result;
 pub mod rewards_recorder_service;
 pub mod sample_performance_service;
+pub mod shred_capture;
 mod shred_fetch_stage;
 pub mod sigverify;
 pub mod sigverify_stage;
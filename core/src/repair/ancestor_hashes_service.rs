@@ -9,6 +9,7 @@ use {
             packet_threshold::DynamicPacketToProcessThreshold,
             quic_endpoint::LocalRequest,
             repair_service::{AncestorDuplicateSlotsSender, RepairInfo, RepairStatsGroup},
+            repair_timeline::{RepairRequestKind, RepairTimeline},
             request_response::RequestResponse,
             serve_repair::{
                 self, AncestorHashesRepairType, AncestorHashesResponse, RepairProtocol, ServeRepair,
@@ -203,6 +204,7 @@ impl AncestorHashesService {
             retryable_slots_sender,
             repair_info.cluster_info.clone(),
             ancestor_hashes_request_socket.clone(),
+            repair_info.repair_timeline.clone(),
         );
 
         // Generate ancestor requests for dead slots that are repairable
@@ -232,6 +234,7 @@ impl AncestorHashesService {
     }
 
     /// Listen for responses to our ancestors hashes repair requests
+    #[allow(clippy::too_many_arguments)]
     fn run_responses_listener(
         ancestor_hashes_request_statuses: Arc<DashMap<Slot, AncestorRequestStatus>>,
         response_receiver: PacketBatchReceiver,
@@ -242,6 +245,7 @@ impl AncestorHashesService {
         retryable_slots_sender: RetryableSlotsSender,
         cluster_info: Arc<ClusterInfo>,
         ancestor_socket: Arc<UdpSocket>,
+        repair_timeline: Option<Arc<RepairTimeline>>,
     ) -> JoinHandle<()> {
         Builder::new()
             .name("solAncHashesSvc".to_string())
@@ -262,6 +266,7 @@ impl AncestorHashesService {
                         &retryable_slots_sender,
                         &keypair,
                         &ancestor_socket,
+                        repair_timeline.as_deref(),
                     );
                     match result {
                         Ok(_) | Err(RecvTimeoutError::Timeout) => (),
@@ -292,6 +297,7 @@ impl AncestorHashesService {
         retryable_slots_sender: &RetryableSlotsSender,
         keypair: &Keypair,
         ancestor_socket: &UdpSocket,
+        repair_timeline: Option<&RepairTimeline>,
     ) -> Result<(), RecvTimeoutError> {
         let timeout = Duration::new(1, 0);
         let mut packet_batches = vec![response_receiver.recv_timeout(timeout)?];
@@ -322,12 +328,14 @@ impl AncestorHashesService {
                 retryable_slots_sender,
                 keypair,
                 ancestor_socket,
+                repair_timeline,
             );
         }
         packet_threshold.update(total_packets, timer.elapsed());
         Ok(())
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn process_packet_batch(
         ancestor_hashes_request_statuses: &DashMap<Slot, AncestorRequestStatus>,
         packet_batch: PacketBatch,
@@ -338,6 +346,7 @@ impl AncestorHashesService {
         retryable_slots_sender: &RetryableSlotsSender,
         keypair: &Keypair,
         ancestor_socket: &UdpSocket,
+        repair_timeline: Option<&RepairTimeline>,
     ) {
         packet_batch.iter().for_each(|packet| {
             let ancestor_request_decision = Self::verify_and_process_ancestor_response(
@@ -348,6 +357,7 @@ impl AncestorHashesService {
                 blockstore,
                 keypair,
                 ancestor_socket,
+                repair_timeline,
             );
             if let Some(ancestor_request_decision) = ancestor_request_decision {
                 Self::handle_ancestor_request_decision(
@@ -370,6 +380,7 @@ impl AncestorHashesService {
         blockstore: &Blockstore,
         keypair: &Keypair,
         ancestor_socket: &UdpSocket,
+        repair_timeline: Option<&RepairTimeline>,
     ) -> Option<AncestorRequestDecision> {
         let from_addr = packet.meta().socket_addr();
         let Some(packet_data) = packet.data(..) else {
@@ -409,6 +420,9 @@ impl AncestorHashesService {
                     stats.invalid_packets += 1;
                     return None;
                 }
+                if let Some(repair_timeline) = repair_timeline {
+                    repair_timeline.record_response(nonce, timestamp());
+                }
 
                 // If was a valid response, there must be a valid `request_slot`
                 let request_slot = request_slot.unwrap();
@@ -755,6 +769,7 @@ impl AncestorHashesService {
                 identity_keypair,
                 request_type,
                 cluster_type,
+                repair_info.repair_timeline.as_deref(),
             ) {
                 request_throttle.push(timestamp());
                 if request_type.is_pruned() {
@@ -834,6 +849,7 @@ impl AncestorHashesService {
         identity_keypair: &Keypair,
         request_type: AncestorRequestType,
         cluster_type: ClusterType,
+        repair_timeline: Option<&RepairTimeline>,
     ) -> bool {
         let repair_protocol = serve_repair::get_repair_protocol(cluster_type);
         let Ok(sampled_validators) = serve_repair.repair_request_ancestor_hashes_sample_peers(
@@ -854,6 +870,15 @@ impl AncestorHashesService {
                 .write()
                 .unwrap()
                 .add_request(ancestor_hashes_repair_type, timestamp());
+            if let Some(repair_timeline) = repair_timeline {
+                repair_timeline.record_request(
+                    RepairRequestKind::AncestorHashes,
+                    duplicate_slot,
+                    pubkey,
+                    nonce,
+                    timestamp(),
+                );
+            }
             let Ok(request_bytes) = serve_repair.ancestor_repair_request_bytes(
                 identity_keypair,
                 pubkey,
@@ -1377,6 +1402,8 @@ mod test {
                 repair_validators: None,
                 repair_whitelist,
                 wen_restart_repair_slots: None,
+                repair_timeline: None,
+                repair_peer_scores: None,
             };
 
             let (ancestor_hashes_replay_update_sender, ancestor_hashes_replay_update_receiver) =
@@ -1521,6 +1548,7 @@ mod test {
             &requester_cluster_info.keypair(),
             AncestorRequestType::DeadDuplicateConfirmed,
             ClusterType::Development,
+            None,
         );
         assert!(ancestor_hashes_request_statuses.is_empty());
 
@@ -1549,6 +1577,7 @@ mod test {
             &requester_blockstore,
             &requester_cluster_info.keypair(),
             &ancestor_hashes_request_socket,
+            None,
         );
         // should have processed a ping packet
         assert_eq!(decision, None);
@@ -1572,6 +1601,7 @@ mod test {
             &requester_cluster_info.keypair(),
             AncestorRequestType::DeadDuplicateConfirmed,
             ClusterType::Development,
+            None,
         );
 
         assert_eq!(ancestor_hashes_request_statuses.len(), 1);
@@ -1597,6 +1627,7 @@ mod test {
             &requester_blockstore,
             &requester_cluster_info.keypair(),
             &ancestor_hashes_request_socket,
+            None,
         )
         .unwrap();
 
@@ -1635,6 +1666,7 @@ mod test {
             &requester_cluster_info.keypair(),
             AncestorRequestType::PopularPruned,
             ClusterType::Development,
+            None,
         );
 
         assert_eq!(ancestor_hashes_request_statuses.len(), 1);
@@ -1660,6 +1692,7 @@ mod test {
             &requester_blockstore,
             &requester_cluster_info.keypair(),
             &ancestor_hashes_request_socket,
+            None,
         )
         .unwrap();
 
@@ -1964,6 +1997,7 @@ mod test {
             &blockstore,
             &repair_info.cluster_info.keypair(),
             &ancestor_hashes_request_socket,
+            None,
         )
         .is_none());
     }
@@ -2047,6 +2081,7 @@ mod test {
             &requester_blockstore,
             &requester_cluster_info.keypair(),
             &ancestor_hashes_request_socket,
+            None,
         );
         // Should have processed a ping packet
         assert_eq!(decision, None);
@@ -2119,6 +2154,7 @@ mod test {
             &requester_blockstore,
             &requester_cluster_info.keypair(),
             &ancestor_hashes_request_socket,
+            None,
         )
         .unwrap();
 
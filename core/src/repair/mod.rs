@@ -5,8 +5,10 @@ pub mod outstanding_requests;
 pub mod packet_threshold;
 pub(crate) mod quic_endpoint;
 pub mod repair_generic_traversal;
+pub mod repair_peer_scores;
 pub mod repair_response;
 pub mod repair_service;
+pub mod repair_timeline;
 pub mod repair_weight;
 pub mod repair_weighted_traversal;
 pub mod request_response;
@@ -0,0 +1,280 @@
+//! The `repair_peer_scores` module tracks how responsive each repair peer has
+//! been, and scales the weights used to sample repair peers accordingly.
+//!
+//! Each repair request is tracked by its nonce until either a valid response
+//! arrives, which yields a round trip time sample, or it times out. Samples
+//! decay exponentially with time, so a peer which has not been sampled
+//! recently drifts back to the neutral score of a peer never sampled at all.
+//! Multipliers are clamped from below so that slow peers are still sampled
+//! occasionally and can recover their score.
+//!
+//! Scores are only kept when the validator runs with `--repair-peer-scoring`;
+//! otherwise repair peers are sampled by their cluster slots weights alone.
+use {
+    solana_gossip::contact_info::ContactInfo,
+    solana_ledger::shred::Nonce,
+    solana_sdk::pubkey::Pubkey,
+    std::{
+        collections::{HashMap, VecDeque},
+        sync::Mutex,
+    },
+};
+
+// Requests without a valid response within this time count as timeouts.
+const REPAIR_RESPONSE_TIMEOUT_MS: u64 = 1_000;
+// Same capacity as OutstandingRequests.
+const MAX_PENDING_REQUESTS: usize = 16 * 1024;
+const SCORE_HALF_LIFE_MS: f64 = 60_000.0;
+// Every peer starts as if it has answered this many requests within
+// REFERENCE_RTT_MS, so that a few samples do not swing its score.
+const PRIOR_NUM_SAMPLES: f64 = 4.0;
+const REFERENCE_RTT_MS: f64 = 100.0;
+const MIN_WEIGHT_MULTIPLIER: f64 = 0.05;
+const MAX_WEIGHT_MULTIPLIER: f64 = 4.0;
+// Peers whose decayed samples fall below this are dropped.
+const MIN_NUM_SAMPLES: f64 = 0.01;
+
+#[derive(Debug, Default)]
+struct PeerScore {
+    num_successes: f64,
+    num_timeouts: f64,
+    rtt_ms_sum: f64,
+    last_update: u64,
+}
+
+#[derive(Default)]
+struct RepairPeerScoresStats {
+    num_requests: usize,
+    num_responses: usize,
+    num_timeouts: usize,
+    rtt_ms_sum: u64,
+}
+
+struct Inner {
+    peers: HashMap<Pubkey, PeerScore>,
+    // Requests awaiting a response, keyed by nonce.
+    pending: HashMap<Nonce, (Pubkey, /*timestamp:*/ u64)>,
+    // Nonces of pending requests in the order sent.
+    pending_order: VecDeque<(Nonce, /*timestamp:*/ u64)>,
+    stats: RepairPeerScoresStats,
+}
+
+pub struct RepairPeerScores {
+    inner: Mutex<Inner>,
+}
+
+impl PeerScore {
+    fn decay(&mut self, now: u64) {
+        if now <= self.last_update {
+            return;
+        }
+        let factor = decay_factor(now - self.last_update);
+        self.num_successes *= factor;
+        self.num_timeouts *= factor;
+        self.rtt_ms_sum *= factor;
+        self.last_update = now;
+    }
+
+    fn num_samples(&self) -> f64 {
+        self.num_successes + self.num_timeouts
+    }
+
+    // Relative to a peer with no samples, which has a multiplier of 1.
+    fn weight_multiplier(&self, now: u64) -> f64 {
+        let factor = decay_factor(now.saturating_sub(self.last_update));
+        let num_successes = self.num_successes * factor + PRIOR_NUM_SAMPLES;
+        let num_timeouts = self.num_timeouts * factor;
+        let rtt_ms_sum = self.rtt_ms_sum * factor + PRIOR_NUM_SAMPLES * REFERENCE_RTT_MS;
+        let success_rate = num_successes / (num_successes + num_timeouts);
+        let mean_rtt_ms = rtt_ms_sum / num_successes;
+        let multiplier = success_rate * 2.0 * REFERENCE_RTT_MS / (REFERENCE_RTT_MS + mean_rtt_ms);
+        multiplier.clamp(MIN_WEIGHT_MULTIPLIER, MAX_WEIGHT_MULTIPLIER)
+    }
+}
+
+fn decay_factor(elapsed_ms: u64) -> f64 {
+    0.5f64.powf(elapsed_ms as f64 / SCORE_HALF_LIFE_MS)
+}
+
+impl Default for RepairPeerScores {
+    fn default() -> Self {
+        Self {
+            inner: Mutex::new(Inner {
+                peers: HashMap::default(),
+                pending: HashMap::default(),
+                pending_order: VecDeque::default(),
+                stats: RepairPeerScoresStats::default(),
+            }),
+        }
+    }
+}
+
+impl RepairPeerScores {
+    pub(crate) fn record_request(&self, peer: &Pubkey, nonce: Nonce, now: u64) {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.pending.len() >= MAX_PENDING_REQUESTS {
+            return;
+        }
+        inner.pending.insert(nonce, (*peer, now));
+        inner.pending_order.push_back((nonce, now));
+        inner.stats.num_requests += 1;
+    }
+
+    /// Records valid repair responses. Only the first response to each
+    /// request contributes a round trip time sample.
+    pub(crate) fn record_responses<I>(&self, nonces: I, now: u64)
+    where
+        I: IntoIterator<Item = Nonce>,
+    {
+        let mut inner = self.inner.lock().unwrap();
+        for nonce in nonces {
+            let Some((peer, timestamp)) = inner.pending.remove(&nonce) else {
+                continue;
+            };
+            let rtt_ms = now.saturating_sub(timestamp);
+            let score = inner.peers.entry(peer).or_default();
+            score.decay(now);
+            score.num_successes += 1.0;
+            score.rtt_ms_sum += rtt_ms as f64;
+            inner.stats.num_responses += 1;
+            inner.stats.rtt_ms_sum += rtt_ms;
+        }
+    }
+
+    /// Counts requests without a response within the timeout against their
+    /// peers.
+    pub(crate) fn expire_requests(&self, now: u64) {
+        let mut inner = self.inner.lock().unwrap();
+        let inner = &mut *inner;
+        while let Some(&(nonce, timestamp)) = inner.pending_order.front() {
+            if now.saturating_sub(timestamp) < REPAIR_RESPONSE_TIMEOUT_MS {
+                break;
+            }
+            inner.pending_order.pop_front();
+            // Nonces wrap around, so the entry may belong to a newer request.
+            match inner.pending.get(&nonce) {
+                Some(&(peer, pending_timestamp)) if pending_timestamp == timestamp => {
+                    inner.pending.remove(&nonce);
+                    let score = inner.peers.entry(peer).or_default();
+                    score.decay(now);
+                    score.num_timeouts += 1.0;
+                    inner.stats.num_timeouts += 1;
+                }
+                _ => (),
+            }
+        }
+    }
+
+    pub(crate) fn weight_multiplier(&self, peer: &Pubkey, now: u64) -> f64 {
+        let inner = self.inner.lock().unwrap();
+        inner
+            .peers
+            .get(peer)
+            .map(|score| score.weight_multiplier(now))
+            .unwrap_or(1.0)
+    }
+
+    /// Scales the sampling weights of repair peers by their scores.
+    pub(crate) fn adjust_weights(
+        &self,
+        repair_peers: &[ContactInfo],
+        weights: Vec<u64>,
+        now: u64,
+    ) -> Vec<u64> {
+        repair_peers
+            .iter()
+            .zip(weights)
+            .map(|(peer, weight)| {
+                let multiplier = self.weight_multiplier(&peer.pubkey, now);
+                // Keep nonzero weights nonzero so the peer is still explored.
+                match (weight as f64 * multiplier) as u64 {
+                    0 if weight > 0 => 1,
+                    weight => weight,
+                }
+            })
+            .collect()
+    }
+
+    pub(crate) fn report(&self, now: u64) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.peers.retain(|_, score| {
+            score.decay(now);
+            score.num_samples() >= MIN_NUM_SAMPLES
+        });
+        let stats = std::mem::take(&mut inner.stats);
+        let mean_rtt_ms = stats
+            .rtt_ms_sum
+            .checked_div(stats.num_responses as u64)
+            .unwrap_or_default();
+        datapoint_info!(
+            "repair_peer_scores",
+            ("num_peers", inner.peers.len(), i64),
+            ("num_pending", inner.pending.len(), i64),
+            ("num_requests", stats.num_requests, i64),
+            ("num_responses", stats.num_responses, i64),
+            ("num_timeouts", stats.num_timeouts, i64),
+            ("mean_rtt_ms", mean_rtt_ms, i64),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repair_peer_scores() {
+        let scores = RepairPeerScores::default();
+        let fast = Pubkey::new_unique();
+        let slow = Pubkey::new_unique();
+        let unresponsive = Pubkey::new_unique();
+        let unknown = Pubkey::new_unique();
+        let mut nonce = 0;
+        let mut now = 1_000_000;
+        for _ in 0..20 {
+            for peer in [&fast, &slow, &unresponsive] {
+                scores.record_request(peer, nonce, now);
+                nonce += 1;
+            }
+            scores.record_responses([nonce - 3], now + 20);
+            scores.record_responses([nonce - 2], now + 500);
+            // Duplicate responses are ignored.
+            scores.record_responses([nonce - 2], now + 900);
+            now += REPAIR_RESPONSE_TIMEOUT_MS;
+            scores.expire_requests(now);
+        }
+        assert!(scores.inner.lock().unwrap().pending.is_empty());
+        let fast_multiplier = scores.weight_multiplier(&fast, now);
+        let slow_multiplier = scores.weight_multiplier(&slow, now);
+        let unresponsive_multiplier = scores.weight_multiplier(&unresponsive, now);
+        assert_eq!(scores.weight_multiplier(&unknown, now), 1.0);
+        assert!(fast_multiplier > 1.0);
+        assert!(slow_multiplier < 1.0);
+        assert!(unresponsive_multiplier < slow_multiplier);
+        assert!(unresponsive_multiplier >= MIN_WEIGHT_MULTIPLIER);
+
+        // Scores decay back toward neutral.
+        let later = now + 20 * SCORE_HALF_LIFE_MS as u64;
+        assert!((scores.weight_multiplier(&fast, later) - 1.0).abs() < 1e-3);
+        assert!((scores.weight_multiplier(&unresponsive, later) - 1.0).abs() < 1e-3);
+        scores.report(later);
+        assert!(scores.inner.lock().unwrap().peers.is_empty());
+    }
+
+    #[test]
+    fn test_adjust_weights() {
+        let scores = RepairPeerScores::default();
+        let peers: Vec<_> = (0..3)
+            .map(|_| ContactInfo::new_localhost(&Pubkey::new_unique(), 0))
+            .collect();
+        let now = 1_000_000;
+        for nonce in 0..10 {
+            scores.record_request(&peers[1].pubkey, nonce, now);
+        }
+        scores.expire_requests(now + REPAIR_RESPONSE_TIMEOUT_MS);
+        let weights = scores.adjust_weights(&peers, vec![100, 100, 0], now);
+        assert_eq!(weights[0], 100);
+        assert!(weights[1] > 0 && weights[1] < 100);
+        assert_eq!(weights[2], 0);
+    }
+}
@@ -14,6 +14,8 @@ use {
             duplicate_repair_status::AncestorDuplicateSlotToRepair,
             outstanding_requests::OutstandingRequests,
             quic_endpoint::LocalRequest,
+            repair_peer_scores::RepairPeerScores,
+            repair_timeline::RepairTimeline,
             repair_weight::RepairWeight,
             serve_repair::{
                 self, RepairProtocol, RepairRequestHeader, ServeRepair, ShredRepairType,
@@ -226,6 +228,10 @@ pub struct RepairInfo {
     pub repair_whitelist: Arc<RwLock<HashSet<Pubkey>>>,
     // A given list of slots to repair when in wen_restart
     pub wen_restart_repair_slots: Option<Arc<RwLock<Vec<Slot>>>>,
+    // Opt-in per-slot timeline of repair requests and responses
+    pub repair_timeline: Option<Arc<RepairTimeline>>,
+    // Responsiveness of repair peers, used to bias peer selection
+    pub repair_peer_scores: Option<Arc<RepairPeerScores>>,
 }
 
 pub struct RepairSlotRange {
@@ -343,6 +349,9 @@ impl RepairService {
                 // Purge outdated slots from the weighting heuristic
                 set_root_elapsed = Measure::start("set_root_elapsed");
                 repair_weight.set_root(new_root);
+                if let Some(repair_timeline) = &repair_info.repair_timeline {
+                    repair_timeline.set_root(new_root);
+                }
                 set_root_elapsed.stop();
 
                 // Remove dumped slots from the weighting heuristic
@@ -450,6 +459,9 @@ impl RepairService {
             let identity_keypair: &Keypair = &repair_info.cluster_info.keypair().clone();
 
             let mut build_repairs_batch_elapsed = Measure::start("build_repairs_batch_elapsed");
+            if let Some(repair_peer_scores) = &repair_info.repair_peer_scores {
+                repair_peer_scores.expire_requests(timestamp());
+            }
             let batch: Vec<(Vec<u8>, SocketAddr)> = {
                 let mut outstanding_requests = outstanding_requests.write().unwrap();
                 repairs
@@ -467,6 +479,8 @@ impl RepairService {
                                 quic_endpoint_sender,
                                 quic_endpoint_response_sender,
                                 repair_protocol,
+                                repair_info.repair_timeline.as_deref(),
+                                repair_info.repair_peer_scores.as_deref(),
                             )
                             .ok()??;
                         Some((req, to))
@@ -534,6 +548,9 @@ impl RepairService {
                         ("orphan-slot-min", nonzero_num(repair_stats.orphan.min), Option<i64>),
                     );
                 }
+                if let Some(repair_peer_scores) = &repair_info.repair_peer_scores {
+                    repair_peer_scores.report(timestamp());
+                }
                 datapoint_info!(
                     "repair_service-repair_timing",
                     ("set-root-elapsed", repair_timing.set_root_elapsed, i64),
@@ -0,0 +1,449 @@
+//! The `repair_timeline` module keeps an opt-in, per-slot record of how each
+//! slot was received: when its first shred arrived, which repairs were
+//! requested from which peers, when those peers responded and when the slot
+//! became full. Slots are finalized once they fall behind the root, at which
+//! point they are appended as json lines to an optional file and kept in a
+//! bounded history for the admin rpc. The number of slots tracked while the
+//! root does not advance, and the size of the file, are bounded as well.
+use {
+    crate::repair::serve_repair::ShredRepairType,
+    lru::LruCache,
+    serde::{Deserialize, Serialize},
+    solana_ledger::shred::Nonce,
+    solana_sdk::{clock::Slot, pubkey::Pubkey},
+    std::{
+        collections::{BTreeMap, HashMap, VecDeque},
+        fs::{File, OpenOptions},
+        io::{self, BufWriter, Write},
+        path::Path,
+        sync::Mutex,
+    },
+};
+
+// Number of finalized slots retained for the admin rpc.
+const MAX_FINALIZED_SLOTS: usize = 1024;
+// Number of slots at or above the root tracked at once; new slots are not
+// tracked beyond this until the root advances.
+const MAX_TRACKED_SLOTS: usize = 4096;
+pub const DEFAULT_MAX_REPAIR_TIMELINE_BYTES: u64 = 1024 * 1024 * 1024;
+// Same capacity as OutstandingRequests, so that any request which may still
+// receive a valid response can be attributed to its peer.
+const MAX_TRACKED_NONCES: usize = 16 * 1024;
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub enum RepairRequestKind {
+    Shred,
+    HighestShred,
+    Orphan,
+    AncestorHashes,
+}
+
+impl From<&ShredRepairType> for RepairRequestKind {
+    fn from(repair_type: &ShredRepairType) -> Self {
+        match repair_type {
+            ShredRepairType::Shred(..) => Self::Shred,
+            ShredRepairType::HighestShred(..) => Self::HighestShred,
+            ShredRepairType::Orphan(_) => Self::Orphan,
+        }
+    }
+}
+
+/// Repairs of one kind requested from one peer for a slot. Timestamps are
+/// milliseconds since the unix epoch.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PeerRepairTimeline {
+    pub kind: RepairRequestKind,
+    pub peer: String,
+    pub num_requests: u64,
+    pub num_responses: u64,
+    pub first_request_timestamp: u64,
+    pub last_request_timestamp: u64,
+    pub first_response_timestamp: Option<u64>,
+    pub last_response_timestamp: Option<u64>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SlotRepairTimeline {
+    pub slot: Slot,
+    pub first_shred_timestamp: Option<u64>,
+    pub first_shred_repaired: bool,
+    pub num_turbine_shreds: u64,
+    pub num_repaired_shreds: u64,
+    pub first_repair_timestamp: Option<u64>,
+    pub completed_timestamp: Option<u64>,
+    pub repairs: Vec<PeerRepairTimeline>,
+}
+
+#[derive(Default)]
+struct PeerEntry {
+    num_requests: u64,
+    num_responses: u64,
+    first_request_timestamp: u64,
+    last_request_timestamp: u64,
+    first_response_timestamp: Option<u64>,
+    last_response_timestamp: Option<u64>,
+}
+
+#[derive(Default)]
+struct SlotEntry {
+    first_shred_timestamp: Option<u64>,
+    first_shred_repaired: bool,
+    num_turbine_shreds: u64,
+    num_repaired_shreds: u64,
+    completed_timestamp: Option<u64>,
+    repairs: HashMap<(RepairRequestKind, Pubkey), PeerEntry>,
+}
+
+struct Inner {
+    slots: BTreeMap<Slot, SlotEntry>,
+    nonces: LruCache<Nonce, (Slot, RepairRequestKind, Pubkey)>,
+    finalized: VecDeque<SlotRepairTimeline>,
+    root: Slot,
+}
+
+struct TimelineWriter {
+    writer: BufWriter<File>,
+    num_bytes: u64,
+    /// Set once a slot did not fit, after which nothing more is written
+    full: bool,
+}
+
+pub struct RepairTimeline {
+    inner: Mutex<Inner>,
+    writer: Option<Mutex<TimelineWriter>>,
+    max_bytes: u64,
+}
+
+impl Inner {
+    // Returns the entry of the slot, or None if the slot is older than the
+    // root or too many slots are already tracked.
+    fn slot_entry(&mut self, slot: Slot) -> Option<&mut SlotEntry> {
+        if slot < self.root
+            || (self.slots.len() >= MAX_TRACKED_SLOTS && !self.slots.contains_key(&slot))
+        {
+            return None;
+        }
+        Some(self.slots.entry(slot).or_default())
+    }
+}
+
+impl RepairTimeline {
+    /// Creates a timeline which, if a path is given, also appends finalized
+    /// slots to that file until it reaches `max_bytes`.
+    pub fn new(path: Option<&Path>, max_bytes: u64) -> io::Result<Self> {
+        let writer = path
+            .map(|path| {
+                let file = OpenOptions::new().create(true).append(true).open(path)?;
+                Ok::<_, io::Error>(Mutex::new(TimelineWriter {
+                    num_bytes: file.metadata()?.len(),
+                    writer: BufWriter::new(file),
+                    full: false,
+                }))
+            })
+            .transpose()?;
+        Ok(Self {
+            inner: Mutex::new(Inner {
+                slots: BTreeMap::new(),
+                nonces: LruCache::new(MAX_TRACKED_NONCES),
+                finalized: VecDeque::with_capacity(MAX_FINALIZED_SLOTS),
+                root: 0,
+            }),
+            writer,
+            max_bytes,
+        })
+    }
+
+    pub(crate) fn record_request(
+        &self,
+        kind: RepairRequestKind,
+        slot: Slot,
+        peer: &Pubkey,
+        nonce: Nonce,
+        now: u64,
+    ) {
+        let mut inner = self.inner.lock().unwrap();
+        let Some(entry) = inner.slot_entry(slot) else {
+            return;
+        };
+        let entry = entry
+            .repairs
+            .entry((kind, *peer))
+            .or_insert_with(|| PeerEntry {
+                first_request_timestamp: now,
+                ..PeerEntry::default()
+            });
+        entry.num_requests += 1;
+        entry.last_request_timestamp = now;
+        inner.nonces.put(nonce, (slot, kind, *peer));
+    }
+
+    /// Records a verified response to the request with the given nonce.
+    pub(crate) fn record_response(&self, nonce: Nonce, now: u64) {
+        let mut inner = self.inner.lock().unwrap();
+        Self::do_record_response(&mut inner, nonce, now);
+    }
+
+    /// Records received shreds, along with the nonce of the repair request
+    /// for repaired shreds.
+    pub(crate) fn record_shreds<I>(&self, shreds: I, now: u64)
+    where
+        I: IntoIterator<Item = (Slot, Option<Nonce>)>,
+    {
+        let mut inner = self.inner.lock().unwrap();
+        for (slot, nonce) in shreds {
+            if let Some(nonce) = nonce {
+                Self::do_record_response(&mut inner, nonce, now);
+            }
+            let Some(entry) = inner.slot_entry(slot) else {
+                continue;
+            };
+            if entry.first_shred_timestamp.is_none() {
+                entry.first_shred_timestamp = Some(now);
+                entry.first_shred_repaired = nonce.is_some();
+            }
+            if nonce.is_some() {
+                entry.num_repaired_shreds += 1;
+            } else {
+                entry.num_turbine_shreds += 1;
+            }
+        }
+    }
+
+    fn do_record_response(inner: &mut Inner, nonce: Nonce, now: u64) {
+        let Some(&(slot, kind, peer)) = inner.nonces.get(&nonce) else {
+            return;
+        };
+        let Some(entry) = inner
+            .slots
+            .get_mut(&slot)
+            .and_then(|entry| entry.repairs.get_mut(&(kind, peer)))
+        else {
+            return;
+        };
+        entry.num_responses += 1;
+        entry.first_response_timestamp.get_or_insert(now);
+        entry.last_response_timestamp = Some(now);
+    }
+
+    /// Returns the slots among the given ones which are tracked but not yet
+    /// marked as completed.
+    pub(crate) fn incomplete_slots(&self, slots: impl IntoIterator<Item = Slot>) -> Vec<Slot> {
+        let inner = self.inner.lock().unwrap();
+        slots
+            .into_iter()
+            .filter(|slot| {
+                inner
+                    .slots
+                    .get(slot)
+                    .is_some_and(|entry| entry.completed_timestamp.is_none())
+            })
+            .collect()
+    }
+
+    pub(crate) fn record_completed(&self, slot: Slot, now: u64) {
+        let mut inner = self.inner.lock().unwrap();
+        if let Some(entry) = inner.slots.get_mut(&slot) {
+            entry.completed_timestamp.get_or_insert(now);
+        }
+    }
+
+    /// Finalizes all slots older than the new root.
+    pub(crate) fn set_root(&self, root: Slot) {
+        let finalized: Vec<SlotRepairTimeline> = {
+            let mut inner = self.inner.lock().unwrap();
+            if root <= inner.root {
+                return;
+            }
+            inner.root = root;
+            let slots = inner.slots.split_off(&root);
+            let finalized: Vec<_> = std::mem::replace(&mut inner.slots, slots)
+                .into_iter()
+                .map(|(slot, entry)| make_slot_timeline(slot, &entry))
+                .collect();
+            for timeline in &finalized {
+                if inner.finalized.len() == MAX_FINALIZED_SLOTS {
+                    inner.finalized.pop_front();
+                }
+                inner.finalized.push_back(timeline.clone());
+            }
+            finalized
+        };
+        if let Some(writer) = &self.writer {
+            if let Err(err) = self.write(&mut writer.lock().unwrap(), &finalized) {
+                error!("failed to write repair timeline: {err}");
+            }
+        }
+    }
+
+    fn write(
+        &self,
+        writer: &mut TimelineWriter,
+        timelines: &[SlotRepairTimeline],
+    ) -> io::Result<()> {
+        if writer.full {
+            return Ok(());
+        }
+        for timeline in timelines {
+            let mut line = serde_json::to_vec(timeline)?;
+            line.push(b'\n');
+            let size = line.len() as u64;
+            if writer.num_bytes.saturating_add(size) > self.max_bytes {
+                warn!(
+                    "repair timeline: file reached {} bytes, no longer writing",
+                    self.max_bytes
+                );
+                writer.full = true;
+                break;
+            }
+            writer.writer.write_all(&line)?;
+            writer.num_bytes += size;
+        }
+        writer.writer.flush()
+    }
+
+    /// Returns the timelines of the given slot, or of all retained slots,
+    /// both finalized and in progress, ordered by slot.
+    pub fn get_timelines(&self, slot: Option<Slot>) -> Vec<SlotRepairTimeline> {
+        let inner = self.inner.lock().unwrap();
+        let finalized = inner.finalized.iter().cloned();
+        let in_progress = inner
+            .slots
+            .iter()
+            .map(|(slot, entry)| make_slot_timeline(*slot, entry));
+        finalized
+            .chain(in_progress)
+            .filter(|timeline| slot.map(|slot| timeline.slot == slot).unwrap_or(true))
+            .collect()
+    }
+}
+
+fn make_slot_timeline(slot: Slot, entry: &SlotEntry) -> SlotRepairTimeline {
+    let mut repairs: Vec<_> = entry
+        .repairs
+        .iter()
+        .map(|((kind, peer), peer_entry)| PeerRepairTimeline {
+            kind: *kind,
+            peer: peer.to_string(),
+            num_requests: peer_entry.num_requests,
+            num_responses: peer_entry.num_responses,
+            first_request_timestamp: peer_entry.first_request_timestamp,
+            last_request_timestamp: peer_entry.last_request_timestamp,
+            first_response_timestamp: peer_entry.first_response_timestamp,
+            last_response_timestamp: peer_entry.last_response_timestamp,
+        })
+        .collect();
+    repairs.sort_unstable_by_key(|repair| (repair.first_request_timestamp, repair.kind));
+    SlotRepairTimeline {
+        slot,
+        first_shred_timestamp: entry.first_shred_timestamp,
+        first_shred_repaired: entry.first_shred_repaired,
+        num_turbine_shreds: entry.num_turbine_shreds,
+        num_repaired_shreds: entry.num_repaired_shreds,
+        first_repair_timestamp: repairs
+            .iter()
+            .map(|repair| repair.first_request_timestamp)
+            .min(),
+        completed_timestamp: entry.completed_timestamp,
+        repairs,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, std::io::BufRead, tempfile::TempDir};
+
+    #[test]
+    fn test_repair_timeline() {
+        let ledger_path = TempDir::new().unwrap();
+        let path = ledger_path.path().join("repair-timeline.json");
+        let timeline = RepairTimeline::new(Some(&path), DEFAULT_MAX_REPAIR_TIMELINE_BYTES).unwrap();
+        let peer = Pubkey::new_unique();
+        timeline.record_shreds([(5, None), (5, None), (6, None)], 100);
+        timeline.record_request(RepairRequestKind::Shred, 5, &peer, 1, 110);
+        timeline.record_request(RepairRequestKind::Shred, 5, &peer, 2, 120);
+        timeline.record_request(RepairRequestKind::Orphan, 7, &peer, 3, 130);
+        // Orphan responses are shreds of ancestor slots, but are attributed
+        // to the slot the request was for.
+        timeline.record_shreds([(5, Some(1)), (4, Some(3))], 150);
+        // Unknown nonce.
+        timeline.record_shreds([(5, Some(42))], 160);
+        assert_eq!(timeline.incomplete_slots([4, 5, 6, 8]), vec![4, 5, 6]);
+        timeline.record_completed(5, 170);
+        assert_eq!(timeline.incomplete_slots([4, 5, 6, 8]), vec![4, 6]);
+
+        let timelines = timeline.get_timelines(Some(5));
+        assert_eq!(
+            timelines,
+            vec![SlotRepairTimeline {
+                slot: 5,
+                first_shred_timestamp: Some(100),
+                first_shred_repaired: false,
+                num_turbine_shreds: 2,
+                num_repaired_shreds: 2,
+                first_repair_timestamp: Some(110),
+                completed_timestamp: Some(170),
+                repairs: vec![PeerRepairTimeline {
+                    kind: RepairRequestKind::Shred,
+                    peer: peer.to_string(),
+                    num_requests: 2,
+                    num_responses: 1,
+                    first_request_timestamp: 110,
+                    last_request_timestamp: 120,
+                    first_response_timestamp: Some(150),
+                    last_response_timestamp: Some(150),
+                }],
+            }]
+        );
+        let orphan = &timeline.get_timelines(Some(7))[0];
+        assert_eq!(orphan.first_shred_timestamp, None);
+        assert_eq!(orphan.repairs[0].num_responses, 1);
+        assert!(timeline.get_timelines(Some(4))[0].first_shred_repaired);
+
+        timeline.set_root(6);
+        // Slots older than the root are no longer tracked.
+        timeline.record_shreds([(5, None)], 200);
+        let slots: Vec<_> = timeline
+            .get_timelines(None)
+            .into_iter()
+            .map(|timeline| (timeline.slot, timeline.num_turbine_shreds))
+            .collect();
+        assert_eq!(slots, vec![(4, 0), (5, 2), (6, 1), (7, 0)]);
+        let lines: Vec<SlotRepairTimeline> = io::BufReader::new(File::open(&path).unwrap())
+            .lines()
+            .map(|line| serde_json::from_str(&line.unwrap()).unwrap())
+            .collect();
+        assert_eq!(lines, timeline.get_timelines(None)[..2]);
+    }
+
+    #[test]
+    fn test_repair_timeline_limits() {
+        let ledger_path = TempDir::new().unwrap();
+        let path = ledger_path.path().join("repair-timeline.json");
+        let max_bytes = 1024;
+        let timeline = RepairTimeline::new(Some(&path), max_bytes).unwrap();
+        let peer = Pubkey::new_unique();
+        // Slots beyond the cap are not tracked while the root does not advance,
+        // but the slots already tracked still are.
+        let num_slots = MAX_TRACKED_SLOTS as Slot;
+        timeline.record_shreds((1..=num_slots + 10).map(|slot| (slot, None)), 100);
+        timeline.record_request(RepairRequestKind::Shred, num_slots + 1, &peer, 1, 110);
+        timeline.record_request(RepairRequestKind::Shred, 1, &peer, 2, 120);
+        let timelines = timeline.get_timelines(None);
+        assert_eq!(timelines.len(), MAX_TRACKED_SLOTS);
+        assert_eq!(timelines.last().unwrap().slot, num_slots);
+        assert_eq!(timelines[0].repairs.len(), 1);
+
+        // The file stops growing once it reaches its maximum size.
+        timeline.set_root(num_slots / 2);
+        assert!(std::fs::metadata(&path).unwrap().len() <= max_bytes);
+        let num_lines = io::BufReader::new(File::open(&path).unwrap())
+            .lines()
+            .count();
+        assert!((1..MAX_TRACKED_SLOTS / 2).contains(&num_lines));
+        // New slots are tracked again once the root advanced.
+        timeline.record_shreds([(num_slots + 1, None)], 200);
+        assert_eq!(timeline.get_timelines(Some(num_slots + 1)).len(), 1);
+    }
+}
@@ -4,8 +4,10 @@ use {
         repair::{
             duplicate_repair_status::get_ancestor_hash_repair_sample_size,
             quic_endpoint::{LocalRequest, RemoteRequest},
+            repair_peer_scores::RepairPeerScores,
             repair_response,
             repair_service::{OutstandingShredRepairs, RepairStats, REPAIR_MS},
+            repair_timeline::{RepairRequestKind, RepairTimeline},
             request_response::RequestResponse,
             result::{Error, RepairVerifyError, Result},
         },
@@ -1040,6 +1042,8 @@ impl ServeRepair {
         quic_endpoint_sender: &AsyncSender<LocalRequest>,
         quic_endpoint_response_sender: &Sender<(SocketAddr, Vec<u8>)>,
         repair_protocol: Protocol,
+        repair_timeline: Option<&RepairTimeline>,
+        repair_peer_scores: Option<&RepairPeerScores>,
     ) -> Result<Option<(SocketAddr, Vec<u8>)>> {
         // find a peer that appears to be accepting replication and has the desired slot, as indicated
         // by a valid tvu port location
@@ -1050,6 +1054,12 @@ impl ServeRepair {
                 peers_cache.pop(&slot);
                 let repair_peers = self.repair_peers(repair_validators, slot);
                 let weights = cluster_slots.compute_weights(slot, &repair_peers);
+                let weights = match repair_peer_scores {
+                    None => weights,
+                    Some(repair_peer_scores) => {
+                        repair_peer_scores.adjust_weights(&repair_peers, weights, timestamp())
+                    }
+                };
                 let repair_peers = RepairPeers::new(Instant::now(), &repair_peers, &weights)?;
                 peers_cache.put(slot, repair_peers);
                 peers_cache.get(&slot).unwrap()
@@ -1057,6 +1067,18 @@ impl ServeRepair {
         };
         let peer = repair_peers.sample(&mut rand::thread_rng());
         let nonce = outstanding_requests.add_request(repair_request, timestamp());
+        if let Some(repair_peer_scores) = repair_peer_scores {
+            repair_peer_scores.record_request(&peer.pubkey, nonce, timestamp());
+        }
+        if let Some(repair_timeline) = repair_timeline {
+            repair_timeline.record_request(
+                RepairRequestKind::from(&repair_request),
+                slot,
+                &peer.pubkey,
+                nonce,
+                timestamp(),
+            );
+        }
         let out = self.map_repair_request(
             &repair_request,
             &peer.pubkey,
@@ -1996,6 +2018,8 @@ mod tests {
             &quic_endpoint_sender,
             &quic_endpoint_response_sender,
             Protocol::UDP, // repair_protocol
+            None,          // repair_timeline
+            None,          // repair_peer_scores
         );
         assert_matches!(rv, Err(Error::ClusterInfo(ClusterInfoError::NoPeers)));
 
@@ -2029,6 +2053,8 @@ mod tests {
                 &quic_endpoint_sender,
                 &quic_endpoint_response_sender,
                 Protocol::UDP, // repair_protocol
+                None,          // repair_timeline
+                None,          // repair_peer_scores
             )
             .unwrap()
             .unwrap();
@@ -2069,6 +2095,8 @@ mod tests {
                     &quic_endpoint_sender,
                     &quic_endpoint_response_sender,
                     Protocol::UDP, // repair_protocol
+                    None,          // repair_timeline
+                    None,          // repair_peer_scores
                 )
                 .unwrap()
                 .unwrap();
@@ -2343,6 +2371,8 @@ mod tests {
                     &quic_endpoint_sender,
                     &quic_endpoint_response_sender,
                     Protocol::UDP, // repair_protocol
+                    None,          // repair_timeline
+                    None,          // repair_peer_scores
                 ),
                 Err(Error::ClusterInfo(ClusterInfoError::NoPeers))
             );
@@ -2365,6 +2395,8 @@ mod tests {
                 &quic_endpoint_sender,
                 &quic_endpoint_response_sender,
                 Protocol::UDP, // repair_protocol
+                None,          // repair_timeline
+                None,          // repair_peer_scores
             ),
             Ok(Some(_))
         );
@@ -2391,6 +2423,8 @@ mod tests {
                 &quic_endpoint_sender,
                 &quic_endpoint_response_sender,
                 Protocol::UDP, // repair_protocol
+                None,          // repair_timeline
+                None,          // repair_peer_scores
             ),
             Ok(Some(_))
         );
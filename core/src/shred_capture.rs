@@ -0,0 +1,407 @@
+//! The `shred_capture` module records the shreds accepted by the shred fetch
+//! stage, along with their arrival times, to a file, and replays such a
+//! capture into the TVU of another validator with the original timing. This
+//! allows duplicate block and partition incidents to be reproduced
+//! deterministically.
+//!
+//! A capture file starts with a magic string and a format version, followed
+//! by bincode encoded `CapturedShred` records in arrival order. Capturing
+//! stops once the file reaches its maximum size.
+//!
+//! Replayed shreds are indistinguishable from the shreds received over
+//! turbine and repair, so a capture should only be replayed into a validator
+//! which does not join a cluster.
+
+use {
+    crossbeam_channel::{bounded, Sender, TrySendError},
+    serde::{Deserialize, Serialize},
+    solana_ledger::shred::SIZE_OF_NONCE,
+    solana_perf::packet::{Packet, PacketBatch, PacketFlags, PACKETS_PER_BATCH},
+    solana_sdk::packet::{Meta, PACKET_DATA_SIZE},
+    std::{
+        fs::File,
+        io::{self, BufReader, BufWriter, Read, Write},
+        net::SocketAddr,
+        path::Path,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+        thread::{self, sleep, Builder, JoinHandle},
+        time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+    },
+};
+
+const CAPTURE_MAGIC: [u8; 8] = *b"SHREDCAP";
+const CAPTURE_VERSION: u32 = 1;
+const CAPTURE_HEADER_SIZE: u64 = (CAPTURE_MAGIC.len() + std::mem::size_of::<u32>()) as u64;
+pub const DEFAULT_MAX_CAPTURE_BYTES: u64 = 16 * 1024 * 1024 * 1024;
+// Number of packet batches buffered for the writer thread before captured
+// shreds are dropped.
+const CAPTURE_CHANNEL_CAPACITY: usize = 4096;
+// Replayed shreds arriving within this duration are sent in the same batch.
+const REPLAY_COALESCE_DURATION: Duration = Duration::from_millis(1);
+const REPLAY_MAX_SLEEP: Duration = Duration::from_millis(100);
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct CapturedShred {
+    /// Microseconds since the unix epoch at which the shred was received.
+    pub timestamp_us: u64,
+    pub from: SocketAddr,
+    pub repair: bool,
+    /// Packet payload, including the trailing nonce of repair responses.
+    pub payload: Vec<u8>,
+}
+
+pub type ShredCaptureSender = Sender<Vec<CapturedShred>>;
+
+/// Writes captured shreds to a file, up to a maximum file size.
+pub struct ShredCaptureWriter {
+    writer: BufWriter<File>,
+    num_bytes: u64,
+    max_bytes: u64,
+    /// Set once a shred did not fit, after which nothing more is captured
+    full: bool,
+}
+
+pub struct ShredCaptureReader {
+    reader: BufReader<File>,
+}
+
+pub struct ShredCaptureService {
+    thread_hdl: JoinHandle<()>,
+}
+
+pub struct ShredReplayService {
+    thread_hdl: JoinHandle<()>,
+}
+
+fn invalid_data<E: ToString>(err: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err.to_string())
+}
+
+impl ShredCaptureWriter {
+    pub fn create(path: &Path, max_bytes: u64) -> io::Result<Self> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writer.write_all(&CAPTURE_MAGIC)?;
+        writer.write_all(&CAPTURE_VERSION.to_le_bytes())?;
+        Ok(Self {
+            writer,
+            num_bytes: CAPTURE_HEADER_SIZE,
+            max_bytes,
+            full: false,
+        })
+    }
+
+    pub fn write(&mut self, shred: &CapturedShred) -> io::Result<()> {
+        if self.full {
+            return Ok(());
+        }
+        let size = bincode::serialized_size(shred).map_err(invalid_data)?;
+        if self.num_bytes.saturating_add(size) > self.max_bytes {
+            warn!(
+                "shred capture: capture reached {} bytes, no longer capturing",
+                self.max_bytes
+            );
+            self.full = true;
+            return Ok(());
+        }
+        bincode::serialize_into(&mut self.writer, shred).map_err(invalid_data)?;
+        self.num_bytes += size;
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+impl ShredCaptureReader {
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let mut reader = BufReader::new(File::open(path)?);
+        let mut magic = [0u8; 8];
+        let mut version = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        reader.read_exact(&mut version)?;
+        if magic != CAPTURE_MAGIC {
+            return Err(invalid_data("not a shred capture file"));
+        }
+        let version = u32::from_le_bytes(version);
+        if version != CAPTURE_VERSION {
+            return Err(invalid_data(format!(
+                "unsupported shred capture version {version}"
+            )));
+        }
+        Ok(Self { reader })
+    }
+}
+
+impl Iterator for ShredCaptureReader {
+    type Item = io::Result<CapturedShred>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match bincode::deserialize_from(&mut self.reader) {
+            Ok(shred) => Some(Ok(shred)),
+            // A capture may end with a partially written record if the
+            // validator did not shut down cleanly.
+            Err(err) => match *err {
+                bincode::ErrorKind::Io(err) if err.kind() == io::ErrorKind::UnexpectedEof => None,
+                err => Some(Err(invalid_data(err))),
+            },
+        }
+    }
+}
+
+impl ShredCaptureService {
+    /// Returns the service writing to the capture file, up to `max_bytes`,
+    /// and the sender on which to capture shreds. The service exits once all
+    /// senders are dropped.
+    pub fn new(path: &Path, max_bytes: u64) -> io::Result<(Self, ShredCaptureSender)> {
+        let mut writer = ShredCaptureWriter::create(path, max_bytes)?;
+        let (sender, receiver) = bounded::<Vec<CapturedShred>>(CAPTURE_CHANNEL_CAPACITY);
+        let thread_hdl = Builder::new()
+            .name("solShredCapture".to_string())
+            .spawn(move || {
+                let result = receiver
+                    .iter()
+                    .flatten()
+                    .try_for_each(|shred| writer.write(&shred))
+                    .and_then(|()| writer.flush());
+                if let Err(err) = result {
+                    error!("Failed to write shred capture: {err}");
+                }
+            })
+            .unwrap();
+        Ok((Self { thread_hdl }, sender))
+    }
+
+    pub fn join(self) -> thread::Result<()> {
+        self.thread_hdl.join()
+    }
+}
+
+/// Captures the shreds in the batch which have not been discarded.
+pub(crate) fn capture_packets(sender: &ShredCaptureSender, packet_batch: &PacketBatch) {
+    let timestamp_us = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_micros() as u64;
+    let shreds: Vec<_> = packet_batch
+        .iter()
+        .filter(|packet| !packet.meta().discard())
+        .filter_map(|packet| {
+            Some(CapturedShred {
+                timestamp_us,
+                from: packet.meta().socket_addr(),
+                repair: packet.meta().repair(),
+                payload: packet.data(..)?.to_vec(),
+            })
+        })
+        .collect();
+    if shreds.is_empty() {
+        return;
+    }
+    let num_shreds = shreds.len();
+    match sender.try_send(shreds) {
+        Ok(()) | Err(TrySendError::Disconnected(_)) => (),
+        Err(TrySendError::Full(_)) => {
+            inc_new_counter_error!("shred_capture-dropped", num_shreds);
+        }
+    }
+}
+
+impl ShredReplayService {
+    /// Replays the capture file into the sender, which should be the channel
+    /// from the shred fetch stage to shred sigverify. The replayed shreds are
+    /// mixed with any shreds received from the network.
+    pub fn new(
+        path: &Path,
+        sender: Sender<PacketBatch>,
+        exit: Arc<AtomicBool>,
+    ) -> io::Result<Self> {
+        let reader = ShredCaptureReader::open(path)?;
+        let thread_hdl = Builder::new()
+            .name("solShredReplay".to_string())
+            .spawn(move || match replay_shreds(reader, &sender, &exit) {
+                Ok(num_shreds) => info!("Replayed {num_shreds} captured shreds"),
+                Err(err) => error!("Failed to replay shred capture: {err}"),
+            })
+            .unwrap();
+        Ok(Self { thread_hdl })
+    }
+
+    pub fn join(self) -> thread::Result<()> {
+        self.thread_hdl.join()
+    }
+}
+
+// Repair responses are replayed as turbine shreds, without the nonce, since
+// the replaying validator has no outstanding requests to match them against.
+fn to_packet(shred: &CapturedShred) -> Option<Packet> {
+    let size = if shred.repair {
+        shred.payload.len().checked_sub(SIZE_OF_NONCE)?
+    } else {
+        shred.payload.len()
+    };
+    if size > PACKET_DATA_SIZE {
+        return None;
+    }
+    let mut packet = Packet::default();
+    packet.buffer_mut()[..size].copy_from_slice(&shred.payload[..size]);
+    *packet.meta_mut() = Meta {
+        size,
+        addr: shred.from.ip(),
+        port: shred.from.port(),
+        flags: PacketFlags::empty(),
+    };
+    Some(packet)
+}
+
+// Returns false if exit was signaled before the deadline.
+fn sleep_until(deadline: Instant, exit: &AtomicBool) -> bool {
+    loop {
+        if exit.load(Ordering::Relaxed) {
+            return false;
+        }
+        match deadline.checked_duration_since(Instant::now()) {
+            None => return true,
+            Some(delay) => sleep(delay.min(REPLAY_MAX_SLEEP)),
+        }
+    }
+}
+
+/// Sends the captured shreds to the sender, preserving the time offsets
+/// between their arrivals. Returns the number of shreds sent.
+pub fn replay_shreds<I>(
+    shreds: I,
+    sender: &Sender<PacketBatch>,
+    exit: &AtomicBool,
+) -> io::Result<usize>
+where
+    I: IntoIterator<Item = io::Result<CapturedShred>>,
+{
+    let start = Instant::now();
+    let mut first_timestamp_us = None;
+    let mut batch_offset = Duration::ZERO;
+    let mut packets = Vec::with_capacity(PACKETS_PER_BATCH);
+    let mut num_shreds = 0;
+    let mut send = |packets: &mut Vec<Packet>| {
+        num_shreds += packets.len();
+        let packets = std::mem::replace(packets, Vec::with_capacity(PACKETS_PER_BATCH));
+        sender
+            .send(PacketBatch::new(packets))
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "shred receiver disconnected"))
+    };
+    for shred in shreds {
+        let shred = shred?;
+        let first_timestamp_us = *first_timestamp_us.get_or_insert(shred.timestamp_us);
+        let offset = Duration::from_micros(shred.timestamp_us.saturating_sub(first_timestamp_us));
+        if !packets.is_empty()
+            && (packets.len() >= PACKETS_PER_BATCH
+                || offset >= batch_offset + REPLAY_COALESCE_DURATION)
+        {
+            send(&mut packets)?;
+        }
+        if packets.is_empty() {
+            batch_offset = offset;
+            if !sleep_until(start + offset, exit) {
+                return Ok(num_shreds);
+            }
+        }
+        packets.extend(to_packet(&shred));
+    }
+    if !packets.is_empty() {
+        send(&mut packets)?;
+    }
+    Ok(num_shreds)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crossbeam_channel::unbounded,
+        std::net::{IpAddr, Ipv4Addr},
+        tempfile::TempDir,
+    };
+
+    fn new_captured_shred(timestamp_us: u64, repair: bool, len: usize) -> CapturedShred {
+        CapturedShred {
+            timestamp_us,
+            from: SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 8001),
+            repair,
+            payload: new_payload(len),
+        }
+    }
+
+    fn new_payload(len: usize) -> Vec<u8> {
+        (0..len).map(|k| k as u8).collect()
+    }
+
+    #[test]
+    fn test_shred_capture_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("shreds.capture");
+        let shreds = vec![
+            new_captured_shred(1_000, false, 1_203),
+            new_captured_shred(1_500, true, 1_207),
+            new_captured_shred(9_000, false, 1_203),
+        ];
+        let (service, sender) = ShredCaptureService::new(&path, DEFAULT_MAX_CAPTURE_BYTES).unwrap();
+        sender.send(shreds[..2].to_vec()).unwrap();
+        sender.send(shreds[2..].to_vec()).unwrap();
+        drop(sender);
+        service.join().unwrap();
+        let captured: Vec<_> = ShredCaptureReader::open(&path)
+            .unwrap()
+            .collect::<io::Result<_>>()
+            .unwrap();
+        assert_eq!(captured, shreds);
+
+        std::fs::write(&path, b"not a capture").unwrap();
+        assert!(ShredCaptureReader::open(&path).is_err());
+    }
+
+    #[test]
+    fn test_shred_capture_max_bytes() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("shreds.capture");
+        let max_bytes = 4_096;
+        let (service, sender) = ShredCaptureService::new(&path, max_bytes).unwrap();
+        for k in 0..20 {
+            sender
+                .send(vec![new_captured_shred(k * 1_000, false, 1_203)])
+                .unwrap();
+        }
+        drop(sender);
+        service.join().unwrap();
+
+        assert!(std::fs::metadata(&path).unwrap().len() <= max_bytes);
+        let num_shreds = ShredCaptureReader::open(&path).unwrap().count();
+        assert_eq!(num_shreds, 3);
+    }
+
+    #[test]
+    fn test_replay_shreds() {
+        let shreds = vec![
+            new_captured_shred(1_000_000, false, 1_203),
+            new_captured_shred(1_000_200, true, 1_207),
+            new_captured_shred(1_050_000, false, 1_203),
+        ];
+        let (sender, receiver) = unbounded();
+        let exit = AtomicBool::new(false);
+        let start = Instant::now();
+        let num_shreds = replay_shreds(shreds.into_iter().map(Ok), &sender, &exit).unwrap();
+        assert_eq!(num_shreds, 3);
+        assert!(start.elapsed() >= Duration::from_millis(50));
+        let batches: Vec<_> = receiver.try_iter().collect();
+        assert_eq!(batches.len(), 2);
+        assert_eq!(batches[0].len(), 2);
+        assert_eq!(batches[1].len(), 1);
+        // The repair nonce is stripped.
+        let packet = &batches[0][1];
+        assert_eq!(packet.meta().size, 1_207 - SIZE_OF_NONCE);
+        assert!(!packet.meta().repair());
+        assert_eq!(packet.data(..).unwrap(), &new_payload(1_203)[..]);
+    }
+}
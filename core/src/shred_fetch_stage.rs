@@ -1,7 +1,10 @@
 //! The `shred_fetch_stage` pulls shreds from UDP sockets and sends it to a channel.
 
 use {
-    crate::repair::serve_repair::ServeRepair,
+    crate::{
+        repair::serve_repair::ServeRepair,
+        shred_capture::{self, ShredCaptureSender},
+    },
     bytes::Bytes,
     crossbeam_channel::{unbounded, Receiver, RecvTimeoutError, Sender},
     itertools::Itertools,
@@ -45,6 +48,7 @@ impl ShredFetchStage {
         flags: PacketFlags,
         repair_context: Option<(&UdpSocket, &ClusterInfo)>,
         turbine_disabled: Arc<AtomicBool>,
+        shred_capture_sender: Option<ShredCaptureSender>,
     ) {
         const STATS_SUBMIT_CADENCE: Duration = Duration::from_secs(1);
         let mut last_updated = Instant::now();
@@ -138,6 +142,9 @@ impl ShredFetchStage {
                     packet.meta_mut().flags.insert(flags);
                 }
             }
+            if let Some(shred_capture_sender) = &shred_capture_sender {
+                shred_capture::capture_packets(shred_capture_sender, &packet_batch);
+            }
             stats.maybe_submit(name, STATS_SUBMIT_CADENCE);
             if sendr.send(packet_batch).is_err() {
                 break;
@@ -159,6 +166,7 @@ impl ShredFetchStage {
         flags: PacketFlags,
         repair_context: Option<(Arc<UdpSocket>, Arc<ClusterInfo>)>,
         turbine_disabled: Arc<AtomicBool>,
+        shred_capture_sender: Option<ShredCaptureSender>,
     ) -> (Vec<JoinHandle<()>>, JoinHandle<()>) {
         let (packet_sender, packet_receiver) = unbounded();
         let streamers = sockets
@@ -193,6 +201,7 @@ impl ShredFetchStage {
                     flags,
                     repair_context,
                     turbine_disabled,
+                    shred_capture_sender,
                 )
             })
             .unwrap();
@@ -210,6 +219,7 @@ impl ShredFetchStage {
         bank_forks: Arc<RwLock<BankForks>>,
         cluster_info: Arc<ClusterInfo>,
         turbine_disabled: Arc<AtomicBool>,
+        shred_capture_sender: Option<ShredCaptureSender>,
         exit: Arc<AtomicBool>,
     ) -> Self {
         let recycler = PacketBatchRecycler::warmed(100, 1024);
@@ -227,6 +237,7 @@ impl ShredFetchStage {
             PacketFlags::empty(),
             None, // repair_context
             turbine_disabled.clone(),
+            shred_capture_sender.clone(),
         );
 
         let (repair_receiver, repair_handler) = Self::packet_modifier(
@@ -242,6 +253,7 @@ impl ShredFetchStage {
             PacketFlags::REPAIR,
             Some((repair_socket, cluster_info)),
             turbine_disabled.clone(),
+            shred_capture_sender.clone(),
         );
 
         tvu_threads.extend(repair_receiver);
@@ -255,6 +267,7 @@ impl ShredFetchStage {
             let exit = exit.clone();
             let sender = sender.clone();
             let turbine_disabled = turbine_disabled.clone();
+            let shred_capture_sender = shred_capture_sender.clone();
             tvu_threads.extend([
                 Builder::new()
                     .name("solTvuRecvRpr".to_string())
@@ -279,6 +292,7 @@ impl ShredFetchStage {
                             PacketFlags::REPAIR,
                             None, // repair_context; no ping packets!
                             turbine_disabled,
+                            shred_capture_sender,
                         )
                     })
                     .unwrap(),
@@ -310,6 +324,7 @@ impl ShredFetchStage {
                         PacketFlags::empty(),
                         None, // repair_context
                         turbine_disabled,
+                        shred_capture_sender,
                     )
                 })
                 .unwrap(),
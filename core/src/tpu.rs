@@ -33,9 +33,12 @@ use {
     solana_runtime::{bank_forks::BankForks, prioritization_fee_cache::PrioritizationFeeCache},
     solana_sdk::{clock::Slot, pubkey::Pubkey, quic::NotifyKeyUpdate, signature::Keypair},
     solana_streamer::{
-        nonblocking::quic::DEFAULT_WAIT_FOR_CHUNK_TIMEOUT,
-        quic::{spawn_server, SpawnServerResult, MAX_STAKED_CONNECTIONS, MAX_UNSTAKED_CONNECTIONS},
-        streamer::StakedNodes,
+        nonblocking::quic::{DEFAULT_MAX_STREAMS_PER_MS, DEFAULT_WAIT_FOR_CHUNK_TIMEOUT},
+        quic::{
+            spawn_server, SpawnServerResult, StreamStats, MAX_STAKED_CONNECTIONS,
+            MAX_UNSTAKED_CONNECTIONS,
+        },
+        streamer::{PacketBatchSender, StakedNodes},
     },
     solana_turbine::broadcast_stage::{BroadcastStage, BroadcastStageType},
     solana_vote::vote_sender_types::{ReplayVoteReceiver, ReplayVoteSender},
@@ -70,6 +73,9 @@ pub struct Tpu {
     broadcast_stage: BroadcastStage,
     tpu_quic_t: thread::JoinHandle<()>,
     tpu_forwards_quic_t: thread::JoinHandle<()>,
+    tpu_quic_stats: Arc<StreamStats>,
+    tpu_forwards_quic_stats: Arc<StreamStats>,
+    packet_sender: PacketBatchSender,
     tpu_entry_notifier: Option<TpuEntryNotifier>,
     staked_nodes_updater_service: StakedNodesUpdaterService,
     tracer_thread_hdl: TracerThread,
@@ -98,6 +104,7 @@ impl Tpu {
         replay_vote_sender: ReplayVoteSender,
         bank_notification_sender: Option<BankNotificationSender>,
         tpu_coalesce: Duration,
+        tpu_max_forwarded_streams_per_ms: u64,
         duplicate_confirmed_slot_sender: DuplicateConfirmedSlotsSender,
         connection_cache: &Arc<ConnectionCache>,
         turbine_quic_endpoint_sender: AsyncSender<(SocketAddr, Bytes)>,
@@ -152,17 +159,19 @@ impl Tpu {
             endpoint: _,
             thread: tpu_quic_t,
             key_updater,
+            stats: tpu_quic_stats,
         } = spawn_server(
             "solQuicTpu",
             "quic_streamer_tpu",
             transactions_quic_sockets,
             keypair,
-            packet_sender,
+            packet_sender.clone(),
             exit.clone(),
             MAX_QUIC_CONNECTIONS_PER_PEER,
             staked_nodes.clone(),
             MAX_STAKED_CONNECTIONS,
             MAX_UNSTAKED_CONNECTIONS,
+            DEFAULT_MAX_STREAMS_PER_MS,
             DEFAULT_WAIT_FOR_CHUNK_TIMEOUT,
             tpu_coalesce,
         )
@@ -172,6 +181,7 @@ impl Tpu {
             endpoint: _,
             thread: tpu_forwards_quic_t,
             key_updater: forwards_key_updater,
+            stats: tpu_forwards_quic_stats,
         } = spawn_server(
             "solQuicTpuFwd",
             "quic_streamer_tpu_forwards",
@@ -183,6 +193,10 @@ impl Tpu {
             staked_nodes.clone(),
             MAX_STAKED_CONNECTIONS.saturating_add(MAX_UNSTAKED_CONNECTIONS),
             0, // Prevent unstaked nodes from forwarding transactions
+            // Forwarded transactions get a separate stake weighted stream
+            // budget so that forwarders cannot crowd out the transactions
+            // sent directly to the leader.
+            tpu_max_forwarded_streams_per_ms,
             DEFAULT_WAIT_FOR_CHUNK_TIMEOUT,
             tpu_coalesce,
         )
@@ -274,6 +288,9 @@ impl Tpu {
                 broadcast_stage,
                 tpu_quic_t,
                 tpu_forwards_quic_t,
+                tpu_quic_stats,
+                tpu_forwards_quic_stats,
+                packet_sender,
                 tpu_entry_notifier,
                 staked_nodes_updater_service,
                 tracer_thread_hdl,
@@ -282,7 +299,25 @@ impl Tpu {
         )
     }
 
+    pub fn tpu_quic_stats(&self) -> &Arc<StreamStats> {
+        &self.tpu_quic_stats
+    }
+
+    pub fn tpu_forwards_quic_stats(&self) -> &Arc<StreamStats> {
+        &self.tpu_forwards_quic_stats
+    }
+
+    /// Returns the sender of the packets received by the TPU, so that
+    /// transactions can be submitted in-process, e.g. over an in-memory
+    /// transport in tests. The sender must be dropped before the TPU is
+    /// joined.
+    pub fn packet_sender(&self) -> &PacketBatchSender {
+        &self.packet_sender
+    }
+
     pub fn join(self) -> thread::Result<()> {
+        // Disconnect the sigverify stage from the packets channel.
+        drop(self.packet_sender);
         let results = vec![
             self.fetch_stage.join(),
             self.sigverify_stage.join(),
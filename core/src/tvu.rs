@@ -16,10 +16,13 @@ use {
         drop_bank_service::DropBankService,
         repair::{
             quic_endpoint::LocalRequest,
+            repair_peer_scores::RepairPeerScores,
             repair_service::{OutstandingShredRepairs, RepairInfo},
+            repair_timeline::RepairTimeline,
         },
         replay_stage::{ReplayStage, ReplayStageConfig},
         rewards_recorder_service::RewardsRecorderSender,
+        shred_capture::{ShredCaptureService, ShredReplayService},
         shred_fetch_stage::ShredFetchStage,
         voting_service::VotingService,
         warm_quic_cache_service::WarmQuicCacheService,
@@ -53,6 +56,7 @@ use {
     std::{
         collections::HashSet,
         net::{SocketAddr, UdpSocket},
+        path::PathBuf,
         sync::{atomic::AtomicBool, Arc, RwLock},
         thread::{self, JoinHandle},
     },
@@ -72,6 +76,8 @@ pub struct Tvu {
     warm_quic_cache_service: Option<WarmQuicCacheService>,
     drop_bank_service: DropBankService,
     duplicate_shred_listener: DuplicateShredListener,
+    shred_capture_service: Option<ShredCaptureService>,
+    shred_replay_service: Option<ShredReplayService>,
 }
 
 pub struct TvuSockets {
@@ -91,6 +97,15 @@ pub struct TvuConfig {
     pub repair_whitelist: Arc<RwLock<HashSet<Pubkey>>>,
     pub wait_for_vote_to_start_leader: bool,
     pub replay_slots_concurrently: bool,
+    // Bias repair peer selection by the measured latency and success rate of
+    // repair peers
+    pub repair_peer_scoring: bool,
+    // Record the shreds accepted by the shred fetch stage to this file
+    pub shred_capture_path: Option<PathBuf>,
+    // Stop capturing shreds once the capture file reaches this size
+    pub shred_capture_max_bytes: u64,
+    // Replay shreds captured to this file, with their original timing
+    pub shred_replay_path: Option<PathBuf>,
 }
 
 impl Tvu {
@@ -144,6 +159,7 @@ impl Tvu {
         outstanding_repair_requests: Arc<RwLock<OutstandingShredRepairs>>,
         cluster_slots: Arc<ClusterSlots>,
         wen_restart_repair_slots: Option<Arc<RwLock<Vec<Slot>>>>,
+        repair_timeline: Option<Arc<RepairTimeline>>,
     ) -> Result<Self, String> {
         let TvuSockets {
             repair: repair_socket,
@@ -159,6 +175,25 @@ impl Tvu {
         let fetch_sockets: Vec<Arc<UdpSocket>> = fetch_sockets.into_iter().map(Arc::new).collect();
         let (repair_quic_endpoint_response_sender, repair_quic_endpoint_response_receiver) =
             unbounded();
+        let (shred_capture_service, shred_capture_sender) = match &tvu_config.shred_capture_path {
+            None => (None, None),
+            Some(path) => {
+                let (service, sender) =
+                    ShredCaptureService::new(path, tvu_config.shred_capture_max_bytes).map_err(
+                        |err| format!("Failed to create shred capture {}: {err}", path.display()),
+                    )?;
+                (Some(service), Some(sender))
+            }
+        };
+        let shred_replay_service = tvu_config
+            .shred_replay_path
+            .as_deref()
+            .map(|path| {
+                ShredReplayService::new(path, fetch_sender.clone(), exit.clone()).map_err(|err| {
+                    format!("Failed to open shred capture {}: {err}", path.display())
+                })
+            })
+            .transpose()?;
         let fetch_stage = ShredFetchStage::new(
             fetch_sockets,
             turbine_quic_endpoint_receiver,
@@ -169,6 +204,7 @@ impl Tvu {
             bank_forks.clone(),
             cluster_info.clone(),
             turbine_disabled,
+            shred_capture_sender,
             exit.clone(),
         );
 
@@ -216,6 +252,10 @@ impl Tvu {
                 cluster_info: cluster_info.clone(),
                 cluster_slots: cluster_slots.clone(),
                 wen_restart_repair_slots,
+                repair_timeline,
+                repair_peer_scores: tvu_config
+                    .repair_peer_scoring
+                    .then(|| Arc::new(RepairPeerScores::default())),
             };
             WindowService::new(
                 blockstore.clone(),
@@ -349,6 +389,8 @@ impl Tvu {
             warm_quic_cache_service,
             drop_bank_service,
             duplicate_shred_listener,
+            shred_capture_service,
+            shred_replay_service,
         })
     }
 
@@ -369,6 +411,12 @@ impl Tvu {
         }
         self.drop_bank_service.join()?;
         self.duplicate_shred_listener.join()?;
+        if let Some(shred_capture_service) = self.shred_capture_service {
+            shred_capture_service.join()?;
+        }
+        if let Some(shred_replay_service) = self.shred_replay_service {
+            shred_replay_service.join()?;
+        }
         Ok(())
     }
 }
@@ -502,6 +550,7 @@ pub mod tests {
             outstanding_repair_requests,
             cluster_slots,
             None,
+            None,
         )
         .expect("assume success");
         exit.store(true, Ordering::Relaxed);
@@ -15,9 +15,15 @@ use {
             ExternalRootSource, Tower,
         },
         poh_timing_report_service::PohTimingReportService,
-        repair::{self, serve_repair::ServeRepair, serve_repair_service::ServeRepairService},
+        repair::{
+            self,
+            repair_timeline::{RepairTimeline, DEFAULT_MAX_REPAIR_TIMELINE_BYTES},
+            serve_repair::ServeRepair,
+            serve_repair_service::ServeRepairService,
+        },
         rewards_recorder_service::{RewardsRecorderSender, RewardsRecorderService},
         sample_performance_service::SamplePerformanceService,
+        shred_capture::DEFAULT_MAX_CAPTURE_BYTES,
         sigverify,
         snapshot_packager_service::SnapshotPackagerService,
         stats_reporter_service::StatsReporterService,
@@ -48,6 +54,7 @@ use {
             DEFAULT_CONTACT_SAVE_INTERVAL_MILLIS,
         },
         crds_gossip_pull::CRDS_GOSSIP_PULL_CRDS_TIMEOUT_MS,
+        gossip_recorder::{GossipRecorder, DEFAULT_MAX_RECORDING_BYTES},
         gossip_service::GossipService,
         legacy_contact_info::LegacyContactInfo as ContactInfo,
     },
@@ -115,7 +122,11 @@ use {
         timing::timestamp,
     },
     solana_send_transaction_service::send_transaction_service,
-    solana_streamer::{socket::SocketAddrSpace, streamer::StakedNodes},
+    solana_streamer::{
+        nonblocking::quic::DEFAULT_MAX_STREAMS_PER_MS,
+        socket::SocketAddrSpace,
+        streamer::{PacketBatchSender, StakedNodes},
+    },
     solana_turbine::{self, broadcast_stage::BroadcastStageType},
     solana_unified_scheduler_pool::DefaultSchedulerPool,
     solana_vote_program::vote_state,
@@ -226,6 +237,17 @@ pub struct ValidatorConfig {
     pub repair_validators: Option<HashSet<Pubkey>>, // None = repair from all
     pub repair_whitelist: Arc<RwLock<HashSet<Pubkey>>>, // Empty = repair with all
     pub gossip_validators: Option<HashSet<Pubkey>>, // None = gossip with all
+    pub gossip_record_path: Option<PathBuf>,
+    pub gossip_record_max_bytes: u64,
+    pub enable_repair_timeline: bool,
+    pub repair_timeline_path: Option<PathBuf>,
+    pub repair_timeline_max_bytes: u64,
+    pub repair_peer_scoring: bool,
+    pub shred_capture_path: Option<PathBuf>,
+    pub shred_capture_max_bytes: u64,
+    /// Only allowed without cluster entrypoints, since the replayed shreds
+    /// would be mixed with the shreds received from the cluster
+    pub shred_replay_path: Option<PathBuf>,
     pub accounts_hash_interval_slots: u64,
     pub max_genesis_archive_unpacked_size: u64,
     pub wal_recovery_mode: Option<BlockstoreRecoveryMode>,
@@ -253,6 +275,8 @@ pub struct ValidatorConfig {
     pub accounts_db_skip_shrink: bool,
     pub accounts_db_force_initial_clean: bool,
     pub tpu_coalesce: Duration,
+    /// Stake weighted stream budget of the TPU forwards QUIC endpoint
+    pub tpu_max_forwarded_streams_per_ms: u64,
     pub staked_nodes_overrides: Arc<RwLock<HashMap<Pubkey, u64>>>,
     pub validator_exit: Arc<RwLock<Exit>>,
     pub no_wait_for_vote_to_start_leader: bool,
@@ -296,6 +320,15 @@ impl Default for ValidatorConfig {
             repair_validators: None,
             repair_whitelist: Arc::new(RwLock::new(HashSet::default())),
             gossip_validators: None,
+            gossip_record_path: None,
+            gossip_record_max_bytes: DEFAULT_MAX_RECORDING_BYTES,
+            enable_repair_timeline: false,
+            repair_timeline_path: None,
+            repair_timeline_max_bytes: DEFAULT_MAX_REPAIR_TIMELINE_BYTES,
+            repair_peer_scoring: false,
+            shred_capture_path: None,
+            shred_capture_max_bytes: DEFAULT_MAX_CAPTURE_BYTES,
+            shred_replay_path: None,
             accounts_hash_interval_slots: std::u64::MAX,
             max_genesis_archive_unpacked_size: MAX_GENESIS_ARCHIVE_UNPACKED_SIZE,
             wal_recovery_mode: None,
@@ -320,6 +353,7 @@ impl Default for ValidatorConfig {
             accounts_db_skip_shrink: false,
             accounts_db_force_initial_clean: false,
             tpu_coalesce: DEFAULT_TPU_COALESCE,
+            tpu_max_forwarded_streams_per_ms: DEFAULT_MAX_STREAMS_PER_MS,
             staked_nodes_overrides: Arc::new(RwLock::new(HashMap::new())),
             validator_exit: Arc::new(RwLock::new(Exit::default())),
             no_wait_for_vote_to_start_leader: true,
@@ -512,6 +546,16 @@ impl Validator {
                 .map_err(|err| format!("Failed to access network stats: {err:?}"))?;
         }
 
+        if let Some(shred_replay_path) = &config.shred_replay_path {
+            if !cluster_entrypoints.is_empty() {
+                return Err(format!(
+                    "Refusing to replay shred capture {} while joining a cluster",
+                    shred_replay_path.display()
+                ));
+            }
+            warn!("replaying shred capture {}", shred_replay_path.display());
+        }
+
         let mut bank_notification_senders = Vec::new();
 
         let exit = Arc::new(AtomicBool::new(false));
@@ -1081,6 +1125,20 @@ impl Validator {
         let stats_reporter_service =
             StatsReporterService::new(stats_reporter_receiver, exit.clone());
 
+        let gossip_recorder = config
+            .gossip_record_path
+            .as_ref()
+            .map(|path| {
+                GossipRecorder::new(path, &cluster_info, config.gossip_record_max_bytes)
+                    .map(Arc::new)
+                    .map_err(|err| {
+                        format!(
+                            "Failed to create gossip recording {}: {err}",
+                            path.display()
+                        )
+                    })
+            })
+            .transpose()?;
         let gossip_service = GossipService::new(
             &cluster_info,
             Some(bank_forks.clone()),
@@ -1088,6 +1146,7 @@ impl Validator {
             config.gossip_validators.clone(),
             should_check_duplicate_instance,
             Some(stats_reporter_sender.clone()),
+            gossip_recorder,
             exit.clone(),
         );
         let serve_repair = ServeRepair::new(
@@ -1265,6 +1324,21 @@ impl Validator {
             Arc::<RwLock<repair::repair_service::OutstandingShredRepairs>>::default();
         let cluster_slots =
             Arc::new(crate::cluster_slots_service::cluster_slots::ClusterSlots::default());
+        let repair_timeline =
+            if config.enable_repair_timeline || config.repair_timeline_path.is_some() {
+                // Only opening the file may fail.
+                let path = config.repair_timeline_path.as_deref();
+                let max_bytes = config.repair_timeline_max_bytes;
+                let repair_timeline = RepairTimeline::new(path, max_bytes).map_err(|err| {
+                    format!(
+                        "Failed to open repair timeline {}: {err}",
+                        path.unwrap().display()
+                    )
+                })?;
+                Some(Arc::new(repair_timeline))
+            } else {
+                None
+            };
 
         let tvu = Tvu::new(
             vote_account,
@@ -1306,6 +1380,10 @@ impl Validator {
                 repair_whitelist: config.repair_whitelist.clone(),
                 wait_for_vote_to_start_leader,
                 replay_slots_concurrently: config.replay_slots_concurrently,
+                repair_peer_scoring: config.repair_peer_scoring,
+                shred_capture_path: config.shred_capture_path.clone(),
+                shred_capture_max_bytes: config.shred_capture_max_bytes,
+                shred_replay_path: config.shred_replay_path.clone(),
             },
             &max_slots,
             block_metadata_notifier,
@@ -1321,6 +1399,7 @@ impl Validator {
             outstanding_repair_requests.clone(),
             cluster_slots.clone(),
             wen_restart_repair_slots.clone(),
+            repair_timeline.clone(),
         )?;
 
         if in_wen_restart {
@@ -1370,6 +1449,7 @@ impl Validator {
             replay_vote_sender,
             bank_notification_sender.map(|sender| sender.sender),
             config.tpu_coalesce,
+            config.tpu_max_forwarded_streams_per_ms,
             duplicate_confirmed_slot_sender,
             &connection_cache,
             turbine_quic_endpoint_sender,
@@ -1399,6 +1479,7 @@ impl Validator {
 
         *admin_rpc_service_post_init.write().unwrap() = Some(AdminRpcRequestMetadataPostInit {
             bank_forks: bank_forks.clone(),
+            blockstore: blockstore.clone(),
             cluster_info: cluster_info.clone(),
             vote_account: *vote_account,
             repair_whitelist: config.repair_whitelist.clone(),
@@ -1406,6 +1487,10 @@ impl Validator {
             repair_socket: Arc::new(node.sockets.repair),
             outstanding_repair_requests,
             cluster_slots,
+            tpu_quic_stats: tpu.tpu_quic_stats().clone(),
+            tpu_forwards_quic_stats: tpu.tpu_forwards_quic_stats().clone(),
+            repair_timeline,
+            blockstore_compactions: Arc::default(),
         });
 
         Ok(Self {
@@ -1460,6 +1545,12 @@ impl Validator {
         self.join();
     }
 
+    /// Returns a sender submitting packets directly to the TPU, bypassing
+    /// its sockets. Clones must be dropped before the validator is joined.
+    pub fn tpu_packet_sender(&self) -> PacketBatchSender {
+        self.tpu.packet_sender().clone()
+    }
+
     fn print_node_info(node: &Node) {
         info!("{:?}", node.info);
         info!(
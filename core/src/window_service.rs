@@ -9,11 +9,13 @@ use {
         repair::{
             ancestor_hashes_service::AncestorHashesReplayUpdateReceiver,
             quic_endpoint::LocalRequest,
+            repair_peer_scores::RepairPeerScores,
             repair_response,
             repair_service::{
                 DumpedSlotsReceiver, OutstandingShredRepairs, PopularPrunedForksSender, RepairInfo,
                 RepairService,
             },
+            repair_timeline::RepairTimeline,
         },
         result::{Error, Result},
     },
@@ -33,6 +35,7 @@ use {
     solana_sdk::{
         clock::{Slot, DEFAULT_MS_PER_SLOT},
         feature_set,
+        timing::timestamp,
     },
     solana_turbine::cluster_nodes,
     std::{
@@ -291,6 +294,8 @@ fn run_insert<F>(
     outstanding_requests: &RwLock<OutstandingShredRepairs>,
     reed_solomon_cache: &ReedSolomonCache,
     accept_repairs_only: bool,
+    repair_timeline: Option<&RepairTimeline>,
+    repair_peer_scores: Option<&RepairPeerScores>,
 ) -> Result<()>
 where
     F: Fn(PossibleDuplicateShred),
@@ -350,6 +355,26 @@ where
     prune_shreds_elapsed.stop();
     ws_metrics.prune_shreds_elapsed_us += prune_shreds_elapsed.as_us();
 
+    if let Some(repair_peer_scores) = repair_peer_scores {
+        let nonces = repair_infos
+            .iter()
+            .flatten()
+            .map(|repair_info| repair_info.nonce);
+        repair_peer_scores.record_responses(nonces, timestamp());
+    }
+
+    let timeline_slots = repair_timeline.map(|repair_timeline| {
+        let received = shreds
+            .iter()
+            .zip(&repair_infos)
+            .map(|(shred, repair_info)| {
+                let nonce = repair_info.as_ref().map(|repair_info| repair_info.nonce);
+                (shred.slot(), nonce)
+            });
+        repair_timeline.record_shreds(received, timestamp());
+        shreds.iter().map(Shred::slot).collect::<HashSet<_>>()
+    });
+
     let completed_data_sets = blockstore.insert_shreds_handle_duplicate(
         shreds,
         repairs,
@@ -361,6 +386,15 @@ where
         metrics,
     )?;
 
+    if let (Some(repair_timeline), Some(slots)) = (repair_timeline, timeline_slots) {
+        let now = timestamp();
+        for slot in repair_timeline.incomplete_slots(slots) {
+            if matches!(blockstore.meta(slot), Ok(Some(meta)) if meta.is_full()) {
+                repair_timeline.record_completed(slot, now);
+            }
+        }
+    }
+
     completed_data_sets_sender.try_send(completed_data_sets)?;
     Ok(())
 }
@@ -402,6 +436,8 @@ impl WindowService {
         // In wen_restart, we discard all shreds from Turbine and keep only those from repair to
         // avoid new shreds make validator OOM before wen_restart is over.
         let accept_repairs_only = repair_info.wen_restart_repair_slots.is_some();
+        let repair_timeline = repair_info.repair_timeline.clone();
+        let repair_peer_scores = repair_info.repair_peer_scores.clone();
 
         let repair_service = RepairService::new(
             blockstore.clone(),
@@ -439,6 +475,8 @@ impl WindowService {
             retransmit_sender,
             outstanding_repair_requests,
             accept_repairs_only,
+            repair_timeline,
+            repair_peer_scores,
         );
 
         WindowService {
@@ -479,6 +517,7 @@ impl WindowService {
             .unwrap()
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn start_window_insert_thread(
         exit: Arc<AtomicBool>,
         blockstore: Arc<Blockstore>,
@@ -489,6 +528,8 @@ impl WindowService {
         retransmit_sender: Sender<Vec<ShredPayload>>,
         outstanding_requests: Arc<RwLock<OutstandingShredRepairs>>,
         accept_repairs_only: bool,
+        repair_timeline: Option<Arc<RepairTimeline>>,
+        repair_peer_scores: Option<Arc<RepairPeerScores>>,
     ) -> JoinHandle<()> {
         let handle_error = || {
             inc_new_counter_error!("solana-window-insert-error", 1, 1);
@@ -522,6 +563,8 @@ impl WindowService {
                         &outstanding_requests,
                         &reed_solomon_cache,
                         accept_repairs_only,
+                        repair_timeline.as_deref(),
+                        repair_peer_scores.as_deref(),
                     ) {
                         ws_metrics.record_error(&e);
                         if Self::should_exit_on_error(e, &handle_error) {
@@ -810,6 +810,9 @@ fn main() {
             TpuClientWrapper::Udp(udp_client) => {
                 run_dos(&nodes, 0, Some(Arc::new(udp_client)), cmd_params);
             }
+            TpuClientWrapper::InMemory(in_memory_client) => {
+                run_dos(&nodes, 0, Some(Arc::new(in_memory_client)), cmd_params);
+            }
         };
     }
 }
@@ -0,0 +1,527 @@
+//! Replays recorded gossip traffic into a cluster of in-process gossip nodes
+//! connected by an in-memory transport, and reports how the replayed values
+//! propagate through the cluster.
+//!
+//! Values received by the recording node are re-signed by the simulated nodes,
+//! see [`replay_value`], so each recorded origin is mapped to one of the
+//! simulated nodes which then pushes its values. Replayed values are paced and
+//! stamped by a clock following the recorded offsets, so that which values are
+//! replayed, and their wallclocks, only depend on the recording.
+
+use {
+    crossbeam_channel::{unbounded, RecvTimeoutError},
+    log::*,
+    solana_gossip::{
+        cluster_info::ClusterInfo,
+        contact_info::ContactInfo,
+        crds_value::{CrdsValue, CrdsValueLabel},
+        gossip_recorder::{
+            decode_message, replay_value, replayed_label, Direction, GossipRecording, MessageKind,
+        },
+        gossip_service::GossipService,
+        legacy_contact_info::LegacyContactInfo,
+    },
+    solana_perf::packet::PacketBatch,
+    solana_sdk::{
+        pubkey::Pubkey,
+        signature::{Keypair, Signer},
+        timing::timestamp,
+    },
+    solana_streamer::{
+        socket::SocketAddrSpace,
+        streamer::{PacketBatchReceiver, PacketBatchSender},
+    },
+    std::{
+        collections::{BTreeMap, HashMap, HashSet},
+        fmt,
+        net::{Ipv4Addr, SocketAddr},
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc, Mutex,
+        },
+        thread::{self, sleep, Builder, JoinHandle},
+        time::{Duration, Instant},
+    },
+};
+
+const GOSSIP_PORT: u16 = 8000;
+
+#[derive(Clone, Debug)]
+pub struct SimulationConfig {
+    pub num_nodes: usize,
+    /// Maximum time for the simulated cluster to converge before replaying
+    /// values. Replay starts as soon as every node knows all the others.
+    pub warmup: Duration,
+    /// Time to keep the cluster running after the last replayed value
+    pub cooldown: Duration,
+    /// Replay speed relative to the recording
+    pub speed: f64,
+    /// Maximum number of values to replay
+    pub max_values: Option<usize>,
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        Self {
+            num_nodes: 10,
+            warmup: Duration::from_secs(10),
+            cooldown: Duration::from_secs(10),
+            speed: 1.0,
+            max_values: None,
+        }
+    }
+}
+
+/// A value received by the recording node.
+#[derive(Clone, Debug)]
+pub struct ReplayEvent {
+    /// Time since the first replayed value was received
+    pub offset: Duration,
+    pub value: CrdsValue,
+}
+
+/// Returns the values carried by push messages and pull responses received by
+/// the recording node, in the order they were first received.
+pub fn load_events(recording: GossipRecording) -> Vec<ReplayEvent> {
+    let mut seen = HashSet::<(CrdsValueLabel, u64)>::new();
+    let mut first_timestamp_us = None;
+    let mut events = Vec::new();
+    for packet in recording.filter(|packet| packet.direction == Direction::Inbound) {
+        let Some((MessageKind::PushMessage | MessageKind::PullResponse, values)) = packet.decode()
+        else {
+            continue;
+        };
+        let first_timestamp_us = *first_timestamp_us.get_or_insert(packet.timestamp_us);
+        let offset = Duration::from_micros(packet.timestamp_us.saturating_sub(first_timestamp_us));
+        for value in values {
+            if seen.insert((value.label(), value.wallclock())) {
+                events.push(ReplayEvent { offset, value });
+            }
+        }
+    }
+    events
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MessageStats {
+    pub count: u64,
+    pub bytes: u64,
+}
+
+struct TrackedValue {
+    origin: usize,
+    replayed: Instant,
+    /// Time until each node first received the value
+    reached: HashMap<usize, Duration>,
+    num_push: u64,
+    num_pull: u64,
+}
+
+#[derive(Default)]
+struct TransportStats {
+    messages: BTreeMap<MessageKind, MessageStats>,
+    num_dropped: u64,
+    tracked: HashMap<(CrdsValueLabel, u64), TrackedValue>,
+}
+
+impl TransportStats {
+    fn record(&mut self, node: usize, data: &[u8]) {
+        let Some((kind, values)) = decode_message(data) else {
+            return;
+        };
+        let stats = self.messages.entry(kind).or_default();
+        stats.count += 1;
+        stats.bytes += data.len() as u64;
+        if !matches!(kind, MessageKind::PushMessage | MessageKind::PullResponse) {
+            return;
+        }
+        for value in values {
+            let Some(tracked) = self.tracked.get_mut(&(value.label(), value.wallclock())) else {
+                continue;
+            };
+            if tracked.origin == node {
+                continue;
+            }
+            if kind == MessageKind::PushMessage {
+                tracked.num_push += 1;
+            } else {
+                tracked.num_pull += 1;
+            }
+            let elapsed = tracked.replayed.elapsed();
+            tracked.reached.entry(node).or_insert(elapsed);
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct SimulationReport {
+    pub num_nodes: usize,
+    pub elapsed: Duration,
+    pub num_replayed: usize,
+    /// Values of types which cannot be replayed, e.g. contact-infos
+    pub num_skipped: usize,
+    /// Values not replayed because another recorded origin mapped to the same
+    /// node already replayed a value with the same label
+    pub num_collisions: usize,
+    /// Mean fraction of the other nodes reached by a replayed value
+    pub coverage: f64,
+    /// Times until a replayed value reached a node, sorted
+    pub latencies: Vec<Duration>,
+    /// Push deliveries of replayed values per node reached
+    pub push_redundancy: f64,
+    /// Pull response deliveries of replayed values per node reached
+    pub pull_redundancy: f64,
+    pub messages: BTreeMap<MessageKind, MessageStats>,
+    /// Packets addressed to nodes outside the simulated cluster
+    pub num_dropped_packets: u64,
+}
+
+impl SimulationReport {
+    pub fn latency_percentile(&self, percentile: usize) -> Option<Duration> {
+        let index =
+            (self.latencies.len() * percentile / 100).min(self.latencies.len().checked_sub(1)?);
+        self.latencies.get(index).copied()
+    }
+}
+
+impl fmt::Display for SimulationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "nodes: {}", self.num_nodes)?;
+        writeln!(f, "elapsed: {:?}", self.elapsed)?;
+        writeln!(
+            f,
+            "replayed values: {} ({} skipped, {} colliding)",
+            self.num_replayed, self.num_skipped, self.num_collisions
+        )?;
+        writeln!(f, "coverage: {:.2}%", self.coverage * 100.0)?;
+        for percentile in [50, 90, 99, 100] {
+            if let Some(latency) = self.latency_percentile(percentile) {
+                writeln!(f, "latency p{percentile}: {latency:?}")?;
+            }
+        }
+        writeln!(f, "push redundancy: {:.2}", self.push_redundancy)?;
+        writeln!(f, "pull redundancy: {:.2}", self.pull_redundancy)?;
+        for (kind, stats) in &self.messages {
+            writeln!(
+                f,
+                "{kind:?}: {} messages, {} bytes",
+                stats.count, stats.bytes
+            )?;
+        }
+        write!(f, "dropped packets: {}", self.num_dropped_packets)
+    }
+}
+
+struct SimulatedNode {
+    cluster_info: Arc<ClusterInfo>,
+    gossip_service: GossipService,
+}
+
+/// Maps offsets into a recording to replay times and wallclocks.
+struct ReplayClock {
+    start: Instant,
+    start_wallclock: u64,
+    speed: f64,
+}
+
+impl ReplayClock {
+    fn new(speed: f64) -> Self {
+        Self {
+            start: Instant::now(),
+            start_wallclock: timestamp(),
+            speed,
+        }
+    }
+
+    /// Sleeps until the replay time of `offset`, and returns it.
+    fn sleep_until(&self, offset: Duration) -> Instant {
+        let replay_time = self.start + offset.div_f64(self.speed);
+        if let Some(delay) = replay_time.checked_duration_since(Instant::now()) {
+            sleep(delay);
+        }
+        replay_time
+    }
+
+    fn wallclock(&self, offset: Duration) -> u64 {
+        self.start_wallclock + offset.div_f64(self.speed).as_millis() as u64
+    }
+}
+
+/// A cluster of gossip nodes exchanging packets over in-memory channels.
+pub struct Simulation {
+    config: SimulationConfig,
+    nodes: Vec<SimulatedNode>,
+    stats: Arc<Mutex<TransportStats>>,
+    exit: Arc<AtomicBool>,
+    routers: Vec<JoinHandle<()>>,
+}
+
+impl Simulation {
+    /// Starts `config.num_nodes` nodes, all using node 0 as their entrypoint.
+    pub fn new(config: SimulationConfig, shred_version: u16) -> Self {
+        let num_nodes = config.num_nodes;
+        assert!((1..=u16::MAX as usize).contains(&num_nodes));
+        let exit = Arc::new(AtomicBool::new(false));
+        let stats = Arc::new(Mutex::new(TransportStats::default()));
+        let addrs: Vec<_> = (0..num_nodes)
+            .map(|index| {
+                let ip = Ipv4Addr::new(10, 0, (index >> 8) as u8, index as u8);
+                SocketAddr::from((ip, GOSSIP_PORT))
+            })
+            .collect();
+        let (inbound_senders, inbound_receivers): (Vec<_>, Vec<_>) =
+            (0..num_nodes).map(|_| unbounded()).unzip();
+        let inbound_senders = Arc::new(inbound_senders);
+        let addr_index: Arc<HashMap<_, _>> = Arc::new(
+            addrs
+                .iter()
+                .enumerate()
+                .map(|(index, addr)| (*addr, index))
+                .collect(),
+        );
+        let mut nodes = Vec::with_capacity(num_nodes);
+        let mut routers = Vec::with_capacity(num_nodes);
+        for (index, inbound_receiver) in inbound_receivers.into_iter().enumerate() {
+            let keypair = Arc::new(Keypair::new());
+            let mut contact_info = ContactInfo::new_localhost(&keypair.pubkey(), timestamp());
+            contact_info.set_gossip(addrs[index]).unwrap();
+            contact_info.set_shred_version(shred_version);
+            let cluster_info =
+                ClusterInfo::new(contact_info, keypair, SocketAddrSpace::Unspecified);
+            if index > 0 {
+                cluster_info.set_entrypoint(LegacyContactInfo::new_gossip_entry_point(&addrs[0]));
+            }
+            let cluster_info = Arc::new(cluster_info);
+            let (outbound_sender, outbound_receiver) = unbounded();
+            let gossip_service = GossipService::new_with_transport(
+                &cluster_info,
+                inbound_receiver,
+                outbound_sender,
+                None, // gossip_validators
+                true, // should_check_duplicate_instance
+                exit.clone(),
+            );
+            routers.push(spawn_router(
+                index,
+                addrs[index],
+                outbound_receiver,
+                inbound_senders.clone(),
+                addr_index.clone(),
+                stats.clone(),
+                exit.clone(),
+            ));
+            nodes.push(SimulatedNode {
+                cluster_info,
+                gossip_service,
+            });
+        }
+        Self {
+            config,
+            nodes,
+            stats,
+            exit,
+            routers,
+        }
+    }
+
+    pub fn cluster_infos(&self) -> impl Iterator<Item = &Arc<ClusterInfo>> {
+        self.nodes.iter().map(|node| &node.cluster_info)
+    }
+
+    /// Waits until every node knows all the others, for at most `timeout`.
+    fn wait_for_convergence(&self, timeout: Duration) {
+        let start = Instant::now();
+        while start.elapsed() < timeout {
+            if self
+                .cluster_infos()
+                .all(|cluster_info| cluster_info.gossip_peers().len() + 1 >= self.nodes.len())
+            {
+                return;
+            }
+            sleep(Duration::from_millis(100));
+        }
+        warn!("cluster did not converge within {timeout:?}");
+    }
+
+    /// Replays `events` following the recorded timing, and reports on the
+    /// propagation of the replayed values.
+    pub fn run(&self, events: &[ReplayEvent]) -> SimulationReport {
+        let config = &self.config;
+        self.wait_for_convergence(config.warmup);
+        let start = Instant::now();
+        let clock = ReplayClock::new(config.speed);
+        let mut origins = HashMap::<Pubkey, usize>::new();
+        // Recorded origin of the values replayed with each label
+        let mut labels = HashMap::<CrdsValueLabel, Pubkey>::new();
+        let mut report = SimulationReport {
+            num_nodes: self.nodes.len(),
+            ..SimulationReport::default()
+        };
+        for event in events.iter().take(config.max_values.unwrap_or(usize::MAX)) {
+            let replay_time = clock.sleep_until(event.offset);
+            let recorded_origin = event.value.pubkey();
+            let next_origin = origins.len() % self.nodes.len();
+            let origin = *origins.entry(recorded_origin).or_insert(next_origin);
+            let cluster_info = &self.nodes[origin].cluster_info;
+            let Some(label) = replayed_label(cluster_info, &event.value) else {
+                report.num_skipped += 1;
+                continue;
+            };
+            if *labels.entry(label).or_insert(recorded_origin) != recorded_origin {
+                report.num_collisions += 1;
+                continue;
+            }
+            // Hold the lock so that the value is tracked before it is delivered.
+            let mut stats = self.stats.lock().unwrap();
+            let wallclock = clock.wallclock(event.offset);
+            let value = replay_value(cluster_info, &event.value, wallclock)
+                .expect("the value has a replayed label");
+            report.num_replayed += 1;
+            stats.tracked.insert(
+                (value.label(), value.wallclock()),
+                TrackedValue {
+                    origin,
+                    replayed: replay_time,
+                    reached: HashMap::new(),
+                    num_push: 0,
+                    num_pull: 0,
+                },
+            );
+        }
+        sleep(config.cooldown);
+        report.elapsed = start.elapsed();
+
+        let mut stats = self.stats.lock().unwrap();
+        let tracked = std::mem::take(&mut stats.tracked);
+        let num_reached: usize = tracked.values().map(|value| value.reached.len()).sum();
+        if !tracked.is_empty() && self.nodes.len() > 1 {
+            report.coverage = num_reached as f64 / (tracked.len() * (self.nodes.len() - 1)) as f64;
+        }
+        if num_reached > 0 {
+            let num_push: u64 = tracked.values().map(|value| value.num_push).sum();
+            let num_pull: u64 = tracked.values().map(|value| value.num_pull).sum();
+            report.push_redundancy = num_push as f64 / num_reached as f64;
+            report.pull_redundancy = num_pull as f64 / num_reached as f64;
+        }
+        report.latencies = tracked
+            .into_values()
+            .flat_map(|value| value.reached.into_values())
+            .collect();
+        report.latencies.sort_unstable();
+        report.messages = std::mem::take(&mut stats.messages);
+        report.num_dropped_packets = std::mem::take(&mut stats.num_dropped);
+        report
+    }
+
+    pub fn close(self) -> thread::Result<()> {
+        self.exit.store(true, Ordering::Relaxed);
+        for node in self.nodes {
+            node.gossip_service.join()?;
+        }
+        for router in self.routers {
+            router.join()?;
+        }
+        Ok(())
+    }
+}
+
+/// Spawns a thread delivering the packets sent by node `index` to their
+/// destination node, as if received from `addr`.
+fn spawn_router(
+    index: usize,
+    addr: SocketAddr,
+    outbound: PacketBatchReceiver,
+    inbound: Arc<Vec<PacketBatchSender>>,
+    addr_index: Arc<HashMap<SocketAddr, usize>>,
+    stats: Arc<Mutex<TransportStats>>,
+    exit: Arc<AtomicBool>,
+) -> JoinHandle<()> {
+    let run_router = move || {
+        while !exit.load(Ordering::Relaxed) {
+            let packets = match outbound.recv_timeout(Duration::from_millis(100)) {
+                Ok(packets) => packets,
+                Err(RecvTimeoutError::Timeout) => continue,
+                Err(RecvTimeoutError::Disconnected) => break,
+            };
+            let mut batches = HashMap::<usize, PacketBatch>::new();
+            let mut stats = stats.lock().unwrap();
+            for packet in packets.iter().filter(|packet| !packet.meta().discard()) {
+                let (Some(&dest), Some(data)) = (
+                    addr_index.get(&packet.meta().socket_addr()),
+                    packet.data(..),
+                ) else {
+                    stats.num_dropped += 1;
+                    continue;
+                };
+                stats.record(dest, data);
+                let mut packet = packet.clone();
+                packet.meta_mut().set_socket_addr(&addr);
+                batches.entry(dest).or_default().push(packet);
+            }
+            drop(stats);
+            for (dest, batch) in batches {
+                if inbound[dest].send(batch).is_err() {
+                    trace!("node {index}: node {dest} is gone");
+                }
+            }
+        }
+    };
+    Builder::new()
+        .name(format!("solGossipSim{index:02}"))
+        .spawn(run_router)
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_gossip::{crds_value::CrdsData, epoch_slots::EpochSlots},
+    };
+
+    #[test]
+    fn test_simulation() {
+        solana_logger::setup();
+        let config = SimulationConfig {
+            num_nodes: 4,
+            warmup: Duration::from_secs(10),
+            cooldown: Duration::from_secs(2),
+            ..SimulationConfig::default()
+        };
+        let simulation = Simulation::new(config, /*shred_version:*/ 1);
+        // One more origin than there are nodes, so that the last one maps to
+        // the same node as the first
+        let origins: Vec<_> = (0..5).map(|_| Keypair::new()).collect();
+        let mut events: Vec<_> = origins
+            .iter()
+            .map(|origin| {
+                let mut epoch_slots = EpochSlots::new(origin.pubkey(), timestamp());
+                epoch_slots.fill(&[1, 2, 3], timestamp());
+                ReplayEvent {
+                    offset: Duration::ZERO,
+                    value: CrdsValue::new_signed(CrdsData::EpochSlots(0, epoch_slots), origin),
+                }
+            })
+            .collect();
+        events.push(ReplayEvent {
+            offset: Duration::from_millis(10),
+            value: CrdsValue::new_signed(
+                CrdsData::LegacyContactInfo(LegacyContactInfo::new_localhost(
+                    &origins[0].pubkey(),
+                    timestamp(),
+                )),
+                &origins[0],
+            ),
+        });
+        let report = simulation.run(&events);
+        simulation.close().unwrap();
+        assert_eq!(report.num_nodes, 4);
+        assert_eq!(report.num_replayed, 4);
+        assert_eq!(report.num_collisions, 1);
+        assert_eq!(report.num_skipped, 1);
+        // Propagation depends on timing, but some values reach some nodes
+        assert!(report.coverage > 0.0 && report.coverage <= 1.0);
+        assert!(!report.latencies.is_empty());
+        assert!(report.latencies.len() <= 4 * 3);
+    }
+}
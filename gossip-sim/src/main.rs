@@ -0,0 +1,94 @@
+//! Replays a gossip recording, made with `solana-validator --gossip-record-path`,
+//! into a simulated cluster and reports on the propagation of the values.
+
+use {
+    clap::{crate_description, crate_name, value_t, value_t_or_exit, App, Arg},
+    solana_gossip::gossip_recorder::GossipRecording,
+    solana_gossip_sim::{load_events, Simulation, SimulationConfig},
+    std::{path::PathBuf, process::exit, time::Duration},
+};
+
+fn main() {
+    solana_logger::setup_with_default("solana=info");
+
+    let matches = App::new(crate_name!())
+        .about(crate_description!())
+        .version(solana_version::version!())
+        .arg(
+            Arg::with_name("recording")
+                .long("recording")
+                .value_name("FILE")
+                .takes_value(true)
+                .required(true)
+                .help("Gossip recording to replay"),
+        )
+        .arg(
+            Arg::with_name("num_nodes")
+                .long("num-nodes")
+                .value_name("NUM")
+                .takes_value(true)
+                .default_value("10")
+                .help("Number of nodes in the simulated cluster"),
+        )
+        .arg(
+            Arg::with_name("warmup")
+                .long("warmup")
+                .value_name("SECONDS")
+                .takes_value(true)
+                .default_value("10")
+                .help("Maximum time for the simulated cluster to converge before replaying"),
+        )
+        .arg(
+            Arg::with_name("cooldown")
+                .long("cooldown")
+                .value_name("SECONDS")
+                .takes_value(true)
+                .default_value("10")
+                .help("Time to keep the cluster running after the last replayed value"),
+        )
+        .arg(
+            Arg::with_name("speed")
+                .long("speed")
+                .value_name("FACTOR")
+                .takes_value(true)
+                .default_value("1.0")
+                .help("Replay speed relative to the recording"),
+        )
+        .arg(
+            Arg::with_name("max_values")
+                .long("max-values")
+                .value_name("NUM")
+                .takes_value(true)
+                .help("Maximum number of values to replay [default: all]"),
+        )
+        .get_matches();
+
+    let path = PathBuf::from(matches.value_of("recording").unwrap());
+    let config = SimulationConfig {
+        num_nodes: value_t_or_exit!(matches, "num_nodes", usize),
+        warmup: Duration::from_secs(value_t_or_exit!(matches, "warmup", u64)),
+        cooldown: Duration::from_secs(value_t_or_exit!(matches, "cooldown", u64)),
+        speed: value_t_or_exit!(matches, "speed", f64),
+        max_values: value_t!(matches, "max_values", usize).ok(),
+    };
+    if config.speed <= 0.0 {
+        eprintln!("--speed must be positive");
+        exit(1);
+    }
+    let recording = GossipRecording::open(&path).unwrap_or_else(|err| {
+        eprintln!("Failed to open {}: {err}", path.display());
+        exit(1);
+    });
+    let shred_version = recording.header().shred_version;
+    let events = load_events(recording);
+    println!(
+        "Replaying {} values into {} nodes",
+        events.len(),
+        config.num_nodes
+    );
+
+    let simulation = Simulation::new(config, shred_version);
+    let report = simulation.run(&events);
+    simulation.close().unwrap();
+    println!("{report}");
+}
@@ -38,6 +38,7 @@ use {
         duplicate_shred::DuplicateShred,
         epoch_slots::EpochSlots,
         gossip_error::GossipError,
+        ingress_budget::{IngressBudgets, PeerIngressStats},
         ping_pong::{self, PingCache, Pong},
         restart_crds_values::{
             RestartHeaviestFork, RestartLastVotedForkSlots, RestartLastVotedForkSlotsError,
@@ -166,6 +167,8 @@ pub struct ClusterInfo {
     /// Network entrypoints
     entrypoints: RwLock<Vec<LegacyContactInfo>>,
     outbound_budget: DataBudget,
+    /// Per peer and per origin budgets of inbound gossip traffic
+    ingress_budgets: IngressBudgets,
     my_contact_info: RwLock<ContactInfo>,
     ping_cache: Mutex<PingCache>,
     stats: GossipStats,
@@ -279,6 +282,19 @@ pub(crate) enum Protocol {
 }
 
 impl Protocol {
+    // Returns the node which sent the message: its signer, or the relaying
+    // peer of push messages and pull responses, whose crds values are each
+    // signed by their origin.
+    pub(crate) fn sender(&self) -> Pubkey {
+        match self {
+            Protocol::PullRequest(_, caller) => caller.pubkey(),
+            Protocol::PullResponse(from, _) | Protocol::PushMessage(from, _) => *from,
+            Protocol::PruneMessage(_, data) => data.pubkey(),
+            Protocol::PingMessage(ping) => ping.pubkey(),
+            Protocol::PongMessage(pong) => pong.pubkey(),
+        }
+    }
+
     fn par_verify(self, stats: &GossipStats) -> Option<Self> {
         match self {
             Protocol::PullRequest(_, ref caller) => {
@@ -410,6 +426,7 @@ impl ClusterInfo {
             keypair: RwLock::new(keypair),
             entrypoints: RwLock::default(),
             outbound_budget: DataBudget::default(),
+            ingress_budgets: IngressBudgets::default(),
             my_contact_info: RwLock::new(contact_info),
             ping_cache: Mutex::new(PingCache::new(
                 GOSSIP_PING_CACHE_TTL,
@@ -706,6 +723,12 @@ impl ClusterInfo {
             .cloned()
     }
 
+    /// Returns the `num` nodes which sent or relayed the most gossip traffic
+    /// received by this node.
+    pub fn gossip_top_talkers(&self, num: usize) -> Vec<PeerIngressStats> {
+        self.ingress_budgets.top_talkers(num)
+    }
+
     pub fn my_contact_info(&self) -> ContactInfo {
         self.my_contact_info.read().unwrap().clone()
     }
@@ -1005,7 +1028,7 @@ impl ClusterInfo {
         TimedGuard::new(self.gossip.crds.read().unwrap(), label, counter)
     }
 
-    fn push_message(&self, message: CrdsValue) {
+    pub(crate) fn push_message(&self, message: CrdsValue) {
         self.local_message_pending_push_queue
             .lock()
             .unwrap()
@@ -2163,11 +2186,17 @@ impl ClusterInfo {
     ) -> (usize, usize, usize) {
         let len = crds_values.len();
         let mut pull_stats = ProcessPullStats::default();
-        let (filtered_pulls, filtered_pulls_expired_timeout, failed_inserts) = {
+        let (mut filtered_pulls, mut filtered_pulls_expired_timeout, failed_inserts) = {
             let _st = ScopedTimer::from(&self.stats.filter_pull_response);
             self.gossip
                 .filter_pull_responses(timeouts, crds_values, timestamp(), &mut pull_stats)
         };
+        // Duplicates are already filtered out, so the remaining values are
+        // all charged to their origin.
+        for values in [&mut filtered_pulls, &mut filtered_pulls_expired_timeout] {
+            self.ingress_budgets
+                .filter_values(values, |_| true, &self.stats);
+        }
         if !filtered_pulls.is_empty()
             || !filtered_pulls_expired_timeout.is_empty()
             || !failed_inserts.is_empty()
@@ -2280,6 +2309,19 @@ impl ClusterInfo {
         self.stats
             .push_message_value_count
             .add_relaxed(num_crds_values);
+        // New crds values are charged to their origin; duplicates are not.
+        let messages = {
+            let crds = self.gossip.crds.read().unwrap();
+            let is_new = |value: &CrdsValue| crds.upserts(value);
+            messages
+                .into_iter()
+                .map(|(from, mut values)| {
+                    self.ingress_budgets
+                        .filter_values(&mut values, is_new, &self.stats);
+                    (from, values)
+                })
+                .collect()
+        };
         // Origins' pubkeys of upserted crds values.
         let origins: HashSet<_> = {
             let _st = ScopedTimer::from(&self.stats.process_push_message);
@@ -2483,6 +2525,7 @@ impl ClusterInfo {
     // handling of requests/messages.
     fn run_socket_consume(
         &self,
+        bank_forks: Option<&RwLock<BankForks>>,
         receiver: &PacketBatchReceiver,
         sender: &Sender<Vec<(/*from:*/ SocketAddr, Protocol)>>,
         thread_pool: &ThreadPool,
@@ -2554,6 +2597,21 @@ impl ClusterInfo {
         self.stats
             .packets_received_verified_count
             .add_relaxed(packets.len() as u64);
+        // Each message is charged to the node which sent it, which is only
+        // known once the message is verified.
+        let stakes = match bank_forks {
+            None => Arc::default(),
+            Some(bank_forks) => bank_forks.read().unwrap().root_bank().staked_nodes(),
+        };
+        let packets = {
+            let crds = self.gossip.crds.read().unwrap();
+            let gossip_ip = |pubkey: &Pubkey| {
+                let node = crds.get::<&LegacyContactInfo>(*pubkey)?;
+                Some(node.gossip().ok()?.ip())
+            };
+            self.ingress_budgets
+                .filter_messages(packets, &stakes, gossip_ip, &self.stats)
+        };
         Ok(sender.send(packets)?)
     }
 
@@ -2615,6 +2673,7 @@ impl ClusterInfo {
 
     pub(crate) fn start_socket_consume_thread(
         self: Arc<Self>,
+        bank_forks: Option<Arc<RwLock<BankForks>>>,
         receiver: PacketBatchReceiver,
         sender: Sender<Vec<(/*from:*/ SocketAddr, Protocol)>>,
         exit: Arc<AtomicBool>,
@@ -2626,7 +2685,12 @@ impl ClusterInfo {
             .unwrap();
         let run_consume = move || {
             while !exit.load(Ordering::Relaxed) {
-                match self.run_socket_consume(&receiver, &sender, &thread_pool) {
+                match self.run_socket_consume(
+                    bank_forks.as_deref(),
+                    &receiver,
+                    &sender,
+                    &thread_pool,
+                ) {
                     Err(GossipError::RecvTimeoutError(RecvTimeoutError::Disconnected)) => break,
                     Err(GossipError::RecvTimeoutError(RecvTimeoutError::Timeout)) => (),
                     // A send operation can only fail if the receiving end of a
@@ -106,6 +106,10 @@ pub struct GossipStats {
     pub(crate) gossip_listen_loop_iterations_since_last_report: Counter,
     pub(crate) gossip_listen_loop_time: Counter,
     pub(crate) gossip_packets_dropped_count: Counter,
+    pub(crate) gossip_packets_dropped_peer_budget_count: Counter,
+    pub(crate) gossip_packets_dropped_unstaked_peer_budget_count: Counter,
+    pub(crate) gossip_values_dropped_origin_budget_count: Counter,
+    pub(crate) gossip_values_dropped_unstaked_origin_budget_count: Counter,
     pub(crate) gossip_ping_msg_verify_fail: Counter,
     pub(crate) gossip_pong_msg_verify_fail: Counter,
     pub(crate) gossip_prune_msg_verify_fail: Counter,
@@ -229,6 +233,30 @@ pub(crate) fn submit_gossip_stats(
             stats.gossip_packets_dropped_count.clear(),
             i64
         ),
+        (
+            "gossip_packets_dropped_peer_budget_count",
+            stats.gossip_packets_dropped_peer_budget_count.clear(),
+            i64
+        ),
+        (
+            "gossip_packets_dropped_unstaked_peer_budget_count",
+            stats
+                .gossip_packets_dropped_unstaked_peer_budget_count
+                .clear(),
+            i64
+        ),
+        (
+            "gossip_values_dropped_origin_budget_count",
+            stats.gossip_values_dropped_origin_budget_count.clear(),
+            i64
+        ),
+        (
+            "gossip_values_dropped_unstaked_origin_budget_count",
+            stats
+                .gossip_values_dropped_unstaked_origin_budget_count
+                .clear(),
+            i64
+        ),
         ("repair_peers", stats.repair_peers.clear(), i64),
         ("new_push_requests", stats.new_push_requests.clear(), i64),
         ("new_push_requests2", stats.new_push_requests2.clear(), i64),
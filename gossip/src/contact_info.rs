@@ -210,6 +210,11 @@ impl ContactInfo {
         self.shred_version
     }
 
+    #[inline]
+    pub fn version(&self) -> &solana_version::Version {
+        &self.version
+    }
+
     pub fn set_pubkey(&mut self, pubkey: Pubkey) {
         self.pubkey = pubkey
     }
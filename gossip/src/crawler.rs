@@ -0,0 +1,332 @@
+//! The `crawler` module summarizes the CRDS table of a spy node into a
+//! cluster topology snapshot, as dumped by `solana-gossip crawl`.
+
+use {
+    crate::{
+        cluster_info::ClusterInfo, contact_info::Protocol, crds::VersionedCrdsValue,
+        crds_value::CrdsData,
+    },
+    solana_sdk::{clock::Slot, pubkey::Pubkey, timing::timestamp},
+    std::{
+        collections::{BTreeMap, HashMap},
+        io::{self, Write},
+        net::SocketAddr,
+    },
+};
+
+/// Everything gossip knows about a single node.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct CrawlNode {
+    pub pubkey: String,
+    pub shred_version: Option<u16>,
+    pub version: Option<String>,
+    pub gossip: Option<SocketAddr>,
+    pub tpu: Option<SocketAddr>,
+    pub rpc: Option<SocketAddr>,
+    /// Latest wallclock across all values from the node
+    pub wallclock: u64,
+    pub num_values: usize,
+    pub num_votes: usize,
+    pub latest_vote_slot: Option<Slot>,
+    pub num_epoch_slots: usize,
+    pub snapshot_full_slot: Option<Slot>,
+    pub snapshot_incremental_slot: Option<Slot>,
+    pub restart_last_voted_slot: Option<Slot>,
+    pub restart_heaviest_fork_slot: Option<Slot>,
+    /// Activated stake of the node's vote accounts, if stakes were provided
+    pub stake: Option<u64>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct CrawlDistribution {
+    pub num_nodes: usize,
+    pub stake: u64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct StakeCoverage {
+    pub total_stake: u64,
+    /// Stake of the staked nodes with a contact-info in gossip
+    pub stake_in_gossip: u64,
+    pub num_staked_nodes: usize,
+    pub num_staked_nodes_in_gossip: usize,
+}
+
+/// A point-in-time summary of the CRDS table.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct CrawlSnapshot {
+    /// Local time the snapshot was taken, in milliseconds since the epoch
+    pub timestamp: u64,
+    pub num_values: usize,
+    /// Number of values per `CrdsData` type
+    pub values_by_type: BTreeMap<&'static str, usize>,
+    pub versions: BTreeMap<String, CrawlDistribution>,
+    pub shred_versions: BTreeMap<u16, CrawlDistribution>,
+    pub stake_coverage: Option<StakeCoverage>,
+    pub nodes: Vec<CrawlNode>,
+}
+
+impl CrawlSnapshot {
+    /// Builds a snapshot of the CRDS table of `cluster_info`, excluding the
+    /// node itself. `stakes` maps node identities to their activated stake.
+    pub fn new(cluster_info: &ClusterInfo, stakes: Option<&HashMap<Pubkey, u64>>) -> Self {
+        let self_pubkey = cluster_info.id();
+        let mut snapshot = Self {
+            timestamp: timestamp(),
+            ..Self::default()
+        };
+        let mut nodes = HashMap::<Pubkey, CrawlNode>::new();
+        {
+            let gossip_crds = cluster_info.gossip.crds.read().unwrap();
+            for entry in gossip_crds.values() {
+                let origin = entry.value.pubkey();
+                if origin == self_pubkey {
+                    continue;
+                }
+                snapshot.num_values += 1;
+                *snapshot
+                    .values_by_type
+                    .entry(data_type(&entry.value.data))
+                    .or_default() += 1;
+                let node = nodes.entry(origin).or_insert_with(|| CrawlNode {
+                    pubkey: origin.to_string(),
+                    ..CrawlNode::default()
+                });
+                node.add_value(entry);
+            }
+        }
+        for (pubkey, node) in &mut nodes {
+            node.stake = stakes.map(|stakes| stakes.get(pubkey).copied().unwrap_or_default());
+        }
+        // Only nodes with a contact-info count as present in gossip.
+        for node in nodes.values().filter(|node| node.gossip.is_some()) {
+            let stake = node.stake.unwrap_or_default();
+            let version = node
+                .version
+                .clone()
+                .unwrap_or_else(|| "unknown".to_string());
+            let entry = snapshot.versions.entry(version).or_default();
+            entry.num_nodes += 1;
+            entry.stake += stake;
+            if let Some(shred_version) = node.shred_version {
+                let entry = snapshot.shred_versions.entry(shred_version).or_default();
+                entry.num_nodes += 1;
+                entry.stake += stake;
+            }
+        }
+        snapshot.stake_coverage = stakes.map(|stakes| {
+            let in_gossip = |pubkey: &Pubkey| {
+                nodes
+                    .get(pubkey)
+                    .map(|node| node.gossip.is_some())
+                    .unwrap_or_default()
+            };
+            let staked = stakes.iter().filter(|(_, stake)| **stake > 0);
+            StakeCoverage {
+                total_stake: staked.clone().map(|(_, stake)| stake).sum(),
+                stake_in_gossip: staked
+                    .clone()
+                    .filter(|(pubkey, _)| in_gossip(pubkey))
+                    .map(|(_, stake)| stake)
+                    .sum(),
+                num_staked_nodes: staked.clone().count(),
+                num_staked_nodes_in_gossip: staked.filter(|(pubkey, _)| in_gossip(pubkey)).count(),
+            }
+        });
+        snapshot.nodes = nodes.into_values().collect();
+        snapshot
+            .nodes
+            .sort_unstable_by(|a, b| b.stake.cmp(&a.stake).then_with(|| a.pubkey.cmp(&b.pubkey)));
+        snapshot
+    }
+
+    /// Writes the nodes of the snapshot as CSV, one row per node.
+    pub fn write_csv<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writeln!(
+            writer,
+            "pubkey,shred_version,version,gossip,tpu,rpc,wallclock,num_values,num_votes,\
+             latest_vote_slot,num_epoch_slots,snapshot_full_slot,snapshot_incremental_slot,\
+             restart_last_voted_slot,restart_heaviest_fork_slot,stake"
+        )?;
+        fn field<T: ToString>(value: &Option<T>) -> String {
+            value.as_ref().map(T::to_string).unwrap_or_default()
+        }
+        for node in &self.nodes {
+            writeln!(
+                writer,
+                "{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+                node.pubkey,
+                field(&node.shred_version),
+                field(&node.version),
+                field(&node.gossip),
+                field(&node.tpu),
+                field(&node.rpc),
+                node.wallclock,
+                node.num_values,
+                node.num_votes,
+                field(&node.latest_vote_slot),
+                node.num_epoch_slots,
+                field(&node.snapshot_full_slot),
+                field(&node.snapshot_incremental_slot),
+                field(&node.restart_last_voted_slot),
+                field(&node.restart_heaviest_fork_slot),
+                field(&node.stake),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl CrawlNode {
+    fn add_value(&mut self, entry: &VersionedCrdsValue) {
+        self.num_values += 1;
+        self.wallclock = self.wallclock.max(entry.value.wallclock());
+        match &entry.value.data {
+            CrdsData::LegacyContactInfo(node) => {
+                // The new contact-info takes precedence if present.
+                if self.gossip.is_none() {
+                    self.shred_version = Some(node.shred_version());
+                    self.gossip = node.gossip().ok();
+                    self.tpu = node.tpu(Protocol::UDP).ok();
+                    self.rpc = node.rpc().ok();
+                }
+            }
+            CrdsData::ContactInfo(node) => {
+                self.shred_version = Some(node.shred_version());
+                self.version = Some(node.version().to_string());
+                self.gossip = node.gossip().ok();
+                self.tpu = node.tpu(Protocol::UDP).ok();
+                self.rpc = node.rpc().ok();
+            }
+            CrdsData::Vote(_, vote) => {
+                self.num_votes += 1;
+                self.latest_vote_slot = self.latest_vote_slot.max(vote.slot());
+            }
+            CrdsData::EpochSlots(_, _) => self.num_epoch_slots += 1,
+            CrdsData::SnapshotHashes(hashes) => {
+                self.snapshot_full_slot = Some(hashes.full.0);
+                self.snapshot_incremental_slot =
+                    hashes.incremental.iter().map(|(slot, _)| *slot).max();
+            }
+            CrdsData::Version(version) => {
+                self.version
+                    .get_or_insert_with(|| version.version.to_string());
+            }
+            CrdsData::LegacyVersion(version) => {
+                self.version
+                    .get_or_insert_with(|| version.version.to_string());
+            }
+            CrdsData::RestartLastVotedForkSlots(slots) => {
+                self.restart_last_voted_slot = Some(slots.last_voted_slot);
+            }
+            CrdsData::RestartHeaviestFork(fork) => {
+                self.restart_heaviest_fork_slot = Some(fork.last_slot);
+            }
+            CrdsData::LowestSlot(_, _)
+            | CrdsData::LegacySnapshotHashes(_)
+            | CrdsData::AccountsHashes(_)
+            | CrdsData::NodeInstance(_)
+            | CrdsData::DuplicateShred(_, _) => (),
+        }
+    }
+}
+
+fn data_type(data: &CrdsData) -> &'static str {
+    match data {
+        CrdsData::LegacyContactInfo(_) => "LegacyContactInfo",
+        CrdsData::Vote(_, _) => "Vote",
+        CrdsData::LowestSlot(_, _) => "LowestSlot",
+        CrdsData::LegacySnapshotHashes(_) => "LegacySnapshotHashes",
+        CrdsData::AccountsHashes(_) => "AccountsHashes",
+        CrdsData::EpochSlots(_, _) => "EpochSlots",
+        CrdsData::LegacyVersion(_) => "LegacyVersion",
+        CrdsData::Version(_) => "Version",
+        CrdsData::NodeInstance(_) => "NodeInstance",
+        CrdsData::DuplicateShred(_, _) => "DuplicateShred",
+        CrdsData::SnapshotHashes(_) => "SnapshotHashes",
+        CrdsData::ContactInfo(_) => "ContactInfo",
+        CrdsData::RestartLastVotedForkSlots(_) => "RestartLastVotedForkSlots",
+        CrdsData::RestartHeaviestFork(_) => "RestartHeaviestFork",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            contact_info::ContactInfo,
+            crds::GossipRoute,
+            crds_value::{CrdsValue, SnapshotHashes},
+        },
+        solana_sdk::{hash::Hash, signature::Keypair, signer::Signer},
+        solana_streamer::socket::SocketAddrSpace,
+        std::{net::Ipv4Addr, sync::Arc},
+    };
+
+    #[test]
+    fn test_crawl_snapshot() {
+        let keypair = Arc::new(Keypair::new());
+        let cluster_info = ClusterInfo::new(
+            ContactInfo::new_localhost(&keypair.pubkey(), timestamp()),
+            keypair,
+            SocketAddrSpace::Unspecified,
+        );
+        let staked = Keypair::new();
+        let unstaked = Keypair::new();
+        let absent = Pubkey::new_unique();
+        {
+            let mut gossip_crds = cluster_info.gossip.crds.write().unwrap();
+            for (keypair, shred_version) in [(&staked, 42), (&unstaked, 7)] {
+                let mut node = ContactInfo::new(keypair.pubkey(), timestamp(), shred_version);
+                node.set_gossip((Ipv4Addr::LOCALHOST, 8001)).unwrap();
+                let value = CrdsValue::new_signed(CrdsData::ContactInfo(node), keypair);
+                gossip_crds
+                    .insert(value, timestamp(), GossipRoute::LocalMessage)
+                    .unwrap();
+            }
+            let hashes = SnapshotHashes {
+                from: staked.pubkey(),
+                full: (100, Hash::new_unique()),
+                incremental: vec![(110, Hash::new_unique()), (120, Hash::new_unique())],
+                wallclock: timestamp(),
+            };
+            let value = CrdsValue::new_signed(CrdsData::SnapshotHashes(hashes), &staked);
+            gossip_crds
+                .insert(value, timestamp(), GossipRoute::LocalMessage)
+                .unwrap();
+        }
+        let stakes = HashMap::from([(staked.pubkey(), 30), (absent, 10)]);
+        let snapshot = CrawlSnapshot::new(&cluster_info, Some(&stakes));
+
+        assert_eq!(snapshot.num_values, 3);
+        assert_eq!(snapshot.values_by_type["ContactInfo"], 2);
+        assert_eq!(snapshot.values_by_type["SnapshotHashes"], 1);
+        assert_eq!(
+            snapshot.shred_versions[&42],
+            CrawlDistribution {
+                num_nodes: 1,
+                stake: 30
+            }
+        );
+        assert_eq!(snapshot.shred_versions[&7].num_nodes, 1);
+        assert_eq!(
+            snapshot.stake_coverage,
+            Some(StakeCoverage {
+                total_stake: 40,
+                stake_in_gossip: 30,
+                num_staked_nodes: 2,
+                num_staked_nodes_in_gossip: 1,
+            })
+        );
+        let node = &snapshot.nodes[0];
+        assert_eq!(node.pubkey, staked.pubkey().to_string());
+        assert_eq!(node.num_values, 2);
+        assert_eq!(node.snapshot_full_slot, Some(100));
+        assert_eq!(node.snapshot_incremental_slot, Some(120));
+
+        let mut csv = Vec::new();
+        snapshot.write_csv(&mut csv).unwrap();
+        assert_eq!(String::from_utf8(csv).unwrap().lines().count(), 3);
+    }
+}
@@ -172,6 +172,60 @@ impl CrdsData {
             ),
         }
     }
+
+    /// Returns a copy of the data attributed to `from` at `wallclock`, so that
+    /// recorded values can be replayed by a different node. Returns None for
+    /// node contact-info and instance values, which only make sense for the
+    /// node which created them.
+    pub(crate) fn with_origin(&self, from: Pubkey, wallclock: u64) -> Option<CrdsData> {
+        let mut data = self.clone();
+        match &mut data {
+            CrdsData::LegacyContactInfo(_)
+            | CrdsData::ContactInfo(_)
+            | CrdsData::NodeInstance(_) => return None,
+            CrdsData::Vote(_, vote) => {
+                vote.from = from;
+                vote.wallclock = wallclock;
+            }
+            CrdsData::LowestSlot(_, slots) => {
+                slots.from = from;
+                slots.wallclock = wallclock;
+            }
+            CrdsData::LegacySnapshotHashes(hashes) | CrdsData::AccountsHashes(hashes) => {
+                hashes.from = from;
+                hashes.wallclock = wallclock;
+            }
+            CrdsData::EpochSlots(_, slots) => {
+                slots.from = from;
+                slots.wallclock = wallclock;
+            }
+            CrdsData::LegacyVersion(version) => {
+                version.from = from;
+                version.wallclock = wallclock;
+            }
+            CrdsData::Version(version) => {
+                version.from = from;
+                version.wallclock = wallclock;
+            }
+            CrdsData::DuplicateShred(_, shred) => {
+                shred.from = from;
+                shred.wallclock = wallclock;
+            }
+            CrdsData::SnapshotHashes(hashes) => {
+                hashes.from = from;
+                hashes.wallclock = wallclock;
+            }
+            CrdsData::RestartLastVotedForkSlots(slots) => {
+                slots.from = from;
+                slots.wallclock = wallclock;
+            }
+            CrdsData::RestartHeaviestFork(fork) => {
+                fork.from = from;
+                fork.wallclock = wallclock;
+            }
+        }
+        Some(data)
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, AbiExample)]
@@ -0,0 +1,371 @@
+//! The `gossip_recorder` module captures the gossip traffic of a node to a
+//! file, so that it can be analyzed or replayed offline.
+//!
+//! A recording is a bincode serialized [`RecordingHeader`] followed by a
+//! sequence of bincode serialized [`RecordedPacket`]s.
+
+use {
+    crate::{
+        cluster_info::{ClusterInfo, Protocol},
+        crds_value::{CrdsValue, CrdsValueLabel},
+    },
+    crossbeam_channel::RecvTimeoutError,
+    solana_perf::packet::{Packet, PacketBatch},
+    solana_sdk::{pubkey::Pubkey, signature::Signer},
+    solana_streamer::streamer::{PacketBatchReceiver, PacketBatchSender},
+    std::{
+        fs::File,
+        io::{self, BufReader, BufWriter, Write},
+        net::SocketAddr,
+        path::Path,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc, Mutex,
+        },
+        thread::{Builder, JoinHandle},
+        time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+    },
+};
+
+pub const RECORDING_VERSION: u32 = 1;
+pub const DEFAULT_MAX_RECORDING_BYTES: u64 = 4 * 1024 * 1024 * 1024;
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Direction {
+    Inbound,
+    Outbound,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordingHeader {
+    pub version: u32,
+    /// Identity of the recording node
+    pub pubkey: Pubkey,
+    pub shred_version: u16,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedPacket {
+    /// Microseconds since the UNIX epoch
+    pub timestamp_us: u64,
+    pub direction: Direction,
+    /// Sender of an inbound packet, or destination of an outbound packet
+    pub addr: SocketAddr,
+    pub data: Vec<u8>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum MessageKind {
+    PullRequest,
+    PullResponse,
+    PushMessage,
+    PruneMessage,
+    PingMessage,
+    PongMessage,
+}
+
+impl RecordedPacket {
+    /// Decodes the gossip message in the packet, returning its kind and the
+    /// crds values it carries.
+    pub fn decode(&self) -> Option<(MessageKind, Vec<CrdsValue>)> {
+        decode_message(&self.data)
+    }
+}
+
+/// Decodes a gossip message, returning its kind and the crds values it
+/// carries. The caller of a pull request is returned as its only value.
+pub fn decode_message(data: &[u8]) -> Option<(MessageKind, Vec<CrdsValue>)> {
+    let mut packet = Packet::default();
+    packet
+        .buffer_mut()
+        .get_mut(..data.len())?
+        .copy_from_slice(data);
+    packet.meta_mut().size = data.len();
+    let message = match packet.deserialize_slice(..).ok()? {
+        Protocol::PullRequest(_, caller) => (MessageKind::PullRequest, vec![caller]),
+        Protocol::PullResponse(_, values) => (MessageKind::PullResponse, values),
+        Protocol::PushMessage(_, values) => (MessageKind::PushMessage, values),
+        Protocol::PruneMessage(_, _) => (MessageKind::PruneMessage, Vec::default()),
+        Protocol::PingMessage(_) => (MessageKind::PingMessage, Vec::default()),
+        Protocol::PongMessage(_) => (MessageKind::PongMessage, Vec::default()),
+    };
+    Some(message)
+}
+
+/// Re-signs a recorded value as originating from the node of `cluster_info`
+/// at `wallclock`, and queues it to be pushed by that node.
+/// Returns the queued value, or None if the value cannot be replayed.
+pub fn replay_value(
+    cluster_info: &ClusterInfo,
+    value: &CrdsValue,
+    wallclock: u64,
+) -> Option<CrdsValue> {
+    let keypair = cluster_info.keypair();
+    let data = value.data.with_origin(keypair.pubkey(), wallclock)?;
+    let value = CrdsValue::new_signed(data, &keypair);
+    cluster_info.push_message(value.clone());
+    Some(value)
+}
+
+/// Returns the label a recorded value would have once replayed by the node of
+/// `cluster_info`, or None if the value cannot be replayed.
+pub fn replayed_label(cluster_info: &ClusterInfo, value: &CrdsValue) -> Option<CrdsValueLabel> {
+    let data = value
+        .data
+        .with_origin(cluster_info.id(), /*wallclock:*/ 0)?;
+    Some(CrdsValue::new_unsigned(data).label())
+}
+
+struct RecordingWriter {
+    writer: BufWriter<File>,
+    num_bytes: u64,
+    /// Set once a packet did not fit, after which nothing more is recorded
+    full: bool,
+}
+
+/// Writes the packets received and sent by the gossip service to a file, up
+/// to a maximum file size.
+pub struct GossipRecorder {
+    writer: Mutex<RecordingWriter>,
+    max_bytes: u64,
+}
+
+impl GossipRecorder {
+    pub fn new(path: &Path, cluster_info: &ClusterInfo, max_bytes: u64) -> io::Result<Self> {
+        let header = RecordingHeader {
+            version: RECORDING_VERSION,
+            pubkey: cluster_info.id(),
+            shred_version: cluster_info.my_shred_version(),
+        };
+        let mut writer = BufWriter::new(File::create(path)?);
+        bincode::serialize_into(&mut writer, &header).map_err(io::Error::other)?;
+        Ok(Self {
+            writer: Mutex::new(RecordingWriter {
+                writer,
+                num_bytes: bincode::serialized_size(&header).map_err(io::Error::other)?,
+                full: false,
+            }),
+            max_bytes,
+        })
+    }
+
+    fn record(&self, direction: Direction, packets: &PacketBatch) -> io::Result<()> {
+        let timestamp_us = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_micros() as u64;
+        let mut writer = self.writer.lock().unwrap();
+        if writer.full {
+            return Ok(());
+        }
+        for packet in packets.iter().filter(|packet| !packet.meta().discard()) {
+            let Some(data) = packet.data(..) else {
+                continue;
+            };
+            let packet = RecordedPacket {
+                timestamp_us,
+                direction,
+                addr: packet.meta().socket_addr(),
+                data: data.to_vec(),
+            };
+            let size = bincode::serialized_size(&packet).map_err(io::Error::other)?;
+            if writer.num_bytes.saturating_add(size) > self.max_bytes {
+                warn!(
+                    "gossip recorder: recording reached {} bytes, no longer recording",
+                    self.max_bytes
+                );
+                writer.full = true;
+                break;
+            }
+            bincode::serialize_into(&mut writer.writer, &packet).map_err(io::Error::other)?;
+            writer.num_bytes += size;
+        }
+        Ok(())
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        self.writer.lock().unwrap().writer.flush()
+    }
+
+    /// Spawns a thread forwarding packets from `receiver` to `sender`,
+    /// recording them on the way.
+    pub(crate) fn tap(
+        self: Arc<Self>,
+        direction: Direction,
+        receiver: PacketBatchReceiver,
+        sender: PacketBatchSender,
+        exit: Arc<AtomicBool>,
+    ) -> JoinHandle<()> {
+        let run_tap = move || {
+            let mut last_flush = Instant::now();
+            while !exit.load(Ordering::Relaxed) {
+                match receiver.recv_timeout(FLUSH_INTERVAL) {
+                    Ok(packets) => {
+                        if let Err(err) = self.record(direction, &packets) {
+                            error!("gossip recorder: {err}");
+                        }
+                        if sender.send(packets).is_err() {
+                            break;
+                        }
+                    }
+                    Err(RecvTimeoutError::Timeout) => (),
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+                if last_flush.elapsed() > FLUSH_INTERVAL {
+                    if let Err(err) = self.flush() {
+                        error!("gossip recorder: {err}");
+                    }
+                    last_flush = Instant::now();
+                }
+            }
+            if let Err(err) = self.flush() {
+                error!("gossip recorder: {err}");
+            }
+        };
+        let thread_name = match direction {
+            Direction::Inbound => "solGossipRecIn",
+            Direction::Outbound => "solGossipRecOut",
+        };
+        Builder::new()
+            .name(thread_name.to_string())
+            .spawn(run_tap)
+            .unwrap()
+    }
+}
+
+/// Reads back the packets written by a [`GossipRecorder`].
+pub struct GossipRecording {
+    header: RecordingHeader,
+    reader: BufReader<File>,
+}
+
+impl GossipRecording {
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let mut reader = BufReader::new(File::open(path)?);
+        let header: RecordingHeader =
+            bincode::deserialize_from(&mut reader).map_err(io::Error::other)?;
+        if header.version != RECORDING_VERSION {
+            return Err(io::Error::other(format!(
+                "unsupported gossip recording version: {}",
+                header.version
+            )));
+        }
+        Ok(Self { header, reader })
+    }
+
+    pub fn header(&self) -> &RecordingHeader {
+        &self.header
+    }
+}
+
+impl Iterator for GossipRecording {
+    type Item = RecordedPacket;
+
+    /// Returns the next packet, or None at the end of the recording. A
+    /// truncated last packet, e.g. from a node that crashed, is ignored.
+    fn next(&mut self) -> Option<RecordedPacket> {
+        bincode::deserialize_from(&mut self.reader).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{contact_info::ContactInfo, crds_value::CrdsData, epoch_slots::EpochSlots},
+        crossbeam_channel::unbounded,
+        solana_sdk::{signature::Keypair, timing::timestamp},
+        solana_streamer::socket::SocketAddrSpace,
+        std::net::Ipv4Addr,
+    };
+
+    #[test]
+    fn test_gossip_recorder() {
+        let keypair = Arc::new(Keypair::new());
+        let cluster_info = ClusterInfo::new(
+            ContactInfo::new_localhost(&keypair.pubkey(), timestamp()),
+            keypair,
+            SocketAddrSpace::Unspecified,
+        );
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("gossip.rec");
+        let recorder = Arc::new(
+            GossipRecorder::new(&path, &cluster_info, DEFAULT_MAX_RECORDING_BYTES).unwrap(),
+        );
+
+        let origin = Keypair::new();
+        let value = CrdsValue::new_signed(
+            CrdsData::EpochSlots(0, EpochSlots::new(origin.pubkey(), timestamp())),
+            &origin,
+        );
+        let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, 8001));
+        let message = Protocol::PushMessage(origin.pubkey(), vec![value.clone()]);
+        let packets = PacketBatch::new(vec![Packet::from_data(Some(&addr), message).unwrap()]);
+
+        let exit = Arc::new(AtomicBool::new(false));
+        let (sender, receiver) = unbounded();
+        let (tap_sender, tap_receiver) = unbounded();
+        let t_tap = recorder.tap(Direction::Inbound, receiver, tap_sender, exit.clone());
+        sender.send(packets).unwrap();
+        assert_eq!(tap_receiver.recv().unwrap().len(), 1);
+        exit.store(true, Ordering::Relaxed);
+        t_tap.join().unwrap();
+
+        let mut recording = GossipRecording::open(&path).unwrap();
+        assert_eq!(recording.header().pubkey, cluster_info.id());
+        let packet = recording.next().unwrap();
+        assert_eq!(packet.direction, Direction::Inbound);
+        assert_eq!(packet.addr, addr);
+        assert_eq!(
+            packet.decode(),
+            Some((MessageKind::PushMessage, vec![value.clone()]))
+        );
+        assert_eq!(recording.next(), None);
+
+        let wallclock = timestamp();
+        let replayed = replay_value(&cluster_info, &value, wallclock).unwrap();
+        assert_eq!(replayed.pubkey(), cluster_info.id());
+        assert_eq!(replayed.wallclock(), wallclock);
+        assert_eq!(
+            replayed_label(&cluster_info, &value),
+            Some(replayed.label())
+        );
+        assert!(replayed.verify());
+        assert_eq!(
+            replayed.data.with_origin(origin.pubkey(), 0),
+            value.data.with_origin(origin.pubkey(), 0)
+        );
+    }
+
+    #[test]
+    fn test_gossip_recorder_max_bytes() {
+        let keypair = Arc::new(Keypair::new());
+        let cluster_info = ClusterInfo::new(
+            ContactInfo::new_localhost(&keypair.pubkey(), timestamp()),
+            keypair,
+            SocketAddrSpace::Unspecified,
+        );
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("gossip.rec");
+        let max_bytes = 1024;
+        let recorder = GossipRecorder::new(&path, &cluster_info, max_bytes).unwrap();
+
+        let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, 8001));
+        for _ in 0..20 {
+            let origin = Keypair::new();
+            let value = CrdsValue::new_signed(
+                CrdsData::EpochSlots(0, EpochSlots::new(origin.pubkey(), timestamp())),
+                &origin,
+            );
+            let message = Protocol::PushMessage(origin.pubkey(), vec![value]);
+            let packets = PacketBatch::new(vec![Packet::from_data(Some(&addr), message).unwrap()]);
+            recorder.record(Direction::Inbound, &packets).unwrap();
+        }
+        recorder.flush().unwrap();
+
+        assert!(std::fs::metadata(&path).unwrap().len() <= max_bytes);
+        let num_packets = GossipRecording::open(&path).unwrap().count();
+        assert!((1..20).contains(&num_packets));
+    }
+}
@@ -1,7 +1,11 @@
 //! The `gossip_service` module implements the network control plane.
 
 use {
-    crate::{cluster_info::ClusterInfo, legacy_contact_info::LegacyContactInfo as ContactInfo},
+    crate::{
+        cluster_info::ClusterInfo,
+        gossip_recorder::{Direction, GossipRecorder},
+        legacy_contact_info::LegacyContactInfo as ContactInfo,
+    },
     crossbeam_channel::{unbounded, Sender},
     rand::{thread_rng, Rng},
     solana_client::{
@@ -17,7 +21,7 @@ use {
     },
     solana_streamer::{
         socket::SocketAddrSpace,
-        streamer::{self, StreamerReceiveStats},
+        streamer::{self, PacketBatchReceiver, PacketBatchSender, StreamerReceiveStats},
     },
     std::{
         collections::HashSet,
@@ -36,6 +40,7 @@ pub struct GossipService {
 }
 
 impl GossipService {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         cluster_info: &Arc<ClusterInfo>,
         bank_forks: Option<Arc<RwLock<BankForks>>>,
@@ -43,6 +48,7 @@ impl GossipService {
         gossip_validators: Option<HashSet<Pubkey>>,
         should_check_duplicate_instance: bool,
         stats_reporter_sender: Option<Sender<Box<dyn FnOnce() + Send>>>,
+        gossip_recorder: Option<Arc<GossipRecorder>>,
         exit: Arc<AtomicBool>,
     ) -> Self {
         let (request_sender, request_receiver) = unbounded();
@@ -64,13 +70,93 @@ impl GossipService {
             false,
             None,
         );
+        let (response_sender, response_receiver) = unbounded();
+        let t_responder = streamer::responder(
+            "Gossip",
+            gossip_socket,
+            response_receiver,
+            socket_addr_space,
+            stats_reporter_sender,
+        );
+        let mut thread_hdls = vec![t_receiver, t_responder];
+        thread_hdls.extend(Self::spawn_gossip_threads(
+            cluster_info,
+            bank_forks,
+            request_receiver,
+            response_sender,
+            gossip_validators,
+            should_check_duplicate_instance,
+            gossip_recorder,
+            exit,
+        ));
+        Self { thread_hdls }
+    }
+
+    /// Runs gossip over packet channels instead of a socket, e.g. for an
+    /// in-memory transport between nodes of a simulated cluster.
+    /// Packets sent to `outbound` are addressed to their destination, and
+    /// packets received from `inbound` should carry their sender address.
+    pub fn new_with_transport(
+        cluster_info: &Arc<ClusterInfo>,
+        inbound: PacketBatchReceiver,
+        outbound: PacketBatchSender,
+        gossip_validators: Option<HashSet<Pubkey>>,
+        should_check_duplicate_instance: bool,
+        exit: Arc<AtomicBool>,
+    ) -> Self {
+        let thread_hdls = Self::spawn_gossip_threads(
+            cluster_info,
+            None, // bank_forks
+            inbound,
+            outbound,
+            gossip_validators,
+            should_check_duplicate_instance,
+            None, // gossip_recorder
+            exit,
+        );
+        Self { thread_hdls }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn spawn_gossip_threads(
+        cluster_info: &Arc<ClusterInfo>,
+        bank_forks: Option<Arc<RwLock<BankForks>>>,
+        request_receiver: PacketBatchReceiver,
+        response_sender: PacketBatchSender,
+        gossip_validators: Option<HashSet<Pubkey>>,
+        should_check_duplicate_instance: bool,
+        gossip_recorder: Option<Arc<GossipRecorder>>,
+        exit: Arc<AtomicBool>,
+    ) -> Vec<JoinHandle<()>> {
+        let mut thread_hdls = Vec::new();
+        let (request_receiver, response_sender) = match gossip_recorder {
+            None => (request_receiver, response_sender),
+            Some(gossip_recorder) => {
+                let (tap_sender, tap_receiver) = unbounded();
+                thread_hdls.push(gossip_recorder.clone().tap(
+                    Direction::Inbound,
+                    request_receiver,
+                    tap_sender,
+                    exit.clone(),
+                ));
+                let request_receiver = tap_receiver;
+                let (tap_sender, tap_receiver) = unbounded();
+                thread_hdls.push(gossip_recorder.tap(
+                    Direction::Outbound,
+                    tap_receiver,
+                    response_sender,
+                    exit.clone(),
+                ));
+                (request_receiver, tap_sender)
+            }
+        };
         let (consume_sender, listen_receiver) = unbounded();
         let t_socket_consume = cluster_info.clone().start_socket_consume_thread(
+            bank_forks.clone(),
             request_receiver,
             consume_sender,
             exit.clone(),
         );
-        let (response_sender, response_receiver) = unbounded();
         let t_listen = cluster_info.clone().listen(
             bank_forks.clone(),
             listen_receiver,
@@ -82,21 +168,8 @@ impl GossipService {
             cluster_info
                 .clone()
                 .gossip(bank_forks, response_sender, gossip_validators, exit);
-        let t_responder = streamer::responder(
-            "Gossip",
-            gossip_socket,
-            response_receiver,
-            socket_addr_space,
-            stats_reporter_sender,
-        );
-        let thread_hdls = vec![
-            t_receiver,
-            t_responder,
-            t_socket_consume,
-            t_listen,
-            t_gossip,
-        ];
-        Self { thread_hdls }
+        thread_hdls.extend([t_socket_consume, t_listen, t_gossip]);
+        thread_hdls
     }
 
     pub fn join(self) -> thread::Result<()> {
@@ -230,6 +303,17 @@ pub fn get_client(
                 panic!("Could not create TpuClient with Udp Cache {err:?}");
             }),
         ),
+        ConnectionCache::InMemory(cache) => TpuClientWrapper::InMemory(
+            TpuClient::new_with_connection_cache(
+                Arc::new(RpcClient::new(rpc_url)),
+                rpc_pubsub_url.as_str(),
+                TpuClientConfig::default(),
+                cache.clone(),
+            )
+            .unwrap_or_else(|err| {
+                panic!("Could not create TpuClient with InMemory Cache {err:?}");
+            }),
+        ),
     }
 }
 
@@ -330,7 +414,8 @@ pub fn make_gossip_node(
         gossip_socket,
         None,
         should_check_duplicate_instance,
-        None,
+        None, // stats_reporter_sender
+        None, // gossip_recorder
         exit,
     );
     (gossip_service, ip_echo, cluster_info)
@@ -366,6 +451,7 @@ mod tests {
             None,
             true, // should_check_duplicate_instance
             None,
+            None,
             exit.clone(),
         );
         exit.store(true, Ordering::Relaxed);
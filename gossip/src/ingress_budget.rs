@@ -0,0 +1,481 @@
+//! The `ingress_budget` module rate limits the gossip traffic received.
+//!
+//! Every verified message is first charged to the peer which sent it: the
+//! signer of pull requests, prunes, pings and pongs, and the relaying peer of
+//! push messages and pull responses. Values relayed by several peers are thus
+//! charged to each relayer rather than to their origin. Since the relaying
+//! peer of a push message or pull response is not signed, a staked peer's
+//! budget is only used for traffic from its gossip address.
+//!
+//! Crds values which pass the duplicate check are then charged to their
+//! origin, so that replaying or relaying values already received does not use
+//! up the budget of the node which signed them.
+//!
+//! Budgets are weighted by stake. Unstaked nodes have a small budget each, and
+//! all of them also share a capped budget, so that new keypairs do not add
+//! budget.
+
+use {
+    crate::{cluster_info::Protocol, cluster_info_metrics::GossipStats, crds_value::CrdsValue},
+    bincode::serialized_size,
+    lru::LruCache,
+    solana_perf::data_budget::DataBudget,
+    solana_sdk::pubkey::Pubkey,
+    std::{
+        cmp::Reverse,
+        collections::HashMap,
+        net::{IpAddr, SocketAddr},
+        sync::{Arc, Mutex},
+    },
+};
+
+const INTERVAL_MS: u64 = 100;
+// Maximum burst, as a multiple of the per interval budget.
+const MAX_BUDGET_MULTIPLE: usize = 5;
+// Budget of each unstaked node, ~128 KB/s.
+const UNSTAKED_BYTES_PER_INTERVAL: usize = 12_800;
+// Budget shared by all unstaked nodes, ~2.5 MB/s.
+const UNSTAKED_TOTAL_BYTES_PER_INTERVAL: usize = 256_000;
+// Budget split among staked nodes proportionally to their stake, ~64 MB/s.
+const STAKED_BYTES_PER_INTERVAL: usize = 6_400_000;
+// Number of unstaked nodes whose traffic is tracked.
+const MAX_NUM_UNSTAKED_NODES: usize = 8192;
+
+/// Gossip traffic relayed by a node, and new crds values signed by it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeerIngressStats {
+    pub pubkey: Pubkey,
+    pub stake: u64,
+    /// Bytes of the messages sent or relayed by the node
+    pub bytes_received: u64,
+    pub messages_received: u64,
+    pub messages_dropped: u64,
+    /// Crds values signed by the node which were not duplicates
+    pub values_received: u64,
+    pub values_dropped: u64,
+}
+
+#[derive(Default)]
+struct Ingress {
+    budget: DataBudget,
+    num_bytes: u64,
+    num_received: u64,
+    num_dropped: u64,
+}
+
+// Budgets and traffic of either the relaying peers, or the origins of crds
+// values.
+struct Ledger {
+    staked: HashMap<Pubkey, Ingress>,
+    unstaked: LruCache<Pubkey, Ingress>,
+    // Shared by all unstaked nodes.
+    unstaked_budget: DataBudget,
+}
+
+struct Budgets {
+    stakes: Arc<HashMap<Pubkey, /*stake:*/ u64>>,
+    total_stake: u64,
+    peers: Ledger,
+    origins: Ledger,
+}
+
+pub(crate) struct IngressBudgets(Mutex<Budgets>);
+
+impl Default for Ledger {
+    fn default() -> Self {
+        Self {
+            staked: HashMap::default(),
+            unstaked: LruCache::new(MAX_NUM_UNSTAKED_NODES),
+            unstaked_budget: DataBudget::default(),
+        }
+    }
+}
+
+impl Default for IngressBudgets {
+    fn default() -> Self {
+        Self(Mutex::new(Budgets {
+            stakes: Arc::default(),
+            total_stake: 0,
+            peers: Ledger::default(),
+            origins: Ledger::default(),
+        }))
+    }
+}
+
+fn refill(budget: &DataBudget, bytes: usize) {
+    budget.update(INTERVAL_MS, |budget| {
+        budget
+            .saturating_add(bytes)
+            .min(bytes.saturating_mul(MAX_BUDGET_MULTIPLE))
+    });
+}
+
+impl Ledger {
+    // Charges `size` bytes to the shared unstaked budget, returning false if
+    // they do not fit.
+    fn take_unstaked(&self, size: usize) -> bool {
+        refill(&self.unstaked_budget, UNSTAKED_TOTAL_BYTES_PER_INTERVAL);
+        self.unstaked_budget.take(size)
+    }
+
+    // Charges `size` bytes to the budget of the node, and to the shared
+    // unstaked budget if the node is unstaked, returning false if they do not
+    // fit, unless `exempt`.
+    fn take(
+        &mut self,
+        pubkey: Pubkey,
+        stake: u64,
+        bytes: usize,
+        size: usize,
+        exempt: bool,
+    ) -> bool {
+        let ingress = if stake > 0 {
+            self.staked.entry(pubkey).or_default()
+        } else {
+            if self.unstaked.get_mut(&pubkey).is_none() {
+                self.unstaked.put(pubkey, Ingress::default());
+            }
+            self.unstaked.get_mut(&pubkey).unwrap()
+        };
+        refill(&ingress.budget, bytes);
+        ingress.num_bytes += size as u64;
+        ingress.num_received += 1;
+        if exempt {
+            return true;
+        }
+        let fits = ingress.budget.take(size)
+            && (stake > 0 || {
+                refill(&self.unstaked_budget, UNSTAKED_TOTAL_BYTES_PER_INTERVAL);
+                self.unstaked_budget.take(size)
+            });
+        if !fits {
+            ingress.num_dropped += 1;
+        }
+        fits
+    }
+
+    fn get(&self, pubkey: &Pubkey) -> Option<&Ingress> {
+        self.staked
+            .get(pubkey)
+            .or_else(|| self.unstaked.peek(pubkey))
+    }
+
+    fn pubkeys(&self) -> impl Iterator<Item = &Pubkey> {
+        self.staked
+            .keys()
+            .chain(self.unstaked.iter().map(|(pubkey, _)| pubkey))
+    }
+}
+
+impl Budgets {
+    fn set_stakes(&mut self, stakes: &Arc<HashMap<Pubkey, u64>>) {
+        if Arc::ptr_eq(&self.stakes, stakes) {
+            return;
+        }
+        self.total_stake = stakes.values().sum();
+        self.stakes = Arc::clone(stakes);
+        let is_staked = |pubkey: &Pubkey| stakes.get(pubkey).is_some_and(|&stake| stake > 0);
+        self.peers.staked.retain(|pubkey, _| is_staked(pubkey));
+        self.origins.staked.retain(|pubkey, _| is_staked(pubkey));
+    }
+
+    fn stake(&self, pubkey: &Pubkey) -> u64 {
+        self.stakes.get(pubkey).copied().unwrap_or_default()
+    }
+
+    fn bytes_per_interval(&self, stake: u64) -> usize {
+        let staked_bytes = if self.total_stake == 0 {
+            0
+        } else {
+            (STAKED_BYTES_PER_INTERVAL as u128 * stake as u128 / self.total_stake as u128) as usize
+        };
+        UNSTAKED_BYTES_PER_INTERVAL + staked_bytes
+    }
+}
+
+impl IngressBudgets {
+    /// Returns the verified messages which fit in the budgets of the peers
+    /// which sent them. A peer's stake only counts for traffic from its
+    /// `gossip_ip`, since the relaying peer of push messages and pull
+    /// responses is not signed. Traffic from loopback addresses is accounted
+    /// for but never dropped.
+    pub(crate) fn filter_messages<F>(
+        &self,
+        messages: Vec<(/*from:*/ SocketAddr, Protocol)>,
+        stakes: &Arc<HashMap<Pubkey, u64>>,
+        gossip_ip: F,
+        stats: &GossipStats,
+    ) -> Vec<(/*from:*/ SocketAddr, Protocol)>
+    where
+        F: Fn(&Pubkey) -> Option<IpAddr>,
+    {
+        let mut num_dropped = 0;
+        let mut num_dropped_unstaked = 0;
+        let mut budgets = self.0.lock().unwrap();
+        budgets.set_stakes(stakes);
+        let messages = messages
+            .into_iter()
+            .filter(|(from, protocol)| {
+                let exempt = from.ip().is_loopback();
+                let peer = protocol.sender();
+                let size = serialized_size(protocol).unwrap_or(u64::MAX) as usize;
+                let stake = budgets.stake(&peer);
+                let fits = if stake > 0 && !exempt && gossip_ip(&peer) != Some(from.ip()) {
+                    // Traffic claiming to be from a staked peer, from another
+                    // address, is only charged to the shared unstaked budget.
+                    budgets.peers.take_unstaked(size)
+                } else {
+                    let bytes = budgets.bytes_per_interval(stake);
+                    budgets.peers.take(peer, stake, bytes, size, exempt)
+                };
+                if !fits {
+                    num_dropped += 1;
+                    if stake == 0 {
+                        num_dropped_unstaked += 1;
+                    }
+                }
+                fits
+            })
+            .collect();
+        stats
+            .gossip_packets_dropped_peer_budget_count
+            .add_relaxed(num_dropped);
+        stats
+            .gossip_packets_dropped_unstaked_peer_budget_count
+            .add_relaxed(num_dropped_unstaked);
+        messages
+    }
+
+    /// Retains the crds values which are duplicates, as given by `is_new`,
+    /// or which fit in the budgets of their origins. Duplicates are kept so
+    /// that they are still accounted for when pruning push paths.
+    pub(crate) fn filter_values<F>(
+        &self,
+        values: &mut Vec<CrdsValue>,
+        is_new: F,
+        stats: &GossipStats,
+    ) where
+        F: Fn(&CrdsValue) -> bool,
+    {
+        let mut num_dropped = 0;
+        let mut num_dropped_unstaked = 0;
+        let mut budgets = self.0.lock().unwrap();
+        values.retain(|value| {
+            if !is_new(value) {
+                return true;
+            }
+            let origin = value.pubkey();
+            let size = serialized_size(value).unwrap_or(u64::MAX) as usize;
+            let stake = budgets.stake(&origin);
+            let bytes = budgets.bytes_per_interval(stake);
+            if budgets
+                .origins
+                .take(origin, stake, bytes, size, /*exempt:*/ false)
+            {
+                return true;
+            }
+            num_dropped += 1;
+            if stake == 0 {
+                num_dropped_unstaked += 1;
+            }
+            false
+        });
+        stats
+            .gossip_values_dropped_origin_budget_count
+            .add_relaxed(num_dropped);
+        stats
+            .gossip_values_dropped_unstaked_origin_budget_count
+            .add_relaxed(num_dropped_unstaked);
+    }
+
+    /// Returns the `num` nodes which relayed the most bytes.
+    pub(crate) fn top_talkers(&self, num: usize) -> Vec<PeerIngressStats> {
+        let budgets = self.0.lock().unwrap();
+        let mut pubkeys: Vec<_> = budgets
+            .peers
+            .pubkeys()
+            .chain(budgets.origins.pubkeys())
+            .copied()
+            .collect();
+        pubkeys.sort_unstable();
+        pubkeys.dedup();
+        let mut talkers: Vec<_> = pubkeys
+            .into_iter()
+            .map(|pubkey| {
+                let peer = budgets.peers.get(&pubkey);
+                let origin = budgets.origins.get(&pubkey);
+                PeerIngressStats {
+                    pubkey,
+                    stake: budgets.stake(&pubkey),
+                    bytes_received: peer.map(|peer| peer.num_bytes).unwrap_or_default(),
+                    messages_received: peer.map(|peer| peer.num_received).unwrap_or_default(),
+                    messages_dropped: peer.map(|peer| peer.num_dropped).unwrap_or_default(),
+                    values_received: origin.map(|origin| origin.num_received).unwrap_or_default(),
+                    values_dropped: origin.map(|origin| origin.num_dropped).unwrap_or_default(),
+                }
+            })
+            .collect();
+        talkers.sort_unstable_by_key(|talker| Reverse((talker.bytes_received, talker.pubkey)));
+        talkers.truncate(num);
+        talkers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        rand::SeedableRng,
+        rand_chacha::ChaChaRng,
+        solana_sdk::signature::{Keypair, Signer},
+        std::{collections::HashSet, net::Ipv4Addr},
+    };
+
+    fn new_push_messages(
+        addr: SocketAddr,
+        relayer: &Pubkey,
+        value: &CrdsValue,
+        num: usize,
+    ) -> Vec<(SocketAddr, Protocol)> {
+        std::iter::repeat_with(|| (addr, Protocol::PushMessage(*relayer, vec![value.clone()])))
+            .take(num)
+            .collect()
+    }
+
+    #[test]
+    fn test_relayed_duplicates() {
+        let mut rng = ChaChaRng::from_seed([7u8; 32]);
+        let stats = GossipStats::default();
+        let budgets = IngressBudgets::default();
+        let origin = Keypair::new();
+        let staked_relayer = Pubkey::new_unique();
+        let staked_addr = SocketAddr::from((Ipv4Addr::new(10, 0, 0, 1), 8001));
+        let stakes = Arc::new(HashMap::from([(staked_relayer, 100)]));
+        let gossip_ip = |pubkey: &Pubkey| (pubkey == &staked_relayer).then_some(staked_addr.ip());
+
+        // An unstaked node replays a value of the origin many times. It
+        // exhausts its own budget, not the budget of the origin.
+        let replayer = Pubkey::new_unique();
+        let replayer_addr = SocketAddr::from((Ipv4Addr::new(10, 0, 0, 2), 8001));
+        let value = CrdsValue::new_rand(&mut rng, Some(&origin));
+        let messages = new_push_messages(replayer_addr, &replayer, &value, 1000);
+        let num_accepted = budgets
+            .filter_messages(messages, &stakes, gossip_ip, &stats)
+            .len();
+        assert!(num_accepted > 0);
+        assert!(num_accepted < 1000);
+        // All but the first copy are duplicates, which are never charged to
+        // the origin.
+        let mut values = vec![value.clone(); 1000];
+        let seen = Mutex::new(HashSet::new());
+        let is_new = |value: &CrdsValue| seen.lock().unwrap().insert(value.signature);
+        budgets.filter_values(&mut values, is_new, &stats);
+        assert_eq!(values.len(), 1000);
+
+        // The same value relayed by a staked peer is accepted.
+        let messages = new_push_messages(staked_addr, &staked_relayer, &value, 1000);
+        let messages = budgets.filter_messages(messages, &stakes, gossip_ip, &stats);
+        assert_eq!(messages.len(), 1000);
+
+        // Claiming to be the staked peer from another address only draws from
+        // the shared unstaked budget.
+        let size = serialized_size(&Protocol::PushMessage(staked_relayer, vec![value.clone()]))
+            .unwrap() as usize;
+        let num_messages = UNSTAKED_TOTAL_BYTES_PER_INTERVAL * MAX_BUDGET_MULTIPLE / size + 1;
+        let messages = new_push_messages(replayer_addr, &staked_relayer, &value, num_messages);
+        let num_spoofed = budgets
+            .filter_messages(messages, &stakes, gossip_ip, &stats)
+            .len();
+        assert!(num_spoofed < num_messages);
+
+        let talkers = budgets.top_talkers(usize::MAX);
+        let talker = |pubkey: &Pubkey| talkers.iter().find(|talker| &talker.pubkey == pubkey);
+        let staked = talker(&staked_relayer).unwrap();
+        assert_eq!(staked.stake, 100);
+        assert_eq!(staked.messages_received, 1000);
+        assert_eq!(staked.messages_dropped, 0);
+        let replayer = talker(&replayer).unwrap();
+        assert_eq!(replayer.messages_received, 1000);
+        assert_eq!(replayer.messages_dropped, 1000 - num_accepted as u64);
+        let origin = talker(&origin.pubkey()).unwrap();
+        assert_eq!(origin.messages_received, 0);
+        assert_eq!(origin.values_received, 1);
+        assert_eq!(origin.values_dropped, 0);
+    }
+
+    #[test]
+    fn test_key_rotation() {
+        const NUM_KEYS: usize = 20_000;
+        let mut rng = ChaChaRng::from_seed([11u8; 32]);
+        let stats = GossipStats::default();
+        let budgets = IngressBudgets::default();
+        let stakes = Arc::default();
+        let addr = SocketAddr::from((Ipv4Addr::new(10, 0, 0, 3), 8001));
+        // Allows for one refill of the shared budget while the test runs.
+        let max_bytes = UNSTAKED_TOTAL_BYTES_PER_INTERVAL * (MAX_BUDGET_MULTIPLE + 1);
+        // An attacker rotating keypairs is limited by the shared unstaked
+        // budget, both as a relayer and as an origin.
+        let mut values: Vec<_> = std::iter::repeat_with(|| {
+            let keypair = Keypair::new();
+            CrdsValue::new_rand(&mut rng, Some(&keypair))
+        })
+        .take(NUM_KEYS)
+        .collect();
+        let messages: Vec<_> = values
+            .iter()
+            .map(|value| {
+                (
+                    addr,
+                    Protocol::PushMessage(value.pubkey(), vec![value.clone()]),
+                )
+            })
+            .collect();
+        let messages = budgets.filter_messages(messages, &stakes, |_| None, &stats);
+        assert!(!messages.is_empty());
+        assert!(messages.len() < NUM_KEYS);
+        let size: u64 = messages
+            .iter()
+            .map(|(_, message)| serialized_size(message).unwrap())
+            .sum();
+        assert!(size as usize <= max_bytes);
+        budgets.filter_values(&mut values, |_| true, &stats);
+        assert!(!values.is_empty());
+        assert!(values.len() < NUM_KEYS);
+        let size: u64 = values
+            .iter()
+            .map(|value| serialized_size(value).unwrap())
+            .sum();
+        assert!(size as usize <= max_bytes);
+        // Unstaked nodes do not evict staked ones.
+        let staked = Pubkey::new_unique();
+        let mut budgets = budgets.0.lock().unwrap();
+        budgets.set_stakes(&Arc::new(HashMap::from([(staked, 100)])));
+        let bytes = budgets.bytes_per_interval(100);
+        assert!(budgets.peers.take(staked, 100, bytes, 1000, false));
+        for _ in 0..MAX_NUM_UNSTAKED_NODES {
+            let pubkey = Pubkey::new_unique();
+            let _ = budgets
+                .peers
+                .take(pubkey, 0, UNSTAKED_BYTES_PER_INTERVAL, 1, false);
+        }
+        assert_eq!(budgets.peers.get(&staked).unwrap().num_received, 1);
+    }
+
+    #[test]
+    fn test_bytes_per_interval() {
+        let staked = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        let budgets = IngressBudgets::default();
+        let mut budgets = budgets.0.lock().unwrap();
+        budgets.set_stakes(&Arc::new(HashMap::from([(staked, 25), (other, 75)])));
+        assert_eq!(budgets.total_stake, 100);
+        assert_eq!(
+            budgets.bytes_per_interval(budgets.stake(&staked)),
+            UNSTAKED_BYTES_PER_INTERVAL + STAKED_BYTES_PER_INTERVAL / 4
+        );
+        assert_eq!(
+            budgets.bytes_per_interval(budgets.stake(&Pubkey::new_unique())),
+            UNSTAKED_BYTES_PER_INTERVAL
+        );
+    }
+}
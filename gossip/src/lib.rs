@@ -4,6 +4,7 @@
 pub mod cluster_info;
 pub mod cluster_info_metrics;
 pub mod contact_info;
+pub mod crawler;
 pub mod crds;
 pub mod crds_entry;
 pub mod crds_gossip;
@@ -18,7 +19,9 @@ pub mod duplicate_shred_handler;
 pub mod duplicate_shred_listener;
 pub mod epoch_slots;
 pub mod gossip_error;
+pub mod gossip_recorder;
 pub mod gossip_service;
+pub mod ingress_budget;
 #[macro_use]
 pub mod legacy_contact_info;
 pub mod ping_pong;
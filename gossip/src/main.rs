@@ -10,15 +10,27 @@ use {
         input_parsers::{keypair_of, pubkeys_of},
         input_validators::{is_keypair_or_ask_keyword, is_port, is_pubkey},
     },
+    solana_client::rpc_client::RpcClient,
     solana_gossip::{
-        gossip_service::discover, legacy_contact_info::LegacyContactInfo as ContactInfo,
+        crawler::CrawlSnapshot,
+        gossip_service::{discover, make_gossip_node},
+        legacy_contact_info::LegacyContactInfo as ContactInfo,
     },
-    solana_sdk::pubkey::Pubkey,
+    solana_sdk::{pubkey::Pubkey, signature::Keypair},
     solana_streamer::socket::SocketAddrSpace,
     std::{
+        collections::HashMap,
         error,
+        fs::File,
+        io::{self, BufWriter, Write},
         net::{IpAddr, Ipv4Addr, SocketAddr},
+        path::{Path, PathBuf},
         process::exit,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+        thread::sleep,
         time::Duration,
     },
 };
@@ -154,6 +166,70 @@ fn parse_matches() -> ArgMatches<'static> {
                         .help("Maximum time to wait in seconds [default: wait forever]"),
                 ),
         )
+        .subcommand(
+            SubCommand::with_name("crawl")
+                .about("Join the cluster as a spy and dump snapshots of its gossip table")
+                .setting(AppSettings::DisableVersion)
+                .arg(
+                    Arg::with_name("entrypoint")
+                        .short("n")
+                        .long("entrypoint")
+                        .value_name("HOST:PORT")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(solana_net_utils::is_host_port)
+                        .help("Rendezvous with the cluster at this entrypoint"),
+                )
+                .arg(&shred_version_arg)
+                .arg(
+                    Arg::with_name("rpc_url")
+                        .long("rpc-url")
+                        .value_name("URL")
+                        .takes_value(true)
+                        .help("Fetch stakes from this RPC node to report stake coverage"),
+                )
+                .arg(
+                    Arg::with_name("warmup")
+                        .long("warmup")
+                        .value_name("SECONDS")
+                        .takes_value(true)
+                        .default_value("30")
+                        .help("Time to spend collecting gossip values before the first snapshot"),
+                )
+                .arg(
+                    Arg::with_name("interval")
+                        .long("interval")
+                        .value_name("SECONDS")
+                        .takes_value(true)
+                        .default_value("60")
+                        .help("Time between snapshots"),
+                )
+                .arg(
+                    Arg::with_name("num_snapshots")
+                        .long("num-snapshots")
+                        .value_name("NUM")
+                        .takes_value(true)
+                        .default_value("1")
+                        .help("Number of snapshots to take, 0 to keep crawling forever"),
+                )
+                .arg(
+                    Arg::with_name("format")
+                        .long("format")
+                        .value_name("FORMAT")
+                        .takes_value(true)
+                        .possible_values(&["json", "csv"])
+                        .default_value("json")
+                        .help("Snapshot format. CSV snapshots only contain the per-node table"),
+                )
+                .arg(
+                    Arg::with_name("output_dir")
+                        .long("output-dir")
+                        .value_name("DIR")
+                        .takes_value(true)
+                        .help("Write each snapshot to a timestamped file in this directory \
+                               [default: print to stdout]"),
+                ),
+        )
         .get_matches()
 }
 
@@ -267,6 +343,101 @@ fn process_spy(matches: &ArgMatches, socket_addr_space: SocketAddrSpace) -> std:
     Ok(())
 }
 
+fn get_stakes(rpc_url: &str) -> Result<HashMap<Pubkey, u64>, Box<dyn error::Error>> {
+    let vote_accounts = RpcClient::new(rpc_url.to_string()).get_vote_accounts()?;
+    let mut stakes = HashMap::new();
+    for vote_account in vote_accounts
+        .current
+        .into_iter()
+        .chain(vote_accounts.delinquent)
+    {
+        let node_pubkey = vote_account.node_pubkey.parse()?;
+        *stakes.entry(node_pubkey).or_default() += vote_account.activated_stake;
+    }
+    Ok(stakes)
+}
+
+fn write_crawl_snapshot<W: Write>(
+    snapshot: &CrawlSnapshot,
+    format: &str,
+    mut writer: W,
+) -> Result<(), Box<dyn error::Error>> {
+    match format {
+        "csv" => snapshot.write_csv(&mut writer)?,
+        _ => {
+            serde_json::to_writer_pretty(&mut writer, snapshot)?;
+            writeln!(writer)?;
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn process_crawl(
+    matches: &ArgMatches,
+    socket_addr_space: SocketAddrSpace,
+) -> Result<(), Box<dyn error::Error>> {
+    let entrypoint_addr = parse_entrypoint(matches);
+    let shred_version = value_t_or_exit!(matches, "shred_version", u16);
+    let warmup = Duration::from_secs(value_t_or_exit!(matches, "warmup", u64));
+    let interval = Duration::from_secs(value_t_or_exit!(matches, "interval", u64));
+    let num_snapshots = value_t_or_exit!(matches, "num_snapshots", usize);
+    let format = matches.value_of("format").unwrap();
+    let output_dir = matches.value_of("output_dir").map(PathBuf::from);
+    let rpc_url = matches.value_of("rpc_url");
+    if let Some(output_dir) = &output_dir {
+        std::fs::create_dir_all(output_dir)?;
+    }
+
+    let exit = Arc::new(AtomicBool::new(false));
+    let (gossip_service, _ip_echo, cluster_info) = make_gossip_node(
+        Keypair::new(),
+        entrypoint_addr.as_ref(),
+        exit.clone(),
+        None, // gossip_addr, join as a spy
+        shred_version,
+        true, // should_check_duplicate_instance
+        socket_addr_space,
+    );
+    eprintln!("Crawling gossip as spy node {}", cluster_info.id());
+    sleep(warmup);
+
+    let mut num_taken = 0;
+    loop {
+        let stakes = rpc_url.and_then(|rpc_url| {
+            get_stakes(rpc_url)
+                .map_err(|err| eprintln!("Failed to fetch stakes from {rpc_url}: {err}"))
+                .ok()
+        });
+        let snapshot = CrawlSnapshot::new(&cluster_info, stakes.as_ref());
+        match &output_dir {
+            Some(output_dir) => {
+                let path = crawl_snapshot_path(output_dir, snapshot.timestamp, format);
+                write_crawl_snapshot(&snapshot, format, BufWriter::new(File::create(&path)?))?;
+                eprintln!(
+                    "Wrote snapshot of {} nodes to {}",
+                    snapshot.nodes.len(),
+                    path.display()
+                );
+            }
+            None => write_crawl_snapshot(&snapshot, format, io::stdout().lock())?,
+        }
+        num_taken += 1;
+        if num_taken == num_snapshots {
+            break;
+        }
+        sleep(interval);
+    }
+
+    exit.store(true, Ordering::Relaxed);
+    gossip_service.join().unwrap();
+    Ok(())
+}
+
+fn crawl_snapshot_path(output_dir: &Path, timestamp: u64, format: &str) -> PathBuf {
+    output_dir.join(format!("crawl-{timestamp}.{format}"))
+}
+
 fn parse_entrypoint(matches: &ArgMatches) -> Option<SocketAddr> {
     matches.value_of("entrypoint").map(|entrypoint| {
         solana_net_utils::parse_host_port(entrypoint).unwrap_or_else(|e| {
@@ -337,6 +508,9 @@ fn main() -> Result<(), Box<dyn error::Error>> {
         ("rpc-url", Some(matches)) => {
             process_rpc_url(matches, socket_addr_space)?;
         }
+        ("crawl", Some(matches)) => {
+            process_crawl(matches, socket_addr_space)?;
+        }
         _ => unreachable!(),
     }
 
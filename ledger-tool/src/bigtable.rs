@@ -24,7 +24,9 @@ use {
         blockstore_options::AccessType,
     },
     solana_sdk::{clock::Slot, pubkey::Pubkey, signature::Signature},
-    solana_storage_bigtable::CredentialType,
+    solana_storage_bigtable::{
+        CompressionConfig, CredentialType, LocalLedgerStorage, LongTermLedgerStorage,
+    },
     solana_transaction_status::{
         BlockEncodingOptions, ConfirmedBlock, EncodeError, EncodedConfirmedBlock,
         TransactionDetails, UiTransactionEncoding, VersionedConfirmedBlock,
@@ -32,7 +34,7 @@ use {
     std::{
         cmp::min,
         collections::HashSet,
-        path::Path,
+        path::{Path, PathBuf},
         process::exit,
         result::Result,
         str::FromStr,
@@ -45,11 +47,20 @@ async fn upload(
     starting_slot: Option<Slot>,
     ending_slot: Option<Slot>,
     force_reupload: bool,
+    ledger_archive_path: Option<PathBuf>,
     config: solana_storage_bigtable::LedgerStorageConfig,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let bigtable = solana_storage_bigtable::LedgerStorage::new_with_config(config)
-        .await
-        .map_err(|err| format!("Failed to connect to storage: {err:?}"))?;
+    let bigtable: Arc<dyn LongTermLedgerStorage> = match ledger_archive_path {
+        Some(ledger_archive_path) => Arc::new(
+            LocalLedgerStorage::open(&ledger_archive_path)
+                .map_err(|err| format!("Failed to open ledger archive: {err:?}"))?,
+        ),
+        None => Arc::new(
+            solana_storage_bigtable::LedgerStorage::new_with_config(config)
+                .await
+                .map_err(|err| format!("Failed to connect to storage: {err:?}"))?,
+        ),
+    };
 
     let config = ConfirmedBlockUploadConfig {
         force_reupload,
@@ -101,6 +112,23 @@ async fn delete_slots(
     solana_ledger::bigtable_delete::delete_confirmed_blocks(bigtable, slots, dry_run).await
 }
 
+async fn train_compression_dictionary(
+    table: &str,
+    start_at: Option<String>,
+    num_samples: usize,
+    max_dictionary_size: usize,
+    config: solana_storage_bigtable::LedgerStorageConfig,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let bigtable = solana_storage_bigtable::LedgerStorage::new_with_config(config)
+        .await
+        .map_err(|err| format!("Failed to connect to storage: {err:?}"))?;
+    let id = bigtable
+        .train_compression_dictionary(table, start_at, num_samples, max_dictionary_size)
+        .await?;
+    println!("Stored compression dictionary {id:08x} for {table}");
+    Ok(())
+}
+
 async fn first_available_block(
     config: solana_storage_bigtable::LedgerStorageConfig,
 ) -> Result<(), Box<dyn std::error::Error>> {
@@ -654,6 +682,7 @@ async fn get_bigtable(
                 instance_name: args.instance_name,
                 app_profile_id: args.app_profile_id,
                 max_message_size: solana_storage_bigtable::DEFAULT_MAX_MESSAGE_SIZE,
+                compression: CompressionConfig::default(),
             },
         )
         .await
@@ -721,6 +750,72 @@ impl BigTableSubCommand for App<'_, '_> {
                                      instance. Note: reupload will *not* delete any data from the \
                                      tx-by-addr table; Use with care.",
                                 ),
+                        )
+                        .arg(
+                            Arg::with_name("ledger_archive")
+                                .long("ledger-archive")
+                                .value_name("DIR")
+                                .takes_value(true)
+                                .help(
+                                    "Upload to a local ledger archive in this directory instead \
+                                     of BigTable, creating it if it does not exist",
+                                ),
+                        )
+                        .arg(
+                            Arg::with_name("compression")
+                                .long("compression")
+                                .value_name("TABLE=METHOD")
+                                .takes_value(true)
+                                .multiple(true)
+                                .number_of_values(1)
+                                .validator(|value| {
+                                    CompressionConfig::default().set_table_compression(&value)
+                                })
+                                .help(
+                                    "Compress the cells of TABLE with METHOD: best, none, bzip2, \
+                                     gzip, zstd or zstd-dictionary [default: best]. \
+                                     zstd-dictionary uses the table's most recently trained \
+                                     dictionary, see `train-compression-dictionary`",
+                                ),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("train-compression-dictionary")
+                        .about(
+                            "Train a zstd compression dictionary on the cells of a table and \
+                             store it in the dictionaries table",
+                        )
+                        .arg(
+                            Arg::with_name("table")
+                                .index(1)
+                                .value_name("TABLE")
+                                .takes_value(true)
+                                .required(true)
+                                .possible_values(&CompressionConfig::TABLES)
+                                .help("Table to train the dictionary for"),
+                        )
+                        .arg(
+                            Arg::with_name("start_at")
+                                .long("start-at")
+                                .value_name("ROW_KEY")
+                                .takes_value(true)
+                                .help("Sample cells starting at this row key [default: first row]"),
+                        )
+                        .arg(
+                            Arg::with_name("num_samples")
+                                .long("num-samples")
+                                .value_name("NUM")
+                                .takes_value(true)
+                                .default_value("1000")
+                                .help("Number of rows to sample"),
+                        )
+                        .arg(
+                            Arg::with_name("max_dictionary_size")
+                                .long("max-dictionary-size")
+                                .value_name("BYTES")
+                                .takes_value(true)
+                                .default_value("112640")
+                                .help("Maximum size of the dictionary"),
                         ),
                 )
                 .subcommand(
@@ -1097,15 +1192,23 @@ pub fn bigtable_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) {
             let starting_slot = value_t!(arg_matches, "starting_slot", Slot).ok();
             let ending_slot = value_t!(arg_matches, "ending_slot", Slot).ok();
             let force_reupload = arg_matches.is_present("force_reupload");
+            let ledger_archive_path = value_t!(arg_matches, "ledger_archive", PathBuf).ok();
             let blockstore = crate::open_blockstore(
                 &canonicalize_ledger_path(ledger_path),
                 arg_matches,
                 AccessType::Secondary,
             );
+            let mut compression = CompressionConfig::default();
+            for table_compression in arg_matches.values_of("compression").unwrap_or_default() {
+                compression
+                    .set_table_compression(table_compression)
+                    .unwrap();
+            }
             let config = solana_storage_bigtable::LedgerStorageConfig {
                 read_only: false,
                 instance_name,
                 app_profile_id,
+                compression,
                 ..solana_storage_bigtable::LedgerStorageConfig::default()
             };
             runtime.block_on(upload(
@@ -1113,6 +1216,7 @@ pub fn bigtable_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) {
                 starting_slot,
                 ending_slot,
                 force_reupload,
+                ledger_archive_path,
                 config,
             ))
         }
@@ -1126,6 +1230,25 @@ pub fn bigtable_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) {
             };
             runtime.block_on(delete_slots(slots, config))
         }
+        ("train-compression-dictionary", Some(arg_matches)) => {
+            let table = value_t_or_exit!(arg_matches, "table", String);
+            let start_at = value_t!(arg_matches, "start_at", String).ok();
+            let num_samples = value_t_or_exit!(arg_matches, "num_samples", usize);
+            let max_dictionary_size = value_t_or_exit!(arg_matches, "max_dictionary_size", usize);
+            let config = solana_storage_bigtable::LedgerStorageConfig {
+                read_only: false,
+                instance_name,
+                app_profile_id,
+                ..solana_storage_bigtable::LedgerStorageConfig::default()
+            };
+            runtime.block_on(train_compression_dictionary(
+                &table,
+                start_at,
+                num_samples,
+                max_dictionary_size,
+                config,
+            ))
+        }
         ("first-available-block", Some(_arg_matches)) => {
             let config = solana_storage_bigtable::LedgerStorageConfig {
                 read_only: true,
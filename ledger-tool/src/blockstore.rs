@@ -2,10 +2,19 @@
 
 use {
     crate::{
+        args::parse_process_options,
         error::{LedgerToolError, Result},
         ledger_path::canonicalize_ledger_path,
-        ledger_utils::{get_program_ids, get_shred_storage_type},
-        output::{output_ledger, output_slot, SlotBounds, SlotInfo},
+        ledger_utils::{
+            get_program_ids, get_shred_storage_type, load_and_process_ledger,
+            open_genesis_config_by,
+        },
+        output::{
+            is_json, output_ledger, output_slot, BlockstoreCheckOutput, CliSlot,
+            ColumnStorageReport, ColumnStorageStats, JsonArrayWriter, SlotBounds, SlotInfo,
+            SstLevelStats,
+        },
+        rebuild_tx_index::rebuild_tx_index,
     },
     chrono::{DateTime, Utc},
     clap::{
@@ -20,20 +29,29 @@ use {
     solana_ledger::{
         ancestor_iterator::AncestorIterator,
         blockstore::{Blockstore, PurgeType},
-        blockstore_db::{self, Column, ColumnName, Database},
+        blockstore_archive::{
+            export_blockstore_archive, import_blockstore_archive, verify_blockstore_archive,
+        },
+        blockstore_db::{
+            self, is_slot_keyed_column, Column, ColumnName, Database, IteratorDirection,
+            IteratorMode,
+        },
         blockstore_options::{AccessType, BLOCKSTORE_DIRECTORY_ROCKS_FIFO},
+        blockstore_tailer::{BlockstoreTailer, TailedBlock},
+        leader_schedule_cache::LeaderScheduleCache,
         shred::Shred,
+        use_snapshot_archives_at_startup,
     },
     solana_sdk::{
         clock::{Slot, UnixTimestamp},
         hash::Hash,
     },
     std::{
-        collections::{BTreeMap, BTreeSet, HashMap},
+        collections::{BTreeMap, BTreeSet, HashMap, HashSet},
         fs::File,
         io::{stdout, BufRead, BufReader, Write},
         path::{Path, PathBuf},
-        sync::atomic::AtomicBool,
+        sync::{atomic::AtomicBool, Arc},
         time::{Duration, UNIX_EPOCH},
     },
 };
@@ -113,6 +131,58 @@ fn analyze_column<
     Ok(())
 }
 
+fn print_tailed_block(tailed_block: &TailedBlock, output_format: &OutputFormat) -> Result<()> {
+    let TailedBlock { slot, block } = tailed_block;
+    match output_format {
+        OutputFormat::Json | OutputFormat::JsonCompact => {
+            // One object per line, for consumption by indexers
+            let transactions = block
+                .transactions
+                .iter()
+                .map(|transaction| {
+                    json!({
+                        "signature": transaction.transaction.signatures[0].to_string(),
+                        "err": transaction.meta.status.as_ref().err(),
+                        "fee": transaction.meta.fee,
+                    })
+                })
+                .collect::<Vec<_>>();
+            println!(
+                "{}",
+                json!({
+                    "slot": slot,
+                    "parentSlot": block.parent_slot,
+                    "blockhash": block.blockhash,
+                    "previousBlockhash": block.previous_blockhash,
+                    "blockTime": block.block_time,
+                    "blockHeight": block.block_height,
+                    "transactions": transactions,
+                })
+            );
+        }
+        _ => {
+            let num_failed = block
+                .transactions
+                .iter()
+                .filter(|transaction| transaction.meta.status.is_err())
+                .count();
+            println!(
+                "Slot {slot}: blockhash {}, parent {}, {} transactions ({num_failed} failed), \
+                 block time {}",
+                block.blockhash,
+                block.parent_slot,
+                block.transactions.len(),
+                block
+                    .block_time
+                    .map(|block_time| block_time.to_string())
+                    .unwrap_or_else(|| "unknown".to_string()),
+            );
+        }
+    }
+    stdout().flush()?;
+    Ok(())
+}
+
 fn analyze_storage(database: &Database) -> Result<()> {
     use solana_ledger::blockstore_db::columns::*;
     analyze_column::<SlotMeta>(database, "SlotMeta")?;
@@ -137,6 +207,166 @@ fn analyze_storage(database: &Database) -> Result<()> {
     analyze_column::<OptimisticSlots>(database, "OptimisticSlots")
 }
 
+fn column_storage_stats<C>(
+    db: &Database,
+    starting_slot: Slot,
+    ending_slot: Slot,
+) -> Result<ColumnStorageStats>
+where
+    C: Column + ColumnName,
+    C::Index: Copy,
+{
+    use solana_ledger::blockstore_db::columns as cf;
+    // Columns keyed by slot are scanned from the starting slot, the others in full
+    let slot_keyed = is_slot_keyed_column(C::NAME);
+    let has_slot = !matches!(
+        C::NAME,
+        cf::TransactionStatusIndex::NAME | cf::ProgramCosts::NAME
+    );
+    let iterator_mode = if slot_keyed {
+        IteratorMode::From(C::as_index(starting_slot), IteratorDirection::Forward)
+    } else {
+        IteratorMode::Start
+    };
+
+    let mut stats = ColumnStorageStats {
+        column: C::NAME.to_string(),
+        ..ColumnStorageStats::default()
+    };
+    let mut slots = HashSet::new();
+    for (index, value) in db.iter::<C>(iterator_mode)? {
+        if has_slot {
+            let slot = C::slot(index);
+            if slot > ending_slot && slot_keyed {
+                break;
+            }
+            if !(starting_slot..=ending_slot).contains(&slot) {
+                continue;
+            }
+            slots.insert(slot);
+        }
+        stats.num_keys += 1;
+        stats.key_bytes += C::key(index).len() as u64;
+        stats.value_bytes += value.len() as u64;
+    }
+    if has_slot {
+        let num_slots = slots.len() as u64;
+        stats.num_slots = Some(num_slots);
+        stats.bytes_per_slot = Some(
+            (stats.key_bytes + stats.value_bytes)
+                .checked_div(num_slots)
+                .unwrap_or_default(),
+        );
+    }
+
+    let mut levels = BTreeMap::<i32, SstLevelStats>::new();
+    for file in db
+        .live_files_metadata()?
+        .iter()
+        .filter(|file| file.column_family_name == C::NAME)
+    {
+        let level = levels.entry(file.level).or_insert_with(|| SstLevelStats {
+            level: file.level,
+            num_files_in_range: slot_keyed.then_some(0),
+            bytes_in_range: slot_keyed.then_some(0),
+            ..SstLevelStats::default()
+        });
+        level.num_files += 1;
+        level.total_bytes += file.size as u64;
+        level.num_entries += file.num_entries;
+        level.num_deletions += file.num_deletions;
+        if slot_keyed {
+            let first_slot = file
+                .start_key
+                .as_deref()
+                .and_then(|key| raw_key_to_slot(key, C::NAME));
+            let last_slot = file
+                .end_key
+                .as_deref()
+                .and_then(|key| raw_key_to_slot(key, C::NAME));
+            if let (Some(first_slot), Some(last_slot)) = (first_slot, last_slot) {
+                if first_slot <= ending_slot && last_slot >= starting_slot {
+                    *level.num_files_in_range.get_or_insert(0) += 1;
+                    *level.bytes_in_range.get_or_insert(0) += file.size as u64;
+                }
+            }
+        }
+    }
+    let column = db.column::<C>();
+    for level in levels.values_mut() {
+        let property = format!("rocksdb.compression-ratio-at-level{}", level.level);
+        // RocksDB reports a negative ratio for levels without files
+        level.compression_ratio = column
+            .get_property(&property)?
+            .and_then(|ratio| ratio.parse::<f64>().ok())
+            .filter(|ratio| *ratio > 0.0);
+    }
+    stats.sst_levels = levels.into_values().collect();
+
+    Ok(stats)
+}
+
+fn analyze_column_storage(
+    db: &Database,
+    columns: &[String],
+    starting_slot: Slot,
+    ending_slot: Slot,
+) -> Result<ColumnStorageReport> {
+    use solana_ledger::blockstore_db::columns::*;
+    let mut report = ColumnStorageReport {
+        starting_slot,
+        ending_slot,
+        columns: vec![],
+    };
+    macro_rules! analyze {
+        ($($column:ty),+ $(,)?) => {
+            // Check every name before scanning, which can take a long time
+            let known_columns = [$(<$column>::NAME),+];
+            if let Some(unknown) = columns
+                .iter()
+                .find(|name| !known_columns.contains(&name.as_str()))
+            {
+                return Err(LedgerToolError::BadArgument(format!(
+                    "unknown column {unknown}"
+                )));
+            }
+            $(
+                if columns.is_empty() || columns.iter().any(|name| name == <$column>::NAME) {
+                    report.columns.push(column_storage_stats::<$column>(
+                        db,
+                        starting_slot,
+                        ending_slot,
+                    )?);
+                }
+            )+
+        };
+    }
+    analyze!(
+        SlotMeta,
+        Orphans,
+        DeadSlots,
+        DuplicateSlots,
+        ErasureMeta,
+        BankHash,
+        Root,
+        Index,
+        ShredData,
+        ShredCode,
+        TransactionStatus,
+        AddressSignatures,
+        TransactionMemos,
+        TransactionStatusIndex,
+        Rewards,
+        Blocktime,
+        PerfSamples,
+        BlockHeight,
+        ProgramCosts,
+        OptimisticSlots,
+        MerkleRootMeta,
+    );
+    Ok(report)
+}
+
 fn raw_key_to_slot(key: &[u8], column_name: &str) -> Option<Slot> {
     use solana_ledger::blockstore_db::columns as cf;
     match column_name {
@@ -166,6 +396,7 @@ fn raw_key_to_slot(key: &[u8], column_name: &str) -> Option<Slot> {
         cf::OptimisticSlots::NAME => {
             Some(cf::OptimisticSlots::slot(cf::OptimisticSlots::index(key)))
         }
+        cf::MerkleRootMeta::NAME => Some(cf::MerkleRootMeta::slot(cf::MerkleRootMeta::index(key))),
         &_ => None,
     }
 }
@@ -237,30 +468,54 @@ fn get_latest_optimistic_slots(
     }
 }
 
-fn print_blockstore_file_metadata(blockstore: &Blockstore, file_name: &Option<&str>) -> Result<()> {
+fn print_blockstore_file_metadata(
+    blockstore: &Blockstore,
+    file_name: &Option<&str>,
+    output_format: OutputFormat,
+) -> Result<()> {
     let live_files = blockstore.live_files_metadata()?;
+    let mut json_writer = is_json(&output_format)
+        .then(|| JsonArrayWriter::new(stdout(), output_format))
+        .transpose()?;
 
     // All files under live_files_metadata are prefixed with "/".
     let sst_file_name = file_name.as_ref().map(|name| format!("/{name}"));
+    let mut found = false;
     for file in live_files {
         if sst_file_name.is_none() || file.name.eq(sst_file_name.as_ref().unwrap()) {
-            println!(
-                "[{}] cf_name: {}, level: {}, start_slot: {:?}, end_slot: {:?}, size: {}, \
+            if let Some(json_writer) = json_writer.as_mut() {
+                json_writer.write(&json!({
+                    "name": file.name,
+                    "cfName": file.column_family_name,
+                    "level": file.level,
+                    "startSlot": raw_key_to_slot(&file.start_key.unwrap(), &file.column_family_name),
+                    "endSlot": raw_key_to_slot(&file.end_key.unwrap(), &file.column_family_name),
+                    "size": file.size,
+                    "numEntries": file.num_entries,
+                }))?;
+            } else {
+                println!(
+                    "[{}] cf_name: {}, level: {}, start_slot: {:?}, end_slot: {:?}, size: {}, \
                  num_entries: {}",
-                file.name,
-                file.column_family_name,
-                file.level,
-                raw_key_to_slot(&file.start_key.unwrap(), &file.column_family_name),
-                raw_key_to_slot(&file.end_key.unwrap(), &file.column_family_name),
-                file.size,
-                file.num_entries,
-            );
+                    file.name,
+                    file.column_family_name,
+                    file.level,
+                    raw_key_to_slot(&file.start_key.unwrap(), &file.column_family_name),
+                    raw_key_to_slot(&file.end_key.unwrap(), &file.column_family_name),
+                    file.size,
+                    file.num_entries,
+                );
+            }
+            found = true;
             if sst_file_name.is_some() {
-                return Ok(());
+                break;
             }
         }
     }
-    if sst_file_name.is_some() {
+    if let Some(json_writer) = json_writer {
+        json_writer.finish()?;
+    }
+    if sst_file_name.is_some() && !found {
         return Err(LedgerToolError::BadArgument(format!(
             "failed to find or load the metadata of the specified file {file_name:?}"
         )));
@@ -268,7 +523,61 @@ fn print_blockstore_file_metadata(blockstore: &Blockstore, file_name: &Option<&s
     Ok(())
 }
 
-pub trait BlockstoreSubCommand {
+pub fn check_blockstore(ledger_path: &Path, arg_matches: &ArgMatches<'_>) -> Result<()> {
+    let output_format = OutputFormat::from_matches(arg_matches, "output_format", false);
+    let starting_slot = value_t_or_exit!(arg_matches, "starting_slot", Slot);
+    let ending_slot = value_t!(arg_matches, "ending_slot", Slot).unwrap_or(Slot::MAX);
+    let fix = arg_matches.is_present("fix");
+    // Fixing writes to the Orphans and Root columns, which needs primary access
+    let access_type = if fix {
+        AccessType::PrimaryForMaintenance
+    } else {
+        AccessType::Secondary
+    };
+    let blockstore = Arc::new(crate::open_blockstore(
+        ledger_path,
+        arg_matches,
+        access_type,
+    ));
+
+    let report = if arg_matches.is_present("verify_signatures") {
+        let mut process_options = parse_process_options(ledger_path, arg_matches);
+        // Only the snapshot bank is needed for its leader schedule
+        process_options.halt_at_slot = Some(0);
+        let (bank_forks, _) = load_and_process_ledger(
+            arg_matches,
+            &open_genesis_config_by(ledger_path, arg_matches),
+            blockstore.clone(),
+            process_options,
+            value_t!(arg_matches, "snapshots", String)
+                .ok()
+                .map(PathBuf::from),
+            value_t!(arg_matches, "incremental_snapshot_archive_path", String)
+                .ok()
+                .map(PathBuf::from),
+            None,
+        )?;
+        let bank = bank_forks.read().unwrap().working_bank();
+        let leader_schedule_cache = LeaderScheduleCache::new_from_bank(&bank);
+        let slot_leader = |slot| leader_schedule_cache.slot_leader_at(slot, Some(bank.as_ref()));
+        blockstore.check(starting_slot, ending_slot, Some(&slot_leader))?
+    } else {
+        blockstore.check(starting_slot, ending_slot, None)?
+    };
+
+    let num_fixed = if fix {
+        Some(blockstore.fix_check_issues(&report.issues)?)
+    } else {
+        None
+    };
+    println!(
+        "{}",
+        output_format.formatted_string(&BlockstoreCheckOutput { report, num_fixed })
+    );
+    Ok(())
+}
+
+trait BlockstoreSubCommand {
     fn blockstore_subcommand(self) -> Self;
 }
 
@@ -279,11 +588,174 @@ impl BlockstoreSubCommand for App<'_, '_> {
                 .about("Commands to interact with a local Blockstore")
                 .setting(AppSettings::InferSubcommands)
                 .setting(AppSettings::SubcommandRequiredElseHelp)
-                .subcommands(blockstore_subcommands(false)),
+                .subcommands(blockstore_subcommands(false))
+                .subcommands(blockstore_only_subcommands()),
         )
     }
 }
 
+/// Subcommands that were never top level subcommands of the binary, and so have no hidden
+/// legacy counterpart
+fn blockstore_only_subcommands<'a, 'b>() -> Vec<App<'a, 'b>> {
+    let starting_slot_arg = Arg::with_name("starting_slot")
+        .long("starting-slot")
+        .value_name("SLOT")
+        .takes_value(true)
+        .default_value("0")
+        .help("Start at this slot");
+    let ending_slot_arg = Arg::with_name("ending_slot")
+        .long("ending-slot")
+        .value_name("SLOT")
+        .takes_value(true)
+        .help("The last slot to iterate to");
+    let max_genesis_archive_unpacked_size_arg = Arg::with_name("max_genesis_archive_unpacked_size")
+        .long("max-genesis-archive-unpacked-size")
+        .value_name("NUMBER")
+        .takes_value(true)
+        .default_value("10485760")
+        .help("maximum total uncompressed size of unpacked genesis archive");
+    let use_snapshot_archives_at_startup_arg =
+        Arg::with_name(use_snapshot_archives_at_startup::cli::NAME)
+            .long(use_snapshot_archives_at_startup::cli::LONG_ARG)
+            .takes_value(true)
+            .possible_values(use_snapshot_archives_at_startup::cli::POSSIBLE_VALUES)
+            .default_value(use_snapshot_archives_at_startup::cli::default_value_for_ledger_tool())
+            .help(use_snapshot_archives_at_startup::cli::HELP)
+            .long_help(use_snapshot_archives_at_startup::cli::LONG_HELP);
+
+    vec![
+        SubCommand::with_name("analyze-columns")
+            .about(
+                "Print per column storage statistics over a range of slots: key counts and \
+                 sizes, bytes per slot, and the SST files and compression ratio of each level",
+            )
+            .arg(&starting_slot_arg)
+            .arg(&ending_slot_arg)
+            .arg(
+                Arg::with_name("column")
+                    .long("column")
+                    .value_name("NAME")
+                    .takes_value(true)
+                    .multiple(true)
+                    .help("Only analyze this column, such as data_shred [default: all columns]"),
+            ),
+        SubCommand::with_name("export")
+            .about(
+                "Export the rooted slots of a range, with their transaction statuses, rewards, \
+                 block times and address signatures, to a checksummed archive file",
+            )
+            .arg(&starting_slot_arg)
+            .arg(&ending_slot_arg)
+            .arg(
+                Arg::with_name("output_file")
+                    .long("output-file")
+                    .short("o")
+                    .value_name("FILE")
+                    .takes_value(true)
+                    .required(true)
+                    .help("Write the archive to this file"),
+            ),
+        SubCommand::with_name("import")
+            .about(
+                "Import an archive written by `blockstore export` into the ledger, which must \
+                 not contain any slots",
+            )
+            .arg(
+                Arg::with_name("input_file")
+                    .long("input-file")
+                    .short("i")
+                    .value_name("FILE")
+                    .takes_value(true)
+                    .required(true)
+                    .help("Archive to import"),
+            )
+            .arg(
+                Arg::with_name("verify_only")
+                    .long("verify-only")
+                    .takes_value(false)
+                    .help("Only check the format and checksum of the archive"),
+            ),
+        SubCommand::with_name("tail")
+            .about(
+                "Follow the blocks rooted by a running validator, printing each new rooted \
+                 block with its transaction statuses",
+            )
+            .arg(
+                Arg::with_name("starting_slot")
+                    .long("starting-slot")
+                    .value_name("SLOT")
+                    .takes_value(true)
+                    .help("Start at this slot [default: the slot after the latest root]"),
+            )
+            .arg(
+                Arg::with_name("num_blocks")
+                    .long("num-blocks")
+                    .value_name("NUM")
+                    .takes_value(true)
+                    .help("Exit after printing this many blocks [default: never exit]"),
+            )
+            .arg(
+                Arg::with_name("poll_interval_ms")
+                    .long("poll-interval-ms")
+                    .value_name("MILLISECONDS")
+                    .takes_value(true)
+                    .default_value("400")
+                    .help("Check for newly rooted blocks at this interval"),
+            ),
+        SubCommand::with_name("rebuild-tx-index")
+            .about(
+                "Rebuild the transaction statuses, address signatures, memos, rewards and block \
+                 times of a range of slots by replaying it from a snapshot below the range. An \
+                 interrupted rebuild resumes from the last slot it completed. The validator must \
+                 not be running.",
+            )
+            .arg(
+                Arg::with_name("starting_slot")
+                    .long("starting-slot")
+                    .alias("start")
+                    .value_name("SLOT")
+                    .takes_value(true)
+                    .required(true)
+                    .help("First slot to rebuild"),
+            )
+            .arg(
+                Arg::with_name("ending_slot")
+                    .long("ending-slot")
+                    .alias("end")
+                    .value_name("SLOT")
+                    .takes_value(true)
+                    .required(true)
+                    .help("Last slot to rebuild"),
+            )
+            .arg(&max_genesis_archive_unpacked_size_arg)
+            .arg(&use_snapshot_archives_at_startup_arg),
+        SubCommand::with_name("check")
+            .about(
+                "Check the blockstore for corruption: every slot meta against the index, erasure \
+                 meta and merkle root meta columns and the stored shreds, the merkle proofs of \
+                 the shreds, the entries of full slots, and the orphans and roots",
+            )
+            .arg(&starting_slot_arg)
+            .arg(&ending_slot_arg)
+            .arg(
+                Arg::with_name("verify_signatures")
+                    .long("verify-signatures")
+                    .takes_value(false)
+                    .help(
+                        "Also verify the shred signatures against the leader schedule of the \
+                         highest snapshot. Shreds of epochs that the snapshot does not know the \
+                         leader schedule of are counted as unverified",
+                    ),
+            )
+            .arg(Arg::with_name("fix").long("fix").takes_value(false).help(
+                "Fix the orphans that are inconsistent with the slot metas. Roots without a \
+                         slot meta are only reported. The validator must not be running",
+            ))
+            .arg(&max_genesis_archive_unpacked_size_arg)
+            .arg(&use_snapshot_archives_at_startup_arg),
+    ]
+}
+
 pub fn blockstore_subcommands<'a, 'b>(hidden: bool) -> Vec<App<'a, 'b>> {
     let hidden = if hidden {
         vec![AppSettings::Hidden]
@@ -611,6 +1083,20 @@ fn do_blockstore_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) -
         ("analyze-storage", Some(arg_matches)) => analyze_storage(
             &crate::open_blockstore(&ledger_path, arg_matches, AccessType::Secondary).db(),
         )?,
+        ("analyze-columns", Some(arg_matches)) => {
+            let output_format = OutputFormat::from_matches(arg_matches, "output_format", false);
+            let starting_slot = value_t_or_exit!(arg_matches, "starting_slot", Slot);
+            let ending_slot = value_t!(arg_matches, "ending_slot", Slot).unwrap_or(Slot::MAX);
+            let columns = arg_matches
+                .values_of("column")
+                .map(|columns| columns.map(String::from).collect::<Vec<_>>())
+                .unwrap_or_default();
+            let blockstore =
+                crate::open_blockstore(&ledger_path, arg_matches, AccessType::Secondary);
+            let report =
+                analyze_column_storage(&blockstore.db(), &columns, starting_slot, ending_slot)?;
+            println!("{}", output_format.formatted_string(&report));
+        }
         ("bounds", Some(arg_matches)) => {
             let output_format = OutputFormat::from_matches(arg_matches, "output_format", false);
             let all = arg_matches.is_present("all");
@@ -702,31 +1188,135 @@ fn do_blockstore_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) -
             }
         }
         ("dead-slots", Some(arg_matches)) => {
+            let output_format = OutputFormat::from_matches(arg_matches, "output_format", false);
             let blockstore =
                 crate::open_blockstore(&ledger_path, arg_matches, AccessType::Secondary);
             let starting_slot = value_t_or_exit!(arg_matches, "starting_slot", Slot);
-            for slot in blockstore.dead_slots_iterator(starting_slot)? {
-                println!("{slot}");
+            if is_json(&output_format) {
+                let mut json_writer = JsonArrayWriter::new(stdout(), output_format)?;
+                for slot in blockstore.dead_slots_iterator(starting_slot)? {
+                    json_writer.write(&slot)?;
+                }
+                json_writer.finish()?;
+            } else {
+                for slot in blockstore.dead_slots_iterator(starting_slot)? {
+                    println!("{slot}");
+                }
             }
         }
         ("duplicate-slots", Some(arg_matches)) => {
+            let output_format = OutputFormat::from_matches(arg_matches, "output_format", false);
             let blockstore =
                 crate::open_blockstore(&ledger_path, arg_matches, AccessType::Secondary);
             let starting_slot = value_t_or_exit!(arg_matches, "starting_slot", Slot);
-            for slot in blockstore.duplicate_slots_iterator(starting_slot)? {
-                println!("{slot}");
+            if is_json(&output_format) {
+                let mut json_writer = JsonArrayWriter::new(stdout(), output_format)?;
+                for slot in blockstore.duplicate_slots_iterator(starting_slot)? {
+                    json_writer.write(&slot)?;
+                }
+                json_writer.finish()?;
+            } else {
+                for slot in blockstore.duplicate_slots_iterator(starting_slot)? {
+                    println!("{slot}");
+                }
+            }
+        }
+        ("export", Some(arg_matches)) => {
+            let starting_slot = value_t_or_exit!(arg_matches, "starting_slot", Slot);
+            let output_file = PathBuf::from(value_t_or_exit!(arg_matches, "output_file", String));
+            let blockstore =
+                crate::open_blockstore(&ledger_path, arg_matches, AccessType::Secondary);
+            let ending_slot = value_t!(arg_matches, "ending_slot", Slot)
+                .unwrap_or_else(|_| blockstore.max_root());
+            if ending_slot < starting_slot {
+                return Err(LedgerToolError::BadArgument(format!(
+                    "starting slot {starting_slot} should be less than or equal to \
+                    ending slot {ending_slot}"
+                )));
             }
+
+            let summary =
+                export_blockstore_archive(&blockstore, starting_slot, ending_slot, &output_file)?;
+            println!(
+                "Exported {} rooted slots in {}..={} ({} shreds, {} transactions) to {}",
+                summary.num_slots,
+                summary.start_slot,
+                summary.end_slot,
+                summary.num_shreds,
+                summary.num_transactions,
+                output_file.display(),
+            );
         }
+        ("import", Some(arg_matches)) => {
+            let input_file = PathBuf::from(value_t_or_exit!(arg_matches, "input_file", String));
+            let summary = if arg_matches.is_present("verify_only") {
+                verify_blockstore_archive(&input_file)?
+            } else {
+                let blockstore =
+                    crate::open_blockstore(&ledger_path, arg_matches, AccessType::Primary);
+                import_blockstore_archive(&blockstore, &input_file)?
+            };
+            println!(
+                "{} {} rooted slots in {}..={} ({} shreds, {} transactions) from {}",
+                if arg_matches.is_present("verify_only") {
+                    "Verified"
+                } else {
+                    "Imported"
+                },
+                summary.num_slots,
+                summary.start_slot,
+                summary.end_slot,
+                summary.num_shreds,
+                summary.num_transactions,
+                input_file.display(),
+            );
+        }
+        ("tail", Some(arg_matches)) => {
+            let output_format = OutputFormat::from_matches(arg_matches, "output_format", false);
+            let num_blocks = value_t!(arg_matches, "num_blocks", usize).unwrap_or(usize::MAX);
+            let poll_interval =
+                Duration::from_millis(value_t_or_exit!(arg_matches, "poll_interval_ms", u64));
+            let blockstore = Arc::new(crate::open_blockstore(
+                &ledger_path,
+                arg_matches,
+                AccessType::Secondary,
+            ));
+            let tailer = match value_t!(arg_matches, "starting_slot", Slot) {
+                Ok(starting_slot) => BlockstoreTailer::new(blockstore, starting_slot),
+                Err(_) => BlockstoreTailer::new_from_latest_root(blockstore)?,
+            };
+            let mut tailer = tailer.with_poll_interval(poll_interval);
+            info!("Tailing rooted blocks from slot {}", tailer.next_slot());
+
+            let exit = AtomicBool::new(false);
+            let mut num_printed = 0;
+            while num_printed < num_blocks {
+                for tailed_block in tailer.wait_for_blocks(&exit)? {
+                    print_tailed_block(&tailed_block, &output_format)?;
+                    num_printed += 1;
+                    if num_printed == num_blocks {
+                        break;
+                    }
+                }
+            }
+        }
+        ("rebuild-tx-index", Some(arg_matches)) => rebuild_tx_index(&ledger_path, arg_matches)?,
+        ("check", Some(arg_matches)) => check_blockstore(&ledger_path, arg_matches)?,
         ("json", Some(arg_matches)) => {
             let starting_slot = value_t_or_exit!(arg_matches, "starting_slot", Slot);
             let ending_slot = value_t!(arg_matches, "ending_slot", Slot).unwrap_or(Slot::MAX);
             let allow_dead_slots = arg_matches.is_present("allow_dead_slots");
+            let output_format =
+                match OutputFormat::from_matches(arg_matches, "output_format", false) {
+                    OutputFormat::JsonCompact => OutputFormat::JsonCompact,
+                    _ => OutputFormat::Json,
+                };
             output_ledger(
                 crate::open_blockstore(&ledger_path, arg_matches, AccessType::Secondary),
                 starting_slot,
                 ending_slot,
                 allow_dead_slots,
-                OutputFormat::Json,
+                output_format,
                 None,
                 std::u64::MAX,
                 true,
@@ -740,6 +1330,20 @@ fn do_blockstore_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) -
             let slots =
                 get_latest_optimistic_slots(&blockstore, num_slots, exclude_vote_only_slots);
 
+            let output_format = OutputFormat::from_matches(arg_matches, "output_format", false);
+            if is_json(&output_format) {
+                let mut json_writer = JsonArrayWriter::new(stdout(), output_format)?;
+                for (slot, hash_and_timestamp_opt, contains_nonvote) in slots {
+                    json_writer.write(&json!({
+                        "slot": slot,
+                        "hash": hash_and_timestamp_opt.map(|(hash, _)| hash.to_string()),
+                        "timestamp": hash_and_timestamp_opt.map(|(_, timestamp)| timestamp),
+                        "voteOnly": !contains_nonvote,
+                    }))?;
+                }
+                json_writer.finish()?;
+                return Ok(());
+            }
             println!(
                 "{:>20} {:>44} {:>32} {:>13}",
                 "Slot", "Hash", "Timestamp", "Vote Only?"
@@ -781,15 +1385,35 @@ fn do_blockstore_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) -
                 Box::new(stdout())
             };
 
-            for slot in iter
+            let roots = iter
                 .take(num_roots)
                 .take_while(|slot| *slot <= max_height as u64)
                 .collect::<Vec<_>>()
                 .into_iter()
                 .rev()
-            {
-                let blockhash = blockstore.get_slot_entries(slot, 0)?.last().unwrap().hash;
-                writeln!(output, "{slot}: {blockhash:?}").expect("failed to write");
+                .map(|slot| -> Result<_> {
+                    Ok((
+                        slot,
+                        blockstore.get_slot_entries(slot, 0)?.last().unwrap().hash,
+                    ))
+                });
+
+            let output_format = OutputFormat::from_matches(arg_matches, "output_format", false);
+            if is_json(&output_format) {
+                let mut json_writer = JsonArrayWriter::new(output, output_format)?;
+                for root in roots {
+                    let (slot, blockhash) = root?;
+                    json_writer.write(&json!({
+                        "slot": slot,
+                        "blockhash": blockhash.to_string(),
+                    }))?;
+                }
+                json_writer.finish()?;
+            } else {
+                for root in roots {
+                    let (slot, blockhash) = root?;
+                    writeln!(output, "{slot}: {blockhash:?}").expect("failed to write");
+                }
             }
         }
         ("parse_full_frozen", Some(arg_matches)) => {
@@ -808,7 +1432,11 @@ fn do_blockstore_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) -
                     break;
                 }
             }
-            println!("ancestors: {:?}", ancestors.iter());
+            let output_format = OutputFormat::from_matches(arg_matches, "output_format", false);
+            let json_output = is_json(&output_format);
+            if !json_output {
+                println!("ancestors: {:?}", ancestors.iter());
+            }
 
             let mut frozen = BTreeMap::new();
             let mut full = BTreeMap::new();
@@ -817,7 +1445,9 @@ fn do_blockstore_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) -
 
             let log_file = PathBuf::from(value_t_or_exit!(arg_matches, "log_path", String));
             let f = BufReader::new(File::open(log_file)?);
-            println!("Reading log file");
+            if !json_output {
+                println!("Reading log file");
+            }
             for line in f.lines().map_while(std::io::Result::ok) {
                 let parse_results = {
                     if let Some(slot_string) = frozen_regex.captures_iter(&line).next() {
@@ -847,9 +1477,22 @@ fn do_blockstore_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) -
                 }
             }
 
+            let mut json_writer = json_output
+                .then(|| JsonArrayWriter::new(stdout(), output_format))
+                .transpose()?;
             for ((slot1, frozen_log), (slot2, full_log)) in frozen.iter().zip(full.iter()) {
                 assert_eq!(slot1, slot2);
-                println!("Slot: {slot1}\n, full: {full_log}\n, frozen: {frozen_log}");
+                match json_writer.as_mut() {
+                    Some(json_writer) => json_writer.write(&json!({
+                        "slot": slot1,
+                        "full": full_log,
+                        "frozen": frozen_log,
+                    }))?,
+                    None => println!("Slot: {slot1}\n, full: {full_log}\n, frozen: {frozen_log}"),
+                }
+            }
+            if let Some(json_writer) = json_writer {
+                json_writer.finish()?;
             }
         }
         ("print", Some(arg_matches)) => {
@@ -863,7 +1506,7 @@ fn do_blockstore_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) -
                 starting_slot,
                 ending_slot,
                 allow_dead_slots,
-                OutputFormat::Display,
+                OutputFormat::from_matches(arg_matches, "output_format", false),
                 num_slots,
                 verbose_level,
                 only_rooted,
@@ -873,7 +1516,8 @@ fn do_blockstore_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) -
             let blockstore =
                 crate::open_blockstore(&ledger_path, arg_matches, AccessType::Secondary);
             let sst_file_name = arg_matches.value_of("file_name");
-            print_blockstore_file_metadata(&blockstore, &sst_file_name)?;
+            let output_format = OutputFormat::from_matches(arg_matches, "output_format", false);
+            print_blockstore_file_metadata(&blockstore, &sst_file_name, output_format)?;
         }
         ("purge", Some(arg_matches)) => {
             let start_slot = value_t_or_exit!(arg_matches, "start_slot", Slot);
@@ -1012,7 +1656,11 @@ fn do_blockstore_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) -
             }
             let starting_slot = value_t_or_exit!(arg_matches, "starting_slot", Slot);
             let ending_slot = value_t!(arg_matches, "ending_slot", Slot).unwrap_or(Slot::MAX);
+            let output_format = OutputFormat::from_matches(arg_matches, "output_format", false);
             let ledger = crate::open_blockstore(&ledger_path, arg_matches, AccessType::Secondary);
+            let mut json_writer = is_json(&output_format)
+                .then(|| JsonArrayWriter::new(stdout(), output_format))
+                .transpose()?;
             for (slot, _meta) in ledger
                 .slot_meta_iterator(starting_slot)?
                 .take_while(|(slot, _)| *slot <= ending_slot)
@@ -1020,6 +1668,21 @@ fn do_blockstore_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) -
                 let full_slot = ledger.is_full(slot);
                 if let Ok(shreds) = ledger.get_data_shreds_for_slot(slot, 0) {
                     for (shred_index, shred) in shreds.iter().enumerate() {
+                        if let Some(json_writer) = json_writer.as_mut() {
+                            json_writer.write(&json!({
+                                "slot": slot,
+                                "fullSlot": full_slot,
+                                "shredIndex": shred_index,
+                                "index": shred.index(),
+                                "fecSetIndex": shred.fec_set_index(),
+                                "version": shred.version(),
+                                "data": shred.is_data(),
+                                "code": shred.is_code(),
+                                "lastInSlot": shred.last_in_slot(),
+                                "dataComplete": shred.data_complete(),
+                            }))?;
+                            continue;
+                        }
                         println!(
                             "{:#?}",
                             ShredMeta {
@@ -1036,12 +1699,27 @@ fn do_blockstore_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) -
                     }
                 }
             }
+            if let Some(json_writer) = json_writer {
+                json_writer.finish()?;
+            }
         }
         ("slot", Some(arg_matches)) => {
             let slots = values_t_or_exit!(arg_matches, "slots", Slot);
             let allow_dead_slots = arg_matches.is_present("allow_dead_slots");
+            let output_format = OutputFormat::from_matches(arg_matches, "output_format", false);
             let blockstore =
                 crate::open_blockstore(&ledger_path, arg_matches, AccessType::Secondary);
+            if is_json(&output_format) {
+                let mut json_writer = JsonArrayWriter::new(stdout(), output_format)?;
+                for slot in slots {
+                    match CliSlot::new(&blockstore, slot, allow_dead_slots) {
+                        Ok(cli_slot) => json_writer.write(&cli_slot)?,
+                        Err(err) => eprintln!("Slot {slot}: {err}"),
+                    }
+                }
+                json_writer.finish()?;
+                return Ok(());
+            }
             for slot in slots {
                 println!("Slot {slot}");
                 if let Err(err) = output_slot(
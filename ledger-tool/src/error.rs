@@ -1,4 +1,8 @@
-use {solana_ledger::blockstore::BlockstoreError, thiserror::Error};
+use {
+    crate::ledger_utils::LoadAndProcessLedgerError,
+    solana_ledger::{blockstore::BlockstoreError, blockstore_archive::BlockstoreArchiveError},
+    thiserror::Error,
+};
 
 pub type Result<T> = std::result::Result<T, LedgerToolError>;
 
@@ -7,6 +11,12 @@ pub enum LedgerToolError {
     #[error("{0}")]
     Blockstore(#[from] BlockstoreError),
 
+    #[error("{0}")]
+    BlockstoreArchive(#[from] BlockstoreArchiveError),
+
+    #[error("{0}")]
+    LoadAndProcessLedger(#[from] LoadAndProcessLedgerError),
+
     #[error("{0}")]
     SerdeJson(#[from] serde_json::Error),
 
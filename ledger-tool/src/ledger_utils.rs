@@ -47,7 +47,7 @@ use {
         path::{Path, PathBuf},
         process::exit,
         sync::{
-            atomic::{AtomicBool, Ordering},
+            atomic::{AtomicBool, AtomicU64, Ordering},
             Arc, RwLock,
         },
     },
@@ -108,6 +108,7 @@ pub fn load_and_process_ledger_or_exit(
         process_options,
         snapshot_archive_path,
         incremental_snapshot_archive_path,
+        None,
     )
     .unwrap_or_else(|err| {
         eprintln!("Exiting. Failed to load and process ledger: {err}");
@@ -115,6 +116,9 @@ pub fn load_and_process_ledger_or_exit(
     })
 }
 
+/// Passing `max_complete_transaction_status_slot` enables transaction history regardless of
+/// `--enable-rpc-transaction-history`, and tracks the highest slot whose transaction statuses
+/// have been written to the blockstore.
 pub fn load_and_process_ledger(
     arg_matches: &ArgMatches,
     genesis_config: &GenesisConfig,
@@ -122,6 +126,7 @@ pub fn load_and_process_ledger(
     process_options: ProcessOptions,
     snapshot_archive_path: Option<PathBuf>,
     incremental_snapshot_archive_path: Option<PathBuf>,
+    max_complete_transaction_status_slot: Option<Arc<AtomicU64>>,
 ) -> Result<(Arc<RwLock<BankForks>>, Option<StartingSnapshotHashes>), LoadAndProcessLedgerError> {
     let bank_snapshots_dir = if blockstore.is_primary_access() {
         blockstore.ledger_path().join("snapshot")
@@ -353,7 +358,8 @@ pub fn load_and_process_ledger(
         None,
     );
 
-    let enable_rpc_transaction_history = arg_matches.is_present("enable_rpc_transaction_history");
+    let enable_rpc_transaction_history = arg_matches.is_present("enable_rpc_transaction_history")
+        || max_complete_transaction_status_slot.is_some();
 
     let (transaction_status_sender, transaction_status_service) = if geyser_plugin_active
         || enable_rpc_transaction_history
@@ -373,7 +379,7 @@ pub fn load_and_process_ledger(
         let (transaction_status_sender, transaction_status_receiver) = unbounded();
         let transaction_status_service = TransactionStatusService::new(
             transaction_status_receiver,
-            Arc::default(),
+            max_complete_transaction_status_slot.unwrap_or_default(),
             enable_rpc_transaction_history,
             transaction_notifier,
             tss_blockstore,
@@ -403,12 +409,15 @@ pub fn load_and_process_ledger(
     .map(|_| (bank_forks, starting_snapshot_hashes))
     .map_err(LoadAndProcessLedgerError::ProcessBlockstoreFromRoot);
 
-    exit.store(true, Ordering::Relaxed);
-    accounts_background_service.join().unwrap();
-    accounts_hash_verifier.join().unwrap();
+    // The transaction status service stops as soon as exit is set, so let it write out the
+    // statuses that are still queued first; it returns once the sender is dropped
+    drop(transaction_status_sender);
     if let Some(service) = transaction_status_service {
         service.join().unwrap();
     }
+    exit.store(true, Ordering::Relaxed);
+    accounts_background_service.join().unwrap();
+    accounts_hash_verifier.join().unwrap();
 
     result
 }
@@ -7,7 +7,9 @@ use {
         ledger_path::*,
         ledger_utils::*,
         output::{
-            output_account, AccountsOutputConfig, AccountsOutputMode, AccountsOutputStreamer,
+            is_json, output_account, read_transaction_statuses, AccountsOutputConfig,
+            AccountsOutputMode, AccountsOutputStreamer, CliCostLimits, CliSlotCost,
+            JsonArrayWriter, SlotCostAuditConfig, DEFAULT_NUM_TOP_WRITABLE_ACCOUNTS,
         },
         program::*,
     },
@@ -37,7 +39,6 @@ use {
         system_monitor_service::{SystemMonitorService, SystemMonitorStatsReportConfig},
         validator::BlockVerificationMethod,
     },
-    solana_cost_model::{cost_model::CostModel, cost_tracker::CostTracker},
     solana_ledger::{
         blockstore::{create_new_ledger, Blockstore},
         blockstore_options::{AccessType, LedgerColumnOptions},
@@ -61,7 +62,7 @@ use {
         account_utils::StateMut,
         clock::{Epoch, Slot},
         feature::{self, Feature},
-        feature_set::{self, FeatureSet},
+        feature_set,
         genesis_config::ClusterType,
         inflation::Inflation,
         native_token::{lamports_to_sol, sol_to_lamports, Sol},
@@ -70,7 +71,6 @@ use {
         shred_version::compute_shred_version,
         stake::{self, state::StakeStateV2},
         system_program,
-        transaction::{MessageHash, SanitizedTransaction, SimpleAddressLoader},
     },
     solana_stake_program::{points::PointValue, stake_state},
     solana_unified_scheduler_pool::DefaultSchedulerPool,
@@ -79,7 +79,7 @@ use {
         vote_state::{self, VoteState},
     },
     std::{
-        collections::{HashMap, HashSet},
+        collections::{BTreeMap, HashMap, HashSet},
         ffi::OsStr,
         fs::File,
         io::{self, Write},
@@ -102,6 +102,7 @@ mod ledger_path;
 mod ledger_utils;
 mod output;
 mod program;
+mod rebuild_tx_index;
 
 fn parse_encoding_format(matches: &ArgMatches<'_>) -> UiAccountEncoding {
     match matches.value_of("encoding") {
@@ -449,60 +450,18 @@ fn compute_slot_cost(
     blockstore: &Blockstore,
     slot: Slot,
     allow_dead_slots: bool,
-) -> Result<(), String> {
+    config: &SlotCostAuditConfig,
+) -> Result<CliSlotCost, String> {
     let (entries, _num_shreds, _is_full) = blockstore
         .get_slot_entries_with_shred_info(slot, 0, allow_dead_slots)
         .map_err(|err| format!("Slot: {slot}, Failed to load entries, err {err:?}"))?;
-
-    let num_entries = entries.len();
-    let mut num_transactions = 0;
-    let mut num_programs = 0;
-
-    let mut program_ids = HashMap::new();
-    let mut cost_tracker = CostTracker::default();
-
-    for entry in entries {
-        num_transactions += entry.transactions.len();
-        entry
-            .transactions
-            .into_iter()
-            .filter_map(|transaction| {
-                SanitizedTransaction::try_create(
-                    transaction,
-                    MessageHash::Compute,
-                    None,
-                    SimpleAddressLoader::Disabled,
-                )
-                .map_err(|err| {
-                    warn!("Failed to compute cost of transaction: {:?}", err);
-                })
-                .ok()
-            })
-            .for_each(|transaction| {
-                num_programs += transaction.message().instructions().len();
-
-                let tx_cost = CostModel::calculate_cost(&transaction, &FeatureSet::all_enabled());
-                let result = cost_tracker.try_add(&tx_cost);
-                if result.is_err() {
-                    println!(
-                        "Slot: {slot}, CostModel rejected transaction {transaction:?}, reason \
-                         {result:?}",
-                    );
-                }
-                for (program_id, _instruction) in transaction.message().program_instructions_iter()
-                {
-                    *program_ids.entry(*program_id).or_insert(0) += 1;
-                }
-            });
-    }
-
-    println!(
-        "Slot: {slot}, Entries: {num_entries}, Transactions: {num_transactions}, Programs \
-         {num_programs}",
-    );
-    println!("  Programs: {program_ids:?}");
-
-    Ok(())
+    let transaction_statuses = read_transaction_statuses(blockstore, slot, &entries);
+    Ok(CliSlotCost::new(
+        slot,
+        &entries,
+        &transaction_statuses,
+        config,
+    ))
 }
 
 /// Finds the accounts needed to replay slots `snapshot_slot` to `ending_slot`.
@@ -774,6 +733,7 @@ fn main() {
         .max(rent.minimum_balance(StakeStateV2::size_of()))
         .to_string();
     let default_graph_vote_account_mode = GraphVoteAccountMode::default();
+    let default_num_top_writable_accounts = DEFAULT_NUM_TOP_WRITABLE_ACCOUNTS.to_string();
 
     let mut measure_total_execution_time = Measure::start("ledger tool");
 
@@ -873,8 +833,9 @@ fn main() {
                 .takes_value(true)
                 .possible_values(&["json", "json-compact"])
                 .help(
-                    "Return information in specified output format, currently only available for \
-                     bigtable and program subcommands",
+                    "Return information in specified output format, for the bigtable, program and \
+                     blockstore subcommands and compute-slot-cost. Transactions are encoded as \
+                     jsonParsed",
                 ),
         )
         .arg(
@@ -962,6 +923,29 @@ fn main() {
                 .arg(&accounts_db_test_skip_rewrites_but_include_in_bank_hash)
                 .arg(&use_snapshot_archives_at_startup),
         )
+        .subcommand(
+            SubCommand::with_name("staked-nodes")
+                .about(
+                    "Prints the staked nodes of the working bank as a JSON object mapping node \
+                     pubkeys to stakes",
+                )
+                .arg(&max_genesis_archive_unpacked_size_arg)
+                .arg(&halt_at_slot_arg)
+                .arg(&accounts_index_bins)
+                .arg(&accounts_index_limit)
+                .arg(&disable_disk_index)
+                .arg(&accountsdb_verify_refcounts)
+                .arg(&accounts_db_skip_initial_hash_calc_arg)
+                .arg(&accounts_db_test_skip_rewrites_but_include_in_bank_hash)
+                .arg(&use_snapshot_archives_at_startup)
+                .arg(
+                    Arg::with_name("epoch")
+                        .long("epoch")
+                        .value_name("EPOCH")
+                        .takes_value(true)
+                        .help("Epoch of the stakes [default: epoch of the working bank]"),
+                ),
+        )
         .subcommand(
             SubCommand::with_name("verify")
                 .about("Verify the ledger")
@@ -1483,7 +1467,51 @@ fn main() {
                              in ledger",
                         ),
                 )
-                .arg(&allow_dead_slots_arg),
+                .arg(&allow_dead_slots_arg)
+                .arg(
+                    Arg::with_name("top_writable_accounts")
+                        .long("top-writable-accounts")
+                        .value_name("NUM")
+                        .takes_value(true)
+                        .validator(is_parsable::<usize>)
+                        .default_value(&default_num_top_writable_accounts)
+                        .help(
+                            "Number of writable accounts with the highest cost to report per slot",
+                        ),
+                )
+                .arg(
+                    Arg::with_name("block_cost_limit")
+                        .long("block-cost-limit")
+                        .value_name("UNITS")
+                        .takes_value(true)
+                        .validator(is_parsable::<u64>)
+                        .help(
+                            "Also report the transactions that would be rejected with this block \
+                             cost limit [default: the current block cost limit]",
+                        ),
+                )
+                .arg(
+                    Arg::with_name("account_cost_limit")
+                        .long("account-cost-limit")
+                        .value_name("UNITS")
+                        .takes_value(true)
+                        .validator(is_parsable::<u64>)
+                        .help(
+                            "Also report the transactions that would be rejected with this \
+                             writable account cost limit [default: the current account cost limit]",
+                        ),
+                )
+                .arg(
+                    Arg::with_name("vote_cost_limit")
+                        .long("vote-cost-limit")
+                        .value_name("UNITS")
+                        .takes_value(true)
+                        .validator(is_parsable::<u64>)
+                        .help(
+                            "Also report the transactions that would be rejected with this vote \
+                             cost limit [default: the current vote cost limit]",
+                        ),
+                ),
         )
         .program_subcommand()
         .get_matches();
@@ -1631,6 +1659,35 @@ fn main() {
                     );
                     println!("{}", &bank_forks.read().unwrap().working_bank().hash());
                 }
+                ("staked-nodes", Some(arg_matches)) => {
+                    let process_options = parse_process_options(&ledger_path, arg_matches);
+                    let genesis_config = open_genesis_config_by(&ledger_path, arg_matches);
+                    let blockstore = open_blockstore(
+                        &ledger_path,
+                        arg_matches,
+                        get_access_type(&process_options),
+                    );
+                    let (bank_forks, _) = load_and_process_ledger_or_exit(
+                        arg_matches,
+                        &genesis_config,
+                        Arc::new(blockstore),
+                        process_options,
+                        snapshot_archive_path,
+                        incremental_snapshot_archive_path,
+                    );
+                    let bank = bank_forks.read().unwrap().working_bank();
+                    let epoch =
+                        value_t!(arg_matches, "epoch", Epoch).unwrap_or_else(|_| bank.epoch());
+                    let Some(staked_nodes) = bank.epoch_staked_nodes(epoch) else {
+                        eprintln!("No stakes for epoch {epoch}");
+                        exit(1);
+                    };
+                    let staked_nodes: BTreeMap<String, u64> = staked_nodes
+                        .iter()
+                        .map(|(pubkey, stake)| (pubkey.to_string(), *stake))
+                        .collect();
+                    println!("{}", serde_json::to_string_pretty(&staked_nodes).unwrap());
+                }
                 ("verify", Some(arg_matches)) => {
                     let exit_signal = Arc::new(AtomicBool::new(false));
                     let report_os_memory_stats =
@@ -2949,12 +3006,59 @@ fn main() {
                         slots = values_t_or_exit!(arg_matches, "slots", Slot);
                     }
                     let allow_dead_slots = arg_matches.is_present("allow_dead_slots");
+                    let output_format =
+                        OutputFormat::from_matches(arg_matches, "output_format", verbose_level > 0);
+                    let hypothetical_limits =
+                        ["block_cost_limit", "account_cost_limit", "vote_cost_limit"]
+                            .iter()
+                            .any(|name| arg_matches.is_present(name))
+                            .then(|| {
+                                let limits = CliCostLimits::default();
+                                CliCostLimits {
+                                    block_cost_limit: value_t!(
+                                        arg_matches,
+                                        "block_cost_limit",
+                                        u64
+                                    )
+                                    .unwrap_or(limits.block_cost_limit),
+                                    account_cost_limit: value_t!(
+                                        arg_matches,
+                                        "account_cost_limit",
+                                        u64
+                                    )
+                                    .unwrap_or(limits.account_cost_limit),
+                                    vote_cost_limit: value_t!(arg_matches, "vote_cost_limit", u64)
+                                        .unwrap_or(limits.vote_cost_limit),
+                                }
+                            });
+                    let config = SlotCostAuditConfig {
+                        num_top_writable_accounts: value_t_or_exit!(
+                            arg_matches,
+                            "top_writable_accounts",
+                            usize
+                        ),
+                        hypothetical_limits,
+                        ..SlotCostAuditConfig::default()
+                    };
 
+                    let mut json_writer = is_json(&output_format).then(|| {
+                        JsonArrayWriter::new(io::stdout(), output_format.clone())
+                            .expect("open array")
+                    });
                     for slot in slots {
-                        if let Err(err) = compute_slot_cost(&blockstore, slot, allow_dead_slots) {
-                            eprintln!("{err}");
+                        match compute_slot_cost(&blockstore, slot, allow_dead_slots, &config) {
+                            Ok(slot_cost) => match json_writer.as_mut() {
+                                Some(json_writer) => {
+                                    json_writer.write(&slot_cost).expect("serialize slot cost")
+                                }
+                                None => print!("{}", output_format.formatted_string(&slot_cost)),
+                            },
+                            Err(err) => eprintln!("{err}"),
                         }
                     }
+                    if let Some(json_writer) = json_writer {
+                        json_writer.finish().expect("close array");
+                    }
                 }
                 ("", _) => {
                     eprintln!("{}", matches.usage());
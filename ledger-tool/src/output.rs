@@ -1,6 +1,7 @@
 use {
     crate::ledger_utils::get_program_ids,
     chrono::{Local, TimeZone},
+    log::*,
     serde::{
         ser::{Impossible, SerializeSeq, SerializeStruct, Serializer},
         Deserialize, Serialize,
@@ -11,22 +12,33 @@ use {
         display::writeln_transaction, CliAccount, CliAccountNewConfig, OutputFormat, QuietDisplay,
         VerboseDisplay,
     },
+    solana_cost_model::{
+        block_cost_limits::{MAX_BLOCK_UNITS, MAX_VOTE_UNITS, MAX_WRITABLE_ACCOUNT_UNITS},
+        cost_model::CostModel,
+        cost_tracker::CostTracker,
+    },
     solana_entry::entry::Entry,
-    solana_ledger::blockstore::Blockstore,
+    solana_ledger::blockstore::{blockstore_check::BlockstoreCheckReport, Blockstore},
     solana_runtime::bank::{Bank, TotalAccountsStats},
     solana_sdk::{
         account::{AccountSharedData, ReadableAccount},
         clock::{Slot, UnixTimestamp},
+        feature_set::FeatureSet,
         hash::Hash,
+        message::v0::LoadedAddresses,
         native_token::lamports_to_sol,
         pubkey::Pubkey,
+        transaction::{
+            MessageHash, SanitizedTransaction, SimpleAddressLoader, VersionedTransaction,
+        },
     },
     solana_transaction_status::{
-        EncodedConfirmedBlock, EncodedTransactionWithStatusMeta, EntrySummary, Rewards,
+        EncodableWithMeta, EncodedConfirmedBlock, EncodedTransactionWithStatusMeta, EntrySummary,
+        Rewards, TransactionStatusMeta, UiTransactionEncoding, VersionedTransactionWithStatusMeta,
     },
     std::{
         cell::RefCell,
-        collections::HashMap,
+        collections::{BTreeMap, HashMap},
         fmt::{self, Display, Formatter},
         io::{stdout, Write},
         rc::Rc,
@@ -99,6 +111,143 @@ impl Display for SlotBounds<'_> {
     }
 }
 
+#[derive(Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SstLevelStats {
+    pub level: i32,
+    pub num_files: usize,
+    pub total_bytes: u64,
+    pub num_entries: u64,
+    pub num_deletions: u64,
+    /// Files overlapping the slot range, only known for columns keyed by slot
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub num_files_in_range: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bytes_in_range: Option<u64>,
+    /// Uncompressed over compressed size, as reported by RocksDB
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compression_ratio: Option<f64>,
+}
+
+#[derive(Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ColumnStorageStats {
+    pub column: String,
+    pub num_keys: u64,
+    pub key_bytes: u64,
+    pub value_bytes: u64,
+    /// Slots with at least one key in the range, not known for columns without slots in keys
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub num_slots: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bytes_per_slot: Option<u64>,
+    pub sst_levels: Vec<SstLevelStats>,
+}
+
+#[derive(Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ColumnStorageReport {
+    pub starting_slot: Slot,
+    pub ending_slot: Slot,
+    pub columns: Vec<ColumnStorageStats>,
+}
+
+impl VerboseDisplay for ColumnStorageReport {}
+impl QuietDisplay for ColumnStorageReport {}
+
+impl Display for ColumnStorageReport {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "Column storage for slots {} to {}",
+            self.starting_slot, self.ending_slot
+        )?;
+        for column in &self.columns {
+            write!(
+                f,
+                "{}: {} keys, {} key bytes, {} value bytes",
+                column.column, column.num_keys, column.key_bytes, column.value_bytes,
+            )?;
+            if let (Some(num_slots), Some(bytes_per_slot)) =
+                (column.num_slots, column.bytes_per_slot)
+            {
+                write!(f, " in {num_slots} slots, {bytes_per_slot} bytes/slot")?;
+            }
+            writeln!(f)?;
+            for level in &column.sst_levels {
+                write!(
+                    f,
+                    "  L{}: {} files, {} bytes, {} entries, {} deletions",
+                    level.level,
+                    level.num_files,
+                    level.total_bytes,
+                    level.num_entries,
+                    level.num_deletions,
+                )?;
+                if let (Some(num_files), Some(bytes)) =
+                    (level.num_files_in_range, level.bytes_in_range)
+                {
+                    write!(f, ", {num_files} files with {bytes} bytes in range")?;
+                }
+                if let Some(compression_ratio) = level.compression_ratio {
+                    write!(f, ", compression ratio {compression_ratio:.2}")?;
+                }
+                writeln!(f)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockstoreCheckOutput {
+    #[serde(flatten)]
+    pub report: BlockstoreCheckReport,
+    /// The number of issues fixed, when fixing was requested
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub num_fixed: Option<usize>,
+}
+
+impl VerboseDisplay for BlockstoreCheckOutput {}
+impl QuietDisplay for BlockstoreCheckOutput {}
+
+impl Display for BlockstoreCheckOutput {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let report = &self.report;
+        writeln!(
+            f,
+            "Checked {} slots ({} full) from {} to {}: {} data shreds, {} coding shreds",
+            report.num_slots,
+            report.num_full_slots,
+            report.starting_slot,
+            report.ending_slot,
+            report.num_data_shreds,
+            report.num_coding_shreds,
+        )?;
+        if report.num_verified_signatures > 0 || report.num_unverified_signatures > 0 {
+            writeln!(
+                f,
+                "Verified {} shred signatures, {} shreds have an unknown slot leader",
+                report.num_verified_signatures, report.num_unverified_signatures,
+            )?;
+        }
+        for issue in &report.issues {
+            let fixable = if issue.kind.is_fixable() {
+                " (fixable)"
+            } else {
+                ""
+            };
+            writeln!(f, "  slot {}: {}{fixable}", issue.slot, issue.kind)?;
+        }
+        write!(f, "{} issues found", report.issues.len())?;
+        if let Some(num_fixed) = self.num_fixed {
+            write!(f, ", {num_fixed} fixed")?;
+        }
+        writeln!(f)
+    }
+}
+
 fn writeln_entry(f: &mut dyn fmt::Write, i: usize, entry: &CliEntry, prefix: &str) -> fmt::Result {
     writeln!(
         f,
@@ -327,8 +476,544 @@ impl EncodedConfirmedBlockWithEntries {
     }
 }
 
+/// JSON schema of a slot, shared by the `slot`, `print` and `json` commands
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliSlot {
+    pub slot: Slot,
+    pub parent_slot: Option<Slot>,
+    pub next_slots: Vec<Slot>,
+    pub is_root: bool,
+    pub is_dead: bool,
+    pub is_full: bool,
+    pub num_shreds: u64,
+    /// The hash of the last entry of the slot
+    pub blockhash: Option<String>,
+    /// Only known for rooted slots
+    pub block_time: Option<UnixTimestamp>,
+    pub block_height: Option<u64>,
+    /// Transactions are encoded with `UiTransactionEncoding::JsonParsed`, and have a `meta` when
+    /// the ledger has transaction history
+    pub entries: Vec<CliPopulatedEntry>,
+    pub rewards: Rewards,
+    pub cost: CliSlotCost,
+}
+
+impl CliSlot {
+    pub fn new(
+        blockstore: &Blockstore,
+        slot: Slot,
+        allow_dead_slots: bool,
+    ) -> Result<Self, String> {
+        let is_dead = blockstore.is_dead(slot);
+        if is_dead && !allow_dead_slots {
+            return Err("Dead slot".to_string());
+        }
+        let (entries, num_shreds, is_full) = blockstore
+            .get_slot_entries_with_shred_info(slot, 0, allow_dead_slots)
+            .map_err(|err| format!("Failed to load entries for slot {slot}: {err:?}"))?;
+        let slot_meta = blockstore.meta(slot).ok().flatten();
+
+        let mut transaction_statuses = read_transaction_statuses(blockstore, slot, &entries);
+        let cost = CliSlotCost::new(
+            slot,
+            &entries,
+            &transaction_statuses,
+            &SlotCostAuditConfig {
+                include_transactions: false,
+                ..SlotCostAuditConfig::default()
+            },
+        );
+        let blockhash = entries.last().map(|entry| entry.hash.to_string());
+        let mut transaction_statuses = transaction_statuses.drain(..);
+        let mut starting_transaction_index = 0;
+        let entries = entries
+            .into_iter()
+            .map(|entry| {
+                let num_transactions = entry.transactions.len();
+                let populated_entry = CliPopulatedEntry {
+                    num_hashes: entry.num_hashes,
+                    hash: entry.hash.to_string(),
+                    num_transactions: num_transactions as u64,
+                    starting_transaction_index,
+                    transactions: entry
+                        .transactions
+                        .into_iter()
+                        .map(|transaction| {
+                            let meta = transaction_statuses.next().flatten();
+                            encode_transaction(transaction, meta)
+                        })
+                        .collect(),
+                };
+                starting_transaction_index += num_transactions;
+                populated_entry
+            })
+            .collect();
+
+        Ok(Self {
+            slot,
+            parent_slot: slot_meta.as_ref().and_then(|meta| meta.parent_slot),
+            next_slots: slot_meta.map(|meta| meta.next_slots).unwrap_or_default(),
+            is_root: blockstore.is_root(slot),
+            is_dead,
+            is_full,
+            num_shreds,
+            blockhash,
+            block_time: blockstore.get_rooted_block_time(slot).ok(),
+            block_height: blockstore.get_block_height(slot).ok().flatten(),
+            entries,
+            rewards: blockstore
+                .read_rewards(slot)
+                .ok()
+                .flatten()
+                .unwrap_or_default(),
+            cost,
+        })
+    }
+}
+
+/// Reads the status metas of the transactions of a slot's entries, in order. A transaction has
+/// none when the ledger has no transaction history for it
+pub fn read_transaction_statuses(
+    blockstore: &Blockstore,
+    slot: Slot,
+    entries: &[Entry],
+) -> Vec<Option<TransactionStatusMeta>> {
+    entries
+        .iter()
+        .flat_map(|entry| &entry.transactions)
+        .map(|transaction| {
+            blockstore
+                .read_transaction_status((transaction.signatures[0], slot))
+                .unwrap_or_else(|err| {
+                    eprintln!(
+                        "Failed to read transaction status for {} at slot {}: {}",
+                        transaction.signatures[0], slot, err
+                    );
+                    None
+                })
+        })
+        .collect()
+}
+
+/// Returns true if the transaction loads addresses from address lookup tables
+fn uses_address_lookup_tables(transaction: &VersionedTransaction) -> bool {
+    transaction
+        .message
+        .address_table_lookups()
+        .map(|lookups| !lookups.is_empty())
+        .unwrap_or(false)
+}
+
+/// Encodes a transaction with `UiTransactionEncoding::JsonParsed`, along with its status meta if
+/// there is one.
+///
+/// Without a status meta, the addresses a v0 transaction loads from lookup tables are unknown,
+/// so such a transaction is encoded with `UiTransactionEncoding::Json` instead.
+fn encode_transaction(
+    transaction: VersionedTransaction,
+    meta: Option<TransactionStatusMeta>,
+) -> EncodedTransactionWithStatusMeta {
+    match meta {
+        Some(meta) => VersionedTransactionWithStatusMeta { transaction, meta }
+            .encode(UiTransactionEncoding::JsonParsed, Some(u8::MAX), true)
+            .expect("every transaction version is supported"),
+        None => {
+            let encoding = if uses_address_lookup_tables(&transaction) {
+                UiTransactionEncoding::Json
+            } else {
+                UiTransactionEncoding::JsonParsed
+            };
+            EncodedTransactionWithStatusMeta {
+                transaction: transaction
+                    .encode_with_meta(encoding, &TransactionStatusMeta::default()),
+                meta: None,
+                version: Some(transaction.version()),
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CliRejectedTransaction {
+    pub signature: String,
+    pub reason: String,
+}
+
+/// Block limits to audit a slot's transactions against
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct CliCostLimits {
+    pub block_cost_limit: u64,
+    pub account_cost_limit: u64,
+    pub vote_cost_limit: u64,
+}
+
+impl Default for CliCostLimits {
+    fn default() -> Self {
+        Self {
+            block_cost_limit: MAX_BLOCK_UNITS,
+            account_cost_limit: MAX_WRITABLE_ACCOUNT_UNITS,
+            vote_cost_limit: MAX_VOTE_UNITS,
+        }
+    }
+}
+
+pub const DEFAULT_NUM_TOP_WRITABLE_ACCOUNTS: usize = 10;
+
+pub struct SlotCostAuditConfig {
+    /// How many of the writable accounts with the highest cost to report
+    pub num_top_writable_accounts: usize,
+    /// Limits to also check the transactions against, besides the current limits
+    pub hypothetical_limits: Option<CliCostLimits>,
+    /// Whether to report the estimated and actual cost of every transaction
+    pub include_transactions: bool,
+}
+
+impl Default for SlotCostAuditConfig {
+    fn default() -> Self {
+        Self {
+            num_top_writable_accounts: DEFAULT_NUM_TOP_WRITABLE_ACCOUNTS,
+            hypothetical_limits: None,
+            include_transactions: true,
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CliWritableAccountCost {
+    pub pubkey: String,
+    /// The summed cost of the transactions that write lock the account
+    pub cost: u64,
+    pub num_transactions: usize,
+    /// `cost` as a percentage of `MAX_WRITABLE_ACCOUNT_UNITS`
+    pub percent_of_limit: f64,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CliTransactionCost {
+    pub signature: String,
+    pub is_simple_vote: bool,
+    /// The full cost estimated by the `CostModel`
+    pub estimated_cost: u64,
+    /// The compute units the `CostModel` estimated for executing the instructions
+    pub estimated_compute_units: u64,
+    /// The compute units the transaction consumed, when the ledger has its status
+    pub actual_compute_units: Option<u64>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CliHypotheticalRejections {
+    pub limits: CliCostLimits,
+    pub rejected_transactions: Vec<CliRejectedTransaction>,
+}
+
+/// The cost of a slot's transactions, as estimated by the `CostModel`, audited against the
+/// block limits
+#[derive(Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CliSlotCost {
+    pub slot: Slot,
+    pub num_entries: usize,
+    pub num_transactions: usize,
+    /// Transactions that use address lookup tables but have no status meta, so that the accounts
+    /// they lock are unknown. They are left out of the audit
+    pub num_unauditable_transactions: usize,
+    /// The number of instructions of the transactions
+    pub num_programs: usize,
+    pub total_cost: u64,
+    pub vote_cost: u64,
+    pub non_vote_cost: u64,
+    /// Transactions that the cost tracker would not fit in a block
+    pub rejected_transactions: Vec<CliRejectedTransaction>,
+    /// Transactions that would not fit in a block under the hypothetical limits
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hypothetical_rejections: Option<CliHypotheticalRejections>,
+    /// The writable accounts with the highest cost, in descending order of cost
+    pub top_writable_accounts: Vec<CliWritableAccountCost>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transactions: Option<Vec<CliTransactionCost>>,
+    /// Number of instructions per program id
+    pub program_ids: BTreeMap<String, u64>,
+}
+
+impl CliSlotCost {
+    /// Audits the cost of a slot's transactions. `transaction_statuses` holds the status meta, if
+    /// any, of each transaction of `entries`, in order
+    pub fn new(
+        slot: Slot,
+        entries: &[Entry],
+        transaction_statuses: &[Option<TransactionStatusMeta>],
+        config: &SlotCostAuditConfig,
+    ) -> Self {
+        let mut slot_cost = Self {
+            slot,
+            num_entries: entries.len(),
+            transactions: config.include_transactions.then(Vec::new),
+            hypothetical_rejections: config.hypothetical_limits.map(|limits| {
+                CliHypotheticalRejections {
+                    limits,
+                    rejected_transactions: vec![],
+                }
+            }),
+            ..Self::default()
+        };
+        let mut cost_tracker = CostTracker::default();
+        let mut hypothetical_cost_tracker = config.hypothetical_limits.map(|limits| {
+            let mut cost_tracker = CostTracker::default();
+            cost_tracker.set_limits(
+                limits.account_cost_limit,
+                limits.block_cost_limit,
+                limits.vote_cost_limit,
+            );
+            cost_tracker
+        });
+        let mut cost_by_writable_accounts = HashMap::<Pubkey, (u64, usize)>::new();
+
+        let transactions = entries.iter().flat_map(|entry| &entry.transactions);
+        for (transaction, meta) in transactions.zip(transaction_statuses) {
+            slot_cost.num_transactions += 1;
+            let address_loader = match meta {
+                Some(meta) => SimpleAddressLoader::Enabled(meta.loaded_addresses.clone()),
+                None if uses_address_lookup_tables(transaction) => {
+                    slot_cost.num_unauditable_transactions += 1;
+                    continue;
+                }
+                None => SimpleAddressLoader::Enabled(LoadedAddresses::default()),
+            };
+            let Ok(transaction) = SanitizedTransaction::try_create(
+                transaction.clone(),
+                MessageHash::Compute,
+                None,
+                address_loader,
+            )
+            .map_err(|err| warn!("Failed to compute cost of transaction: {:?}", err)) else {
+                continue;
+            };
+            slot_cost.num_programs += transaction.message().instructions().len();
+
+            let tx_cost = CostModel::calculate_cost(&transaction, &FeatureSet::all_enabled());
+            if tx_cost.is_simple_vote() {
+                slot_cost.vote_cost += tx_cost.sum();
+            } else {
+                slot_cost.non_vote_cost += tx_cost.sum();
+            }
+            if let Err(err) = cost_tracker.try_add(&tx_cost) {
+                slot_cost
+                    .rejected_transactions
+                    .push(CliRejectedTransaction {
+                        signature: transaction.signature().to_string(),
+                        reason: format!("{err:?}"),
+                    });
+            }
+            if let (Some(cost_tracker), Some(rejections)) = (
+                hypothetical_cost_tracker.as_mut(),
+                slot_cost.hypothetical_rejections.as_mut(),
+            ) {
+                if let Err(err) = cost_tracker.try_add(&tx_cost) {
+                    rejections
+                        .rejected_transactions
+                        .push(CliRejectedTransaction {
+                            signature: transaction.signature().to_string(),
+                            reason: format!("{err:?}"),
+                        });
+                }
+            }
+            for writable_account in tx_cost.writable_accounts() {
+                let (cost, num_transactions) = cost_by_writable_accounts
+                    .entry(*writable_account)
+                    .or_default();
+                *cost += tx_cost.sum();
+                *num_transactions += 1;
+            }
+
+            if let Some(transactions) = slot_cost.transactions.as_mut() {
+                transactions.push(CliTransactionCost {
+                    signature: transaction.signature().to_string(),
+                    is_simple_vote: tx_cost.is_simple_vote(),
+                    estimated_cost: tx_cost.sum(),
+                    estimated_compute_units: tx_cost.programs_execution_cost(),
+                    actual_compute_units: meta
+                        .as_ref()
+                        .and_then(|meta| meta.compute_units_consumed),
+                });
+            }
+
+            for (program_id, _instruction) in transaction.message().program_instructions_iter() {
+                *slot_cost
+                    .program_ids
+                    .entry(program_id.to_string())
+                    .or_insert(0) += 1;
+            }
+        }
+        slot_cost.total_cost = cost_tracker.block_cost();
+
+        let mut cost_by_writable_accounts: Vec<_> = cost_by_writable_accounts.into_iter().collect();
+        cost_by_writable_accounts.sort_unstable_by(
+            |(pubkey_a, (cost_a, _)), (pubkey_b, (cost_b, _))| {
+                cost_b.cmp(cost_a).then(pubkey_a.cmp(pubkey_b))
+            },
+        );
+        slot_cost.top_writable_accounts = cost_by_writable_accounts
+            .into_iter()
+            .take(config.num_top_writable_accounts)
+            .map(
+                |(pubkey, (cost, num_transactions))| CliWritableAccountCost {
+                    pubkey: pubkey.to_string(),
+                    cost,
+                    num_transactions,
+                    percent_of_limit: cost as f64 * 100.0 / MAX_WRITABLE_ACCOUNT_UNITS as f64,
+                },
+            )
+            .collect();
+        slot_cost
+    }
+}
+
+impl QuietDisplay for CliSlotCost {}
+
+impl VerboseDisplay for CliSlotCost {
+    fn write_str(&self, w: &mut dyn std::fmt::Write) -> std::fmt::Result {
+        write!(w, "{self}")?;
+        let Some(transactions) = &self.transactions else {
+            return Ok(());
+        };
+        writeln!(
+            w,
+            "  {:<88}  {:>10}  {:>14}  {:>14}",
+            "Transaction", "Cost", "Estimated CUs", "Actual CUs"
+        )?;
+        for transaction in transactions {
+            writeln!(
+                w,
+                "  {:<88}  {:>10}  {:>14}  {:>14}",
+                transaction.signature,
+                transaction.estimated_cost,
+                transaction.estimated_compute_units,
+                transaction
+                    .actual_compute_units
+                    .map(|compute_units| compute_units.to_string())
+                    .unwrap_or_else(|| "-".to_string()),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for CliSlotCost {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        for rejected in &self.rejected_transactions {
+            writeln!(
+                f,
+                "Slot: {}, CostModel rejected transaction {}, reason {}",
+                self.slot, rejected.signature, rejected.reason,
+            )?;
+        }
+        writeln!(
+            f,
+            "Slot: {}, Entries: {}, Transactions: {}, Programs {}, Cost: {}, Vote cost: {}, \
+             Non-vote cost: {}",
+            self.slot,
+            self.num_entries,
+            self.num_transactions,
+            self.num_programs,
+            self.total_cost,
+            self.vote_cost,
+            self.non_vote_cost,
+        )?;
+        writeln!(f, "  Programs: {:?}", self.program_ids)?;
+        if self.num_unauditable_transactions > 0 {
+            writeln!(
+                f,
+                "  {} transactions use address lookup tables but have no transaction status, and \
+                 are not audited",
+                self.num_unauditable_transactions,
+            )?;
+        }
+        if !self.top_writable_accounts.is_empty() {
+            writeln!(
+                f,
+                "  Top writable accounts, of {MAX_WRITABLE_ACCOUNT_UNITS} units per account:"
+            )?;
+            for account in &self.top_writable_accounts {
+                writeln!(
+                    f,
+                    "    {:<44}  {:>10} units  {:>6.2}%  {} transactions",
+                    account.pubkey,
+                    account.cost,
+                    account.percent_of_limit,
+                    account.num_transactions,
+                )?;
+            }
+        }
+        if let Some(rejections) = &self.hypothetical_rejections {
+            let limits = &rejections.limits;
+            writeln!(
+                f,
+                "  {} transactions rejected with a block limit of {}, an account limit of {} and \
+                 a vote limit of {}",
+                rejections.rejected_transactions.len(),
+                limits.block_cost_limit,
+                limits.account_cost_limit,
+                limits.vote_cost_limit,
+            )?;
+            for rejected in &rejections.rejected_transactions {
+                writeln!(f, "    {}: {}", rejected.signature, rejected.reason)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Writes a JSON array one element at a time, so that long outputs need not be held in memory
+pub struct JsonArrayWriter<W: Write> {
+    writer: W,
+    output_format: OutputFormat,
+    num_elements: usize,
+}
+
+impl<W: Write> JsonArrayWriter<W> {
+    pub fn new(mut writer: W, output_format: OutputFormat) -> std::io::Result<Self> {
+        writer.write_all(b"[\n")?;
+        Ok(Self {
+            writer,
+            output_format,
+            num_elements: 0,
+        })
+    }
+
+    pub fn write<T: Serialize>(&mut self, element: &T) -> std::io::Result<()> {
+        if self.num_elements > 0 {
+            self.writer.write_all(b",\n")?;
+        }
+        match self.output_format {
+            OutputFormat::JsonCompact => serde_json::to_writer(&mut self.writer, element)?,
+            _ => serde_json::to_writer_pretty(&mut self.writer, element)?,
+        }
+        self.num_elements += 1;
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> std::io::Result<W> {
+        self.writer.write_all(b"\n]\n")?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Returns true if `output_format` is one of the JSON formats
+pub fn is_json(output_format: &OutputFormat) -> bool {
+    matches!(
+        output_format,
+        OutputFormat::Json | OutputFormat::JsonCompact
+    )
+}
+
 pub fn output_slot_rewards(blockstore: &Blockstore, slot: Slot, method: &OutputFormat) {
-    // Note: rewards are not output in JSON yet
     if *method == OutputFormat::Display {
         if let Ok(Some(rewards)) = blockstore.read_rewards(slot) {
             if !rewards.is_empty() {
@@ -401,11 +1086,6 @@ pub fn output_entry(
                 );
             }
         }
-        OutputFormat::Json => {
-            // Note: transaction status is not output in JSON yet
-            serde_json::to_writer(stdout(), &entry).expect("serialize entry");
-            stdout().write_all(b",\n").expect("newline");
-        }
         _ => unreachable!(),
     }
 }
@@ -503,14 +1183,15 @@ pub fn output_ledger(
             std::process::exit(1);
         });
 
-    if method == OutputFormat::Json {
-        stdout().write_all(b"{\"ledger\":[\n").expect("open array");
-    }
+    let mut json_writer = is_json(&method).then(|| {
+        stdout().write_all(b"{\"ledger\":").expect("open object");
+        JsonArrayWriter::new(stdout(), method.clone()).expect("open array")
+    });
 
     let num_slots = num_slots.unwrap_or(Slot::MAX);
     let mut num_printed = 0;
     let mut all_program_ids = HashMap::new();
-    for (slot, slot_meta) in slot_iterator {
+    for (slot, _slot_meta) in slot_iterator {
         if only_rooted && !blockstore.is_root(slot) {
             continue;
         }
@@ -518,26 +1199,23 @@ pub fn output_ledger(
             break;
         }
 
-        match method {
-            OutputFormat::Display => {
-                println!("Slot {} root?: {}", slot, blockstore.is_root(slot))
+        if let Some(json_writer) = json_writer.as_mut() {
+            match CliSlot::new(&blockstore, slot, allow_dead_slots) {
+                Ok(cli_slot) => json_writer.write(&cli_slot).expect("serialize slot"),
+                Err(err) => eprintln!("Slot {slot}: {err}"),
             }
-            OutputFormat::Json => {
-                serde_json::to_writer(stdout(), &slot_meta).expect("serialize slot_meta");
-                stdout().write_all(b",\n").expect("newline");
+        } else {
+            println!("Slot {} root?: {}", slot, blockstore.is_root(slot));
+            if let Err(err) = output_slot(
+                &blockstore,
+                slot,
+                allow_dead_slots,
+                &method,
+                verbose_level,
+                &mut all_program_ids,
+            ) {
+                eprintln!("{err}");
             }
-            _ => unreachable!(),
-        }
-
-        if let Err(err) = output_slot(
-            &blockstore,
-            slot,
-            allow_dead_slots,
-            &method,
-            verbose_level,
-            &mut all_program_ids,
-        ) {
-            eprintln!("{err}");
         }
         num_printed += 1;
         if num_printed >= num_slots as usize {
@@ -545,8 +1223,9 @@ pub fn output_ledger(
         }
     }
 
-    if method == OutputFormat::Json {
-        stdout().write_all(b"\n]}\n").expect("close array");
+    if let Some(json_writer) = json_writer {
+        json_writer.finish().expect("close array");
+        println!("}}");
     } else {
         println!("Summary of Programs:");
         output_sorted_program_ids(all_program_ids);
@@ -790,3 +1469,246 @@ pub fn output_account(
         };
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_ledger::get_tmp_ledger_path_auto_delete,
+        solana_sdk::{
+            instruction::CompiledInstruction,
+            message::{
+                v0::{self, MessageAddressTableLookup},
+                MessageHeader, VersionedMessage,
+            },
+            signature::{Keypair, Signer},
+            system_transaction,
+        },
+        solana_transaction_status::{EncodedTransaction, UiMessage},
+    };
+
+    fn new_entry(transactions: Vec<VersionedTransaction>) -> Entry {
+        Entry {
+            num_hashes: 0,
+            hash: Hash::default(),
+            transactions,
+        }
+    }
+
+    /// A v0 transaction whose instruction writes the first address loaded from a lookup table
+    fn new_lookup_table_transaction() -> VersionedTransaction {
+        let payer = Keypair::new();
+        let message = v0::Message {
+            header: MessageHeader {
+                num_required_signatures: 1,
+                num_readonly_signed_accounts: 0,
+                num_readonly_unsigned_accounts: 1,
+            },
+            account_keys: vec![payer.pubkey(), Pubkey::new_unique()],
+            recent_blockhash: Hash::new_unique(),
+            instructions: vec![CompiledInstruction::new_from_raw_parts(
+                1,
+                vec![],
+                vec![0, 2],
+            )],
+            address_table_lookups: vec![MessageAddressTableLookup {
+                account_key: Pubkey::new_unique(),
+                writable_indexes: vec![0],
+                readonly_indexes: vec![],
+            }],
+        };
+        VersionedTransaction::try_new(VersionedMessage::V0(message), &[&payer]).unwrap()
+    }
+
+    #[test]
+    fn test_encode_transaction_with_lookup_table_without_meta() {
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Blockstore::open(ledger_path.path()).unwrap();
+
+        let transaction = new_lookup_table_transaction();
+        let transaction_statuses =
+            read_transaction_statuses(&blockstore, 1, &[new_entry(vec![transaction.clone()])]);
+        assert_eq!(transaction_statuses, vec![None]);
+
+        let encoded = encode_transaction(transaction, None);
+        assert!(encoded.meta.is_none());
+        assert!(matches!(
+            encoded.transaction,
+            EncodedTransaction::Json(ui_transaction)
+                if matches!(ui_transaction.message, UiMessage::Raw(_))
+        ));
+    }
+
+    #[test]
+    fn test_slot_cost_top_writable_accounts() {
+        let keypairs: Vec<_> = (0..4).map(|_| Keypair::new()).collect();
+        let [a, b, c, d] = [0, 1, 2, 3].map(|i| keypairs[i].pubkey());
+        let hash = Hash::default();
+        let transactions = vec![
+            system_transaction::transfer(&keypairs[0], &b, 1, hash).into(),
+            system_transaction::transfer(&keypairs[0], &c, 1, hash).into(),
+            system_transaction::transfer(&keypairs[3], &b, 1, hash).into(),
+        ];
+        let entries = [new_entry(transactions)];
+
+        let slot_cost = CliSlotCost::new(
+            1,
+            &entries,
+            &[None, None, None],
+            &SlotCostAuditConfig {
+                num_top_writable_accounts: 3,
+                ..SlotCostAuditConfig::default()
+            },
+        );
+        assert_eq!(slot_cost.num_transactions, 3);
+        assert_eq!(slot_cost.num_unauditable_transactions, 0);
+        assert_eq!(slot_cost.vote_cost, 0);
+        assert_eq!(slot_cost.non_vote_cost, slot_cost.total_cost);
+        let transactions = slot_cost.transactions.unwrap();
+        let transaction_cost = transactions[0].estimated_cost;
+        assert!(transactions.iter().all(|transaction| {
+            transaction.estimated_cost == transaction_cost
+                && transaction.actual_compute_units.is_none()
+        }));
+
+        // Ordered by cost, and then by pubkey
+        let mut expected = vec![(a, 2), (b, 2)];
+        expected.sort_unstable();
+        expected.push((c.min(d), 1));
+        let top_writable_accounts: Vec<_> = slot_cost
+            .top_writable_accounts
+            .iter()
+            .map(|account| {
+                assert_eq!(
+                    account.cost,
+                    account.num_transactions as u64 * transaction_cost
+                );
+                (account.pubkey.clone(), account.num_transactions)
+            })
+            .collect();
+        assert_eq!(
+            top_writable_accounts,
+            expected
+                .into_iter()
+                .map(|(pubkey, num_transactions)| (pubkey.to_string(), num_transactions))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            slot_cost.top_writable_accounts[0].percent_of_limit,
+            (2 * transaction_cost) as f64 * 100.0 / MAX_WRITABLE_ACCOUNT_UNITS as f64
+        );
+    }
+
+    #[test]
+    fn test_slot_cost_hypothetical_limits() {
+        let payer = Keypair::new();
+        let other_payer = Keypair::new();
+        let hash = Hash::default();
+        let transactions: Vec<VersionedTransaction> = vec![
+            system_transaction::transfer(&payer, &Pubkey::new_unique(), 1, hash).into(),
+            system_transaction::transfer(&payer, &Pubkey::new_unique(), 1, hash).into(),
+            system_transaction::transfer(&other_payer, &Pubkey::new_unique(), 1, hash).into(),
+        ];
+        let entries = [new_entry(transactions.clone())];
+        let transaction_statuses = [None, None, None];
+
+        let slot_cost = CliSlotCost::new(
+            1,
+            &entries,
+            &transaction_statuses,
+            &SlotCostAuditConfig::default(),
+        );
+        assert!(slot_cost.hypothetical_rejections.is_none());
+        let transaction_cost = slot_cost.transactions.unwrap()[0].estimated_cost;
+
+        // The second transfer of the payer exceeds the account limit
+        let limits = CliCostLimits {
+            account_cost_limit: transaction_cost,
+            ..CliCostLimits::default()
+        };
+        let slot_cost = CliSlotCost::new(
+            1,
+            &entries,
+            &transaction_statuses,
+            &SlotCostAuditConfig {
+                hypothetical_limits: Some(limits),
+                include_transactions: false,
+                ..SlotCostAuditConfig::default()
+            },
+        );
+        assert!(slot_cost.rejected_transactions.is_empty());
+        assert!(slot_cost.transactions.is_none());
+        let rejections = slot_cost.hypothetical_rejections.unwrap();
+        assert_eq!(rejections.limits, limits);
+        let rejected_transactions: Vec<_> = rejections
+            .rejected_transactions
+            .iter()
+            .map(|rejected| (rejected.signature.clone(), rejected.reason.as_str()))
+            .collect();
+        assert_eq!(
+            rejected_transactions,
+            vec![(
+                transactions[1].signatures[0].to_string(),
+                "WouldExceedAccountMaxLimit"
+            )]
+        );
+
+        // Only the first transfer fits in the block
+        let limits = CliCostLimits {
+            block_cost_limit: transaction_cost,
+            ..CliCostLimits::default()
+        };
+        let slot_cost = CliSlotCost::new(
+            1,
+            &entries,
+            &transaction_statuses,
+            &SlotCostAuditConfig {
+                hypothetical_limits: Some(limits),
+                ..SlotCostAuditConfig::default()
+            },
+        );
+        let rejections = slot_cost.hypothetical_rejections.unwrap();
+        assert_eq!(
+            rejections
+                .rejected_transactions
+                .iter()
+                .map(|rejected| rejected.reason.as_str())
+                .collect::<Vec<_>>(),
+            vec!["WouldExceedBlockMaxLimit"; 2]
+        );
+    }
+
+    #[test]
+    fn test_slot_cost_lookup_table_transactions() {
+        let entries = [new_entry(vec![new_lookup_table_transaction()])];
+
+        // Without a status meta, the written account is unknown
+        let slot_cost = CliSlotCost::new(1, &entries, &[None], &SlotCostAuditConfig::default());
+        assert_eq!(slot_cost.num_transactions, 1);
+        assert_eq!(slot_cost.num_unauditable_transactions, 1);
+        assert_eq!(slot_cost.total_cost, 0);
+        assert_eq!(slot_cost.transactions.unwrap().len(), 0);
+
+        let loaded_address = Pubkey::new_unique();
+        let meta = TransactionStatusMeta {
+            loaded_addresses: LoadedAddresses {
+                writable: vec![loaded_address],
+                readonly: vec![],
+            },
+            compute_units_consumed: Some(150),
+            ..TransactionStatusMeta::default()
+        };
+        let slot_cost =
+            CliSlotCost::new(1, &entries, &[Some(meta)], &SlotCostAuditConfig::default());
+        assert_eq!(slot_cost.num_unauditable_transactions, 0);
+        assert!(slot_cost.total_cost > 0);
+        assert!(slot_cost
+            .top_writable_accounts
+            .iter()
+            .any(|account| account.pubkey == loaded_address.to_string()));
+        assert_eq!(
+            slot_cost.transactions.unwrap()[0].actual_compute_units,
+            Some(150)
+        );
+    }
+}
@@ -0,0 +1,267 @@
+//! The `blockstore rebuild-tx-index` subcommand
+//!
+//! Backfills the transaction history of a range of slots, for nodes that enabled
+//! `--enable-rpc-transaction-history` after they had already replayed those slots. The slots are
+//! replayed from a snapshot below the range, which writes their transaction statuses, address
+//! signatures and memos, and the rewards, block time and block height of each replayed bank are
+//! written along the way. Progress is saved so that an interrupted rebuild picks up from the last
+//! slot it completed, replaying from the highest snapshot below that slot.
+
+use {
+    crate::{
+        args::parse_process_options,
+        error::{LedgerToolError, Result},
+        ledger_utils::{load_and_process_ledger, open_blockstore, open_genesis_config_by},
+        LEDGER_TOOL_DIRECTORY,
+    },
+    clap::{value_t, value_t_or_exit, ArgMatches},
+    log::*,
+    serde::{Deserialize, Serialize},
+    solana_ledger::{blockstore::Blockstore, blockstore_options::AccessType},
+    solana_runtime::{
+        bank::Bank, snapshot_archive_info::SnapshotArchiveInfoGetter, snapshot_utils,
+    },
+    solana_sdk::clock::Slot,
+    solana_transaction_status::Reward,
+    std::{
+        fs,
+        path::{Path, PathBuf},
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Arc,
+        },
+    },
+};
+
+const PROGRESS_FILE: &str = "rebuild-tx-index-progress.json";
+const SNAPSHOT_ARCHIVES_DIRECTORY: &str = "rebuild-tx-index-snapshot-archives";
+
+#[derive(Debug, Serialize, Deserialize)]
+struct RebuildProgress {
+    starting_slot: Slot,
+    ending_slot: Slot,
+    /// The highest slot whose transaction history has been rebuilt
+    completed_slot: Slot,
+}
+
+impl RebuildProgress {
+    fn load(path: &Path) -> Result<Option<Self>> {
+        match fs::read(path) {
+            Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn save(&self, path: &Path) -> Result<()> {
+        // Write to a temporary file first so that an interrupted save never leaves a torn file
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, serde_json::to_vec(self)?)?;
+        fs::rename(tmp_path, path)?;
+        Ok(())
+    }
+}
+
+pub fn rebuild_tx_index(ledger_path: &Path, arg_matches: &ArgMatches<'_>) -> Result<()> {
+    let starting_slot = value_t_or_exit!(arg_matches, "starting_slot", Slot);
+    let ending_slot = value_t_or_exit!(arg_matches, "ending_slot", Slot);
+    if ending_slot < starting_slot {
+        return Err(LedgerToolError::BadArgument(format!(
+            "starting slot {starting_slot} should be less than or equal to ending slot \
+             {ending_slot}"
+        )));
+    }
+
+    let ledger_tool_path = ledger_path.join(LEDGER_TOOL_DIRECTORY);
+    fs::create_dir_all(&ledger_tool_path)?;
+    let progress_path = ledger_tool_path.join(PROGRESS_FILE);
+    let resume_slot = match RebuildProgress::load(&progress_path)? {
+        Some(progress)
+            if progress.starting_slot == starting_slot && progress.ending_slot == ending_slot =>
+        {
+            info!(
+                "Resuming the rebuild of slots {starting_slot}..={ending_slot} after slot {}",
+                progress.completed_slot
+            );
+            progress.completed_slot.saturating_add(1)
+        }
+        _ => starting_slot,
+    };
+
+    let (full_snapshot_archives_dir, incremental_snapshot_archives_dir) =
+        snapshot_archives_dirs_below(ledger_path, arg_matches, resume_slot)?;
+
+    let genesis_config = open_genesis_config_by(ledger_path, arg_matches);
+    // Writing the rebuilt columns needs primary access, so the validator must not be running
+    let blockstore = Arc::new(open_blockstore(
+        ledger_path,
+        arg_matches,
+        AccessType::PrimaryForMaintenance,
+    ));
+    let max_complete_transaction_status_slot = Arc::<AtomicU64>::default();
+
+    let mut process_options = parse_process_options(ledger_path, arg_matches);
+    process_options.halt_at_slot = Some(ending_slot);
+    process_options.slot_callback = Some({
+        let blockstore = blockstore.clone();
+        let max_complete_transaction_status_slot = max_complete_transaction_status_slot.clone();
+        let saved_slot = AtomicU64::new(0);
+        Arc::new(move |bank: &Bank| {
+            if (starting_slot..=ending_slot).contains(&bank.slot()) {
+                write_bank_metadata(&blockstore, bank);
+            }
+
+            // The metadata of every bank up to the completed slot was written by earlier
+            // callbacks, so the slot is done once its transaction statuses are written as well
+            let completed_slot = max_complete_transaction_status_slot
+                .load(Ordering::Acquire)
+                .min(ending_slot);
+            if completed_slot >= resume_slot
+                && saved_slot.swap(completed_slot, Ordering::Relaxed) != completed_slot
+            {
+                let progress = RebuildProgress {
+                    starting_slot,
+                    ending_slot,
+                    completed_slot,
+                };
+                if let Err(err) = progress.save(&progress_path) {
+                    warn!("Failed to save rebuild progress at slot {completed_slot}: {err}");
+                }
+            }
+        })
+    });
+
+    info!("Rebuilding the transaction history of slots {resume_slot}..={ending_slot}");
+    load_and_process_ledger(
+        arg_matches,
+        &genesis_config,
+        blockstore,
+        process_options,
+        Some(full_snapshot_archives_dir),
+        Some(incremental_snapshot_archives_dir),
+        Some(max_complete_transaction_status_slot),
+    )?;
+
+    let progress_path = ledger_tool_path.join(PROGRESS_FILE);
+    if progress_path.exists() {
+        fs::remove_file(progress_path)?;
+    }
+    println!("Rebuilt the transaction history of slots {starting_slot}..={ending_slot}");
+    Ok(())
+}
+
+/// Mirrors what the rewards recorder and block meta cache services write for a replayed bank
+fn write_bank_metadata(blockstore: &Blockstore, bank: &Bank) {
+    let slot = bank.slot();
+    let rewards = bank.rewards.read().unwrap();
+    if !rewards.is_empty() {
+        let rewards = rewards
+            .iter()
+            .map(|(pubkey, reward_info)| Reward {
+                pubkey: pubkey.to_string(),
+                lamports: reward_info.lamports,
+                post_balance: reward_info.post_balance,
+                reward_type: Some(reward_info.reward_type),
+                commission: reward_info.commission,
+            })
+            .collect();
+        blockstore
+            .write_rewards(slot, rewards)
+            .expect("Expect database write to succeed");
+    }
+    blockstore
+        .cache_block_time(slot, bank.clock().unix_timestamp)
+        .expect("Expect database write to succeed");
+    blockstore
+        .cache_block_height(slot, bank.block_height())
+        .expect("Expect database write to succeed");
+}
+
+/// Return the full and incremental snapshot archive directories to replay `slot` from
+///
+/// Replay starts from the highest snapshot in the directories, so when that snapshot is not
+/// below `slot`, the highest archives that are get linked into a directory of their own.
+fn snapshot_archives_dirs_below(
+    ledger_path: &Path,
+    arg_matches: &ArgMatches<'_>,
+    slot: Slot,
+) -> Result<(PathBuf, PathBuf)> {
+    let full_snapshot_archives_dir = value_t!(arg_matches, "snapshots", String)
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| ledger_path.to_path_buf());
+    let incremental_snapshot_archives_dir =
+        value_t!(arg_matches, "incremental_snapshot_archive_path", String)
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| full_snapshot_archives_dir.clone());
+
+    let full_snapshot_archive =
+        snapshot_utils::get_full_snapshot_archives(&full_snapshot_archives_dir)
+            .into_iter()
+            .filter(|archive| archive.slot() < slot)
+            .max()
+            .ok_or_else(|| {
+                LedgerToolError::BadArgument(format!(
+                    "no full snapshot archive below slot {slot} in {}; the transaction history \
+                     of a slot can only be rebuilt by replaying it from an earlier snapshot",
+                    full_snapshot_archives_dir.display(),
+                ))
+            })?;
+    let incremental_snapshot_archive =
+        snapshot_utils::get_incremental_snapshot_archives(&incremental_snapshot_archives_dir)
+            .into_iter()
+            .filter(|archive| {
+                archive.base_slot() == full_snapshot_archive.slot() && archive.slot() < slot
+            })
+            .max();
+    info!(
+        "Replaying from full snapshot slot {} and incremental snapshot slot {:?}",
+        full_snapshot_archive.slot(),
+        incremental_snapshot_archive
+            .as_ref()
+            .map(|archive| archive.slot()),
+    );
+
+    let highest_full_snapshot_slot =
+        snapshot_utils::get_highest_full_snapshot_archive_slot(&full_snapshot_archives_dir);
+    let highest_incremental_snapshot_slot =
+        snapshot_utils::get_highest_incremental_snapshot_archive_slot(
+            &incremental_snapshot_archives_dir,
+            full_snapshot_archive.slot(),
+        );
+    if highest_full_snapshot_slot == Some(full_snapshot_archive.slot())
+        && highest_incremental_snapshot_slot
+            == incremental_snapshot_archive
+                .as_ref()
+                .map(|archive| archive.slot())
+    {
+        return Ok((
+            full_snapshot_archives_dir,
+            incremental_snapshot_archives_dir,
+        ));
+    }
+
+    let archives_dir = ledger_path
+        .join(LEDGER_TOOL_DIRECTORY)
+        .join(SNAPSHOT_ARCHIVES_DIRECTORY);
+    if archives_dir.exists() {
+        fs::remove_dir_all(&archives_dir)?;
+    }
+    fs::create_dir_all(&archives_dir)?;
+    let archive_paths = std::iter::once(full_snapshot_archive.path()).chain(
+        incremental_snapshot_archive
+            .as_ref()
+            .map(|archive| archive.path()),
+    );
+    for archive_path in archive_paths {
+        let link_path = archives_dir.join(archive_path.file_name().unwrap());
+        fs::hard_link(archive_path, &link_path).map_err(|err| {
+            LedgerToolError::BadArgument(format!(
+                "failed to link snapshot archive {} into {}: {err}; move it to the file system \
+                 of the ledger to rebuild from it",
+                archive_path.display(),
+                archives_dir.display(),
+            ))
+        })?;
+    }
+    Ok((archives_dir.clone(), archives_dir))
+}
@@ -3,7 +3,8 @@ use {
     solana_entry::entry,
     solana_ledger::{
         blockstore, blockstore::Blockstore, blockstore_options::ShredStorageType,
-        create_new_tmp_ledger, create_new_tmp_ledger_fifo, genesis_utils::create_genesis_config,
+        blockstore_processor::fill_blockstore_slot_with_ticks, create_new_tmp_ledger,
+        create_new_tmp_ledger_fifo, genesis_utils::create_genesis_config,
         get_tmp_ledger_path_auto_delete,
     },
     solana_sdk::hash::Hash,
@@ -135,3 +136,109 @@ fn copy_test() {
     ledger_tool_copy_test("fifo", "level");
     ledger_tool_copy_test("fifo", "fifo");
 }
+
+#[test]
+fn json_output() {
+    let genesis_config = create_genesis_config(100).genesis_config;
+    let (ledger_path, blockhash) = create_new_tmp_ledger!(&genesis_config);
+    let ledger_path = ledger_path.to_str().unwrap();
+
+    let output = run_ledger_tool(&["-l", ledger_path, "print", "--output", "json"]);
+    assert!(output.status.success());
+    let ledger: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let slots = ledger["ledger"].as_array().unwrap();
+    assert_eq!(slots.len(), 1);
+    assert_eq!(slots[0]["slot"], 0);
+    assert_eq!(slots[0]["blockhash"], blockhash.to_string());
+    assert_eq!(
+        slots[0]["entries"].as_array().unwrap().len(),
+        genesis_config.ticks_per_slot as usize
+    );
+    assert_eq!(slots[0]["cost"]["numTransactions"], 0);
+
+    let output = run_ledger_tool(&["-l", ledger_path, "slot", "0", "--output", "json-compact"]);
+    assert!(output.status.success());
+    let slots: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(slots[0], ledger["ledger"][0]);
+}
+
+#[test]
+fn rebuild_tx_index() {
+    const ENDING_SLOT: u64 = 6;
+    const INTERRUPTED_SLOT: u64 = 3;
+    let genesis_config = create_genesis_config(100).genesis_config;
+    let (ledger_path, blockhash) = create_new_tmp_ledger!(&genesis_config);
+    {
+        let blockstore = Blockstore::open(&ledger_path).unwrap();
+        let mut last_entry_hash = blockhash;
+        for slot in 1..=ENDING_SLOT {
+            last_entry_hash = fill_blockstore_slot_with_ticks(
+                &blockstore,
+                genesis_config.ticks_per_slot,
+                slot,
+                slot - 1,
+                last_entry_hash,
+            );
+        }
+        blockstore
+            .set_roots((0..=ENDING_SLOT).collect::<Vec<_>>().iter())
+            .unwrap();
+    }
+    let ledger_path_str = ledger_path.to_str().unwrap();
+    for snapshot_slot in [0, INTERRUPTED_SLOT] {
+        let output = run_ledger_tool(&[
+            "-l",
+            ledger_path_str,
+            "create-snapshot",
+            &snapshot_slot.to_string(),
+            ledger_path_str,
+        ]);
+        assert!(output.status.success(), "{output:?}");
+    }
+    let rebuilt_slots = || {
+        let blockstore = Blockstore::open(&ledger_path).unwrap();
+        (1..=ENDING_SLOT)
+            .map(|slot| blockstore.get_block_height(slot).unwrap().is_some())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(rebuilt_slots(), vec![false; ENDING_SLOT as usize]);
+
+    // An interrupted rebuild resumes from the snapshot below the first slot it didn't complete
+    let progress_path = ledger_path
+        .join("ledger_tool")
+        .join("rebuild-tx-index-progress.json");
+    fs::create_dir_all(progress_path.parent().unwrap()).unwrap();
+    fs::write(
+        &progress_path,
+        format!(
+            r#"{{"starting_slot":1,"ending_slot":{ENDING_SLOT},"completed_slot":{INTERRUPTED_SLOT}}}"#
+        ),
+    )
+    .unwrap();
+    let rebuild_args = [
+        "-l",
+        ledger_path_str,
+        "blockstore",
+        "rebuild-tx-index",
+        "--starting-slot",
+        "1",
+        "--ending-slot",
+        &ENDING_SLOT.to_string(),
+    ];
+    let output = run_ledger_tool(&rebuild_args);
+    assert!(output.status.success(), "{output:?}");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Resuming the rebuild"));
+    assert!(!progress_path.exists());
+    assert_eq!(
+        rebuilt_slots(),
+        (1..=ENDING_SLOT)
+            .map(|slot| slot > INTERRUPTED_SLOT)
+            .collect::<Vec<_>>()
+    );
+
+    // Without saved progress the whole range is replayed, from the snapshot below it
+    let output = run_ledger_tool(&rebuild_args);
+    assert!(output.status.success(), "{output:?}");
+    assert!(!String::from_utf8_lossy(&output.stderr).contains("Resuming the rebuild"));
+    assert_eq!(rebuilt_slots(), vec![true; ENDING_SLOT as usize]);
+}
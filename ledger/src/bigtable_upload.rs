@@ -4,6 +4,7 @@ use {
     log::*,
     solana_measure::measure::Measure,
     solana_sdk::clock::Slot,
+    solana_storage_bigtable::LongTermLedgerStorage,
     std::{
         cmp::{max, min},
         collections::HashSet,
@@ -41,12 +42,12 @@ struct BlockstoreLoadStats {
     pub elapsed: Duration,
 }
 
-/// Uploads a range of blocks from a Blockstore to bigtable LedgerStorage
+/// Uploads a range of blocks from a Blockstore to long-term ledger storage, such as bigtable
 /// Returns the Slot of the last block checked. If no blocks in the range `[staring_slot,
 /// ending_slot]` are found in Blockstore, this value is equal to `ending_slot`.
 pub async fn upload_confirmed_blocks(
     blockstore: Arc<Blockstore>,
-    bigtable: solana_storage_bigtable::LedgerStorage,
+    bigtable: Arc<dyn LongTermLedgerStorage>,
     starting_slot: Slot,
     ending_slot: Slot,
     config: ConfirmedBlockUploadConfig,
@@ -80,6 +80,7 @@ use {
     thiserror::Error,
     trees::{Tree, TreeWalk},
 };
+pub mod blockstore_check;
 pub mod blockstore_purge;
 pub use {
     crate::{
@@ -3569,11 +3570,55 @@ impl Blockstore {
             .get_int_property(RocksProperties::TOTAL_SST_FILES_SIZE)
     }
 
+    /// Compact the column named `column_name`, limited to the keys of slots `from..=to` when
+    /// given a slot range
+    pub fn compact_column(
+        &self,
+        column_name: &str,
+        slot_range: Option<(Slot, Slot)>,
+    ) -> Result<()> {
+        self.db.compact_column(column_name, slot_range)
+    }
+
+    /// Check the arguments of `compact_column` without compacting anything
+    pub fn check_compact_column(
+        &self,
+        column_name: &str,
+        slot_range: Option<(Slot, Slot)>,
+    ) -> Result<()> {
+        Database::check_compact_column(column_name, slot_range).map(|_| ())
+    }
+
+    /// Change the size limits of the shred columns of a blockstore using
+    /// `ShredStorageType::RocksFifo`, as set by `BlockstoreRocksFifoOptions` when it was opened
+    pub fn set_rocks_fifo_options(
+        &self,
+        shred_data_cf_size: Option<u64>,
+        shred_code_cf_size: Option<u64>,
+    ) -> Result<()> {
+        self.db
+            .set_rocks_fifo_options(shred_data_cf_size, shred_code_cf_size)
+    }
+
     /// Returns whether the blockstore has primary (read and write) access
     pub fn is_primary_access(&self) -> bool {
         self.db.is_primary_access()
     }
 
+    /// Catch up a blockstore opened with `AccessType::Secondary` with the writes made by the
+    /// primary instance since it was opened or last caught up, including new roots.
+    pub fn try_catch_up_with_primary(&self) -> Result<()> {
+        self.db.try_catch_up_with_primary()?;
+        let max_root = self
+            .db
+            .iter::<cf::Root>(IteratorMode::End)?
+            .next()
+            .map(|(slot, _)| slot)
+            .unwrap_or(0);
+        self.max_root.fetch_max(max_root, Ordering::Relaxed);
+        Ok(())
+    }
+
     /// Scan for any ancestors of the supplied `start_root` that are not
     /// marked as roots themselves. Mark any found slots as roots since
     /// the ancestor of a root is also inherently a root. Returns the
@@ -4849,6 +4894,74 @@ pub mod tests {
             .exists());
     }
 
+    #[test]
+    fn test_compact_column() {
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Blockstore::open(ledger_path.path()).unwrap();
+        let (shreds, _) = make_slot_entries(1, 0, 4, true);
+        blockstore.insert_shreds(shreds, None, false).unwrap();
+
+        blockstore
+            .compact_column(cf::ShredData::NAME, Some((0, 1)))
+            .unwrap();
+        blockstore
+            .compact_column(cf::TransactionStatus::NAME, None)
+            .unwrap();
+        assert!(blockstore.get_data_shred(1, 0).unwrap().is_some());
+        assert_matches!(
+            blockstore.compact_column("unknown", None),
+            Err(BlockstoreError::UnknownColumn(_))
+        );
+        assert_matches!(
+            blockstore.compact_column(cf::TransactionStatus::NAME, Some((0, 1))),
+            Err(BlockstoreError::ColumnNotKeyedBySlot(_))
+        );
+        assert_matches!(
+            blockstore.compact_column(cf::ShredData::NAME, Some((1, 0))),
+            Err(BlockstoreError::InvalidSlotRange(1, 0))
+        );
+    }
+
+    #[test]
+    fn test_set_rocks_fifo_options() {
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        {
+            let blockstore = Blockstore::open(ledger_path.path()).unwrap();
+            assert_matches!(
+                blockstore.set_rocks_fifo_options(Some(150_000_000_000), None),
+                Err(BlockstoreError::InvalidRocksFifoOptions(_))
+            );
+        }
+
+        let blockstore = Blockstore::open_with_options(
+            ledger_path.path(),
+            BlockstoreOptions {
+                column_options: LedgerColumnOptions {
+                    shred_storage_type: ShredStorageType::RocksFifo(
+                        BlockstoreRocksFifoOptions::new_for_tests(),
+                    ),
+                    ..LedgerColumnOptions::default()
+                },
+                ..BlockstoreOptions::default()
+            },
+        )
+        .unwrap();
+        assert_matches!(
+            blockstore.set_rocks_fifo_options(Some(200_000_000_000), Some(1)),
+            Err(BlockstoreError::InvalidRocksFifoOptions(_))
+        );
+        blockstore
+            .set_rocks_fifo_options(Some(200_000_000_000), None)
+            .unwrap();
+        blockstore
+            .set_rocks_fifo_options(Some(100_000_000_000), Some(100_000_000_000))
+            .unwrap();
+
+        let (shreds, _) = make_slot_entries(1, 0, 4, true);
+        blockstore.insert_shreds(shreds, None, false).unwrap();
+        assert!(blockstore.get_data_shred(1, 0).unwrap().is_some());
+    }
+
     #[test]
     fn test_insert_get_bytes() {
         // Create enough entries to ensure there are at least two shreds created
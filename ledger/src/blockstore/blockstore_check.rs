@@ -0,0 +1,774 @@
+//! Offline integrity checks of the blockstore.
+//!
+//! [`Blockstore::check`] cross-checks the columns that describe a slot against each other and
+//! against the shreds themselves, and [`Blockstore::fix_check_issues`] repairs the issues that
+//! can be repaired without any shreds.
+
+use {
+    super::*,
+    serde::Serialize,
+    std::{collections::BTreeMap, fmt},
+};
+
+/// The column a shred is stored in
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ShredColumn {
+    Data,
+    Code,
+}
+
+impl fmt::Display for ShredColumn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Data => write!(f, "data"),
+            Self::Code => write!(f, "coding"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(
+    tag = "kind",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum BlockstoreIssueKind {
+    /// The SlotMeta could not be deserialized
+    UnreadableSlotMeta { error: String },
+    /// The slot has shreds but no Index entry
+    MissingIndex,
+    /// A stored shred is not marked as present in the Index
+    ShredNotIndexed { column: ShredColumn, index: u64 },
+    /// The Index marks a shred as present that is not stored
+    IndexedShredMissing { column: ShredColumn, index: u64 },
+    /// A stored shred failed to deserialize or sanitize, or is stored under another key
+    InvalidShred {
+        column: ShredColumn,
+        index: u64,
+        error: String,
+    },
+    /// A shred signature does not verify against the slot leader
+    InvalidSignature { column: ShredColumn, index: u64 },
+    /// `SlotMeta::consumed` is not the number of consecutive data shreds from index 0
+    ConsumedMismatch { consumed: u64, expected: u64 },
+    /// `SlotMeta::received` is not one past the highest data shred index
+    ReceivedMismatch { received: u64, expected: u64 },
+    /// A data shred is stored past `SlotMeta::last_index`
+    ShredPastLastIndex { index: u64, last_index: u64 },
+    /// A data shred in `SlotMeta::completed_data_indexes` is not stored
+    MissingCompletedDataShred { index: u32 },
+    /// A coding shred has no ErasureMeta for its erasure set
+    MissingErasureMeta { fec_set_index: u32 },
+    /// The erasure config of a coding shred differs from the ErasureMeta of its erasure set
+    ErasureMetaMismatch { index: u64 },
+    /// An erasure set with shreds has no MerkleRootMeta
+    MissingMerkleRootMeta { fec_set_index: u32 },
+    /// The merkle root recomputed from a shred's merkle proof differs from the root of its
+    /// erasure set
+    MerkleRootMismatch {
+        column: ShredColumn,
+        index: u64,
+        fec_set_index: u32,
+    },
+    /// The slot is full but its shreds do not deserialize into entries
+    UnreadableEntries { error: String },
+    /// A slot in `SlotMeta::next_slots` has no SlotMeta
+    MissingChildSlotMeta { child: Slot },
+    /// A slot in `SlotMeta::next_slots` has another parent
+    ChildParentMismatch {
+        child: Slot,
+        child_parent: Option<Slot>,
+    },
+    /// The slot has no parent but is not in the Orphans column, and is neither rooted nor
+    /// connected
+    MissingOrphan,
+    /// The slot is in the Orphans column but has no SlotMeta or has a parent
+    StaleOrphan,
+    /// The slot is rooted but has no SlotMeta. Roots reconciled with an external source, such
+    /// as tower, legitimately have none, so this is only reported
+    DanglingRoot,
+}
+
+impl BlockstoreIssueKind {
+    /// Returns true if [`Blockstore::fix_check_issues`] can repair the issue
+    pub fn is_fixable(&self) -> bool {
+        matches!(self, Self::MissingOrphan | Self::StaleOrphan)
+    }
+}
+
+impl fmt::Display for BlockstoreIssueKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnreadableSlotMeta { error } => write!(f, "unreadable slot meta: {error}"),
+            Self::MissingIndex => write!(f, "shreds without an index"),
+            Self::ShredNotIndexed { column, index } => {
+                write!(f, "{column} shred {index} is not in the index")
+            }
+            Self::IndexedShredMissing { column, index } => {
+                write!(f, "indexed {column} shred {index} is missing")
+            }
+            Self::InvalidShred {
+                column,
+                index,
+                error,
+            } => write!(f, "invalid {column} shred {index}: {error}"),
+            Self::InvalidSignature { column, index } => {
+                write!(f, "{column} shred {index} has an invalid signature")
+            }
+            Self::ConsumedMismatch { consumed, expected } => {
+                write!(f, "consumed is {consumed}, expected {expected}")
+            }
+            Self::ReceivedMismatch { received, expected } => {
+                write!(f, "received is {received}, expected {expected}")
+            }
+            Self::ShredPastLastIndex { index, last_index } => {
+                write!(f, "data shred {index} is past the last index {last_index}")
+            }
+            Self::MissingCompletedDataShred { index } => {
+                write!(f, "completed data shred {index} is missing")
+            }
+            Self::MissingErasureMeta { fec_set_index } => {
+                write!(f, "erasure set {fec_set_index} has no erasure meta")
+            }
+            Self::ErasureMetaMismatch { index } => {
+                write!(f, "coding shred {index} does not match its erasure meta")
+            }
+            Self::MissingMerkleRootMeta { fec_set_index } => {
+                write!(f, "erasure set {fec_set_index} has no merkle root meta")
+            }
+            Self::MerkleRootMismatch {
+                column,
+                index,
+                fec_set_index,
+            } => write!(
+                f,
+                "merkle root of {column} shred {index} does not match erasure set \
+                 {fec_set_index}"
+            ),
+            Self::UnreadableEntries { error } => write!(f, "unreadable entries: {error}"),
+            Self::MissingChildSlotMeta { child } => {
+                write!(f, "child slot {child} has no slot meta")
+            }
+            Self::ChildParentMismatch {
+                child,
+                child_parent,
+            } => write!(f, "child slot {child} has parent {child_parent:?}"),
+            Self::MissingOrphan => write!(f, "parentless slot is not an orphan"),
+            Self::StaleOrphan => write!(f, "stale orphan"),
+            Self::DanglingRoot => write!(f, "root without a slot meta"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockstoreIssue {
+    pub slot: Slot,
+    #[serde(flatten)]
+    pub kind: BlockstoreIssueKind,
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockstoreCheckReport {
+    pub starting_slot: Slot,
+    pub ending_slot: Slot,
+    pub num_slots: u64,
+    pub num_full_slots: u64,
+    pub num_data_shreds: u64,
+    pub num_coding_shreds: u64,
+    /// Shreds whose signature was verified against the slot leader
+    pub num_verified_signatures: u64,
+    /// Shreds whose signature was not verified because their slot leader is unknown
+    pub num_unverified_signatures: u64,
+    pub issues: Vec<BlockstoreIssue>,
+}
+
+impl BlockstoreCheckReport {
+    fn push(&mut self, slot: Slot, kind: BlockstoreIssueKind) {
+        self.issues.push(BlockstoreIssue { slot, kind });
+    }
+}
+
+/// The shreds of an erasure set, with the merkle root recomputed from each shred's proof
+type ErasureSetShreds = Vec<(ShredColumn, /*index:*/ u64, Option<Hash>)>;
+
+impl Blockstore {
+    /// Checks the slots in \[`starting_slot`, `ending_slot`\], and the orphans and roots in that
+    /// range, for inconsistencies between the blockstore columns.
+    ///
+    /// When `slot_leader` is given, the signature of every shred is verified against the leader
+    /// it returns for the shred's slot.
+    pub fn check(
+        &self,
+        starting_slot: Slot,
+        ending_slot: Slot,
+        slot_leader: Option<&dyn Fn(Slot) -> Option<Pubkey>>,
+    ) -> Result<BlockstoreCheckReport> {
+        let mut report = BlockstoreCheckReport {
+            starting_slot,
+            ending_slot,
+            ..BlockstoreCheckReport::default()
+        };
+
+        let slot_metas = self.db.iter::<cf::SlotMeta>(IteratorMode::From(
+            starting_slot,
+            IteratorDirection::Forward,
+        ))?;
+        for (slot, slot_meta_bytes) in slot_metas.take_while(|(slot, _)| *slot <= ending_slot) {
+            report.num_slots += 1;
+            match deserialize::<SlotMeta>(&slot_meta_bytes) {
+                Ok(slot_meta) => self.check_slot(&slot_meta, slot_leader, &mut report)?,
+                Err(err) => report.push(
+                    slot,
+                    BlockstoreIssueKind::UnreadableSlotMeta {
+                        error: err.to_string(),
+                    },
+                ),
+            }
+        }
+
+        for slot in self
+            .orphans_iterator(starting_slot)?
+            .take_while(|slot| *slot <= ending_slot)
+        {
+            // An unreadable SlotMeta has already been reported, so leave its orphan be
+            if let Ok(slot_meta) = self.meta_cf.get(slot) {
+                if slot_meta
+                    .map(|slot_meta| !slot_meta.is_orphan())
+                    .unwrap_or(true)
+                {
+                    report.push(slot, BlockstoreIssueKind::StaleOrphan);
+                }
+            }
+        }
+
+        // The max root may legitimately have no SlotMeta, such as the root of a snapshot the
+        // node started from before it received any shreds
+        let max_root = self.max_root();
+        for slot in self
+            .rooted_slot_iterator(starting_slot)?
+            .take_while(|slot| *slot <= ending_slot)
+        {
+            if slot != max_root && matches!(self.meta_cf.get(slot), Ok(None)) {
+                report.push(slot, BlockstoreIssueKind::DanglingRoot);
+            }
+        }
+
+        report.issues.sort_by_key(|issue| issue.slot);
+        Ok(report)
+    }
+
+    fn check_slot(
+        &self,
+        slot_meta: &SlotMeta,
+        slot_leader: Option<&dyn Fn(Slot) -> Option<Pubkey>>,
+        report: &mut BlockstoreCheckReport,
+    ) -> Result<()> {
+        let slot = slot_meta.slot;
+        let index = self.index_cf.get(slot)?;
+        let leader = slot_leader.map(|slot_leader| slot_leader(slot));
+        let mut erasure_sets = BTreeMap::<u32, ErasureSetShreds>::new();
+        let mut data_indexes = BTreeSet::new();
+        let mut coding_indexes = BTreeSet::new();
+        let mut missing_erasure_metas = BTreeSet::new();
+
+        let shreds = self
+            .slot_data_iterator(slot, 0)?
+            .map(|(key, payload)| (ShredColumn::Data, key, payload))
+            .chain(
+                self.slot_coding_iterator(slot, 0)?
+                    .map(|(key, payload)| (ShredColumn::Code, key, payload)),
+            );
+        for (column, (_, shred_index), payload) in shreds {
+            let (indexed, stored_indexes) = match column {
+                ShredColumn::Data => {
+                    report.num_data_shreds += 1;
+                    (index.as_ref().map(Index::data), &mut data_indexes)
+                }
+                ShredColumn::Code => {
+                    report.num_coding_shreds += 1;
+                    (index.as_ref().map(Index::coding), &mut coding_indexes)
+                }
+            };
+            stored_indexes.insert(shred_index);
+            if matches!(indexed, Some(indexed) if !indexed.contains(shred_index)) {
+                report.push(
+                    slot,
+                    BlockstoreIssueKind::ShredNotIndexed {
+                        column,
+                        index: shred_index,
+                    },
+                );
+            }
+
+            let shred = match Self::check_shred(slot, shred_index, column, payload) {
+                Ok(shred) => shred,
+                Err(error) => {
+                    report.push(
+                        slot,
+                        BlockstoreIssueKind::InvalidShred {
+                            column,
+                            index: shred_index,
+                            error,
+                        },
+                    );
+                    continue;
+                }
+            };
+
+            match leader {
+                Some(Some(leader)) if shred.verify(&leader) => report.num_verified_signatures += 1,
+                Some(Some(_)) => report.push(
+                    slot,
+                    BlockstoreIssueKind::InvalidSignature {
+                        column,
+                        index: shred_index,
+                    },
+                ),
+                Some(None) => report.num_unverified_signatures += 1,
+                None => (),
+            }
+
+            if column == ShredColumn::Code {
+                match self.erasure_meta(shred.erasure_set())? {
+                    Some(erasure_meta) if !erasure_meta.check_coding_shred(&shred) => report.push(
+                        slot,
+                        BlockstoreIssueKind::ErasureMetaMismatch { index: shred_index },
+                    ),
+                    Some(_) => (),
+                    // Report a missing ErasureMeta once per erasure set
+                    None if missing_erasure_metas.insert(shred.fec_set_index()) => report.push(
+                        slot,
+                        BlockstoreIssueKind::MissingErasureMeta {
+                            fec_set_index: shred.fec_set_index(),
+                        },
+                    ),
+                    None => (),
+                }
+            }
+
+            erasure_sets
+                .entry(shred.fec_set_index())
+                .or_default()
+                .push((column, shred_index, shred.merkle_root().ok()));
+        }
+
+        if index.is_none() && !(data_indexes.is_empty() && coding_indexes.is_empty()) {
+            report.push(slot, BlockstoreIssueKind::MissingIndex);
+        }
+        if let Some(index) = &index {
+            for (column, indexed, stored_indexes) in [
+                (ShredColumn::Data, index.data(), &data_indexes),
+                (ShredColumn::Code, index.coding(), &coding_indexes),
+            ] {
+                for shred_index in indexed.range(..) {
+                    if !stored_indexes.contains(shred_index) {
+                        report.push(
+                            slot,
+                            BlockstoreIssueKind::IndexedShredMissing {
+                                column,
+                                index: *shred_index,
+                            },
+                        );
+                    }
+                }
+            }
+        }
+
+        self.check_slot_meta(slot_meta, &data_indexes, report)?;
+
+        for (fec_set_index, shreds) in erasure_sets {
+            let merkle_root = match self.merkle_root_meta_cf.get((slot, fec_set_index))? {
+                Some(merkle_root_meta) => merkle_root_meta.merkle_root(),
+                None => {
+                    report.push(
+                        slot,
+                        BlockstoreIssueKind::MissingMerkleRootMeta { fec_set_index },
+                    );
+                    // Still check that the shreds of the erasure set agree with each other
+                    shreds[0].2
+                }
+            };
+            for (column, index, shred_merkle_root) in shreds {
+                if shred_merkle_root != merkle_root {
+                    report.push(
+                        slot,
+                        BlockstoreIssueKind::MerkleRootMismatch {
+                            column,
+                            index,
+                            fec_set_index,
+                        },
+                    );
+                }
+            }
+        }
+
+        if slot_meta.is_full() {
+            report.num_full_slots += 1;
+            if let Err(err) = self.get_slot_entries(slot, 0) {
+                report.push(
+                    slot,
+                    BlockstoreIssueKind::UnreadableEntries {
+                        error: err.to_string(),
+                    },
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks the shred counters and chaining of a SlotMeta against the stored data shreds
+    fn check_slot_meta(
+        &self,
+        slot_meta: &SlotMeta,
+        data_indexes: &BTreeSet<u64>,
+        report: &mut BlockstoreCheckReport,
+    ) -> Result<()> {
+        let slot = slot_meta.slot;
+        let expected_consumed = (0..)
+            .zip(data_indexes)
+            .take_while(|(expected, index)| expected == *index)
+            .count() as u64;
+        if slot_meta.consumed != expected_consumed {
+            report.push(
+                slot,
+                BlockstoreIssueKind::ConsumedMismatch {
+                    consumed: slot_meta.consumed,
+                    expected: expected_consumed,
+                },
+            );
+        }
+        let expected_received = data_indexes.last().map(|index| index + 1).unwrap_or(0);
+        if slot_meta.received != expected_received {
+            report.push(
+                slot,
+                BlockstoreIssueKind::ReceivedMismatch {
+                    received: slot_meta.received,
+                    expected: expected_received,
+                },
+            );
+        }
+        if let Some(last_index) = slot_meta.last_index {
+            for index in data_indexes.range(last_index + 1..) {
+                report.push(
+                    slot,
+                    BlockstoreIssueKind::ShredPastLastIndex {
+                        index: *index,
+                        last_index,
+                    },
+                );
+            }
+        }
+        for index in &slot_meta.completed_data_indexes {
+            if !data_indexes.contains(&u64::from(*index)) {
+                report.push(
+                    slot,
+                    BlockstoreIssueKind::MissingCompletedDataShred { index: *index },
+                );
+            }
+        }
+
+        for child in &slot_meta.next_slots {
+            match self.meta_cf.get(*child) {
+                Ok(Some(child_meta)) if child_meta.parent_slot != Some(slot) => report.push(
+                    slot,
+                    BlockstoreIssueKind::ChildParentMismatch {
+                        child: *child,
+                        child_parent: child_meta.parent_slot,
+                    },
+                ),
+                Ok(Some(_)) => (),
+                Ok(None) => report.push(
+                    slot,
+                    BlockstoreIssueKind::MissingChildSlotMeta { child: *child },
+                ),
+                // Reported when the child itself is checked
+                Err(_) => (),
+            }
+        }
+        // The SlotMeta of a snapshot root the node started from has no parent either, but is
+        // connected and rooted rather than an orphan
+        if slot_meta.is_orphan()
+            && !slot_meta.is_connected()
+            && !self.is_root(slot)
+            && self.orphans_cf.get(slot)?.is_none()
+        {
+            report.push(slot, BlockstoreIssueKind::MissingOrphan);
+        }
+        Ok(())
+    }
+
+    /// Deserializes and sanitizes a stored shred, and checks that it is stored under its own key
+    fn check_shred(
+        slot: Slot,
+        index: u64,
+        column: ShredColumn,
+        payload: Box<[u8]>,
+    ) -> std::result::Result<Shred, String> {
+        let shred =
+            Shred::new_from_serialized_shred(payload.into_vec()).map_err(|err| err.to_string())?;
+        shred.sanitize().map_err(|err| err.to_string())?;
+        if shred.slot() != slot
+            || u64::from(shred.index()) != index
+            || shred.is_code() != (column == ShredColumn::Code)
+        {
+            return Err(format!(
+                "shred of slot {} index {} type {:?} is stored under the wrong key",
+                shred.slot(),
+                shred.index(),
+                shred.shred_type(),
+            ));
+        }
+        Ok(shred)
+    }
+
+    /// Repairs the issues of a check report that are fixable, and returns how many were fixed.
+    ///
+    /// Missing orphans are added to the Orphans column, and stale orphans are removed from it.
+    pub fn fix_check_issues(&self, issues: &[BlockstoreIssue]) -> Result<usize> {
+        let mut write_batch = self.db.batch()?;
+        let mut num_fixed = 0;
+        for issue in issues {
+            match issue.kind {
+                BlockstoreIssueKind::MissingOrphan => {
+                    write_batch.put::<cf::Orphans>(issue.slot, &true)?
+                }
+                BlockstoreIssueKind::StaleOrphan => {
+                    write_batch.delete::<cf::Orphans>(issue.slot)?
+                }
+                _ => continue,
+            }
+            num_fixed += 1;
+        }
+        self.db.write(write_batch)?;
+        Ok(num_fixed)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use {
+        super::*,
+        crate::{
+            blockstore::{make_many_slot_entries, make_slot_entries},
+            get_tmp_ledger_path_auto_delete,
+        },
+        assert_matches::assert_matches,
+    };
+
+    fn issue_kinds(report: &BlockstoreCheckReport, slot: Slot) -> Vec<&BlockstoreIssueKind> {
+        report
+            .issues
+            .iter()
+            .filter(|issue| issue.slot == slot)
+            .map(|issue| &issue.kind)
+            .collect()
+    }
+
+    #[test]
+    fn test_check_consistent_blockstore() {
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Blockstore::open(ledger_path.path()).unwrap();
+
+        let (shreds, _) = make_many_slot_entries(0, 4, 10);
+        let num_shreds = shreds.len() as u64;
+        blockstore.insert_shreds(shreds, None, false).unwrap();
+        blockstore.set_roots([0, 1, 2].iter()).unwrap();
+
+        let report = blockstore.check(0, Slot::MAX, None).unwrap();
+        assert_eq!(report.issues, vec![]);
+        assert_eq!(report.num_slots, 4);
+        assert_eq!(report.num_full_slots, 4);
+        assert_eq!(report.num_data_shreds, num_shreds);
+        assert_eq!(report.num_verified_signatures, 0);
+
+        let report = blockstore.check(1, 2, None).unwrap();
+        assert_eq!(report.num_slots, 2);
+    }
+
+    #[test]
+    fn test_check_signatures() {
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Blockstore::open(ledger_path.path()).unwrap();
+
+        let leader = Keypair::new();
+        let entries = create_ticks(10, 1, Hash::new_unique());
+        let (data_shreds, coding_shreds) = Shredder::new(1, 0, 0, 0).unwrap().entries_to_shreds(
+            &leader,
+            &entries,
+            true, // is_last_in_slot
+            Some(Hash::new_unique()),
+            0,    // next_shred_index
+            0,    // next_code_index
+            true, // merkle_variant
+            &ReedSolomonCache::default(),
+            &mut ProcessShredsStats::default(),
+        );
+        let num_shreds = (data_shreds.len() + coding_shreds.len()) as u64;
+        let shreds = data_shreds.into_iter().chain(coding_shreds).collect();
+        blockstore.insert_shreds(shreds, None, false).unwrap();
+
+        let slot_leader = |slot: Slot| (slot == 1).then_some(leader.pubkey());
+        let report = blockstore.check(1, 1, Some(&slot_leader)).unwrap();
+        assert_eq!(report.issues, vec![]);
+        assert_eq!(report.num_verified_signatures, num_shreds);
+
+        let other_leader = Pubkey::new_unique();
+        let report = blockstore
+            .check(1, 1, Some(&|_: Slot| Some(other_leader)))
+            .unwrap();
+        assert_eq!(report.issues.len() as u64, num_shreds);
+        assert!(report
+            .issues
+            .iter()
+            .all(|issue| matches!(issue.kind, BlockstoreIssueKind::InvalidSignature { .. })));
+
+        let report = blockstore.check(1, 1, Some(&|_: Slot| None)).unwrap();
+        assert_eq!(report.issues, vec![]);
+        assert_eq!(report.num_unverified_signatures, num_shreds);
+    }
+
+    #[test]
+    fn test_check_corrupted_shreds() {
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Blockstore::open(ledger_path.path()).unwrap();
+
+        let (shreds, _) = make_slot_entries(1, 0, 300, true);
+        assert!(shreds.len() > 3);
+        let num_shreds = shreds.len() as u64;
+        let payload = shreds[2].payload().clone();
+        blockstore.insert_shreds(shreds, None, false).unwrap();
+
+        // A shred stored under another index, and a missing shred
+        blockstore
+            .data_shred_cf
+            .put_bytes((1, 1), &payload)
+            .unwrap();
+        blockstore.data_shred_cf.delete((1, 3)).unwrap();
+
+        let report = blockstore.check(0, Slot::MAX, None).unwrap();
+        let kinds = issue_kinds(&report, 1);
+        assert_matches!(
+            kinds[0],
+            BlockstoreIssueKind::InvalidShred {
+                column: ShredColumn::Data,
+                index: 1,
+                ..
+            }
+        );
+        assert!(kinds.contains(&&BlockstoreIssueKind::IndexedShredMissing {
+            column: ShredColumn::Data,
+            index: 3,
+        }));
+        assert!(kinds.contains(&&BlockstoreIssueKind::ConsumedMismatch {
+            consumed: num_shreds,
+            expected: 3,
+        }));
+        assert_matches!(
+            kinds.last().unwrap(),
+            BlockstoreIssueKind::UnreadableEntries { .. }
+        );
+    }
+
+    #[test]
+    fn test_check_and_fix_orphans_and_roots() {
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Blockstore::open(ledger_path.path()).unwrap();
+
+        // Slot 4 is the orphaned parent of slot 5
+        let (shreds, _) = make_slot_entries(5, 4, 10, true);
+        blockstore.insert_shreds(shreds, None, false).unwrap();
+        assert_eq!(blockstore.orphan(4).unwrap(), Some(true));
+        assert_eq!(blockstore.check(0, Slot::MAX, None).unwrap().issues, vec![]);
+
+        blockstore.orphans_cf.delete(4).unwrap();
+        blockstore.orphans_cf.put(7, &true).unwrap();
+        blockstore.set_roots([2, 5].iter()).unwrap();
+
+        let report = blockstore.check(0, Slot::MAX, None).unwrap();
+        assert_eq!(
+            report.issues,
+            vec![
+                BlockstoreIssue {
+                    slot: 2,
+                    kind: BlockstoreIssueKind::DanglingRoot,
+                },
+                BlockstoreIssue {
+                    slot: 4,
+                    kind: BlockstoreIssueKind::MissingOrphan,
+                },
+                BlockstoreIssue {
+                    slot: 7,
+                    kind: BlockstoreIssueKind::StaleOrphan,
+                },
+            ]
+        );
+        assert!(!report.issues[0].kind.is_fixable());
+        assert!(report.issues[1..]
+            .iter()
+            .all(|issue| issue.kind.is_fixable()));
+
+        // The dangling root is only reported
+        assert_eq!(blockstore.fix_check_issues(&report.issues).unwrap(), 2);
+        assert_eq!(
+            blockstore.check(0, Slot::MAX, None).unwrap().issues,
+            vec![BlockstoreIssue {
+                slot: 2,
+                kind: BlockstoreIssueKind::DanglingRoot,
+            }]
+        );
+        assert_eq!(blockstore.orphan(4).unwrap(), Some(true));
+        assert!(blockstore.is_root(2));
+        assert!(blockstore.is_root(5));
+    }
+
+    #[test]
+    fn test_check_snapshot_startup() {
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Blockstore::open(ledger_path.path()).unwrap();
+
+        // A node that started from a snapshot at slot 10 writes a parentless, connected
+        // SlotMeta for it that is not in the Orphans column
+        blockstore
+            .set_and_chain_connected_on_root_and_next_slots(10)
+            .unwrap();
+        assert!(blockstore.meta(10).unwrap().unwrap().is_orphan());
+        assert_eq!(blockstore.orphan(10).unwrap(), None);
+        assert_eq!(blockstore.check(0, Slot::MAX, None).unwrap().issues, vec![]);
+
+        blockstore.set_roots([10].iter()).unwrap();
+        let (shreds, _) = make_slot_entries(11, 10, 10, true);
+        blockstore.insert_shreds(shreds, None, false).unwrap();
+        blockstore.set_roots([11].iter()).unwrap();
+        assert_eq!(blockstore.check(0, Slot::MAX, None).unwrap().issues, vec![]);
+    }
+
+    #[test]
+    fn test_check_roots_reconciled_at_startup() {
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Blockstore::open(ledger_path.path()).unwrap();
+
+        let (shreds, _) = make_slot_entries(5, 4, 10, true);
+        blockstore.insert_shreds(shreds, None, false).unwrap();
+        // Roots reconciled with tower at startup have no SlotMeta
+        blockstore
+            .mark_slots_as_if_rooted_normally_at_startup(vec![(2, None), (3, None)], false)
+            .unwrap();
+        blockstore.set_roots([5].iter()).unwrap();
+
+        let report = blockstore.check(0, Slot::MAX, None).unwrap();
+        assert_eq!(
+            issue_kinds(&report, 2),
+            vec![&BlockstoreIssueKind::DanglingRoot]
+        );
+        assert_eq!(
+            issue_kinds(&report, 3),
+            vec![&BlockstoreIssueKind::DanglingRoot]
+        );
+        assert_eq!(blockstore.fix_check_issues(&report.issues).unwrap(), 0);
+        assert!(blockstore.is_root(2));
+        assert!(blockstore.is_root(3));
+    }
+}
@@ -0,0 +1,609 @@
+//! Portable archives of a range of rooted slots
+//!
+//! An archive holds everything needed to rebuild the rooted blocks of a slot range in a fresh
+//! `Blockstore`: the data shreds of each slot, its block time, block height and rewards, and the
+//! status, memos and account keys of each of its transactions, from which the address
+//! signatures are rebuilt.
+//!
+//! The file starts with `ARCHIVE_MAGIC` and an `ArchiveHeader`, followed by one record per slot
+//! and an end record, each a little-endian u64 length followed by a bincode-serialized
+//! `ArchiveRecord`. It ends with the sha256 hash of every preceding byte.
+
+use {
+    crate::{
+        blockstore::{Blockstore, BlockstoreError},
+        shred::{self, Shred},
+    },
+    log::*,
+    prost::Message,
+    serde::{Deserialize, Serialize},
+    solana_sdk::{
+        clock::{Slot, UnixTimestamp},
+        hash::{Hash, Hasher, HASH_BYTES},
+        message::{
+            v0::{LoadedAddresses, LoadedMessage},
+            LegacyMessage, SanitizedMessage, VersionedMessage,
+        },
+        pubkey::Pubkey,
+        signature::Signature,
+        transaction::VersionedTransaction,
+    },
+    solana_storage_proto::convert::generated,
+    solana_transaction_status::{Rewards, TransactionStatusMeta},
+    std::{
+        fs::File,
+        io::{self, BufReader, BufWriter, Read, Write},
+        path::Path,
+    },
+    thiserror::Error,
+};
+
+pub const ARCHIVE_MAGIC: &[u8; 8] = b"SOLBSARC";
+pub const ARCHIVE_VERSION: u32 = 1;
+
+// Records are at most a slot's worth of shreds and transaction statuses; anything larger is
+// corruption rather than data.
+const MAX_RECORD_SIZE: u64 = 1 << 30;
+
+#[derive(Error, Debug)]
+pub enum BlockstoreArchiveError {
+    #[error("io error: {0}")]
+    Io(#[from] io::Error),
+    #[error("blockstore error: {0}")]
+    Blockstore(#[from] BlockstoreError),
+    #[error("serialization error: {0}")]
+    Serialize(#[from] Box<bincode::ErrorKind>),
+    #[error("protobuf decode error: {0}")]
+    ProtobufDecode(#[from] prost::DecodeError),
+    #[error("invalid shred: {0}")]
+    InvalidShred(#[from] shred::Error),
+    #[error("not a blockstore archive")]
+    InvalidMagic,
+    #[error("unsupported archive version: {0}")]
+    UnsupportedVersion(u32),
+    #[error("invalid archive: {0}")]
+    InvalidArchive(String),
+    #[error("archive checksum mismatch: expected {expected}, computed {computed}")]
+    ChecksumMismatch { expected: Hash, computed: Hash },
+    #[error("target blockstore is not empty")]
+    BlockstoreNotEmpty,
+}
+
+pub type Result<T> = std::result::Result<T, BlockstoreArchiveError>;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ArchiveHeader {
+    version: u32,
+    start_slot: Slot,
+    end_slot: Slot,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ArchiveTransaction {
+    signature: Signature,
+    index: u32,
+    writable_keys: Vec<Pubkey>,
+    readonly_keys: Vec<Pubkey>,
+    // Protobuf encoded `generated::TransactionStatusMeta`, as in the blockstore
+    status_meta: Vec<u8>,
+    memos: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ArchiveSlot {
+    slot: Slot,
+    data_shreds: Vec<Vec<u8>>,
+    block_time: Option<UnixTimestamp>,
+    block_height: Option<u64>,
+    rewards: Option<Rewards>,
+    transactions: Vec<ArchiveTransaction>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+enum ArchiveRecord {
+    Slot(ArchiveSlot),
+    End { num_slots: u64 },
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ArchiveSummary {
+    pub start_slot: Slot,
+    pub end_slot: Slot,
+    pub num_slots: u64,
+    pub num_shreds: u64,
+    pub num_transactions: u64,
+}
+
+impl ArchiveSummary {
+    fn add_slot(&mut self, slot: &ArchiveSlot) {
+        self.num_slots += 1;
+        self.num_shreds += slot.data_shreds.len() as u64;
+        self.num_transactions += slot.transactions.len() as u64;
+    }
+}
+
+struct HashingWriter<W> {
+    inner: W,
+    hasher: Hasher,
+}
+
+impl<W: Write> Write for HashingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let len = self.inner.write(buf)?;
+        self.hasher.hash(&buf[..len]);
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+struct HashingReader<R> {
+    inner: R,
+    hasher: Hasher,
+}
+
+impl<R: Read> Read for HashingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.inner.read(buf)?;
+        self.hasher.hash(&buf[..len]);
+        Ok(len)
+    }
+}
+
+fn write_record<W: Write, T: serde::Serialize>(writer: &mut W, record: &T) -> Result<()> {
+    let bytes = bincode::serialize(record)?;
+    writer.write_all(&(bytes.len() as u64).to_le_bytes())?;
+    writer.write_all(&bytes)?;
+    Ok(())
+}
+
+fn read_record<R: Read, T: serde::de::DeserializeOwned>(reader: &mut R) -> Result<T> {
+    let mut len = [0; 8];
+    reader.read_exact(&mut len)?;
+    let len = u64::from_le_bytes(len);
+    if len > MAX_RECORD_SIZE {
+        return Err(BlockstoreArchiveError::InvalidArchive(format!(
+            "record of {len} bytes"
+        )));
+    }
+    // The length is untrusted, so the buffer grows with the bytes actually read rather than
+    // being allocated upfront.
+    let mut bytes = Vec::new();
+    reader.take(len).read_to_end(&mut bytes)?;
+    if bytes.len() as u64 != len {
+        return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+    }
+    Ok(bincode::deserialize(&bytes)?)
+}
+
+// The account keys a transaction locked, split into writable and readonly keys, as recorded in
+// the address signatures column
+fn transaction_account_keys(
+    transaction: &VersionedTransaction,
+    loaded_addresses: &LoadedAddresses,
+) -> (Vec<Pubkey>, Vec<Pubkey>) {
+    let message = match &transaction.message {
+        VersionedMessage::Legacy(message) => {
+            SanitizedMessage::Legacy(LegacyMessage::new(message.clone()))
+        }
+        VersionedMessage::V0(message) => SanitizedMessage::V0(LoadedMessage::new(
+            message.clone(),
+            loaded_addresses.clone(),
+        )),
+    };
+    let mut writable_keys = vec![];
+    let mut readonly_keys = vec![];
+    for (index, key) in message.account_keys().iter().enumerate() {
+        if message.is_writable(index) {
+            writable_keys.push(*key);
+        } else {
+            readonly_keys.push(*key);
+        }
+    }
+    (writable_keys, readonly_keys)
+}
+
+fn export_slot(blockstore: &Blockstore, slot: Slot) -> Result<ArchiveSlot> {
+    let data_shreds = blockstore
+        .get_data_shreds_for_slot(slot, 0)?
+        .into_iter()
+        .map(Shred::into_payload)
+        .collect();
+
+    let mut transactions = vec![];
+    let slot_transactions = blockstore
+        .get_slot_entries(slot, 0)?
+        .into_iter()
+        .flat_map(|entry| entry.transactions);
+    for (index, transaction) in slot_transactions.enumerate() {
+        let signature = transaction.signatures[0];
+        // Ledgers without transaction history have no statuses to export
+        let Some(meta) = blockstore.read_transaction_status((signature, slot))? else {
+            continue;
+        };
+        let (writable_keys, readonly_keys) =
+            transaction_account_keys(&transaction, &meta.loaded_addresses);
+        transactions.push(ArchiveTransaction {
+            signature,
+            index: u32::try_from(index).map_err(|_| BlockstoreError::TransactionIndexOverflow)?,
+            writable_keys,
+            readonly_keys,
+            status_meta: generated::TransactionStatusMeta::from(meta).encode_to_vec(),
+            memos: blockstore.read_transaction_memos(signature, slot)?,
+        });
+    }
+
+    Ok(ArchiveSlot {
+        slot,
+        data_shreds,
+        block_time: blockstore.get_rooted_block_time(slot).ok(),
+        block_height: blockstore.get_block_height(slot)?,
+        rewards: blockstore.read_rewards(slot)?,
+        transactions,
+    })
+}
+
+/// Write the rooted slots of `blockstore` from `start_slot` through `end_slot` to an archive at
+/// `archive_path`
+pub fn export_blockstore_archive(
+    blockstore: &Blockstore,
+    start_slot: Slot,
+    end_slot: Slot,
+    archive_path: &Path,
+) -> Result<ArchiveSummary> {
+    let mut writer = HashingWriter {
+        inner: BufWriter::new(File::create(archive_path)?),
+        hasher: Hasher::default(),
+    };
+    writer.write_all(ARCHIVE_MAGIC)?;
+    write_record(
+        &mut writer,
+        &ArchiveHeader {
+            version: ARCHIVE_VERSION,
+            start_slot,
+            end_slot,
+        },
+    )?;
+
+    let mut summary = ArchiveSummary {
+        start_slot,
+        end_slot,
+        ..ArchiveSummary::default()
+    };
+    for slot in blockstore
+        .rooted_slot_iterator(start_slot)?
+        .take_while(|slot| *slot <= end_slot)
+    {
+        let archive_slot = export_slot(blockstore, slot)?;
+        summary.add_slot(&archive_slot);
+        write_record(&mut writer, &ArchiveRecord::Slot(archive_slot))?;
+        debug!("exported slot {}", slot);
+    }
+    write_record(
+        &mut writer,
+        &ArchiveRecord::End {
+            num_slots: summary.num_slots,
+        },
+    )?;
+
+    let HashingWriter { mut inner, hasher } = writer;
+    inner.write_all(hasher.result().as_ref())?;
+    inner.flush()?;
+    Ok(summary)
+}
+
+// Reads the archive at `archive_path`, passing each slot to `f`, and checks its checksum
+fn read_archive(
+    archive_path: &Path,
+    mut f: impl FnMut(ArchiveSlot) -> Result<()>,
+) -> Result<ArchiveSummary> {
+    let mut reader = HashingReader {
+        inner: BufReader::new(File::open(archive_path)?),
+        hasher: Hasher::default(),
+    };
+    let mut magic = [0; ARCHIVE_MAGIC.len()];
+    reader.read_exact(&mut magic)?;
+    if &magic != ARCHIVE_MAGIC {
+        return Err(BlockstoreArchiveError::InvalidMagic);
+    }
+    let header: ArchiveHeader = read_record(&mut reader)?;
+    if header.version != ARCHIVE_VERSION {
+        return Err(BlockstoreArchiveError::UnsupportedVersion(header.version));
+    }
+
+    let mut summary = ArchiveSummary {
+        start_slot: header.start_slot,
+        end_slot: header.end_slot,
+        ..ArchiveSummary::default()
+    };
+    loop {
+        match read_record(&mut reader)? {
+            ArchiveRecord::Slot(archive_slot) => {
+                if archive_slot.slot < header.start_slot || archive_slot.slot > header.end_slot {
+                    return Err(BlockstoreArchiveError::InvalidArchive(format!(
+                        "slot {} outside of {}..={}",
+                        archive_slot.slot, header.start_slot, header.end_slot
+                    )));
+                }
+                summary.add_slot(&archive_slot);
+                f(archive_slot)?;
+            }
+            ArchiveRecord::End { num_slots } => {
+                if num_slots != summary.num_slots {
+                    return Err(BlockstoreArchiveError::InvalidArchive(format!(
+                        "expected {num_slots} slots, found {}",
+                        summary.num_slots
+                    )));
+                }
+                break;
+            }
+        }
+    }
+
+    let HashingReader { mut inner, hasher } = reader;
+    let computed = hasher.result();
+    let mut expected = [0; HASH_BYTES];
+    inner.read_exact(&mut expected)?;
+    let expected = Hash::new_from_array(expected);
+    if expected != computed {
+        return Err(BlockstoreArchiveError::ChecksumMismatch { expected, computed });
+    }
+    if inner.read(&mut [0])? != 0 {
+        return Err(BlockstoreArchiveError::InvalidArchive(
+            "trailing data".to_string(),
+        ));
+    }
+    Ok(summary)
+}
+
+/// Check the format and checksum of the archive at `archive_path`
+pub fn verify_blockstore_archive(archive_path: &Path) -> Result<ArchiveSummary> {
+    read_archive(archive_path, |_| Ok(()))
+}
+
+fn import_slot(blockstore: &Blockstore, archive_slot: ArchiveSlot) -> Result<()> {
+    let ArchiveSlot {
+        slot,
+        data_shreds,
+        block_time,
+        block_height,
+        rewards,
+        transactions,
+    } = archive_slot;
+
+    let shreds = data_shreds
+        .into_iter()
+        .map(Shred::new_from_serialized_shred)
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    blockstore.insert_shreds(shreds, None, true)?;
+    if let Some(block_time) = block_time {
+        blockstore.cache_block_time(slot, block_time)?;
+    }
+    if let Some(block_height) = block_height {
+        blockstore.cache_block_height(slot, block_height)?;
+    }
+    if let Some(rewards) = rewards {
+        blockstore.write_rewards(slot, rewards)?;
+    }
+    for transaction in transactions {
+        let meta = TransactionStatusMeta::try_from(generated::TransactionStatusMeta::decode(
+            transaction.status_meta.as_slice(),
+        )?)?;
+        blockstore.write_transaction_status(
+            slot,
+            transaction.signature,
+            transaction.writable_keys.iter().collect(),
+            transaction.readonly_keys.iter().collect(),
+            meta,
+            transaction.index as usize,
+        )?;
+        if let Some(memos) = transaction.memos {
+            blockstore.write_transaction_memos(&transaction.signature, slot, memos)?;
+        }
+    }
+    debug!("imported slot {}", slot);
+    Ok(())
+}
+
+/// Rebuild the slots of the archive at `archive_path` in `blockstore`, which must be empty, and
+/// mark them as rooted. The archive is verified before anything is written.
+pub fn import_blockstore_archive(
+    blockstore: &Blockstore,
+    archive_path: &Path,
+) -> Result<ArchiveSummary> {
+    if blockstore.highest_slot()?.is_some() {
+        return Err(BlockstoreArchiveError::BlockstoreNotEmpty);
+    }
+    verify_blockstore_archive(archive_path)?;
+
+    let mut slots = vec![];
+    let summary = read_archive(archive_path, |archive_slot| {
+        slots.push(archive_slot.slot);
+        import_slot(blockstore, archive_slot)
+    })?;
+    blockstore.set_roots(slots.iter())?;
+    if let Some(first_slot) = slots.first() {
+        blockstore.set_and_chain_connected_on_root_and_next_slots(*first_slot)?;
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{blockstore::entries_to_test_shreds, get_tmp_ledger_path_auto_delete},
+        solana_entry::entry::{create_ticks, next_entry_mut},
+        solana_sdk::{signature::Keypair, system_transaction},
+        solana_transaction_status::{Reward, RewardType},
+        std::fs,
+    };
+
+    // Inserts rooted slots `1..=num_slots` with one transfer each, with their transaction
+    // history, and returns the transfers
+    fn setup_blockstore(blockstore: &Blockstore, num_slots: u64) -> Vec<VersionedTransaction> {
+        let mut transactions = vec![];
+        for slot in 1..=num_slots {
+            let transaction = system_transaction::transfer(
+                &Keypair::new(),
+                &Pubkey::new_unique(),
+                slot,
+                Hash::new_unique(),
+            );
+            let mut hash = Hash::new_unique();
+            let mut entries = vec![next_entry_mut(&mut hash, 1, vec![transaction.clone()])];
+            entries.extend(create_ticks(2, 1, hash));
+            let shreds = entries_to_test_shreds(&entries, slot, slot - 1, true, 0, true);
+            blockstore.insert_shreds(shreds, None, false).unwrap();
+
+            let transaction = VersionedTransaction::from(transaction);
+            let (writable_keys, readonly_keys) =
+                transaction_account_keys(&transaction, &LoadedAddresses::default());
+            blockstore
+                .write_transaction_status(
+                    slot,
+                    transaction.signatures[0],
+                    writable_keys.iter().collect(),
+                    readonly_keys.iter().collect(),
+                    TransactionStatusMeta {
+                        fee: 5000,
+                        pre_balances: vec![slot, 0, 1],
+                        post_balances: vec![0, slot, 1],
+                        ..TransactionStatusMeta::default()
+                    },
+                    0,
+                )
+                .unwrap();
+            blockstore
+                .write_transaction_memos(&transaction.signatures[0], slot, format!("memo {slot}"))
+                .unwrap();
+            blockstore
+                .write_rewards(
+                    slot,
+                    vec![Reward {
+                        pubkey: Pubkey::new_unique().to_string(),
+                        lamports: slot as i64,
+                        post_balance: slot,
+                        reward_type: Some(RewardType::Fee),
+                        commission: None,
+                    }],
+                )
+                .unwrap();
+            blockstore.cache_block_time(slot, slot as i64).unwrap();
+            blockstore.cache_block_height(slot, slot).unwrap();
+            transactions.push(transaction);
+        }
+        let slots = (1..=num_slots).collect::<Vec<_>>();
+        blockstore.set_roots(slots.iter()).unwrap();
+        transactions
+    }
+
+    #[test]
+    fn test_export_import_blockstore_archive() {
+        let source_path = get_tmp_ledger_path_auto_delete!();
+        let source = Blockstore::open(source_path.path()).unwrap();
+        let transactions = setup_blockstore(&source, 5);
+
+        let archive_dir = tempfile::tempdir().unwrap();
+        let archive_path = archive_dir.path().join("archive");
+        let summary = export_blockstore_archive(&source, 2, 4, &archive_path).unwrap();
+        assert_eq!(summary.start_slot, 2);
+        assert_eq!(summary.end_slot, 4);
+        assert_eq!(summary.num_slots, 3);
+        assert_eq!(summary.num_transactions, 3);
+        assert_eq!(verify_blockstore_archive(&archive_path).unwrap(), summary);
+
+        let target_path = get_tmp_ledger_path_auto_delete!();
+        let target = Blockstore::open(target_path.path()).unwrap();
+        assert_eq!(
+            import_blockstore_archive(&target, &archive_path).unwrap(),
+            summary
+        );
+        assert!(matches!(
+            import_blockstore_archive(&target, &archive_path),
+            Err(BlockstoreArchiveError::BlockstoreNotEmpty)
+        ));
+
+        assert_eq!(
+            target.rooted_slot_iterator(0).unwrap().collect::<Vec<_>>(),
+            vec![2, 3, 4]
+        );
+        assert!(target.meta(1).unwrap().is_none());
+        assert!(target.meta(5).unwrap().is_none());
+        for slot in 2..=4 {
+            assert_eq!(
+                target.get_slot_entries(slot, 0).unwrap(),
+                source.get_slot_entries(slot, 0).unwrap()
+            );
+            // The parent of the first slot is not in the archive, so previous blockhashes differ
+            let target_block = target.get_rooted_block(slot, false).unwrap();
+            let source_block = source.get_rooted_block(slot, false).unwrap();
+            assert_eq!(target_block.blockhash, source_block.blockhash);
+            assert_eq!(target_block.transactions, source_block.transactions);
+            assert_eq!(target_block.rewards, source_block.rewards);
+            assert_eq!(target_block.block_time, source_block.block_time);
+            assert_eq!(
+                target.get_block_height(slot).unwrap(),
+                source.get_block_height(slot).unwrap()
+            );
+
+            let signature = transactions[slot as usize - 1].signatures[0];
+            assert_eq!(
+                target.read_transaction_memos(signature, slot).unwrap(),
+                Some(format!("memo {slot}"))
+            );
+            let address = transactions[slot as usize - 1]
+                .message
+                .static_account_keys()[1];
+            let signatures = target
+                .get_confirmed_signatures_for_address(address, slot, slot)
+                .unwrap();
+            assert_eq!(signatures, vec![signature]);
+        }
+    }
+
+    #[test]
+    fn test_corrupt_blockstore_archive() {
+        let source_path = get_tmp_ledger_path_auto_delete!();
+        let source = Blockstore::open(source_path.path()).unwrap();
+        setup_blockstore(&source, 2);
+
+        let archive_dir = tempfile::tempdir().unwrap();
+        let archive_path = archive_dir.path().join("archive");
+        export_blockstore_archive(&source, 0, 2, &archive_path).unwrap();
+        let archive = fs::read(&archive_path).unwrap();
+
+        let mut corrupt = archive.clone();
+        let middle = corrupt.len() / 2;
+        corrupt[middle] ^= 0xff;
+        fs::write(&archive_path, &corrupt).unwrap();
+        assert!(verify_blockstore_archive(&archive_path).is_err());
+
+        // Nothing is written from an archive that fails verification
+        let target_path = get_tmp_ledger_path_auto_delete!();
+        let target = Blockstore::open(target_path.path()).unwrap();
+        assert!(import_blockstore_archive(&target, &archive_path).is_err());
+        assert_eq!(target.highest_slot().unwrap(), None);
+
+        fs::write(&archive_path, &archive[..archive.len() - 1]).unwrap();
+        assert!(verify_blockstore_archive(&archive_path).is_err());
+
+        // A truncated record claiming the maximum size is rejected.
+        let mut truncated = MAX_RECORD_SIZE.to_le_bytes().to_vec();
+        truncated.extend_from_slice(&[0; 16]);
+        assert!(matches!(
+            read_record::<_, u64>(&mut truncated.as_slice()),
+            Err(BlockstoreArchiveError::Io(err)) if err.kind() == io::ErrorKind::UnexpectedEof
+        ));
+
+        let mut bad_magic = archive;
+        bad_magic[0] = b'X';
+        fs::write(&archive_path, &bad_magic).unwrap();
+        assert!(matches!(
+            verify_blockstore_archive(&archive_path),
+            Err(BlockstoreArchiveError::InvalidMagic)
+        ));
+    }
+}
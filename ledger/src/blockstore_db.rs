@@ -151,6 +151,14 @@ pub enum BlockstoreError {
     MissingTransactionMetadata,
     #[error("transaction-index overflow")]
     TransactionIndexOverflow,
+    #[error("unknown column: {0}")]
+    UnknownColumn(String),
+    #[error("column {0} is not keyed by slot")]
+    ColumnNotKeyedBySlot(String),
+    #[error("invalid slot range: {0} is greater than {1}")]
+    InvalidSlotRange(Slot, Slot),
+    #[error("invalid rocks fifo options: {0}")]
+    InvalidRocksFifoOptions(String),
 }
 pub type Result<T> = std::result::Result<T, BlockstoreError>;
 
@@ -743,6 +751,11 @@ impl Rocks {
             || self.access_type == AccessType::PrimaryForMaintenance
     }
 
+    fn try_catch_up_with_primary(&self) -> Result<()> {
+        self.db.try_catch_up_with_primary()?;
+        Ok(())
+    }
+
     /// Retrieves the specified RocksDB integer property of the current
     /// column family.
     ///
@@ -756,6 +769,12 @@ impl Rocks {
         }
     }
 
+    /// Retrieves the specified RocksDB string property of the current
+    /// column family, such as `rocksdb.compression-ratio-at-level0`.
+    fn get_property_cf(&self, cf: &ColumnFamily, name: &str) -> Result<Option<String>> {
+        Ok(self.db.property_value_cf(cf, name)?)
+    }
+
     fn live_files_metadata(&self) -> Result<Vec<LiveFile>> {
         match self.db.live_files() {
             Ok(live_files) => Ok(live_files),
@@ -1529,6 +1548,11 @@ impl Database {
         self.backend.is_primary_access()
     }
 
+    /// Make the writes of the primary instance visible to this secondary instance
+    pub fn try_catch_up_with_primary(&self) -> Result<()> {
+        self.backend.try_catch_up_with_primary()
+    }
+
     pub fn set_oldest_slot(&self, oldest_slot: Slot) {
         self.backend.oldest_slot.set(oldest_slot);
     }
@@ -1545,6 +1569,93 @@ impl Database {
         let cf = self.cf_handle::<C>();
         self.backend.db.compact_range_cf(cf, Some(from), Some(to));
     }
+
+    /// Compact the column named `cf_name`, limited to the keys of slots `from..=to` when given a
+    /// slot range. Only columns whose keys start with the slot can be compacted by slot range.
+    pub fn compact_column(&self, cf_name: &str, slot_range: Option<(Slot, Slot)>) -> Result<()> {
+        let cf_name = Self::check_compact_column(cf_name, slot_range)?;
+        let cf = self.backend.cf_handle(cf_name);
+        match slot_range {
+            Some((from, to)) => {
+                // Keys of slot keyed columns start with the big endian slot, so this range
+                // covers every key of the slots from..=to
+                let from = from.to_be_bytes();
+                let to = to.saturating_add(1).to_be_bytes();
+                self.backend.db.compact_range_cf(cf, Some(from), Some(to));
+            }
+            None => self
+                .backend
+                .db
+                .compact_range_cf(cf, None::<&[u8]>, None::<&[u8]>),
+        }
+        Ok(())
+    }
+
+    /// Returns the name of the column `compact_column` would compact, or the error it would
+    /// fail with, without compacting anything
+    pub fn check_compact_column(
+        cf_name: &str,
+        slot_range: Option<(Slot, Slot)>,
+    ) -> Result<&'static str> {
+        let Some(cf_name) = Rocks::columns().into_iter().find(|name| *name == cf_name) else {
+            return Err(BlockstoreError::UnknownColumn(cf_name.to_string()));
+        };
+        if slot_range.is_some() && !is_slot_keyed_column(cf_name) {
+            return Err(BlockstoreError::ColumnNotKeyedBySlot(cf_name.to_string()));
+        }
+        if let Some((from, to)) = slot_range {
+            if from > to {
+                return Err(BlockstoreError::InvalidSlotRange(from, to));
+            }
+        }
+        Ok(cf_name)
+    }
+
+    /// Change the FIFO compaction size limits of the shred columns of a blockstore using
+    /// `ShredStorageType::RocksFifo`. Only the limits that are `Some` are changed.
+    pub fn set_rocks_fifo_options(
+        &self,
+        shred_data_cf_size: Option<u64>,
+        shred_code_cf_size: Option<u64>,
+    ) -> Result<()> {
+        if !matches!(
+            self.column_options.shred_storage_type,
+            ShredStorageType::RocksFifo(_)
+        ) {
+            return Err(BlockstoreError::InvalidRocksFifoOptions(
+                "the blockstore does not use rocks fifo shred storage".to_string(),
+            ));
+        }
+        let sizes = [
+            (columns::ShredData::NAME, shred_data_cf_size),
+            (columns::ShredCode::NAME, shred_code_cf_size),
+        ];
+        // Validate every size before changing any of them
+        for (cf_name, max_cf_size) in sizes {
+            if matches!(max_cf_size, Some(size) if size <= FIFO_WRITE_BUFFER_SIZE) {
+                return Err(BlockstoreError::InvalidRocksFifoOptions(format!(
+                    "{cf_name} cf_size must be greater than write buffer size \
+                     {FIFO_WRITE_BUFFER_SIZE}"
+                )));
+            }
+        }
+        for (cf_name, max_cf_size) in sizes {
+            let Some(max_cf_size) = max_cf_size else {
+                continue;
+            };
+            // See get_cf_options_fifo() for why the write buffer size is subtracted
+            let fifo_options = format!(
+                "{{max_table_files_size={};}}",
+                max_cf_size.saturating_sub(FIFO_WRITE_BUFFER_SIZE)
+            );
+            self.backend.db.set_options_cf(
+                self.backend.cf_handle(cf_name),
+                &[("compaction_options_fifo", fifo_options.as_str())],
+            )?;
+            info!("Changed the fifo size limit of {cf_name} to {max_cf_size} bytes");
+        }
+        Ok(())
+    }
 }
 
 impl<C> LedgerColumn<C>
@@ -1663,6 +1774,12 @@ where
         self.backend.get_int_property_cf(self.handle(), name)
     }
 
+    /// Retrieves the specified RocksDB string property of the current
+    /// column family.
+    pub fn get_property(&self, name: &str) -> Result<Option<String>> {
+        self.backend.get_property_cf(self.handle(), name)
+    }
+
     pub fn delete(&self, key: C::Index) -> Result<()> {
         let is_perf_enabled = maybe_enable_rocksdb_perf(
             self.column_options.rocks_perf_sample_interval,
@@ -2212,6 +2329,19 @@ fn should_enable_cf_compaction(cf_name: &str) -> bool {
     )
 }
 
+/// Returns whether the keys of the given column (name) start with the slot, so that its slot
+/// ranges are also key ranges.
+pub fn is_slot_keyed_column(cf_name: &str) -> bool {
+    !matches!(
+        cf_name,
+        columns::TransactionStatus::NAME
+            | columns::TransactionMemos::NAME
+            | columns::AddressSignatures::NAME
+            | columns::TransactionStatusIndex::NAME
+            | columns::ProgramCosts::NAME
+    )
+}
+
 // Returns true if the column family enables compression.
 fn should_enable_compression<C: 'static + Column + ColumnName>() -> bool {
     C::NAME == columns::TransactionStatus::NAME
@@ -1835,6 +1835,11 @@ fn process_single_slot(
         result?
     }
     bank.freeze(); // all banks handled by this routine are created from complete slots
+    if let Some(transaction_status_sender) = transaction_status_sender {
+        // Lets the transaction status service report when this slot's statuses are written
+        transaction_status_sender
+            .send_transaction_status_freeze_message(&bank.clone_without_scheduler());
+    }
 
     if let Some(slot_callback) = &opts.slot_callback {
         slot_callback(bank);
@@ -0,0 +1,242 @@
+//! Follows the blocks rooted by a validator, from the same or a second process
+//!
+//! A `BlockstoreTailer` over a blockstore opened with `AccessType::Secondary` periodically
+//! catches it up with the validator's primary instance and yields each newly rooted block with
+//! its transaction statuses, so a co-located indexer can follow the ledger without a Geyser
+//! plugin. Transaction statuses are only written by validators with transaction history enabled.
+
+use {
+    crate::{
+        blockstore::{Blockstore, BlockstoreError},
+        blockstore_db::Result,
+    },
+    log::*,
+    solana_sdk::clock::Slot,
+    solana_transaction_status::VersionedConfirmedBlock,
+    std::{
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+        thread::sleep,
+        time::Duration,
+    },
+};
+
+pub const DEFAULT_TAILER_POLL_INTERVAL: Duration = Duration::from_millis(400);
+
+// Bounds the blocks held in memory when starting far behind the latest root
+const MAX_BLOCKS_PER_POLL: usize = 256;
+
+#[derive(Debug)]
+pub struct TailedBlock {
+    pub slot: Slot,
+    pub block: VersionedConfirmedBlock,
+}
+
+pub struct BlockstoreTailer {
+    blockstore: Arc<Blockstore>,
+    // The lowest slot that has not been yielded yet
+    next_slot: Slot,
+    poll_interval: Duration,
+    // The rooted slot whose missing transaction statuses were last warned about
+    stalled_slot: Option<Slot>,
+}
+
+impl BlockstoreTailer {
+    /// Follow the blocks rooted at or after `start_slot`
+    pub fn new(blockstore: Arc<Blockstore>, start_slot: Slot) -> Self {
+        Self {
+            blockstore,
+            next_slot: start_slot,
+            poll_interval: DEFAULT_TAILER_POLL_INTERVAL,
+            stalled_slot: None,
+        }
+    }
+
+    /// Follow the blocks rooted after the current latest root
+    pub fn new_from_latest_root(blockstore: Arc<Blockstore>) -> Result<Self> {
+        if !blockstore.is_primary_access() {
+            blockstore.try_catch_up_with_primary()?;
+        }
+        let start_slot = blockstore.max_root().saturating_add(1);
+        Ok(Self::new(blockstore, start_slot))
+    }
+
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// The lowest slot that has not been yielded yet
+    pub fn next_slot(&self) -> Slot {
+        self.next_slot
+    }
+
+    /// Return the blocks rooted since the last call, in slot order, catching up with the primary
+    /// instance first if the blockstore is a secondary instance.
+    ///
+    /// Returns no blocks while the transaction statuses of the next rooted block have not been
+    /// written yet, warning once per such block, since they are never written by validators
+    /// running without `--enable-rpc-transaction-history`.
+    pub fn poll(&mut self) -> Result<Vec<TailedBlock>> {
+        if !self.blockstore.is_primary_access() {
+            self.blockstore.try_catch_up_with_primary()?;
+        }
+
+        let slots = self
+            .blockstore
+            .rooted_slot_iterator(self.next_slot)?
+            .take(MAX_BLOCKS_PER_POLL)
+            .collect::<Vec<_>>();
+        let mut blocks = Vec::with_capacity(slots.len());
+        for slot in slots {
+            match self.blockstore.get_rooted_block(slot, false) {
+                Ok(block) => blocks.push(TailedBlock { slot, block }),
+                Err(BlockstoreError::MissingTransactionMetadata) => {
+                    if self.stalled_slot == Some(slot) {
+                        debug!("transaction statuses of slot {} are not written yet", slot);
+                    } else {
+                        warn!(
+                            "transaction statuses of rooted slot {} are not written yet, waiting \
+                             for them. They are only written by validators running with \
+                             --enable-rpc-transaction-history",
+                            slot
+                        );
+                        self.stalled_slot = Some(slot);
+                    }
+                    break;
+                }
+                Err(err @ (BlockstoreError::SlotCleanedUp | BlockstoreError::SlotUnavailable)) => {
+                    warn!("skipping rooted slot {}: {}", slot, err);
+                }
+                Err(err) => return Err(err),
+            }
+            self.next_slot = slot.saturating_add(1);
+        }
+        Ok(blocks)
+    }
+
+    /// Wait for newly rooted blocks, polling every poll interval. Returns no blocks only once
+    /// `exit` is set.
+    pub fn wait_for_blocks(&mut self, exit: &AtomicBool) -> Result<Vec<TailedBlock>> {
+        while !exit.load(Ordering::Relaxed) {
+            let blocks = self.poll()?;
+            if !blocks.is_empty() {
+                return Ok(blocks);
+            }
+            sleep(self.poll_interval);
+        }
+        Ok(vec![])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            blockstore::{
+                entries_to_test_shreds, make_slot_entries,
+                tests::make_slot_entries_with_transactions,
+            },
+            blockstore_options::{AccessType, BlockstoreOptions},
+            get_tmp_ledger_path_auto_delete,
+        },
+    };
+
+    fn insert_rooted_slots(blockstore: &Blockstore, slots: &[Slot]) {
+        for slot in slots {
+            let (shreds, _) = make_slot_entries(*slot, slot.saturating_sub(1), 4, true);
+            blockstore.insert_shreds(shreds, None, false).unwrap();
+        }
+        blockstore.set_roots(slots.iter()).unwrap();
+    }
+
+    fn tailed_slots(blocks: &[TailedBlock]) -> Vec<Slot> {
+        blocks.iter().map(|block| block.slot).collect()
+    }
+
+    #[test]
+    fn test_blockstore_tailer() {
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Arc::new(Blockstore::open(ledger_path.path()).unwrap());
+        insert_rooted_slots(&blockstore, &[1, 2, 3]);
+
+        let mut tailer = BlockstoreTailer::new(blockstore.clone(), 2);
+        assert_eq!(tailed_slots(&tailer.poll().unwrap()), vec![2, 3]);
+        assert!(tailer.poll().unwrap().is_empty());
+        assert_eq!(tailer.next_slot(), 4);
+
+        // Unrooted slots are not yielded
+        insert_rooted_slots(&blockstore, &[4, 6]);
+        let (shreds, _) = make_slot_entries(7, 6, 4, true);
+        blockstore.insert_shreds(shreds, None, false).unwrap();
+        let blocks = tailer.poll().unwrap();
+        assert_eq!(tailed_slots(&blocks), vec![4, 6]);
+        assert_eq!(
+            blocks[1].block.blockhash,
+            blockstore
+                .get_slot_entries(6, 0)
+                .unwrap()
+                .last()
+                .unwrap()
+                .hash
+                .to_string()
+        );
+
+        let exit = AtomicBool::new(true);
+        assert!(tailer.wait_for_blocks(&exit).unwrap().is_empty());
+        blockstore.set_roots([7].iter()).unwrap();
+        exit.store(false, Ordering::Relaxed);
+        assert_eq!(
+            tailed_slots(&tailer.wait_for_blocks(&exit).unwrap()),
+            vec![7]
+        );
+    }
+
+    #[test]
+    fn test_blockstore_tailer_missing_transaction_statuses() {
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Arc::new(Blockstore::open(ledger_path.path()).unwrap());
+        insert_rooted_slots(&blockstore, &[1]);
+        let entries = make_slot_entries_with_transactions(2);
+        let shreds = entries_to_test_shreds(&entries, 2, 1, true, 0, /*merkle_variant:*/ true);
+        blockstore.insert_shreds(shreds, None, false).unwrap();
+        insert_rooted_slots(&blockstore, &[3]);
+        blockstore.set_roots([2].iter()).unwrap();
+
+        // The tailer stalls at the slot without transaction statuses, and warns only once
+        let mut tailer = BlockstoreTailer::new(blockstore, 1);
+        assert_eq!(tailed_slots(&tailer.poll().unwrap()), vec![1]);
+        assert_eq!(tailer.stalled_slot, Some(2));
+        assert!(tailer.poll().unwrap().is_empty());
+        assert_eq!(tailer.next_slot(), 2);
+        assert_eq!(tailer.stalled_slot, Some(2));
+    }
+
+    #[test]
+    fn test_blockstore_tailer_secondary() {
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let primary = Blockstore::open(ledger_path.path()).unwrap();
+        insert_rooted_slots(&primary, &[1, 2]);
+
+        let secondary = Arc::new(
+            Blockstore::open_with_options(
+                ledger_path.path(),
+                BlockstoreOptions {
+                    access_type: AccessType::Secondary,
+                    ..BlockstoreOptions::default()
+                },
+            )
+            .unwrap(),
+        );
+        let mut tailer = BlockstoreTailer::new_from_latest_root(secondary.clone()).unwrap();
+        assert_eq!(tailer.next_slot(), 3);
+        assert!(tailer.poll().unwrap().is_empty());
+
+        insert_rooted_slots(&primary, &[3, 4]);
+        assert_eq!(tailed_slots(&tailer.poll().unwrap()), vec![3, 4]);
+        assert_eq!(secondary.max_root(), 4);
+    }
+}
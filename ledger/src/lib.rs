@@ -9,6 +9,7 @@ pub mod block_error;
 #[macro_use]
 pub mod blockstore;
 pub mod ancestor_iterator;
+pub mod blockstore_archive;
 pub mod blockstore_cleanup_service;
 pub mod blockstore_db;
 pub mod blockstore_meta;
@@ -16,6 +17,7 @@ pub mod blockstore_metric_report_service;
 pub mod blockstore_metrics;
 pub mod blockstore_options;
 pub mod blockstore_processor;
+pub mod blockstore_tailer;
 pub mod entry_notifier_interface;
 pub mod entry_notifier_service;
 pub mod genesis_utils;
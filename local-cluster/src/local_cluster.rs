@@ -534,6 +534,45 @@ impl LocalCluster {
         validator_pubkey
     }
 
+    /// Starts a validator from the genesis of the cluster without any
+    /// entrypoint, so that it does not join the cluster. The caller is
+    /// responsible for shutting down the validator.
+    pub fn start_isolated_validator(
+        &self,
+        validator_config: &ValidatorConfig,
+        socket_addr_space: SocketAddrSpace,
+    ) -> (Validator, PathBuf) {
+        let validator_keypair = Arc::new(Keypair::new());
+        let voting_keypair = Arc::new(Keypair::new());
+        let validator_node = Node::new_localhost_with_pubkey(&validator_keypair.pubkey());
+        let (ledger_path, _blockhash) = create_new_tmp_ledger!(&self.genesis_config);
+        let mut config = safe_clone_config(validator_config);
+        config.rpc_addrs = Some((
+            validator_node.info.rpc().unwrap(),
+            validator_node.info.rpc_pubsub().unwrap(),
+        ));
+        Self::sync_ledger_path_across_nested_config_fields(&mut config, &ledger_path);
+        let validator = Validator::new(
+            validator_node,
+            validator_keypair,
+            &ledger_path,
+            &voting_keypair.pubkey(),
+            Arc::new(RwLock::new(vec![voting_keypair])),
+            vec![], // cluster_entrypoints
+            &config,
+            true, // should_check_duplicate_instance
+            None, // rpc_to_plugin_manager_receiver
+            Arc::new(RwLock::new(ValidatorStartProgress::default())),
+            socket_addr_space,
+            DEFAULT_TPU_USE_QUIC,
+            DEFAULT_TPU_CONNECTION_POOL_SIZE,
+            DEFAULT_TPU_ENABLE_UDP,
+            Arc::new(RwLock::new(None)),
+        )
+        .expect("assume successful validator start");
+        (validator, ledger_path)
+    }
+
     pub fn ledger_path(&self, validator_pubkey: &Pubkey) -> PathBuf {
         self.validators
             .get(validator_pubkey)
@@ -817,10 +856,10 @@ impl LocalCluster {
 
         let cache = match &*self.connection_cache {
             ConnectionCache::Quic(cache) => cache,
-            ConnectionCache::Udp(_) => {
+            ConnectionCache::Udp(_) | ConnectionCache::InMemory(_) => {
                 return Err(Error::new(
                     ErrorKind::Other,
-                    "Expected a Quic ConnectionCache. Got UDP",
+                    "Expected a Quic ConnectionCache",
                 ))
             }
         };
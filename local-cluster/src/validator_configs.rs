@@ -29,6 +29,15 @@ pub fn safe_clone_config(config: &ValidatorConfig) -> ValidatorConfig {
         repair_validators: config.repair_validators.clone(),
         repair_whitelist: config.repair_whitelist.clone(),
         gossip_validators: config.gossip_validators.clone(),
+        gossip_record_path: config.gossip_record_path.clone(),
+        gossip_record_max_bytes: config.gossip_record_max_bytes,
+        enable_repair_timeline: config.enable_repair_timeline,
+        repair_timeline_path: config.repair_timeline_path.clone(),
+        repair_timeline_max_bytes: config.repair_timeline_max_bytes,
+        repair_peer_scoring: config.repair_peer_scoring,
+        shred_capture_path: config.shred_capture_path.clone(),
+        shred_capture_max_bytes: config.shred_capture_max_bytes,
+        shred_replay_path: config.shred_replay_path.clone(),
         accounts_hash_interval_slots: config.accounts_hash_interval_slots,
         max_genesis_archive_unpacked_size: config.max_genesis_archive_unpacked_size,
         wal_recovery_mode: config.wal_recovery_mode.clone(),
@@ -51,6 +60,7 @@ pub fn safe_clone_config(config: &ValidatorConfig) -> ValidatorConfig {
         accounts_db_skip_shrink: config.accounts_db_skip_shrink,
         accounts_db_force_initial_clean: config.accounts_db_force_initial_clean,
         tpu_coalesce: config.tpu_coalesce,
+        tpu_max_forwarded_streams_per_ms: config.tpu_max_forwarded_streams_per_ms,
         staked_nodes_overrides: config.staked_nodes_overrides.clone(),
         validator_exit: Arc::new(RwLock::new(Exit::default())),
         poh_hashes_per_batch: config.poh_hashes_per_batch,
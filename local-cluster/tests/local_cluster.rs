@@ -16,6 +16,7 @@ use {
         },
         optimistic_confirmation_verifier::OptimisticConfirmationVerifier,
         replay_stage::DUPLICATE_THRESHOLD,
+        shred_capture::ShredCaptureReader,
         validator::{BlockProductionMethod, BlockVerificationMethod, ValidatorConfig},
     },
     solana_download_utils::download_snapshot_archive,
@@ -27,7 +28,7 @@ use {
         blockstore::{entries_to_test_shreds, Blockstore},
         blockstore_processor::ProcessOptions,
         leader_schedule::FixedSchedule,
-        shred::{ProcessShredsStats, ReedSolomonCache, Shred, Shredder},
+        shred::{self, ProcessShredsStats, ReedSolomonCache, Shred, Shredder},
         use_snapshot_archives_at_startup::UseSnapshotArchivesAtStartup,
     },
     solana_local_cluster::{
@@ -307,6 +308,63 @@ fn test_spend_and_verify_all_nodes_env_num_nodes() {
     );
 }
 
+#[test]
+#[serial]
+fn test_shred_capture_and_replay() {
+    solana_logger::setup_with_default(RUST_LOG_FILTER);
+    let capture_dir = tempfile::tempdir_in(farf_dir()).unwrap();
+    let capture_path = capture_dir.path().join("shreds.capture");
+    let capture_config = ValidatorConfig {
+        shred_capture_path: Some(capture_path.clone()),
+        ..ValidatorConfig::default_for_test()
+    };
+    let mut config = ClusterConfig {
+        cluster_lamports: DEFAULT_CLUSTER_LAMPORTS,
+        node_stakes: vec![DEFAULT_NODE_STAKE; 2],
+        validator_configs: vec![ValidatorConfig::default_for_test(), capture_config],
+        ..ClusterConfig::default()
+    };
+    let mut cluster = LocalCluster::new(&mut config, SocketAddrSpace::Unspecified);
+    cluster.check_for_new_roots(
+        16,
+        "test_shred_capture_and_replay",
+        SocketAddrSpace::Unspecified,
+    );
+    let capture_pubkey = cluster
+        .get_node_pubkeys()
+        .into_iter()
+        .find(|pubkey| pubkey != cluster.entry_point_info.pubkey())
+        .unwrap();
+    // Shutting down the validator flushes the capture.
+    cluster.exit_node(&capture_pubkey);
+    let captured_slots: BTreeSet<Slot> = ShredCaptureReader::open(&capture_path)
+        .unwrap()
+        .map(|captured| shred::layout::get_slot(&captured.unwrap().payload).unwrap())
+        .collect();
+    assert!(!captured_slots.is_empty());
+
+    // Replay the capture into a validator which did not join the cluster.
+    let replay_config = ValidatorConfig {
+        shred_replay_path: Some(capture_path),
+        voting_disabled: true,
+        ..ValidatorConfig::default_for_test()
+    };
+    let (validator, _ledger_path) =
+        cluster.start_isolated_validator(&replay_config, SocketAddrSpace::Unspecified);
+    let deadline = Instant::now() + Duration::from_secs(120);
+    while !captured_slots
+        .iter()
+        .all(|slot| validator.blockstore.meta(*slot).unwrap().is_some())
+    {
+        assert!(
+            Instant::now() < deadline,
+            "replayed shreds did not reach the blockstore"
+        );
+        sleep(Duration::from_millis(500));
+    }
+    validator.close();
+}
+
 #[test]
 #[serial]
 fn test_two_unbalanced_stakes() {
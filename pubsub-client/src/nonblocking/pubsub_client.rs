@@ -30,6 +30,11 @@
 //!
 //! This pattern is illustrated in the example below.
 //!
+//! By default all subscription streams end when the websocket connection
+//! closes. A client created with [`PubsubClient::new_with_reconnect`] instead
+//! reconnects with backoff and restores its subscriptions, see
+//! [`PubsubReconnectConfig`] and [`PubsubEvent`].
+//!
 //! By default the [`block_subscribe`] and [`vote_subscribe`] events are
 //! disabled on RPC nodes. They can be enabled by passing
 //! `--rpc-pubsub-enable-block-subscription` and
@@ -180,17 +185,18 @@ use {
         config::{
             RpcAccountInfoConfig, RpcBlockSubscribeConfig, RpcBlockSubscribeFilter,
             RpcProgramAccountsConfig, RpcSignatureSubscribeConfig, RpcTransactionLogsConfig,
-            RpcTransactionLogsFilter,
+            RpcTransactionLogsFilter, RpcTransactionSubscribeConfig, RpcTransactionSubscribeFilter,
         },
         error_object::RpcErrorObject,
         filter::maybe_map_filters,
         response::{
             Response as RpcResponse, RpcBlockUpdate, RpcKeyedAccount, RpcLogsResponse,
-            RpcSignatureResult, RpcVersionInfo, RpcVote, SlotInfo, SlotUpdate,
+            RpcSignatureResult, RpcTransactionUpdate, RpcVersionInfo, RpcVote, SlotInfo,
+            SlotUpdate,
         },
     },
     solana_sdk::{clock::Slot, pubkey::Pubkey, signature::Signature},
-    std::collections::BTreeMap,
+    std::collections::{BTreeMap, HashSet},
     thiserror::Error,
     tokio::{
         net::TcpStream,
@@ -250,7 +256,7 @@ pub enum PubsubClientError {
 
 type UnsubscribeFn = Box<dyn FnOnce() -> BoxFuture<'static, ()> + Send>;
 type SubscribeResponseMsg =
-    Result<(mpsc::UnboundedReceiver<Value>, UnsubscribeFn), PubsubClientError>;
+    Result<(mpsc::UnboundedReceiver<SubscriptionMessage>, UnsubscribeFn), PubsubClientError>;
 type SubscribeRequestMsg = (String, Value, oneshot::Sender<SubscribeResponseMsg>);
 type SubscribeResult<'a, T> = PubsubClientResult<(BoxStream<'a, T>, UnsubscribeFn)>;
 type RequestMsg = (
@@ -259,6 +265,54 @@ type RequestMsg = (
     oneshot::Sender<Result<Value, PubsubClientError>>,
 );
 
+/// Configuration of the reconnecting mode of a [`PubsubClient`], see
+/// [`PubsubClient::new_with_reconnect`].
+#[derive(Debug, Clone)]
+pub struct PubsubReconnectConfig {
+    /// Delay before the first reconnection attempt, doubled after every
+    /// failed attempt
+    pub initial_backoff: Duration,
+    /// Upper bound of the delay between reconnection attempts
+    pub max_backoff: Duration,
+    /// Number of consecutive failed attempts after which the client gives up,
+    /// `None` to retry forever
+    pub max_attempts: Option<usize>,
+    /// Ask the server to replay the notifications missed while disconnected,
+    /// with `fromSlot`, for the subscriptions that support it
+    pub replay_from_slot: bool,
+}
+
+impl Default for PubsubReconnectConfig {
+    fn default() -> Self {
+        Self {
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(10),
+            max_attempts: None,
+            replay_from_slot: true,
+        }
+    }
+}
+
+/// An item of a subscription stream returned by
+/// [`PubsubClient::subscribe_events`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PubsubEvent<T> {
+    Notification(T),
+    /// The connection was re-established and the subscription restored, but
+    /// notifications sent while disconnected may have been missed.
+    Gap {
+        /// Highest slot seen in a notification of this subscription before
+        /// the connection dropped
+        last_seen_slot: Option<Slot>,
+    },
+}
+
+#[derive(Debug)]
+enum SubscriptionMessage {
+    Notification(Value),
+    Gap { last_seen_slot: Option<Slot> },
+}
+
 /// A client for subscribing to messages from the RPC server.
 ///
 /// See the [module documentation][self].
@@ -273,8 +327,30 @@ pub struct PubsubClient {
 
 impl PubsubClient {
     pub async fn new(url: &str) -> PubsubClientResult<Self> {
+        Self::connect(url, None).await
+    }
+
+    /// Creates a client that re-establishes the connection when it drops.
+    ///
+    /// Subscriptions are restored on the new connection and keep their
+    /// streams and unsubscribe functions. Notifications sent while
+    /// disconnected are replayed where the server supports `fromSlot`;
+    /// otherwise a [`PubsubEvent::Gap`] is emitted on the streams returned by
+    /// [`PubsubClient::subscribe_events`]. Requests in flight when the
+    /// connection drops, other than subscribes, fail.
+    pub async fn new_with_reconnect(
+        url: &str,
+        config: PubsubReconnectConfig,
+    ) -> PubsubClientResult<Self> {
+        Self::connect(url, Some(config)).await
+    }
+
+    async fn connect(
+        url: &str,
+        reconnect: Option<PubsubReconnectConfig>,
+    ) -> PubsubClientResult<Self> {
         let url = Url::parse(url)?;
-        let (ws, _response) = connect_async(url)
+        let (ws, _response) = connect_async(url.clone())
             .await
             .map_err(PubsubClientError::ConnectionError)?;
 
@@ -292,6 +368,7 @@ impl PubsubClient {
                 subscribe_receiver,
                 request_receiver,
                 shutdown_receiver,
+                reconnect.map(|config| (url, config)),
             )),
         })
     }
@@ -339,6 +416,36 @@ impl PubsubClient {
     }
 
     async fn subscribe<'a, T>(&self, operation: &str, params: Value) -> SubscribeResult<'a, T>
+    where
+        T: DeserializeOwned + Send + 'a,
+    {
+        let (events, unsubscribe) = self.subscribe_events(operation, params).await?;
+        Ok((
+            events
+                .filter_map(|event| {
+                    ready(match event {
+                        PubsubEvent::Notification(notification) => Some(notification),
+                        PubsubEvent::Gap { last_seen_slot } => {
+                            warn!("pubsub subscription restored, notifications after slot {last_seen_slot:?} may be missing");
+                            None
+                        }
+                    })
+                })
+                .boxed(),
+            unsubscribe,
+        ))
+    }
+
+    /// Subscribe to `{operation}Subscribe` with raw `params`, receiving
+    /// [`PubsubEvent`]s.
+    ///
+    /// Unlike the typed subscription methods, the stream also carries the
+    /// [`PubsubEvent::Gap`] events of a reconnecting client.
+    pub async fn subscribe_events<'a, T>(
+        &self,
+        operation: &str,
+        params: Value,
+    ) -> SubscribeResult<'a, PubsubEvent<T>>
     where
         T: DeserializeOwned + Send + 'a,
     {
@@ -352,7 +459,18 @@ impl PubsubClient {
             .map_err(|err| PubsubClientError::ConnectionClosed(err.to_string()))??;
         Ok((
             UnboundedReceiverStream::new(notifications)
-                .filter_map(|value| ready(serde_json::from_value::<T>(value).ok()))
+                .filter_map(|message| {
+                    ready(match message {
+                        SubscriptionMessage::Notification(value) => {
+                            serde_json::from_value::<T>(value)
+                                .ok()
+                                .map(PubsubEvent::Notification)
+                        }
+                        SubscriptionMessage::Gap { last_seen_slot } => {
+                            Some(PubsubEvent::Gap { last_seen_slot })
+                        }
+                    })
+                })
                 .boxed(),
             unsubscribe,
         ))
@@ -413,6 +531,25 @@ impl PubsubClient {
         self.subscribe("logs", json!([filter, config])).await
     }
 
+    /// Subscribe to transaction events.
+    ///
+    /// Receives messages of type [`RpcTransactionUpdate`] when a transaction
+    /// matching `filter` is confirmed or finalized.
+    ///
+    /// This method is disabled by default. It can be enabled by passing
+    /// `--rpc-pubsub-enable-transaction-subscription` to `solana-validator`.
+    ///
+    /// # RPC Reference
+    ///
+    /// This method corresponds directly to the `transactionSubscribe` RPC method.
+    pub async fn transaction_subscribe(
+        &self,
+        filter: RpcTransactionSubscribeFilter,
+        config: Option<RpcTransactionSubscribeConfig>,
+    ) -> SubscribeResult<'_, RpcResponse<RpcTransactionUpdate>> {
+        self.subscribe("transaction", json!([filter, config])).await
+    }
+
     /// Subscribe to program account events.
     ///
     /// Receives messages of type [`RpcKeyedAccount`] when an account owned
@@ -533,27 +670,268 @@ impl PubsubClient {
     }
 
     async fn run_ws(
-        mut ws: WebSocketStream<MaybeTlsStream<TcpStream>>,
+        mut ws: WsStream,
         mut subscribe_receiver: mpsc::UnboundedReceiver<SubscribeRequestMsg>,
         mut request_receiver: mpsc::UnboundedReceiver<RequestMsg>,
         mut shutdown_receiver: oneshot::Receiver<()>,
+        reconnect: Option<(Url, PubsubReconnectConfig)>,
     ) -> PubsubClientResult {
-        let mut request_id: u64 = 0;
-
-        let mut requests_subscribe = BTreeMap::new();
-        let mut requests_unsubscribe = BTreeMap::<u64, oneshot::Sender<()>>::new();
-        let mut other_requests = BTreeMap::new();
-        let mut subscriptions = BTreeMap::new();
         let (unsubscribe_sender, mut unsubscribe_receiver) = mpsc::unbounded_channel();
+        let mut state = WsState::new(unsubscribe_sender);
 
+        loop {
+            let result = state
+                .run_connection(
+                    &mut ws,
+                    &mut subscribe_receiver,
+                    &mut request_receiver,
+                    &mut unsubscribe_receiver,
+                    &mut shutdown_receiver,
+                )
+                .await;
+            let Some((url, config)) = &reconnect else {
+                return result.map(|_| ());
+            };
+            match result {
+                Ok(ConnectionEnd::Shutdown) => return Ok(()),
+                Ok(ConnectionEnd::Closed) => info!("pubsub connection closed, reconnecting"),
+                Err(err) => warn!("pubsub connection failed, reconnecting: {err}"),
+            }
+            state.disconnected();
+
+            tokio::select! {
+                _ = &mut shutdown_receiver => return Ok(()),
+                result = Self::reconnect(url, config) => ws = result?,
+            }
+            // A failure here surfaces on the next read or write, which reconnects again
+            if let Err(err) = state.restore(&mut ws, config.replay_from_slot).await {
+                warn!("failed to restore pubsub subscriptions: {err}");
+            }
+        }
+    }
+
+    async fn reconnect(url: &Url, config: &PubsubReconnectConfig) -> PubsubClientResult<WsStream> {
+        let mut backoff = config.initial_backoff;
+        let mut attempts = 0;
+        loop {
+            sleep(backoff).await;
+            match connect_async(url.clone()).await {
+                Ok((ws, _response)) => return Ok(ws),
+                Err(err) => {
+                    attempts += 1;
+                    if config
+                        .max_attempts
+                        .map_or(false, |max_attempts| attempts >= max_attempts)
+                    {
+                        return Err(PubsubClientError::ConnectionError(err));
+                    }
+                    debug!("pubsub reconnection attempt {attempts} failed: {err}");
+                    backoff = backoff.saturating_mul(2).min(config.max_backoff);
+                }
+            }
+        }
+    }
+}
+
+type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+type UnsubscribeMsg = (u64, oneshot::Sender<()>);
+
+enum ConnectionEnd {
+    /// The client was shut down, or the caller it was serving went away
+    Shutdown,
+    /// The server closed the connection
+    Closed,
+}
+
+enum PendingSubscribe {
+    New {
+        operation: String,
+        params: Value,
+        response_sender: oneshot::Sender<SubscribeResponseMsg>,
+    },
+    /// Resubscribing an existing subscription after a reconnect
+    Restore {
+        key: u64,
+        operation: String,
+        from_slot: Option<Slot>,
+    },
+}
+
+struct ActiveSubscription {
+    operation: String,
+    params: Value,
+    sender: mpsc::UnboundedSender<SubscriptionMessage>,
+    /// `None` while the subscription is being restored
+    server_id: Option<u64>,
+    last_seen_slot: Option<Slot>,
+    /// Signatures of the logs notifications of `last_seen_slot`, which is
+    /// replayed in full since it may not have been complete
+    last_seen_signatures: HashSet<String>,
+}
+
+impl ActiveSubscription {
+    /// Records the slot of a notification, returning false if it is a
+    /// replayed notification that was already delivered.
+    fn observe(&mut self, result: &Value) -> bool {
+        let Some(slot) = notification_slot(result) else {
+            return true;
+        };
+        let signature = (self.operation == "logs")
+            .then(|| result.pointer("/value/signature").and_then(Value::as_str))
+            .flatten();
+        match self.last_seen_slot {
+            Some(last_seen_slot) if slot < last_seen_slot => return true,
+            Some(last_seen_slot) if slot == last_seen_slot => {}
+            _ => {
+                self.last_seen_slot = Some(slot);
+                self.last_seen_signatures.clear();
+            }
+        }
+        match signature {
+            Some(signature) => self.last_seen_signatures.insert(signature.to_string()),
+            None => true,
+        }
+    }
+}
+
+/// Subscriptions and in-flight requests of a `PubsubClient`. Subscriptions are
+/// keyed by a client-side id, so that they survive reconnects even though the
+/// server assigns them new ids.
+struct WsState {
+    request_id: u64,
+    next_subscription_key: u64,
+    requests_subscribe: BTreeMap<u64, PendingSubscribe>,
+    requests_unsubscribe: BTreeMap<u64, Option<oneshot::Sender<()>>>,
+    other_requests: BTreeMap<u64, oneshot::Sender<Result<Value, PubsubClientError>>>,
+    /// Subscribe requests to send again once reconnected
+    queued_subscribes: Vec<PendingSubscribe>,
+    subscriptions: BTreeMap<u64, ActiveSubscription>,
+    server_ids: BTreeMap<u64, u64>,
+    unsubscribe_sender: mpsc::UnboundedSender<UnsubscribeMsg>,
+}
+
+impl WsState {
+    fn new(unsubscribe_sender: mpsc::UnboundedSender<UnsubscribeMsg>) -> Self {
+        Self {
+            request_id: 0,
+            next_subscription_key: 0,
+            requests_subscribe: BTreeMap::new(),
+            requests_unsubscribe: BTreeMap::new(),
+            other_requests: BTreeMap::new(),
+            queued_subscribes: Vec::new(),
+            subscriptions: BTreeMap::new(),
+            server_ids: BTreeMap::new(),
+            unsubscribe_sender,
+        }
+    }
+
+    fn request(&mut self, method: &str, params: &Value) -> (u64, Message) {
+        self.request_id += 1;
+        let text = json!({"jsonrpc":"2.0","id":self.request_id,"method":method,"params":params})
+            .to_string();
+        (self.request_id, Message::Text(text))
+    }
+
+    async fn send_subscribe(
+        &mut self,
+        ws: &mut WsStream,
+        pending: PendingSubscribe,
+    ) -> PubsubClientResult {
+        let (operation, params) = match &pending {
+            PendingSubscribe::New {
+                operation, params, ..
+            } => (operation, params.clone()),
+            PendingSubscribe::Restore {
+                key,
+                operation,
+                from_slot,
+            } => {
+                let params = &self.subscriptions[key].params;
+                let params = match from_slot {
+                    Some(from_slot) => params_with_from_slot(operation, params, *from_slot)
+                        .unwrap_or_else(|| params.clone()),
+                    None => params.clone(),
+                };
+                (operation, params)
+            }
+        };
+        let (id, message) = self.request(&format!("{operation}Subscribe"), &params);
+        self.requests_subscribe.insert(id, pending);
+        ws.send(message).await?;
+        Ok(())
+    }
+
+    async fn send_unsubscribe(
+        &mut self,
+        ws: &mut WsStream,
+        operation: &str,
+        server_id: u64,
+        response_sender: Option<oneshot::Sender<()>>,
+    ) -> PubsubClientResult {
+        let (id, message) = self.request(&format!("{operation}Unsubscribe"), &json!([server_id]));
+        self.requests_unsubscribe.insert(id, response_sender);
+        ws.send(message).await?;
+        Ok(())
+    }
+
+    /// Forgets everything tied to the connection that just went away. Pending
+    /// subscribe requests are kept to be sent again; other requests fail.
+    fn disconnected(&mut self) {
+        self.server_ids.clear();
+        self.requests_unsubscribe.clear();
+        self.other_requests.clear();
+        for pending in std::mem::take(&mut self.requests_subscribe).into_values() {
+            if let PendingSubscribe::New { .. } = pending {
+                self.queued_subscribes.push(pending);
+            }
+        }
+        for subscription in self.subscriptions.values_mut() {
+            subscription.server_id = None;
+        }
+    }
+
+    /// Resends the subscribe requests interrupted by a reconnect, and
+    /// resubscribes every active subscription.
+    async fn restore(&mut self, ws: &mut WsStream, replay_from_slot: bool) -> PubsubClientResult {
+        for pending in std::mem::take(&mut self.queued_subscribes) {
+            self.send_subscribe(ws, pending).await?;
+        }
+        let restores: Vec<_> = self
+            .subscriptions
+            .iter()
+            .map(|(key, subscription)| PendingSubscribe::Restore {
+                key: *key,
+                operation: subscription.operation.clone(),
+                from_slot: subscription
+                    .last_seen_slot
+                    .filter(|_| replay_from_slot && is_replayable(&subscription.operation))
+                    .map(|last_seen_slot| {
+                        first_unseen_slot(&subscription.operation, last_seen_slot)
+                    }),
+            })
+            .collect();
+        for pending in restores {
+            self.send_subscribe(ws, pending).await?;
+        }
+        Ok(())
+    }
+
+    async fn run_connection(
+        &mut self,
+        ws: &mut WsStream,
+        subscribe_receiver: &mut mpsc::UnboundedReceiver<SubscribeRequestMsg>,
+        request_receiver: &mut mpsc::UnboundedReceiver<RequestMsg>,
+        unsubscribe_receiver: &mut mpsc::UnboundedReceiver<UnsubscribeMsg>,
+        shutdown_receiver: &mut oneshot::Receiver<()>,
+    ) -> PubsubClientResult<ConnectionEnd> {
         loop {
             tokio::select! {
                 // Send close on shutdown signal
-                _ = (&mut shutdown_receiver) => {
+                _ = &mut *shutdown_receiver => {
                     let frame = CloseFrame { code: CloseCode::Normal, reason: "".into() };
                     ws.send(Message::Close(Some(frame))).await?;
                     ws.flush().await?;
-                    break;
+                    return Ok(ConnectionEnd::Shutdown);
                 },
                 // Send `Message::Ping` each 10s if no any other communication
                 () = sleep(Duration::from_secs(10)) => {
@@ -561,33 +939,32 @@ impl PubsubClient {
                 },
                 // Read message for subscribe
                 Some((operation, params, response_sender)) = subscribe_receiver.recv() => {
-                    request_id += 1;
-                    let method = format!("{operation}Subscribe");
-                    let text = json!({"jsonrpc":"2.0","id":request_id,"method":method,"params":params}).to_string();
-                    ws.send(Message::Text(text)).await?;
-                    requests_subscribe.insert(request_id, (operation, response_sender));
+                    self.send_subscribe(ws, PendingSubscribe::New { operation, params, response_sender }).await?;
                 },
                 // Read message for unsubscribe
-                Some((operation, sid, response_sender)) = unsubscribe_receiver.recv() => {
-                    subscriptions.remove(&sid);
-                    request_id += 1;
-                    let method = format!("{operation}Unsubscribe");
-                    let text = json!({"jsonrpc":"2.0","id":request_id,"method":method,"params":[sid]}).to_string();
-                    ws.send(Message::Text(text)).await?;
-                    requests_unsubscribe.insert(request_id, response_sender);
+                Some((key, response_sender)) = unsubscribe_receiver.recv() => {
+                    match self.subscriptions.remove(&key) {
+                        Some(ActiveSubscription { operation, server_id: Some(server_id), .. }) => {
+                            self.server_ids.remove(&server_id);
+                            self.send_unsubscribe(ws, &operation, server_id, Some(response_sender)).await?;
+                        }
+                        // Not subscribed on the server (yet), nothing to tell it
+                        _ => {
+                            let _ = response_sender.send(());
+                        }
+                    }
                 },
                 // Read message for other requests
                 Some((method, params, response_sender)) = request_receiver.recv() => {
-                    request_id += 1;
-                    let text = json!({"jsonrpc":"2.0","id":request_id,"method":method,"params":params}).to_string();
-                    ws.send(Message::Text(text)).await?;
-                    other_requests.insert(request_id, response_sender);
+                    let (id, message) = self.request(&method, &params);
+                    self.other_requests.insert(id, response_sender);
+                    ws.send(message).await?;
                 }
                 // Read incoming WebSocket message
                 next_msg = ws.next() => {
                     let msg = match next_msg {
                         Some(msg) => msg?,
-                        None => break,
+                        None => return Ok(ConnectionEnd::Closed),
                     };
                     trace!("ws.next(): {:?}", &msg);
 
@@ -600,7 +977,7 @@ impl PubsubClient {
                             continue
                         },
                         Message::Pong(_data) => continue,
-                        Message::Close(_frame) => break,
+                        Message::Close(_frame) => return Ok(ConnectionEnd::Closed),
                         Message::Frame(_frame) => continue,
                     };
 
@@ -626,8 +1003,11 @@ impl PubsubClient {
                                 )
                             }
                         });
+                        let subscription_id = || json.get("result").and_then(Value::as_u64).ok_or_else(|| {
+                            PubsubClientError::SubscribeFailed { reason: "invalid `result` field".into(), message: text.clone() }
+                        });
 
-                        if let Some(response_sender) = other_requests.remove(&id) {
+                        if let Some(response_sender) = self.other_requests.remove(&id) {
                             match err {
                                 Some(reason) => {
                                     let _ = response_sender.send(Err(PubsubClientError::RequestFailed { reason, message: text.clone()}));
@@ -637,43 +1017,81 @@ impl PubsubClient {
                                         PubsubClientError::RequestFailed { reason: "missing `result` field".into(), message: text.clone() }
                                     })?;
                                     if response_sender.send(Ok(json_result.clone())).is_err() {
-                                        break;
+                                        return Ok(ConnectionEnd::Shutdown);
                                     }
                                 }
                             }
-                        } else if let Some(response_sender) = requests_unsubscribe.remove(&id) {
-                            let _ = response_sender.send(()); // do not care if receiver is closed
-                        } else if let Some((operation, response_sender)) = requests_subscribe.remove(&id) {
+                        } else if let Some(response_sender) = self.requests_unsubscribe.remove(&id) {
+                            if let Some(response_sender) = response_sender {
+                                let _ = response_sender.send(()); // do not care if receiver is closed
+                            }
+                        } else if let Some(PendingSubscribe::New { operation, params, response_sender }) = self.requests_subscribe.remove(&id) {
                             match err {
                                 Some(reason) => {
                                     let _ = response_sender.send(Err(PubsubClientError::SubscribeFailed { reason, message: text.clone()}));
                                 },
                                 None => {
-                                    // Subscribe Id
-                                    let sid = json.get("result").and_then(Value::as_u64).ok_or_else(|| {
-                                        PubsubClientError::SubscribeFailed { reason: "invalid `result` field".into(), message: text.clone() }
-                                    })?;
+                                    let server_id = subscription_id()?;
+                                    let key = self.next_subscription_key;
+                                    self.next_subscription_key += 1;
 
                                     // Create notifications channel and unsubscribe function
                                     let (notifications_sender, notifications_receiver) = mpsc::unbounded_channel();
-                                    let unsubscribe_sender = unsubscribe_sender.clone();
+                                    let unsubscribe_sender = self.unsubscribe_sender.clone();
                                     let unsubscribe = Box::new(move || async move {
                                         let (response_sender, response_receiver) = oneshot::channel();
                                         // do nothing if ws already closed
-                                        if unsubscribe_sender.send((operation, sid, response_sender)).is_ok() {
+                                        if unsubscribe_sender.send((key, response_sender)).is_ok() {
                                             let _ = response_receiver.await; // channel can be closed only if ws is closed
                                         }
                                     }.boxed());
 
                                     if response_sender.send(Ok((notifications_receiver, unsubscribe))).is_err() {
-                                        break;
+                                        return Ok(ConnectionEnd::Shutdown);
+                                    }
+                                    self.server_ids.insert(server_id, key);
+                                    self.subscriptions.insert(key, ActiveSubscription {
+                                        operation,
+                                        params,
+                                        sender: notifications_sender,
+                                        server_id: Some(server_id),
+                                        last_seen_slot: None,
+                                        last_seen_signatures: HashSet::new(),
+                                    });
+                                }
+                            }
+                        } else if let Some(PendingSubscribe::Restore { key, operation, from_slot }) = self.requests_subscribe.remove(&id) {
+                            match (err, from_slot) {
+                                (Some(reason), Some(from_slot)) => {
+                                    debug!("cannot replay {operation} subscription from slot {from_slot}: {reason}");
+                                    if self.subscriptions.contains_key(&key) {
+                                        self.send_subscribe(ws, PendingSubscribe::Restore { key, operation, from_slot: None }).await?;
+                                    }
+                                }
+                                (Some(reason), None) => {
+                                    // Dropping the sender ends the subscription stream
+                                    warn!("failed to restore {operation} subscription: {reason}");
+                                    self.subscriptions.remove(&key);
+                                }
+                                (None, _) => {
+                                    let server_id = subscription_id()?;
+                                    if let Some(subscription) = self.subscriptions.get_mut(&key) {
+                                        subscription.server_id = Some(server_id);
+                                        self.server_ids.insert(server_id, key);
+                                        if from_slot.is_none() {
+                                            let _ = subscription.sender.send(SubscriptionMessage::Gap {
+                                                last_seen_slot: subscription.last_seen_slot,
+                                            });
+                                        }
+                                    } else {
+                                        // Unsubscribed while being restored
+                                        self.send_unsubscribe(ws, &operation, server_id, None).await?;
                                     }
-                                    subscriptions.insert(sid, notifications_sender);
                                 }
                             }
                         } else {
                             error!("Unknown request id: {}", id);
-                            break;
+                            return Ok(ConnectionEnd::Closed);
                         }
                         continue;
                     }
@@ -681,12 +1099,16 @@ impl PubsubClient {
                     // Notification, example:
                     // `{"jsonrpc":"2.0","method":"logsNotification","params":{"result":{...},"subscription":3114862}}`
                     if let Some(Value::Object(params)) = json.get_mut("params") {
-                        if let Some(sid) = params.get("subscription").and_then(Value::as_u64) {
+                        if let Some(server_id) = params.get("subscription").and_then(Value::as_u64) {
                             let mut unsubscribe_required = false;
 
-                            if let Some(notifications_sender) = subscriptions.get(&sid) {
+                            let subscription = self
+                                .server_ids
+                                .get(&server_id)
+                                .and_then(|key| self.subscriptions.get_mut(key));
+                            if let Some(subscription) = subscription {
                                 if let Some(result) = params.remove("result") {
-                                    if notifications_sender.send(result).is_err() {
+                                    if subscription.observe(&result) && subscription.sender.send(SubscriptionMessage::Notification(result)).is_err() {
                                         unsubscribe_required = true;
                                     }
                                 }
@@ -695,10 +1117,12 @@ impl PubsubClient {
                             }
 
                             if unsubscribe_required {
+                                if let Some(key) = self.server_ids.remove(&server_id) {
+                                    self.subscriptions.remove(&key);
+                                }
                                 if let Some(Value::String(method)) = json.remove("method") {
                                     if let Some(operation) = method.strip_suffix("Notification") {
-                                        let (response_sender, _response_receiver) = oneshot::channel();
-                                        let _ = unsubscribe_sender.send((operation.to_string(), sid, response_sender));
+                                        self.send_unsubscribe(ws, operation, server_id, None).await?;
                                     }
                                 }
                             }
@@ -707,9 +1131,53 @@ impl PubsubClient {
                 }
             }
         }
+    }
+}
 
-        Ok(())
+/// Operations whose subscribe requests accept `fromSlot`.
+fn is_replayable(operation: &str) -> bool {
+    matches!(operation, "account" | "block" | "logs" | "slot")
+}
+
+/// The slot to resume a subscription from after a reconnect. The server
+/// replays notifications at or after `fromSlot`, and only logs subscriptions
+/// can be notified more than once in a slot.
+fn first_unseen_slot(operation: &str, last_seen_slot: Slot) -> Slot {
+    match operation {
+        "logs" => last_seen_slot,
+        _ => last_seen_slot.saturating_add(1),
+    }
+}
+
+/// Returns `params` with `fromSlot` set in the subscription config, or `None`
+/// if the operation doesn't support it.
+fn params_with_from_slot(operation: &str, params: &Value, from_slot: Slot) -> Option<Value> {
+    let config_index = match operation {
+        "slot" => 0,
+        "account" | "block" | "logs" => 1,
+        _ => return None,
+    };
+    let mut params = params.as_array()?.clone();
+    if params.len() <= config_index {
+        params.resize(config_index + 1, Value::Null);
     }
+    let config = &mut params[config_index];
+    if config.is_null() {
+        *config = json!({});
+    }
+    config
+        .as_object_mut()?
+        .insert("fromSlot".to_string(), json!(from_slot));
+    Some(Value::Array(params))
+}
+
+/// The slot a notification refers to, as far as it can be told from its result.
+fn notification_slot(result: &Value) -> Option<Slot> {
+    result
+        .pointer("/context/slot")
+        .or_else(|| result.get("slot"))
+        .unwrap_or(result)
+        .as_u64()
 }
 
 #[cfg(test)]
@@ -101,12 +101,12 @@ use {
         config::{
             RpcAccountInfoConfig, RpcBlockSubscribeConfig, RpcBlockSubscribeFilter,
             RpcProgramAccountsConfig, RpcSignatureSubscribeConfig, RpcTransactionLogsConfig,
-            RpcTransactionLogsFilter,
+            RpcTransactionLogsFilter, RpcTransactionSubscribeConfig, RpcTransactionSubscribeFilter,
         },
         filter,
         response::{
             Response as RpcResponse, RpcBlockUpdate, RpcKeyedAccount, RpcLogsResponse,
-            RpcSignatureResult, RpcVote, SlotInfo, SlotUpdate,
+            RpcSignatureResult, RpcTransactionUpdate, RpcVote, SlotInfo, SlotUpdate,
         },
     },
     solana_sdk::{clock::Slot, pubkey::Pubkey, signature::Signature},
@@ -303,6 +303,13 @@ pub type BlockSubscription = (
     Receiver<RpcResponse<RpcBlockUpdate>>,
 );
 
+pub type PubsubTransactionClientSubscription =
+    PubsubClientSubscription<RpcResponse<RpcTransactionUpdate>>;
+pub type TransactionSubscription = (
+    PubsubTransactionClientSubscription,
+    Receiver<RpcResponse<RpcTransactionUpdate>>,
+);
+
 pub type PubsubProgramClientSubscription = PubsubClientSubscription<RpcResponse<RpcKeyedAccount>>;
 pub type ProgramSubscription = (
     PubsubProgramClientSubscription,
@@ -510,6 +517,57 @@ impl PubsubClient {
         Ok((result, receiver))
     }
 
+    /// Subscribe to transaction events.
+    ///
+    /// Receives messages of type [`RpcTransactionUpdate`] when a transaction
+    /// matching `filter` is confirmed or finalized.
+    ///
+    /// This method is disabled by default. It can be enabled by passing
+    /// `--rpc-pubsub-enable-transaction-subscription` to `solana-validator`.
+    ///
+    /// # RPC Reference
+    ///
+    /// This method corresponds directly to the `transactionSubscribe` RPC method.
+    pub fn transaction_subscribe(
+        url: &str,
+        filter: RpcTransactionSubscribeFilter,
+        config: Option<RpcTransactionSubscribeConfig>,
+    ) -> Result<TransactionSubscription, PubsubClientError> {
+        let url = Url::parse(url)?;
+        let socket = connect_with_retry(url)?;
+        let (sender, receiver) = unbounded();
+
+        let socket = Arc::new(RwLock::new(socket));
+        let socket_clone = socket.clone();
+        let exit = Arc::new(AtomicBool::new(false));
+        let exit_clone = exit.clone();
+        let body = json!({
+            "jsonrpc":"2.0",
+            "id":1,
+            "method":"transactionSubscribe",
+            "params":[filter, config]
+        })
+        .to_string();
+
+        let subscription_id =
+            PubsubTransactionClientSubscription::send_subscribe(&socket_clone, body)?;
+
+        let t_cleanup = std::thread::spawn(move || {
+            Self::cleanup_with_sender(exit_clone, &socket_clone, sender)
+        });
+
+        let result = PubsubClientSubscription {
+            message_type: PhantomData,
+            operation: "transaction",
+            socket,
+            subscription_id,
+            t_cleanup: Some(t_cleanup),
+            exit,
+        };
+
+        Ok((result, receiver))
+    }
+
     /// Subscribe to program account events.
     ///
     /// Receives messages of type [`RpcKeyedAccount`] when an account owned
@@ -181,6 +181,31 @@ pub struct RpcTransactionLogsConfig {
     pub commitment: Option<CommitmentConfig>,
 }
 
+/// Extends a subscription config with `fromSlot`, asking the node to replay
+/// buffered notifications since that slot before streaming new ones.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcSubscribeReplayConfig<T> {
+    #[serde(flatten)]
+    pub config: T,
+    pub from_slot: Option<Slot>,
+}
+
+impl<T> From<T> for RpcSubscribeReplayConfig<T> {
+    fn from(config: T) -> Self {
+        Self {
+            config,
+            from_slot: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcSlotSubscribeConfig {
+    pub from_slot: Option<Slot>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub enum RpcTokenAccountsFilter {
@@ -214,6 +239,29 @@ pub struct RpcBlockSubscribeConfig {
     pub max_supported_transaction_version: Option<u8>,
 }
 
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcTransactionSubscribeFilter {
+    pub vote: Option<bool>,
+    pub failed: Option<bool>,
+    #[serde(default)]
+    pub account_include: Vec<String>, // base58-encoded list of addresses, any of
+    #[serde(default)]
+    pub account_exclude: Vec<String>, // base58-encoded list of addresses, none of
+    #[serde(default)]
+    pub account_required: Vec<String>, // base58-encoded list of addresses, all of
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcTransactionSubscribeConfig {
+    #[serde(flatten)]
+    pub commitment: Option<CommitmentConfig>,
+    pub encoding: Option<UiTransactionEncoding>,
+    pub show_rewards: Option<bool>,
+    pub max_supported_transaction_version: Option<u8>,
+}
+
 #[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RpcSignaturesForAddressConfig {
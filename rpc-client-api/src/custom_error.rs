@@ -24,6 +24,9 @@ pub const JSON_RPC_SERVER_ERROR_TRANSACTION_SIGNATURE_LEN_MISMATCH: i64 = -32013
 pub const JSON_RPC_SERVER_ERROR_BLOCK_STATUS_NOT_AVAILABLE_YET: i64 = -32014;
 pub const JSON_RPC_SERVER_ERROR_UNSUPPORTED_TRANSACTION_VERSION: i64 = -32015;
 pub const JSON_RPC_SERVER_ERROR_MIN_CONTEXT_SLOT_NOT_REACHED: i64 = -32016;
+pub const JSON_RPC_SERVER_ERROR_SUBSCRIPTION_REPLAY_UNAVAILABLE: i64 = -32017;
+pub const JSON_RPC_SERVER_ERROR_CONNECTION_SUBSCRIPTION_LIMIT_REACHED: i64 = -32018;
+pub const JSON_RPC_SERVER_ERROR_IP_SUBSCRIPTION_LIMIT_REACHED: i64 = -32019;
 
 #[derive(Error, Debug)]
 pub enum RpcCustomError {
@@ -65,6 +68,15 @@ pub enum RpcCustomError {
     UnsupportedTransactionVersion(u8),
     #[error("MinContextSlotNotReached")]
     MinContextSlotNotReached { context_slot: Slot },
+    #[error("SubscriptionReplayUnavailable")]
+    SubscriptionReplayUnavailable {
+        from_slot: Slot,
+        first_available_slot: Option<Slot>,
+    },
+    #[error("ConnectionSubscriptionLimitReached")]
+    ConnectionSubscriptionLimitReached { max_subscriptions: usize },
+    #[error("IpSubscriptionLimitReached")]
+    IpSubscriptionLimitReached { max_subscriptions: usize },
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -79,6 +91,12 @@ pub struct MinContextSlotNotReachedErrorData {
     pub context_slot: Slot,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionReplayUnavailableErrorData {
+    pub first_available_slot: Option<Slot>,
+}
+
 impl From<EncodeError> for RpcCustomError {
     fn from(err: EncodeError) -> Self {
         match err {
@@ -206,6 +224,36 @@ impl From<RpcCustomError> for Error {
                     context_slot,
                 })),
             },
+            RpcCustomError::SubscriptionReplayUnavailable {
+                from_slot,
+                first_available_slot,
+            } => Self {
+                code: ErrorCode::ServerError(JSON_RPC_SERVER_ERROR_SUBSCRIPTION_REPLAY_UNAVAILABLE),
+                message: format!(
+                    "Notifications since slot {from_slot} are no longer buffered by this node"
+                ),
+                data: Some(serde_json::json!(SubscriptionReplayUnavailableErrorData {
+                    first_available_slot,
+                })),
+            },
+            RpcCustomError::ConnectionSubscriptionLimitReached { max_subscriptions } => Self {
+                code: ErrorCode::ServerError(
+                    JSON_RPC_SERVER_ERROR_CONNECTION_SUBSCRIPTION_LIMIT_REACHED,
+                ),
+                message: format!(
+                    "Subscription refused: this connection already has the maximum of \
+                     {max_subscriptions} subscriptions"
+                ),
+                data: None,
+            },
+            RpcCustomError::IpSubscriptionLimitReached { max_subscriptions } => Self {
+                code: ErrorCode::ServerError(JSON_RPC_SERVER_ERROR_IP_SUBSCRIPTION_LIMIT_REACHED),
+                message: format!(
+                    "Subscription refused: connections from this address already have the \
+                     maximum of {max_subscriptions} subscriptions"
+                ),
+                data: None,
+            },
         }
     }
 }
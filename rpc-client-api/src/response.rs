@@ -10,8 +10,9 @@ use {
         transaction::{Result, TransactionError},
     },
     solana_transaction_status::{
-        ConfirmedTransactionStatusWithSignature, TransactionConfirmationStatus, UiConfirmedBlock,
-        UiInnerInstructions, UiTransactionReturnData,
+        ConfirmedTransactionStatusWithSignature, EncodedTransactionWithStatusMeta,
+        TransactionConfirmationStatus, UiConfirmedBlock, UiInnerInstructions,
+        UiTransactionReturnData,
     },
     std::{collections::HashMap, fmt, net::SocketAddr, str::FromStr},
     thiserror::Error,
@@ -522,6 +523,16 @@ pub struct RpcBlockUpdate {
     pub err: Option<RpcBlockUpdateError>,
 }
 
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcTransactionUpdate {
+    pub slot: Slot,
+    pub signature: Option<String>,
+    pub transaction: Option<EncodedTransactionWithStatusMeta>,
+    pub block_time: Option<UnixTimestamp>,
+    pub err: Option<RpcBlockUpdateError>,
+}
+
 impl From<ConfirmedTransactionStatusWithSignature> for RpcConfirmedTransactionStatusWithSignature {
     fn from(value: ConfirmedTransactionStatusWithSignature) -> Self {
         let ConfirmedTransactionStatusWithSignature {
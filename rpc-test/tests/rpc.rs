@@ -7,7 +7,7 @@ use {
     serde_json::{json, Value},
     solana_account_decoder::UiAccount,
     solana_client::{
-        connection_cache::ConnectionCache,
+        connection_cache::{ConnectionCache, InMemoryNetwork},
         tpu_client::{TpuClient, TpuClientConfig},
     },
     solana_pubsub_client::nonblocking::pubsub_client::PubsubClient,
@@ -28,7 +28,7 @@ use {
         transaction::Transaction,
     },
     solana_streamer::socket::SocketAddrSpace,
-    solana_test_validator::TestValidator,
+    solana_test_validator::{TestValidator, TestValidatorGenesis},
     solana_tpu_client::tpu_client::DEFAULT_TPU_CONNECTION_POOL_SIZE,
     solana_transaction_status::TransactionStatus,
     std::{
@@ -456,10 +456,6 @@ fn run_tpu_send_transaction(tpu_use_quic: bool) {
     let mint_pubkey = mint_keypair.pubkey();
     let test_validator =
         TestValidator::with_no_fees(mint_pubkey, None, SocketAddrSpace::Unspecified);
-    let rpc_client = Arc::new(RpcClient::new_with_commitment(
-        test_validator.rpc_url(),
-        CommitmentConfig::processed(),
-    ));
     let connection_cache = match tpu_use_quic {
         true => {
             ConnectionCache::new_quic("connection_cache_test", DEFAULT_TPU_CONNECTION_POOL_SIZE)
@@ -468,9 +464,21 @@ fn run_tpu_send_transaction(tpu_use_quic: bool) {
             ConnectionCache::with_udp("connection_cache_test", DEFAULT_TPU_CONNECTION_POOL_SIZE)
         }
     };
+    send_transaction_with_connection_cache(&test_validator, &mint_keypair, connection_cache);
+}
+
+fn send_transaction_with_connection_cache(
+    test_validator: &TestValidator,
+    mint_keypair: &Keypair,
+    connection_cache: ConnectionCache,
+) {
+    let rpc_client = Arc::new(RpcClient::new_with_commitment(
+        test_validator.rpc_url(),
+        CommitmentConfig::processed(),
+    ));
     let recent_blockhash = rpc_client.get_latest_blockhash().unwrap();
     let tx =
-        system_transaction::transfer(&mint_keypair, &Pubkey::new_unique(), 42, recent_blockhash);
+        system_transaction::transfer(mint_keypair, &Pubkey::new_unique(), 42, recent_blockhash);
     let success = match connection_cache {
         ConnectionCache::Quic(cache) => TpuClient::new_with_connection_cache(
             rpc_client.clone(),
@@ -488,6 +496,14 @@ fn run_tpu_send_transaction(tpu_use_quic: bool) {
         )
         .unwrap()
         .send_transaction(&tx),
+        ConnectionCache::InMemory(cache) => TpuClient::new_with_connection_cache(
+            rpc_client.clone(),
+            &test_validator.rpc_pubsub_url(),
+            TpuClientConfig::default(),
+            cache,
+        )
+        .unwrap()
+        .send_transaction(&tx),
     };
     assert!(success);
     let timeout = Duration::from_secs(5);
@@ -512,6 +528,21 @@ fn test_tpu_send_transaction_with_quic() {
     run_tpu_send_transaction(/*tpu_use_quic*/ true)
 }
 
+#[test]
+fn test_tpu_send_transaction_in_memory() {
+    let network = InMemoryNetwork::default();
+    let (test_validator, mint_keypair) = TestValidatorGenesis::default()
+        .in_memory_tpu(network.clone())
+        .start();
+    let connection_cache = ConnectionCache::with_in_memory(
+        "connection_cache_test",
+        DEFAULT_TPU_CONNECTION_POOL_SIZE,
+        network.clone(),
+    );
+    send_transaction_with_connection_cache(&test_validator, &mint_keypair, connection_cache);
+    assert!(network.stats().packets_delivered > 0);
+}
+
 #[test]
 fn deserialize_rpc_error() -> ClientResult<()> {
     solana_logger::setup();
@@ -84,7 +84,7 @@ use {
         tpu_info::NullTpuInfo,
     },
     solana_stake_program,
-    solana_storage_bigtable::Error as StorageError,
+    solana_storage_bigtable::{Error as StorageError, LongTermLedgerStorage},
     solana_streamer::socket::SocketAddrSpace,
     solana_transaction_status::{
         map_inner_instructions, BlockEncodingOptions, ConfirmedBlock,
@@ -104,6 +104,7 @@ use {
         collections::{BinaryHeap, HashMap, HashSet},
         convert::TryFrom,
         net::SocketAddr,
+        path::PathBuf,
         str::FromStr,
         sync::{
             atomic::{AtomicBool, AtomicU64, Ordering},
@@ -144,6 +145,7 @@ pub struct JsonRpcConfig {
     pub faucet_addr: Option<SocketAddr>,
     pub health_check_slot_distance: u64,
     pub rpc_bigtable_config: Option<RpcBigtableConfig>,
+    pub rpc_ledger_archive_config: Option<RpcLedgerArchiveConfig>,
     pub max_multiple_accounts: Option<usize>,
     pub account_indexes: AccountSecondaryIndexes,
     pub rpc_threads: usize,
@@ -173,6 +175,7 @@ pub struct RpcBigtableConfig {
     pub bigtable_app_profile_id: String,
     pub timeout: Option<Duration>,
     pub max_message_size: usize,
+    pub compression: solana_storage_bigtable::CompressionConfig,
 }
 
 impl Default for RpcBigtableConfig {
@@ -185,10 +188,18 @@ impl Default for RpcBigtableConfig {
             bigtable_app_profile_id,
             timeout: None,
             max_message_size: solana_storage_bigtable::DEFAULT_MAX_MESSAGE_SIZE,
+            compression: solana_storage_bigtable::CompressionConfig::default(),
         }
     }
 }
 
+/// Serves historical queries from a local ledger archive, in place of BigTable
+#[derive(Debug, Clone, Default)]
+pub struct RpcLedgerArchiveConfig {
+    pub ledger_archive_path: PathBuf,
+    pub enable_ledger_archive_upload: bool,
+}
+
 #[derive(Clone)]
 pub struct JsonRpcRequestProcessor {
     bank_forks: Arc<RwLock<BankForks>>,
@@ -202,7 +213,7 @@ pub struct JsonRpcRequestProcessor {
     cluster_info: Arc<ClusterInfo>,
     genesis_hash: Hash,
     transaction_sender: Arc<Mutex<Sender<TransactionInfo>>>,
-    bigtable_ledger_storage: Option<solana_storage_bigtable::LedgerStorage>,
+    bigtable_ledger_storage: Option<Arc<dyn LongTermLedgerStorage>>,
     optimistically_confirmed_bank: Arc<RwLock<OptimisticallyConfirmedBank>>,
     largest_accounts_cache: Arc<RwLock<LargestAccountsCache>>,
     max_slots: Arc<MaxSlots>,
@@ -318,7 +329,7 @@ impl JsonRpcRequestProcessor {
         health: Arc<RpcHealth>,
         cluster_info: Arc<ClusterInfo>,
         genesis_hash: Hash,
-        bigtable_ledger_storage: Option<solana_storage_bigtable::LedgerStorage>,
+        bigtable_ledger_storage: Option<Arc<dyn LongTermLedgerStorage>>,
         optimistically_confirmed_bank: Arc<RwLock<OptimisticallyConfirmedBank>>,
         largest_accounts_cache: Arc<RwLock<LargestAccountsCache>>,
         max_slots: Arc<MaxSlots>,
@@ -4,12 +4,12 @@ use crate::{rpc_pubsub_service, rpc_subscriptions::RpcSubscriptions};
 use {
     crate::{
         rpc::check_is_at_least_confirmed,
-        rpc_pubsub_service::PubSubConfig,
+        rpc_pubsub_service::{IpSubscriptionQuota, PubSubConfig, ReplayQueue},
         rpc_subscription_tracker::{
             AccountSubscriptionParams, BlockSubscriptionKind, BlockSubscriptionParams,
             LogsSubscriptionKind, LogsSubscriptionParams, ProgramSubscriptionParams,
             SignatureSubscriptionParams, SubscriptionControl, SubscriptionId, SubscriptionParams,
-            SubscriptionToken,
+            SubscriptionToken, TransactionSubscriptionFilter, TransactionSubscriptionParams,
         },
     },
     dashmap::DashMap,
@@ -20,12 +20,15 @@ use {
     solana_rpc_client_api::{
         config::{
             RpcAccountInfoConfig, RpcBlockSubscribeConfig, RpcBlockSubscribeFilter,
-            RpcProgramAccountsConfig, RpcSignatureSubscribeConfig, RpcTransactionLogsConfig,
-            RpcTransactionLogsFilter,
+            RpcProgramAccountsConfig, RpcSignatureSubscribeConfig, RpcSlotSubscribeConfig,
+            RpcSubscribeReplayConfig, RpcTransactionLogsConfig, RpcTransactionLogsFilter,
+            RpcTransactionSubscribeConfig, RpcTransactionSubscribeFilter,
         },
+        custom_error::RpcCustomError,
         response::{
             Response as RpcResponse, RpcBlockUpdate, RpcKeyedAccount, RpcLogsResponse,
-            RpcSignatureResult, RpcVersionInfo, RpcVote, SlotInfo, SlotUpdate,
+            RpcSignatureResult, RpcTransactionUpdate, RpcVersionInfo, RpcVote, SlotInfo,
+            SlotUpdate,
         },
     },
     solana_sdk::{clock::Slot, pubkey::Pubkey, signature::Signature},
@@ -214,6 +217,32 @@ pub trait RpcSolPubSub {
         id: PubSubSubscriptionId,
     ) -> Result<bool>;
 
+    // Subscribe to transactions matching an account filter
+    #[pubsub(
+        subscription = "transactionNotification",
+        subscribe,
+        name = "transactionSubscribe"
+    )]
+    fn transaction_subscribe(
+        &self,
+        meta: Self::Metadata,
+        subscriber: Subscriber<RpcResponse<RpcTransactionUpdate>>,
+        filter: RpcTransactionSubscribeFilter,
+        config: Option<RpcTransactionSubscribeConfig>,
+    );
+
+    // Unsubscribe from transaction notification subscription.
+    #[pubsub(
+        subscription = "transactionNotification",
+        unsubscribe,
+        name = "transactionUnsubscribe"
+    )]
+    fn transaction_unsubscribe(
+        &self,
+        meta: Option<Self::Metadata>,
+        id: PubSubSubscriptionId,
+    ) -> Result<bool>;
+
     // Get notification when vote is encountered
     #[pubsub(subscription = "voteNotification", subscribe, name = "voteSubscribe")]
     fn vote_subscribe(&self, meta: Self::Metadata, subscriber: Subscriber<RpcVote>);
@@ -261,7 +290,7 @@ mod internal {
         fn account_subscribe(
             &self,
             pubkey_str: String,
-            config: Option<RpcAccountInfoConfig>,
+            config: Option<RpcSubscribeReplayConfig<RpcAccountInfoConfig>>,
         ) -> Result<SubscriptionId>;
 
         // Unsubscribe from account notification subscription.
@@ -286,7 +315,7 @@ mod internal {
         fn logs_subscribe(
             &self,
             filter: RpcTransactionLogsFilter,
-            config: Option<RpcTransactionLogsConfig>,
+            config: Option<RpcSubscribeReplayConfig<RpcTransactionLogsConfig>>,
         ) -> Result<SubscriptionId>;
 
         // Unsubscribe from logs notification subscription.
@@ -308,7 +337,7 @@ mod internal {
 
         // Get notification when slot is encountered
         #[rpc(name = "slotSubscribe")]
-        fn slot_subscribe(&self) -> Result<SubscriptionId>;
+        fn slot_subscribe(&self, config: Option<RpcSlotSubscribeConfig>) -> Result<SubscriptionId>;
 
         // Unsubscribe from slot notification subscription.
         #[rpc(name = "slotUnsubscribe")]
@@ -327,13 +356,25 @@ mod internal {
         fn block_subscribe(
             &self,
             filter: RpcBlockSubscribeFilter,
-            config: Option<RpcBlockSubscribeConfig>,
+            config: Option<RpcSubscribeReplayConfig<RpcBlockSubscribeConfig>>,
         ) -> Result<SubscriptionId>;
 
         // Unsubscribe from block notification subscription.
         #[rpc(name = "blockUnsubscribe")]
         fn block_unsubscribe(&self, id: SubscriptionId) -> Result<bool>;
 
+        // Subscribe to transactions matching an account filter
+        #[rpc(name = "transactionSubscribe")]
+        fn transaction_subscribe(
+            &self,
+            filter: RpcTransactionSubscribeFilter,
+            config: Option<RpcTransactionSubscribeConfig>,
+        ) -> Result<SubscriptionId>;
+
+        // Unsubscribe from transaction notification subscription.
+        #[rpc(name = "transactionUnsubscribe")]
+        fn transaction_unsubscribe(&self, id: SubscriptionId) -> Result<bool>;
+
         // Get notification when vote is encountered
         #[rpc(name = "voteSubscribe")]
         fn vote_subscribe(&self) -> Result<SubscriptionId>;
@@ -360,6 +401,8 @@ pub struct RpcSolPubSubImpl {
     config: PubSubConfig,
     subscription_control: SubscriptionControl,
     current_subscriptions: Arc<DashMap<SubscriptionId, SubscriptionToken>>,
+    replay_queue: ReplayQueue,
+    ip_quota: Option<IpSubscriptionQuota>,
 }
 
 impl RpcSolPubSubImpl {
@@ -367,27 +410,98 @@ impl RpcSolPubSubImpl {
         config: PubSubConfig,
         subscription_control: SubscriptionControl,
         current_subscriptions: Arc<DashMap<SubscriptionId, SubscriptionToken>>,
+        replay_queue: ReplayQueue,
+        ip_quota: Option<IpSubscriptionQuota>,
     ) -> Self {
         Self {
             config,
             subscription_control,
             current_subscriptions,
+            replay_queue,
+            ip_quota,
         }
     }
 
-    fn subscribe(&self, params: SubscriptionParams) -> Result<SubscriptionId> {
-        let token = self
-            .subscription_control
-            .subscribe(params)
-            .map_err(|_| Error {
-                code: ErrorCode::InternalError,
-                message: "Internal Error: Subscription refused. Node subscription limit reached"
-                    .into(),
-                data: None,
+    /// Subscribes this connection to `params` if `prepare` succeeds for the
+    /// subscription id. The quota of the client IP address is checked and used
+    /// up in one step, so concurrent connections from it can't exceed it.
+    fn add_subscription(
+        &self,
+        params: SubscriptionParams,
+        prepare: impl FnOnce(SubscriptionId) -> Result<()>,
+    ) -> Result<SubscriptionId> {
+        let kind = params.kind();
+        let refused = |reason: &str| {
+            datapoint_info!(
+                "rpc_pubsub-subscription_refused",
+                "kind" => kind,
+                "reason" => reason,
+                ("count", 1, i64)
+            );
+        };
+        let max_subscriptions = self.config.max_subscriptions_per_connection;
+        if self.current_subscriptions.len() >= max_subscriptions {
+            refused("connection_limit");
+            return Err(
+                RpcCustomError::ConnectionSubscriptionLimitReached { max_subscriptions }.into(),
+            );
+        }
+        let subscribe = || {
+            let token = self.subscription_control.subscribe(params).map_err(|_| {
+                refused("node_limit");
+                Error {
+                    code: ErrorCode::InternalError,
+                    message:
+                        "Internal Error: Subscription refused. Node subscription limit reached"
+                            .into(),
+                    data: None,
+                }
             })?;
-        let id = token.id();
-        self.current_subscriptions.insert(id, token);
-        Ok(id)
+            let id = token.id();
+            prepare(id)?;
+            self.current_subscriptions.insert(id, token);
+            Ok(id)
+        };
+        let Some(ip_quota) = &self.ip_quota else {
+            return subscribe();
+        };
+        let max_subscriptions = self.config.max_subscriptions_per_ip;
+        ip_quota
+            .reserve(max_subscriptions, subscribe)
+            .unwrap_or_else(|| {
+                refused("ip_limit");
+                Err(RpcCustomError::IpSubscriptionLimitReached { max_subscriptions }.into())
+            })
+    }
+
+    fn subscribe(&self, params: SubscriptionParams) -> Result<SubscriptionId> {
+        self.add_subscription(params, |_| Ok(()))
+    }
+
+    /// Like `subscribe`, but first queues the buffered notifications sent to
+    /// this subscription since `from_slot`, failing if any may be missing.
+    fn subscribe_from(
+        &self,
+        params: SubscriptionParams,
+        from_slot: Option<Slot>,
+    ) -> Result<SubscriptionId> {
+        let Some(from_slot) = from_slot else {
+            return self.subscribe(params);
+        };
+        let replay_unavailable = |first_available_slot| {
+            Error::from(RpcCustomError::SubscriptionReplayUnavailable {
+                from_slot,
+                first_available_slot,
+            })
+        };
+        let Some(history) = self.subscription_control.history() else {
+            return Err(replay_unavailable(None));
+        };
+        self.add_subscription(params, |id| {
+            let replayed = history.replay(id, from_slot).map_err(replay_unavailable)?;
+            self.replay_queue.lock().unwrap().extend(replayed);
+            Ok(())
+        })
     }
 
     fn unsubscribe(&self, id: SubscriptionId) -> Result<bool> {
@@ -405,7 +519,7 @@ impl RpcSolPubSubImpl {
     #[cfg(test)]
     pub fn block_until_processed(&self, rpc_subscriptions: &Arc<RpcSubscriptions>) {
         let (rpc, mut receiver) = rpc_pubsub_service::test_connection(rpc_subscriptions);
-        rpc.slot_subscribe().unwrap();
+        rpc.slot_subscribe(None).unwrap();
         rpc_subscriptions.notify_slot(1, 0, 0);
         receiver.recv();
     }
@@ -419,25 +533,36 @@ fn param<T: FromStr>(param_str: &str, thing: &str) -> Result<T> {
     })
 }
 
+fn pubkeys_param(keys: &[String], thing: &str) -> Result<Vec<Pubkey>> {
+    let mut pubkeys = keys
+        .iter()
+        .map(|key| param::<Pubkey>(key, thing))
+        .collect::<Result<Vec<_>>>()?;
+    pubkeys.sort_unstable();
+    pubkeys.dedup();
+    Ok(pubkeys)
+}
+
 impl RpcSolPubSubInternal for RpcSolPubSubImpl {
     fn account_subscribe(
         &self,
         pubkey_str: String,
-        config: Option<RpcAccountInfoConfig>,
+        config: Option<RpcSubscribeReplayConfig<RpcAccountInfoConfig>>,
     ) -> Result<SubscriptionId> {
+        let RpcSubscribeReplayConfig { config, from_slot } = config.unwrap_or_default();
         let RpcAccountInfoConfig {
             encoding,
             data_slice,
             commitment,
             min_context_slot: _, // ignored
-        } = config.unwrap_or_default();
+        } = config;
         let params = AccountSubscriptionParams {
             pubkey: param::<Pubkey>(&pubkey_str, "pubkey")?,
             commitment: commitment.unwrap_or_default(),
             data_slice,
             encoding: encoding.unwrap_or(UiAccountEncoding::Binary),
         };
-        self.subscribe(SubscriptionParams::Account(params))
+        self.subscribe_from(SubscriptionParams::Account(params), from_slot)
     }
 
     fn account_unsubscribe(&self, id: SubscriptionId) -> Result<bool> {
@@ -471,26 +596,35 @@ impl RpcSolPubSubInternal for RpcSolPubSubImpl {
     fn logs_subscribe(
         &self,
         filter: RpcTransactionLogsFilter,
-        config: Option<RpcTransactionLogsConfig>,
+        config: Option<RpcSubscribeReplayConfig<RpcTransactionLogsConfig>>,
     ) -> Result<SubscriptionId> {
+        let from_slot = config.as_ref().and_then(|c| c.from_slot);
         let params = LogsSubscriptionParams {
             kind: match filter {
                 RpcTransactionLogsFilter::All => LogsSubscriptionKind::All,
                 RpcTransactionLogsFilter::AllWithVotes => LogsSubscriptionKind::AllWithVotes,
                 RpcTransactionLogsFilter::Mentions(keys) => {
-                    if keys.len() != 1 {
+                    if keys.is_empty() || keys.len() > self.config.max_logs_mentions_addresses {
                         return Err(Error {
                             code: ErrorCode::InvalidParams,
-                            message: "Invalid Request: Only 1 address supported".into(),
+                            message: format!(
+                                "Invalid Request: Between 1 and {} addresses supported",
+                                self.config.max_logs_mentions_addresses
+                            ),
                             data: None,
                         });
                     }
-                    LogsSubscriptionKind::Single(param::<Pubkey>(&keys[0], "mentions")?)
+                    let mut keys = pubkeys_param(&keys, "mentions")?;
+                    if keys.len() == 1 {
+                        LogsSubscriptionKind::Single(keys.pop().unwrap())
+                    } else {
+                        LogsSubscriptionKind::Mentions(keys)
+                    }
                 }
             },
-            commitment: config.and_then(|c| c.commitment).unwrap_or_default(),
+            commitment: config.and_then(|c| c.config.commitment).unwrap_or_default(),
         };
-        self.subscribe(SubscriptionParams::Logs(params))
+        self.subscribe_from(SubscriptionParams::Logs(params), from_slot)
     }
 
     fn logs_unsubscribe(&self, id: SubscriptionId) -> Result<bool> {
@@ -515,8 +649,9 @@ impl RpcSolPubSubInternal for RpcSolPubSubImpl {
         self.unsubscribe(id)
     }
 
-    fn slot_subscribe(&self) -> Result<SubscriptionId> {
-        self.subscribe(SubscriptionParams::Slot)
+    fn slot_subscribe(&self, config: Option<RpcSlotSubscribeConfig>) -> Result<SubscriptionId> {
+        let from_slot = config.and_then(|c| c.from_slot);
+        self.subscribe_from(SubscriptionParams::Slot, from_slot)
     }
 
     fn slot_unsubscribe(&self, id: SubscriptionId) -> Result<bool> {
@@ -534,12 +669,12 @@ impl RpcSolPubSubInternal for RpcSolPubSubImpl {
     fn block_subscribe(
         &self,
         filter: RpcBlockSubscribeFilter,
-        config: Option<RpcBlockSubscribeConfig>,
+        config: Option<RpcSubscribeReplayConfig<RpcBlockSubscribeConfig>>,
     ) -> Result<SubscriptionId> {
         if !self.config.enable_block_subscription {
             return Err(Error::new(jsonrpc_core::ErrorCode::MethodNotFound));
         }
-        let config = config.unwrap_or_default();
+        let RpcSubscribeReplayConfig { config, from_slot } = config.unwrap_or_default();
         let commitment = config.commitment.unwrap_or_default();
         check_is_at_least_confirmed(commitment)?;
         let params = BlockSubscriptionParams {
@@ -558,7 +693,7 @@ impl RpcSolPubSubInternal for RpcSolPubSubImpl {
             show_rewards: config.show_rewards.unwrap_or_default(),
             max_supported_transaction_version: config.max_supported_transaction_version,
         };
-        self.subscribe(SubscriptionParams::Block(params))
+        self.subscribe_from(SubscriptionParams::Block(params), from_slot)
     }
 
     fn block_unsubscribe(&self, id: SubscriptionId) -> Result<bool> {
@@ -568,6 +703,53 @@ impl RpcSolPubSubInternal for RpcSolPubSubImpl {
         self.unsubscribe(id)
     }
 
+    fn transaction_subscribe(
+        &self,
+        filter: RpcTransactionSubscribeFilter,
+        config: Option<RpcTransactionSubscribeConfig>,
+    ) -> Result<SubscriptionId> {
+        if !self.config.enable_transaction_subscription {
+            return Err(Error::new(jsonrpc_core::ErrorCode::MethodNotFound));
+        }
+        let config = config.unwrap_or_default();
+        let commitment = config.commitment.unwrap_or_default();
+        check_is_at_least_confirmed(commitment)?;
+        let num_addresses = filter.account_include.len()
+            + filter.account_exclude.len()
+            + filter.account_required.len();
+        if num_addresses > self.config.max_transaction_subscription_addresses {
+            return Err(Error {
+                code: ErrorCode::InvalidParams,
+                message: format!(
+                    "Invalid Request: Too many addresses provided; max {}",
+                    self.config.max_transaction_subscription_addresses
+                ),
+                data: None,
+            });
+        }
+        let params = TransactionSubscriptionParams {
+            commitment,
+            encoding: config.encoding.unwrap_or(UiTransactionEncoding::Base64),
+            filter: TransactionSubscriptionFilter {
+                vote: filter.vote,
+                failed: filter.failed,
+                account_include: pubkeys_param(&filter.account_include, "account_include")?,
+                account_exclude: pubkeys_param(&filter.account_exclude, "account_exclude")?,
+                account_required: pubkeys_param(&filter.account_required, "account_required")?,
+            },
+            show_rewards: config.show_rewards.unwrap_or_default(),
+            max_supported_transaction_version: config.max_supported_transaction_version,
+        };
+        self.subscribe(SubscriptionParams::Transaction(params))
+    }
+
+    fn transaction_unsubscribe(&self, id: SubscriptionId) -> Result<bool> {
+        if !self.config.enable_transaction_subscription {
+            return Err(Error::new(jsonrpc_core::ErrorCode::MethodNotFound));
+        }
+        self.unsubscribe(id)
+    }
+
     fn vote_subscribe(&self) -> Result<SubscriptionId> {
         if !self.config.enable_vote_subscription {
             return Err(Error::new(jsonrpc_core::ErrorCode::MethodNotFound));
@@ -611,8 +793,11 @@ mod tests {
         jsonrpc_core::{IoHandler, Response},
         serial_test::serial,
         solana_account_decoder::{parse_account_data::parse_account_data, UiAccountEncoding},
-        solana_rpc_client_api::response::{
-            ProcessedSignatureResult, ReceivedSignatureResult, RpcSignatureResult, SlotInfo,
+        solana_rpc_client_api::{
+            custom_error::JSON_RPC_SERVER_ERROR_CONNECTION_SUBSCRIPTION_LIMIT_REACHED,
+            response::{
+                ProcessedSignatureResult, ReceivedSignatureResult, RpcSignatureResult, SlotInfo,
+            },
         },
         solana_runtime::{
             bank::Bank,
@@ -894,12 +1079,15 @@ mod tests {
 
         rpc.account_subscribe(
             stake_account.pubkey().to_string(),
-            Some(RpcAccountInfoConfig {
-                commitment: Some(CommitmentConfig::processed()),
-                encoding: Some(encoding),
-                data_slice: None,
-                min_context_slot: None,
-            }),
+            Some(
+                RpcAccountInfoConfig {
+                    commitment: Some(CommitmentConfig::processed()),
+                    encoding: Some(encoding),
+                    data_slice: None,
+                    min_context_slot: None,
+                }
+                .into(),
+            ),
         )
         .unwrap();
         rpc.block_until_processed(&rpc_subscriptions);
@@ -1020,12 +1208,15 @@ mod tests {
 
         rpc.account_subscribe(
             nonce_account.pubkey().to_string(),
-            Some(RpcAccountInfoConfig {
-                commitment: Some(CommitmentConfig::processed()),
-                encoding: Some(UiAccountEncoding::JsonParsed),
-                data_slice: None,
-                min_context_slot: None,
-            }),
+            Some(
+                RpcAccountInfoConfig {
+                    commitment: Some(CommitmentConfig::processed()),
+                    encoding: Some(UiAccountEncoding::JsonParsed),
+                    data_slice: None,
+                    min_context_slot: None,
+                }
+                .into(),
+            ),
         )
         .unwrap();
         rpc.block_until_processed(&rpc_subscriptions);
@@ -1155,12 +1346,15 @@ mod tests {
 
         rpc.account_subscribe(
             bob.pubkey().to_string(),
-            Some(RpcAccountInfoConfig {
-                commitment: Some(CommitmentConfig::finalized()),
-                encoding: None,
-                data_slice: None,
-                min_context_slot: None,
-            }),
+            Some(
+                RpcAccountInfoConfig {
+                    commitment: Some(CommitmentConfig::finalized()),
+                    encoding: None,
+                    data_slice: None,
+                    min_context_slot: None,
+                }
+                .into(),
+            ),
         )
         .unwrap();
 
@@ -1210,12 +1404,15 @@ mod tests {
 
         rpc.account_subscribe(
             bob.pubkey().to_string(),
-            Some(RpcAccountInfoConfig {
-                commitment: Some(CommitmentConfig::finalized()),
-                encoding: None,
-                data_slice: None,
-                min_context_slot: None,
-            }),
+            Some(
+                RpcAccountInfoConfig {
+                    commitment: Some(CommitmentConfig::finalized()),
+                    encoding: None,
+                    data_slice: None,
+                    min_context_slot: None,
+                }
+                .into(),
+            ),
         )
         .unwrap();
 
@@ -1279,7 +1476,7 @@ mod tests {
             bank_forks,
         ));
         let (rpc, mut receiver) = rpc_pubsub_service::test_connection(&rpc_subscriptions);
-        rpc.slot_subscribe().unwrap();
+        rpc.slot_subscribe(None).unwrap();
 
         rpc_subscriptions.notify_slot(0, 0, 0);
 
@@ -1298,6 +1495,43 @@ mod tests {
         assert_eq!(expected, response);
     }
 
+    #[test]
+    #[serial]
+    fn test_subscriptions_per_connection_limit() {
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(10_000);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let bank_forks = BankForks::new_rw_arc(bank);
+        let rpc_subscriptions = Arc::new(RpcSubscriptions::default_with_bank_forks(
+            Arc::new(AtomicU64::default()),
+            Arc::new(AtomicU64::default()),
+            bank_forks,
+        ));
+        let rpc = RpcSolPubSubImpl::new(
+            PubSubConfig {
+                max_subscriptions_per_connection: 1,
+                ..PubSubConfig::default()
+            },
+            rpc_subscriptions.control().clone(),
+            Arc::new(DashMap::new()),
+            ReplayQueue::default(),
+            None,
+        );
+        let sub_id = rpc.slot_subscribe(None).unwrap();
+
+        let err = rpc.root_subscribe().unwrap_err();
+        assert_eq!(
+            err.code,
+            ErrorCode::ServerError(JSON_RPC_SERVER_ERROR_CONNECTION_SUBSCRIPTION_LIMIT_REACHED)
+        );
+        rpc_subscriptions
+            .control()
+            .assert_unsubscribed(&SubscriptionParams::Root);
+
+        // Unsubscribing frees up the quota
+        rpc.slot_unsubscribe(sub_id).unwrap();
+        rpc.root_subscribe().unwrap();
+    }
+
     #[test]
     #[serial]
     fn test_slot_unsubscribe() {
@@ -1312,7 +1546,7 @@ mod tests {
             bank_forks,
         ));
         let (rpc, mut receiver) = rpc_pubsub_service::test_connection(&rpc_subscriptions);
-        let sub_id = rpc.slot_subscribe().unwrap();
+        let sub_id = rpc.slot_subscribe(None).unwrap();
 
         rpc_subscriptions.notify_slot(0, 0, 0);
         let response = receiver.recv();
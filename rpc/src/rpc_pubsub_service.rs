@@ -15,19 +15,26 @@ use {
     solana_rayon_threadlimit::get_thread_count,
     solana_sdk::timing::AtomicInterval,
     std::{
+        collections::VecDeque,
         io,
-        net::SocketAddr,
+        net::{IpAddr, SocketAddr},
         num::NonZeroUsize,
         str,
         sync::{
             atomic::{AtomicU64, AtomicUsize, Ordering},
-            Arc,
+            Arc, Mutex, Weak,
         },
         thread::{self, Builder, JoinHandle},
+        time::{Duration, Instant},
     },
     stream_cancel::{Trigger, Tripwire},
     thiserror::Error,
-    tokio::{net::TcpStream, pin, select, sync::broadcast},
+    tokio::{
+        net::TcpStream,
+        pin, select,
+        sync::{broadcast, mpsc},
+        task::JoinHandle as TaskJoinHandle,
+    },
     tokio_util::compat::TokioAsyncReadCompatExt,
 };
 
@@ -36,16 +43,39 @@ pub const DEFAULT_QUEUE_CAPACITY_ITEMS: usize = 10_000_000;
 pub const DEFAULT_TEST_QUEUE_CAPACITY_ITEMS: usize = 100;
 pub const DEFAULT_QUEUE_CAPACITY_BYTES: usize = 256 * 1024 * 1024;
 pub const DEFAULT_WORKER_THREADS: usize = 1;
+pub const MAX_TRANSACTION_SUBSCRIPTION_ADDRESSES: usize = 256;
+pub const MAX_LOGS_MENTIONS_ADDRESSES: usize = 10_000;
+pub const DEFAULT_REPLAY_CAPACITY_BYTES: usize = 64 * 1024 * 1024;
+pub const DEFAULT_REPLAY_LINGER: Duration = Duration::from_secs(30);
+pub const DEFAULT_MAX_SUBSCRIPTIONS_PER_CONNECTION: usize = MAX_ACTIVE_SUBSCRIPTIONS;
+pub const DEFAULT_MAX_SUBSCRIPTIONS_PER_IP: usize = 10_000;
+pub const DEFAULT_MAX_QUEUED_BYTES_PER_CONNECTION: usize = 32 * 1024 * 1024;
+// How long a closing connection may keep writing its already queued messages.
+const WRITER_DRAIN_TIMEOUT: Duration = Duration::from_secs(1);
 
 #[derive(Debug, Clone)]
 pub struct PubSubConfig {
     pub enable_block_subscription: bool,
     pub enable_vote_subscription: bool,
+    pub enable_transaction_subscription: bool,
     pub max_active_subscriptions: usize,
+    pub max_subscriptions_per_connection: usize,
+    pub max_subscriptions_per_ip: usize,
+    /// Max bytes of notifications and responses waiting to be written to a
+    /// single connection; the client is disconnected when it's exceeded
+    pub max_queued_bytes_per_connection: usize,
+    pub max_transaction_subscription_addresses: usize,
+    pub max_logs_mentions_addresses: usize,
     pub queue_capacity_items: usize,
     pub queue_capacity_bytes: usize,
     pub worker_threads: usize,
     pub notification_threads: Option<NonZeroUsize>,
+    /// Max notifications kept for `fromSlot` replay; 0 disables replay
+    pub replay_capacity_items: usize,
+    pub replay_capacity_bytes: usize,
+    /// How long subscriptions of a closed connection stay alive so that a
+    /// reconnecting client can replay what it missed
+    pub replay_linger: Duration,
 }
 
 impl Default for PubSubConfig {
@@ -53,11 +83,20 @@ impl Default for PubSubConfig {
         Self {
             enable_block_subscription: false,
             enable_vote_subscription: false,
+            enable_transaction_subscription: false,
             max_active_subscriptions: MAX_ACTIVE_SUBSCRIPTIONS,
+            max_subscriptions_per_connection: DEFAULT_MAX_SUBSCRIPTIONS_PER_CONNECTION,
+            max_subscriptions_per_ip: DEFAULT_MAX_SUBSCRIPTIONS_PER_IP,
+            max_queued_bytes_per_connection: DEFAULT_MAX_QUEUED_BYTES_PER_CONNECTION,
+            max_transaction_subscription_addresses: MAX_TRANSACTION_SUBSCRIPTION_ADDRESSES,
+            max_logs_mentions_addresses: MAX_LOGS_MENTIONS_ADDRESSES,
             queue_capacity_items: DEFAULT_QUEUE_CAPACITY_ITEMS,
             queue_capacity_bytes: DEFAULT_QUEUE_CAPACITY_BYTES,
             worker_threads: DEFAULT_WORKER_THREADS,
             notification_threads: NonZeroUsize::new(get_thread_count()),
+            replay_capacity_items: 0,
+            replay_capacity_bytes: DEFAULT_REPLAY_CAPACITY_BYTES,
+            replay_linger: DEFAULT_REPLAY_LINGER,
         }
     }
 }
@@ -67,11 +106,20 @@ impl PubSubConfig {
         Self {
             enable_block_subscription: false,
             enable_vote_subscription: false,
+            enable_transaction_subscription: false,
             max_active_subscriptions: MAX_ACTIVE_SUBSCRIPTIONS,
+            max_subscriptions_per_connection: DEFAULT_MAX_SUBSCRIPTIONS_PER_CONNECTION,
+            max_subscriptions_per_ip: DEFAULT_MAX_SUBSCRIPTIONS_PER_IP,
+            max_queued_bytes_per_connection: DEFAULT_MAX_QUEUED_BYTES_PER_CONNECTION,
+            max_transaction_subscription_addresses: MAX_TRANSACTION_SUBSCRIPTION_ADDRESSES,
+            max_logs_mentions_addresses: MAX_LOGS_MENTIONS_ADDRESSES,
             queue_capacity_items: DEFAULT_TEST_QUEUE_CAPACITY_ITEMS,
             queue_capacity_bytes: DEFAULT_QUEUE_CAPACITY_BYTES,
             worker_threads: DEFAULT_WORKER_THREADS,
             notification_threads: NonZeroUsize::new(2),
+            replay_capacity_items: 0,
+            replay_capacity_bytes: DEFAULT_REPLAY_CAPACITY_BYTES,
+            replay_linger: DEFAULT_REPLAY_LINGER,
         }
     }
 }
@@ -137,6 +185,7 @@ struct SentNotificationStats {
     num_root: AtomicUsize,
     num_vote: AtomicUsize,
     num_block: AtomicUsize,
+    num_transaction: AtomicUsize,
     total_creation_to_queue_time_us: AtomicU64,
     last_report: AtomicInterval,
 }
@@ -191,6 +240,11 @@ impl SentNotificationStats {
                     self.num_block.swap(0, Ordering::Relaxed) as i64,
                     i64
                 ),
+                (
+                    "num_transaction",
+                    self.num_transaction.swap(0, Ordering::Relaxed) as i64,
+                    i64
+                ),
                 (
                     "total_creation_to_queue_time_us",
                     self.total_creation_to_queue_time_us
@@ -240,6 +294,9 @@ fn increment_sent_notification_stats(
         SubscriptionParams::Block(_) => {
             stats.num_block.fetch_add(1, Ordering::Relaxed);
         }
+        SubscriptionParams::Transaction(_) => {
+            stats.num_transaction.fetch_add(1, Ordering::Relaxed);
+        }
     }
     stats.total_creation_to_queue_time_us.fetch_add(
         notification.created_at.elapsed().as_micros() as u64,
@@ -283,10 +340,116 @@ impl BroadcastHandler {
     }
 }
 
+/// Replayed notifications waiting to be written to a connection, right after
+/// the response to the subscribe request that produced them.
+pub type ReplayQueue = Arc<Mutex<VecDeque<Arc<String>>>>;
+
+/// The subscriptions of every open connection, grouped by client IP address.
+#[derive(Default)]
+struct SubscriptionsByIp(DashMap<IpAddr, Vec<Weak<DashMap<SubscriptionId, SubscriptionToken>>>>);
+
+impl SubscriptionsByIp {
+    fn register(
+        &self,
+        ip: IpAddr,
+        current_subscriptions: &Arc<DashMap<SubscriptionId, SubscriptionToken>>,
+    ) {
+        self.0
+            .entry(ip)
+            .or_default()
+            .push(Arc::downgrade(current_subscriptions));
+    }
+
+    fn unregister_closed(&self, ip: IpAddr) {
+        if let Entry::Occupied(mut entry) = self.0.entry(ip) {
+            entry
+                .get_mut()
+                .retain(|subscriptions| subscriptions.strong_count() > 0);
+            if entry.get().is_empty() {
+                entry.remove();
+            }
+        }
+    }
+
+    /// Runs `subscribe` if the connections from `ip` hold fewer than
+    /// `max_subscriptions`, keeping the other connections from `ip` from
+    /// subscribing meanwhile.
+    fn reserve<T>(
+        &self,
+        ip: IpAddr,
+        max_subscriptions: usize,
+        subscribe: impl FnOnce() -> T,
+    ) -> Option<T> {
+        let connections = self.0.entry(ip).or_default();
+        let num_subscriptions: usize = connections
+            .iter()
+            .filter_map(Weak::upgrade)
+            .map(|subscriptions| subscriptions.len())
+            .sum();
+        (num_subscriptions < max_subscriptions).then(subscribe)
+    }
+}
+
+/// Lets a connection's RPC handler limit the subscriptions held by all
+/// connections from the same client IP address.
+#[derive(Clone)]
+pub struct IpSubscriptionQuota {
+    ip: IpAddr,
+    subscriptions_by_ip: Arc<SubscriptionsByIp>,
+}
+
+impl IpSubscriptionQuota {
+    /// Runs `subscribe`, which adds a subscription to the connection, unless
+    /// the connections from the same address already hold `max_subscriptions`.
+    pub fn reserve<T>(&self, max_subscriptions: usize, subscribe: impl FnOnce() -> T) -> Option<T> {
+        self.subscriptions_by_ip
+            .reserve(self.ip, max_subscriptions, subscribe)
+    }
+}
+
+/// Keeps the subscriptions of closed connections alive for a while, so that
+/// their notifications are still recorded for replay.
+struct LingeringSubscriptions {
+    linger: Duration,
+    queue: Mutex<VecDeque<(Instant, Vec<SubscriptionToken>)>>,
+}
+
+impl LingeringSubscriptions {
+    fn new(linger: Duration) -> Self {
+        Self {
+            linger,
+            queue: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    fn park(&self, current_subscriptions: &DashMap<SubscriptionId, SubscriptionToken>) {
+        let tokens: Vec<_> = current_subscriptions
+            .iter()
+            .filter(|entry| entry.value().params().is_replayable())
+            .map(|entry| entry.value().clone())
+            .collect();
+        if !tokens.is_empty() {
+            self.queue
+                .lock()
+                .unwrap()
+                .push_back((Instant::now() + self.linger, tokens));
+        }
+    }
+
+    fn prune(&self) {
+        let now = Instant::now();
+        let mut queue = self.queue.lock().unwrap();
+        while queue.front().map_or(false, |(expiry, _)| *expiry <= now) {
+            queue.pop_front();
+        }
+    }
+}
+
 #[cfg(test)]
 pub struct TestBroadcastReceiver {
     handler: BroadcastHandler,
     inner: tokio::sync::broadcast::Receiver<RpcNotification>,
+    replay_queue: ReplayQueue,
 }
 
 #[cfg(test)]
@@ -303,6 +466,9 @@ impl TestBroadcastReceiver {
 
         let started = std::time::Instant::now();
 
+        if let Some(json) = self.replay_queue.lock().unwrap().pop_front() {
+            return Ok(json.to_string());
+        }
         loop {
             match self.inner.try_recv() {
                 Ok(notification) => {
@@ -331,21 +497,26 @@ pub fn test_connection(
     subscriptions: &Arc<RpcSubscriptions>,
 ) -> (RpcSolPubSubImpl, TestBroadcastReceiver) {
     let current_subscriptions = Arc::new(DashMap::new());
+    let replay_queue = ReplayQueue::default();
 
     let rpc_impl = RpcSolPubSubImpl::new(
         PubSubConfig {
             enable_block_subscription: true,
             enable_vote_subscription: true,
+            enable_transaction_subscription: true,
             queue_capacity_items: 100,
             ..PubSubConfig::default()
         },
         subscriptions.control().clone(),
         Arc::clone(&current_subscriptions),
+        Arc::clone(&replay_queue),
+        None,
     );
     let broadcast_handler = BroadcastHandler::new(current_subscriptions);
     let receiver = TestBroadcastReceiver {
         inner: subscriptions.control().broadcast_receiver(),
         handler: broadcast_handler,
+        replay_queue,
     };
     (rpc_impl, receiver)
 }
@@ -360,12 +531,50 @@ enum Error {
     Broadcast(#[from] broadcast::error::RecvError),
     #[error("client has lagged behind (notification is gone)")]
     NotificationIsGone,
+    #[error("client has exceeded the queued bytes limit")]
+    QueuedBytesLimitReached,
+    #[error("connection writer has stopped")]
+    WriterStopped,
+}
+
+/// Messages waiting to be written to a connection by its writer task.
+struct OutgoingQueue {
+    sender: mpsc::UnboundedSender<Arc<String>>,
+    queued_bytes: Arc<AtomicUsize>,
+    max_queued_bytes: usize,
+}
+
+impl OutgoingQueue {
+    fn push(&self, json: Arc<String>) -> Result<(), Error> {
+        let queued_bytes = self
+            .queued_bytes
+            .fetch_add(json.len(), Ordering::Relaxed)
+            .saturating_add(json.len());
+        if queued_bytes > self.max_queued_bytes {
+            inc_new_counter_info!("rpc-pubsub-queued-bytes-limit-reached", 1);
+            return Err(Error::QueuedBytesLimitReached);
+        }
+        self.sender.send(json).map_err(|_| Error::WriterStopped)
+    }
+}
+
+/// Aborts the connection writer task once the connection handler returns, so
+/// a client that stopped reading can't keep its socket alive past
+/// `WRITER_DRAIN_TIMEOUT`.
+struct AbortOnDrop(TaskJoinHandle<Result<(), Error>>);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
 }
 
 async fn handle_connection(
     socket: TcpStream,
     subscription_control: SubscriptionControl,
     config: PubSubConfig,
+    current_subscriptions: Arc<DashMap<SubscriptionId, SubscriptionToken>>,
+    ip_quota: IpSubscriptionQuota,
     mut tripwire: Tripwire,
 ) -> Result<(), Error> {
     let mut server = Server::new(socket.compat());
@@ -377,60 +586,100 @@ async fn handle_connection(
     server.send_response(&accept).await?;
     let (mut sender, mut receiver) = server.into_builder().finish();
 
+    let (outgoing_sender, mut outgoing_receiver) = mpsc::unbounded_channel::<Arc<String>>();
+    let outgoing = OutgoingQueue {
+        sender: outgoing_sender,
+        queued_bytes: Arc::new(AtomicUsize::new(0)),
+        max_queued_bytes: config.max_queued_bytes_per_connection,
+    };
+    let queued_bytes = Arc::clone(&outgoing.queued_bytes);
+    let mut writer = AbortOnDrop(tokio::spawn(async move {
+        while let Some(json) = outgoing_receiver.recv().await {
+            sender.send_text(&*json).await?;
+            queued_bytes.fetch_sub(json.len(), Ordering::Relaxed);
+        }
+        Ok(())
+    }));
+
     let mut broadcast_receiver = subscription_control.broadcast_receiver();
     let mut data = Vec::new();
-    let current_subscriptions = Arc::new(DashMap::new());
+    let replay_queue = ReplayQueue::default();
 
     let mut json_rpc_handler = IoHandler::new();
     let rpc_impl = RpcSolPubSubImpl::new(
         config,
         subscription_control,
         Arc::clone(&current_subscriptions),
+        Arc::clone(&replay_queue),
+        Some(ip_quota),
     );
     json_rpc_handler.extend_with(rpc_impl.to_delegate());
     let broadcast_handler = BroadcastHandler::new(current_subscriptions);
-    loop {
-        // Extra block for dropping `receive_future`.
-        {
-            // soketto is not cancel safe, so we have to introduce an inner loop to poll
-            // `receive_data` to completion.
-            let receive_future = receiver.receive_data(&mut data);
-            pin!(receive_future);
-            loop {
-                select! {
-                    result = &mut receive_future => match result {
-                        Ok(_) => break,
-                        Err(soketto::connection::Error::Closed) => return Ok(()),
-                        Err(err) => return Err(err.into()),
-                    },
-                    result = broadcast_receiver.recv() => {
-
-                        // In both possible error cases (closed or lagged) we disconnect the client.
-                        if let Some(json) = broadcast_handler.handle(result?)? {
-                            sender.send_text(&*json).await?;
-                        }
-                    },
-                    _ = &mut tripwire => {
-                        warn!("disconnecting websocket client: shutting down");
-                        return Ok(())
-                    },
+    let mut writer_finished = false;
+    let result = async {
+        loop {
+            // Extra block for dropping `receive_future`.
+            {
+                // soketto is not cancel safe, so we have to introduce an inner loop to poll
+                // `receive_data` to completion.
+                let receive_future = receiver.receive_data(&mut data);
+                pin!(receive_future);
+                loop {
+                    select! {
+                        result = &mut receive_future => match result {
+                            Ok(_) => break,
+                            Err(soketto::connection::Error::Closed) => return Ok(()),
+                            Err(err) => return Err(err.into()),
+                        },
+                        result = broadcast_receiver.recv() => {
+
+                            // In both possible error cases (closed or lagged) we disconnect the client.
+                            if let Some(json) = broadcast_handler.handle(result?)? {
+                                outgoing.push(json)?;
+                            }
+                        },
+                        result = &mut writer.0 => {
+                            writer_finished = true;
+                            return match result {
+                                Ok(result) => result,
+                                Err(_) => Err(Error::WriterStopped),
+                            };
+                        },
+                        _ = &mut tripwire => {
+                            warn!("disconnecting websocket client: shutting down");
+                            return Ok(())
+                        },
 
+                    }
                 }
             }
+            let Ok(data_str) = str::from_utf8(&data) else {
+                // Old implementation just closes the connection, so we preserve that behavior
+                // for now. It would be more correct to respond with an error.
+                break;
+            };
+
+            if let Some(response) = json_rpc_handler.handle_request(data_str).await {
+                outgoing.push(Arc::new(response))?;
+            }
+            let replayed = std::mem::take(&mut *replay_queue.lock().unwrap());
+            for json in replayed {
+                outgoing.push(json)?;
+            }
+            data.clear();
         }
-        let Ok(data_str) = str::from_utf8(&data) else {
-            // Old implementation just closes the connection, so we preserve that behavior
-            // for now. It would be more correct to respond with an error.
-            break;
-        };
 
-        if let Some(response) = json_rpc_handler.handle_request(data_str).await {
-            sender.send_text(&response).await?;
-        }
-        data.clear();
+        Ok::<_, Error>(())
     }
+    .await;
 
-    Ok(())
+    // Let the writer flush what is already queued, such as the response to the
+    // last request, unless the client stopped reading.
+    drop(outgoing);
+    if !writer_finished && !matches!(result, Err(Error::QueuedBytesLimitReached)) {
+        let _ = tokio::time::timeout(WRITER_DRAIN_TIMEOUT, &mut writer.0).await;
+    }
+    result
 }
 
 async fn listen(
@@ -441,6 +690,10 @@ async fn listen(
 ) -> io::Result<()> {
     let listener = tokio::net::TcpListener::bind(&listen_address).await?;
     let counter = TokenCounter::new("rpc_pubsub_connections");
+    let lingering = (subscription_control.history().is_some() && !config.replay_linger.is_zero())
+        .then(|| Arc::new(LingeringSubscriptions::new(config.replay_linger)));
+    let subscriptions_by_ip = Arc::new(SubscriptionsByIp::default());
+    let mut prune_interval = tokio::time::interval(Duration::from_secs(1));
     loop {
         select! {
             result = listener.accept() => match result {
@@ -449,20 +702,43 @@ async fn listen(
                     let subscription_control = subscription_control.clone();
                     let config = config.clone();
                     let tripwire = tripwire.clone();
+                    let lingering = lingering.clone();
+                    let subscriptions_by_ip = Arc::clone(&subscriptions_by_ip);
                     let counter_token = counter.create_token();
                     tokio::spawn(async move {
+                        let current_subscriptions = Arc::new(DashMap::new());
+                        subscriptions_by_ip.register(addr.ip(), &current_subscriptions);
+                        let ip_quota = IpSubscriptionQuota {
+                            ip: addr.ip(),
+                            subscriptions_by_ip: Arc::clone(&subscriptions_by_ip),
+                        };
                         let handle = handle_connection(
-                            socket, subscription_control, config, tripwire
+                            socket,
+                            subscription_control,
+                            config,
+                            Arc::clone(&current_subscriptions),
+                            ip_quota,
+                            tripwire,
                         );
                         match handle.await {
                             Ok(()) => debug!("connection closed ({:?})", addr),
                             Err(err) => warn!("connection handler error ({:?}): {}", addr, err),
                         }
+                        if let Some(lingering) = lingering {
+                            lingering.park(&current_subscriptions);
+                        }
+                        drop(current_subscriptions);
+                        subscriptions_by_ip.unregister_closed(addr.ip());
                         drop(counter_token); // Force moving token into the task.
                     });
                 }
                 Err(e) => error!("couldn't accept connection: {:?}", e),
             },
+            _ = prune_interval.tick() => {
+                if let Some(lingering) = &lingering {
+                    lingering.prune();
+                }
+            },
             _ = &mut tripwire => return Ok(()),
         }
     }
@@ -473,6 +749,8 @@ mod tests {
     use {
         super::*,
         crate::optimistically_confirmed_bank_tracker::OptimisticallyConfirmedBank,
+        jsonrpc_core::ErrorCode,
+        solana_rpc_client_api::custom_error::JSON_RPC_SERVER_ERROR_IP_SUBSCRIPTION_LIMIT_REACHED,
         solana_runtime::{
             bank::Bank,
             bank_forks::BankForks,
@@ -512,4 +790,89 @@ mod tests {
         let thread = pubsub_service.thread_hdl.thread();
         assert_eq!(thread.name().unwrap(), "solRpcPubSub");
     }
+
+    #[test]
+    fn test_subscriptions_per_ip_limit() {
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(10_000);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let bank_forks = BankForks::new_rw_arc(bank);
+        let rpc_subscriptions = Arc::new(RpcSubscriptions::default_with_bank_forks(
+            Arc::new(AtomicU64::default()),
+            Arc::new(AtomicU64::default()),
+            bank_forks,
+        ));
+        let config = PubSubConfig {
+            max_subscriptions_per_ip: 2,
+            ..PubSubConfig::default()
+        };
+        let ip = IpAddr::V4(Ipv4Addr::LOCALHOST);
+        let subscriptions_by_ip = Arc::new(SubscriptionsByIp::default());
+        let connect = || {
+            let current_subscriptions = Arc::new(DashMap::new());
+            subscriptions_by_ip.register(ip, &current_subscriptions);
+            RpcSolPubSubImpl::new(
+                config.clone(),
+                rpc_subscriptions.control().clone(),
+                current_subscriptions,
+                ReplayQueue::default(),
+                Some(IpSubscriptionQuota {
+                    ip,
+                    subscriptions_by_ip: Arc::clone(&subscriptions_by_ip),
+                }),
+            )
+        };
+        let rpc_a = connect();
+        let rpc_b = connect();
+        let sub_id = rpc_a.slot_subscribe(None).unwrap();
+        rpc_b.root_subscribe().unwrap();
+
+        // Both connections share the quota of their address
+        let err = rpc_a.slots_updates_subscribe().unwrap_err();
+        assert_eq!(
+            err.code,
+            ErrorCode::ServerError(JSON_RPC_SERVER_ERROR_IP_SUBSCRIPTION_LIMIT_REACHED)
+        );
+        let err = rpc_b.slots_updates_subscribe().unwrap_err();
+        assert_eq!(
+            err.code,
+            ErrorCode::ServerError(JSON_RPC_SERVER_ERROR_IP_SUBSCRIPTION_LIMIT_REACHED)
+        );
+
+        // Unsubscribing on one connection frees up the quota for the other
+        rpc_a.slot_unsubscribe(sub_id).unwrap();
+        rpc_b.slots_updates_subscribe().unwrap();
+
+        // Closed connections don't count
+        drop(rpc_b);
+        subscriptions_by_ip.unregister_closed(ip);
+        rpc_a.slot_subscribe(None).unwrap();
+        rpc_a.root_subscribe().unwrap();
+    }
+
+    #[test]
+    fn test_outgoing_queue_max_queued_bytes() {
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let outgoing = OutgoingQueue {
+            sender,
+            queued_bytes: Arc::new(AtomicUsize::new(0)),
+            max_queued_bytes: 10,
+        };
+        outgoing.push(Arc::new("hello".to_string())).unwrap();
+        outgoing.push(Arc::new("world".to_string())).unwrap();
+        assert!(matches!(
+            outgoing.push(Arc::new("!".to_string())),
+            Err(Error::QueuedBytesLimitReached)
+        ));
+
+        // Written messages no longer count against the limit
+        assert_eq!(*receiver.try_recv().unwrap(), "hello");
+        outgoing.queued_bytes.fetch_sub(11, Ordering::Relaxed);
+        outgoing.push(Arc::new("again".to_string())).unwrap();
+
+        drop(receiver);
+        assert!(matches!(
+            outgoing.push(Arc::new("".to_string())),
+            Err(Error::WriterStopped)
+        ));
+    }
 }
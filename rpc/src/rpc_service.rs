@@ -41,7 +41,7 @@ use {
         native_token::lamports_to_sol,
     },
     solana_send_transaction_service::send_transaction_service::{self, SendTransactionService},
-    solana_storage_bigtable::CredentialType,
+    solana_storage_bigtable::{CredentialType, LocalLedgerStorage, LongTermLedgerStorage},
     std::{
         net::SocketAddr,
         path::{Path, PathBuf},
@@ -401,13 +401,14 @@ impl JsonRpcService {
 
         let exit_bigtable_ledger_upload_service = Arc::new(AtomicBool::new(false));
 
-        let (bigtable_ledger_storage, _bigtable_ledger_upload_service) =
+        let long_term_ledger_storage: Option<(Arc<dyn LongTermLedgerStorage>, bool)> =
             if let Some(RpcBigtableConfig {
                 enable_bigtable_ledger_upload,
                 ref bigtable_instance_name,
                 ref bigtable_app_profile_id,
                 timeout,
                 max_message_size,
+                compression,
             }) = config.rpc_bigtable_config
             {
                 let bigtable_config = solana_storage_bigtable::LedgerStorageConfig {
@@ -417,6 +418,7 @@ impl JsonRpcService {
                     instance_name: bigtable_instance_name.clone(),
                     app_profile_id: bigtable_app_profile_id.clone(),
                     max_message_size,
+                    compression,
                 };
                 runtime
                     .block_on(solana_storage_bigtable::LedgerStorage::new_with_config(
@@ -424,33 +426,55 @@ impl JsonRpcService {
                     ))
                     .map(|bigtable_ledger_storage| {
                         info!("BigTable ledger storage initialized");
-
-                        let bigtable_ledger_upload_service = if enable_bigtable_ledger_upload {
-                            Some(Arc::new(BigTableUploadService::new_with_config(
-                                runtime.clone(),
-                                bigtable_ledger_storage.clone(),
-                                blockstore.clone(),
-                                block_commitment_cache.clone(),
-                                max_complete_transaction_status_slot.clone(),
-                                max_complete_rewards_slot.clone(),
-                                ConfirmedBlockUploadConfig::default(),
-                                exit_bigtable_ledger_upload_service.clone(),
-                            )))
-                        } else {
-                            None
-                        };
-
-                        (
-                            Some(bigtable_ledger_storage),
-                            bigtable_ledger_upload_service,
-                        )
+                        let storage: Arc<dyn LongTermLedgerStorage> =
+                            Arc::new(bigtable_ledger_storage);
+                        (storage, enable_bigtable_ledger_upload)
                     })
-                    .unwrap_or_else(|err| {
+                    .map_err(|err| {
                         error!("Failed to initialize BigTable ledger storage: {:?}", err);
-                        (None, None)
                     })
+                    .ok()
+            } else if let Some(RpcLedgerArchiveConfig {
+                ref ledger_archive_path,
+                enable_ledger_archive_upload,
+            }) = config.rpc_ledger_archive_config
+            {
+                LocalLedgerStorage::open(ledger_archive_path)
+                    .map(|local_ledger_storage| {
+                        info!(
+                            "Ledger archive initialized at {}",
+                            ledger_archive_path.display()
+                        );
+                        let storage: Arc<dyn LongTermLedgerStorage> =
+                            Arc::new(local_ledger_storage);
+                        (storage, enable_ledger_archive_upload)
+                    })
+                    .map_err(|err| {
+                        error!("Failed to initialize ledger archive: {:?}", err);
+                    })
+                    .ok()
             } else {
-                (None, None)
+                None
+            };
+
+        let (bigtable_ledger_storage, _bigtable_ledger_upload_service) =
+            match long_term_ledger_storage {
+                Some((storage, enable_upload)) => {
+                    let upload_service = enable_upload.then(|| {
+                        Arc::new(BigTableUploadService::new_with_config(
+                            runtime.clone(),
+                            storage.clone(),
+                            blockstore.clone(),
+                            block_commitment_cache.clone(),
+                            max_complete_transaction_status_slot.clone(),
+                            max_complete_rewards_slot.clone(),
+                            ConfirmedBlockUploadConfig::default(),
+                            exit_bigtable_ledger_upload_service.clone(),
+                        ))
+                    });
+                    (Some(storage), upload_service)
+                }
+                None => (None, None),
             };
 
         let full_api = config.full_api;
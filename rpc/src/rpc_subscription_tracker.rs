@@ -1,5 +1,7 @@
 use {
-    crate::rpc_subscriptions::{NotificationEntry, RpcNotification, TimestampedNotificationEntry},
+    crate::rpc_subscriptions::{
+        NotificationEntry, NotificationHistory, RpcNotification, TimestampedNotificationEntry,
+    },
     dashmap::{mapref::entry::Entry as DashEntry, DashMap},
     solana_account_decoder::{UiAccountEncoding, UiDataSliceConfig},
     solana_metrics::{CounterToken, TokenCounter},
@@ -49,6 +51,7 @@ pub enum SubscriptionParams {
     Slot,
     SlotsUpdates,
     Root,
+    Transaction(TransactionSubscriptionParams),
     Vote,
 }
 
@@ -63,10 +66,27 @@ impl SubscriptionParams {
             SubscriptionParams::SlotsUpdates => "slotsUpdatesNotification",
             SubscriptionParams::Block(_) => "blockNotification",
             SubscriptionParams::Root => "rootNotification",
+            SubscriptionParams::Transaction(_) => "transactionNotification",
             SubscriptionParams::Vote => "voteNotification",
         }
     }
 
+    /// Short name of the subscription kind, used to tag metrics.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            SubscriptionParams::Account(_) => "account",
+            SubscriptionParams::Block(_) => "block",
+            SubscriptionParams::Logs(_) => "logs",
+            SubscriptionParams::Program(_) => "program",
+            SubscriptionParams::Signature(_) => "signature",
+            SubscriptionParams::Slot => "slot",
+            SubscriptionParams::SlotsUpdates => "slots_updates",
+            SubscriptionParams::Root => "root",
+            SubscriptionParams::Transaction(_) => "transaction",
+            SubscriptionParams::Vote => "vote",
+        }
+    }
+
     fn commitment(&self) -> Option<CommitmentConfig> {
         match self {
             SubscriptionParams::Account(params) => Some(params.commitment),
@@ -74,6 +94,7 @@ impl SubscriptionParams {
             SubscriptionParams::Program(params) => Some(params.commitment),
             SubscriptionParams::Signature(params) => Some(params.commitment),
             SubscriptionParams::Block(params) => Some(params.commitment),
+            SubscriptionParams::Transaction(params) => Some(params.commitment),
             SubscriptionParams::Slot
             | SubscriptionParams::SlotsUpdates
             | SubscriptionParams::Root
@@ -81,6 +102,17 @@ impl SubscriptionParams {
         }
     }
 
+    /// Whether notifications for these params are kept for `fromSlot` replay.
+    pub(crate) fn is_replayable(&self) -> bool {
+        matches!(
+            self,
+            SubscriptionParams::Account(_)
+                | SubscriptionParams::Block(_)
+                | SubscriptionParams::Logs(_)
+                | SubscriptionParams::Slot
+        )
+    }
+
     fn is_commitment_watcher(&self) -> bool {
         let commitment = match self {
             SubscriptionParams::Account(params) => &params.commitment,
@@ -88,6 +120,7 @@ impl SubscriptionParams {
             SubscriptionParams::Logs(params) => &params.commitment,
             SubscriptionParams::Program(params) => &params.commitment,
             SubscriptionParams::Signature(params) => &params.commitment,
+            SubscriptionParams::Transaction(params) => &params.commitment,
             SubscriptionParams::Root
             | SubscriptionParams::Slot
             | SubscriptionParams::SlotsUpdates
@@ -103,6 +136,7 @@ impl SubscriptionParams {
             SubscriptionParams::Logs(params) => &params.commitment,
             SubscriptionParams::Program(params) => &params.commitment,
             SubscriptionParams::Signature(params) => &params.commitment,
+            SubscriptionParams::Transaction(params) => &params.commitment,
             SubscriptionParams::Root
             | SubscriptionParams::Slot
             | SubscriptionParams::SlotsUpdates
@@ -146,6 +180,26 @@ pub enum BlockSubscriptionKind {
     MentionsAccountOrProgram(Pubkey),
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TransactionSubscriptionParams {
+    pub commitment: CommitmentConfig,
+    pub encoding: UiTransactionEncoding,
+    pub filter: TransactionSubscriptionFilter,
+    pub show_rewards: bool,
+    pub max_supported_transaction_version: Option<u8>,
+}
+
+/// Address sets are kept sorted and deduplicated so that equivalent filters
+/// share a single subscription.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct TransactionSubscriptionFilter {
+    pub vote: Option<bool>,
+    pub failed: Option<bool>,
+    pub account_include: Vec<Pubkey>,
+    pub account_exclude: Vec<Pubkey>,
+    pub account_required: Vec<Pubkey>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct LogsSubscriptionParams {
     pub kind: LogsSubscriptionKind,
@@ -157,6 +211,8 @@ pub enum LogsSubscriptionKind {
     All,
     AllWithVotes,
     Single(Pubkey),
+    // Sorted and deduplicated, with more than one address
+    Mentions(Vec<Pubkey>),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -186,7 +242,26 @@ struct SubscriptionControlInner {
     max_active_subscriptions: usize,
     sender: crossbeam_channel::Sender<TimestampedNotificationEntry>,
     broadcast_sender: broadcast::Sender<RpcNotification>,
+    history: Option<Arc<NotificationHistory>>,
     counter: TokenCounter,
+    active_by_kind: DashMap<&'static str, usize>,
+}
+
+impl SubscriptionControlInner {
+    fn update_active_by_kind(&self, params: &SubscriptionParams, created: bool) {
+        let kind = params.kind();
+        let mut count = self.active_by_kind.entry(kind).or_default();
+        if created {
+            *count = count.saturating_add(1);
+        } else {
+            *count = count.saturating_sub(1);
+        }
+        datapoint_info!(
+            "rpc_pubsub_subscriptions_by_kind",
+            "kind" => kind,
+            ("count", *count, i64)
+        );
+    }
 }
 
 impl SubscriptionControl {
@@ -194,6 +269,7 @@ impl SubscriptionControl {
         max_active_subscriptions: usize,
         sender: crossbeam_channel::Sender<TimestampedNotificationEntry>,
         broadcast_sender: broadcast::Sender<RpcNotification>,
+        history: Option<Arc<NotificationHistory>>,
     ) -> Self {
         Self(Arc::new(SubscriptionControlInner {
             subscriptions: DashMap::new(),
@@ -201,7 +277,9 @@ impl SubscriptionControl {
             max_active_subscriptions,
             sender,
             broadcast_sender,
+            history,
             counter: TokenCounter::new("rpc_pubsub_total_subscriptions"),
+            active_by_kind: DashMap::new(),
         }))
     }
 
@@ -248,6 +326,12 @@ impl SubscriptionControl {
                 }
                 let id = SubscriptionId::from(self.0.next_id.fetch_add(1, Ordering::AcqRel));
                 let (token, weak_ref) = create_token_and_weak_ref(id, entry.key().clone());
+                // Record before handing out the id, so that it can be replayed from right away
+                if let Some(history) = &self.0.history {
+                    if token.0.params.is_replayable() {
+                        history.start_recording(id, token.0.params.commitment());
+                    }
+                }
                 let _ = self
                     .0
                     .sender
@@ -257,6 +341,7 @@ impl SubscriptionControl {
                     "rpc-subscription",
                     ("total", self.0.subscriptions.len(), i64)
                 );
+                self.0.update_active_by_kind(&token.0.params, true);
                 Ok(token)
             }
         }
@@ -266,6 +351,10 @@ impl SubscriptionControl {
         self.0.subscriptions.len()
     }
 
+    pub fn history(&self) -> Option<&NotificationHistory> {
+        self.0.history.as_deref()
+    }
+
     #[cfg(test)]
     pub fn assert_subscribed(&self, params: &SubscriptionParams) {
         assert!(self.0.subscriptions.contains_key(params));
@@ -291,11 +380,16 @@ impl SubscriptionControl {
     pub fn logs_subscribed(&self, pubkey: Option<&Pubkey>) -> bool {
         self.0.subscriptions.iter().any(|item| {
             if let SubscriptionParams::Logs(params) = item.key() {
-                let subscribed_pubkey = match &params.kind {
-                    LogsSubscriptionKind::All | LogsSubscriptionKind::AllWithVotes => None,
-                    LogsSubscriptionKind::Single(pubkey) => Some(pubkey),
-                };
-                subscribed_pubkey == pubkey
+                match (&params.kind, pubkey) {
+                    (LogsSubscriptionKind::All | LogsSubscriptionKind::AllWithVotes, None) => true,
+                    (LogsSubscriptionKind::Single(subscribed_pubkey), Some(pubkey)) => {
+                        subscribed_pubkey == pubkey
+                    }
+                    (LogsSubscriptionKind::Mentions(subscribed_pubkeys), Some(pubkey)) => {
+                        subscribed_pubkeys.contains(pubkey)
+                    }
+                    _ => false,
+                }
             } else {
                 false
             }
@@ -350,42 +444,55 @@ pub enum Error {
 struct LogsSubscriptionsIndex {
     all_count: usize,
     all_with_votes_count: usize,
-    single_count: HashMap<Pubkey, usize>,
+    // Number of `Single` and `Mentions` subscriptions referencing each address
+    mentioned_count: HashMap<Pubkey, usize>,
 
     bank_forks: Arc<RwLock<BankForks>>,
 }
 
 impl LogsSubscriptionsIndex {
     fn add(&mut self, params: &LogsSubscriptionParams) {
-        match params.kind {
+        match &params.kind {
             LogsSubscriptionKind::All => self.all_count += 1,
             LogsSubscriptionKind::AllWithVotes => self.all_with_votes_count += 1,
-            LogsSubscriptionKind::Single(key) => {
-                *self.single_count.entry(key).or_default() += 1;
+            LogsSubscriptionKind::Single(key) => self.add_mentioned(*key),
+            LogsSubscriptionKind::Mentions(keys) => {
+                keys.iter().for_each(|key| self.add_mentioned(*key));
             }
         }
         self.update_config();
     }
 
     fn remove(&mut self, params: &LogsSubscriptionParams) {
-        match params.kind {
+        match &params.kind {
             LogsSubscriptionKind::All => self.all_count -= 1,
             LogsSubscriptionKind::AllWithVotes => self.all_with_votes_count -= 1,
-            LogsSubscriptionKind::Single(key) => match self.single_count.entry(key) {
-                Entry::Occupied(mut entry) => {
-                    *entry.get_mut() -= 1;
-                    if *entry.get() == 0 {
-                        entry.remove();
-                    }
-                }
-                Entry::Vacant(_) => error!("missing entry in single_count"),
-            },
+            LogsSubscriptionKind::Single(key) => self.remove_mentioned(*key),
+            LogsSubscriptionKind::Mentions(keys) => {
+                keys.iter().for_each(|key| self.remove_mentioned(*key));
+            }
         }
         self.update_config();
     }
 
+    fn add_mentioned(&mut self, key: Pubkey) {
+        *self.mentioned_count.entry(key).or_default() += 1;
+    }
+
+    fn remove_mentioned(&mut self, key: Pubkey) {
+        match self.mentioned_count.entry(key) {
+            Entry::Occupied(mut entry) => {
+                *entry.get_mut() -= 1;
+                if *entry.get() == 0 {
+                    entry.remove();
+                }
+            }
+            Entry::Vacant(_) => error!("missing entry in mentioned_count"),
+        }
+    }
+
     fn update_config(&self) {
-        let mentioned_addresses = self.single_count.keys().copied().collect();
+        let mentioned_addresses = self.mentioned_count.keys().copied().collect();
         let config = if self.all_with_votes_count > 0 {
             TransactionLogCollectorConfig {
                 filter: TransactionLogCollectorFilter::AllWithVotes,
@@ -431,7 +538,7 @@ impl SubscriptionsTracker {
             logs_subscriptions_index: LogsSubscriptionsIndex {
                 all_count: 0,
                 all_with_votes_count: 0,
-                single_count: HashMap::new(),
+                mentioned_count: HashMap::new(),
                 bank_forks,
             },
             by_signature: HashMap::new(),
@@ -558,6 +665,9 @@ impl Drop for SubscriptionTokenInner {
             // Check the strong refs count to ensure no other thread recreated this subscription (not token)
             // while we were acquiring the lock.
             DashEntry::Occupied(entry) if entry.get().0.strong_count() == 0 => {
+                if let Some(history) = &self.control.history {
+                    history.stop_recording(self.id);
+                }
                 let _ = self
                     .control
                     .sender
@@ -567,6 +677,7 @@ impl Drop for SubscriptionTokenInner {
                     "rpc-subscription",
                     ("total", self.control.subscriptions.len(), i64)
                 );
+                self.control.update_active_by_kind(&self.params, false);
             }
             // This branch handles the case in which this entry got recreated
             // while we were waiting for the lock (inside the `DashMap::entry` method).
@@ -595,7 +706,7 @@ mod tests {
         crate::rpc_pubsub_service::PubSubConfig,
         solana_ledger::genesis_utils::{create_genesis_config, GenesisConfigInfo},
         solana_runtime::bank::Bank,
-        std::str::FromStr,
+        std::{collections::HashSet, str::FromStr},
     };
 
     struct ControlWrapper {
@@ -612,6 +723,7 @@ mod tests {
                 PubSubConfig::default().max_active_subscriptions,
                 sender,
                 broadcast_sender,
+                None,
             );
             Self { control, receiver }
         }
@@ -733,6 +845,51 @@ mod tests {
         assert_eq!(*info.last_notified_slot.read().unwrap(), 42);
     }
 
+    #[test]
+    fn logs_subscriptions_index() {
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(10_000);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let bank_forks = BankForks::new_rw_arc(bank);
+        let mut tracker = SubscriptionsTracker::new(bank_forks.clone());
+        let mentioned_addresses = || {
+            bank_forks
+                .read()
+                .unwrap()
+                .root_bank()
+                .transaction_log_collector_config
+                .read()
+                .unwrap()
+                .mentioned_addresses
+                .clone()
+        };
+
+        let (alice, bob, carol) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let mut keys = vec![alice, bob, carol];
+        keys.sort_unstable();
+        let single_params = SubscriptionParams::Logs(LogsSubscriptionParams {
+            kind: LogsSubscriptionKind::Single(alice),
+            commitment: CommitmentConfig::processed(),
+        });
+        let mentions_params = SubscriptionParams::Logs(LogsSubscriptionParams {
+            kind: LogsSubscriptionKind::Mentions(keys),
+            commitment: CommitmentConfig::processed(),
+        });
+
+        tracker.subscribe(single_params.clone(), 0.into(), || 0);
+        tracker.subscribe(mentions_params.clone(), 1.into(), || 0);
+        assert_eq!(mentioned_addresses(), HashSet::from([alice, bob, carol]));
+
+        tracker.unsubscribe(mentions_params, 1.into());
+        assert_eq!(mentioned_addresses(), HashSet::from([alice]));
+
+        tracker.unsubscribe(single_params, 0.into());
+        assert!(mentioned_addresses().is_empty());
+    }
+
     #[test]
     fn subscription_indexes() {
         fn counts(tracker: &SubscriptionsTracker) -> (usize, usize, usize, usize) {
@@ -9,7 +9,8 @@ use {
             AccountSubscriptionParams, BlockSubscriptionKind, BlockSubscriptionParams,
             LogsSubscriptionKind, LogsSubscriptionParams, ProgramSubscriptionParams,
             SignatureSubscriptionParams, SubscriptionControl, SubscriptionId, SubscriptionInfo,
-            SubscriptionParams, SubscriptionsTracker,
+            SubscriptionParams, SubscriptionsTracker, TransactionSubscriptionFilter,
+            TransactionSubscriptionParams,
         },
     },
     crossbeam_channel::{Receiver, RecvTimeoutError, SendError, Sender},
@@ -22,7 +23,7 @@ use {
     solana_rpc_client_api::response::{
         ProcessedSignatureResult, ReceivedSignatureResult, Response as RpcResponse, RpcBlockUpdate,
         RpcBlockUpdateError, RpcKeyedAccount, RpcLogsResponse, RpcResponseContext,
-        RpcSignatureResult, RpcVote, SlotInfo, SlotUpdate,
+        RpcSignatureResult, RpcTransactionUpdate, RpcVote, SlotInfo, SlotUpdate,
     },
     solana_runtime::{
         bank::{Bank, TransactionLogInfo},
@@ -32,13 +33,15 @@ use {
     solana_sdk::{
         account::{AccountSharedData, ReadableAccount},
         clock::Slot,
+        commitment_config::CommitmentConfig,
         pubkey::Pubkey,
         signature::Signature,
         timing::timestamp,
-        transaction,
+        transaction, vote,
     },
     solana_transaction_status::{
         BlockEncodingOptions, ConfirmedBlock, EncodeError, VersionedConfirmedBlock,
+        VersionedTransactionWithStatusMeta,
     },
     solana_vote::vote_transaction::VoteTransaction,
     std::{
@@ -62,11 +65,13 @@ fn get_transaction_logs(
     bank: &Bank,
     params: &LogsSubscriptionParams,
 ) -> Option<Vec<TransactionLogInfo>> {
-    let pubkey = match &params.kind {
-        LogsSubscriptionKind::All | LogsSubscriptionKind::AllWithVotes => None,
-        LogsSubscriptionKind::Single(pubkey) => Some(pubkey),
+    let mut logs = match &params.kind {
+        LogsSubscriptionKind::All | LogsSubscriptionKind::AllWithVotes => {
+            bank.get_transaction_logs(None)
+        }
+        LogsSubscriptionKind::Single(pubkey) => bank.get_transaction_logs(Some(pubkey)),
+        LogsSubscriptionKind::Mentions(pubkeys) => bank.get_transaction_logs_for_addresses(pubkeys),
     };
-    let mut logs = bank.get_transaction_logs(pubkey);
     if matches!(params.kind, LogsSubscriptionKind::All) {
         // Filter out votes if the subscriber doesn't want them
         if let Some(logs) = &mut logs {
@@ -160,6 +165,7 @@ where
                     value: result,
                 }),
                 subscription,
+                slot,
                 is_final,
             );
             *w_last_notified_slot = result_slot;
@@ -260,9 +266,172 @@ impl RecentItems {
     }
 }
 
+/// Notifications recently sent to replayable subscriptions, kept so that a
+/// client reconnecting with `fromSlot` can catch up on what it missed.
+///
+/// Each subscription id carries a watermark slot: the history holds every
+/// notification sent for that id with a slot strictly above the watermark.
+/// The watermark starts at the latest slot notified at the subscription's
+/// commitment when recording began, and is raised whenever one of the id's
+/// notifications is evicted.
+pub struct NotificationHistory {
+    inner: Mutex<NotificationHistoryInner>,
+}
+
+struct RecordedSubscription {
+    watermark: Slot,
+    /// Buffered notifications and their slots, oldest first
+    items: VecDeque<(Slot, Arc<String>)>,
+}
+
+/// The latest slot notified at each commitment
+#[derive(Default)]
+struct LatestSlots {
+    /// Of slot notifications, which have no commitment
+    slot: Slot,
+    processed: Slot,
+    confirmed: Slot,
+    finalized: Slot,
+}
+
+impl LatestSlots {
+    fn get(&self, commitment: Option<CommitmentConfig>) -> Slot {
+        match commitment {
+            None => self.slot,
+            Some(commitment) if commitment.is_finalized() => self.finalized,
+            Some(commitment) if commitment.is_confirmed() => self.confirmed,
+            Some(_) => self.processed,
+        }
+    }
+}
+
+struct NotificationHistoryInner {
+    subscriptions: HashMap<SubscriptionId, RecordedSubscription>,
+    /// The subscription of every buffered notification, oldest first
+    queue: VecDeque<SubscriptionId>,
+    latest_slots: LatestSlots,
+    total_bytes: usize,
+    max_len: usize,
+    max_total_bytes: usize,
+}
+
+impl NotificationHistory {
+    pub fn new(max_len: usize, max_total_bytes: usize) -> Self {
+        Self {
+            inner: Mutex::new(NotificationHistoryInner {
+                subscriptions: HashMap::new(),
+                queue: VecDeque::new(),
+                latest_slots: LatestSlots::default(),
+                total_bytes: 0,
+                max_len,
+                max_total_bytes,
+            }),
+        }
+    }
+
+    fn observe_slot(&self, slot: Slot) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.latest_slots.slot = inner.latest_slots.slot.max(slot);
+    }
+
+    fn observe_commitment_slots(&self, commitment_slots: &CommitmentSlots) {
+        let mut inner = self.inner.lock().unwrap();
+        let latest_slots = &mut inner.latest_slots;
+        latest_slots.processed = latest_slots.processed.max(commitment_slots.slot);
+        latest_slots.confirmed = latest_slots
+            .confirmed
+            .max(commitment_slots.highest_confirmed_slot);
+        latest_slots.finalized = latest_slots
+            .finalized
+            .max(commitment_slots.highest_super_majority_root);
+    }
+
+    /// Called when a subscription is created, before its id is handed out,
+    /// so that a replay can be requested for it right away.
+    pub(crate) fn start_recording(
+        &self,
+        subscription_id: SubscriptionId,
+        commitment: Option<CommitmentConfig>,
+    ) {
+        let mut inner = self.inner.lock().unwrap();
+        let watermark = inner.latest_slots.get(commitment);
+        inner
+            .subscriptions
+            .entry(subscription_id)
+            .or_insert(RecordedSubscription {
+                watermark,
+                items: VecDeque::new(),
+            });
+    }
+
+    pub(crate) fn stop_recording(&self, subscription_id: SubscriptionId) {
+        let mut inner = self.inner.lock().unwrap();
+        let Some(recorded) = inner.subscriptions.remove(&subscription_id) else {
+            return;
+        };
+        let removed_bytes: usize = recorded.items.iter().map(|(_, json)| json.len()).sum();
+        inner.total_bytes = inner
+            .total_bytes
+            .checked_sub(removed_bytes)
+            .expect("total bytes underflow");
+        inner.queue.retain(|id| *id != subscription_id);
+    }
+
+    fn push(&self, subscription_id: SubscriptionId, slot: Slot, json: Arc<String>) {
+        let mut inner = self.inner.lock().unwrap();
+        let json_len = json.len();
+        let Some(recorded) = inner.subscriptions.get_mut(&subscription_id) else {
+            return;
+        };
+        recorded.items.push_back((slot, json));
+        inner.queue.push_back(subscription_id);
+        inner.total_bytes = inner
+            .total_bytes
+            .checked_add(json_len)
+            .expect("total bytes overflow");
+
+        while inner.total_bytes > inner.max_total_bytes || inner.queue.len() > inner.max_len {
+            let id = inner.queue.pop_front().expect("can't be empty");
+            let recorded = inner
+                .subscriptions
+                .get_mut(&id)
+                .expect("queued notifications belong to recorded subscriptions");
+            let (slot, json) = recorded.items.pop_front().expect("can't be empty");
+            recorded.watermark = recorded.watermark.max(slot);
+            inner.total_bytes = inner
+                .total_bytes
+                .checked_sub(json.len())
+                .expect("total bytes underflow");
+        }
+    }
+
+    /// Returns the buffered notifications for `subscription_id` at or after
+    /// `from_slot`, oldest first. If the history cannot cover `from_slot`,
+    /// returns the first slot it can replay from instead, or `None` if
+    /// nothing is being recorded for the subscription.
+    pub fn replay(
+        &self,
+        subscription_id: SubscriptionId,
+        from_slot: Slot,
+    ) -> Result<Vec<Arc<String>>, Option<Slot>> {
+        let inner = self.inner.lock().unwrap();
+        let recorded = inner.subscriptions.get(&subscription_id).ok_or(None)?;
+        if from_slot <= recorded.watermark {
+            return Err(Some(recorded.watermark.saturating_add(1)));
+        }
+        Ok(recorded
+            .items
+            .iter()
+            .filter(|(slot, _)| *slot >= from_slot)
+            .map(|(_, json)| Arc::clone(json))
+            .collect())
+    }
+}
+
 struct RpcNotifier {
     sender: broadcast::Sender<RpcNotification>,
     recent_items: Mutex<RecentItems>,
+    history: Option<Arc<NotificationHistory>>,
 }
 
 thread_local! {
@@ -283,7 +452,7 @@ struct Notification<T> {
 }
 
 impl RpcNotifier {
-    fn notify<T>(&self, value: T, subscription: &SubscriptionInfo, is_final: bool)
+    fn notify<T>(&self, value: T, subscription: &SubscriptionInfo, slot: Slot, is_final: bool)
     where
         T: serde::Serialize,
     {
@@ -317,6 +486,11 @@ impl RpcNotifier {
         inc_new_counter_info!("rpc-pubsub-messages", 1);
         inc_new_counter_info!("rpc-pubsub-bytes", buf_arc.len());
 
+        if let Some(history) = &self.history {
+            if subscription.params().is_replayable() {
+                history.push(subscription.id(), slot, Arc::clone(&buf_arc));
+            }
+        }
         self.recent_items.lock().unwrap().push(buf_arc);
     }
 }
@@ -366,6 +540,78 @@ fn filter_block_result_txs(
     }))
 }
 
+fn transaction_matches_filter(
+    tx: &VersionedTransactionWithStatusMeta,
+    filter: &TransactionSubscriptionFilter,
+) -> bool {
+    let account_keys = tx.account_keys();
+    if let Some(vote) = filter.vote {
+        if vote != account_keys.iter().any(vote::program::check_id) {
+            return false;
+        }
+    }
+    if let Some(failed) = filter.failed {
+        if failed != tx.meta.status.is_err() {
+            return false;
+        }
+    }
+    // Address sets are sorted by `transactionSubscribe`, see `TransactionSubscriptionFilter`
+    (filter.account_include.is_empty()
+        || account_keys
+            .iter()
+            .any(|key| filter.account_include.binary_search(key).is_ok()))
+        && !account_keys
+            .iter()
+            .any(|key| filter.account_exclude.binary_search(key).is_ok())
+        && filter
+            .account_required
+            .iter()
+            .all(|required| account_keys.iter().any(|key| key == required))
+}
+
+fn filter_transaction_results(
+    block: VersionedConfirmedBlock,
+    slot: Slot,
+    params: &TransactionSubscriptionParams,
+) -> impl Iterator<Item = RpcTransactionUpdate> + '_ {
+    let block_time = block.block_time;
+    block
+        .transactions
+        .into_iter()
+        .filter(move |tx| transaction_matches_filter(tx, &params.filter))
+        .map(move |tx| {
+            let signature = tx.transaction.signatures[0].to_string();
+            let (transaction, err) = match tx.encode(
+                params.encoding,
+                params.max_supported_transaction_version,
+                params.show_rewards,
+            ) {
+                Ok(transaction) => (Some(transaction), None),
+                Err(EncodeError::UnsupportedTransactionVersion(version)) => (
+                    None,
+                    Some(RpcBlockUpdateError::UnsupportedTransactionVersion(version)),
+                ),
+            };
+            RpcTransactionUpdate {
+                slot,
+                signature: Some(signature),
+                transaction,
+                block_time,
+                err,
+            }
+        })
+}
+
+/// Returns the slots from `last_unnotified_slot` up to and including `slot`
+/// that are on the same fork as `bank`, which must be the bank for `slot`.
+fn unnotified_slots_on_fork(bank: &Bank, last_unnotified_slot: Slot, slot: Slot) -> Vec<Slot> {
+    let mut slots_to_notify: Vec<_> = (last_unnotified_slot..slot).collect();
+    let ancestors = bank.proper_ancestors_set();
+    slots_to_notify.retain(|slot| ancestors.contains(slot));
+    slots_to_notify.push(slot);
+    slots_to_notify
+}
+
 fn filter_account_result(
     result: Option<(AccountSharedData, Slot)>,
     params: &AccountSubscriptionParams,
@@ -623,12 +869,20 @@ impl RpcSubscriptions {
 
         let (broadcast_sender, _) = broadcast::channel(config.queue_capacity_items);
 
+        let history = (config.replay_capacity_items > 0).then(|| {
+            Arc::new(NotificationHistory::new(
+                config.replay_capacity_items,
+                config.replay_capacity_bytes,
+            ))
+        });
+
         let notifier = RpcNotifier {
             sender: broadcast_sender.clone(),
             recent_items: Mutex::new(RecentItems::new(
                 config.queue_capacity_items,
                 config.queue_capacity_bytes,
             )),
+            history: history.clone(),
         };
 
         let t_cleanup = config.notification_threads.map(|notification_threads| {
@@ -666,6 +920,7 @@ impl RpcSubscriptions {
             config.max_active_subscriptions,
             notification_sender.clone(),
             broadcast_sender,
+            history,
         );
 
         Self {
@@ -798,13 +1053,16 @@ impl RpcSubscriptions {
                             subscriptions.unsubscribe(params, id);
                         }
                         NotificationEntry::Slot(slot_info) => {
+                            if let Some(history) = &notifier.history {
+                                history.observe_slot(slot_info.slot);
+                            }
                             if let Some(sub) = subscriptions
                                 .node_progress_watchers()
                                 .get(&SubscriptionParams::Slot)
                             {
                                 debug!("slot notify: {:?}", slot_info);
                                 inc_new_counter_info!("rpc-subscription-notify-slot", 1);
-                                notifier.notify(slot_info, sub, false);
+                                notifier.notify(slot_info, sub, slot_info.slot, false);
                             }
                         }
                         NotificationEntry::SlotUpdate(slot_update) => {
@@ -813,7 +1071,7 @@ impl RpcSubscriptions {
                                 .get(&SubscriptionParams::SlotsUpdates)
                             {
                                 inc_new_counter_info!("rpc-subscription-notify-slots-updates", 1);
-                                notifier.notify(slot_update, sub, false);
+                                notifier.notify(slot_update, sub, slot_update.slot(), false);
                             }
                         }
                         // These notifications are only triggered by votes observed on gossip,
@@ -833,7 +1091,12 @@ impl RpcSubscriptions {
                                 };
                                 debug!("vote notify: {:?}", vote_info);
                                 inc_new_counter_info!("rpc-subscription-notify-vote", 1);
-                                notifier.notify(&rpc_vote, sub, false);
+                                notifier.notify(
+                                    &rpc_vote,
+                                    sub,
+                                    vote_info.last_voted_slot().unwrap_or_default(),
+                                    false,
+                                );
                             }
                         }
                         NotificationEntry::Root(root) => {
@@ -843,10 +1106,13 @@ impl RpcSubscriptions {
                             {
                                 debug!("root notify: {:?}", root);
                                 inc_new_counter_info!("rpc-subscription-notify-root", 1);
-                                notifier.notify(root, sub, false);
+                                notifier.notify(root, sub, root, false);
                             }
                         }
                         NotificationEntry::Bank(commitment_slots) => {
+                            if let Some(history) = &notifier.history {
+                                history.observe_commitment_slots(&commitment_slots);
+                            }
                             const SOURCE: &str = "bank";
                             RpcSubscriptions::notify_watchers(
                                 max_complete_transaction_status_slot.clone(),
@@ -864,6 +1130,9 @@ impl RpcSubscriptions {
                                 highest_confirmed_slot: slot,
                                 ..CommitmentSlots::default()
                             };
+                            if let Some(history) = &notifier.history {
+                                history.observe_commitment_slots(&commitment_slots);
+                            }
                             const SOURCE: &str = "gossip";
                             RpcSubscriptions::notify_watchers(
                                 max_complete_transaction_status_slot.clone(),
@@ -893,6 +1162,7 @@ impl RpcSubscriptions {
                                                         ),
                                                     }),
                                                     subscription,
+                                                    slot,
                                                     false,
                                                 );
                                             }
@@ -947,6 +1217,9 @@ impl RpcSubscriptions {
         let num_signatures_found = AtomicUsize::new(0);
         let num_signatures_notified = AtomicUsize::new(0);
 
+        let num_transactions_found = AtomicUsize::new(0);
+        let num_transactions_notified = AtomicUsize::new(0);
+
         let subscriptions = subscriptions.into_par_iter();
         subscriptions.for_each(|(_id, subscription)| {
             let slot = if let Some(commitment) = subscription.commitment() {
@@ -1006,11 +1279,8 @@ impl RpcSubscriptions {
                             if *w_last_unnotified_slot == 0 {
                                 *w_last_unnotified_slot = slot;
                             }
-                            let mut slots_to_notify: Vec<_> =
-                                (*w_last_unnotified_slot..slot).collect();
-                            let ancestors = bank.proper_ancestors_set();
-                            slots_to_notify.retain(|slot| ancestors.contains(slot));
-                            slots_to_notify.push(slot);
+                            let slots_to_notify =
+                                unnotified_slots_on_fork(&bank, *w_last_unnotified_slot, slot);
                             for s in slots_to_notify {
                                 // To avoid skipping a slot that fails this condition,
                                 // caused by non-deterministic concurrency accesses, we
@@ -1039,6 +1309,7 @@ impl RpcSubscriptions {
                                                     value: block_update,
                                                 }),
                                                 subscription,
+                                                s,
                                                 false,
                                             );
                                             num_blocks_notified.fetch_add(1, Ordering::Relaxed);
@@ -1060,8 +1331,72 @@ impl RpcSubscriptions {
                                                 },
                                             }),
                                             subscription,
+                                            s,
+                                            false,
+                                        );
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+                SubscriptionParams::Transaction(params) => {
+                    num_transactions_found.fetch_add(1, Ordering::Relaxed);
+                    if let Some(slot) = slot {
+                        let bank = bank_forks.read().unwrap().get(slot);
+                        if let Some(bank) = bank {
+                            // Same catch-up scheme as block subscriptions above.
+                            let mut w_last_unnotified_slot =
+                                subscription.last_notified_slot.write().unwrap();
+                            if *w_last_unnotified_slot == 0 {
+                                *w_last_unnotified_slot = slot;
+                            }
+                            let slots_to_notify =
+                                unnotified_slots_on_fork(&bank, *w_last_unnotified_slot, slot);
+                            for s in slots_to_notify {
+                                if s > max_complete_transaction_status_slot.load(Ordering::SeqCst) {
+                                    break;
+                                }
+
+                                match blockstore.get_complete_block(s, false) {
+                                    Ok(block) => {
+                                        for transaction_update in
+                                            filter_transaction_results(block, s, params)
+                                        {
+                                            notifier.notify(
+                                                RpcResponse::from(RpcNotificationResponse {
+                                                    context: RpcNotificationContext { slot: s },
+                                                    value: transaction_update,
+                                                }),
+                                                subscription,
+                                                s,
+                                                false,
+                                            );
+                                            num_transactions_notified
+                                                .fetch_add(1, Ordering::Relaxed);
+                                        }
+                                        *w_last_unnotified_slot = s + 1;
+                                    }
+                                    Err(e) => {
+                                        error!("get_complete_block error: {}", e);
+                                        // we don't advance `w_last_unnotified_slot` so that
+                                        // it'll retry on the next notification trigger
+                                        notifier.notify(
+                                            RpcResponse::from(RpcNotificationResponse {
+                                                context: RpcNotificationContext { slot: s },
+                                                value: RpcTransactionUpdate {
+                                                    slot: s,
+                                                    signature: None,
+                                                    transaction: None,
+                                                    block_time: None,
+                                                    err: Some(RpcBlockUpdateError::BlockStoreError),
+                                                },
+                                            }),
+                                            subscription,
+                                            s,
                                             false,
                                         );
+                                        break;
                                     }
                                 }
                             }
@@ -1138,11 +1473,13 @@ impl RpcSubscriptions {
         let total_notified = num_accounts_notified.load(Ordering::Relaxed)
             + num_logs_notified.load(Ordering::Relaxed)
             + num_programs_notified.load(Ordering::Relaxed)
-            + num_signatures_notified.load(Ordering::Relaxed);
+            + num_signatures_notified.load(Ordering::Relaxed)
+            + num_transactions_notified.load(Ordering::Relaxed);
         let total_ms = total_time.as_ms();
         if total_notified > 0 || total_ms > 10 {
             debug!(
-                "notified({}): accounts: {} / {} logs: {} / {} programs: {} / {} signatures: {} / {}",
+                "notified({}): accounts: {} / {} logs: {} / {} programs: {} / {} signatures: {} / \
+                 {} transactions: {} / {}",
                 source,
                 num_accounts_found.load(Ordering::Relaxed),
                 num_accounts_notified.load(Ordering::Relaxed),
@@ -1152,6 +1489,8 @@ impl RpcSubscriptions {
                 num_programs_notified.load(Ordering::Relaxed),
                 num_signatures_found.load(Ordering::Relaxed),
                 num_signatures_notified.load(Ordering::Relaxed),
+                num_transactions_found.load(Ordering::Relaxed),
+                num_transactions_notified.load(Ordering::Relaxed),
             );
             inc_new_counter_info!("rpc-subscription-notify-bank-or-gossip", total_notified);
             datapoint_info!(
@@ -1197,6 +1536,16 @@ impl RpcSubscriptions {
                     num_signatures_notified.load(Ordering::Relaxed),
                     i64
                 ),
+                (
+                    "num_transaction_subscriptions",
+                    num_transactions_found.load(Ordering::Relaxed),
+                    i64
+                ),
+                (
+                    "num_transactions_notified",
+                    num_transactions_notified.load(Ordering::Relaxed),
+                    i64
+                ),
                 ("notifications_time", total_time.as_us() as i64, i64),
             );
             inc_new_counter_info!(
@@ -1215,6 +1564,10 @@ impl RpcSubscriptions {
                 "rpc-subscription-counter-num_signatures_notified",
                 num_signatures_notified.load(Ordering::Relaxed)
             );
+            inc_new_counter_info!(
+                "rpc-subscription-counter-num_transactions_notified",
+                num_transactions_notified.load(Ordering::Relaxed)
+            );
         }
     }
 
@@ -1249,12 +1602,17 @@ pub(crate) mod tests {
             rpc_pubsub::RpcSolPubSubInternal,
             rpc_pubsub_service,
         },
+        jsonrpc_core::ErrorCode,
         serial_test::serial,
         solana_ledger::get_tmp_ledger_path_auto_delete,
-        solana_rpc_client_api::config::{
-            RpcAccountInfoConfig, RpcBlockSubscribeConfig, RpcBlockSubscribeFilter,
-            RpcProgramAccountsConfig, RpcSignatureSubscribeConfig, RpcTransactionLogsConfig,
-            RpcTransactionLogsFilter,
+        solana_rpc_client_api::{
+            config::{
+                RpcAccountInfoConfig, RpcBlockSubscribeConfig, RpcBlockSubscribeFilter,
+                RpcProgramAccountsConfig, RpcSignatureSubscribeConfig, RpcSlotSubscribeConfig,
+                RpcSubscribeReplayConfig, RpcTransactionLogsConfig, RpcTransactionLogsFilter,
+                RpcTransactionSubscribeConfig, RpcTransactionSubscribeFilter,
+            },
+            custom_error::JSON_RPC_SERVER_ERROR_SUBSCRIPTION_REPLAY_UNAVAILABLE,
         },
         solana_runtime::{
             commitment::BlockCommitment,
@@ -1262,7 +1620,6 @@ pub(crate) mod tests {
             prioritization_fee_cache::PrioritizationFeeCache,
         },
         solana_sdk::{
-            commitment_config::CommitmentConfig,
             message::Message,
             signature::{Keypair, Signer},
             stake, system_instruction, system_program, system_transaction,
@@ -1400,12 +1757,15 @@ pub(crate) mod tests {
             let sub_id = rpc
                 .account_subscribe(
                     pubkey.to_string(),
-                    Some(RpcAccountInfoConfig {
-                        commitment: Some(CommitmentConfig::processed()),
-                        encoding: None,
-                        data_slice: None,
-                        min_context_slot: None,
-                    }),
+                    Some(
+                        RpcAccountInfoConfig {
+                            commitment: Some(CommitmentConfig::processed()),
+                            encoding: None,
+                            data_slice: None,
+                            min_context_slot: None,
+                        }
+                        .into(),
+                    ),
                 )
                 .unwrap();
 
@@ -1496,7 +1856,7 @@ pub(crate) mod tests {
             show_rewards: config.show_rewards.unwrap_or_default(),
             max_supported_transaction_version: config.max_supported_transaction_version,
         };
-        let sub_id = rpc.block_subscribe(filter, Some(config)).unwrap();
+        let sub_id = rpc.block_subscribe(filter, Some(config.into())).unwrap();
 
         subscriptions
             .control
@@ -1618,7 +1978,7 @@ pub(crate) mod tests {
             show_rewards: config.show_rewards.unwrap_or_default(),
             max_supported_transaction_version: config.max_supported_transaction_version,
         };
-        let sub_id = rpc.block_subscribe(filter, Some(config)).unwrap();
+        let sub_id = rpc.block_subscribe(filter, Some(config.into())).unwrap();
 
         subscriptions
             .control
@@ -1689,6 +2049,131 @@ pub(crate) mod tests {
             .assert_unsubscribed(&SubscriptionParams::Block(params));
     }
 
+    #[test]
+    #[serial]
+    fn test_check_confirmed_transaction_subscribe() {
+        let exit = Arc::new(AtomicBool::new(false));
+        let GenesisConfigInfo {
+            genesis_config,
+            mint_keypair,
+            ..
+        } = create_genesis_config(10_000);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let rent_exempt_amount = bank.get_minimum_balance_for_rent_exemption(0);
+        let bank_forks = BankForks::new_rw_arc(bank);
+        let optimistically_confirmed_bank =
+            OptimisticallyConfirmedBank::locked_from_bank_forks_root(&bank_forks);
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Blockstore::open(ledger_path.path()).unwrap();
+        let blockstore = Arc::new(blockstore);
+        let max_complete_transaction_status_slot = Arc::new(AtomicU64::default());
+        let max_complete_rewards_slot = Arc::new(AtomicU64::default());
+        let subscriptions = Arc::new(RpcSubscriptions::new_for_tests_with_blockstore(
+            exit,
+            max_complete_transaction_status_slot,
+            max_complete_rewards_slot,
+            blockstore.clone(),
+            bank_forks.clone(),
+            Arc::new(RwLock::new(BlockCommitmentCache::new_for_tests())),
+            optimistically_confirmed_bank,
+        ));
+        let (rpc, mut receiver) = rpc_pubsub_service::test_connection(&subscriptions);
+        let keypair1 = Keypair::new();
+        let keypair2 = Keypair::new();
+        let keypair3 = Keypair::new();
+        // Both test transactions mention one of these, but only the first one succeeds
+        let filter = RpcTransactionSubscribeFilter {
+            failed: Some(false),
+            account_include: vec![
+                keypair3.pubkey().to_string(),
+                mint_keypair.pubkey().to_string(),
+            ],
+            ..RpcTransactionSubscribeFilter::default()
+        };
+        let config = RpcTransactionSubscribeConfig {
+            commitment: Some(CommitmentConfig::confirmed()),
+            encoding: Some(UiTransactionEncoding::Json),
+            show_rewards: None,
+            max_supported_transaction_version: None,
+        };
+        let mut account_include = vec![keypair3.pubkey(), mint_keypair.pubkey()];
+        account_include.sort_unstable();
+        let params = TransactionSubscriptionParams {
+            commitment: config.commitment.unwrap(),
+            encoding: config.encoding.unwrap(),
+            filter: TransactionSubscriptionFilter {
+                failed: Some(false),
+                account_include,
+                ..TransactionSubscriptionFilter::default()
+            },
+            show_rewards: false,
+            max_supported_transaction_version: None,
+        };
+        let sub_id = rpc.transaction_subscribe(filter, Some(config)).unwrap();
+
+        subscriptions
+            .control
+            .assert_subscribed(&SubscriptionParams::Transaction(params.clone()));
+
+        let bank = bank_forks.read().unwrap().working_bank();
+        let max_complete_transaction_status_slot = Arc::new(AtomicU64::new(blockstore.max_root()));
+        bank.transfer(rent_exempt_amount, &mint_keypair, &keypair2.pubkey())
+            .unwrap();
+        let (entries, signatures) = create_test_transaction_entries(
+            vec![&mint_keypair, &keypair1, &keypair2, &keypair3],
+            bank.clone(),
+        );
+        populate_blockstore_for_tests(
+            entries,
+            bank,
+            blockstore.clone(),
+            max_complete_transaction_status_slot,
+        );
+
+        let slot = 0;
+        subscriptions.notify_gossip_subscribers(slot);
+        let actual_resp = receiver.recv();
+        let actual_resp = serde_json::from_str::<serde_json::Value>(&actual_resp).unwrap();
+
+        let block = blockstore.get_complete_block(slot, false).unwrap();
+        let block_time = block.block_time;
+        let transaction = block
+            .transactions
+            .into_iter()
+            .find(|tx| tx.transaction.signatures[0] == signatures[0])
+            .unwrap()
+            .encode(params.encoding, None, false)
+            .unwrap();
+        let expected_resp = RpcTransactionUpdate {
+            slot,
+            signature: Some(signatures[0].to_string()),
+            transaction: Some(transaction),
+            block_time,
+            err: None,
+        };
+        let expected_resp = json!({
+           "jsonrpc": "2.0",
+           "method": "transactionNotification",
+           "params": {
+               "result": {
+                   "context": { "slot": slot },
+                   "value": expected_resp,
+               },
+               "subscription": 0,
+           }
+        });
+        assert_eq!(expected_resp, actual_resp);
+
+        // the failed transaction is filtered out
+        let should_err = receiver.recv_timeout(Duration::from_millis(300));
+        assert!(should_err.is_err());
+
+        rpc.transaction_unsubscribe(sub_id).unwrap();
+        subscriptions
+            .control
+            .assert_unsubscribed(&SubscriptionParams::Transaction(params));
+    }
+
     #[test]
     #[serial]
     fn test_check_finalized_block_subscribe() {
@@ -1734,7 +2219,7 @@ pub(crate) mod tests {
             show_rewards: config.show_rewards.unwrap_or_default(),
             max_supported_transaction_version: config.max_supported_transaction_version,
         };
-        let sub_id = rpc.block_subscribe(filter, Some(config)).unwrap();
+        let sub_id = rpc.block_subscribe(filter, Some(config.into())).unwrap();
         subscriptions
             .control
             .assert_subscribed(&SubscriptionParams::Block(params.clone()));
@@ -2667,7 +3152,7 @@ pub(crate) mod tests {
             optimistically_confirmed_bank,
         ));
         let (rpc, mut receiver) = rpc_pubsub_service::test_connection(&subscriptions);
-        let sub_id = rpc.slot_subscribe().unwrap();
+        let sub_id = rpc.slot_subscribe(None).unwrap();
 
         subscriptions
             .control
@@ -2694,6 +3179,327 @@ pub(crate) mod tests {
             .assert_unsubscribed(&SubscriptionParams::Slot);
     }
 
+    #[test]
+    #[serial]
+    fn test_slot_subscribe_from_slot() {
+        let exit = Arc::new(AtomicBool::new(false));
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(10_000);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let bank_forks = BankForks::new_rw_arc(bank);
+        let optimistically_confirmed_bank =
+            OptimisticallyConfirmedBank::locked_from_bank_forks_root(&bank_forks);
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Arc::new(Blockstore::open(ledger_path.path()).unwrap());
+        let subscriptions = Arc::new(RpcSubscriptions::new_with_config(
+            exit,
+            Arc::new(AtomicU64::default()),
+            Arc::new(AtomicU64::default()),
+            blockstore,
+            bank_forks,
+            Arc::new(RwLock::new(BlockCommitmentCache::new_for_tests())),
+            optimistically_confirmed_bank,
+            &PubSubConfig {
+                replay_capacity_items: 100,
+                ..PubSubConfig::default_for_tests()
+            },
+            None,
+        ));
+        let (rpc, mut receiver) = rpc_pubsub_service::test_connection(&subscriptions);
+        let sub_id = rpc.slot_subscribe(None).unwrap();
+
+        subscriptions.notify_slot(1, 0, 0);
+        receiver.recv();
+        subscriptions.notify_slot(2, 1, 0);
+        let slot_2_notification = receiver.recv();
+
+        // A second connection resuming from slot 2 gets the buffered notification first
+        let (rpc_resumed, mut receiver_resumed) =
+            rpc_pubsub_service::test_connection(&subscriptions);
+        let resumed_sub_id = rpc_resumed
+            .slot_subscribe(Some(RpcSlotSubscribeConfig { from_slot: Some(2) }))
+            .unwrap();
+        assert_eq!(resumed_sub_id, sub_id);
+        assert_eq!(receiver_resumed.recv(), slot_2_notification);
+
+        // Nothing was recorded before the subscription was created
+        let (rpc_too_old, _receiver_too_old) = rpc_pubsub_service::test_connection(&subscriptions);
+        let err = rpc_too_old
+            .slot_subscribe(Some(RpcSlotSubscribeConfig { from_slot: Some(0) }))
+            .unwrap_err();
+        assert_eq!(
+            err.code,
+            ErrorCode::ServerError(JSON_RPC_SERVER_ERROR_SUBSCRIPTION_REPLAY_UNAVAILABLE)
+        );
+        assert_eq!(err.data, Some(json!({ "firstAvailableSlot": 1 })));
+    }
+
+    fn new_subscriptions_with_history(
+        bank_forks: &Arc<RwLock<BankForks>>,
+        blockstore: Arc<Blockstore>,
+        max_complete_slot: Slot,
+    ) -> Arc<RpcSubscriptions> {
+        Arc::new(RpcSubscriptions::new_with_config(
+            Arc::new(AtomicBool::new(false)),
+            Arc::new(AtomicU64::new(max_complete_slot)),
+            Arc::new(AtomicU64::new(max_complete_slot)),
+            blockstore,
+            bank_forks.clone(),
+            Arc::new(RwLock::new(BlockCommitmentCache::new_for_tests())),
+            OptimisticallyConfirmedBank::locked_from_bank_forks_root(bank_forks),
+            &PubSubConfig {
+                replay_capacity_items: 100,
+                ..PubSubConfig::default_for_tests()
+            },
+            None,
+        ))
+    }
+
+    fn assert_replay_unavailable(err: jsonrpc_core::Error, first_available_slot: Slot) {
+        assert_eq!(
+            err.code,
+            ErrorCode::ServerError(JSON_RPC_SERVER_ERROR_SUBSCRIPTION_REPLAY_UNAVAILABLE)
+        );
+        assert_eq!(
+            err.data,
+            Some(json!({ "firstAvailableSlot": first_available_slot }))
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_account_subscribe_from_slot() {
+        let GenesisConfigInfo {
+            genesis_config,
+            mint_keypair,
+            ..
+        } = create_genesis_config(100);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let blockhash = bank.last_blockhash();
+        let bank_forks = BankForks::new_rw_arc(bank);
+        let bank0 = bank_forks.read().unwrap().get(0).unwrap();
+        let bank1 = Bank::new_from_parent(bank0, &Pubkey::default(), 1);
+        bank_forks.write().unwrap().insert(bank1);
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Arc::new(Blockstore::open(ledger_path.path()).unwrap());
+        let subscriptions = new_subscriptions_with_history(&bank_forks, blockstore, 0);
+        let alice = Keypair::new();
+        let config = |commitment, from_slot| {
+            Some(RpcSubscribeReplayConfig {
+                config: RpcAccountInfoConfig {
+                    commitment: Some(commitment),
+                    ..RpcAccountInfoConfig::default()
+                },
+                from_slot: Some(from_slot),
+            })
+        };
+
+        // A new subscription can be replayed from the next slot at its commitment right away
+        let (rpc, mut receiver) = rpc_pubsub_service::test_connection(&subscriptions);
+        let sub_id = rpc
+            .account_subscribe(
+                alice.pubkey().to_string(),
+                config(CommitmentConfig::processed(), 1),
+            )
+            .unwrap();
+        rpc.block_until_processed(&subscriptions);
+
+        let tx = system_transaction::create_account(
+            &mint_keypair,
+            &alice,
+            blockhash,
+            1,
+            0,
+            &system_program::id(),
+        );
+        bank_forks
+            .read()
+            .unwrap()
+            .get(1)
+            .unwrap()
+            .process_transaction(&tx)
+            .unwrap();
+        subscriptions.notify_subscribers(CommitmentSlots {
+            slot: 1,
+            ..CommitmentSlots::default()
+        });
+        let notification = receiver.recv();
+
+        let (rpc_resumed, mut receiver_resumed) =
+            rpc_pubsub_service::test_connection(&subscriptions);
+        let resumed_sub_id = rpc_resumed
+            .account_subscribe(
+                alice.pubkey().to_string(),
+                config(CommitmentConfig::processed(), 1),
+            )
+            .unwrap();
+        assert_eq!(resumed_sub_id, sub_id);
+        assert_eq!(receiver_resumed.recv(), notification);
+
+        // Slot 1 was processed before this subscription was created
+        let (rpc_other, _receiver_other) = rpc_pubsub_service::test_connection(&subscriptions);
+        let err = rpc_other
+            .account_subscribe(
+                Pubkey::new_unique().to_string(),
+                config(CommitmentConfig::processed(), 1),
+            )
+            .unwrap_err();
+        assert_replay_unavailable(err, 2);
+        // but not confirmed
+        rpc_other
+            .account_subscribe(
+                Pubkey::new_unique().to_string(),
+                config(CommitmentConfig::confirmed(), 1),
+            )
+            .unwrap();
+    }
+
+    #[test]
+    #[serial]
+    fn test_logs_subscribe_from_slot() {
+        let GenesisConfigInfo {
+            genesis_config,
+            mint_keypair,
+            ..
+        } = create_genesis_config(100);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let blockhash = bank.last_blockhash();
+        let bank_forks = BankForks::new_rw_arc(bank);
+        let bank0 = bank_forks.read().unwrap().get(0).unwrap();
+        let bank1 = Bank::new_from_parent(bank0, &Pubkey::default(), 1);
+        bank_forks.write().unwrap().insert(bank1);
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Arc::new(Blockstore::open(ledger_path.path()).unwrap());
+        let subscriptions = new_subscriptions_with_history(&bank_forks, blockstore, 0);
+        let config = |from_slot| {
+            Some(RpcSubscribeReplayConfig {
+                config: RpcTransactionLogsConfig {
+                    commitment: Some(CommitmentConfig::processed()),
+                },
+                from_slot: Some(from_slot),
+            })
+        };
+
+        let (rpc, mut receiver) = rpc_pubsub_service::test_connection(&subscriptions);
+        let sub_id = rpc
+            .logs_subscribe(RpcTransactionLogsFilter::All, config(1))
+            .unwrap();
+        rpc.block_until_processed(&subscriptions);
+
+        let bank1 = bank_forks.read().unwrap().get(1).unwrap();
+        for _ in 0..2 {
+            let tx = system_transaction::create_account(
+                &mint_keypair,
+                &Keypair::new(),
+                blockhash,
+                1,
+                0,
+                &system_program::id(),
+            );
+            assert!(bank1.process_transaction_with_metadata(tx).was_executed());
+        }
+        subscriptions.notify_subscribers(CommitmentSlots {
+            slot: 1,
+            ..CommitmentSlots::default()
+        });
+        let notifications = [receiver.recv(), receiver.recv()];
+
+        // Both notifications of the slot are replayed, in order
+        let (rpc_resumed, mut receiver_resumed) =
+            rpc_pubsub_service::test_connection(&subscriptions);
+        let resumed_sub_id = rpc_resumed
+            .logs_subscribe(RpcTransactionLogsFilter::All, config(1))
+            .unwrap();
+        assert_eq!(resumed_sub_id, sub_id);
+        assert_eq!(
+            [receiver_resumed.recv(), receiver_resumed.recv()],
+            notifications
+        );
+
+        let (rpc_resumed, mut receiver_resumed) =
+            rpc_pubsub_service::test_connection(&subscriptions);
+        rpc_resumed
+            .logs_subscribe(RpcTransactionLogsFilter::All, config(2))
+            .unwrap();
+        assert!(receiver_resumed
+            .recv_timeout(Duration::from_millis(100))
+            .is_err());
+    }
+
+    #[test]
+    #[serial]
+    fn test_block_subscribe_from_slot() {
+        let GenesisConfigInfo {
+            genesis_config,
+            mint_keypair,
+            ..
+        } = create_genesis_config(10_000);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let rent_exempt_amount = bank.get_minimum_balance_for_rent_exemption(0);
+        let bank_forks = BankForks::new_rw_arc(bank);
+        let bank0 = bank_forks.read().unwrap().get(0).unwrap();
+        let bank1 = Bank::new_from_parent(bank0, &Pubkey::default(), 1);
+        let bank1 = bank_forks
+            .write()
+            .unwrap()
+            .insert(bank1)
+            .clone_without_scheduler();
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Arc::new(Blockstore::open(ledger_path.path()).unwrap());
+        let subscriptions = new_subscriptions_with_history(&bank_forks, blockstore.clone(), 1);
+        let config = |show_rewards, from_slot| {
+            Some(RpcSubscribeReplayConfig {
+                config: RpcBlockSubscribeConfig {
+                    commitment: Some(CommitmentConfig::confirmed()),
+                    encoding: Some(UiTransactionEncoding::Json),
+                    transaction_details: Some(TransactionDetails::Signatures),
+                    show_rewards: Some(show_rewards),
+                    max_supported_transaction_version: None,
+                },
+                from_slot: Some(from_slot),
+            })
+        };
+
+        let (rpc, mut receiver) = rpc_pubsub_service::test_connection(&subscriptions);
+        let sub_id = rpc
+            .block_subscribe(RpcBlockSubscribeFilter::All, config(false, 1))
+            .unwrap();
+        rpc.block_until_processed(&subscriptions);
+
+        let keypair1 = Keypair::new();
+        let keypair2 = Keypair::new();
+        let keypair3 = Keypair::new();
+        bank1
+            .transfer(rent_exempt_amount, &mint_keypair, &keypair2.pubkey())
+            .unwrap();
+        populate_blockstore_for_tests(
+            create_test_transaction_entries(
+                vec![&mint_keypair, &keypair1, &keypair2, &keypair3],
+                bank1.clone(),
+            )
+            .0,
+            bank1,
+            blockstore,
+            Arc::new(AtomicU64::default()),
+        );
+        subscriptions.notify_gossip_subscribers(1);
+        let notification = receiver.recv();
+
+        let (rpc_resumed, mut receiver_resumed) =
+            rpc_pubsub_service::test_connection(&subscriptions);
+        let resumed_sub_id = rpc_resumed
+            .block_subscribe(RpcBlockSubscribeFilter::All, config(false, 1))
+            .unwrap();
+        assert_eq!(resumed_sub_id, sub_id);
+        assert_eq!(receiver_resumed.recv(), notification);
+
+        // Slot 1 was confirmed before this subscription was created
+        let (rpc_other, _receiver_other) = rpc_pubsub_service::test_connection(&subscriptions);
+        let err = rpc_other
+            .block_subscribe(RpcBlockSubscribeFilter::All, config(true, 1))
+            .unwrap_err();
+        assert_replay_unavailable(err, 2);
+    }
+
     #[test]
     #[serial]
     fn test_check_root_subscribe() {
@@ -2781,12 +3587,15 @@ pub(crate) mod tests {
         let sub_id0 = rpc0
             .account_subscribe(
                 alice.pubkey().to_string(),
-                Some(RpcAccountInfoConfig {
-                    commitment: Some(CommitmentConfig::confirmed()),
-                    encoding: None,
-                    data_slice: None,
-                    min_context_slot: None,
-                }),
+                Some(
+                    RpcAccountInfoConfig {
+                        commitment: Some(CommitmentConfig::confirmed()),
+                        encoding: None,
+                        data_slice: None,
+                        min_context_slot: None,
+                    }
+                    .into(),
+                ),
             )
             .unwrap();
 
@@ -2877,12 +3686,15 @@ pub(crate) mod tests {
         let sub_id1 = rpc1
             .account_subscribe(
                 alice.pubkey().to_string(),
-                Some(RpcAccountInfoConfig {
-                    commitment: Some(CommitmentConfig::confirmed()),
-                    encoding: None,
-                    data_slice: None,
-                    min_context_slot: None,
-                }),
+                Some(
+                    RpcAccountInfoConfig {
+                        commitment: Some(CommitmentConfig::confirmed()),
+                        encoding: None,
+                        data_slice: None,
+                        min_context_slot: None,
+                    }
+                    .into(),
+                ),
             )
             .unwrap();
         rpc1.block_until_processed(&subscriptions);
@@ -2987,7 +3799,10 @@ pub(crate) mod tests {
 
         let (rpc_all, mut receiver_all) = rpc_pubsub_service::test_connection(&subscriptions);
         let sub_id_for_all = rpc_all
-            .logs_subscribe(RpcTransactionLogsFilter::All, Some(sub_config.clone()))
+            .logs_subscribe(
+                RpcTransactionLogsFilter::All,
+                Some(sub_config.clone().into()),
+            )
             .unwrap();
         assert!(subscriptions.control.logs_subscribed(None));
 
@@ -2995,7 +3810,7 @@ pub(crate) mod tests {
         let sub_id_for_alice = rpc_alice
             .logs_subscribe(
                 RpcTransactionLogsFilter::Mentions(vec![alice.pubkey().to_string()]),
-                Some(sub_config),
+                Some(sub_config.into()),
             )
             .unwrap();
         assert!(subscriptions.control.logs_subscribed(Some(&alice.pubkey())));
@@ -3041,6 +3856,85 @@ pub(crate) mod tests {
         assert!(!subscriptions.control.logs_subscribed(Some(&alice.pubkey())));
     }
 
+    #[test]
+    #[serial]
+    fn test_logs_subscribe_multiple_mentions() {
+        let GenesisConfigInfo {
+            genesis_config,
+            mint_keypair,
+            ..
+        } = create_genesis_config(100);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let blockhash = bank.last_blockhash();
+        let bank_forks = BankForks::new_rw_arc(bank);
+
+        let alice = Keypair::new();
+        let bob = Keypair::new();
+
+        let exit = Arc::new(AtomicBool::new(false));
+        let max_complete_transaction_status_slot = Arc::new(AtomicU64::default());
+        let max_complete_rewards_slot = Arc::new(AtomicU64::default());
+        let optimistically_confirmed_bank =
+            OptimisticallyConfirmedBank::locked_from_bank_forks_root(&bank_forks);
+        let subscriptions = Arc::new(RpcSubscriptions::new_for_tests(
+            exit,
+            max_complete_transaction_status_slot,
+            max_complete_rewards_slot,
+            bank_forks.clone(),
+            Arc::new(RwLock::new(BlockCommitmentCache::new_for_tests())),
+            optimistically_confirmed_bank,
+        ));
+
+        let (rpc, mut receiver) = rpc_pubsub_service::test_connection(&subscriptions);
+        let sub_id = rpc
+            .logs_subscribe(
+                RpcTransactionLogsFilter::Mentions(vec![
+                    bob.pubkey().to_string(),
+                    alice.pubkey().to_string(),
+                    bob.pubkey().to_string(),
+                ]),
+                Some(
+                    RpcTransactionLogsConfig {
+                        commitment: Some(CommitmentConfig::processed()),
+                    }
+                    .into(),
+                ),
+            )
+            .unwrap();
+        assert!(subscriptions.control.logs_subscribed(Some(&alice.pubkey())));
+        assert!(subscriptions.control.logs_subscribed(Some(&bob.pubkey())));
+        rpc.block_until_processed(&subscriptions);
+
+        let tx = system_transaction::create_account(
+            &mint_keypair,
+            &alice,
+            blockhash,
+            1,
+            0,
+            &system_program::id(),
+        );
+        assert!(bank_forks
+            .read()
+            .unwrap()
+            .get(0)
+            .unwrap()
+            .process_transaction_with_metadata(tx.clone())
+            .was_executed());
+
+        subscriptions.notify_subscribers(CommitmentSlots::new_from_slot(0));
+
+        let expected_response = make_logs_result(&tx.signatures[0].to_string(), u64::from(sub_id));
+        let response = receiver.recv();
+        assert_eq!(
+            expected_response,
+            serde_json::from_str::<serde_json::Value>(&response).unwrap(),
+        );
+
+        rpc.logs_unsubscribe(sub_id).unwrap();
+        assert!(!subscriptions.control.logs_subscribed(Some(&alice.pubkey())));
+        assert!(!subscriptions.control.logs_subscribed(Some(&bob.pubkey())));
+    }
+
     #[test]
     fn test_total_subscriptions() {
         let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(100);
@@ -3082,7 +3976,7 @@ pub(crate) mod tests {
         assert_eq!(subscriptions.total(), 4);
 
         let (rpc5, _receiver5) = rpc_pubsub_service::test_connection(&subscriptions);
-        let sub_id5 = rpc5.slot_subscribe().unwrap();
+        let sub_id5 = rpc5.slot_subscribe(None).unwrap();
 
         assert_eq!(subscriptions.total(), 5);
 
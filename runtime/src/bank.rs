@@ -393,6 +393,39 @@ impl TransactionLogCollector {
             }),
         }
     }
+
+    /// Returns the logs of transactions that mentioned any of `addresses`, in
+    /// the order they were collected. `addresses` must be sorted.
+    pub fn get_logs_for_addresses(&self, addresses: &[Pubkey]) -> Option<Vec<TransactionLogInfo>> {
+        // Walk whichever side is smaller: the subscribed addresses or the
+        // addresses actually mentioned in this bank.
+        let mut log_indices: Vec<usize> = if addresses.len() <= self.mentioned_address_map.len() {
+            addresses
+                .iter()
+                .filter_map(|address| self.mentioned_address_map.get(address))
+                .flatten()
+                .copied()
+                .collect()
+        } else {
+            self.mentioned_address_map
+                .iter()
+                .filter(|(address, _)| addresses.binary_search(address).is_ok())
+                .flat_map(|(_, log_indices)| log_indices)
+                .copied()
+                .collect()
+        };
+        if log_indices.is_empty() {
+            return None;
+        }
+        log_indices.sort_unstable();
+        log_indices.dedup();
+        Some(
+            log_indices
+                .into_iter()
+                .filter_map(|i| self.logs.get(i).cloned())
+                .collect(),
+        )
+    }
 }
 
 /// Bank's common fields shared by all supported snapshot versions for deserialization.
@@ -6191,6 +6224,17 @@ impl Bank {
             .get_logs_for_address(address)
     }
 
+    /// See [`TransactionLogCollector::get_logs_for_addresses`]
+    pub fn get_transaction_logs_for_addresses(
+        &self,
+        addresses: &[Pubkey],
+    ) -> Option<Vec<TransactionLogInfo>> {
+        self.transaction_log_collector
+            .read()
+            .unwrap()
+            .get_logs_for_addresses(addresses)
+    }
+
     /// Returns all the accounts stored in this slot
     pub fn get_all_accounts_modified_since_parent(&self) -> Vec<TransactionAccount> {
         self.rc.accounts.load_by_program_slot(self.slot(), None)
@@ -10230,6 +10230,46 @@ fn test_transaction_log_collector_get_logs_for_address() {
     );
 }
 
+#[test]
+fn test_transaction_log_collector_get_logs_for_addresses() {
+    let make_log = |i| TransactionLogInfo {
+        signature: Signature::new_unique(),
+        result: Ok(()),
+        is_vote: false,
+        log_messages: vec![format!("log {i}")],
+    };
+    let logs: Vec<_> = (0..3).map(make_log).collect();
+    let mut addresses: Vec<_> = (0..4).map(|_| Pubkey::new_unique()).collect();
+    addresses.sort_unstable();
+    let mut mentioned_address_map = HashMap::new();
+    mentioned_address_map.insert(addresses[0], vec![0, 2]);
+    mentioned_address_map.insert(addresses[1], vec![1, 2]);
+    mentioned_address_map.insert(addresses[2], vec![2]);
+    let transaction_log_collector = TransactionLogCollector {
+        logs: logs.clone(),
+        mentioned_address_map,
+    };
+
+    // Fewer subscribed addresses than mentioned addresses
+    assert_eq!(
+        transaction_log_collector.get_logs_for_addresses(&addresses[..2]),
+        Some(logs.clone()),
+    );
+    // More subscribed addresses than mentioned addresses
+    assert_eq!(
+        transaction_log_collector.get_logs_for_addresses(&addresses),
+        Some(logs.clone()),
+    );
+    assert_eq!(
+        transaction_log_collector.get_logs_for_addresses(&addresses[1..2]),
+        Some(vec![logs[1].clone(), logs[2].clone()]),
+    );
+    assert_eq!(
+        transaction_log_collector.get_logs_for_addresses(&addresses[3..]),
+        None,
+    );
+}
+
 /// Test processing a good transaction correctly modifies the accounts data size
 #[test]
 fn test_accounts_data_size_with_good_transaction() {
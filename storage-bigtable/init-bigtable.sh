@@ -25,3 +25,11 @@ for table in blocks entries tx tx-by-addr; do
     "${cbt[@]}" setgcpolicy $table x maxage=360d
   )
 done
+
+# Compression dictionaries must outlive the cells compressed with them, so they never expire
+(
+  set -x
+  "${cbt[@]}" createtable dictionaries
+  "${cbt[@]}" createfamily dictionaries x
+  "${cbt[@]}" setgcpolicy dictionaries x maxversions=1
+)
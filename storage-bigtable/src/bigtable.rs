@@ -3,14 +3,17 @@
 use {
     crate::{
         access_token::{AccessToken, Scope},
-        compression::{compress_best, decompress},
+        compression::{
+            compress_cell, decompress, dictionary_id, register_dictionary,
+            unregistered_dictionary_id, CompressionConfig,
+        },
         root_ca_certificate, CredentialType,
     },
     backoff::{future::retry, Error as BackoffError, ExponentialBackoff},
     log::*,
     std::{
         str::FromStr,
-        time::{Duration, Instant},
+        time::{Duration, Instant, SystemTime, UNIX_EPOCH},
     },
     thiserror::Error,
     tonic::{codegen::InterceptedService, transport::ClientTlsConfig, Request, Status},
@@ -35,6 +38,9 @@ mod google {
 }
 use google::bigtable::v2::*;
 
+#[cfg(test)]
+pub(crate) mod fake_bigtable;
+
 pub type RowKey = String;
 pub type RowData = Vec<(CellName, CellValue)>;
 pub type RowDataSlice<'a> = &'a [(CellName, CellValue)];
@@ -112,6 +118,9 @@ impl std::convert::From<tonic::Status> for Error {
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
+
+/// Table of the zstd dictionaries used to compress the cells of the other tables
+pub const DICTIONARIES_TABLE: &str = "dictionaries";
 type InterceptedRequestResult = std::result::Result<Request<()>, Status>;
 
 #[derive(Clone)]
@@ -122,6 +131,7 @@ pub struct BigTableConnection {
     app_profile_id: String,
     timeout: Option<Duration>,
     max_message_size: usize,
+    compression: CompressionConfig,
 }
 
 impl BigTableConnection {
@@ -219,6 +229,7 @@ impl BigTableConnection {
                     app_profile_id: app_profile_id.to_string(),
                     timeout,
                     max_message_size,
+                    compression: CompressionConfig::default(),
                 })
             }
         }
@@ -240,9 +251,16 @@ impl BigTableConnection {
             app_profile_id: app_profile_id.to_string(),
             timeout,
             max_message_size,
+            compression: CompressionConfig::default(),
         })
     }
 
+    /// Set the compression of the cells written by this connection's clients
+    pub fn with_compression(mut self, compression: CompressionConfig) -> Self {
+        self.compression = compression;
+        self
+    }
+
     /// Create a new BigTable client.
     ///
     /// Clients require `&mut self`, due to `Tonic::transport::Channel` limitations, however
@@ -274,6 +292,7 @@ impl BigTableConnection {
             table_prefix: self.table_prefix.clone(),
             app_profile_id: self.app_profile_id.clone(),
             timeout: self.timeout,
+            compression: self.compression,
         }
     }
 
@@ -339,6 +358,7 @@ pub struct BigTable<F: FnMut(Request<()>) -> InterceptedRequestResult> {
     table_prefix: String,
     app_profile_id: String,
     timeout: Option<Duration>,
+    compression: CompressionConfig,
 }
 
 impl<F: FnMut(Request<()>) -> InterceptedRequestResult> BigTable<F> {
@@ -744,6 +764,7 @@ impl<F: FnMut(Request<()>) -> InterceptedRequestResult> BigTable<F> {
         T: serde::de::DeserializeOwned,
     {
         let row_data = self.get_single_row_data(table, key.clone()).await?;
+        self.load_missing_dictionaries([row_data.as_slice()]).await;
         deserialize_bincode_cell_data(&row_data, table, key.to_string())
     }
 
@@ -755,9 +776,10 @@ impl<F: FnMut(Request<()>) -> InterceptedRequestResult> BigTable<F> {
     where
         T: serde::de::DeserializeOwned,
     {
-        Ok(self
-            .get_multi_row_data(table, keys)
-            .await?
+        let rows = self.get_multi_row_data(table, keys).await?;
+        self.load_missing_dictionaries(rows.iter().map(|(_, row_data)| row_data.as_slice()))
+            .await;
+        Ok(rows
             .into_iter()
             .map(|(key, row_data)| {
                 let key_str = key.to_string();
@@ -774,6 +796,7 @@ impl<F: FnMut(Request<()>) -> InterceptedRequestResult> BigTable<F> {
         P: prost::Message + Default,
     {
         let row_data = self.get_single_row_data(table, key.clone()).await?;
+        self.load_missing_dictionaries([row_data.as_slice()]).await;
         deserialize_protobuf_cell_data(&row_data, table, key.to_string())
     }
 
@@ -787,6 +810,7 @@ impl<F: FnMut(Request<()>) -> InterceptedRequestResult> BigTable<F> {
         P: prost::Message + Default,
     {
         let row_data = self.get_single_row_data(table, key.clone()).await?;
+        self.load_missing_dictionaries([row_data.as_slice()]).await;
         deserialize_protobuf_or_bincode_cell_data(&row_data, table, key)
     }
 
@@ -799,20 +823,21 @@ impl<F: FnMut(Request<()>) -> InterceptedRequestResult> BigTable<F> {
         B: serde::de::DeserializeOwned,
         P: prost::Message + Default,
     {
-        Ok(self
+        let rows = self
             .get_multi_row_data(
                 table,
                 row_keys.into_iter().collect::<Vec<RowKey>>().as_slice(),
             )
-            .await?
-            .into_iter()
-            .map(|(key, row_data)| {
-                let key_str = key.to_string();
-                (
-                    key,
-                    deserialize_protobuf_or_bincode_cell_data(&row_data, table, key_str).unwrap(),
-                )
-            }))
+            .await?;
+        self.load_missing_dictionaries(rows.iter().map(|(_, row_data)| row_data.as_slice()))
+            .await;
+        Ok(rows.into_iter().map(|(key, row_data)| {
+            let key_str = key.to_string();
+            (
+                key,
+                deserialize_protobuf_or_bincode_cell_data(&row_data, table, key_str).unwrap(),
+            )
+        }))
     }
 
     pub async fn put_bincode_cells<T>(
@@ -826,7 +851,11 @@ impl<F: FnMut(Request<()>) -> InterceptedRequestResult> BigTable<F> {
         let mut bytes_written = 0;
         let mut new_row_data = vec![];
         for (row_key, data) in cells {
-            let data = compress_best(&bincode::serialize(&data).unwrap())?;
+            let data = compress_cell(
+                self.compression.for_table(table),
+                table,
+                &bincode::serialize(&data).unwrap(),
+            )?;
             bytes_written += data.len();
             new_row_data.push((row_key, vec![("bin".to_string(), data)]));
         }
@@ -848,7 +877,7 @@ impl<F: FnMut(Request<()>) -> InterceptedRequestResult> BigTable<F> {
         for (row_key, data) in cells {
             let mut buf = Vec::with_capacity(data.encoded_len());
             data.encode(&mut buf).unwrap();
-            let data = compress_best(&buf)?;
+            let data = compress_cell(self.compression.for_table(table), table, &buf)?;
             bytes_written += data.len();
             new_row_data.push((row_key, vec![("proto".to_string(), data)]));
         }
@@ -856,6 +885,75 @@ impl<F: FnMut(Request<()>) -> InterceptedRequestResult> BigTable<F> {
         self.put_row_data(table, "x", &new_row_data).await?;
         Ok(bytes_written)
     }
+
+    /// Get all compression dictionaries, as `(table, row key, dictionary)`, oldest first for each
+    /// table
+    pub async fn get_compression_dictionaries(&mut self) -> Result<Vec<(String, RowKey, Vec<u8>)>> {
+        let rows = self
+            .get_row_data(DICTIONARIES_TABLE, None, None, i64::MAX)
+            .await?;
+        Ok(rows
+            .into_iter()
+            .filter_map(|(row_key, row_data)| {
+                let (table, _) = row_key.split_once('/')?;
+                let table = table.to_string();
+                let (_, dictionary) = row_data.into_iter().find(|(name, _)| name == "dict")?;
+                Some((table, row_key, dictionary))
+            })
+            .collect())
+    }
+
+    /// Load and register the compression dictionaries stored in the `dictionaries` table.
+    /// Returns the number of dictionaries loaded.
+    pub async fn load_compression_dictionaries(&mut self) -> Result<usize> {
+        let dictionaries = self.get_compression_dictionaries().await?;
+        for (table, row_key, dictionary) in &dictionaries {
+            let id = register_dictionary(table, row_key, dictionary);
+            debug!("Loaded compression dictionary {:08x} for {}", id, table);
+        }
+        Ok(dictionaries.len())
+    }
+
+    /// Load the compression dictionaries again if any cell of `rows` is compressed with a
+    /// dictionary this process does not know, such as one trained after it started. Cells whose
+    /// dictionary is still unknown fail to decompress.
+    pub async fn load_missing_dictionaries<'a>(
+        &mut self,
+        rows: impl IntoIterator<Item = RowDataSlice<'a>>,
+    ) {
+        let missing_id = rows
+            .into_iter()
+            .flatten()
+            .find_map(|(_, value)| unregistered_dictionary_id(value));
+        let Some(missing_id) = missing_id else {
+            return;
+        };
+        debug!("Loading compression dictionaries for dictionary {missing_id:08x}");
+        if let Err(err) = self.load_compression_dictionaries().await {
+            warn!("Failed to load compression dictionaries: {}", err);
+        }
+    }
+
+    /// Store a compression dictionary for `table`, returning its row key
+    pub async fn put_compression_dictionary(
+        &mut self,
+        table: &str,
+        dictionary: &[u8],
+    ) -> Result<RowKey> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos() as u64;
+        // The dictionary id keeps the row keys of dictionaries stored at once distinct
+        let row_key = format!("{table}/{timestamp:016x}-{:08x}", dictionary_id(dictionary));
+        self.put_row_data(
+            DICTIONARIES_TABLE,
+            "x",
+            &[(&row_key, vec![("dict".to_string(), dictionary.to_vec())])],
+        )
+        .await?;
+        Ok(row_key)
+    }
 }
 
 pub(crate) fn deserialize_protobuf_or_bincode_cell_data<B, P>(
@@ -923,7 +1021,7 @@ where
 mod tests {
     use {
         super::*,
-        crate::StoredConfirmedBlock,
+        crate::{compression::compress_best, StoredConfirmedBlock},
         prost::Message,
         solana_sdk::{
             hash::Hash, message::v0::LoadedAddresses, signature::Keypair, system_transaction,
@@ -0,0 +1,415 @@
+//! An in-process fake of the BigTable gRPC service, for testing `LedgerStorage` without GCP or
+//! the BigTable emulator.
+//!
+//! Only the `ReadRows`, `MutateRows` and `CheckAndMutateRow` calls are served, with the subset of
+//! row filters that `BigTable` uses. Tables are created on first write, and only the latest
+//! version of each cell is kept.
+use {
+    super::google::bigtable::v2::{
+        mutate_rows_response, mutation, read_rows_response, row_filter, row_range,
+        CheckAndMutateRowRequest, CheckAndMutateRowResponse, MutateRowsRequest, MutateRowsResponse,
+        Mutation, ReadRowsRequest, ReadRowsResponse, RowFilter, RowRange, RowSet,
+    },
+    std::{
+        collections::{BTreeMap, HashMap},
+        convert::Infallible,
+        future::{ready, Ready},
+        net::SocketAddr,
+        sync::{Arc, Mutex},
+    },
+    tokio::net::TcpListener,
+    tonic::{
+        body::{empty_body, BoxBody},
+        codec::ProstCodec,
+        codegen::{http, Body, BoxFuture, Context, Poll, Service, StdError},
+        server::{Grpc, NamedService, ServerStreamingService, UnaryService},
+        Request, Response, Status,
+    },
+};
+
+// Cells of a row, keyed by (family name, column qualifier), with their timestamp and value.
+type Row = BTreeMap<(String, Vec<u8>), (i64, Vec<u8>)>;
+type Table = BTreeMap<Vec<u8>, Row>;
+
+struct Cell {
+    family_name: String,
+    qualifier: Vec<u8>,
+    timestamp_micros: i64,
+    value: Vec<u8>,
+}
+
+#[derive(Default)]
+struct Tables {
+    // Keyed by the full table name, including the instance prefix.
+    tables: HashMap<String, Table>,
+    // Assigned to cells written with a server assigned timestamp.
+    timestamp_micros: i64,
+}
+
+#[derive(Clone, Default)]
+pub(crate) struct FakeBigTable {
+    tables: Arc<Mutex<Tables>>,
+}
+
+fn row_cells(row: &Row) -> Vec<Cell> {
+    row.iter()
+        .map(
+            |((family_name, qualifier), (timestamp_micros, value))| Cell {
+                family_name: family_name.clone(),
+                qualifier: qualifier.clone(),
+                timestamp_micros: *timestamp_micros,
+                value: value.clone(),
+            },
+        )
+        .collect()
+}
+
+fn apply_filter(filter: Option<&RowFilter>, mut cells: Vec<Cell>) -> Result<Vec<Cell>, Status> {
+    let Some(filter) = filter.and_then(|filter| filter.filter.as_ref()) else {
+        return Ok(cells);
+    };
+    match filter {
+        row_filter::Filter::Chain(chain) => chain
+            .filters
+            .iter()
+            .try_fold(cells, |cells, filter| apply_filter(Some(filter), cells)),
+        row_filter::Filter::PassAllFilter(true) => Ok(cells),
+        row_filter::Filter::BlockAllFilter(true) => Ok(vec![]),
+        row_filter::Filter::CellsPerRowLimitFilter(limit) => {
+            cells.truncate(*limit as usize);
+            Ok(cells)
+        }
+        // Only the latest version of each cell is kept
+        row_filter::Filter::CellsPerColumnLimitFilter(limit) if *limit >= 1 => Ok(cells),
+        row_filter::Filter::StripValueTransformer(true) => {
+            cells.iter_mut().for_each(|cell| cell.value.clear());
+            Ok(cells)
+        }
+        filter => Err(Status::unimplemented(format!(
+            "unsupported row filter: {filter:?}"
+        ))),
+    }
+}
+
+fn row_range_contains(row_range: &RowRange, row_key: &[u8]) -> bool {
+    let after_start = match &row_range.start_key {
+        None => true,
+        Some(row_range::StartKey::StartKeyClosed(start_key)) => row_key >= start_key.as_slice(),
+        Some(row_range::StartKey::StartKeyOpen(start_key)) => row_key > start_key.as_slice(),
+    };
+    let before_end = match &row_range.end_key {
+        None => true,
+        Some(row_range::EndKey::EndKeyClosed(end_key)) => row_key <= end_key.as_slice(),
+        Some(row_range::EndKey::EndKeyOpen(end_key)) => row_key < end_key.as_slice(),
+    };
+    after_start && before_end
+}
+
+fn row_set_contains(row_set: Option<&RowSet>, row_key: &[u8]) -> bool {
+    match row_set {
+        // An empty row set reads the whole table
+        None => true,
+        Some(RowSet {
+            row_keys,
+            row_ranges,
+        }) if row_keys.is_empty() && row_ranges.is_empty() => true,
+        Some(RowSet {
+            row_keys,
+            row_ranges,
+        }) => {
+            row_keys.iter().any(|key| key == row_key)
+                || row_ranges
+                    .iter()
+                    .any(|row_range| row_range_contains(row_range, row_key))
+        }
+    }
+}
+
+impl Tables {
+    fn mutate_row(
+        &mut self,
+        table_name: &str,
+        row_key: Vec<u8>,
+        mutations: Vec<Mutation>,
+    ) -> Result<(), Status> {
+        self.timestamp_micros += 1;
+        let server_timestamp_micros = self.timestamp_micros;
+        let table = self.tables.entry(table_name.to_string()).or_default();
+        let row = table.entry(row_key.clone()).or_default();
+        for mutation in mutations {
+            match mutation.mutation {
+                Some(mutation::Mutation::SetCell(mutation::SetCell {
+                    family_name,
+                    column_qualifier,
+                    timestamp_micros,
+                    value,
+                })) => {
+                    let timestamp_micros = if timestamp_micros == -1 {
+                        server_timestamp_micros
+                    } else {
+                        timestamp_micros
+                    };
+                    let cell = row
+                        .entry((family_name, column_qualifier))
+                        .or_insert((timestamp_micros, vec![]));
+                    if timestamp_micros >= cell.0 {
+                        *cell = (timestamp_micros, value);
+                    }
+                }
+                Some(mutation::Mutation::DeleteFromColumn(mutation::DeleteFromColumn {
+                    family_name,
+                    column_qualifier,
+                    time_range: None,
+                })) => {
+                    row.remove(&(family_name, column_qualifier));
+                }
+                Some(mutation::Mutation::DeleteFromFamily(mutation::DeleteFromFamily {
+                    family_name,
+                })) => row.retain(|(cell_family_name, _), _| *cell_family_name != family_name),
+                Some(mutation::Mutation::DeleteFromRow(_)) => row.clear(),
+                mutation => {
+                    return Err(Status::unimplemented(format!(
+                        "unsupported mutation: {mutation:?}"
+                    )))
+                }
+            }
+        }
+        if row.is_empty() {
+            table.remove(&row_key);
+        }
+        Ok(())
+    }
+
+    fn read_rows(&self, request: ReadRowsRequest) -> Result<Vec<ReadRowsResponse>, Status> {
+        let ReadRowsRequest {
+            table_name,
+            rows,
+            filter,
+            rows_limit,
+            reversed,
+            ..
+        } = request;
+        if reversed {
+            return Err(Status::unimplemented("reversed scans are not supported"));
+        }
+        let rows_limit = if rows_limit > 0 {
+            rows_limit as usize
+        } else {
+            usize::MAX
+        };
+        let mut responses = vec![];
+        let Some(table) = self.tables.get(&table_name) else {
+            return Ok(responses);
+        };
+        for (row_key, row) in table {
+            if responses.len() >= rows_limit {
+                break;
+            }
+            if !row_set_contains(rows.as_ref(), row_key) {
+                continue;
+            }
+            let cells = apply_filter(filter.as_ref(), row_cells(row))?;
+            let num_cells = cells.len();
+            let chunks = cells
+                .into_iter()
+                .enumerate()
+                .map(|(i, cell)| read_rows_response::CellChunk {
+                    row_key: if i == 0 { row_key.clone() } else { vec![] },
+                    family_name: Some(cell.family_name),
+                    qualifier: Some(cell.qualifier),
+                    timestamp_micros: cell.timestamp_micros,
+                    value: cell.value,
+                    row_status: (i + 1 == num_cells)
+                        .then_some(read_rows_response::cell_chunk::RowStatus::CommitRow(true)),
+                    ..read_rows_response::CellChunk::default()
+                })
+                .collect::<Vec<_>>();
+            // Rows without any cells left after filtering are not returned
+            if !chunks.is_empty() {
+                responses.push(ReadRowsResponse {
+                    chunks,
+                    last_scanned_row_key: row_key.clone(),
+                    ..ReadRowsResponse::default()
+                });
+            }
+        }
+        Ok(responses)
+    }
+}
+
+impl FakeBigTable {
+    /// Serves the fake on a local port until the runtime shuts down, returning its address for
+    /// `LedgerStorage::new_for_emulator()`.
+    pub(crate) async fn serve(&self) -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let incoming = futures::stream::unfold(listener, |listener| async move {
+            let stream = listener.accept().await.map(|(stream, _addr)| stream);
+            Some((stream, listener))
+        });
+        tokio::spawn(
+            tonic::transport::Server::builder()
+                .add_service(self.clone())
+                .serve_with_incoming(Box::pin(incoming)),
+        );
+        addr
+    }
+
+    /// Returns the row keys of the table, in lexical order.
+    pub(crate) fn row_keys(&self, table: &str) -> Vec<String> {
+        let suffix = format!("/tables/{table}");
+        let tables = self.tables.lock().unwrap();
+        tables
+            .tables
+            .iter()
+            .filter(|(table_name, _)| table_name.ends_with(&suffix))
+            .flat_map(|(_, table)| table.keys())
+            .map(|row_key| String::from_utf8(row_key.clone()).unwrap())
+            .collect()
+    }
+
+    fn read_rows(&self, request: ReadRowsRequest) -> Result<Vec<ReadRowsResponse>, Status> {
+        self.tables.lock().unwrap().read_rows(request)
+    }
+
+    fn mutate_rows(&self, request: MutateRowsRequest) -> Result<MutateRowsResponse, Status> {
+        let MutateRowsRequest {
+            table_name,
+            entries,
+            ..
+        } = request;
+        let mut tables = self.tables.lock().unwrap();
+        let mut response = MutateRowsResponse::default();
+        for (index, entry) in entries.into_iter().enumerate() {
+            tables.mutate_row(&table_name, entry.row_key, entry.mutations)?;
+            response.entries.push(mutate_rows_response::Entry {
+                index: index as i64,
+                // The default status has an OK code
+                status: Some(Default::default()),
+            });
+        }
+        Ok(response)
+    }
+
+    fn check_and_mutate_row(
+        &self,
+        request: CheckAndMutateRowRequest,
+    ) -> Result<CheckAndMutateRowResponse, Status> {
+        let CheckAndMutateRowRequest {
+            table_name,
+            row_key,
+            predicate_filter,
+            true_mutations,
+            false_mutations,
+            ..
+        } = request;
+        let mut tables = self.tables.lock().unwrap();
+        let cells = tables
+            .tables
+            .get(&table_name)
+            .and_then(|table| table.get(&row_key))
+            .map(row_cells)
+            .unwrap_or_default();
+        let predicate_matched = !apply_filter(predicate_filter.as_ref(), cells)?.is_empty();
+        let mutations = if predicate_matched {
+            true_mutations
+        } else {
+            false_mutations
+        };
+        tables.mutate_row(&table_name, row_key, mutations)?;
+        Ok(CheckAndMutateRowResponse { predicate_matched })
+    }
+}
+
+struct ReadRowsSvc(FakeBigTable);
+
+impl ServerStreamingService<ReadRowsRequest> for ReadRowsSvc {
+    type Response = ReadRowsResponse;
+    type ResponseStream =
+        futures::stream::Iter<std::vec::IntoIter<Result<ReadRowsResponse, Status>>>;
+    type Future = Ready<Result<Response<Self::ResponseStream>, Status>>;
+
+    fn call(&mut self, request: Request<ReadRowsRequest>) -> Self::Future {
+        ready(self.0.read_rows(request.into_inner()).map(|responses| {
+            Response::new(futures::stream::iter(
+                responses.into_iter().map(Ok).collect::<Vec<_>>(),
+            ))
+        }))
+    }
+}
+
+struct MutateRowsSvc(FakeBigTable);
+
+impl ServerStreamingService<MutateRowsRequest> for MutateRowsSvc {
+    type Response = MutateRowsResponse;
+    type ResponseStream =
+        futures::stream::Iter<std::vec::IntoIter<Result<MutateRowsResponse, Status>>>;
+    type Future = Ready<Result<Response<Self::ResponseStream>, Status>>;
+
+    fn call(&mut self, request: Request<MutateRowsRequest>) -> Self::Future {
+        ready(
+            self.0
+                .mutate_rows(request.into_inner())
+                .map(|response| Response::new(futures::stream::iter(vec![Ok(response)]))),
+        )
+    }
+}
+
+struct CheckAndMutateRowSvc(FakeBigTable);
+
+impl UnaryService<CheckAndMutateRowRequest> for CheckAndMutateRowSvc {
+    type Response = CheckAndMutateRowResponse;
+    type Future = Ready<Result<Response<Self::Response>, Status>>;
+
+    fn call(&mut self, request: Request<CheckAndMutateRowRequest>) -> Self::Future {
+        ready(
+            self.0
+                .check_and_mutate_row(request.into_inner())
+                .map(Response::new),
+        )
+    }
+}
+
+impl<B> Service<http::Request<B>> for FakeBigTable
+where
+    B: Body + Send + 'static,
+    B::Error: Into<StdError> + Send + 'static,
+{
+    type Response = http::Response<BoxBody>;
+    type Error = Infallible;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: http::Request<B>) -> Self::Future {
+        let fake = self.clone();
+        match request.uri().path() {
+            "/google.bigtable.v2.Bigtable/ReadRows" => Box::pin(async move {
+                let mut grpc = Grpc::new(ProstCodec::default());
+                Ok(grpc.server_streaming(ReadRowsSvc(fake), request).await)
+            }),
+            "/google.bigtable.v2.Bigtable/MutateRows" => Box::pin(async move {
+                let mut grpc = Grpc::new(ProstCodec::default());
+                Ok(grpc.server_streaming(MutateRowsSvc(fake), request).await)
+            }),
+            "/google.bigtable.v2.Bigtable/CheckAndMutateRow" => Box::pin(async move {
+                let mut grpc = Grpc::new(ProstCodec::default());
+                Ok(grpc.unary(CheckAndMutateRowSvc(fake), request).await)
+            }),
+            _ => Box::pin(async move {
+                Ok(http::Response::builder()
+                    .status(200)
+                    .header("grpc-status", "12") // Unimplemented
+                    .header("content-type", "application/grpc")
+                    .body(empty_body())
+                    .unwrap())
+            }),
+        }
+    }
+}
+
+impl NamedService for FakeBigTable {
+    const NAME: &'static str = "google.bigtable.v2.Bigtable";
+}
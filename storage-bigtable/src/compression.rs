@@ -1,25 +1,184 @@
 use {
     enum_iterator::{all, Sequence},
-    std::io::{self, BufReader, Read, Write},
+    solana_sdk::hash::hash,
+    std::{
+        collections::BTreeMap,
+        io::{self, BufReader, Read, Write},
+        str::FromStr,
+        sync::{Arc, RwLock},
+    },
+    zstd::dict::EncoderDictionary,
 };
 
+// New methods must be appended, as the variant index prefixes each compressed cell.
 #[derive(Debug, Serialize, Deserialize, Sequence)]
 pub enum CompressionMethod {
     NoCompression,
     Bzip2,
     Gzip,
     Zstd,
+    /// Zstd with a trained dictionary, whose id follows the method in the cell
+    ZstdDictionary,
+}
+
+/// How cells of a table are compressed on upload
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CellCompression {
+    /// Compress with every method and keep the smallest result
+    #[default]
+    Best,
+    NoCompression,
+    Bzip2,
+    Gzip,
+    Zstd,
+    /// Zstd with the most recently trained dictionary of the table, or plain Zstd if the table
+    /// has no dictionary
+    ZstdDictionary,
+}
+
+impl FromStr for CellCompression {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "best" => Ok(Self::Best),
+            "none" => Ok(Self::NoCompression),
+            "bzip2" => Ok(Self::Bzip2),
+            "gzip" => Ok(Self::Gzip),
+            "zstd" => Ok(Self::Zstd),
+            "zstd-dictionary" => Ok(Self::ZstdDictionary),
+            _ => Err(format!("unknown compression method: {s}")),
+        }
+    }
+}
+
+/// The compression of each table on upload
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CompressionConfig {
+    pub blocks: CellCompression,
+    pub entries: CellCompression,
+    pub tx: CellCompression,
+    pub tx_by_addr: CellCompression,
+}
+
+impl CompressionConfig {
+    pub const TABLES: [&'static str; 4] = ["blocks", "entries", "tx", "tx-by-addr"];
+
+    pub fn for_table(&self, table: &str) -> CellCompression {
+        match table {
+            "blocks" => self.blocks,
+            "entries" => self.entries,
+            "tx" => self.tx,
+            "tx-by-addr" => self.tx_by_addr,
+            _ => CellCompression::Best,
+        }
+    }
+
+    /// Sets the compression of a table from `TABLE=METHOD`
+    pub fn set_table_compression(&mut self, table_compression: &str) -> Result<(), String> {
+        let (table, compression) = table_compression
+            .split_once('=')
+            .ok_or_else(|| format!("expected TABLE=METHOD: {table_compression}"))?;
+        let compression = compression.parse()?;
+        match table {
+            "blocks" => self.blocks = compression,
+            "entries" => self.entries = compression,
+            "tx" => self.tx = compression,
+            "tx-by-addr" => self.tx_by_addr = compression,
+            _ => return Err(format!("unknown table: {table}")),
+        }
+        Ok(())
+    }
+}
+
+struct Dictionary {
+    table: String,
+    // Sorts the dictionaries of a table from oldest to newest.
+    row_key: String,
+    dictionary: Arc<[u8]>,
+    encoder_dictionary: Arc<EncoderDictionary<'static>>,
+}
+
+// Dictionaries known to this process, by id. Cells compressed with a dictionary can only be
+// decompressed once it has been registered.
+static DICTIONARIES: RwLock<BTreeMap<u32, Dictionary>> = RwLock::new(BTreeMap::new());
+
+/// Returns the id of a dictionary, which is derived from its contents.
+pub fn dictionary_id(dictionary: &[u8]) -> u32 {
+    let hash = hash(dictionary);
+    u32::from_le_bytes(hash.as_ref()[..4].try_into().unwrap())
+}
+
+pub fn register_dictionary(table: &str, row_key: &str, dictionary: &[u8]) -> u32 {
+    let id = dictionary_id(dictionary);
+    DICTIONARIES.write().unwrap().insert(
+        id,
+        Dictionary {
+            table: table.to_string(),
+            row_key: row_key.to_string(),
+            dictionary: Arc::from(dictionary),
+            encoder_dictionary: Arc::new(EncoderDictionary::copy(dictionary, 0)),
+        },
+    );
+    id
+}
+
+/// Returns the id of the dictionary a cell is compressed with, if this process has not registered
+/// it yet
+pub fn unregistered_dictionary_id(data: &[u8]) -> Option<u32> {
+    let method_size = bincode::serialized_size(&CompressionMethod::NoCompression).unwrap() as usize;
+    let method = bincode::deserialize(data.get(..method_size)?).ok()?;
+    if !matches!(method, CompressionMethod::ZstdDictionary) {
+        return None;
+    }
+    let id = u32::from_le_bytes(data.get(method_size..method_size + 4)?.try_into().ok()?);
+    (!DICTIONARIES.read().unwrap().contains_key(&id)).then_some(id)
+}
+
+#[cfg(test)]
+pub(crate) fn unregister_dictionary(id: u32) {
+    DICTIONARIES.write().unwrap().remove(&id);
+}
+
+fn latest_dictionary(table: &str) -> Option<(u32, Arc<EncoderDictionary<'static>>)> {
+    DICTIONARIES
+        .read()
+        .unwrap()
+        .iter()
+        .filter(|(_, dictionary)| dictionary.table == table)
+        .max_by(|(_, a), (_, b)| a.row_key.cmp(&b.row_key))
+        .map(|(id, dictionary)| (*id, dictionary.encoder_dictionary.clone()))
 }
 
 fn decompress_reader<'a, R: Read + 'a>(
     method: CompressionMethod,
     stream: R,
 ) -> Result<Box<dyn Read + 'a>, io::Error> {
-    let buf_reader = BufReader::new(stream);
+    let mut buf_reader = BufReader::new(stream);
     let decompress_reader: Box<dyn Read> = match method {
         CompressionMethod::Bzip2 => Box::new(bzip2::bufread::BzDecoder::new(buf_reader)),
         CompressionMethod::Gzip => Box::new(flate2::read::GzDecoder::new(buf_reader)),
         CompressionMethod::Zstd => Box::new(zstd::stream::read::Decoder::new(buf_reader)?),
+        CompressionMethod::ZstdDictionary => {
+            let mut id = [0; 4];
+            buf_reader.read_exact(&mut id)?;
+            let id = u32::from_le_bytes(id);
+            let dictionary = DICTIONARIES
+                .read()
+                .unwrap()
+                .get(&id)
+                .map(|dictionary| dictionary.dictionary.clone())
+                .ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::Other,
+                        format!("unknown compression dictionary: {id:08x}"),
+                    )
+                })?;
+            Box::new(zstd::stream::read::Decoder::with_dictionary(
+                buf_reader,
+                &dictionary,
+            )?)
+        }
         CompressionMethod::NoCompression => Box::new(buf_reader),
     };
     Ok(decompress_reader)
@@ -64,15 +223,61 @@ pub fn compress(method: CompressionMethod, data: &[u8]) -> Result<Vec<u8>, io::E
             e.write_all(data)?;
             e.finish()?
         }
+        CompressionMethod::ZstdDictionary => {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                "dictionary compression requires a dictionary",
+            ))
+        }
         CompressionMethod::NoCompression => data.to_vec(),
     });
 
     Ok(compressed_data)
 }
 
+fn compress_with_dictionary(
+    id: u32,
+    dictionary: &EncoderDictionary,
+    data: &[u8],
+) -> Result<Vec<u8>, io::Error> {
+    let mut compressed_data = bincode::serialize(&CompressionMethod::ZstdDictionary).unwrap();
+    compressed_data.extend(id.to_le_bytes());
+    let mut e =
+        zstd::stream::write::Encoder::with_prepared_dictionary(compressed_data, dictionary)?;
+    e.write_all(data)?;
+    e.finish()
+}
+
+/// Compresses a cell of `table`
+pub fn compress_cell(
+    compression: CellCompression,
+    table: &str,
+    data: &[u8],
+) -> Result<Vec<u8>, io::Error> {
+    match compression {
+        CellCompression::Best => compress_best(data),
+        CellCompression::NoCompression => compress(CompressionMethod::NoCompression, data),
+        CellCompression::Bzip2 => compress(CompressionMethod::Bzip2, data),
+        CellCompression::Gzip => compress(CompressionMethod::Gzip, data),
+        CellCompression::Zstd => compress(CompressionMethod::Zstd, data),
+        CellCompression::ZstdDictionary => match latest_dictionary(table) {
+            Some((id, dictionary)) => compress_with_dictionary(id, &dictionary, data),
+            None => compress(CompressionMethod::Zstd, data),
+        },
+    }
+}
+
+/// Trains a dictionary on uncompressed cells of a table.
+pub fn train_dictionary(samples: &[Vec<u8>], max_size: usize) -> Result<Vec<u8>, io::Error> {
+    zstd::dict::from_samples(samples, max_size)
+}
+
 pub fn compress_best(data: &[u8]) -> Result<Vec<u8>, io::Error> {
     let mut candidates = vec![];
     for method in all::<CompressionMethod>() {
+        if matches!(method, CompressionMethod::ZstdDictionary) {
+            continue;
+        }
         candidates.push(compress(method, data)?);
     }
 
@@ -100,4 +305,84 @@ mod test {
         let data = vec![0; 256];
         assert!(compress_best(&data).expect("compress_best").len() < data.len());
     }
+
+    #[test]
+    fn test_compress_cell() {
+        let data = vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9];
+        for compression in [
+            CellCompression::Best,
+            CellCompression::NoCompression,
+            CellCompression::Bzip2,
+            CellCompression::Gzip,
+            CellCompression::Zstd,
+            // Falls back to zstd, as no dictionary is registered for this table
+            CellCompression::ZstdDictionary,
+        ] {
+            let compressed = compress_cell(compression, "test-no-dictionary", &data).unwrap();
+            assert_eq!(decompress(&compressed).unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn test_compress_cell_with_dictionary() {
+        let samples = (0..1000u32)
+            .map(|i| {
+                format!(
+                    "{{\"slot\":{i},\"status\":\"confirmed\",\"fee\":{}}}",
+                    i * 5000
+                )
+                .into_bytes()
+            })
+            .collect::<Vec<_>>();
+        let dictionary = train_dictionary(&samples, 4096).unwrap();
+
+        // Cells written before the dictionary was trained still decompress
+        let plain = compress_cell(CellCompression::Zstd, "test-dictionary", &samples[0]).unwrap();
+
+        let id = register_dictionary("test-dictionary", "test-dictionary/0", &dictionary);
+        assert_eq!(id, dictionary_id(&dictionary));
+        let compressed = compress_cell(
+            CellCompression::ZstdDictionary,
+            "test-dictionary",
+            &samples[0],
+        )
+        .unwrap();
+        assert_eq!(
+            bincode::deserialize::<CompressionMethod>(&compressed).unwrap() as u32,
+            CompressionMethod::ZstdDictionary as u32
+        );
+        assert_eq!(compressed[4..8], id.to_le_bytes());
+        assert!(compressed.len() < plain.len());
+        assert_eq!(decompress(&compressed).unwrap(), samples[0]);
+        assert_eq!(decompress(&plain).unwrap(), samples[0]);
+        assert_eq!(unregistered_dictionary_id(&compressed), None);
+        assert_eq!(unregistered_dictionary_id(&plain), None);
+
+        // Unknown dictionaries are reported
+        let mut unknown = compressed;
+        unknown[4..8].copy_from_slice(&id.wrapping_add(1).to_le_bytes());
+        assert!(decompress(&unknown).is_err());
+        assert_eq!(
+            unregistered_dictionary_id(&unknown),
+            Some(id.wrapping_add(1))
+        );
+    }
+
+    #[test]
+    fn test_compression_config() {
+        let mut config = CompressionConfig::default();
+        config.set_table_compression("tx=zstd").unwrap();
+        config
+            .set_table_compression("tx-by-addr=zstd-dictionary")
+            .unwrap();
+        assert_eq!(config.for_table("blocks"), CellCompression::Best);
+        assert_eq!(config.for_table("tx"), CellCompression::Zstd);
+        assert_eq!(
+            config.for_table("tx-by-addr"),
+            CellCompression::ZstdDictionary
+        );
+        assert!(config.set_table_compression("tx").is_err());
+        assert!(config.set_table_compression("accounts=zstd").is_err());
+        assert!(config.set_table_compression("tx=lz4").is_err());
+    }
 }
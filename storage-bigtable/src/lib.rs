@@ -1,7 +1,7 @@
 #![allow(clippy::arithmetic_side_effects)]
 
 use {
-    crate::bigtable::RowKey,
+    crate::bigtable::{RowData, RowKey},
     log::*,
     serde::{Deserialize, Serialize},
     solana_metrics::datapoint_info,
@@ -45,8 +45,16 @@ extern crate serde_derive;
 mod access_token;
 mod bigtable;
 mod compression;
+mod local_storage;
+mod long_term_storage;
 mod root_ca_certificate;
 
+pub use {
+    compression::{CellCompression, CompressionConfig},
+    local_storage::LocalLedgerStorage,
+    long_term_storage::LongTermLedgerStorage,
+};
+
 #[derive(Debug, Error)]
 pub enum Error {
     #[error("BigTable: {0}")]
@@ -66,6 +74,9 @@ pub enum Error {
 
     #[error("tokio error")]
     TokioJoinError(JoinError),
+
+    #[error("RocksDB: {0}")]
+    RocksDb(rocksdb::Error),
 }
 
 impl std::convert::From<bigtable::Error> for Error {
@@ -80,6 +91,12 @@ impl std::convert::From<std::io::Error> for Error {
     }
 }
 
+impl std::convert::From<rocksdb::Error> for Error {
+    fn from(err: rocksdb::Error) -> Self {
+        Self::RocksDb(err)
+    }
+}
+
 pub type Result<T> = std::result::Result<T, Error>;
 
 // Convert a slot to its bucket representation whereby lower slots are always lexically ordered
@@ -379,6 +396,254 @@ impl From<LegacyTransactionByAddrInfo> for TransactionByAddrInfo {
     }
 }
 
+/// Collects the signatures for an address, in descending ledger order, from its `tx-by-addr`
+/// rows listed in ascending key order, skipping those outside of the `before` and `until`
+/// transactions.
+fn collect_signatures_for_address<I>(
+    address_prefix: &str,
+    tx_by_addr_data: I,
+    first_slot: Slot,
+    before_transaction_index: u32,
+    last_slot: Slot,
+    until_transaction_index: u32,
+    limit: usize,
+) -> Result<
+    Vec<(
+        ConfirmedTransactionStatusWithSignature,
+        u32, /*slot index*/
+    )>,
+>
+where
+    I: IntoIterator<Item = Result<(RowKey, RowData)>>,
+{
+    let mut infos = vec![];
+    'outer: for row in tx_by_addr_data {
+        let (row_key, data) = row?;
+        let slot = !key_to_slot(&row_key[address_prefix.len()..]).ok_or_else(|| {
+            bigtable::Error::ObjectCorrupt(format!(
+                "Failed to convert key to slot: tx-by-addr/{row_key}"
+            ))
+        })?;
+
+        let deserialized_cell_data = bigtable::deserialize_protobuf_or_bincode_cell_data::<
+            Vec<LegacyTransactionByAddrInfo>,
+            tx_by_addr::TransactionByAddr,
+        >(&data, "tx-by-addr", row_key.clone())?;
+
+        let mut cell_data: Vec<TransactionByAddrInfo> = match deserialized_cell_data {
+            bigtable::CellData::Bincode(tx_by_addr) => {
+                tx_by_addr.into_iter().map(|legacy| legacy.into()).collect()
+            }
+            bigtable::CellData::Protobuf(tx_by_addr) => tx_by_addr.try_into().map_err(|error| {
+                bigtable::Error::ObjectCorrupt(format!(
+                    "Failed to deserialize: {}: tx-by-addr/{}",
+                    error,
+                    row_key.clone()
+                ))
+            })?,
+        };
+
+        cell_data.reverse();
+        for tx_by_addr_info in cell_data.into_iter() {
+            // Filter out records before `before_transaction_index`
+            if slot == first_slot && tx_by_addr_info.index >= before_transaction_index {
+                continue;
+            }
+            // Filter out records after `until_transaction_index`
+            if slot == last_slot && tx_by_addr_info.index <= until_transaction_index {
+                continue;
+            }
+            infos.push((
+                ConfirmedTransactionStatusWithSignature {
+                    signature: tx_by_addr_info.signature,
+                    slot,
+                    err: tx_by_addr_info.err,
+                    memo: tx_by_addr_info.memo,
+                    block_time: tx_by_addr_info.block_time,
+                },
+                tx_by_addr_info.index,
+            ));
+            // Respect limit
+            if infos.len() >= limit {
+                break 'outer;
+            }
+        }
+    }
+    Ok(infos)
+}
+
+// Returns the `tx-by-addr` row keys written for the block, and the `tx` rows expected for its
+// transactions, keyed by signature.
+fn block_index_rows(
+    slot: Slot,
+    confirmed_block: &ConfirmedBlock,
+) -> (Vec<RowKey>, HashMap<String, UploadedTransaction>) {
+    let mut addresses: HashSet<&Pubkey> = HashSet::new();
+    let mut expected_tx_infos: HashMap<String, UploadedTransaction> = HashMap::new();
+    for (index, transaction_with_meta) in confirmed_block.transactions.iter().enumerate() {
+        match transaction_with_meta {
+            TransactionWithStatusMeta::MissingMetadata(transaction) => {
+                let signature = transaction.signatures[0];
+                let index = index as u32;
+                let err = None;
+
+                for address in transaction.message.account_keys.iter() {
+                    if !is_sysvar_id(address) {
+                        addresses.insert(address);
+                    }
+                }
+
+                expected_tx_infos.insert(
+                    signature.to_string(),
+                    UploadedTransaction { slot, index, err },
+                );
+            }
+            TransactionWithStatusMeta::Complete(tx_with_meta) => {
+                let VersionedTransactionWithStatusMeta { transaction, meta } = tx_with_meta;
+                let signature = transaction.signatures[0];
+                let index = index as u32;
+                let err = meta.status.clone().err();
+
+                for address in tx_with_meta.account_keys().iter() {
+                    if !is_sysvar_id(address) {
+                        addresses.insert(address);
+                    }
+                }
+
+                expected_tx_infos.insert(
+                    signature.to_string(),
+                    UploadedTransaction { slot, index, err },
+                );
+            }
+        }
+    }
+
+    let address_slot_rows = addresses
+        .into_iter()
+        .map(|address| format!("{}/{}", address, slot_to_tx_by_addr_key(slot)))
+        .collect();
+    (address_slot_rows, expected_tx_infos)
+}
+
+// Returns the `tx` rows which match the transactions of the block being deleted.
+fn tx_rows_to_delete<E: std::fmt::Debug>(
+    expected_tx_infos: HashMap<String, UploadedTransaction>,
+    fetched_tx_infos: HashMap<String, std::result::Result<UploadedTransaction, E>>,
+) -> Vec<RowKey> {
+    let mut deletion_rows = Vec::with_capacity(expected_tx_infos.len());
+    for (signature, expected_tx_info) in expected_tx_infos {
+        match fetched_tx_infos.get(&signature) {
+            Some(Ok(fetched_tx_info)) if fetched_tx_info == &expected_tx_info => {
+                deletion_rows.push(signature);
+            }
+            Some(Ok(fetched_tx_info)) => {
+                warn!(
+                    "skipped tx row {} because the stored entry ({:?}) did not match to {:?}",
+                    signature, fetched_tx_info, &expected_tx_info,
+                );
+            }
+            Some(Err(err)) => {
+                warn!(
+                    "skipped tx row {} because the stored entry was corrupted: {:?}",
+                    signature, err
+                );
+            }
+            None => {
+                warn!("skipped tx row {} because it was not found", signature);
+            }
+        }
+    }
+    deletion_rows
+}
+
+// The cells written to each table when uploading a block
+struct BlockCells {
+    tx_cells: Vec<(RowKey, TransactionInfo)>,
+    tx_by_addr_cells: Vec<(RowKey, tx_by_addr::TransactionByAddr)>,
+    entries_cell: Option<(RowKey, entries::Entries)>,
+    blocks_cell: (RowKey, generated::ConfirmedBlock),
+    num_transactions: usize,
+    num_entries: usize,
+}
+
+impl BlockCells {
+    fn new(slot: Slot, confirmed_block: VersionedConfirmedBlockWithEntries) -> Self {
+        let mut by_addr: HashMap<&Pubkey, Vec<TransactionByAddrInfo>> = HashMap::new();
+        let VersionedConfirmedBlockWithEntries {
+            block: confirmed_block,
+            entries,
+        } = confirmed_block;
+
+        let mut tx_cells = Vec::with_capacity(confirmed_block.transactions.len());
+        for (index, transaction_with_meta) in confirmed_block.transactions.iter().enumerate() {
+            let VersionedTransactionWithStatusMeta { meta, transaction } = transaction_with_meta;
+            let err = meta.status.clone().err();
+            let index = index as u32;
+            let signature = transaction.signatures[0];
+            let memo = extract_and_fmt_memos(transaction_with_meta);
+
+            for address in transaction_with_meta.account_keys().iter() {
+                if !is_sysvar_id(address) {
+                    by_addr
+                        .entry(address)
+                        .or_default()
+                        .push(TransactionByAddrInfo {
+                            signature,
+                            err: err.clone(),
+                            index,
+                            memo: memo.clone(),
+                            block_time: confirmed_block.block_time,
+                        });
+                }
+            }
+
+            tx_cells.push((
+                signature.to_string(),
+                TransactionInfo {
+                    slot,
+                    index,
+                    err,
+                    memo,
+                },
+            ));
+        }
+
+        let tx_by_addr_cells: Vec<_> = by_addr
+            .into_iter()
+            .map(|(address, transaction_info_by_addr)| {
+                (
+                    format!("{}/{}", address, slot_to_tx_by_addr_key(slot)),
+                    tx_by_addr::TransactionByAddr {
+                        tx_by_addrs: transaction_info_by_addr
+                            .into_iter()
+                            .map(|by_addr| by_addr.into())
+                            .collect(),
+                    },
+                )
+            })
+            .collect();
+
+        let num_entries = entries.len();
+        let entries_cell = (num_entries > 0).then(|| {
+            (
+                slot_to_entries_key(slot),
+                entries::Entries {
+                    entries: entries.into_iter().enumerate().map(Into::into).collect(),
+                },
+            )
+        });
+
+        Self {
+            tx_cells,
+            tx_by_addr_cells,
+            entries_cell,
+            num_transactions: confirmed_block.transactions.len(),
+            blocks_cell: (slot_to_blocks_key(slot), confirmed_block.into()),
+            num_entries,
+        }
+    }
+}
+
 pub const DEFAULT_INSTANCE_NAME: &str = "solana-ledger";
 pub const DEFAULT_APP_PROFILE_ID: &str = "default";
 pub const DEFAULT_MAX_MESSAGE_SIZE: usize = 64 * 1024 * 1024; // 64MB
@@ -397,6 +662,7 @@ pub struct LedgerStorageConfig {
     pub instance_name: String,
     pub app_profile_id: String,
     pub max_message_size: usize,
+    pub compression: CompressionConfig,
 }
 
 impl Default for LedgerStorageConfig {
@@ -408,6 +674,7 @@ impl Default for LedgerStorageConfig {
             instance_name: DEFAULT_INSTANCE_NAME.to_string(),
             app_profile_id: DEFAULT_APP_PROFILE_ID.to_string(),
             max_message_size: DEFAULT_MAX_MESSAGE_SIZE,
+            compression: CompressionConfig::default(),
         }
     }
 }
@@ -489,6 +756,7 @@ impl LedgerStorage {
             app_profile_id,
             credential_type,
             max_message_size,
+            compression,
         } = config;
         let connection = bigtable::BigTableConnection::new(
             instance_name.as_str(),
@@ -498,8 +766,14 @@ impl LedgerStorage {
             credential_type,
             max_message_size,
         )
-        .await?;
-        Ok(Self { stats, connection })
+        .await?
+        .with_compression(compression);
+        let ledger_storage = Self { stats, connection };
+        // Instances predating dictionary compression have no dictionaries table
+        if let Err(err) = ledger_storage.load_compression_dictionaries().await {
+            warn!("Failed to load compression dictionaries: {}", err);
+        }
+        Ok(ledger_storage)
     }
 
     pub async fn new_with_stringified_credential(credential: String) -> Result<Self> {
@@ -812,8 +1086,6 @@ impl LedgerStorage {
             }
         };
 
-        let mut infos = vec![];
-
         let starting_slot_tx_len = bigtable
             .get_protobuf_or_bincode_cell::<Vec<LegacyTransactionByAddrInfo>, tx_by_addr::TransactionByAddr>(
                 "tx-by-addr",
@@ -846,61 +1118,23 @@ impl LedgerStorage {
                 limit as i64 + starting_slot_tx_len as i64,
             )
             .await?;
-
-        'outer: for (row_key, data) in tx_by_addr_data {
-            let slot = !key_to_slot(&row_key[address_prefix.len()..]).ok_or_else(|| {
-                bigtable::Error::ObjectCorrupt(format!(
-                    "Failed to convert key to slot: tx-by-addr/{row_key}"
-                ))
-            })?;
-
-            let deserialized_cell_data = bigtable::deserialize_protobuf_or_bincode_cell_data::<
-                Vec<LegacyTransactionByAddrInfo>,
-                tx_by_addr::TransactionByAddr,
-            >(&data, "tx-by-addr", row_key.clone())?;
-
-            let mut cell_data: Vec<TransactionByAddrInfo> = match deserialized_cell_data {
-                bigtable::CellData::Bincode(tx_by_addr) => {
-                    tx_by_addr.into_iter().map(|legacy| legacy.into()).collect()
-                }
-                bigtable::CellData::Protobuf(tx_by_addr) => {
-                    tx_by_addr.try_into().map_err(|error| {
-                        bigtable::Error::ObjectCorrupt(format!(
-                            "Failed to deserialize: {}: tx-by-addr/{}",
-                            error,
-                            row_key.clone()
-                        ))
-                    })?
-                }
-            };
-
-            cell_data.reverse();
-            for tx_by_addr_info in cell_data.into_iter() {
-                // Filter out records before `before_transaction_index`
-                if slot == first_slot && tx_by_addr_info.index >= before_transaction_index {
-                    continue;
-                }
-                // Filter out records after `until_transaction_index`
-                if slot == last_slot && tx_by_addr_info.index <= until_transaction_index {
-                    continue;
-                }
-                infos.push((
-                    ConfirmedTransactionStatusWithSignature {
-                        signature: tx_by_addr_info.signature,
-                        slot,
-                        err: tx_by_addr_info.err,
-                        memo: tx_by_addr_info.memo,
-                        block_time: tx_by_addr_info.block_time,
-                    },
-                    tx_by_addr_info.index,
-                ));
-                // Respect limit
-                if infos.len() >= limit {
-                    break 'outer;
-                }
-            }
-        }
-        Ok(infos)
+        bigtable
+            .load_missing_dictionaries(
+                tx_by_addr_data
+                    .iter()
+                    .map(|(_, row_data)| row_data.as_slice()),
+            )
+            .await;
+
+        collect_signatures_for_address(
+            &address_prefix,
+            tx_by_addr_data.into_iter().map(Ok),
+            first_slot,
+            before_transaction_index,
+            last_slot,
+            until_transaction_index,
+            limit,
+        )
     }
 
     /// Upload a new confirmed block and associated meta data.
@@ -932,68 +1166,14 @@ impl LedgerStorage {
             "LedgerStorage::upload_confirmed_block_with_entries request received: {:?}",
             slot
         );
-        let mut by_addr: HashMap<&Pubkey, Vec<TransactionByAddrInfo>> = HashMap::new();
-        let VersionedConfirmedBlockWithEntries {
-            block: confirmed_block,
-            entries,
-        } = confirmed_block;
-
-        let mut tx_cells = Vec::with_capacity(confirmed_block.transactions.len());
-        for (index, transaction_with_meta) in confirmed_block.transactions.iter().enumerate() {
-            let VersionedTransactionWithStatusMeta { meta, transaction } = transaction_with_meta;
-            let err = meta.status.clone().err();
-            let index = index as u32;
-            let signature = transaction.signatures[0];
-            let memo = extract_and_fmt_memos(transaction_with_meta);
-
-            for address in transaction_with_meta.account_keys().iter() {
-                if !is_sysvar_id(address) {
-                    by_addr
-                        .entry(address)
-                        .or_default()
-                        .push(TransactionByAddrInfo {
-                            signature,
-                            err: err.clone(),
-                            index,
-                            memo: memo.clone(),
-                            block_time: confirmed_block.block_time,
-                        });
-                }
-            }
-
-            tx_cells.push((
-                signature.to_string(),
-                TransactionInfo {
-                    slot,
-                    index,
-                    err,
-                    memo,
-                },
-            ));
-        }
-
-        let tx_by_addr_cells: Vec<_> = by_addr
-            .into_iter()
-            .map(|(address, transaction_info_by_addr)| {
-                (
-                    format!("{}/{}", address, slot_to_tx_by_addr_key(slot)),
-                    tx_by_addr::TransactionByAddr {
-                        tx_by_addrs: transaction_info_by_addr
-                            .into_iter()
-                            .map(|by_addr| by_addr.into())
-                            .collect(),
-                    },
-                )
-            })
-            .collect();
-
-        let num_entries = entries.len();
-        let entry_cell = (
-            slot_to_entries_key(slot),
-            entries::Entries {
-                entries: entries.into_iter().enumerate().map(Into::into).collect(),
-            },
-        );
+        let BlockCells {
+            tx_cells,
+            tx_by_addr_cells,
+            entries_cell,
+            blocks_cell,
+            num_transactions,
+            num_entries,
+        } = BlockCells::new(slot, confirmed_block);
 
         let mut tasks = vec![];
 
@@ -1016,10 +1196,10 @@ impl LedgerStorage {
             }));
         }
 
-        if num_entries > 0 {
+        if let Some(entries_cell) = entries_cell {
             let conn = self.connection.clone();
             tasks.push(tokio::spawn(async move {
-                conn.put_protobuf_cells_with_retry::<entries::Entries>("entries", &[entry_cell])
+                conn.put_protobuf_cells_with_retry::<entries::Entries>("entries", &[entries_cell])
                     .await
             }));
         }
@@ -1050,12 +1230,10 @@ impl LedgerStorage {
             return Err(err);
         }
 
-        let num_transactions = confirmed_block.transactions.len();
-
         // Store the block itself last, after all other metadata about the block has been
         // successfully stored.  This avoids partial uploaded blocks from becoming visible to
         // `get_confirmed_block()` and `get_confirmed_blocks()`
-        let blocks_cells = [(slot_to_blocks_key(slot), confirmed_block.into())];
+        let blocks_cells = [blocks_cell];
         bytes_written += self
             .connection
             .put_protobuf_cells_with_retry::<generated::ConfirmedBlock>("blocks", &blocks_cells)
@@ -1072,51 +1250,8 @@ impl LedgerStorage {
 
     // Delete a confirmed block and associated meta data.
     pub async fn delete_confirmed_block(&self, slot: Slot, dry_run: bool) -> Result<()> {
-        let mut addresses: HashSet<&Pubkey> = HashSet::new();
-        let mut expected_tx_infos: HashMap<String, UploadedTransaction> = HashMap::new();
         let confirmed_block = self.get_confirmed_block(slot).await?;
-        for (index, transaction_with_meta) in confirmed_block.transactions.iter().enumerate() {
-            match transaction_with_meta {
-                TransactionWithStatusMeta::MissingMetadata(transaction) => {
-                    let signature = transaction.signatures[0];
-                    let index = index as u32;
-                    let err = None;
-
-                    for address in transaction.message.account_keys.iter() {
-                        if !is_sysvar_id(address) {
-                            addresses.insert(address);
-                        }
-                    }
-
-                    expected_tx_infos.insert(
-                        signature.to_string(),
-                        UploadedTransaction { slot, index, err },
-                    );
-                }
-                TransactionWithStatusMeta::Complete(tx_with_meta) => {
-                    let VersionedTransactionWithStatusMeta { transaction, meta } = tx_with_meta;
-                    let signature = transaction.signatures[0];
-                    let index = index as u32;
-                    let err = meta.status.clone().err();
-
-                    for address in tx_with_meta.account_keys().iter() {
-                        if !is_sysvar_id(address) {
-                            addresses.insert(address);
-                        }
-                    }
-
-                    expected_tx_infos.insert(
-                        signature.to_string(),
-                        UploadedTransaction { slot, index, err },
-                    );
-                }
-            }
-        }
-
-        let address_slot_rows: Vec<_> = addresses
-            .into_iter()
-            .map(|address| format!("{}/{}", address, slot_to_tx_by_addr_key(slot)))
-            .collect();
+        let (address_slot_rows, expected_tx_infos) = block_index_rows(slot, &confirmed_block);
 
         let tx_deletion_rows = if !expected_tx_infos.is_empty() {
             let signatures = expected_tx_infos.keys().cloned().collect::<Vec<_>>();
@@ -1128,32 +1263,7 @@ impl LedgerStorage {
                     .map(|(signature, tx_info_res)| (signature, tx_info_res.map(Into::into)))
                     .collect::<HashMap<_, _>>();
 
-            let mut deletion_rows = Vec::with_capacity(expected_tx_infos.len());
-            for (signature, expected_tx_info) in expected_tx_infos {
-                match fetched_tx_infos.get(&signature) {
-                    Some(Ok(fetched_tx_info)) if fetched_tx_info == &expected_tx_info => {
-                        deletion_rows.push(signature);
-                    }
-                    Some(Ok(fetched_tx_info)) => {
-                        warn!(
-                            "skipped tx row {} because the bigtable entry ({:?}) did not match to {:?}",
-                            signature,
-                            fetched_tx_info,
-                            &expected_tx_info,
-                        );
-                    }
-                    Some(Err(err)) => {
-                        warn!(
-                            "skipped tx row {} because the bigtable entry was corrupted: {:?}",
-                            signature, err
-                        );
-                    }
-                    None => {
-                        warn!("skipped tx row {} because it was not found", signature);
-                    }
-                }
-            }
-            deletion_rows
+            tx_rows_to_delete(expected_tx_infos, fetched_tx_infos)
         } else {
             vec![]
         };
@@ -1200,15 +1310,569 @@ impl LedgerStorage {
 
         Ok(())
     }
+
+    /// Load the compression dictionaries stored in the `dictionaries` table, which are needed to
+    /// read cells compressed with them. Returns the number of dictionaries loaded.
+    /// Dictionaries trained afterwards are loaded when a cell compressed with one is read.
+    pub async fn load_compression_dictionaries(&self) -> Result<usize> {
+        let mut bigtable = self.connection.client();
+        Ok(bigtable.load_compression_dictionaries().await?)
+    }
+
+    /// Train a zstd dictionary on up to `num_samples` cells of `table`, starting at the row key
+    /// `start_at`, and store it in the `dictionaries` table. Cells subsequently uploaded to
+    /// `table` with `CellCompression::ZstdDictionary` are compressed with it.
+    ///
+    /// Returns the id of the new dictionary.
+    pub async fn train_compression_dictionary(
+        &self,
+        table: &str,
+        start_at: Option<RowKey>,
+        num_samples: usize,
+        max_dictionary_size: usize,
+    ) -> Result<u32> {
+        if !CompressionConfig::TABLES.contains(&table) {
+            return Err(Error::IoError(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("unknown table: {table}"),
+            )));
+        }
+        let mut bigtable = self.connection.client();
+        let rows = bigtable
+            .get_row_data(table, start_at, None, num_samples as i64)
+            .await?;
+        let mut samples = vec![];
+        for (_row_key, row_data) in rows {
+            for (_cell_name, cell_data) in row_data {
+                samples.push(compression::decompress(&cell_data)?);
+            }
+        }
+        if samples.is_empty() {
+            return Err(Error::IoError(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("no cells to sample in {table}"),
+            )));
+        }
+
+        let dictionary = compression::train_dictionary(&samples, max_dictionary_size)?;
+        let row_key = bigtable
+            .put_compression_dictionary(table, &dictionary)
+            .await?;
+        let id = compression::register_dictionary(table, &row_key, &dictionary);
+        info!(
+            "Trained compression dictionary {:08x} for {} on {} cells: {} bytes",
+            id,
+            table,
+            samples.len(),
+            dictionary.len()
+        );
+        Ok(id)
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use super::*;
+    use {
+        super::*,
+        crate::bigtable::fake_bigtable::FakeBigTable,
+        solana_account_decoder::parse_token::UiTokenAmount,
+        solana_sdk::{
+            address_lookup_table::AddressLookupTableAccount,
+            hash::Hash,
+            instruction::{CompiledInstruction, InstructionError},
+            message::{v0, VersionedMessage},
+            reward_type::RewardType,
+            signature::{Keypair, Signer},
+            system_instruction, system_program, system_transaction,
+            transaction_context::TransactionReturnData,
+        },
+        solana_transaction_status::{InnerInstruction, InnerInstructions, TransactionTokenBalance},
+    };
 
     #[test]
     fn test_slot_to_key() {
         assert_eq!(slot_to_key(0), "0000000000000000");
         assert_eq!(slot_to_key(!0), "ffffffffffffffff");
     }
+
+    async fn new_fake_ledger_storage() -> (LedgerStorage, FakeBigTable) {
+        let fake_bigtable = FakeBigTable::default();
+        let addr = fake_bigtable.serve().await;
+        let ledger_storage =
+            LedgerStorage::new_for_emulator("test", "default", &addr.to_string(), None).unwrap();
+        (ledger_storage, fake_bigtable)
+    }
+
+    fn default_meta() -> TransactionStatusMeta {
+        TransactionStatusMeta {
+            status: Ok(()),
+            fee: 5000,
+            pre_balances: vec![1_000_000, 0, 1],
+            post_balances: vec![994_958, 42, 1],
+            inner_instructions: Some(vec![]),
+            log_messages: Some(vec![]),
+            pre_token_balances: Some(vec![]),
+            post_token_balances: Some(vec![]),
+            rewards: Some(vec![]),
+            loaded_addresses: LoadedAddresses::default(),
+            return_data: None,
+            compute_units_consumed: Some(150),
+        }
+    }
+
+    fn transfer_with_meta(
+        from: &Keypair,
+        to: &Pubkey,
+        meta: TransactionStatusMeta,
+    ) -> VersionedTransactionWithStatusMeta {
+        let transaction = system_transaction::transfer(from, to, 42, Hash::new_unique());
+        VersionedTransactionWithStatusMeta {
+            transaction: VersionedTransaction::from(transaction),
+            meta,
+        }
+    }
+
+    // A transfer to `to`, which is loaded from an address lookup table
+    fn v0_transfer_with_meta(from: &Keypair, to: &Pubkey) -> VersionedTransactionWithStatusMeta {
+        let lookup_table = AddressLookupTableAccount {
+            key: Pubkey::new_unique(),
+            addresses: vec![Pubkey::new_unique(), *to],
+        };
+        let message = v0::Message::try_compile(
+            &from.pubkey(),
+            &[system_instruction::transfer(&from.pubkey(), to, 42)],
+            &[lookup_table.clone()],
+            Hash::new_unique(),
+        )
+        .unwrap();
+        let lookup = &message.address_table_lookups[0];
+        let loaded_addresses = LoadedAddresses {
+            writable: lookup
+                .writable_indexes
+                .iter()
+                .map(|index| lookup_table.addresses[*index as usize])
+                .collect(),
+            readonly: lookup
+                .readonly_indexes
+                .iter()
+                .map(|index| lookup_table.addresses[*index as usize])
+                .collect(),
+        };
+        assert_eq!(loaded_addresses.writable, vec![*to]);
+        let transaction =
+            VersionedTransaction::try_new(VersionedMessage::V0(message), &[from]).unwrap();
+        VersionedTransactionWithStatusMeta {
+            transaction,
+            meta: TransactionStatusMeta {
+                loaded_addresses,
+                ..default_meta()
+            },
+        }
+    }
+
+    fn new_block(
+        slot: Slot,
+        transactions: Vec<VersionedTransactionWithStatusMeta>,
+    ) -> VersionedConfirmedBlockWithEntries {
+        let entries = vec![
+            EntrySummary {
+                num_hashes: 12,
+                hash: Hash::new_unique(),
+                num_transactions: transactions.len() as u64,
+                starting_transaction_index: 0,
+            },
+            EntrySummary {
+                num_hashes: 64,
+                hash: Hash::new_unique(),
+                num_transactions: 0,
+                starting_transaction_index: transactions.len(),
+            },
+        ];
+        VersionedConfirmedBlockWithEntries {
+            block: VersionedConfirmedBlock {
+                previous_blockhash: Hash::new_unique().to_string(),
+                blockhash: Hash::new_unique().to_string(),
+                parent_slot: slot.saturating_sub(1),
+                transactions,
+                rewards: vec![],
+                block_time: Some(1_700_000_000 + slot as UnixTimestamp),
+                block_height: Some(slot),
+            },
+            entries,
+        }
+    }
+
+    fn signatures_for_address(
+        ledger_storage: &LedgerStorage,
+        runtime: &tokio::runtime::Runtime,
+        address: &Pubkey,
+        before: Option<&Signature>,
+        until: Option<&Signature>,
+        limit: usize,
+    ) -> Vec<(Signature, Slot, u32)> {
+        runtime
+            .block_on(
+                ledger_storage.get_confirmed_signatures_for_address(address, before, until, limit),
+            )
+            .unwrap()
+            .into_iter()
+            .map(|(status, index)| (status.signature, status.slot, index))
+            .collect()
+    }
+
+    #[test]
+    fn test_upload_and_get_confirmed_block() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let (ledger_storage, _fake_bigtable) = runtime.block_on(new_fake_ledger_storage());
+        let payer = Keypair::new();
+        let recipient = Pubkey::new_unique();
+        let slot = 10;
+
+        // Exercise each optional field of the protobuf encoding
+        let token_balance = |amount: &str, ui_amount| TransactionTokenBalance {
+            account_index: 1,
+            mint: Pubkey::new_unique().to_string(),
+            ui_token_amount: UiTokenAmount {
+                ui_amount: Some(ui_amount),
+                decimals: 2,
+                amount: amount.to_string(),
+                ui_amount_string: ui_amount.to_string(),
+            },
+            owner: Pubkey::new_unique().to_string(),
+            program_id: Pubkey::new_unique().to_string(),
+        };
+        let full_meta = TransactionStatusMeta {
+            inner_instructions: Some(vec![InnerInstructions {
+                index: 0,
+                instructions: vec![InnerInstruction {
+                    instruction: CompiledInstruction::new_from_raw_parts(
+                        2,
+                        vec![1, 2, 3],
+                        vec![0, 1],
+                    ),
+                    stack_height: Some(2),
+                }],
+            }]),
+            log_messages: Some(vec!["Program log: transfer".to_string()]),
+            pre_token_balances: Some(vec![token_balance("150", 1.5)]),
+            post_token_balances: Some(vec![token_balance("25", 0.25)]),
+            rewards: Some(vec![Reward {
+                pubkey: Pubkey::new_unique().to_string(),
+                lamports: -10,
+                post_balance: 100,
+                reward_type: Some(RewardType::Rent),
+                commission: None,
+            }]),
+            return_data: Some(TransactionReturnData {
+                program_id: system_program::id(),
+                data: vec![4, 5, 6],
+            }),
+            ..default_meta()
+        };
+        let failed_meta = TransactionStatusMeta {
+            status: Err(TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(7),
+            )),
+            inner_instructions: None,
+            log_messages: None,
+            return_data: None,
+            compute_units_consumed: None,
+            ..default_meta()
+        };
+        let VersionedConfirmedBlockWithEntries { mut block, entries } = new_block(
+            slot,
+            vec![
+                transfer_with_meta(&payer, &recipient, full_meta),
+                transfer_with_meta(&payer, &recipient, failed_meta),
+                v0_transfer_with_meta(&payer, &recipient),
+            ],
+        );
+        block.rewards = vec![Reward {
+            pubkey: Pubkey::new_unique().to_string(),
+            lamports: 1_000,
+            post_balance: 1_000_000,
+            reward_type: Some(RewardType::Voting),
+            commission: Some(10),
+        }];
+        // EntrySummary is not comparable, so compare its fields
+        let entry_fields = |entries: &[EntrySummary]| {
+            entries
+                .iter()
+                .map(|entry| {
+                    (
+                        entry.num_hashes,
+                        entry.hash,
+                        entry.num_transactions,
+                        entry.starting_transaction_index,
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        let expected_entries = entry_fields(&entries);
+        runtime
+            .block_on(ledger_storage.upload_confirmed_block_with_entries(
+                slot,
+                VersionedConfirmedBlockWithEntries {
+                    block: block.clone(),
+                    entries,
+                },
+            ))
+            .unwrap();
+
+        let confirmed_block = runtime
+            .block_on(ledger_storage.get_confirmed_block(slot))
+            .unwrap();
+        assert_eq!(confirmed_block, ConfirmedBlock::from(block.clone()));
+        let entries: Vec<_> = runtime
+            .block_on(ledger_storage.get_entries(slot))
+            .unwrap()
+            .collect();
+        assert_eq!(entry_fields(&entries), expected_entries);
+
+        for transaction in &block.transactions {
+            let signature = transaction.transaction.signatures[0];
+            assert_eq!(
+                runtime
+                    .block_on(ledger_storage.get_confirmed_transaction(&signature))
+                    .unwrap(),
+                Some(ConfirmedTransactionWithStatusMeta {
+                    slot,
+                    tx_with_meta: TransactionWithStatusMeta::Complete(transaction.clone()),
+                    block_time: block.block_time,
+                })
+            );
+            let status = runtime
+                .block_on(ledger_storage.get_signature_status(&signature))
+                .unwrap();
+            assert_eq!(status.slot, slot);
+            assert_eq!(status.status, transaction.meta.status);
+        }
+        assert!(matches!(
+            runtime.block_on(ledger_storage.get_confirmed_transaction(&Signature::default())),
+            Err(Error::SignatureNotFound)
+        ));
+
+        assert!(runtime
+            .block_on(ledger_storage.confirmed_block_exists(slot))
+            .unwrap());
+        assert!(!runtime
+            .block_on(ledger_storage.confirmed_block_exists(slot + 1))
+            .unwrap());
+        assert!(matches!(
+            runtime.block_on(ledger_storage.get_confirmed_block(slot + 1)),
+            Err(Error::BlockNotFound(_))
+        ));
+        assert_eq!(
+            runtime
+                .block_on(ledger_storage.get_first_available_block())
+                .unwrap(),
+            Some(slot)
+        );
+        assert_eq!(
+            runtime
+                .block_on(ledger_storage.get_confirmed_blocks(0, 10))
+                .unwrap(),
+            vec![slot]
+        );
+    }
+
+    #[test]
+    fn test_get_confirmed_signatures_for_address() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let (ledger_storage, _fake_bigtable) = runtime.block_on(new_fake_ledger_storage());
+        let address = Pubkey::new_unique();
+
+        // Expected in descending ledger order
+        let mut expected = vec![];
+        for slot in [3, 5, 6, 9] {
+            let transactions: Vec<_> = (0..3)
+                .map(|_| transfer_with_meta(&Keypair::new(), &address, default_meta()))
+                .collect();
+            for (index, transaction) in transactions.iter().enumerate() {
+                expected.push((transaction.transaction.signatures[0], slot, index as u32));
+            }
+            runtime
+                .block_on(
+                    ledger_storage
+                        .upload_confirmed_block_with_entries(slot, new_block(slot, transactions)),
+                )
+                .unwrap();
+        }
+        expected.reverse();
+
+        let get_signatures = |before: Option<&Signature>, until: Option<&Signature>, limit| {
+            signatures_for_address(&ledger_storage, &runtime, &address, before, until, limit)
+        };
+        assert_eq!(get_signatures(None, None, 1000), expected);
+        assert_eq!(get_signatures(None, None, 4), expected[..4]);
+        assert_eq!(
+            get_signatures(None, Some(&expected[7].0), 1000),
+            expected[..7]
+        );
+        assert_eq!(
+            get_signatures(Some(&expected[2].0), Some(&expected[8].0), 1000),
+            expected[3..8]
+        );
+        assert_eq!(
+            get_signatures(Some(&expected[2].0), Some(&expected[8].0), 2),
+            expected[3..5]
+        );
+        assert!(get_signatures(Some(&expected[11].0), None, 1000).is_empty());
+
+        // Paging through with `before` visits every signature once
+        let mut paged = vec![];
+        loop {
+            let page = get_signatures(paged.last().map(|(signature, _, _)| signature), None, 5);
+            if page.is_empty() {
+                break;
+            }
+            paged.extend(page);
+        }
+        assert_eq!(paged, expected);
+
+        assert!(signatures_for_address(
+            &ledger_storage,
+            &runtime,
+            &Pubkey::new_unique(),
+            None,
+            None,
+            1000
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn test_delete_confirmed_block() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let (ledger_storage, fake_bigtable) = runtime.block_on(new_fake_ledger_storage());
+        let address = Pubkey::new_unique();
+        let mut signatures = vec![];
+        for slot in [1, 2] {
+            let transaction = transfer_with_meta(&Keypair::new(), &address, default_meta());
+            signatures.push(transaction.transaction.signatures[0]);
+            runtime
+                .block_on(
+                    ledger_storage.upload_confirmed_block_with_entries(
+                        slot,
+                        new_block(slot, vec![transaction]),
+                    ),
+                )
+                .unwrap();
+        }
+        let row_counts = |fake_bigtable: &FakeBigTable| {
+            ["blocks", "entries", "tx", "tx-by-addr"]
+                .map(|table| fake_bigtable.row_keys(table).len())
+        };
+        // Each transaction indexes its payer, the recipient and the system program
+        assert_eq!(row_counts(&fake_bigtable), [2, 2, 2, 6]);
+
+        runtime
+            .block_on(ledger_storage.delete_confirmed_block(1, /*dry_run:*/ true))
+            .unwrap();
+        assert_eq!(row_counts(&fake_bigtable), [2, 2, 2, 6]);
+
+        runtime
+            .block_on(ledger_storage.delete_confirmed_block(1, /*dry_run:*/ false))
+            .unwrap();
+        assert_eq!(row_counts(&fake_bigtable), [1, 1, 1, 3]);
+        assert_eq!(
+            fake_bigtable.row_keys("blocks"),
+            vec![slot_to_blocks_key(2)]
+        );
+        assert_eq!(
+            fake_bigtable.row_keys("tx"),
+            vec![signatures[1].to_string()]
+        );
+        assert!(fake_bigtable
+            .row_keys("tx-by-addr")
+            .iter()
+            .all(|row_key| row_key.ends_with(&slot_to_tx_by_addr_key(2))));
+        assert!(matches!(
+            runtime.block_on(ledger_storage.get_confirmed_block(1)),
+            Err(Error::BlockNotFound(1))
+        ));
+        assert_eq!(
+            signatures_for_address(&ledger_storage, &runtime, &address, None, None, 1000),
+            vec![(signatures[1], 2, 0)]
+        );
+    }
+
+    #[test]
+    fn test_read_cells_compressed_with_new_dictionary() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        // Started before the dictionary is trained, so only loads it when reading a cell
+        let (reader, fake_bigtable) = runtime.block_on(new_fake_ledger_storage());
+        let addr = runtime.block_on(fake_bigtable.serve());
+        let writer = LedgerStorage {
+            connection: bigtable::BigTableConnection::new_for_emulator(
+                "test",
+                "default",
+                &addr.to_string(),
+                None,
+                LedgerStorageConfig::default().max_message_size,
+            )
+            .unwrap()
+            .with_compression(CompressionConfig {
+                tx: CellCompression::ZstdDictionary,
+                ..CompressionConfig::default()
+            }),
+            stats: Arc::default(),
+        };
+
+        let train = |name: &str| {
+            let samples = (0..1000u32)
+                .map(|i| format!("{{\"{name}\":{i},\"index\":{}}}", i % 7).into_bytes())
+                .collect::<Vec<_>>();
+            compression::train_dictionary(&samples, 4096).unwrap()
+        };
+        let (old_dictionary, dictionary) = (train("old"), train("new"));
+        let mut bigtable = writer.connection.client();
+        // Dictionaries stored back-to-back must not overwrite each other
+        for dictionary in [&old_dictionary, &dictionary] {
+            runtime
+                .block_on(bigtable.put_compression_dictionary("tx", dictionary))
+                .unwrap();
+        }
+        assert_eq!(fake_bigtable.row_keys("dictionaries").len(), 2);
+        assert_eq!(
+            runtime
+                .block_on(writer.load_compression_dictionaries())
+                .unwrap(),
+            2
+        );
+
+        let slot = 10;
+        let transaction =
+            transfer_with_meta(&Keypair::new(), &Pubkey::new_unique(), default_meta());
+        let signature = transaction.transaction.signatures[0];
+        runtime
+            .block_on(
+                writer
+                    .upload_confirmed_block_with_entries(slot, new_block(slot, vec![transaction])),
+            )
+            .unwrap();
+
+        // Forget the dictionaries, as a reader in another process would not have them
+        let id = compression::dictionary_id(&dictionary);
+        compression::unregister_dictionary(compression::dictionary_id(&old_dictionary));
+        compression::unregister_dictionary(id);
+        let row_data = runtime
+            .block_on(bigtable.get_single_row_data("tx", signature.to_string()))
+            .unwrap();
+        assert_eq!(
+            compression::unregistered_dictionary_id(&row_data[0].1),
+            Some(id)
+        );
+
+        let confirmed_transaction = runtime
+            .block_on(reader.get_confirmed_transaction(&signature))
+            .unwrap()
+            .unwrap();
+        assert_eq!(confirmed_transaction.slot, slot);
+        assert_eq!(
+            compression::unregistered_dictionary_id(&row_data[0].1),
+            None
+        );
+    }
 }
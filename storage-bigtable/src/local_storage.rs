@@ -0,0 +1,600 @@
+//! The `local_storage` module implements long-term ledger storage as a RocksDB archive on the
+//! local filesystem, for self-hosted archival without BigTable. The archive has a column family
+//! for each BigTable table, holding the same row keys and cell encodings.
+use {
+    crate::{
+        bigtable::{
+            deserialize_bincode_cell_data, deserialize_protobuf_cell_data, RowData, RowKey,
+        },
+        block_index_rows, collect_signatures_for_address,
+        compression::{compress, CompressionMethod},
+        key_to_slot,
+        long_term_storage::LongTermLedgerStorage,
+        slot_to_blocks_key, slot_to_entries_key, slot_to_tx_by_addr_key, tx_rows_to_delete,
+        BlockCells, Error, Result, TransactionInfo,
+    },
+    async_trait::async_trait,
+    log::*,
+    rocksdb::{
+        ColumnFamily, ColumnFamilyDescriptor, Direction, IteratorMode, Options, WriteBatch, DB,
+    },
+    solana_sdk::{clock::Slot, pubkey::Pubkey, signature::Signature},
+    solana_storage_proto::convert::{entries, generated},
+    solana_transaction_status::{
+        ConfirmedBlock, ConfirmedTransactionStatusWithSignature,
+        ConfirmedTransactionWithStatusMeta, EntrySummary, TransactionStatus,
+        VersionedConfirmedBlockWithEntries,
+    },
+    std::{collections::HashMap, convert::TryInto, path::Path, sync::Arc},
+};
+
+const TABLES: [&str; 4] = ["blocks", "entries", "tx", "tx-by-addr"];
+
+#[derive(Clone)]
+pub struct LocalLedgerStorage {
+    db: Arc<DB>,
+}
+
+fn encode_bincode_cell<T: serde::Serialize>(data: &T) -> Result<Vec<u8>> {
+    Ok(compress(
+        CompressionMethod::Zstd,
+        &bincode::serialize(data).unwrap(),
+    )?)
+}
+
+fn encode_protobuf_cell<T: prost::Message>(data: &T) -> Result<Vec<u8>> {
+    let mut buf = Vec::with_capacity(data.encoded_len());
+    data.encode(&mut buf).unwrap();
+    Ok(compress(CompressionMethod::Zstd, &buf)?)
+}
+
+// Returns the transaction at `index` of the block, if its signature matches.
+fn transaction_from_block(
+    slot: Slot,
+    block: &ConfirmedBlock,
+    index: u32,
+    signature: &Signature,
+) -> Option<ConfirmedTransactionWithStatusMeta> {
+    match block.transactions.get(index as usize) {
+        Some(tx_with_meta) if tx_with_meta.transaction_signature() == signature => {
+            Some(ConfirmedTransactionWithStatusMeta {
+                slot,
+                tx_with_meta: tx_with_meta.clone(),
+                block_time: block.block_time,
+            })
+        }
+        _ => {
+            warn!(
+                "Transaction info or confirmed block for {} is corrupt",
+                signature
+            );
+            None
+        }
+    }
+}
+
+impl LocalLedgerStorage {
+    /// Opens the archive at `path`, creating it if it does not exist. The archive can only be
+    /// open by one process at a time.
+    pub fn open(path: &Path) -> Result<Self> {
+        let mut options = Options::default();
+        options.create_if_missing(true);
+        options.create_missing_column_families(true);
+        let column_families = TABLES
+            .iter()
+            .map(|table| ColumnFamilyDescriptor::new(*table, Options::default()));
+        let db = DB::open_cf_descriptors(&options, path, column_families)?;
+        Ok(Self { db: Arc::new(db) })
+    }
+
+    // Runs `f` on the blocking thread pool of the tokio runtime, as RocksDB reads and writes
+    // block the calling thread.
+    async fn run_blocking<T, F>(&self, f: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Self) -> Result<T> + Send + 'static,
+    {
+        let storage = self.clone();
+        tokio::task::spawn_blocking(move || f(&storage))
+            .await
+            .map_err(Error::TokioJoinError)?
+    }
+
+    fn cf(&self, table: &str) -> &ColumnFamily {
+        self.db.cf_handle(table).unwrap()
+    }
+
+    fn get_row_data(&self, table: &str, key: &str, cell_name: &str) -> Result<Option<RowData>> {
+        let value = self.db.get_cf(self.cf(table), key)?;
+        Ok(value.map(|value| vec![(cell_name.to_string(), value)]))
+    }
+
+    fn get_bincode_cell<T>(&self, table: &str, key: &str) -> Result<Option<T>>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let Some(row_data) = self.get_row_data(table, key, "bin")? else {
+            return Ok(None);
+        };
+        Ok(Some(deserialize_bincode_cell_data(
+            &row_data,
+            table,
+            key.to_string(),
+        )?))
+    }
+
+    fn get_protobuf_cell<P>(&self, table: &str, key: &str) -> Result<Option<P>>
+    where
+        P: prost::Message + Default,
+    {
+        let Some(row_data) = self.get_row_data(table, key, "proto")? else {
+            return Ok(None);
+        };
+        Ok(Some(deserialize_protobuf_cell_data(
+            &row_data,
+            table,
+            key.to_string(),
+        )?))
+    }
+
+    // Iterates over the row keys and cells of the table, in key order, starting at `start_at`.
+    fn rows<'a>(
+        &'a self,
+        table: &str,
+        start_at: &'a str,
+        cell_name: &'a str,
+    ) -> impl Iterator<Item = Result<(RowKey, RowData)>> + 'a {
+        self.db
+            .iterator_cf(
+                self.cf(table),
+                IteratorMode::From(start_at.as_bytes(), Direction::Forward),
+            )
+            .map(move |row| {
+                let (key, value) = row?;
+                let key = String::from_utf8_lossy(&key).into_owned();
+                Ok((key, vec![(cell_name.to_string(), value.into_vec())]))
+            })
+    }
+
+    fn get_optional_block(&self, slot: Slot) -> Result<Option<ConfirmedBlock>> {
+        let key = slot_to_blocks_key(slot);
+        let Some(block) = self.get_protobuf_cell::<generated::ConfirmedBlock>("blocks", &key)?
+        else {
+            return Ok(None);
+        };
+        let block = block
+            .try_into()
+            .map_err(|_err| crate::bigtable::Error::ObjectCorrupt(format!("blocks/{key}")))?;
+        Ok(Some(block))
+    }
+
+    fn get_transaction_info(&self, signature: &Signature) -> Result<TransactionInfo> {
+        self.get_bincode_cell("tx", &signature.to_string())?
+            .ok_or(Error::SignatureNotFound)
+    }
+
+    fn get_block(&self, slot: Slot) -> Result<ConfirmedBlock> {
+        self.get_optional_block(slot)?
+            .ok_or(Error::BlockNotFound(slot))
+    }
+}
+
+#[async_trait]
+impl LongTermLedgerStorage for LocalLedgerStorage {
+    async fn get_first_available_block(&self) -> Result<Option<Slot>> {
+        self.run_blocking(|storage| match storage.rows("blocks", "", "proto").next() {
+            None => Ok(None),
+            Some(row) => Ok(key_to_slot(&row?.0)),
+        })
+        .await
+    }
+
+    async fn get_confirmed_blocks(&self, start_slot: Slot, limit: usize) -> Result<Vec<Slot>> {
+        self.run_blocking(move |storage| {
+            let start_at = slot_to_blocks_key(start_slot);
+            let mut slots = Vec::new();
+            for row in storage.rows("blocks", &start_at, "proto").take(limit) {
+                slots.extend(key_to_slot(&row?.0));
+            }
+            Ok(slots)
+        })
+        .await
+    }
+
+    async fn get_confirmed_block(&self, slot: Slot) -> Result<ConfirmedBlock> {
+        self.run_blocking(move |storage| storage.get_block(slot))
+            .await
+    }
+
+    async fn confirmed_block_exists(&self, slot: Slot) -> Result<bool> {
+        self.run_blocking(move |storage| {
+            let key = slot_to_blocks_key(slot);
+            Ok(storage
+                .db
+                .get_pinned_cf(storage.cf("blocks"), key)?
+                .is_some())
+        })
+        .await
+    }
+
+    async fn get_entries(&self, slot: Slot) -> Result<Vec<EntrySummary>> {
+        self.run_blocking(move |storage| {
+            let entries = storage
+                .get_protobuf_cell::<entries::Entries>("entries", &slot_to_entries_key(slot))?
+                .ok_or(Error::BlockNotFound(slot))?;
+            Ok(entries.entries.into_iter().map(Into::into).collect())
+        })
+        .await
+    }
+
+    async fn get_signature_status(&self, signature: &Signature) -> Result<TransactionStatus> {
+        let signature = *signature;
+        self.run_blocking(move |storage| Ok(storage.get_transaction_info(&signature)?.into()))
+            .await
+    }
+
+    async fn get_confirmed_transactions(
+        &self,
+        signatures: &[Signature],
+    ) -> Result<Vec<ConfirmedTransactionWithStatusMeta>> {
+        let signatures = signatures.to_vec();
+        self.run_blocking(move |storage| {
+            let mut blocks = HashMap::<Slot, Option<ConfirmedBlock>>::new();
+            let mut transactions = Vec::new();
+            for signature in &signatures {
+                let Some(TransactionInfo { slot, index, .. }) =
+                    storage.get_bincode_cell("tx", &signature.to_string())?
+                else {
+                    continue;
+                };
+                if !blocks.contains_key(&slot) {
+                    blocks.insert(slot, storage.get_optional_block(slot)?);
+                }
+                if let Some(block) = &blocks[&slot] {
+                    transactions.extend(transaction_from_block(slot, block, index, signature));
+                }
+            }
+            Ok(transactions)
+        })
+        .await
+    }
+
+    async fn get_confirmed_transaction(
+        &self,
+        signature: &Signature,
+    ) -> Result<Option<ConfirmedTransactionWithStatusMeta>> {
+        let signature = *signature;
+        self.run_blocking(move |storage| {
+            let TransactionInfo { slot, index, .. } = storage.get_transaction_info(&signature)?;
+            let block = storage.get_block(slot)?;
+            Ok(transaction_from_block(slot, &block, index, &signature))
+        })
+        .await
+    }
+
+    async fn get_confirmed_signatures_for_address(
+        &self,
+        address: &Pubkey,
+        before_signature: Option<&Signature>,
+        until_signature: Option<&Signature>,
+        limit: usize,
+    ) -> Result<Vec<(ConfirmedTransactionStatusWithSignature, u32)>> {
+        let address_prefix = format!("{address}/");
+        let before_signature = before_signature.copied();
+        let until_signature = until_signature.copied();
+        self.run_blocking(move |storage| {
+            let (first_slot, before_transaction_index) = match before_signature {
+                None => (Slot::MAX, 0),
+                Some(before_signature) => {
+                    let TransactionInfo { slot, index, .. } =
+                        storage.get_transaction_info(&before_signature)?;
+                    (slot, index)
+                }
+            };
+            let (last_slot, until_transaction_index) = match until_signature {
+                None => (0, u32::MAX),
+                Some(until_signature) => {
+                    let TransactionInfo { slot, index, .. } =
+                        storage.get_transaction_info(&until_signature)?;
+                    (slot, index)
+                }
+            };
+            let start_at = format!("{}{}", address_prefix, slot_to_tx_by_addr_key(first_slot));
+            let end_at = format!("{}{}", address_prefix, slot_to_tx_by_addr_key(last_slot));
+            let rows = storage
+                .rows("tx-by-addr", &start_at, "proto")
+                .take_while(|row| match row {
+                    Ok((row_key, _)) => *row_key <= end_at,
+                    Err(_) => true,
+                });
+            collect_signatures_for_address(
+                &address_prefix,
+                rows,
+                first_slot,
+                before_transaction_index,
+                last_slot,
+                until_transaction_index,
+                limit,
+            )
+        })
+        .await
+    }
+
+    async fn upload_confirmed_block_with_entries(
+        &self,
+        slot: Slot,
+        confirmed_block: VersionedConfirmedBlockWithEntries,
+    ) -> Result<()> {
+        self.run_blocking(move |storage| {
+            let BlockCells {
+                tx_cells,
+                tx_by_addr_cells,
+                entries_cell,
+                blocks_cell,
+                num_transactions,
+                num_entries,
+            } = BlockCells::new(slot, confirmed_block);
+
+            // The block is written in the same batch as its metadata, so that partially
+            // uploaded blocks never become visible.
+            let mut batch = WriteBatch::default();
+            let mut bytes_written = 0;
+            let mut put = |table: &str, key: RowKey, value: Vec<u8>| {
+                bytes_written += value.len();
+                batch.put_cf(storage.cf(table), key, value);
+            };
+            for (key, tx_info) in tx_cells {
+                put("tx", key, encode_bincode_cell(&tx_info)?);
+            }
+            for (key, tx_by_addr) in tx_by_addr_cells {
+                put("tx-by-addr", key, encode_protobuf_cell(&tx_by_addr)?);
+            }
+            if let Some((key, entries)) = entries_cell {
+                put("entries", key, encode_protobuf_cell(&entries)?);
+            }
+            let (key, block) = blocks_cell;
+            put("blocks", key, encode_protobuf_cell(&block)?);
+            storage.db.write(batch)?;
+
+            datapoint_info!(
+                "storage-local-upload-block",
+                ("slot", slot, i64),
+                ("transactions", num_transactions, i64),
+                ("entries", num_entries, i64),
+                ("bytes", bytes_written, i64),
+            );
+            Ok(())
+        })
+        .await
+    }
+
+    async fn delete_confirmed_block(&self, slot: Slot, dry_run: bool) -> Result<()> {
+        self.run_blocking(move |storage| {
+            let confirmed_block = storage.get_block(slot)?;
+            let (address_slot_rows, expected_tx_infos) =
+                block_index_rows(slot, &confirmed_block);
+            let fetched_tx_infos = expected_tx_infos
+                .keys()
+                .filter_map(|signature| {
+                    let tx_info = storage.get_bincode_cell::<TransactionInfo>("tx", signature);
+                    let tx_info = tx_info.transpose()?.map(Into::into);
+                    Some((signature.clone(), tx_info))
+                })
+                .collect();
+            let tx_deletion_rows = tx_rows_to_delete(expected_tx_infos, fetched_tx_infos);
+            let entries_key = slot_to_entries_key(slot);
+            let entries_exist = storage
+                .db
+                .get_pinned_cf(storage.cf("entries"), &entries_key)?
+                .is_some();
+
+            if !dry_run {
+                let mut batch = WriteBatch::default();
+                for key in &address_slot_rows {
+                    batch.delete_cf(storage.cf("tx-by-addr"), key);
+                }
+                for key in &tx_deletion_rows {
+                    batch.delete_cf(storage.cf("tx"), key);
+                }
+                batch.delete_cf(storage.cf("entries"), entries_key);
+                batch.delete_cf(storage.cf("blocks"), slot_to_blocks_key(slot));
+                storage.db.write(batch)?;
+            }
+
+            info!(
+                "{}deleted ledger data for slot {}: {} transaction rows, {} address slot rows, {} entry row",
+                if dry_run { "[dry run] " } else { "" },
+                slot,
+                tx_deletion_rows.len(),
+                address_slot_rows.len(),
+                if entries_exist { "with" } else { "WITHOUT" }
+            );
+            Ok(())
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_sdk::{
+            hash::Hash, message::v0::LoadedAddresses, signature::Keypair, system_transaction,
+            transaction::VersionedTransaction, transaction_context::TransactionReturnData,
+        },
+        solana_transaction_status::{
+            TransactionStatusMeta, VersionedConfirmedBlock, VersionedTransactionWithStatusMeta,
+        },
+        tokio::runtime::Runtime,
+    };
+
+    fn transfer_block(slot: Slot, recipient: &Pubkey) -> (VersionedConfirmedBlock, Signature) {
+        let from = Keypair::new();
+        let transaction = system_transaction::transfer(&from, recipient, 42, Hash::new_unique());
+        let signature = transaction.signatures[0];
+        let block = VersionedConfirmedBlock {
+            previous_blockhash: Hash::default().to_string(),
+            blockhash: Hash::new_unique().to_string(),
+            parent_slot: slot.saturating_sub(1),
+            transactions: vec![VersionedTransactionWithStatusMeta {
+                transaction: VersionedTransaction::from(transaction),
+                meta: TransactionStatusMeta {
+                    status: Ok(()),
+                    fee: 1,
+                    pre_balances: vec![43, 0, 1],
+                    post_balances: vec![0, 42, 1],
+                    inner_instructions: Some(vec![]),
+                    log_messages: Some(vec![]),
+                    pre_token_balances: Some(vec![]),
+                    post_token_balances: Some(vec![]),
+                    rewards: Some(vec![]),
+                    loaded_addresses: LoadedAddresses::default(),
+                    return_data: Some(TransactionReturnData::default()),
+                    compute_units_consumed: Some(1234),
+                },
+            }],
+            rewards: vec![],
+            block_time: Some(1_234_567_890 + slot as i64),
+            block_height: Some(slot),
+        };
+        (block, signature)
+    }
+
+    fn signatures_for_address(
+        runtime: &Runtime,
+        storage: &LocalLedgerStorage,
+        address: &Pubkey,
+        before: Option<&Signature>,
+        until: Option<&Signature>,
+        limit: usize,
+    ) -> Vec<Signature> {
+        runtime
+            .block_on(storage.get_confirmed_signatures_for_address(address, before, until, limit))
+            .unwrap()
+            .into_iter()
+            .map(|(status, _index)| status.signature)
+            .collect()
+    }
+
+    #[test]
+    fn test_local_ledger_storage() {
+        let runtime = Runtime::new().unwrap();
+        let ledger_path = tempfile::tempdir().unwrap();
+        let storage = LocalLedgerStorage::open(ledger_path.path()).unwrap();
+        let recipient = Pubkey::new_unique();
+        assert_eq!(
+            runtime
+                .block_on(storage.get_first_available_block())
+                .unwrap(),
+            None
+        );
+
+        let mut signatures = vec![];
+        for slot in 1..=3 {
+            let (block, signature) = transfer_block(slot, &recipient);
+            runtime
+                .block_on(storage.upload_confirmed_block(slot, block))
+                .unwrap();
+            signatures.push(signature);
+        }
+        assert_eq!(
+            runtime
+                .block_on(storage.get_first_available_block())
+                .unwrap(),
+            Some(1)
+        );
+        assert_eq!(
+            runtime
+                .block_on(storage.get_confirmed_blocks(2, 10))
+                .unwrap(),
+            vec![2, 3]
+        );
+        assert!(runtime.block_on(storage.confirmed_block_exists(3)).unwrap());
+        assert!(!runtime.block_on(storage.confirmed_block_exists(4)).unwrap());
+
+        let transaction = runtime
+            .block_on(storage.get_confirmed_transaction(&signatures[1]))
+            .unwrap()
+            .unwrap();
+        assert_eq!(transaction.slot, 2);
+        assert_eq!(transaction.block_time, Some(1_234_567_892));
+        let status = runtime
+            .block_on(storage.get_signature_status(&signatures[1]))
+            .unwrap();
+        assert_eq!(status.slot, 2);
+        assert!(matches!(
+            runtime.block_on(storage.get_signature_status(&Signature::default())),
+            Err(Error::SignatureNotFound)
+        ));
+
+        // Signatures are returned newest first, and paginate on `before` and `until`.
+        assert_eq!(
+            signatures_for_address(&runtime, &storage, &recipient, None, None, 10),
+            vec![signatures[2], signatures[1], signatures[0]]
+        );
+        assert_eq!(
+            signatures_for_address(
+                &runtime,
+                &storage,
+                &recipient,
+                Some(&signatures[2]),
+                None,
+                1
+            ),
+            vec![signatures[1]]
+        );
+        assert_eq!(
+            signatures_for_address(
+                &runtime,
+                &storage,
+                &recipient,
+                None,
+                Some(&signatures[0]),
+                10
+            ),
+            vec![signatures[2], signatures[1]]
+        );
+
+        runtime
+            .block_on(storage.delete_confirmed_block(2, /*dry_run:*/ true))
+            .unwrap();
+        assert!(runtime.block_on(storage.confirmed_block_exists(2)).unwrap());
+        runtime
+            .block_on(storage.delete_confirmed_block(2, /*dry_run:*/ false))
+            .unwrap();
+        assert!(matches!(
+            runtime.block_on(storage.get_confirmed_block(2)),
+            Err(Error::BlockNotFound(2))
+        ));
+        assert_eq!(
+            runtime
+                .block_on(storage.get_confirmed_blocks(0, 10))
+                .unwrap(),
+            vec![1, 3]
+        );
+        assert_eq!(
+            signatures_for_address(&runtime, &storage, &recipient, None, None, 10),
+            vec![signatures[2], signatures[0]]
+        );
+        assert_eq!(
+            runtime
+                .block_on(storage.get_confirmed_transactions(&signatures))
+                .unwrap()
+                .len(),
+            2
+        );
+
+        // Data persists across reopening the archive.
+        drop(storage);
+        let storage = LocalLedgerStorage::open(ledger_path.path()).unwrap();
+        assert_eq!(
+            runtime
+                .block_on(storage.get_confirmed_block(3))
+                .unwrap()
+                .transactions[0]
+                .transaction_signature(),
+            &signatures[2]
+        );
+    }
+}
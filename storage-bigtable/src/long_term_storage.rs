@@ -0,0 +1,162 @@
+//! The `long_term_storage` module defines the interface through which RPC historical queries and
+//! block uploads reach long-term ledger storage, so that a local archive can stand in for
+//! BigTable.
+use {
+    crate::{LedgerStorage, Result},
+    async_trait::async_trait,
+    solana_sdk::{clock::Slot, pubkey::Pubkey, signature::Signature},
+    solana_transaction_status::{
+        ConfirmedBlock, ConfirmedTransactionStatusWithSignature,
+        ConfirmedTransactionWithStatusMeta, EntrySummary, TransactionStatus,
+        VersionedConfirmedBlock, VersionedConfirmedBlockWithEntries,
+    },
+};
+
+#[async_trait]
+pub trait LongTermLedgerStorage: Send + Sync {
+    /// Return the available slot that contains a block
+    async fn get_first_available_block(&self) -> Result<Option<Slot>>;
+
+    /// Fetch the next slots after the provided slot that contains a block
+    ///
+    /// start_slot: slot to start the search from (inclusive)
+    /// limit: stop after this many slots have been found
+    async fn get_confirmed_blocks(&self, start_slot: Slot, limit: usize) -> Result<Vec<Slot>>;
+
+    /// Fetch the confirmed block from the desired slot
+    async fn get_confirmed_block(&self, slot: Slot) -> Result<ConfirmedBlock>;
+
+    /// Does the confirmed block exist in storage
+    async fn confirmed_block_exists(&self, slot: Slot) -> Result<bool>;
+
+    /// Fetch the entries of the block in the desired slot
+    async fn get_entries(&self, slot: Slot) -> Result<Vec<EntrySummary>>;
+
+    async fn get_signature_status(&self, signature: &Signature) -> Result<TransactionStatus>;
+
+    /// Fetch confirmed transactions; signatures which are not found are skipped
+    async fn get_confirmed_transactions(
+        &self,
+        signatures: &[Signature],
+    ) -> Result<Vec<ConfirmedTransactionWithStatusMeta>>;
+
+    /// Fetch a confirmed transaction
+    async fn get_confirmed_transaction(
+        &self,
+        signature: &Signature,
+    ) -> Result<Option<ConfirmedTransactionWithStatusMeta>>;
+
+    /// Get confirmed signatures for the provided address, in descending ledger order
+    ///
+    /// address: address to search for
+    /// before_signature: start with the first signature older than this one
+    /// until_signature: end with the last signature more recent than this one
+    /// limit: stop after this many signatures
+    async fn get_confirmed_signatures_for_address(
+        &self,
+        address: &Pubkey,
+        before_signature: Option<&Signature>,
+        until_signature: Option<&Signature>,
+        limit: usize,
+    ) -> Result<
+        Vec<(
+            ConfirmedTransactionStatusWithSignature,
+            u32, /*slot index*/
+        )>,
+    >;
+
+    /// Upload a new confirmed block and associated meta data.
+    async fn upload_confirmed_block(
+        &self,
+        slot: Slot,
+        confirmed_block: VersionedConfirmedBlock,
+    ) -> Result<()> {
+        self.upload_confirmed_block_with_entries(
+            slot,
+            VersionedConfirmedBlockWithEntries {
+                block: confirmed_block,
+                entries: vec![],
+            },
+        )
+        .await
+    }
+
+    async fn upload_confirmed_block_with_entries(
+        &self,
+        slot: Slot,
+        confirmed_block: VersionedConfirmedBlockWithEntries,
+    ) -> Result<()>;
+
+    /// Delete a confirmed block and associated meta data.
+    async fn delete_confirmed_block(&self, slot: Slot, dry_run: bool) -> Result<()>;
+}
+
+#[async_trait]
+impl LongTermLedgerStorage for LedgerStorage {
+    async fn get_first_available_block(&self) -> Result<Option<Slot>> {
+        LedgerStorage::get_first_available_block(self).await
+    }
+
+    async fn get_confirmed_blocks(&self, start_slot: Slot, limit: usize) -> Result<Vec<Slot>> {
+        LedgerStorage::get_confirmed_blocks(self, start_slot, limit).await
+    }
+
+    async fn get_confirmed_block(&self, slot: Slot) -> Result<ConfirmedBlock> {
+        LedgerStorage::get_confirmed_block(self, slot).await
+    }
+
+    async fn confirmed_block_exists(&self, slot: Slot) -> Result<bool> {
+        LedgerStorage::confirmed_block_exists(self, slot).await
+    }
+
+    async fn get_entries(&self, slot: Slot) -> Result<Vec<EntrySummary>> {
+        Ok(LedgerStorage::get_entries(self, slot).await?.collect())
+    }
+
+    async fn get_signature_status(&self, signature: &Signature) -> Result<TransactionStatus> {
+        LedgerStorage::get_signature_status(self, signature).await
+    }
+
+    async fn get_confirmed_transactions(
+        &self,
+        signatures: &[Signature],
+    ) -> Result<Vec<ConfirmedTransactionWithStatusMeta>> {
+        LedgerStorage::get_confirmed_transactions(self, signatures).await
+    }
+
+    async fn get_confirmed_transaction(
+        &self,
+        signature: &Signature,
+    ) -> Result<Option<ConfirmedTransactionWithStatusMeta>> {
+        LedgerStorage::get_confirmed_transaction(self, signature).await
+    }
+
+    async fn get_confirmed_signatures_for_address(
+        &self,
+        address: &Pubkey,
+        before_signature: Option<&Signature>,
+        until_signature: Option<&Signature>,
+        limit: usize,
+    ) -> Result<Vec<(ConfirmedTransactionStatusWithSignature, u32)>> {
+        LedgerStorage::get_confirmed_signatures_for_address(
+            self,
+            address,
+            before_signature,
+            until_signature,
+            limit,
+        )
+        .await
+    }
+
+    async fn upload_confirmed_block_with_entries(
+        &self,
+        slot: Slot,
+        confirmed_block: VersionedConfirmedBlockWithEntries,
+    ) -> Result<()> {
+        LedgerStorage::upload_confirmed_block_with_entries(self, slot, confirmed_block).await
+    }
+
+    async fn delete_confirmed_block(&self, slot: Slot, dry_run: bool) -> Result<()> {
+        LedgerStorage::delete_confirmed_block(self, slot, dry_run).await
+    }
+}
@@ -0,0 +1,151 @@
+//! Per connection stats of a QUIC streamer server, so that operators can see
+//! how the streams of a given client are accepted or throttled.
+
+use {
+    crate::nonblocking::quic::ConnectionPeerType,
+    quinn::Connection,
+    solana_sdk::pubkey::Pubkey,
+    std::{
+        cmp::Reverse,
+        collections::HashMap,
+        net::SocketAddr,
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Arc, RwLock,
+        },
+        time::{Duration, Instant},
+    },
+};
+
+/// Snapshot of the stats of an open connection.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QuicConnectionStats {
+    pub remote_addr: SocketAddr,
+    /// Identity of the client, if it presented a certificate
+    pub pubkey: Option<Pubkey>,
+    pub stake: u64,
+    /// Time since the connection was established
+    pub age: Duration,
+    pub streams_accepted: u64,
+    /// Streams stopped by the server because the client exceeded its
+    /// stream budget
+    pub streams_throttled: u64,
+    /// Streams which were accepted but failed or timed out before a whole
+    /// packet was received
+    pub streams_dropped: u64,
+    pub packets_received: u64,
+    pub bytes_received: u64,
+    pub rtt: Duration,
+}
+
+#[derive(Default)]
+pub(crate) struct ConnectionCounters {
+    pub(crate) streams_accepted: AtomicU64,
+    pub(crate) streams_throttled: AtomicU64,
+    pub(crate) streams_dropped: AtomicU64,
+    pub(crate) packets_received: AtomicU64,
+    pub(crate) bytes_received: AtomicU64,
+}
+
+struct ConnectionRecord {
+    connection: Connection,
+    pubkey: Option<Pubkey>,
+    stake: u64,
+    start: Instant,
+    counters: Arc<ConnectionCounters>,
+}
+
+impl ConnectionRecord {
+    fn stats(&self) -> QuicConnectionStats {
+        let counters = &self.counters;
+        QuicConnectionStats {
+            remote_addr: self.connection.remote_address(),
+            pubkey: self.pubkey,
+            stake: self.stake,
+            age: self.start.elapsed(),
+            streams_accepted: counters.streams_accepted.load(Ordering::Relaxed),
+            streams_throttled: counters.streams_throttled.load(Ordering::Relaxed),
+            streams_dropped: counters.streams_dropped.load(Ordering::Relaxed),
+            packets_received: counters.packets_received.load(Ordering::Relaxed),
+            bytes_received: counters.bytes_received.load(Ordering::Relaxed),
+            rtt: self.connection.rtt(),
+        }
+    }
+}
+
+/// Open connections of a server, keyed by their stable id.
+#[derive(Default)]
+pub struct ConnectionStatsTable {
+    connections: RwLock<HashMap<usize, ConnectionRecord>>,
+}
+
+impl ConnectionStatsTable {
+    pub(crate) fn add(
+        &self,
+        connection: &Connection,
+        pubkey: Option<Pubkey>,
+        peer_type: ConnectionPeerType,
+    ) -> Arc<ConnectionCounters> {
+        let stake = match peer_type {
+            ConnectionPeerType::Unstaked => 0,
+            ConnectionPeerType::Staked(stake) => stake,
+        };
+        let counters = Arc::<ConnectionCounters>::default();
+        let record = ConnectionRecord {
+            connection: connection.clone(),
+            pubkey,
+            stake,
+            start: Instant::now(),
+            counters: counters.clone(),
+        };
+        self.connections
+            .write()
+            .unwrap()
+            .insert(connection.stable_id(), record);
+        counters
+    }
+
+    pub(crate) fn remove(&self, stable_id: usize) {
+        self.connections.write().unwrap().remove(&stable_id);
+    }
+
+    /// Returns the stats of the open connections, the most throttled first.
+    pub fn snapshot(&self) -> Vec<QuicConnectionStats> {
+        let mut stats: Vec<_> = self
+            .connections
+            .read()
+            .unwrap()
+            .values()
+            .map(ConnectionRecord::stats)
+            .collect();
+        stats.sort_unstable_by_key(|stats| {
+            (
+                Reverse(stats.streams_throttled),
+                Reverse(stats.streams_accepted),
+            )
+        });
+        stats
+    }
+
+    /// Logs the stats of the `num` most throttled connections.
+    pub(crate) fn report(&self, name: &'static str, num: usize) {
+        let stats = self.snapshot();
+        info!("{name}: {} open connections", stats.len());
+        for stats in stats.iter().take(num) {
+            info!(
+                "{name}: connection {} pubkey {:?} stake {} age {:?} streams accepted {} \
+                 throttled {} dropped {} packets {} bytes {} rtt {:?}",
+                stats.remote_addr,
+                stats.pubkey,
+                stats.stake,
+                stats.age,
+                stats.streams_accepted,
+                stats.streams_throttled,
+                stats.streams_dropped,
+                stats.packets_received,
+                stats.bytes_received,
+                stats.rtt,
+            );
+        }
+    }
+}
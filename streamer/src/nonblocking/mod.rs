@@ -1,3 +1,4 @@
+pub mod connection_stats;
 pub mod quic;
 pub mod recvmmsg;
 pub mod sendmmsg;
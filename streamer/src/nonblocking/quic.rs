@@ -1,7 +1,10 @@
 use {
     crate::{
-        nonblocking::stream_throttle::{
-            ConnectionStreamCounter, StakedStreamLoadEMA, STREAM_STOP_CODE_THROTTLING,
+        nonblocking::{
+            connection_stats::ConnectionCounters,
+            stream_throttle::{
+                ConnectionStreamCounter, StakedStreamLoadEMA, STREAM_STOP_CODE_THROTTLING,
+            },
         },
         quic::{configure_server, QuicServerError, StreamStats},
         streamer::StakedNodes,
@@ -56,7 +59,11 @@ use {
 };
 
 const WAIT_FOR_STREAM_TIMEOUT: Duration = Duration::from_millis(100);
+const CONNECTION_STATS_REPORT_INTERVAL: Duration = Duration::from_secs(60);
+const CONNECTION_STATS_REPORT_COUNT: usize = 20;
 pub const DEFAULT_WAIT_FOR_CHUNK_TIMEOUT: Duration = Duration::from_secs(10);
+/// Limit to 250K PPS
+pub const DEFAULT_MAX_STREAMS_PER_MS: u64 = 250;
 
 pub const ALPN_TPU_PROTOCOL_ID: &[u8] = b"solana-tpu";
 
@@ -119,6 +126,7 @@ pub fn spawn_server(
     staked_nodes: Arc<RwLock<StakedNodes>>,
     max_staked_connections: usize,
     max_unstaked_connections: usize,
+    max_streams_per_ms: u64,
     wait_for_chunk_timeout: Duration,
     coalesce: Duration,
 ) -> Result<(Endpoint, Arc<StreamStats>, JoinHandle<()>), QuicServerError> {
@@ -142,6 +150,7 @@ pub fn spawn_server(
         staked_nodes,
         max_staked_connections,
         max_unstaked_connections,
+        max_streams_per_ms,
         stats.clone(),
         wait_for_chunk_timeout,
         coalesce,
@@ -159,6 +168,7 @@ async fn run_server(
     staked_nodes: Arc<RwLock<StakedNodes>>,
     max_staked_connections: usize,
     max_unstaked_connections: usize,
+    max_streams_per_ms: u64,
     stats: Arc<StreamStats>,
     wait_for_chunk_timeout: Duration,
     coalesce: Duration,
@@ -166,11 +176,13 @@ async fn run_server(
     const WAIT_FOR_CONNECTION_TIMEOUT: Duration = Duration::from_secs(1);
     debug!("spawn quic server");
     let mut last_datapoint = Instant::now();
+    let mut last_connection_stats_report = Instant::now();
     let unstaked_connection_table: Arc<Mutex<ConnectionTable>> =
         Arc::new(Mutex::new(ConnectionTable::new()));
     let stream_load_ema = Arc::new(StakedStreamLoadEMA::new(
         max_unstaked_connections > 0,
         stats.clone(),
+        max_streams_per_ms,
     ));
     let staked_connection_table: Arc<Mutex<ConnectionTable>> =
         Arc::new(Mutex::new(ConnectionTable::new()));
@@ -189,6 +201,12 @@ async fn run_server(
             stats.report(name);
             last_datapoint = Instant::now();
         }
+        if last_connection_stats_report.elapsed() >= CONNECTION_STATS_REPORT_INTERVAL {
+            stats
+                .connections
+                .report(name, CONNECTION_STATS_REPORT_COUNT);
+            last_connection_stats_report = Instant::now();
+        }
 
         if let Ok(Some(connection)) = timeout_connection {
             info!("Got a connection {:?}", connection.remote_address());
@@ -737,6 +755,10 @@ async fn handle_connection(
         stats.total_connections.load(Ordering::Relaxed),
     );
     let stable_id = connection.stable_id();
+    let connection_stats =
+        stats
+            .connections
+            .add(&connection, params.remote_pubkey, params.peer_type);
     stats.total_connections.fetch_add(1, Ordering::Relaxed);
     while !stream_exit.load(Ordering::Relaxed) {
         if let Ok(stream) =
@@ -755,11 +777,17 @@ async fn handle_connection(
                         >= max_streams_per_throttling_interval
                     {
                         stats.throttled_streams.fetch_add(1, Ordering::Relaxed);
+                        connection_stats
+                            .streams_throttled
+                            .fetch_add(1, Ordering::Relaxed);
                         let _ = stream.stop(VarInt::from_u32(STREAM_STOP_CODE_THROTTLING));
                         continue;
                     }
                     stream_load_ema.increment_load(params.peer_type);
                     stream_counter.stream_count.fetch_add(1, Ordering::Relaxed);
+                    connection_stats
+                        .streams_accepted
+                        .fetch_add(1, Ordering::Relaxed);
                     stats.total_streams.fetch_add(1, Ordering::Relaxed);
                     stats.total_new_streams.fetch_add(1, Ordering::Relaxed);
                     let stream_exit = stream_exit.clone();
@@ -767,6 +795,7 @@ async fn handle_connection(
                     let packet_sender = params.packet_sender.clone();
                     let last_update = last_update.clone();
                     let stream_load_ema = stream_load_ema.clone();
+                    let connection_stats = connection_stats.clone();
                     tokio::spawn(async move {
                         let mut maybe_batch = None;
                         // The min is to guard against a value too small which can wake up unnecessarily
@@ -790,6 +819,7 @@ async fn handle_connection(
                                     &remote_addr,
                                     &packet_sender,
                                     stats.clone(),
+                                    &connection_stats,
                                     params.peer_type,
                                 )
                                 .await
@@ -803,6 +833,9 @@ async fn handle_connection(
                                 stats
                                     .total_stream_read_timeouts
                                     .fetch_add(1, Ordering::Relaxed);
+                                connection_stats
+                                    .streams_dropped
+                                    .fetch_add(1, Ordering::Relaxed);
                                 break;
                             }
                         }
@@ -818,6 +851,7 @@ async fn handle_connection(
         }
     }
 
+    stats.connections.remove(stable_id);
     let removed_connection_count = connection_table.lock().await.remove_connection(
         ConnectionTableKey::new(remote_addr.ip(), params.remote_pubkey),
         remote_addr.port(),
@@ -842,8 +876,15 @@ async fn handle_chunk(
     remote_addr: &SocketAddr,
     packet_sender: &AsyncSender<PacketAccumulator>,
     stats: Arc<StreamStats>,
+    connection_stats: &ConnectionCounters,
     peer_type: ConnectionPeerType,
 ) -> bool {
+    let drop_stream = || {
+        connection_stats
+            .streams_dropped
+            .fetch_add(1, Ordering::Relaxed);
+        true
+    };
     match chunk {
         Ok(maybe_chunk) => {
             if let Some(chunk) = maybe_chunk {
@@ -853,16 +894,16 @@ async fn handle_chunk(
                 // shouldn't happen, but sanity check the size and offsets
                 if chunk.offset > PACKET_DATA_SIZE as u64 || chunk_len > PACKET_DATA_SIZE as u64 {
                     stats.total_invalid_chunks.fetch_add(1, Ordering::Relaxed);
-                    return true;
+                    return drop_stream();
                 }
                 let Some(end_of_chunk) = chunk.offset.checked_add(chunk_len) else {
-                    return true;
+                    return drop_stream();
                 };
                 if end_of_chunk > PACKET_DATA_SIZE as u64 {
                     stats
                         .total_invalid_chunk_size
                         .fetch_add(1, Ordering::Relaxed);
-                    return true;
+                    return drop_stream();
                 }
 
                 // chunk looks valid
@@ -879,7 +920,7 @@ async fn handle_chunk(
                     let offset = chunk.offset;
                     let Some(end_of_chunk) = (chunk.offset as usize).checked_add(chunk.bytes.len())
                     else {
-                        return true;
+                        return drop_stream();
                     };
                     accum.chunks.push(PacketChunk {
                         bytes: chunk.bytes,
@@ -911,6 +952,7 @@ async fn handle_chunk(
                             .total_handle_chunk_to_packet_batcher_send_err
                             .fetch_add(1, Ordering::Relaxed);
                         trace!("packet batch send error {:?}", err);
+                        drop_stream();
                     } else {
                         stats
                             .total_packets_sent_for_batching
@@ -921,6 +963,12 @@ async fn handle_chunk(
                         stats
                             .total_chunks_sent_for_batching
                             .fetch_add(chunks_sent, Ordering::Relaxed);
+                        connection_stats
+                            .packets_received
+                            .fetch_add(1, Ordering::Relaxed);
+                        connection_stats
+                            .bytes_received
+                            .fetch_add(bytes_sent as u64, Ordering::Relaxed);
 
                         trace!("sent {} byte packet for batching", bytes_sent);
                     }
@@ -928,6 +976,7 @@ async fn handle_chunk(
                     stats
                         .total_packet_batches_none
                         .fetch_add(1, Ordering::Relaxed);
+                    drop_stream();
                 }
                 return true;
             }
@@ -937,7 +986,7 @@ async fn handle_chunk(
             stats
                 .total_stream_read_errors
                 .fetch_add(1, Ordering::Relaxed);
-            return true;
+            return drop_stream();
         }
     }
     false
@@ -1249,6 +1298,7 @@ pub mod test {
             staked_nodes,
             MAX_STAKED_CONNECTIONS,
             MAX_UNSTAKED_CONNECTIONS,
+            DEFAULT_MAX_STREAMS_PER_MS,
             Duration::from_secs(2),
             DEFAULT_TPU_COALESCE,
         )
@@ -1488,6 +1538,47 @@ pub mod test {
         handle.await.unwrap();
     }
 
+    #[tokio::test]
+    async fn test_quic_connection_stats() {
+        solana_logger::setup();
+        let (t, exit, receiver, server_address, stats) = setup_quic_server(None, 1);
+        let client_keypair = Keypair::new();
+        let conn = make_client_endpoint(&server_address, Some(&client_keypair)).await;
+        let num_packets = 3;
+        for _ in 0..num_packets {
+            let mut s = conn.open_uni().await.unwrap();
+            s.write_all(&[1u8; 10]).await.unwrap();
+            s.finish().await.unwrap();
+        }
+        let mut num_received = 0;
+        while num_received < num_packets {
+            let packets = receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+            num_received += packets.len();
+        }
+
+        let connection_stats = stats.connection_stats();
+        assert_eq!(connection_stats.len(), 1);
+        let connection_stats = &connection_stats[0];
+        assert_eq!(connection_stats.pubkey, Some(client_keypair.pubkey()));
+        assert_eq!(connection_stats.stake, 0);
+        assert_eq!(connection_stats.streams_accepted, num_packets as u64);
+        assert_eq!(connection_stats.streams_throttled, 0);
+        assert_eq!(connection_stats.streams_dropped, 0);
+        assert_eq!(connection_stats.packets_received, num_packets as u64);
+        assert_eq!(connection_stats.bytes_received, 10 * num_packets as u64);
+
+        // Closed connections are removed from the table.
+        conn.close(0u32.into(), b"done");
+        let start = Instant::now();
+        while !stats.connection_stats().is_empty() {
+            assert!(start.elapsed() < Duration::from_secs(10));
+            sleep(Duration::from_millis(100)).await;
+        }
+
+        exit.store(true, Ordering::Relaxed);
+        t.await.unwrap();
+    }
+
     #[tokio::test]
     async fn test_quic_stream_timeout() {
         solana_logger::setup();
@@ -1683,6 +1774,7 @@ pub mod test {
             staked_nodes,
             MAX_STAKED_CONNECTIONS,
             0, // Do not allow any connection from unstaked clients/nodes
+            DEFAULT_MAX_STREAMS_PER_MS,
             DEFAULT_WAIT_FOR_CHUNK_TIMEOUT,
             DEFAULT_TPU_COALESCE,
         )
@@ -1712,6 +1804,7 @@ pub mod test {
             staked_nodes,
             MAX_STAKED_CONNECTIONS,
             MAX_UNSTAKED_CONNECTIONS,
+            DEFAULT_MAX_STREAMS_PER_MS,
             DEFAULT_WAIT_FOR_CHUNK_TIMEOUT,
             DEFAULT_TPU_COALESCE,
         )
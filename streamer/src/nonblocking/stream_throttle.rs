@@ -14,8 +14,6 @@ use {
     },
 };
 
-/// Limit to 250K PPS
-const MAX_STREAMS_PER_MS: u64 = 250;
 const MAX_UNSTAKED_STREAMS_PERCENT: u64 = 20;
 const STREAM_THROTTLING_INTERVAL_MS: u64 = 100;
 pub const STREAM_STOP_CODE_THROTTLING: u32 = 15;
@@ -38,13 +36,17 @@ pub(crate) struct StakedStreamLoadEMA {
 }
 
 impl StakedStreamLoadEMA {
-    pub(crate) fn new(allow_unstaked_streams: bool, stats: Arc<StreamStats>) -> Self {
+    pub(crate) fn new(
+        allow_unstaked_streams: bool,
+        stats: Arc<StreamStats>,
+        max_streams_per_ms: u64,
+    ) -> Self {
         let max_staked_load_in_ema_window = if allow_unstaked_streams {
-            (MAX_STREAMS_PER_MS
-                - Percentage::from(MAX_UNSTAKED_STREAMS_PERCENT).apply_to(MAX_STREAMS_PER_MS))
+            (max_streams_per_ms
+                - Percentage::from(MAX_UNSTAKED_STREAMS_PERCENT).apply_to(max_streams_per_ms))
                 * EMA_WINDOW_MS
         } else {
-            MAX_STREAMS_PER_MS * EMA_WINDOW_MS
+            max_streams_per_ms * EMA_WINDOW_MS
         };
 
         let max_num_unstaked_connections =
@@ -57,7 +59,7 @@ impl StakedStreamLoadEMA {
             });
 
         let max_unstaked_load_in_throttling_window = Percentage::from(MAX_UNSTAKED_STREAMS_PERCENT)
-            .apply_to(MAX_STREAMS_PER_MS * STREAM_THROTTLING_INTERVAL_MS)
+            .apply_to(max_streams_per_ms * STREAM_THROTTLING_INTERVAL_MS)
             .saturating_div(max_num_unstaked_connections);
 
         Self {
@@ -225,7 +227,12 @@ impl ConnectionStreamCounter {
 pub mod test {
     use {
         super::*,
-        crate::{nonblocking::stream_throttle::STREAM_LOAD_EMA_INTERVAL_MS, quic::StreamStats},
+        crate::{
+            nonblocking::{
+                quic::DEFAULT_MAX_STREAMS_PER_MS, stream_throttle::STREAM_LOAD_EMA_INTERVAL_MS,
+            },
+            quic::StreamStats,
+        },
         std::{
             sync::{atomic::Ordering, Arc},
             time::{Duration, Instant},
@@ -237,6 +244,7 @@ pub mod test {
         let load_ema = Arc::new(StakedStreamLoadEMA::new(
             true,
             Arc::new(StreamStats::default()),
+            DEFAULT_MAX_STREAMS_PER_MS,
         ));
         // 25K packets per ms * 20% / 500 max unstaked connections
         assert_eq!(
@@ -253,6 +261,7 @@ pub mod test {
         let load_ema = Arc::new(StakedStreamLoadEMA::new(
             true,
             Arc::new(StreamStats::default()),
+            DEFAULT_MAX_STREAMS_PER_MS,
         ));
 
         // EMA load is used for staked connections to calculate max number of allowed streams.
@@ -344,6 +353,7 @@ pub mod test {
         let load_ema = Arc::new(StakedStreamLoadEMA::new(
             false,
             Arc::new(StreamStats::default()),
+            DEFAULT_MAX_STREAMS_PER_MS,
         ));
 
         // EMA load is used for staked connections to calculate max number of allowed streams.
@@ -431,6 +441,7 @@ pub mod test {
         let stream_load_ema = Arc::new(StakedStreamLoadEMA::new(
             true,
             Arc::new(StreamStats::default()),
+            DEFAULT_MAX_STREAMS_PER_MS,
         ));
         stream_load_ema
             .load_in_recent_interval
@@ -459,6 +470,7 @@ pub mod test {
         let stream_load_ema = Arc::new(StakedStreamLoadEMA::new(
             true,
             Arc::new(StreamStats::default()),
+            DEFAULT_MAX_STREAMS_PER_MS,
         ));
         stream_load_ema
             .load_in_recent_interval
@@ -478,6 +490,7 @@ pub mod test {
         let stream_load_ema = Arc::new(StakedStreamLoadEMA::new(
             true,
             Arc::new(StreamStats::default()),
+            DEFAULT_MAX_STREAMS_PER_MS,
         ));
         stream_load_ema
             .load_in_recent_interval
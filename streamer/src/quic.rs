@@ -1,6 +1,10 @@
 use {
     crate::{
-        nonblocking::quic::ALPN_TPU_PROTOCOL_ID, streamer::StakedNodes,
+        nonblocking::{
+            connection_stats::{ConnectionStatsTable, QuicConnectionStats},
+            quic::ALPN_TPU_PROTOCOL_ID,
+        },
+        streamer::StakedNodes,
         tls_certificates::new_dummy_x509_certificate,
     },
     crossbeam_channel::Sender,
@@ -40,6 +44,7 @@ pub struct SpawnServerResult {
     pub endpoint: Endpoint,
     pub thread: thread::JoinHandle<()>,
     pub key_updater: Arc<EndpointKeyUpdater>,
+    pub stats: Arc<StreamStats>,
 }
 
 impl rustls::server::ClientCertVerifier for SkipClientVerification {
@@ -175,9 +180,15 @@ pub struct StreamStats {
     pub(crate) stream_load_ema: AtomicUsize,
     pub(crate) stream_load_ema_overflow: AtomicUsize,
     pub(crate) stream_load_capacity_overflow: AtomicUsize,
+    pub(crate) connections: ConnectionStatsTable,
 }
 
 impl StreamStats {
+    /// Returns the stats of the open connections, the most throttled first.
+    pub fn connection_stats(&self) -> Vec<QuicConnectionStats> {
+        self.connections.snapshot()
+    }
+
     pub fn report(&self, name: &'static str) {
         datapoint_info!(
             name,
@@ -441,11 +452,12 @@ pub fn spawn_server(
     staked_nodes: Arc<RwLock<StakedNodes>>,
     max_staked_connections: usize,
     max_unstaked_connections: usize,
+    max_streams_per_ms: u64,
     wait_for_chunk_timeout: Duration,
     coalesce: Duration,
 ) -> Result<SpawnServerResult, QuicServerError> {
     let runtime = rt(format!("{thread_name}Rt"));
-    let (endpoint, _stats, task) = {
+    let (endpoint, stats, task) = {
         let _guard = runtime.enter();
         crate::nonblocking::quic::spawn_server(
             metrics_name,
@@ -457,6 +469,7 @@ pub fn spawn_server(
             staked_nodes,
             max_staked_connections,
             max_unstaked_connections,
+            max_streams_per_ms,
             wait_for_chunk_timeout,
             coalesce,
         )
@@ -476,6 +489,7 @@ pub fn spawn_server(
         endpoint,
         thread: handle,
         key_updater: Arc::new(updater),
+        stats,
     })
 }
 
@@ -483,7 +497,9 @@ pub fn spawn_server(
 mod test {
     use {
         super::*,
-        crate::nonblocking::quic::{test::*, DEFAULT_WAIT_FOR_CHUNK_TIMEOUT},
+        crate::nonblocking::quic::{
+            test::*, DEFAULT_MAX_STREAMS_PER_MS, DEFAULT_WAIT_FOR_CHUNK_TIMEOUT,
+        },
         crossbeam_channel::unbounded,
         solana_sdk::net::DEFAULT_TPU_COALESCE,
         std::net::SocketAddr,
@@ -505,6 +521,7 @@ mod test {
             endpoint: _,
             thread: t,
             key_updater: _,
+            stats: _,
         } = spawn_server(
             "solQuicTest",
             "quic_streamer_test",
@@ -516,6 +533,7 @@ mod test {
             staked_nodes,
             MAX_STAKED_CONNECTIONS,
             MAX_UNSTAKED_CONNECTIONS,
+            DEFAULT_MAX_STREAMS_PER_MS,
             DEFAULT_WAIT_FOR_CHUNK_TIMEOUT,
             DEFAULT_TPU_COALESCE,
         )
@@ -564,6 +582,7 @@ mod test {
             endpoint: _,
             thread: t,
             key_updater: _,
+            stats: _,
         } = spawn_server(
             "solQuicTest",
             "quic_streamer_test",
@@ -575,6 +594,7 @@ mod test {
             staked_nodes,
             MAX_STAKED_CONNECTIONS,
             MAX_UNSTAKED_CONNECTIONS,
+            DEFAULT_MAX_STREAMS_PER_MS,
             DEFAULT_WAIT_FOR_CHUNK_TIMEOUT,
             DEFAULT_TPU_COALESCE,
         )
@@ -610,6 +630,7 @@ mod test {
             endpoint: _,
             thread: t,
             key_updater: _,
+            stats: _,
         } = spawn_server(
             "solQuicTest",
             "quic_streamer_test",
@@ -621,6 +642,7 @@ mod test {
             staked_nodes,
             MAX_STAKED_CONNECTIONS,
             0, // Do not allow any connection from unstaked clients/nodes
+            DEFAULT_MAX_STREAMS_PER_MS,
             DEFAULT_WAIT_FOR_CHUNK_TIMEOUT,
             DEFAULT_TPU_COALESCE,
         )
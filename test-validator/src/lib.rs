@@ -1,7 +1,7 @@
 #![allow(clippy::arithmetic_side_effects)]
 use {
     base64::{prelude::BASE64_STANDARD, Engine},
-    crossbeam_channel::Receiver,
+    crossbeam_channel::{Receiver, RecvTimeoutError},
     log::*,
     solana_accounts_db::{
         accounts_db::AccountsDbConfig, accounts_index::AccountsIndexConfig,
@@ -9,7 +9,7 @@ use {
         utils::create_accounts_run_and_snapshot_dirs,
     },
     solana_cli_output::CliAccount,
-    solana_client::rpc_request::MAX_MULTIPLE_ACCOUNTS,
+    solana_client::{connection_cache::InMemoryNetwork, rpc_request::MAX_MULTIPLE_ACCOUNTS},
     solana_core::{
         admin_rpc_post_init::AdminRpcRequestMetadataPostInit,
         consensus::tower_storage::TowerStorage,
@@ -53,7 +53,11 @@ use {
         rent::Rent,
         signature::{read_keypair_file, write_keypair_file, Keypair, Signer},
     },
-    solana_streamer::socket::SocketAddrSpace,
+    solana_streamer::{
+        packet::{Packet, PacketBatch, PACKETS_PER_BATCH},
+        socket::SocketAddrSpace,
+        streamer::PacketBatchSender,
+    },
     solana_tpu_client::tpu_client::{
         DEFAULT_TPU_CONNECTION_POOL_SIZE, DEFAULT_TPU_ENABLE_UDP, DEFAULT_TPU_USE_QUIC,
     },
@@ -66,7 +70,11 @@ use {
         net::{IpAddr, Ipv4Addr, SocketAddr},
         path::{Path, PathBuf},
         str::FromStr,
-        sync::{Arc, RwLock},
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc, RwLock,
+        },
+        thread::{Builder, JoinHandle},
         time::Duration,
     },
     tokio::time::sleep,
@@ -146,6 +154,7 @@ pub struct TestValidatorGenesis {
     pub transaction_account_lock_limit: Option<usize>,
     pub tpu_enable_udp: bool,
     pub geyser_plugin_manager: Arc<RwLock<GeyserPluginManager>>,
+    in_memory_tpu: Option<InMemoryNetwork>,
     admin_rpc_service_post_init: Arc<RwLock<Option<AdminRpcRequestMetadataPostInit>>>,
 }
 
@@ -180,6 +189,7 @@ impl Default for TestValidatorGenesis {
             transaction_account_lock_limit: Option::<usize>::default(),
             tpu_enable_udp: DEFAULT_TPU_ENABLE_UDP,
             geyser_plugin_manager: Arc::new(RwLock::new(GeyserPluginManager::new())),
+            in_memory_tpu: Option::<InMemoryNetwork>::default(),
             admin_rpc_service_post_init:
                 Arc::<RwLock<Option<AdminRpcRequestMetadataPostInit>>>::default(),
         }
@@ -214,6 +224,14 @@ impl TestValidatorGenesis {
         self
     }
 
+    /// Binds the TPU address of the validator on `network`, so that clients
+    /// created with `ConnectionCache::with_in_memory` on the same network
+    /// submit transactions to the validator without sockets.
+    pub fn in_memory_tpu(&mut self, network: InMemoryNetwork) -> &mut Self {
+        self.in_memory_tpu = Some(network);
+        self
+    }
+
     pub fn fee_rate_governor(&mut self, fee_rate_governor: FeeRateGovernor) -> &mut Self {
         self.fee_rate_governor = fee_rate_governor;
         self
@@ -619,9 +637,78 @@ pub struct TestValidator {
     tpu: SocketAddr,
     gossip: SocketAddr,
     validator: Option<Validator>,
+    in_memory_tpu: Option<InMemoryTpu>,
     vote_account_address: Pubkey,
 }
 
+/// Forwards the packets sent to the TPU address of an `InMemoryNetwork` to
+/// the TPU of the validator.
+struct InMemoryTpu {
+    network: InMemoryNetwork,
+    addr: SocketAddr,
+    exit: Arc<AtomicBool>,
+    thread: JoinHandle<()>,
+}
+
+impl InMemoryTpu {
+    fn new(
+        network: InMemoryNetwork,
+        addr: SocketAddr,
+        packet_sender: PacketBatchSender,
+        validator_exit: &RwLock<Exit>,
+    ) -> Self {
+        let receiver = network.bind(addr);
+        let exit = Arc::new(AtomicBool::new(false));
+        // The TPU cannot shut down while its packets channel is connected.
+        validator_exit.write().unwrap().register_exit(Box::new({
+            let exit = exit.clone();
+            move || exit.store(true, Ordering::Relaxed)
+        }));
+        let thread = Builder::new()
+            .name("solInMemoryTpu".to_string())
+            .spawn({
+                let exit = exit.clone();
+                move || {
+                    while !exit.load(Ordering::Relaxed) {
+                        let data = match receiver.recv_timeout(Duration::from_millis(100)) {
+                            Ok(data) => data,
+                            Err(RecvTimeoutError::Timeout) => continue,
+                            Err(RecvTimeoutError::Disconnected) => break,
+                        };
+                        let packets: Vec<_> = std::iter::once(data)
+                            .chain(receiver.try_iter().take(PACKETS_PER_BATCH - 1))
+                            .filter_map(|data| {
+                                let mut packet = Packet::default();
+                                packet
+                                    .buffer_mut()
+                                    .get_mut(..data.len())?
+                                    .copy_from_slice(&data);
+                                packet.meta_mut().size = data.len();
+                                Some(packet)
+                            })
+                            .collect();
+                        if packet_sender.send(PacketBatch::new(packets)).is_err() {
+                            break;
+                        }
+                    }
+                }
+            })
+            .unwrap();
+        Self {
+            network,
+            addr,
+            exit,
+            thread,
+        }
+    }
+
+    fn join(self) {
+        self.network.unbind(&self.addr);
+        self.exit.store(true, Ordering::Relaxed);
+        self.thread.join().unwrap();
+    }
+}
+
 impl TestValidator {
     /// Create and start a `TestValidator` with no transaction fees and minimal rent.
     /// Faucet optional.
@@ -972,7 +1059,7 @@ impl TestValidator {
             validator_config.tower_storage = tower_storage.clone();
         }
 
-        let validator = Some(Validator::new(
+        let validator = Validator::new(
             node,
             Arc::new(validator_identity),
             &ledger_path,
@@ -988,7 +1075,15 @@ impl TestValidator {
             DEFAULT_TPU_CONNECTION_POOL_SIZE,
             config.tpu_enable_udp,
             config.admin_rpc_service_post_init.clone(),
-        )?);
+        )?;
+        let in_memory_tpu = config.in_memory_tpu.clone().map(|network| {
+            InMemoryTpu::new(
+                network,
+                tpu,
+                validator.tpu_packet_sender(),
+                &config.validator_exit,
+            )
+        });
 
         // Needed to avoid panics in `solana-responder-gossip` in tests that create a number of
         // test validators concurrently...
@@ -1002,7 +1097,8 @@ impl TestValidator {
             rpc_url,
             tpu,
             gossip,
-            validator,
+            validator: Some(validator),
+            in_memory_tpu,
             vote_account_address,
         };
         Ok(test_validator)
@@ -1112,6 +1208,9 @@ impl TestValidator {
         if let Some(validator) = self.validator.take() {
             validator.join();
         }
+        if let Some(in_memory_tpu) = self.in_memory_tpu.take() {
+            in_memory_tpu.join();
+        }
     }
 
     pub fn cluster_info(&self) -> Arc<ClusterInfo> {
@@ -1132,6 +1231,9 @@ impl Drop for TestValidator {
         if let Some(validator) = self.validator.take() {
             validator.close();
         }
+        if let Some(in_memory_tpu) = self.in_memory_tpu.take() {
+            in_memory_tpu.join();
+        }
         if !self.preserve_ledger {
             remove_dir_all(&self.ledger_path).unwrap_or_else(|err| {
                 panic!(
@@ -0,0 +1,211 @@
+//! Simulates shred propagation through the turbine retransmit tree for a
+//! given stake distribution, and reports per-node arrival times and erasure
+//! recovery probabilities.
+//!
+//! The stake distribution is a JSON object mapping node pubkeys to stakes, as
+//! printed by `solana-ledger-tool staked-nodes` from a snapshot.
+
+use {
+    clap::{crate_description, crate_name, value_t_or_exit, App, Arg},
+    serde_json::json,
+    solana_sdk::pubkey::Pubkey,
+    solana_turbine::retransmit_simulator::{simulate, SimulationConfig, SimulationReport},
+    std::{collections::HashMap, fs::File, io::BufReader, path::Path, process::exit},
+};
+
+fn load_stakes(path: &Path) -> Result<HashMap<Pubkey, u64>, String> {
+    let file = File::open(path).map_err(|err| format!("Failed to open: {err}"))?;
+    let stakes: HashMap<String, u64> = serde_json::from_reader(BufReader::new(file))
+        .map_err(|err| format!("Failed to parse: {err}"))?;
+    stakes
+        .into_iter()
+        .map(|(pubkey, stake)| {
+            let pubkey = pubkey
+                .parse()
+                .map_err(|err| format!("Invalid pubkey {pubkey}: {err}"))?;
+            Ok((pubkey, stake))
+        })
+        .collect()
+}
+
+fn report_to_json(report: &SimulationReport) -> serde_json::Value {
+    let nodes: Vec<_> = report
+        .nodes
+        .iter()
+        .map(|node| {
+            json!({
+                "pubkey": node.pubkey.to_string(),
+                "stake": node.stake,
+                "numShreds": node.num_shreds,
+                "numReceived": node.num_received,
+                "meanRootDistance": node.mean_root_distance,
+                "meanArrivalMs": node.mean_arrival_ms,
+                "recoveryProbability": node.recovery_probability,
+                "meanRecoveryMs": node.mean_recovery_ms,
+            })
+        })
+        .collect();
+    json!({
+        "numSlots": report.num_slots,
+        "stakeWeightedRecoveryProbability": report.stake_weighted_recovery_probability(),
+        "recoveryMsForStake": {
+            "0.33": report.recovery_time_for_stake(0.33),
+            "0.67": report.recovery_time_for_stake(0.67),
+            "0.90": report.recovery_time_for_stake(0.90),
+        },
+        "nodes": nodes,
+    })
+}
+
+fn main() {
+    solana_logger::setup_with_default("solana=info");
+    let defaults = SimulationConfig::default();
+    let fanout = defaults.fanout.to_string();
+    let num_data_shreds = defaults.num_data_shreds.to_string();
+    let num_coding_shreds = defaults.num_coding_shreds.to_string();
+    let num_slots = defaults.num_slots.to_string();
+    let hop_latency = defaults.hop_latency_ms.to_string();
+    let latency_jitter = defaults.latency_jitter_ms.to_string();
+
+    let matches = App::new(crate_name!())
+        .about(crate_description!())
+        .version(solana_version::version!())
+        .arg(
+            Arg::with_name("stakes")
+                .long("stakes")
+                .value_name("FILE")
+                .takes_value(true)
+                .required(true)
+                .help("JSON file mapping node pubkeys to stakes"),
+        )
+        .arg(
+            Arg::with_name("fanout")
+                .long("fanout")
+                .value_name("NUM")
+                .takes_value(true)
+                .default_value(&fanout)
+                .help("Turbine fanout"),
+        )
+        .arg(
+            Arg::with_name("num_data_shreds")
+                .long("num-data-shreds")
+                .value_name("NUM")
+                .takes_value(true)
+                .default_value(&num_data_shreds)
+                .help("Number of data shreds in each erasure batch"),
+        )
+        .arg(
+            Arg::with_name("num_coding_shreds")
+                .long("num-coding-shreds")
+                .value_name("NUM")
+                .takes_value(true)
+                .default_value(&num_coding_shreds)
+                .help("Number of coding shreds in each erasure batch"),
+        )
+        .arg(
+            Arg::with_name("num_slots")
+                .long("num-slots")
+                .value_name("NUM")
+                .takes_value(true)
+                .default_value(&num_slots)
+                .help("Number of slots to simulate, each broadcasting one erasure batch"),
+        )
+        .arg(
+            Arg::with_name("num_unstaked_nodes")
+                .long("num-unstaked-nodes")
+                .value_name("NUM")
+                .takes_value(true)
+                .default_value("0")
+                .help("Number of unstaked nodes to add to the cluster"),
+        )
+        .arg(
+            Arg::with_name("packet_loss")
+                .long("packet-loss")
+                .value_name("PROBABILITY")
+                .takes_value(true)
+                .default_value("0.0")
+                .help("Probability that a packet is lost on each hop"),
+        )
+        .arg(
+            Arg::with_name("hop_latency")
+                .long("hop-latency")
+                .value_name("MILLISECONDS")
+                .takes_value(true)
+                .default_value(&hop_latency)
+                .help("Fixed latency of each hop"),
+        )
+        .arg(
+            Arg::with_name("latency_jitter")
+                .long("latency-jitter")
+                .value_name("MILLISECONDS")
+                .takes_value(true)
+                .default_value(&latency_jitter)
+                .help("Upper bound of the uniformly random latency added to each hop"),
+        )
+        .arg(
+            Arg::with_name("leader")
+                .long("leader")
+                .value_name("PUBKEY")
+                .takes_value(true)
+                .help("Broadcast every slot from this node [default: stake weighted leaders]"),
+        )
+        .arg(
+            Arg::with_name("seed")
+                .long("seed")
+                .value_name("NUM")
+                .takes_value(true)
+                .default_value("0")
+                .help("Seed of the random number generator"),
+        )
+        .arg(
+            Arg::with_name("output_format")
+                .long("output")
+                .takes_value(true)
+                .value_name("MODE")
+                .possible_values(&["json", "json-compact"])
+                .help("Output display mode"),
+        )
+        .get_matches();
+
+    let config = SimulationConfig {
+        fanout: value_t_or_exit!(matches, "fanout", usize),
+        num_data_shreds: value_t_or_exit!(matches, "num_data_shreds", usize),
+        num_coding_shreds: value_t_or_exit!(matches, "num_coding_shreds", usize),
+        num_slots: value_t_or_exit!(matches, "num_slots", usize),
+        num_unstaked_nodes: value_t_or_exit!(matches, "num_unstaked_nodes", usize),
+        packet_loss: value_t_or_exit!(matches, "packet_loss", f64),
+        hop_latency_ms: value_t_or_exit!(matches, "hop_latency", f64),
+        latency_jitter_ms: value_t_or_exit!(matches, "latency_jitter", f64),
+        leader: matches
+            .is_present("leader")
+            .then(|| value_t_or_exit!(matches, "leader", Pubkey)),
+        seed: value_t_or_exit!(matches, "seed", u64),
+    };
+    if config.fanout == 0 || config.num_data_shreds == 0 {
+        eprintln!("--fanout and --num-data-shreds must be positive");
+        exit(1);
+    }
+    if !(0.0..=1.0).contains(&config.packet_loss) {
+        eprintln!("--packet-loss must be between 0 and 1");
+        exit(1);
+    }
+    let path = Path::new(matches.value_of("stakes").unwrap());
+    let stakes = load_stakes(path).unwrap_or_else(|err| {
+        eprintln!("{}: {err}", path.display());
+        exit(1);
+    });
+    if stakes.values().all(|&stake| stake == 0) && config.leader.is_none() {
+        eprintln!("No staked nodes in {}", path.display());
+        exit(1);
+    }
+
+    let report = simulate(&stakes, &config);
+    match matches.value_of("output_format") {
+        Some("json") => println!(
+            "{}",
+            serde_json::to_string_pretty(&report_to_json(&report)).unwrap()
+        ),
+        Some("json-compact") => println!("{}", report_to_json(&report)),
+        _ => print!("{report}"),
+    }
+}
@@ -36,7 +36,7 @@ use {
     thiserror::Error,
 };
 
-const DATA_PLANE_FANOUT: usize = 200;
+pub(crate) const DATA_PLANE_FANOUT: usize = 200;
 pub(crate) const MAX_NUM_TURBINE_HOPS: usize = 4;
 
 #[derive(Debug, Error)]
@@ -331,7 +331,7 @@ fn get_nodes(cluster_info: &ClusterInfo, stakes: &HashMap<Pubkey, u64>) -> Vec<N
     .collect()
 }
 
-fn get_seeded_rng(leader: &Pubkey, shred: &ShredId) -> ChaChaRng {
+pub(crate) fn get_seeded_rng(leader: &Pubkey, shred: &ShredId) -> ChaChaRng {
     let seed = shred.seed(leader);
     ChaChaRng::from_seed(seed)
 }
@@ -349,7 +349,7 @@ fn get_seeded_rng(leader: &Pubkey, shred: &ShredId) -> ChaChaRng {
 // Each other node retransmits shreds to fanout many nodes in the next layer.
 // For example the node k in the 1st layer will retransmit to nodes:
 // fanout + k, 2*fanout + k, ..., fanout*fanout + k
-fn get_retransmit_peers<T: Copy>(
+pub(crate) fn get_retransmit_peers<T: Copy>(
     fanout: usize,
     index: usize, // Local node's index within the nodes slice.
     nodes: &[T],
@@ -3,6 +3,7 @@
 pub mod broadcast_stage;
 pub mod cluster_nodes;
 pub mod quic_endpoint;
+pub mod retransmit_simulator;
 pub mod retransmit_stage;
 pub mod sigverify_shreds;
 
@@ -0,0 +1,406 @@
+//! Offline simulation of shred propagation through the turbine retransmit
+//! tree.
+//!
+//! The simulator builds the same stake-weighted shuffle and retransmit tree
+//! as `ClusterNodes` for every shred of an erasure batch, and then propagates
+//! the shreds hop by hop with configurable packet loss and latency. For each
+//! node it reports when shreds arrive and how often the node receives enough
+//! shreds of the batch to recover it through erasure coding.
+//!
+//! All nodes are assumed to have the same view of the cluster, i.e. the
+//! stakes passed in, which is the case for staked nodes in practice.
+use {
+    crate::cluster_nodes::{get_retransmit_peers, get_seeded_rng},
+    itertools::Itertools,
+    rand::{Rng, SeedableRng},
+    rand_chacha::ChaChaRng,
+    solana_gossip::weighted_shuffle::WeightedShuffle,
+    solana_ledger::shred::{ShredId, ShredType},
+    solana_sdk::{clock::Slot, pubkey::Pubkey},
+    std::{cmp::Reverse, collections::HashMap, fmt},
+};
+
+#[derive(Clone, Debug)]
+pub struct SimulationConfig {
+    pub fanout: usize,
+    /// Number of data shreds in each erasure batch.
+    pub num_data_shreds: usize,
+    /// Number of coding shreds in each erasure batch.
+    pub num_coding_shreds: usize,
+    /// Number of slots, each broadcasting one erasure batch, to simulate.
+    pub num_slots: usize,
+    /// Number of unstaked nodes appended to the staked nodes.
+    pub num_unstaked_nodes: usize,
+    /// Probability that a packet is lost on any single hop.
+    pub packet_loss: f64,
+    /// Fixed latency of each hop, in milliseconds.
+    pub hop_latency_ms: f64,
+    /// Upper bound of the uniformly distributed latency added to each hop.
+    pub latency_jitter_ms: f64,
+    /// If set, broadcasts every slot from this node; otherwise slot leaders
+    /// are sampled proportionally to stake.
+    pub leader: Option<Pubkey>,
+    pub seed: u64,
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        Self {
+            fanout: crate::cluster_nodes::DATA_PLANE_FANOUT,
+            num_data_shreds: 32,
+            num_coding_shreds: 32,
+            num_slots: 100,
+            num_unstaked_nodes: 0,
+            packet_loss: 0.0,
+            hop_latency_ms: 20.0,
+            latency_jitter_ms: 10.0,
+            leader: None,
+            seed: 0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct NodeReport {
+    pub pubkey: Pubkey,
+    pub stake: u64,
+    /// Number of shreds broadcast while this node was not the leader.
+    pub num_shreds: usize,
+    pub num_received: usize,
+    /// Average distance from the root of the tree; 0 is the root node.
+    pub mean_root_distance: f64,
+    /// Average arrival time of received shreds, since broadcast.
+    pub mean_arrival_ms: Option<f64>,
+    /// Fraction of erasure batches the node was able to recover.
+    pub recovery_probability: f64,
+    /// Average time until enough shreds arrived to recover the batch.
+    pub mean_recovery_ms: Option<f64>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SimulationReport {
+    pub num_slots: usize,
+    /// Nodes sorted by (stake, pubkey) in descending order.
+    pub nodes: Vec<NodeReport>,
+}
+
+#[derive(Default)]
+struct NodeAccumulator {
+    num_shreds: usize,
+    num_received: usize,
+    root_distance: usize,
+    arrival_ms: f64,
+    num_batches: usize,
+    num_recovered: usize,
+    recovery_ms: f64,
+}
+
+pub fn simulate(stakes: &HashMap<Pubkey, u64>, config: &SimulationConfig) -> SimulationReport {
+    assert!(config.fanout > 0, "fanout must be positive");
+    assert!(config.num_data_shreds > 0, "batch must have data shreds");
+    let mut rng = ChaChaRng::seed_from_u64(config.seed);
+    let nodes: Vec<(Pubkey, u64)> = stakes
+        .iter()
+        .map(|(&pubkey, &stake)| (pubkey, stake))
+        .chain(
+            std::iter::repeat_with(|| (Pubkey::new_from_array(rng.gen()), 0))
+                .take(config.num_unstaked_nodes),
+        )
+        .sorted_by_key(|&(pubkey, stake)| Reverse((stake, pubkey)))
+        .dedup_by(|a, b| a.0 == b.0)
+        .collect();
+    let index: HashMap<Pubkey, usize> = nodes
+        .iter()
+        .enumerate()
+        .map(|(ix, (pubkey, _))| (*pubkey, ix))
+        .collect();
+    let weights: Vec<u64> = nodes.iter().map(|(_, stake)| *stake).collect();
+    let weighted_shuffle = WeightedShuffle::new("retransmit-simulator", &weights);
+    let mut accumulators: Vec<NodeAccumulator> = std::iter::repeat_with(NodeAccumulator::default)
+        .take(nodes.len())
+        .collect();
+    let num_shreds = config.num_data_shreds + config.num_coding_shreds;
+    for slot in 0..config.num_slots as Slot {
+        let leader = match config.leader {
+            Some(leader) => index.get(&leader).copied(),
+            None => weighted_shuffle.first(&mut rng),
+        };
+        let leader_pubkey = match (config.leader, leader) {
+            (Some(pubkey), _) => pubkey,
+            (None, Some(leader)) => nodes[leader].0,
+            (None, None) => break,
+        };
+        // Arrival times of each shred of the erasure batch at each node.
+        let mut arrivals: Vec<Vec<f64>> = vec![Vec::with_capacity(num_shreds); nodes.len()];
+        for k in 0..num_shreds {
+            let shred = if k < config.num_data_shreds {
+                ShredId::new(slot, k as u32, ShredType::Data)
+            } else {
+                ShredId::new(slot, (k - config.num_data_shreds) as u32, ShredType::Code)
+            };
+            let mut weighted_shuffle = weighted_shuffle.clone();
+            if let Some(leader) = leader {
+                weighted_shuffle.remove_index(leader);
+            }
+            let mut shuffle_rng = get_seeded_rng(&leader_pubkey, &shred);
+            let tree: Vec<usize> = weighted_shuffle.shuffle(&mut shuffle_rng).collect();
+            let mut arrival = vec![None; tree.len()];
+            // The leader sends each shred to the root of the tree.
+            if !tree.is_empty() && !is_lost(&mut rng, config) {
+                arrival[0] = Some(hop_latency(&mut rng, config));
+            }
+            // Parents always precede their children in the tree, so a
+            // single pass propagates the shred through all the layers.
+            let positions: Vec<usize> = (0..tree.len()).collect();
+            for (position, &node) in tree.iter().enumerate() {
+                accumulators[node].num_shreds += 1;
+                accumulators[node].root_distance += root_distance(config.fanout, position);
+                let Some(time) = arrival[position] else {
+                    continue;
+                };
+                accumulators[node].num_received += 1;
+                accumulators[node].arrival_ms += time;
+                arrivals[node].push(time);
+                for child in get_retransmit_peers(config.fanout, position, &positions) {
+                    if !is_lost(&mut rng, config) {
+                        arrival[child] = Some(time + hop_latency(&mut rng, config));
+                    }
+                }
+            }
+        }
+        for (node, mut times) in arrivals.into_iter().enumerate() {
+            if Some(node) == leader {
+                continue;
+            }
+            let accumulator = &mut accumulators[node];
+            accumulator.num_batches += 1;
+            if times.len() >= config.num_data_shreds {
+                times.sort_unstable_by(f64::total_cmp);
+                accumulator.num_recovered += 1;
+                accumulator.recovery_ms += times[config.num_data_shreds - 1];
+            }
+        }
+    }
+    let nodes = nodes
+        .into_iter()
+        .zip(accumulators)
+        .map(|((pubkey, stake), acc)| NodeReport {
+            pubkey,
+            stake,
+            num_shreds: acc.num_shreds,
+            num_received: acc.num_received,
+            mean_root_distance: ratio(acc.root_distance as f64, acc.num_shreds),
+            mean_arrival_ms: (acc.num_received > 0)
+                .then_some(acc.arrival_ms / acc.num_received as f64),
+            recovery_probability: ratio(acc.num_recovered as f64, acc.num_batches),
+            mean_recovery_ms: (acc.num_recovered > 0)
+                .then_some(acc.recovery_ms / acc.num_recovered as f64),
+        })
+        .collect();
+    SimulationReport {
+        num_slots: config.num_slots,
+        nodes,
+    }
+}
+
+impl SimulationReport {
+    pub fn total_stake(&self) -> u64 {
+        self.nodes.iter().map(|node| node.stake).sum()
+    }
+
+    /// Probability of recovering an erasure batch, weighted by stake.
+    pub fn stake_weighted_recovery_probability(&self) -> f64 {
+        let total_stake = self.total_stake();
+        if total_stake == 0 {
+            return 0.0;
+        }
+        self.nodes
+            .iter()
+            .map(|node| node.stake as f64 * node.recovery_probability)
+            .sum::<f64>()
+            / total_stake as f64
+    }
+
+    /// Average time by which nodes holding the given fraction of the stake
+    /// have recovered an erasure batch. Returns None if nodes which have
+    /// recovered batches do not hold that much stake.
+    pub fn recovery_time_for_stake(&self, fraction: f64) -> Option<f64> {
+        let total_stake = self.total_stake();
+        let target = fraction * total_stake as f64;
+        let mut nodes: Vec<(f64, u64)> = self
+            .nodes
+            .iter()
+            .filter_map(|node| Some((node.mean_recovery_ms?, node.stake)))
+            .collect();
+        nodes.sort_unstable_by(|a, b| a.0.total_cmp(&b.0));
+        let mut stake = 0;
+        nodes.into_iter().find_map(|(time, node_stake)| {
+            stake += node_stake;
+            (stake as f64 >= target).then_some(time)
+        })
+    }
+}
+
+impl fmt::Display for SimulationReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Slots simulated: {}", self.num_slots)?;
+        writeln!(
+            f,
+            "Stake weighted recovery probability: {:.4}",
+            self.stake_weighted_recovery_probability()
+        )?;
+        for fraction in [0.33, 0.67, 0.90] {
+            match self.recovery_time_for_stake(fraction) {
+                Some(time) => writeln!(f, "Recovered by {fraction:.2} of stake: {time:.1}ms")?,
+                None => writeln!(f, "Recovered by {fraction:.2} of stake: never")?,
+            }
+        }
+        writeln!(
+            f,
+            "{:<44} {:>20} {:>6} {:>10} {:>10} {:>10}",
+            "Pubkey", "Stake", "Hops", "Arrival", "Recovery", "Recovered"
+        )?;
+        for node in &self.nodes {
+            let format_ms = |ms: Option<f64>| match ms {
+                Some(ms) => format!("{ms:.1}ms"),
+                None => "-".to_string(),
+            };
+            writeln!(
+                f,
+                "{:<44} {:>20} {:>6.2} {:>10} {:>10} {:>10.4}",
+                node.pubkey.to_string(),
+                node.stake,
+                node.mean_root_distance + 1.0,
+                format_ms(node.mean_arrival_ms),
+                format_ms(node.mean_recovery_ms),
+                node.recovery_probability,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn is_lost<R: Rng>(rng: &mut R, config: &SimulationConfig) -> bool {
+    config.packet_loss > 0.0 && rng.gen_bool(config.packet_loss.min(1.0))
+}
+
+fn hop_latency<R: Rng>(rng: &mut R, config: &SimulationConfig) -> f64 {
+    if config.latency_jitter_ms > 0.0 {
+        config.hop_latency_ms + rng.gen_range(0.0..config.latency_jitter_ms)
+    } else {
+        config.hop_latency_ms
+    }
+}
+
+// Same as the root distance computed in ClusterNodes::get_retransmit_peers,
+// without the cap on the number of hops.
+fn root_distance(fanout: usize, position: usize) -> usize {
+    let mut distance = 0;
+    let mut layer_end = 0;
+    let mut layer_size = 1;
+    while position > layer_end {
+        layer_size *= fanout;
+        layer_end += layer_size;
+        distance += 1;
+    }
+    distance
+}
+
+fn ratio(num: f64, den: usize) -> f64 {
+    if den == 0 {
+        0.0
+    } else {
+        num / den as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_stakes(num_nodes: usize) -> HashMap<Pubkey, u64> {
+        (1..=num_nodes)
+            .map(|k| (Pubkey::new_unique(), k as u64 * 1_000))
+            .collect()
+    }
+
+    #[test]
+    fn test_root_distance() {
+        assert_eq!(root_distance(200, 0), 0);
+        assert_eq!(root_distance(200, 1), 1);
+        assert_eq!(root_distance(200, 200), 1);
+        assert_eq!(root_distance(200, 201), 2);
+        assert_eq!(root_distance(200, 200 * 201), 2);
+        assert_eq!(root_distance(200, 200 * 201 + 1), 3);
+        assert_eq!(root_distance(2, 6), 2);
+        assert_eq!(root_distance(2, 7), 3);
+    }
+
+    #[test]
+    fn test_simulate_lossless() {
+        let stakes = make_stakes(50);
+        let config = SimulationConfig {
+            fanout: 4,
+            num_slots: 10,
+            num_unstaked_nodes: 10,
+            hop_latency_ms: 10.0,
+            latency_jitter_ms: 0.0,
+            ..SimulationConfig::default()
+        };
+        let report = simulate(&stakes, &config);
+        assert_eq!(report.nodes.len(), 60);
+        assert_eq!(report.total_stake(), stakes.values().sum::<u64>());
+        assert!(report
+            .nodes
+            .windows(2)
+            .all(|w| (w[0].stake, w[0].pubkey) > (w[1].stake, w[1].pubkey)));
+        for node in &report.nodes {
+            assert_eq!(node.num_received, node.num_shreds);
+            assert_eq!(node.recovery_probability, 1.0);
+            // Every hop takes exactly 10ms.
+            let arrival = node.mean_arrival_ms.unwrap();
+            assert!((arrival - 10.0 * (node.mean_root_distance + 1.0)).abs() < 1e-6);
+        }
+        assert_eq!(report.stake_weighted_recovery_probability(), 1.0);
+        assert!(report.recovery_time_for_stake(0.67).is_some());
+        // The simulation is deterministic given the seed.
+        assert_eq!(report, simulate(&stakes, &config));
+    }
+
+    #[test]
+    fn test_simulate_packet_loss() {
+        let stakes = make_stakes(100);
+        let config = SimulationConfig {
+            fanout: 8,
+            num_slots: 20,
+            packet_loss: 0.2,
+            ..SimulationConfig::default()
+        };
+        let report = simulate(&stakes, &config);
+        let lossless = simulate(
+            &stakes,
+            &SimulationConfig {
+                packet_loss: 0.0,
+                ..config.clone()
+            },
+        );
+        let received: usize = report.nodes.iter().map(|node| node.num_received).sum();
+        let sent: usize = report.nodes.iter().map(|node| node.num_shreds).sum();
+        assert!(received < sent);
+        assert!(
+            report.stake_weighted_recovery_probability()
+                < lossless.stake_weighted_recovery_probability()
+        );
+        let leader = report.nodes[0].pubkey;
+        let report = simulate(
+            &stakes,
+            &SimulationConfig {
+                leader: Some(leader),
+                ..config
+            },
+        );
+        // The leader never receives its own shreds.
+        assert_eq!(report.nodes[0].num_shreds, 0);
+        assert_eq!(report.nodes[0].mean_arrival_ms, None);
+    }
+}
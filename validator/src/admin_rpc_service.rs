@@ -12,18 +12,23 @@ use {
     solana_core::{
         admin_rpc_post_init::AdminRpcRequestMetadataPostInit,
         consensus::{tower_storage::TowerStorage, Tower},
-        repair::repair_service,
+        repair::{repair_service, repair_timeline::SlotRepairTimeline},
         validator::ValidatorStartProgress,
     },
     solana_geyser_plugin_manager::GeyserPluginManagerRequest,
-    solana_gossip::contact_info::{ContactInfo, Protocol, SOCKET_ADDR_UNSPECIFIED},
+    solana_gossip::{
+        contact_info::{ContactInfo, Protocol, SOCKET_ADDR_UNSPECIFIED},
+        ingress_budget::PeerIngressStats,
+    },
     solana_rpc::rpc::verify_pubkey,
     solana_rpc_client_api::{config::RpcAccountIndex, custom_error::RpcCustomError},
     solana_sdk::{
+        clock::Slot,
         exit::Exit,
         pubkey::Pubkey,
         signature::{read_keypair_file, Keypair, Signer},
     },
+    solana_streamer::{nonblocking::connection_stats::QuicConnectionStats, quic::StreamStats},
     std::{
         collections::{HashMap, HashSet},
         error,
@@ -32,11 +37,13 @@ use {
         path::{Path, PathBuf},
         sync::{Arc, RwLock},
         thread::{self, Builder},
-        time::{Duration, SystemTime},
+        time::{Duration, Instant, SystemTime},
     },
     tokio::runtime::Runtime,
 };
 
+const DEFAULT_GOSSIP_TOP_TALKERS: usize = 20;
+
 #[derive(Clone)]
 pub struct AdminRpcRequestMetadata {
     pub rpc_addr: Option<SocketAddr>,
@@ -84,11 +91,41 @@ pub struct AdminRpcContactInfo {
     pub shred_version: u16,
 }
 
+#[derive(Debug, Deserialize, Serialize)]
+pub struct AdminRpcGossipTopTalkers {
+    pub talkers: Vec<PeerIngressStats>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct AdminRpcQuicConnection {
+    pub remote_addr: SocketAddr,
+    pub pubkey: Option<String>,
+    pub stake: u64,
+    pub age_secs: u64,
+    pub streams_accepted: u64,
+    pub streams_throttled: u64,
+    pub streams_dropped: u64,
+    pub packets_received: u64,
+    pub bytes_received: u64,
+    pub rtt_us: u64,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct AdminRpcQuicConnectionStats {
+    pub tpu: Vec<AdminRpcQuicConnection>,
+    pub tpu_forwards: Vec<AdminRpcQuicConnection>,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct AdminRpcRepairWhitelist {
     pub whitelist: Vec<Pubkey>,
 }
 
+#[derive(Debug, Deserialize, Serialize)]
+pub struct AdminRpcRepairTimeline {
+    pub slots: Vec<SlotRepairTimeline>,
+}
+
 impl From<ContactInfo> for AdminRpcContactInfo {
     fn from(node: ContactInfo) -> Self {
         macro_rules! unwrap_socket {
@@ -134,12 +171,130 @@ impl Display for AdminRpcContactInfo {
     }
 }
 
+impl Display for AdminRpcGossipTopTalkers {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{:<44} {:>20} {:>16} {:>12} {:>12} {:>12} {:>14}",
+            "Pubkey", "Stake", "Bytes", "Messages", "Dropped", "Values", "Values Dropped"
+        )?;
+        for talker in &self.talkers {
+            writeln!(
+                f,
+                "{:<44} {:>20} {:>16} {:>12} {:>12} {:>12} {:>14}",
+                talker.pubkey.to_string(),
+                talker.stake,
+                talker.bytes_received,
+                talker.messages_received,
+                talker.messages_dropped,
+                talker.values_received,
+                talker.values_dropped,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl From<QuicConnectionStats> for AdminRpcQuicConnection {
+    fn from(stats: QuicConnectionStats) -> Self {
+        Self {
+            remote_addr: stats.remote_addr,
+            pubkey: stats.pubkey.as_ref().map(Pubkey::to_string),
+            stake: stats.stake,
+            age_secs: stats.age.as_secs(),
+            streams_accepted: stats.streams_accepted,
+            streams_throttled: stats.streams_throttled,
+            streams_dropped: stats.streams_dropped,
+            packets_received: stats.packets_received,
+            bytes_received: stats.bytes_received,
+            rtt_us: stats.rtt.as_micros() as u64,
+        }
+    }
+}
+
+impl Display for AdminRpcQuicConnectionStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (name, connections) in [("TPU", &self.tpu), ("TPU Forwards", &self.tpu_forwards)] {
+            writeln!(f, "{name}: {} connections", connections.len())?;
+            for connection in connections {
+                writeln!(
+                    f,
+                    "  {} {} stake={} age={}s accepted={} throttled={} dropped={} packets={} \
+                     bytes={} rtt={}us",
+                    connection.remote_addr,
+                    connection.pubkey.as_deref().unwrap_or("-"),
+                    connection.stake,
+                    connection.age_secs,
+                    connection.streams_accepted,
+                    connection.streams_throttled,
+                    connection.streams_dropped,
+                    connection.packets_received,
+                    connection.bytes_received,
+                    connection.rtt_us,
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
 impl Display for AdminRpcRepairWhitelist {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         writeln!(f, "Repair whitelist: {:?}", &self.whitelist)
     }
 }
 
+impl Display for AdminRpcRepairTimeline {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for timeline in &self.slots {
+            // Times are displayed relative to the first event of the slot.
+            let Some(start) = timeline
+                .first_shred_timestamp
+                .into_iter()
+                .chain(timeline.first_repair_timestamp)
+                .min()
+            else {
+                continue;
+            };
+            let elapsed = |timestamp: Option<u64>| match timestamp {
+                Some(timestamp) => format!("+{}ms", timestamp.saturating_sub(start)),
+                None => "-".to_string(),
+            };
+            writeln!(
+                f,
+                "Slot {}: first shred {} ({}), first repair {}, completed {}, {} turbine shreds, \
+                 {} repaired shreds",
+                timeline.slot,
+                elapsed(timeline.first_shred_timestamp),
+                if timeline.first_shred_repaired {
+                    "repair"
+                } else {
+                    "turbine"
+                },
+                elapsed(timeline.first_repair_timestamp),
+                elapsed(timeline.completed_timestamp),
+                timeline.num_turbine_shreds,
+                timeline.num_repaired_shreds,
+            )?;
+            for repair in &timeline.repairs {
+                writeln!(
+                    f,
+                    "  {:<14} {:<44} requests={} ({} .. {}) responses={} ({} .. {})",
+                    format!("{:?}", repair.kind),
+                    repair.peer,
+                    repair.num_requests,
+                    elapsed(Some(repair.first_request_timestamp)),
+                    elapsed(Some(repair.last_request_timestamp)),
+                    repair.num_responses,
+                    elapsed(repair.first_response_timestamp),
+                    elapsed(repair.last_response_timestamp),
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
 #[rpc]
 pub trait AdminRpc {
     type Metadata;
@@ -208,6 +363,20 @@ pub trait AdminRpc {
     #[rpc(meta, name = "contactInfo")]
     fn contact_info(&self, meta: Self::Metadata) -> Result<AdminRpcContactInfo>;
 
+    #[rpc(meta, name = "quicConnectionStats")]
+    fn quic_connection_stats(
+        &self,
+        meta: Self::Metadata,
+        pubkey: Option<Pubkey>,
+    ) -> Result<AdminRpcQuicConnectionStats>;
+
+    #[rpc(meta, name = "gossipTopTalkers")]
+    fn gossip_top_talkers(
+        &self,
+        meta: Self::Metadata,
+        num: Option<usize>,
+    ) -> Result<AdminRpcGossipTopTalkers>;
+
     #[rpc(meta, name = "repairShredFromPeer")]
     fn repair_shred_from_peer(
         &self,
@@ -220,9 +389,32 @@ pub trait AdminRpc {
     #[rpc(meta, name = "repairWhitelist")]
     fn repair_whitelist(&self, meta: Self::Metadata) -> Result<AdminRpcRepairWhitelist>;
 
+    #[rpc(meta, name = "repairTimeline")]
+    fn repair_timeline(
+        &self,
+        meta: Self::Metadata,
+        slot: Option<Slot>,
+    ) -> Result<AdminRpcRepairTimeline>;
+
     #[rpc(meta, name = "setRepairWhitelist")]
     fn set_repair_whitelist(&self, meta: Self::Metadata, whitelist: Vec<Pubkey>) -> Result<()>;
 
+    #[rpc(meta, name = "compactBlockstoreColumn")]
+    fn compact_blockstore_column(
+        &self,
+        meta: Self::Metadata,
+        column: String,
+        slot_range: Option<(Slot, Slot)>,
+    ) -> Result<()>;
+
+    #[rpc(meta, name = "setBlockstoreFifoOptions")]
+    fn set_blockstore_fifo_options(
+        &self,
+        meta: Self::Metadata,
+        shred_data_cf_size: Option<u64>,
+        shred_code_cf_size: Option<u64>,
+    ) -> Result<()>;
+
     #[rpc(meta, name = "getSecondaryIndexKeySize")]
     fn get_secondary_index_key_size(
         &self,
@@ -497,6 +689,42 @@ impl AdminRpc for AdminRpcImpl {
         meta.with_post_init(|post_init| Ok(post_init.cluster_info.my_contact_info().into()))
     }
 
+    fn quic_connection_stats(
+        &self,
+        meta: Self::Metadata,
+        pubkey: Option<Pubkey>,
+    ) -> Result<AdminRpcQuicConnectionStats> {
+        debug!("quic_connection_stats admin rpc request received");
+        meta.with_post_init(|post_init| {
+            let get_connections = |stats: &StreamStats| {
+                stats
+                    .connection_stats()
+                    .into_iter()
+                    .filter(|connection| pubkey.is_none() || connection.pubkey == pubkey)
+                    .map(AdminRpcQuicConnection::from)
+                    .collect()
+            };
+            Ok(AdminRpcQuicConnectionStats {
+                tpu: get_connections(&post_init.tpu_quic_stats),
+                tpu_forwards: get_connections(&post_init.tpu_forwards_quic_stats),
+            })
+        })
+    }
+
+    fn gossip_top_talkers(
+        &self,
+        meta: Self::Metadata,
+        num: Option<usize>,
+    ) -> Result<AdminRpcGossipTopTalkers> {
+        debug!("gossip_top_talkers admin rpc request received");
+        meta.with_post_init(|post_init| {
+            let talkers = post_init
+                .cluster_info
+                .gossip_top_talkers(num.unwrap_or(DEFAULT_GOSSIP_TOP_TALKERS));
+            Ok(AdminRpcGossipTopTalkers { talkers })
+        })
+    }
+
     fn repair_shred_from_peer(
         &self,
         meta: Self::Metadata,
@@ -535,6 +763,27 @@ impl AdminRpc for AdminRpcImpl {
         })
     }
 
+    fn repair_timeline(
+        &self,
+        meta: Self::Metadata,
+        slot: Option<Slot>,
+    ) -> Result<AdminRpcRepairTimeline> {
+        debug!("repair_timeline request received");
+
+        meta.with_post_init(|post_init| {
+            let Some(repair_timeline) = &post_init.repair_timeline else {
+                return Err(jsonrpc_core::Error {
+                    code: ErrorCode::InvalidRequest,
+                    message: "Repair timeline is not enabled".to_string(),
+                    data: None,
+                });
+            };
+            Ok(AdminRpcRepairTimeline {
+                slots: repair_timeline.get_timelines(slot),
+            })
+        })
+    }
+
     fn set_repair_whitelist(&self, meta: Self::Metadata, whitelist: Vec<Pubkey>) -> Result<()> {
         debug!("set_repair_whitelist request received");
 
@@ -549,6 +798,65 @@ impl AdminRpc for AdminRpcImpl {
         })
     }
 
+    fn compact_blockstore_column(
+        &self,
+        meta: Self::Metadata,
+        column: String,
+        slot_range: Option<(Slot, Slot)>,
+    ) -> Result<()> {
+        debug!("compact_blockstore_column request received: {column} {slot_range:?}");
+
+        meta.with_post_init(|post_init| {
+            let blockstore = post_init.blockstore.clone();
+            blockstore
+                .check_compact_column(&column, slot_range)
+                .map_err(|err| jsonrpc_core::error::Error::invalid_params(err.to_string()))?;
+            let compactions = post_init.blockstore_compactions.clone();
+            if !compactions.lock().unwrap().insert(column.clone()) {
+                return Err(jsonrpc_core::Error {
+                    code: ErrorCode::InvalidRequest,
+                    message: format!("Blockstore column {column} is already being compacted"),
+                    data: None,
+                });
+            }
+
+            // Compacting a large column can take hours, so respond once it has started
+            thread::Builder::new()
+                .name("solCompactCol".into())
+                .spawn(move || {
+                    let start = Instant::now();
+                    match blockstore.compact_column(&column, slot_range) {
+                        Ok(()) => info!(
+                            "Compacted blockstore column {column} {slot_range:?} in {:?}",
+                            start.elapsed()
+                        ),
+                        Err(err) => {
+                            warn!("Failed to compact blockstore column {column} {slot_range:?}: {err}")
+                        }
+                    }
+                    compactions.lock().unwrap().remove(&column);
+                })
+                .unwrap();
+            Ok(())
+        })
+    }
+
+    fn set_blockstore_fifo_options(
+        &self,
+        meta: Self::Metadata,
+        shred_data_cf_size: Option<u64>,
+        shred_code_cf_size: Option<u64>,
+    ) -> Result<()> {
+        debug!("set_blockstore_fifo_options request received");
+
+        meta.with_post_init(|post_init| {
+            post_init
+                .blockstore
+                .set_rocks_fifo_options(shred_data_cf_size, shred_code_cf_size)
+                .map_err(|err| jsonrpc_core::error::Error::invalid_params(err.to_string()))
+        })
+    }
+
     fn get_secondary_index_key_size(
         &self,
         meta: Self::Metadata,
@@ -863,7 +1171,11 @@ mod tests {
         solana_accounts_db::{accounts_index::AccountSecondaryIndexes, inline_spl_token},
         solana_core::consensus::tower_storage::NullTowerStorage,
         solana_gossip::cluster_info::ClusterInfo,
-        solana_ledger::genesis_utils::{create_genesis_config, GenesisConfigInfo},
+        solana_ledger::{
+            blockstore::Blockstore,
+            genesis_utils::{create_genesis_config, GenesisConfigInfo},
+            get_tmp_ledger_path_auto_delete,
+        },
         solana_rpc::rpc::create_validator_exit,
         solana_runtime::{
             bank::{Bank, BankTestConfig},
@@ -880,6 +1192,7 @@ mod tests {
             state::{Account as TokenAccount, AccountState as TokenAccountState, Mint},
         },
         std::{collections::HashSet, sync::atomic::AtomicBool},
+        tempfile::TempDir,
     };
 
     #[derive(Default)]
@@ -891,10 +1204,11 @@ mod tests {
         io: MetaIoHandler<AdminRpcRequestMetadata>,
         meta: AdminRpcRequestMetadata,
         bank_forks: Arc<RwLock<BankForks>>,
+        _ledger_path: TempDir,
     }
 
     impl RpcHandler {
-        fn _start() -> Self {
+        fn start() -> Self {
             Self::start_with_config(TestConfig::default())
         }
 
@@ -917,6 +1231,8 @@ mod tests {
             let vote_account = vote_keypair.pubkey();
             let start_progress = Arc::new(RwLock::new(ValidatorStartProgress::default()));
             let repair_whitelist = Arc::new(RwLock::new(HashSet::new()));
+            let ledger_path = get_tmp_ledger_path_auto_delete!();
+            let blockstore = Arc::new(Blockstore::open(ledger_path.path()).unwrap());
             let meta = AdminRpcRequestMetadata {
                 rpc_addr: None,
                 start_time: SystemTime::now(),
@@ -927,6 +1243,7 @@ mod tests {
                 post_init: Arc::new(RwLock::new(Some(AdminRpcRequestMetadataPostInit {
                     cluster_info,
                     bank_forks: bank_forks.clone(),
+                    blockstore,
                     vote_account,
                     repair_whitelist,
                     notifies: Vec::new(),
@@ -937,6 +1254,10 @@ mod tests {
                     cluster_slots: Arc::new(
                         solana_core::cluster_slots_service::cluster_slots::ClusterSlots::default(),
                     ),
+                    tpu_quic_stats: Arc::default(),
+                    tpu_forwards_quic_stats: Arc::default(),
+                    repair_timeline: None,
+                    blockstore_compactions: Arc::default(),
                 }))),
                 staked_nodes_overrides: Arc::new(RwLock::new(HashMap::new())),
                 rpc_to_plugin_manager_sender: None,
@@ -948,6 +1269,7 @@ mod tests {
                 io,
                 meta,
                 bank_forks,
+                _ledger_path: ledger_path,
             }
         }
 
@@ -1274,4 +1596,55 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_compact_blockstore_column() {
+        let RpcHandler { io, meta, .. } = RpcHandler::start();
+
+        let req = r#"{"jsonrpc":"2.0","id":1,"method":"compactBlockstoreColumn","params":["data_shred",[0,10]]}"#;
+        let res = io.handle_request_sync(req, meta.clone());
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        assert_eq!(result["result"], Value::Null);
+
+        let req = r#"{"jsonrpc":"2.0","id":1,"method":"compactBlockstoreColumn","params":["transaction_status",[0,10]]}"#;
+        let res = io.handle_request_sync(req, meta.clone());
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        assert!(result["error"].is_object());
+
+        let req = r#"{"jsonrpc":"2.0","id":1,"method":"compactBlockstoreColumn","params":["data_shred",[10,0]]}"#;
+        let res = io.handle_request_sync(req, meta.clone());
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        assert!(result["error"].is_object());
+
+        // Only one compaction of a column runs at a time
+        let compactions = meta
+            .post_init
+            .read()
+            .unwrap()
+            .as_ref()
+            .unwrap()
+            .blockstore_compactions
+            .clone();
+        compactions.lock().unwrap().insert("code_shred".to_string());
+        let req = r#"{"jsonrpc":"2.0","id":1,"method":"compactBlockstoreColumn","params":["code_shred",null]}"#;
+        let res = io.handle_request_sync(req, meta.clone());
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        assert!(result["error"].is_object());
+        compactions.lock().unwrap().remove("code_shred");
+        let res = io.handle_request_sync(req, meta.clone());
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        assert_eq!(result["result"], Value::Null);
+
+        // The test blockstore does not use rocks fifo shred storage
+        let req = r#"{"jsonrpc":"2.0","id":1,"method":"setBlockstoreFifoOptions","params":[100000000000,null]}"#;
+        let res = io.handle_request_sync(req, meta);
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        assert!(result["error"].is_object());
+    }
 }
@@ -168,6 +168,7 @@ fn start_gossip_node(
         gossip_validators,
         should_check_duplicate_instance,
         None,
+        None,
         gossip_exit_flag.clone(),
     );
     (cluster_info, gossip_exit_flag, gossip_service)
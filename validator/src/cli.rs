@@ -21,9 +21,12 @@ use {
     },
     solana_core::{
         banking_trace::{DirByteLimit, BANKING_TRACE_DIR_DEFAULT_BYTE_LIMIT},
+        repair::repair_timeline::DEFAULT_MAX_REPAIR_TIMELINE_BYTES,
+        shred_capture::DEFAULT_MAX_CAPTURE_BYTES,
         validator::{BlockProductionMethod, BlockVerificationMethod},
     },
     solana_faucet::faucet::{self, FAUCET_PORT},
+    solana_gossip::gossip_recorder::DEFAULT_MAX_RECORDING_BYTES,
     solana_ledger::use_snapshot_archives_at_startup,
     solana_net_utils::{MINIMUM_VALIDATOR_PORT_RANGE_WIDTH, VALIDATOR_PORT_RANGE},
     solana_rayon_threadlimit::get_thread_count,
@@ -47,6 +50,7 @@ use {
     solana_send_transaction_service::send_transaction_service::{
         self, MAX_BATCH_SEND_RATE_MS, MAX_TRANSACTION_BATCH_SIZE,
     },
+    solana_streamer::nonblocking::quic::DEFAULT_MAX_STREAMS_PER_MS,
     solana_tpu_client::tpu_client::DEFAULT_TPU_CONNECTION_POOL_SIZE,
     solana_unified_scheduler_pool::DefaultSchedulerPool,
     std::{path::PathBuf, str::FromStr},
@@ -250,6 +254,28 @@ pub fn app<'a>(version: &'a str, default_args: &'a DefaultArgs) -> App<'a, 'a> {
                 .takes_value(false)
                 .help("Upload new confirmed blocks into a BigTable instance"),
         )
+        .arg(
+            Arg::with_name("rpc_ledger_archive")
+                .long("rpc-ledger-archive")
+                .value_name("DIR")
+                .takes_value(true)
+                .requires("enable_rpc_transaction_history")
+                .conflicts_with_all(&[
+                    "enable_rpc_bigtable_ledger_storage",
+                    "enable_bigtable_ledger_upload",
+                ])
+                .help(
+                    "Fetch historical transaction info from a local ledger archive in this \
+                     directory as a fallback to local ledger data",
+                ),
+        )
+        .arg(
+            Arg::with_name("enable_ledger_archive_upload")
+                .long("enable-ledger-archive-upload")
+                .requires("rpc_ledger_archive")
+                .takes_value(false)
+                .help("Upload new confirmed blocks into the local ledger archive"),
+        )
         .arg(
             Arg::with_name("enable_extended_tx_metadata_storage")
                 .long("enable-extended-tx-metadata-storage")
@@ -842,6 +868,103 @@ pub fn app<'a>(version: &'a str, default_args: &'a DefaultArgs) -> App<'a, 'a> {
                      push/pull from from validators outside this set. [default: all validators]",
                 ),
         )
+        .arg(
+            Arg::with_name("gossip_record_path")
+                .long("gossip-record-path")
+                .value_name("FILE")
+                .takes_value(true)
+                .hidden(hidden_unless_forced())
+                .help(
+                    "Record all gossip packets sent and received to this file, for offline \
+                     analysis and replay with solana-gossip-sim",
+                ),
+        )
+        .arg(
+            Arg::with_name("gossip_record_max_bytes")
+                .long("gossip-record-max-bytes")
+                .value_name("BYTES")
+                .takes_value(true)
+                .default_value(&default_args.gossip_record_max_bytes)
+                .validator(is_parsable::<u64>)
+                .hidden(hidden_unless_forced())
+                .help("Stop recording gossip packets once the recording reaches this size"),
+        )
+        .arg(
+            Arg::with_name("enable_repair_timeline")
+                .long("enable-repair-timeline")
+                .takes_value(false)
+                .hidden(hidden_unless_forced())
+                .help(
+                    "Keep a per-slot timeline of received shreds, repair requests and responses, \
+                     displayed by the repair-timeline subcommand",
+                ),
+        )
+        .arg(
+            Arg::with_name("repair_timeline_path")
+                .long("repair-timeline-path")
+                .value_name("FILE")
+                .takes_value(true)
+                .hidden(hidden_unless_forced())
+                .help(
+                    "Append the repair timeline of each slot, as a json line, to this file once \
+                     the slot is older than the root. Implies --enable-repair-timeline",
+                ),
+        )
+        .arg(
+            Arg::with_name("repair_timeline_max_bytes")
+                .long("repair-timeline-max-bytes")
+                .value_name("BYTES")
+                .takes_value(true)
+                .default_value(&default_args.repair_timeline_max_bytes)
+                .validator(is_parsable::<u64>)
+                .hidden(hidden_unless_forced())
+                .help("Stop appending to --repair-timeline-path once the file reaches this size"),
+        )
+        .arg(
+            Arg::with_name("repair_peer_scoring")
+                .long("repair-peer-scoring")
+                .takes_value(false)
+                .hidden(hidden_unless_forced())
+                .help(
+                    "Bias repair peer selection toward peers with low measured round trip times \
+                     and few timed out requests",
+                ),
+        )
+        .arg(
+            Arg::with_name("shred_capture_path")
+                .long("shred-capture-path")
+                .value_name("FILE")
+                .takes_value(true)
+                .hidden(hidden_unless_forced())
+                .help(
+                    "Record every turbine and repair shred accepted by the shred fetch stage, \
+                     with its arrival time, to this file",
+                ),
+        )
+        .arg(
+            Arg::with_name("shred_capture_max_bytes")
+                .long("shred-capture-max-bytes")
+                .value_name("BYTES")
+                .takes_value(true)
+                .default_value(&default_args.shred_capture_max_bytes)
+                .validator(is_parsable::<u64>)
+                .hidden(hidden_unless_forced())
+                .help("Stop recording shreds once the capture reaches this size"),
+        )
+        .arg(
+            Arg::with_name("shred_replay_path")
+                .long("shred-replay-path")
+                .value_name("FILE")
+                .takes_value(true)
+                .conflicts_with("entrypoint")
+                .hidden(hidden_unless_forced())
+                .help(
+                    "Replay the shreds recorded by --shred-capture-path into the TVU, with their \
+                     original timing. The replayed shreds are indistinguishable from the shreds \
+                     received from the network, so replaying is only allowed without \
+                     --entrypoint",
+                ),
+        )
         .arg(
             Arg::with_name("tpu_coalesce_ms")
                 .long("tpu-coalesce-ms")
@@ -878,6 +1001,18 @@ pub fn app<'a>(version: &'a str, default_args: &'a DefaultArgs) -> App<'a, 'a> {
                 .validator(is_parsable::<usize>)
                 .help("Controls the TPU connection pool size per remote address"),
         )
+        .arg(
+            Arg::with_name("tpu_max_forwarded_streams_per_ms")
+                .long("tpu-max-forwarded-streams-per-ms")
+                .value_name("STREAMS")
+                .takes_value(true)
+                .default_value(&default_args.tpu_max_forwarded_streams_per_ms)
+                .validator(is_parsable::<u64>)
+                .help(
+                    "Maximum number of streams per millisecond the TPU forwards QUIC endpoint \
+                     accepts, divided between the forwarding nodes by stake",
+                ),
+        )
         .arg(
             Arg::with_name("staked_nodes_overrides")
                 .long("staked-nodes-overrides")
@@ -966,6 +1101,23 @@ pub fn app<'a>(version: &'a str, default_args: &'a DefaultArgs) -> App<'a, 'a> {
                 .default_value(&default_args.rpc_bigtable_max_message_size)
                 .help("Max encoding and decoding message size used in Bigtable Grpc client"),
         )
+        .arg(
+            Arg::with_name("rpc_bigtable_compression")
+                .long("rpc-bigtable-compression")
+                .value_name("TABLE=METHOD")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .requires("enable_bigtable_ledger_upload")
+                .validator(|value| {
+                    solana_storage_bigtable::CompressionConfig::default()
+                        .set_table_compression(&value)
+                })
+                .help(
+                    "Compress the cells uploaded to the Bigtable TABLE with METHOD: best, none, \
+                     bzip2, gzip, zstd or zstd-dictionary [default: best]",
+                ),
+        )
         .arg(
             Arg::with_name("rpc_pubsub_worker_threads")
                 .long("rpc-pubsub-worker-threads")
@@ -982,6 +1134,13 @@ pub fn app<'a>(version: &'a str, default_args: &'a DefaultArgs) -> App<'a, 'a> {
                 .takes_value(false)
                 .help("Enable the unstable RPC PubSub `blockSubscribe` subscription"),
         )
+        .arg(
+            Arg::with_name("rpc_pubsub_enable_transaction_subscription")
+                .long("rpc-pubsub-enable-transaction-subscription")
+                .requires("enable_rpc_transaction_history")
+                .takes_value(false)
+                .help("Enable the unstable RPC PubSub `transactionSubscribe` subscription"),
+        )
         .arg(
             Arg::with_name("rpc_pubsub_enable_vote_subscription")
                 .long("rpc-pubsub-enable-vote-subscription")
@@ -1049,6 +1208,66 @@ pub fn app<'a>(version: &'a str, default_args: &'a DefaultArgs) -> App<'a, 'a> {
                      across all connections.",
                 ),
         )
+        .arg(
+            Arg::with_name("rpc_pubsub_max_subscriptions_per_connection")
+                .long("rpc-pubsub-max-subscriptions-per-connection")
+                .takes_value(true)
+                .value_name("NUMBER")
+                .validator(is_parsable::<usize>)
+                .default_value(&default_args.rpc_pubsub_max_subscriptions_per_connection)
+                .help(
+                    "The maximum number of active subscriptions that a single RPC PubSub \
+                     connection may hold.",
+                ),
+        )
+        .arg(
+            Arg::with_name("rpc_pubsub_max_subscriptions_per_ip")
+                .long("rpc-pubsub-max-subscriptions-per-ip")
+                .takes_value(true)
+                .value_name("NUMBER")
+                .validator(is_parsable::<usize>)
+                .default_value(&default_args.rpc_pubsub_max_subscriptions_per_ip)
+                .help(
+                    "The maximum number of active subscriptions that all RPC PubSub connections \
+                     from a single IP address may hold.",
+                ),
+        )
+        .arg(
+            Arg::with_name("rpc_pubsub_max_queued_bytes_per_connection")
+                .long("rpc-pubsub-max-queued-bytes-per-connection")
+                .takes_value(true)
+                .value_name("BYTES")
+                .validator(is_parsable::<usize>)
+                .default_value(&default_args.rpc_pubsub_max_queued_bytes_per_connection)
+                .help(
+                    "The maximum total size of notifications waiting to be written to a single \
+                     RPC PubSub connection. Clients exceeding it are disconnected.",
+                ),
+        )
+        .arg(
+            Arg::with_name("rpc_pubsub_max_transaction_subscription_addresses")
+                .long("rpc-pubsub-max-transaction-subscription-addresses")
+                .takes_value(true)
+                .value_name("NUMBER")
+                .validator(is_parsable::<usize>)
+                .default_value(&default_args.rpc_pubsub_max_transaction_subscription_addresses)
+                .help(
+                    "The maximum number of addresses that a single RPC PubSub \
+                     `transactionSubscribe` filter may reference.",
+                ),
+        )
+        .arg(
+            Arg::with_name("rpc_pubsub_max_logs_mentions_addresses")
+                .long("rpc-pubsub-max-logs-mentions-addresses")
+                .takes_value(true)
+                .value_name("NUMBER")
+                .validator(is_parsable::<usize>)
+                .default_value(&default_args.rpc_pubsub_max_logs_mentions_addresses)
+                .help(
+                    "The maximum number of addresses that a single RPC PubSub `logsSubscribe` \
+                     mentions filter may reference.",
+                ),
+        )
         .arg(
             Arg::with_name("rpc_pubsub_queue_capacity_items")
                 .long("rpc-pubsub-queue-capacity-items")
@@ -1073,6 +1292,44 @@ pub fn app<'a>(version: &'a str, default_args: &'a DefaultArgs) -> App<'a, 'a> {
                      all connections.",
                 ),
         )
+        .arg(
+            Arg::with_name("rpc_pubsub_replay_capacity_items")
+                .long("rpc-pubsub-replay-capacity-items")
+                .takes_value(true)
+                .value_name("NUMBER")
+                .validator(is_parsable::<usize>)
+                .default_value(&default_args.rpc_pubsub_replay_capacity_items)
+                .help(
+                    "The maximum number of slot, block, logs and account notifications that RPC \
+                     PubSub will keep for clients resubscribing with `fromSlot`. 0 disables \
+                     replay.",
+                ),
+        )
+        .arg(
+            Arg::with_name("rpc_pubsub_replay_capacity_bytes")
+                .long("rpc-pubsub-replay-capacity-bytes")
+                .takes_value(true)
+                .value_name("BYTES")
+                .validator(is_parsable::<usize>)
+                .default_value(&default_args.rpc_pubsub_replay_capacity_bytes)
+                .help(
+                    "The maximum total size of notifications that RPC PubSub will keep for \
+                     `fromSlot` replay.",
+                ),
+        )
+        .arg(
+            Arg::with_name("rpc_pubsub_replay_linger_secs")
+                .long("rpc-pubsub-replay-linger-secs")
+                .takes_value(true)
+                .value_name("SECONDS")
+                .validator(is_parsable::<u64>)
+                .default_value(&default_args.rpc_pubsub_replay_linger_secs)
+                .help(
+                    "How long RPC PubSub keeps the subscriptions of a closed connection alive, \
+                     so that a client reconnecting within this window can replay what it \
+                     missed with `fromSlot`.",
+                ),
+        )
         .arg(
             Arg::with_name("rpc_pubsub_notification_threads")
                 .long("rpc-pubsub-notification-threads")
@@ -1655,6 +1912,135 @@ pub fn app<'a>(version: &'a str, default_args: &'a DefaultArgs) -> App<'a, 'a> {
                         .help("Output display mode"),
                 ),
         )
+        .subcommand(
+            SubCommand::with_name("quic-connection-stats")
+                .about("Display the stats of the open TPU QUIC connections")
+                .arg(
+                    Arg::with_name("pubkey")
+                        .long("pubkey")
+                        .value_name("PUBKEY")
+                        .takes_value(true)
+                        .validator(is_pubkey)
+                        .help("Only display the connections of this client identity"),
+                )
+                .arg(
+                    Arg::with_name("output")
+                        .long("output")
+                        .takes_value(true)
+                        .value_name("MODE")
+                        .possible_values(&["json", "json-compact"])
+                        .help("Output display mode"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("gossip-top-talkers")
+                .about("Display the nodes sending or relaying the most gossip traffic received")
+                .arg(
+                    Arg::with_name("limit")
+                        .long("limit")
+                        .takes_value(true)
+                        .value_name("NUM")
+                        .validator(is_parsable::<usize>)
+                        .help("Number of nodes to display [default: 20]"),
+                )
+                .arg(
+                    Arg::with_name("output")
+                        .long("output")
+                        .takes_value(true)
+                        .value_name("MODE")
+                        .possible_values(&["json", "json-compact"])
+                        .help("Output display mode"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("repair-timeline")
+                .about(
+                    "Display the per-slot repair timeline, requires the validator to run with \
+                     --enable-repair-timeline",
+                )
+                .arg(
+                    Arg::with_name("slot")
+                        .long("slot")
+                        .takes_value(true)
+                        .value_name("SLOT")
+                        .validator(is_slot)
+                        .help("Only display the timeline of this slot"),
+                )
+                .arg(
+                    Arg::with_name("output")
+                        .long("output")
+                        .takes_value(true)
+                        .value_name("MODE")
+                        .possible_values(&["json", "json-compact"])
+                        .help("Output display mode"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("compact-blockstore-column")
+                .about(
+                    "Start compacting a column of the blockstore of the running validator; \
+                     the validator logs when the compaction completes",
+                )
+                .arg(
+                    Arg::with_name("column")
+                        .index(1)
+                        .value_name("COLUMN")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Name of the column to compact, such as data_shred"),
+                )
+                .arg(
+                    Arg::with_name("starting_slot")
+                        .long("starting-slot")
+                        .value_name("SLOT")
+                        .takes_value(true)
+                        .validator(is_slot)
+                        .requires("ending_slot")
+                        .help(
+                            "Only compact the keys of the slots from this slot, for columns \
+                             keyed by slot",
+                        ),
+                )
+                .arg(
+                    Arg::with_name("ending_slot")
+                        .long("ending-slot")
+                        .value_name("SLOT")
+                        .takes_value(true)
+                        .validator(is_slot)
+                        .requires("starting_slot")
+                        .help("Only compact the keys of the slots up to this slot"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("set-rocksdb-fifo-shred-storage-size")
+                .about(
+                    "Change the size limits of the shred columns of the running validator, which \
+                     must use --rocksdb-shred-compaction fifo",
+                )
+                .arg(
+                    Arg::with_name("shred_data_cf_size")
+                        .long("shred-data-cf-size")
+                        .value_name("BYTES")
+                        .takes_value(true)
+                        .validator(is_parsable::<u64>)
+                        .help("Size limit of the data shred column"),
+                )
+                .arg(
+                    Arg::with_name("shred_code_cf_size")
+                        .long("shred-code-cf-size")
+                        .value_name("BYTES")
+                        .takes_value(true)
+                        .validator(is_parsable::<u64>)
+                        .help("Size limit of the coding shred column"),
+                )
+                .group(
+                    ArgGroup::with_name("shred_cf_sizes")
+                        .args(&["shred_data_cf_size", "shred_code_cf_size"])
+                        .required(true)
+                        .multiple(true),
+                )
+                .after_help("Note: At least one arg must be used. Using multiple is ok"),
+        )
         .subcommand(
             SubCommand::with_name("repair-shred-from-peer")
                 .about("Request a repair from the specified validator")
@@ -2144,8 +2530,16 @@ pub struct DefaultArgs {
 
     pub rpc_max_multiple_accounts: String,
     pub rpc_pubsub_max_active_subscriptions: String,
+    pub rpc_pubsub_max_subscriptions_per_connection: String,
+    pub rpc_pubsub_max_subscriptions_per_ip: String,
+    pub rpc_pubsub_max_queued_bytes_per_connection: String,
+    pub rpc_pubsub_max_transaction_subscription_addresses: String,
+    pub rpc_pubsub_max_logs_mentions_addresses: String,
     pub rpc_pubsub_queue_capacity_items: String,
     pub rpc_pubsub_queue_capacity_bytes: String,
+    pub rpc_pubsub_replay_capacity_items: String,
+    pub rpc_pubsub_replay_capacity_bytes: String,
+    pub rpc_pubsub_replay_linger_secs: String,
     pub rpc_send_transaction_retry_ms: String,
     pub rpc_send_transaction_batch_ms: String,
     pub rpc_send_transaction_leader_forward_count: String,
@@ -2183,6 +2577,10 @@ pub struct DefaultArgs {
     pub accounts_shrink_optimize_total_space: String,
     pub accounts_shrink_ratio: String,
     pub tpu_connection_pool_size: String,
+    pub tpu_max_forwarded_streams_per_ms: String,
+    pub gossip_record_max_bytes: String,
+    pub repair_timeline_max_bytes: String,
+    pub shred_capture_max_bytes: String,
 
     // Exit subcommand
     pub exit_min_idle_time: String,
@@ -2214,12 +2612,37 @@ impl DefaultArgs {
             rpc_pubsub_max_active_subscriptions: PubSubConfig::default()
                 .max_active_subscriptions
                 .to_string(),
+            rpc_pubsub_max_subscriptions_per_connection: PubSubConfig::default()
+                .max_subscriptions_per_connection
+                .to_string(),
+            rpc_pubsub_max_subscriptions_per_ip: PubSubConfig::default()
+                .max_subscriptions_per_ip
+                .to_string(),
+            rpc_pubsub_max_queued_bytes_per_connection: PubSubConfig::default()
+                .max_queued_bytes_per_connection
+                .to_string(),
+            rpc_pubsub_max_transaction_subscription_addresses: PubSubConfig::default()
+                .max_transaction_subscription_addresses
+                .to_string(),
+            rpc_pubsub_max_logs_mentions_addresses: PubSubConfig::default()
+                .max_logs_mentions_addresses
+                .to_string(),
             rpc_pubsub_queue_capacity_items: PubSubConfig::default()
                 .queue_capacity_items
                 .to_string(),
             rpc_pubsub_queue_capacity_bytes: PubSubConfig::default()
                 .queue_capacity_bytes
                 .to_string(),
+            rpc_pubsub_replay_capacity_items: PubSubConfig::default()
+                .replay_capacity_items
+                .to_string(),
+            rpc_pubsub_replay_capacity_bytes: PubSubConfig::default()
+                .replay_capacity_bytes
+                .to_string(),
+            rpc_pubsub_replay_linger_secs: PubSubConfig::default()
+                .replay_linger
+                .as_secs()
+                .to_string(),
             send_transaction_service_config: send_transaction_service::Config::default(),
             rpc_send_transaction_retry_ms: default_send_transaction_service_config
                 .retry_rate_ms
@@ -2270,6 +2693,10 @@ impl DefaultArgs {
                 .to_string(),
             accounts_shrink_ratio: DEFAULT_ACCOUNTS_SHRINK_RATIO.to_string(),
             tpu_connection_pool_size: DEFAULT_TPU_CONNECTION_POOL_SIZE.to_string(),
+            tpu_max_forwarded_streams_per_ms: DEFAULT_MAX_STREAMS_PER_MS.to_string(),
+            gossip_record_max_bytes: DEFAULT_MAX_RECORDING_BYTES.to_string(),
+            repair_timeline_max_bytes: DEFAULT_MAX_REPAIR_TIMELINE_BYTES.to_string(),
+            shred_capture_max_bytes: DEFAULT_MAX_CAPTURE_BYTES.to_string(),
             rpc_max_request_body_size: MAX_REQUEST_BODY_SIZE.to_string(),
             exit_min_idle_time: "10".to_string(),
             exit_max_delinquent_stake: "5".to_string(),
@@ -2462,6 +2889,12 @@ pub fn test_app<'a>(version: &'a str, default_args: &'a DefaultTestArgs) -> App<
                 .takes_value(false)
                 .help("Enable the unstable RPC PubSub `blockSubscribe` subscription"),
         )
+        .arg(
+            Arg::with_name("rpc_pubsub_enable_transaction_subscription")
+                .long("rpc-pubsub-enable-transaction-subscription")
+                .takes_value(false)
+                .help("Enable the unstable RPC PubSub `transactionSubscribe` subscription"),
+        )
         .arg(
             Arg::with_name("bpf_program")
                 .long("bpf-program")
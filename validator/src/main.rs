@@ -40,7 +40,7 @@ use {
     solana_poh::poh_service,
     solana_program_runtime::runtime_config::RuntimeConfig,
     solana_rpc::{
-        rpc::{JsonRpcConfig, RpcBigtableConfig},
+        rpc::{JsonRpcConfig, RpcBigtableConfig, RpcLedgerArchiveConfig},
         rpc_pubsub_service::PubSubConfig,
     },
     solana_rpc_client::rpc_client::RpcClient,
@@ -641,6 +641,115 @@ pub fn main() {
             }
             return;
         }
+        ("quic-connection-stats", Some(subcommand_matches)) => {
+            let pubkey = pubkey_of(subcommand_matches, "pubkey");
+            let output_mode = subcommand_matches.value_of("output");
+            let admin_client = admin_rpc_service::connect(&ledger_path);
+            let connection_stats = admin_rpc_service::runtime()
+                .block_on(async move { admin_client.await?.quic_connection_stats(pubkey).await })
+                .unwrap_or_else(|err| {
+                    eprintln!("QUIC connection stats query failed: {err}");
+                    exit(1);
+                });
+            if let Some(mode) = output_mode {
+                match mode {
+                    "json" => println!(
+                        "{}",
+                        serde_json::to_string_pretty(&connection_stats).unwrap()
+                    ),
+                    "json-compact" => {
+                        print!("{}", serde_json::to_string(&connection_stats).unwrap())
+                    }
+                    _ => unreachable!(),
+                }
+            } else {
+                print!("{connection_stats}");
+            }
+            return;
+        }
+        ("gossip-top-talkers", Some(subcommand_matches)) => {
+            let num = value_t!(subcommand_matches, "limit", usize).ok();
+            let output_mode = subcommand_matches.value_of("output");
+            let admin_client = admin_rpc_service::connect(&ledger_path);
+            let top_talkers = admin_rpc_service::runtime()
+                .block_on(async move { admin_client.await?.gossip_top_talkers(num).await })
+                .unwrap_or_else(|err| {
+                    eprintln!("Gossip top talkers query failed: {err}");
+                    exit(1);
+                });
+            if let Some(mode) = output_mode {
+                match mode {
+                    "json" => println!("{}", serde_json::to_string_pretty(&top_talkers).unwrap()),
+                    "json-compact" => print!("{}", serde_json::to_string(&top_talkers).unwrap()),
+                    _ => unreachable!(),
+                }
+            } else {
+                print!("{top_talkers}");
+            }
+            return;
+        }
+        ("repair-timeline", Some(subcommand_matches)) => {
+            let slot = value_t!(subcommand_matches, "slot", Slot).ok();
+            let output_mode = subcommand_matches.value_of("output");
+            let admin_client = admin_rpc_service::connect(&ledger_path);
+            let repair_timeline = admin_rpc_service::runtime()
+                .block_on(async move { admin_client.await?.repair_timeline(slot).await })
+                .unwrap_or_else(|err| {
+                    eprintln!("Repair timeline query failed: {err}");
+                    exit(1);
+                });
+            if let Some(mode) = output_mode {
+                match mode {
+                    "json" => println!(
+                        "{}",
+                        serde_json::to_string_pretty(&repair_timeline).unwrap()
+                    ),
+                    "json-compact" => {
+                        print!("{}", serde_json::to_string(&repair_timeline).unwrap())
+                    }
+                    _ => unreachable!(),
+                }
+            } else {
+                print!("{repair_timeline}");
+            }
+            return;
+        }
+        ("compact-blockstore-column", Some(subcommand_matches)) => {
+            let column = value_t_or_exit!(subcommand_matches, "column", String);
+            let slot_range = value_t!(subcommand_matches, "starting_slot", Slot)
+                .ok()
+                .zip(value_t!(subcommand_matches, "ending_slot", Slot).ok());
+            let admin_client = admin_rpc_service::connect(&ledger_path);
+            admin_rpc_service::runtime()
+                .block_on(async move {
+                    admin_client
+                        .await?
+                        .compact_blockstore_column(column, slot_range)
+                        .await
+                })
+                .unwrap_or_else(|err| {
+                    eprintln!("compactBlockstoreColumn request failed: {err}");
+                    exit(1);
+                });
+            return;
+        }
+        ("set-rocksdb-fifo-shred-storage-size", Some(subcommand_matches)) => {
+            let shred_data_cf_size = value_t!(subcommand_matches, "shred_data_cf_size", u64).ok();
+            let shred_code_cf_size = value_t!(subcommand_matches, "shred_code_cf_size", u64).ok();
+            let admin_client = admin_rpc_service::connect(&ledger_path);
+            admin_rpc_service::runtime()
+                .block_on(async move {
+                    admin_client
+                        .await?
+                        .set_blockstore_fifo_options(shred_data_cf_size, shred_code_cf_size)
+                        .await
+                })
+                .unwrap_or_else(|err| {
+                    eprintln!("setBlockstoreFifoOptions request failed: {err}");
+                    exit(1);
+                });
+            return;
+        }
         ("init", _) => Operation::Initialize,
         ("exit", Some(subcommand_matches)) => {
             let min_idle_time = value_t_or_exit!(subcommand_matches, "min_idle_time", usize);
@@ -1279,11 +1388,31 @@ pub fn main() {
                 .ok()
                 .map(Duration::from_secs),
             max_message_size: value_t_or_exit!(matches, "rpc_bigtable_max_message_size", usize),
+            compression: {
+                let mut compression = solana_storage_bigtable::CompressionConfig::default();
+                for table_compression in matches
+                    .values_of("rpc_bigtable_compression")
+                    .unwrap_or_default()
+                {
+                    compression
+                        .set_table_compression(table_compression)
+                        .unwrap();
+                }
+                compression
+            },
         })
     } else {
         None
     };
 
+    let rpc_ledger_archive_config =
+        value_t!(matches, "rpc_ledger_archive", PathBuf)
+            .ok()
+            .map(|ledger_archive_path| RpcLedgerArchiveConfig {
+                ledger_archive_path,
+                enable_ledger_archive_upload: matches.is_present("enable_ledger_archive_upload"),
+            });
+
     let rpc_send_retry_rate_ms = value_t_or_exit!(matches, "rpc_send_transaction_retry_ms", u64);
     let rpc_send_batch_size = value_t_or_exit!(matches, "rpc_send_transaction_batch_size", usize);
     let rpc_send_batch_send_rate_ms =
@@ -1348,6 +1477,7 @@ pub fn main() {
             enable_extended_tx_metadata_storage: matches.is_present("enable_cpi_and_log_storage")
                 || matches.is_present("enable_extended_tx_metadata_storage"),
             rpc_bigtable_config,
+            rpc_ledger_archive_config,
             faucet_addr: matches.value_of("rpc_faucet_addr").map(|address| {
                 solana_net_utils::parse_host_port(address).expect("failed to parse faucet address")
             }),
@@ -1387,11 +1517,38 @@ pub fn main() {
         pubsub_config: PubSubConfig {
             enable_block_subscription: matches.is_present("rpc_pubsub_enable_block_subscription"),
             enable_vote_subscription: matches.is_present("rpc_pubsub_enable_vote_subscription"),
+            enable_transaction_subscription: matches
+                .is_present("rpc_pubsub_enable_transaction_subscription"),
             max_active_subscriptions: value_t_or_exit!(
                 matches,
                 "rpc_pubsub_max_active_subscriptions",
                 usize
             ),
+            max_subscriptions_per_connection: value_t_or_exit!(
+                matches,
+                "rpc_pubsub_max_subscriptions_per_connection",
+                usize
+            ),
+            max_subscriptions_per_ip: value_t_or_exit!(
+                matches,
+                "rpc_pubsub_max_subscriptions_per_ip",
+                usize
+            ),
+            max_queued_bytes_per_connection: value_t_or_exit!(
+                matches,
+                "rpc_pubsub_max_queued_bytes_per_connection",
+                usize
+            ),
+            max_transaction_subscription_addresses: value_t_or_exit!(
+                matches,
+                "rpc_pubsub_max_transaction_subscription_addresses",
+                usize
+            ),
+            max_logs_mentions_addresses: value_t_or_exit!(
+                matches,
+                "rpc_pubsub_max_logs_mentions_addresses",
+                usize
+            ),
             queue_capacity_items: value_t_or_exit!(
                 matches,
                 "rpc_pubsub_queue_capacity_items",
@@ -1406,6 +1563,21 @@ pub fn main() {
             notification_threads: value_t!(matches, "rpc_pubsub_notification_threads", usize)
                 .ok()
                 .and_then(NonZeroUsize::new),
+            replay_capacity_items: value_t_or_exit!(
+                matches,
+                "rpc_pubsub_replay_capacity_items",
+                usize
+            ),
+            replay_capacity_bytes: value_t_or_exit!(
+                matches,
+                "rpc_pubsub_replay_capacity_bytes",
+                usize
+            ),
+            replay_linger: Duration::from_secs(value_t_or_exit!(
+                matches,
+                "rpc_pubsub_replay_linger_secs",
+                u64
+            )),
         },
         voting_disabled: matches.is_present("no_voting") || restricted_repair_only_mode,
         wait_for_supermajority: value_t!(matches, "wait_for_supermajority", Slot).ok(),
@@ -1413,6 +1585,15 @@ pub fn main() {
         repair_validators,
         repair_whitelist,
         gossip_validators,
+        gossip_record_path: matches.value_of("gossip_record_path").map(PathBuf::from),
+        gossip_record_max_bytes: value_t_or_exit!(matches, "gossip_record_max_bytes", u64),
+        enable_repair_timeline: matches.is_present("enable_repair_timeline"),
+        repair_timeline_path: matches.value_of("repair_timeline_path").map(PathBuf::from),
+        repair_timeline_max_bytes: value_t_or_exit!(matches, "repair_timeline_max_bytes", u64),
+        repair_peer_scoring: matches.is_present("repair_peer_scoring"),
+        shred_capture_path: matches.value_of("shred_capture_path").map(PathBuf::from),
+        shred_capture_max_bytes: value_t_or_exit!(matches, "shred_capture_max_bytes", u64),
+        shred_replay_path: matches.value_of("shred_replay_path").map(PathBuf::from),
         wal_recovery_mode,
         run_verification: !(matches.is_present("skip_poh_verify")
             || matches.is_present("skip_startup_ledger_verification")),
@@ -1457,6 +1638,11 @@ pub fn main() {
         accounts_db_skip_shrink: true,
         accounts_db_force_initial_clean: matches.is_present("no_skip_initial_accounts_db_clean"),
         tpu_coalesce,
+        tpu_max_forwarded_streams_per_ms: value_t_or_exit!(
+            matches,
+            "tpu_max_forwarded_streams_per_ms",
+            u64
+        ),
         no_wait_for_vote_to_start_leader: matches.is_present("no_wait_for_vote_to_start_leader"),
         accounts_shrink_ratio,
         runtime_config: RuntimeConfig {